@@ -0,0 +1,358 @@
+use std::{
+    cmp::{
+        Ordering,
+        Reverse,
+    },
+    collections::HashMap,
+    sync::Arc,
+    time::Duration
+};
+
+use chrono::{
+    Datelike,
+    NaiveDate,
+};
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JSONValue;
+
+use super::{
+    MediaTag,
+    Session,
+    Track,
+};
+
+/// A Tidal album.
+#[derive(Clone, Debug)]
+pub struct Album {
+    session: Arc<Session>,
+    pub id: String,
+
+    // Cache the duration regex result.
+    duration: OnceCell<Duration>,
+
+    // The following fields are used to cache API results.
+    tracks: OnceCell<Vec<Track>>,
+    track_numbers: OnceCell<Vec<TrackNumber>>,
+
+    pub attributes: AlbumAttributes,
+    /// The sizes the album's cover art is available in, largest first. Empty if the album has no cover art.
+    pub cover_art: Vec<CoverArtFile>,
+}
+
+/// One size of an album's cover art.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverArtFile {
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where a track is on an album: its disc (volume) and its number on that disc, both starting at 1.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TrackNumber {
+    pub volume: u32,
+    pub track: u32,
+}
+
+/// The tracks on a page of an album's items, with where each is on the album,
+/// along with the endpoint of the next page if there is one.
+type ItemsPage = (Vec<(String, TrackNumber)>, Option<String>);
+
+/// An album's API attributes.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlbumAttributes {
+    pub title: String,
+    #[serde(default)]
+    pub barcode_id: String,
+    #[serde(default)]
+    pub number_of_volumes: u32,
+    #[serde(default)]
+    pub number_of_items: u32,
+    #[serde(default)]
+    pub duration: String,
+    #[serde(default)]
+    pub explicit: bool,
+    #[serde(default)]
+    pub release_date: String,
+    #[serde(default)]
+    pub copyright: HashMap<String, String>,
+    #[serde(default)]
+    pub popularity: f32,
+    #[serde(default)]
+    pub availability: Vec<String>,
+    #[serde(default)]
+    pub media_tags: Vec<MediaTag>,
+    /// The names of the album's genres. These aren't one of its attributes, so they are filled in from the genres
+    /// included with it, and are empty if it wasn't fetched on its own.
+    #[serde(default)]
+    pub genres: Vec<String>,
+}
+
+impl Album {
+    /// Returns a new `Album` from an album's id.
+    pub fn new(session: Arc<Session>, id: String) -> Result<Self, String> {
+        let endpoint = format!("/albums/{}?include=coverArt,genres", id);
+        let json = session.get(&endpoint)?;
+
+        let (attributes, cover_art) = Self::parse_response(json)?;
+
+        Ok(Self {
+            session,
+            id,
+            duration: OnceCell::new(),
+            tracks: OnceCell::new(),
+            track_numbers: OnceCell::new(),
+            attributes,
+            cover_art,
+        })
+    }
+
+    /// Returns an `Album` whose attributes were already fetched as part of another response (e.g. a search).
+    ///
+    /// Those responses don't include the cover art, so it has none.
+    pub(crate) fn from_attributes(session: Arc<Session>, id: String, attributes: AlbumAttributes) -> Self {
+        Self {
+            session,
+            id,
+            duration: OnceCell::new(),
+            tracks: OnceCell::new(),
+            track_numbers: OnceCell::new(),
+            attributes,
+            cover_art: vec![],
+        }
+    }
+
+    /// Returns a list of the tracks on this album, in album order.
+    /// 
+    /// This list is then cached within `self`, along with the track numbers returned by `get_track_numbers`.
+    pub fn get_tracks(&self) -> Result<&Vec<Track>, String> {
+        self.tracks.get_or_try_init(|| -> Result<Vec<Track>, String> {
+            let mut tracks: Vec<Track> = Vec::with_capacity(self.attributes.number_of_items as usize);
+            let mut track_numbers: Vec<TrackNumber> = Vec::with_capacity(self.attributes.number_of_items as usize);
+            let mut endpoint = Some(format!("/albums/{}/relationships/items", self.id));
+
+            // Follow the pagination links until all items have been fetched.
+            while let Some(current_endpoint) = endpoint {
+                let json = self.session.get(&current_endpoint)?;
+
+                let (items, next) = Self::parse_items_page(&json)?;
+                for (track_id, track_number) in items {
+                    tracks.push(Track::new(Arc::clone(&self.session), track_id)?);
+                    track_numbers.push(track_number);
+                }
+
+                endpoint = next;
+            }
+
+            let _ = self.track_numbers.set(track_numbers);
+
+            Ok(tracks)
+        })
+    }
+
+    /// Returns the disc and track number of each track returned by `get_tracks`, in the same order,
+    /// fetching the tracks if they haven't been.
+    pub fn get_track_numbers(&self) -> Result<&Vec<TrackNumber>, String> {
+        self.get_tracks()?;
+
+        self.track_numbers.get()
+            .ok_or(String::from("Unable to get album track numbers"))
+    }
+
+    /// Returns the url of the smallest size of this album's cover art that is at least `min_size` pixels wide,
+    /// or the largest size if none are that big. Returns `None` if the album has no cover art.
+    pub fn get_cover_art_url(&self, min_size: u32) -> Option<&str> {
+        Self::pick_cover_art(&self.cover_art, min_size).map(|file| file.url.as_str())
+    }
+
+    /// Returns the smallest of `cover_art` (sorted largest first) that is at least `min_size` pixels wide,
+    /// or the largest if none are that big.
+    pub(crate) fn pick_cover_art(cover_art: &[CoverArtFile], min_size: u32) -> Option<&CoverArtFile> {
+        cover_art
+            .iter()
+            .rev()
+            .find(|file| file.width >= min_size)
+            .or(cover_art.first())
+    }
+
+    /// Parses the attributes, genres, and cover art sizes from an `/albums/{id}?include=coverArt,genres` API response.
+    /// 
+    /// Some albums have no cover art, in which case there are no cover art sizes.
+    pub(crate) fn parse_response(mut json: JSONValue) -> Result<(AlbumAttributes, Vec<CoverArtFile>), String> {
+        let attributes_json = json["data"]["attributes"].take();
+        let mut attributes: AlbumAttributes = serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse album API response: {}", e.to_string()))?;
+
+        let included = json["included"].as_array().map(Vec::as_slice).unwrap_or_default();
+        attributes.genres = included.iter()
+            .filter(|item| item["type"] == "genres")
+            .filter_map(|genre| genre["attributes"]["genreName"].as_str().map(String::from))
+            .collect();
+
+        let mut cover_art: Vec<CoverArtFile> = included.iter()
+            .find(|item| item["type"] == "artworks")
+            .and_then(|cover_art| cover_art["attributes"]["files"].as_array())
+            .map(|files| {
+                files.iter()
+                    .filter_map(|file| Some(CoverArtFile {
+                        url: file["href"].as_str()?.to_string(),
+                        width: file["meta"]["width"].as_u64().unwrap_or(0) as u32,
+                        height: file["meta"]["height"].as_u64().unwrap_or(0) as u32,
+                    }))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // The files are usually listed largest first already, but don't rely on it.
+        cover_art.sort_by_key(|file| Reverse(file.width));
+
+        Ok((attributes, cover_art))
+    }
+
+    /// Parses a page of an `/albums/{id}/relationships/items` API response.
+    /// 
+    /// Returns the ids of the tracks on this page with where each is on the album,
+    /// and the endpoint of the next page if there is one.
+    pub(crate) fn parse_items_page(json: &JSONValue) -> Result<ItemsPage, String> {
+        let items = json["data"].as_array()
+            .ok_or(String::from("Unable to parse album items API response"))?;
+
+        let mut tracks = Vec::with_capacity(items.len());
+
+        for item in items {
+            // Albums can also contain videos, which we can't play.
+            if item["type"].as_str() != Some("tracks") {
+                continue;
+            }
+
+            let track_id = item["id"]
+                .as_str()
+                .ok_or(String::from("Unable to parse album items API response"))?
+                .to_string();
+            // Fall back to the position on the page if the numbers are missing, which shouldn't happen.
+            let track_number = TrackNumber {
+                volume: item["meta"]["volumeNumber"].as_u64().unwrap_or(1) as u32,
+                track: item["meta"]["trackNumber"].as_u64().unwrap_or(tracks.len() as u64 + 1) as u32,
+            };
+            tracks.push((track_id, track_number));
+        }
+
+        let next = json["links"]["next"].as_str().map(|next| next.to_string());
+
+        Ok((tracks, next))
+    }
+
+    /// Returns a `Duration` corresponding this `Album`'s duration attribute.
+    pub fn get_duration(&self) -> Result<&Duration, String> {
+        self.duration.get_or_try_init(|| -> Result<Duration, String> {
+            let re = Regex::new(r"^PT((?<hours>\d+)H)*((?<mins>\d+)M)*((?<secs>\d+)S)*$")
+                .map_err(|e| format!("{}", e.to_string()))?;
+            let Some(captures) = re.captures(&self.attributes.duration) else {
+                return Ok(Duration::from_secs(0));
+            };
+
+            let hours: u64 = match captures.name("hours") {
+                None => 0,
+                Some(hours_match) => hours_match.as_str().parse().unwrap_or(0),
+            };
+            let mins: u64 = match captures.name("mins") {
+                None => 0,
+                Some(mins_match) => mins_match.as_str().parse().unwrap_or(0),
+            };
+            let secs: u64 = match captures.name("secs") {
+                None => 0,
+                Some(secs_match) => secs_match.as_str().parse().unwrap_or(0),
+            };
+        
+            Ok(Duration::from_secs((hours * 60 * 60) + (mins * 60) + (secs)))
+        })
+    }
+
+    /// Compares two albums by release date, oldest first, for use with `sort_by`.
+    /// 
+    /// Albums without a release date are ordered last.
+    pub fn cmp_release_date(&self, other: &Self) -> Ordering {
+        match (self.attributes.get_release_date(), other.attributes.get_release_date()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Album {
+    /// Parses a page of a `/users/{id}/favorites/albums` unofficial API response.
+    ///
+    /// Returns the id and attributes of each album on this page, and the total number of albums in the collection.
+    pub(crate) fn parse_unofficial_page(res_json: &JSONValue) -> Result<(Vec<(String, AlbumAttributes)>, usize), String> {
+        let total = res_json["totalNumberOfItems"]
+            .as_u64()
+            .ok_or(String::from("Unable to get collection albums"))? as usize;
+
+        let items_array = res_json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get collection albums"))?;
+
+        let mut albums = Vec::with_capacity(items_array.len());
+
+        for json in items_array {
+            let album = Self::parse_unofficial(&json["item"])
+                .ok_or(String::from("Unable to get collection albums"))?;
+            albums.push(album);
+        }
+
+        Ok((albums, total))
+    }
+
+    /// Parses an album as it is listed in unofficial API responses.
+    ///
+    /// Returns its id and attributes, or `None` if it has no id.
+    pub(crate) fn parse_unofficial(json: &JSONValue) -> Option<(String, AlbumAttributes)> {
+        let id = json["id"].as_u64()?.to_string();
+
+        // The unofficial API gives the duration in seconds, which is written the way the official API does.
+        let attributes = AlbumAttributes {
+            title: json["title"].as_str().unwrap_or_default().to_string(),
+            barcode_id: json["upc"].as_str().unwrap_or_default().to_string(),
+            number_of_volumes: json["numberOfVolumes"].as_u64().unwrap_or_default() as u32,
+            number_of_items: json["numberOfTracks"].as_u64().unwrap_or_default() as u32,
+            duration: json["duration"].as_u64().map(|secs| format!("PT{secs}S")).unwrap_or_default(),
+            explicit: json["explicit"].as_bool().unwrap_or_default(),
+            release_date: json["releaseDate"].as_str().unwrap_or_default().to_string(),
+            copyright: HashMap::new(),
+            popularity: json["popularity"].as_f64().unwrap_or_default() as f32,
+            availability: vec![],
+            media_tags: serde_json::from_value(json["mediaMetadata"]["tags"].clone()).unwrap_or_default(),
+            genres: vec![],
+        };
+
+        Some((id, attributes))
+    }
+}
+
+impl AlbumAttributes {
+    /// Returns this album's release date, or `None` if it is missing or invalid.
+    /// 
+    /// Tidal usually gives a full date (e.g. "1997-05-28"), but some releases only have a year,
+    /// in which case January 1st of that year is used.
+    pub fn get_release_date(&self) -> Option<NaiveDate> {
+        let release_date = self.release_date.trim();
+
+        NaiveDate::parse_from_str(release_date, "%Y-%m-%d").ok()
+            .or_else(|| {
+                let year = release_date.parse::<i32>().ok()?;
+                NaiveDate::from_ymd_opt(year, 1, 1)
+            })
+    }
+
+    /// Returns the year this album was released, or `None` if its release date is missing or invalid.
+    pub fn get_release_year(&self) -> Option<i32> {
+        self.get_release_date().map(|date| date.year())
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    cmp::Reverse,
+    sync::Arc,
+};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JSONValue;
+
+use super::{
+    album::AlbumAttributes,
+    Album,
+    Session,
+    Track,
+};
+
+/// A Tidal artist.
+#[derive(Clone, Debug)]
+pub struct Artist {
+    session: Arc<Session>,
+    pub id: String,
+    pub attributes: ArtistAttributes,
+
+    // The following fields are used to cache API results.
+    top_tracks: OnceCell<Vec<Track>>,
+    albums: OnceCell<Vec<Album>>,
+}
+
+/// The id and attributes of each album on a page of an artist's albums, and the endpoint of the next page if there is one.
+type AlbumsPage = (Vec<(String, AlbumAttributes)>, Option<String>);
+
+/// An artist's API attributes.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistAttributes {
+    pub name: String,
+    #[serde(default)]
+    pub popularity: f32,
+}
+
+/// What an artist is credited for on a track.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArtistRole {
+    Main,
+    Featured,
+    Remixer,
+    Composer,
+    /// A role we don't display specially, by its API name (e.g. "PRODUCER").
+    Other(String),
+}
+
+/// An artist credited on a track.
+#[derive(Clone, Debug)]
+pub struct ArtistCredit {
+    pub artist: Artist,
+    pub role: ArtistRole,
+}
+
+/// Every artist credited on a track, in the order Tidal lists them.
+#[derive(Clone, Debug, Default)]
+pub struct ArtistCredits {
+    credits: Vec<ArtistCredit>,
+}
+
+impl Artist {
+    /// Returns a new `Artist` from an artist's id.
+    pub fn new(session: Arc<Session>, id: String) -> Result<Self, String> {
+        let endpoint = format!("/artists/{}", id);
+        let json = session.get(&endpoint)?;
+
+        let attributes = ArtistAttributes::from_response(json)?;
+
+        Ok(Self::from_attributes(session, id, attributes))
+    }
+
+    /// Returns an `Artist` whose attributes were already fetched, e.g. as part of another response.
+    pub(crate) fn from_attributes(session: Arc<Session>, id: String, attributes: ArtistAttributes) -> Self {
+        Self {
+            session,
+            id,
+            attributes,
+            top_tracks: OnceCell::new(),
+            albums: OnceCell::new(),
+        }
+    }
+
+    /// Returns this artist's most popular tracks, most popular first.
+    ///
+    /// Only the first page is fetched, since some artists have thousands of tracks. This list is then cached within `self`.
+    pub fn get_top_tracks(&self) -> Result<&Vec<Track>, String> {
+        self.top_tracks.get_or_try_init(|| -> Result<Vec<Track>, String> {
+            let endpoint = format!("/artists/{}/relationships/tracks?collapseBy=FINGERPRINT", self.id);
+            let json = self.session.get(&endpoint)?;
+
+            Self::parse_tracks_response(&json)?
+                .into_iter()
+                .map(|track_id| Track::new(Arc::clone(&self.session), track_id))
+                .collect()
+        })
+    }
+
+    /// Returns this artist's albums, newest first.
+    ///
+    /// This list is then cached within `self`.
+    pub fn get_albums(&self) -> Result<&Vec<Album>, String> {
+        self.albums.get_or_try_init(|| -> Result<Vec<Album>, String> {
+            let mut albums: Vec<Album> = vec![];
+            let mut endpoint = Some(format!("/artists/{}/relationships/albums?include=albums", self.id));
+
+            // Follow the pagination links until all albums have been fetched.
+            while let Some(current_endpoint) = endpoint {
+                let json = self.session.get(&current_endpoint)?;
+
+                let (page, next) = Self::parse_albums_page(&json)?;
+                for (album_id, attributes) in page {
+                    albums.push(Album::from_attributes(Arc::clone(&self.session), album_id, attributes));
+                }
+
+                endpoint = next;
+            }
+
+            // Albums without a release date go last.
+            albums.sort_by_key(|album| Reverse(album.attributes.get_release_date()));
+
+            Ok(albums)
+        })
+    }
+
+    /// Parses a page of an `/artists/{id}/relationships/albums?include=albums` API response.
+    ///
+    /// Returns the id and attributes of each album on this page, and the endpoint of the next page if there is one.
+    /// Albums whose attributes weren't included are left out.
+    pub(crate) fn parse_albums_page(json: &JSONValue) -> Result<AlbumsPage, String> {
+        let items = json["data"].as_array()
+            .ok_or(String::from("Unable to parse artist albums API response"))?;
+        let included = json["included"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+        let albums = items.iter()
+            .filter_map(|item| {
+                let id = item["id"].as_str()?;
+                let attributes = included.iter()
+                    .find(|resource| resource["id"].as_str() == Some(id) && resource["type"] == "albums")
+                    .and_then(|resource| serde_json::from_value(resource["attributes"].clone()).ok())?;
+
+                Some((id.to_string(), attributes))
+            })
+            .collect();
+
+        let next = json["links"]["next"].as_str().map(|next| next.to_string());
+
+        Ok((albums, next))
+    }
+
+    /// Parses the track ids from an `/artists/{id}/relationships/tracks` API response.
+    pub(crate) fn parse_tracks_response(json: &JSONValue) -> Result<Vec<String>, String> {
+        let items = json["data"].as_array()
+            .ok_or(String::from("Unable to parse artist tracks API response"))?;
+
+        let track_ids = items.iter()
+            .filter(|item| item["type"].as_str() == Some("tracks"))
+            .filter_map(|item| item["id"].as_str().map(|id| id.to_string()))
+            .collect();
+
+        Ok(track_ids)
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Artist {
+    /// Fetches the tracks of this artist's radio, which Tidal generates from their tracks and those of artists like them.
+    pub fn get_radio(&self) -> Result<Vec<Track>, String> {
+        let endpoint = format!("/artists/{}/radio?limit={}", self.id, Track::MAX_RADIO_TRACKS);
+        let json = self.session.get_unofficial(&endpoint)?;
+
+        Track::parse_radio_response(&json)?
+            .into_iter()
+            .map(|(id, attributes)| Ok(Track::new(Arc::clone(&self.session), id)?.with_attributes(attributes)))
+            .collect()
+    }
+
+    /// Parses a page of a `/users/{id}/favorites/artists` unofficial API response.
+    ///
+    /// Returns the id and attributes of each artist on this page, and the total number of artists in the collection.
+    pub(crate) fn parse_unofficial_page(res_json: &JSONValue) -> Result<(Vec<(String, ArtistAttributes)>, usize), String> {
+        let total = res_json["totalNumberOfItems"]
+            .as_u64()
+            .ok_or(String::from("Unable to get collection artists"))? as usize;
+
+        let items_array = res_json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get collection artists"))?;
+
+        let mut artists = Vec::with_capacity(items_array.len());
+
+        for json in items_array {
+            let artist = Self::parse_unofficial(&json["item"])
+                .ok_or(String::from("Unable to get collection artists"))?;
+            artists.push(artist);
+        }
+
+        Ok((artists, total))
+    }
+
+    /// Parses an artist as it is listed in unofficial API responses.
+    ///
+    /// Returns its id and attributes, or `None` if it has no id.
+    pub(crate) fn parse_unofficial(json: &JSONValue) -> Option<(String, ArtistAttributes)> {
+        let id = json["id"].as_u64()?.to_string();
+
+        let attributes = ArtistAttributes {
+            name: json["name"].as_str().unwrap_or_default().to_string(),
+            popularity: json["popularity"].as_f64().unwrap_or_default() as f32,
+        };
+
+        Some((id, attributes))
+    }
+}
+
+impl ArtistRole {
+    /// Returns the role with the given API name (e.g. "MAIN" or "FEATURED").
+    pub(crate) fn from_api_name(name: &str) -> Self {
+        match name {
+            "MAIN" | "MAIN_ARTIST" => Self::Main,
+            "FEATURED" | "FEATURED_ARTIST" => Self::Featured,
+            "REMIXER" => Self::Remixer,
+            "COMPOSER" => Self::Composer,
+            _ => Self::Other(name.to_string()),
+        }
+    }
+}
+
+impl ArtistCredits {
+    /// Returns the credits of a track, in the order Tidal lists them.
+    pub fn new(credits: Vec<ArtistCredit>) -> Self {
+        Self { credits }
+    }
+
+    /// Returns every credit.
+    pub fn get_all(&self) -> &[ArtistCredit] {
+        &self.credits
+    }
+
+    /// Returns the artists credited with `role`.
+    pub fn get_by_role(&self, role: &ArtistRole) -> Vec<&Artist> {
+        self.credits.iter()
+            .filter(|credit| &credit.role == role)
+            .map(|credit| &credit.artist)
+            .collect()
+    }
+
+    /// Returns the track's main artist, which is the first one credited as main, or the first one credited at all.
+    pub fn get_main(&self) -> Option<&Artist> {
+        self.get_by_role(&ArtistRole::Main)
+            .first()
+            .copied()
+            .or(self.credits.first().map(|credit| &credit.artist))
+    }
+
+    /// Returns the artists for displaying, with the featured artists and remixers after the main ones
+    /// (e.g. "Artist (feat. X & Y) [Z Remix]").
+    pub fn get_display_name(&self) -> String {
+        let credits = self.credits.iter()
+            .map(|credit| (credit.artist.attributes.name.as_str(), &credit.role))
+            .collect::<Vec<_>>();
+
+        format_display_name(&credits)
+    }
+}
+
+/// Formats the names of the artists credited on a track, with their roles, like "Artist (feat. X & Y) [Z Remix]".
+///
+/// If no one is credited as a main artist, the first artist credited is shown as one instead of in their own role.
+pub(crate) fn format_display_name(credits: &[(&str, &ArtistRole)]) -> String {
+    let has_main = credits.iter().any(|(_, role)| **role == ArtistRole::Main);
+    let (main, credits) = match credits {
+        [(name, _), rest @ ..] if !has_main => (vec![*name], rest),
+        _ => (vec![], credits),
+    };
+
+    let names = |role: &ArtistRole| -> Vec<&str> {
+        credits.iter()
+            .filter(|(_, credit_role)| *credit_role == role)
+            .map(|(name, _)| *name)
+            .collect()
+    };
+
+    let main = if has_main { names(&ArtistRole::Main) } else { main };
+    let featured = names(&ArtistRole::Featured);
+    let remixers = names(&ArtistRole::Remixer);
+
+    let mut display_name = join_names(&main);
+    if !featured.is_empty() {
+        display_name.push_str(&format!(" (feat. {})", join_names(&featured)));
+    }
+    if !remixers.is_empty() {
+        display_name.push_str(&format!(" [{} Remix]", join_names(&remixers)));
+    }
+
+    display_name
+}
+
+/// Joins names into a list like "A, B & C".
+fn join_names(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.to_string(),
+        [rest @ .., last] => format!("{} & {last}", rest.join(", ")),
+    }
+}
+
+impl ArtistAttributes {
+    /// Parses an `ArtistAttributes` from an `/artists/{id}` API response.
+    pub(crate) fn from_response(mut json: JSONValue) -> Result<Self, String> {
+        let attributes_json = json["data"]["attributes"].take();
+
+        serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse artist API response: {}", e.to_string()))
+    }
+
+    /// Parses the credited artists from a `/tracks/{id}/relationships/artists?include=artists` API response,
+    /// as each one's id, role, and attributes (if they were included).
+    ///
+    /// Artists without a role are credited as main artists.
+    pub(crate) fn parse_credits(json: &JSONValue) -> Result<Vec<(String, ArtistRole, Option<Self>)>, String> {
+        let items = json["data"].as_array()
+            .ok_or(String::from("Unable to parse artist relationship API response"))?;
+        let included = json["included"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+        let credits = items.iter()
+            .filter_map(|item| {
+                let id = item["id"].as_str()?.to_string();
+
+                let role = item["meta"]["roles"][0].as_str()
+                    .or(item["meta"]["role"].as_str())
+                    .map_or(ArtistRole::Main, ArtistRole::from_api_name);
+
+                let attributes = included.iter()
+                    .find(|resource| resource["id"].as_str() == Some(&id) && resource["type"] == "artists")
+                    .and_then(|resource| serde_json::from_value(resource["attributes"].clone()).ok());
+
+                Some((id, role, attributes))
+            })
+            .collect();
+
+        Ok(credits)
+    }
+}
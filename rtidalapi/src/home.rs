@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+#[cfg(feature = "unofficial")]
+use serde_json::Value as JSONValue;
+
+#[cfg(feature = "unofficial")]
+use super::{
+    album::AlbumAttributes,
+    artist::ArtistAttributes,
+    playlist::PlaylistAttributes,
+    track::TrackAttributes,
+};
+use super::{
+    Album,
+    Artist,
+    Playlist,
+    Session,
+    Track,
+};
+
+/// The user's personalized home page, as rows of items Tidal picked for them.
+#[derive(Clone, Debug, Default)]
+pub struct HomePage {
+    pub sections: Vec<HomeSection>,
+}
+
+/// A row of the home page, like "My Mixes" or "Suggested new albums", in the order Tidal lists its items.
+#[derive(Clone, Debug)]
+pub struct HomeSection {
+    pub title: String,
+    pub items: Vec<HomeItem>,
+}
+
+/// Something listed on the home page.
+#[derive(Clone, Debug)]
+pub enum HomeItem {
+    /// Tracks are much bigger than the rest, so they are boxed to keep the others small.
+    Track(Box<Track>),
+    Album(Album),
+    Artist(Artist),
+    Playlist(Playlist),
+    Mix(Mix),
+}
+
+/// A mix of tracks that Tidal put together for the user, like "My Mix 1" or "My Daily Discovery".
+#[cfg_attr(not(feature = "unofficial"), allow(dead_code))]
+#[derive(Clone, Debug)]
+pub struct Mix {
+    session: Arc<Session>,
+    pub id: String,
+    pub title: String,
+    /// What the mix is made from, which is usually a few of its artists.
+    pub subtitle: String,
+    tracks: OnceCell<Vec<Track>>,
+}
+
+/// An item of the home page as it was parsed out of the response, before it is tied to a session.
+#[cfg(feature = "unofficial")]
+#[derive(Debug)]
+pub(crate) enum ParsedHomeItem {
+    Track(String, TrackAttributes),
+    Album(String, AlbumAttributes),
+    Artist(String, ArtistAttributes),
+    Playlist(String, PlaylistAttributes),
+    Mix {
+        id: String,
+        title: String,
+        subtitle: String,
+    },
+}
+
+#[cfg(feature = "unofficial")]
+impl HomePage {
+    /// Returns the home page from the sections parsed out of the response.
+    pub(crate) fn from_sections(session: &Arc<Session>, sections: Vec<(String, Vec<ParsedHomeItem>)>) -> Result<Self, String> {
+        let mut home_sections = Vec::with_capacity(sections.len());
+
+        for (title, parsed_items) in sections {
+            let mut items = Vec::with_capacity(parsed_items.len());
+            for parsed_item in parsed_items {
+                let session = Arc::clone(session);
+                items.push(match parsed_item {
+                    ParsedHomeItem::Track(id, attributes) => HomeItem::Track(Box::new(Track::new(session, id)?.with_attributes(attributes))),
+                    ParsedHomeItem::Album(id, attributes) => HomeItem::Album(Album::from_attributes(session, id, attributes)),
+                    ParsedHomeItem::Artist(id, attributes) => HomeItem::Artist(Artist::from_attributes(session, id, attributes)),
+                    ParsedHomeItem::Playlist(id, attributes) => HomeItem::Playlist(Playlist::from_attributes(session, id, attributes)),
+                    ParsedHomeItem::Mix { id, title, subtitle } => HomeItem::Mix(Mix { session, id, title, subtitle, tracks: OnceCell::new() }),
+                });
+            }
+
+            home_sections.push(HomeSection { title, items });
+        }
+
+        Ok(Self { sections: home_sections })
+    }
+
+    /// Parses a `/pages/home` unofficial API response into the title and items of each section.
+    ///
+    /// Sections of things that can't be opened (like promotions and links to other pages) are left out,
+    /// as are sections with nothing that could be parsed.
+    pub(crate) fn parse_response(json: &JSONValue) -> Result<Vec<(String, Vec<ParsedHomeItem>)>, String> {
+        let rows = json["rows"]
+            .as_array()
+            .ok_or(String::from("Unable to parse home page"))?;
+
+        let sections = rows.iter()
+            .filter_map(|row| row["modules"].as_array())
+            .flatten()
+            .filter_map(|module| {
+                let items = module["pagedList"]["items"].as_array()?;
+                let kind = module["type"].as_str()?;
+
+                let parsed_items: Vec<ParsedHomeItem> = items.iter()
+                    .filter_map(|item| match kind {
+                        "TRACK_LIST" => parse_item("TRACK", item),
+                        "ALBUM_LIST" => parse_item("ALBUM", item),
+                        "ARTIST_LIST" => parse_item("ARTIST", item),
+                        "PLAYLIST_LIST" => parse_item("PLAYLIST", item),
+                        "MIX_LIST" => parse_item("MIX", item),
+                        // Each item of a mixed list says what it is, and is wrapped in an object with its type.
+                        "MIXED_TYPES_LIST" => parse_item(item["type"].as_str()?, &item["item"]),
+                        _ => None,
+                    })
+                    .collect();
+                if parsed_items.is_empty() {
+                    return None;
+                }
+
+                let title = module["title"].as_str().unwrap_or_default().to_string();
+                Some((title, parsed_items))
+            })
+            .collect();
+
+        Ok(sections)
+    }
+}
+
+/// Parses an item of the home page whose type is `kind` (e.g. "ALBUM"), or returns `None` if it isn't something
+/// that can be opened or couldn't be parsed.
+#[cfg(feature = "unofficial")]
+fn parse_item(kind: &str, json: &JSONValue) -> Option<ParsedHomeItem> {
+    match kind {
+        "TRACK" => Track::parse_unofficial(json).map(|(id, attributes)| ParsedHomeItem::Track(id, attributes)),
+        "ALBUM" => Album::parse_unofficial(json).map(|(id, attributes)| ParsedHomeItem::Album(id, attributes)),
+        "ARTIST" => Artist::parse_unofficial(json).map(|(id, attributes)| ParsedHomeItem::Artist(id, attributes)),
+        "PLAYLIST" => Playlist::parse_unofficial(json).map(|(id, attributes)| ParsedHomeItem::Playlist(id, attributes)),
+        "MIX" => Some(ParsedHomeItem::Mix {
+            id: json["id"].as_str()?.to_string(),
+            title: json["title"].as_str().unwrap_or_default().to_string(),
+            subtitle: json["subTitle"].as_str().unwrap_or_default().to_string(),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Mix {
+    /// The most tracks fetched from a mix, which is more than Tidal puts in one.
+    const MAX_TRACKS: usize = 100;
+
+    /// Returns the tracks of this mix, in order.
+    ///
+    /// They are fetched the first time this is called, and cached within `self`.
+    pub fn get_tracks(&self) -> Result<&Vec<Track>, String> {
+        self.tracks.get_or_try_init(|| -> Result<Vec<Track>, String> {
+            let endpoint = format!("/mixes/{}/items?limit={}", self.id, Self::MAX_TRACKS);
+            let json = self.session.get_unofficial(&endpoint)?;
+
+            Self::parse_items_response(&json)?
+                .into_iter()
+                .map(|(id, attributes)| Ok(Track::new(Arc::clone(&self.session), id)?.with_attributes(attributes)))
+                .collect()
+        })
+    }
+
+    /// Parses a `/mixes/{id}/items` unofficial API response into the id and attributes of each of the mix's tracks.
+    /// Videos, which mixes can have too, are left out.
+    pub(crate) fn parse_items_response(json: &JSONValue) -> Result<Vec<(String, TrackAttributes)>, String> {
+        let items = json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get mix tracks"))?;
+
+        Ok(items.iter()
+            .filter(|item| item["type"].as_str() == Some("track"))
+            .filter_map(|item| Track::parse_unofficial(&item["item"]))
+            .collect())
+    }
+}
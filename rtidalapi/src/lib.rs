@@ -0,0 +1,169 @@
+//! A wrapper around the Tidal REST API.
+//!
+//! Enable the `unofficial` feature to use Tidal's unofficial API (and its device login flow) for
+//! endpoints that the official API doesn't provide yet, such as a user's collection.
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Audio quality options in Tidal.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum AudioQuality {
+    /// 96 kbps
+    Low96,
+    /// 320 kbps
+    Low320,
+    /// 16-bit, 44.1 kHz
+    High,
+    /// Up to 24-bit, 192 kHz
+    Max,
+} 
+
+impl AudioQuality {
+    /// Returns a string representation of this quality setting similar to how it is written in Tidal.
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::Low96 => String::from("Low (96 kbps)"),
+            Self::Low320 => String::from("Low (320 kbps)"),
+            Self::High => String::from("High"),
+            Self::Max => String::from("Max"),
+        }
+    }
+
+    /// Returns the next lower quality, or `None` if this is the lowest.
+    pub fn lower(&self) -> Option<Self> {
+        match self {
+            Self::Low96 => None,
+            Self::Low320 => Some(Self::Low96),
+            Self::High => Some(Self::Low320),
+            Self::Max => Some(Self::High),
+        }
+    }
+
+    /// Returns the audio quality of a format listed in a track manifest (e.g. "FLAC"), or `None` if it is unknown.
+    pub(crate) fn from_manifest_format(format: &str) -> Option<Self> {
+        match format {
+            "HEAACV1" => Some(Self::Low96),
+            "AACLC" => Some(Self::Low320),
+            "FLAC" => Some(Self::High),
+            "FLAC_HIRES" => Some(Self::Max),
+            _ => None,
+        }
+    }
+
+    /// Returns the string used by the unofficial Tidal API corresponding to this audio quality setting.
+    #[cfg(feature = "unofficial")]
+    fn to_api_string(&self) -> String {
+        match self {
+            Self::Low96 => String::from("LOW"),
+            Self::Low320 => String::from("HIGH"),
+            Self::High => String::from("LOSSLESS"),
+            Self::Max => String::from("HI_RES_LOSSLES"),
+        }
+    }
+
+    /// Returns the audio quality corresponding to a sound quality string used by the unofficial Tidal API,
+    /// or `None` if it is unknown.
+    #[cfg(feature = "unofficial")]
+    fn from_api_string(api_string: &str) -> Option<Self> {
+        match api_string {
+            "LOW" => Some(Self::Low96),
+            "HIGH" => Some(Self::Low320),
+            "LOSSLESS" => Some(Self::High),
+            "HI_RES" | "HI_RES_LOSSLESS" => Some(Self::Max),
+            _ => None,
+        }
+    }
+}
+
+/// Tags that Tidal attaches to tracks and albums to describe which formats they are available in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MediaTag {
+    /// 16-bit, 44.1 kHz FLAC
+    Lossless,
+    /// Up to 24-bit, 192 kHz FLAC
+    HiresLossless,
+    DolbyAtmos,
+    #[serde(rename = "SONY_360RA")]
+    Sony360Ra,
+    Mqa,
+    /// A tag that we don't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl MediaTag {
+    /// Returns a short badge for this tag to display next to a title,
+    /// or `None` if it isn't worth displaying (e.g. Lossless, which most content is).
+    pub fn to_badge(&self) -> Option<&'static str> {
+        match self {
+            Self::HiresLossless => Some("HiRes"),
+            Self::DolbyAtmos => Some("Atmos"),
+            Self::Sony360Ra => Some("360"),
+            Self::Mqa => Some("MQA"),
+            Self::Lossless | Self::Unknown => None,
+        }
+    }
+
+    /// Returns the highest `AudioQuality` available for content with the given tags.
+    /// 
+    /// If there are no tags, we can't tell, so `AudioQuality::Max` is returned.
+    pub fn get_max_quality(tags: &[MediaTag]) -> AudioQuality {
+        if tags.is_empty() || tags.contains(&Self::HiresLossless) {
+            AudioQuality::Max
+        } else if tags.contains(&Self::Lossless) {
+            AudioQuality::High
+        } else {
+            AudioQuality::Low320
+        }
+    }
+}
+
+pub mod album;
+pub mod artist;
+pub mod home;
+pub mod lyrics;
+pub mod playlist;
+pub mod search;
+pub mod session;
+pub mod track;
+pub mod user;
+
+// Re-exports
+pub use album::{
+    Album,
+    CoverArtFile,
+    TrackNumber,
+};
+pub use artist::{
+    Artist,
+    ArtistCredit,
+    ArtistCredits,
+    ArtistRole,
+};
+pub use playlist::Playlist;
+pub use search::SearchResults;
+pub use session::Session;
+pub use track::Track;
+pub use user::{
+    Subscription,
+    User,
+};
+pub use home::{
+    HomeItem,
+    HomePage,
+    HomeSection,
+    Mix,
+};
+pub use lyrics::{
+    Lyrics,
+    LyricsLine,
+};
+pub use user::PublicUser;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+#[cfg(feature = "unofficial")]
+use serde_json::Value as JSONValue;
+
+/// A track's lyrics, which are timed line by line for many tracks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lyrics {
+    pub lines: Vec<LyricsLine>,
+    /// Whether the lyrics are written right to left, like Arabic or Hebrew.
+    pub is_right_to_left: bool,
+    /// Who provided the lyrics, which Tidal asks to be credited.
+    pub provider: Option<String>,
+}
+
+/// A line of a track's lyrics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LyricsLine {
+    /// When the line is sung, or `None` if the lyrics aren't timed.
+    pub start: Option<Duration>,
+    pub text: String,
+}
+
+impl Lyrics {
+    /// Returns true if the lines are timed, so that the current one can be followed along with the track.
+    pub fn is_synced(&self) -> bool {
+        self.lines.iter().any(|line| line.start.is_some())
+    }
+
+    /// Returns the index of the line being sung `position` into the track, which is the last one started by then,
+    /// or `None` if the lyrics aren't timed or none has started yet.
+    pub fn get_current_line(&self, position: Duration) -> Option<usize> {
+        self.lines.iter().rposition(|line| line.start.is_some_and(|start| start <= position))
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Lyrics {
+    /// Parses a `/tracks/{id}/lyrics` unofficial API response.
+    ///
+    /// The timed lyrics are used if there are any, and the plain ones otherwise.
+    pub(crate) fn parse_response(json: &JSONValue) -> Result<Self, String> {
+        let subtitles = json["subtitles"].as_str().unwrap_or_default();
+        let lines = if subtitles.trim().is_empty() {
+            json["lyrics"].as_str()
+                .ok_or(String::from("Unable to get lyrics"))?
+                .lines()
+                .map(|text| LyricsLine { start: None, text: text.trim().to_string() })
+                .collect()
+        } else {
+            subtitles.lines().filter_map(parse_timed_line).collect()
+        };
+
+        Ok(Self {
+            lines,
+            is_right_to_left: json["isRightToLeft"].as_bool().unwrap_or_default(),
+            provider: json["lyricsProvider"].as_str().filter(|provider| !provider.is_empty()).map(String::from),
+        })
+    }
+}
+
+/// Parses a line of LRC timed lyrics, written like "[01:23.45] text", or returns `None` if it isn't timed.
+#[cfg(feature = "unofficial")]
+pub(crate) fn parse_timed_line(line: &str) -> Option<LyricsLine> {
+    let (timestamp, text) = line.trim().strip_prefix('[')?.split_once(']')?;
+    let (minutes, seconds) = timestamp.split_once(':')?;
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+
+    Some(LyricsLine {
+        start: Some(Duration::from_secs(minutes * 60) + Duration::try_from_secs_f64(seconds).ok()?),
+        text: text.trim().to_string(),
+    })
+}
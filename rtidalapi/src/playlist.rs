@@ -0,0 +1,271 @@
+use std::{
+    sync::Arc,
+};
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::{
+    json,
+    Value as JSONValue,
+};
+
+use super::{
+    Session,
+    Track,
+};
+
+/// A Tidal playlist.
+#[derive(Clone, Debug)]
+pub struct Playlist {
+    session: Arc<Session>,
+    pub id: String,
+
+    // The following fields are used to cache API results.
+    items: OnceCell<PlaylistItems>,
+
+    pub attributes: PlaylistAttributes,
+}
+
+/// The tracks in a playlist, along with the id of each one's entry in the playlist.
+#[derive(Clone, Debug)]
+struct PlaylistItems {
+    tracks: Vec<Track>,
+    /// Identifies each entry separately from its track, since a playlist can have the same track more than once.
+    item_ids: Vec<String>,
+}
+
+/// The track id and item id of each track on a page of playlist items, and the endpoint of the next page if there is one.
+type ItemsPage = (Vec<(String, String)>, Option<String>);
+
+/// A playlist's API attributes.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistAttributes {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub number_of_items: u32,
+    /// Who can see this playlist (e.g. "PUBLIC" or "UNLISTED").
+    #[serde(default)]
+    pub access_type: String,
+    /// Who made this playlist (e.g. "USER" or "EDITORIAL").
+    #[serde(default)]
+    pub playlist_type: String,
+    /// True if other users that have been invited can add tracks to this playlist.
+    #[serde(default)]
+    pub collaborative: bool,
+}
+
+impl Playlist {
+    /// Returns a new `Playlist` from a playlist's id.
+    pub fn new(session: Arc<Session>, id: String) -> Result<Self, String> {
+        let endpoint = format!("/playlists/{}", id);
+        let json = session.get(&endpoint)?;
+
+        let attributes = PlaylistAttributes::from_response(json)?;
+
+        Ok(Self::from_attributes(session, id, attributes))
+    }
+
+    /// Returns a `Playlist` whose attributes have already been fetched (e.g. as part of a list of playlists).
+    pub(crate) fn from_attributes(session: Arc<Session>, id: String, attributes: PlaylistAttributes) -> Self {
+        Self {
+            session,
+            id,
+            items: OnceCell::new(),
+            attributes,
+        }
+    }
+
+    /// Returns a list of the tracks in this playlist, in playlist order.
+    ///
+    /// This list is then cached within `self`.
+    pub fn get_tracks(&self) -> Result<&Vec<Track>, String> {
+        self.get_items().map(|items| &items.tracks)
+    }
+
+    /// Returns the id of each track's entry in this playlist, in playlist order, which is needed to move tracks.
+    ///
+    /// These are fetched and cached along with the tracks.
+    pub fn get_item_ids(&self) -> Result<&Vec<String>, String> {
+        self.get_items().map(|items| &items.item_ids)
+    }
+
+    /// Returns the tracks in this playlist and the ids of their entries, fetching them if they haven't been yet.
+    fn get_items(&self) -> Result<&PlaylistItems, String> {
+        self.items.get_or_try_init(|| -> Result<PlaylistItems, String> {
+            let capacity = self.attributes.number_of_items as usize;
+            let mut tracks: Vec<Track> = Vec::with_capacity(capacity);
+            let mut item_ids: Vec<String> = Vec::with_capacity(capacity);
+            let mut endpoint = Some(format!("/playlists/{}/relationships/items", self.id));
+
+            // Follow the pagination links until all items have been fetched.
+            while let Some(current_endpoint) = endpoint {
+                let json = self.session.get(&current_endpoint)?;
+
+                let (items, next) = Self::parse_items_page(&json)?;
+                for (track_id, item_id) in items {
+                    tracks.push(Track::new(Arc::clone(&self.session), track_id)?);
+                    item_ids.push(item_id);
+                }
+
+                endpoint = next;
+            }
+
+            Ok(PlaylistItems { tracks, item_ids })
+        })
+    }
+
+    /// Parses a page of a `/playlists/{id}/relationships/items` API response.
+    ///
+    /// Returns the track id and item id of each track on this page, and the endpoint of the next page if there is one.
+    pub(crate) fn parse_items_page(json: &JSONValue) -> Result<ItemsPage, String> {
+        let items = json["data"].as_array()
+            .ok_or(String::from("Unable to parse playlist items API response"))?;
+
+        let mut ids = Vec::with_capacity(items.len());
+
+        for item in items {
+            // Playlists can also contain videos, which we can't play.
+            if item["type"].as_str() != Some("tracks") {
+                continue;
+            }
+
+            let track_id = item["id"]
+                .as_str()
+                .ok_or(String::from("Unable to parse playlist items API response"))?
+                .to_string();
+            let item_id = item["meta"]["itemId"]
+                .as_str()
+                .ok_or(String::from("Unable to parse playlist items API response"))?
+                .to_string();
+            ids.push((track_id, item_id));
+        }
+
+        let next = json["links"]["next"].as_str().map(|next| next.to_string());
+
+        Ok((ids, next))
+    }
+
+    /// Adds `tracks` to the end of this playlist.
+    ///
+    /// The user must own (or collaborate on) this playlist. Tracks already fetched with `get_tracks` aren't updated.
+    pub fn add_tracks(&self, tracks: &[&Track]) -> Result<(), String> {
+        let endpoint = format!("/playlists/{}/relationships/items", self.id);
+        let data: Vec<JSONValue> = tracks
+            .iter()
+            .map(|track| json!({ "id": track.id, "type": "tracks" }))
+            .collect();
+
+        self.session.post(&endpoint, &json!({ "data": data }))
+    }
+
+    /// Moves the entry with id `item_id` (of the track with id `track_id`) to just before the entry with id
+    /// `before_item_id`, or to the end of this playlist if it's `None`.
+    ///
+    /// The user must own (or collaborate on) this playlist. Tracks already fetched with `get_tracks` aren't updated.
+    pub fn move_item(&self, item_id: &str, track_id: &str, before_item_id: Option<&str>) -> Result<(), String> {
+        let endpoint = format!("/playlists/{}/relationships/items", self.id);
+
+        self.session.patch(&endpoint, &Self::move_item_body(item_id, track_id, before_item_id))
+    }
+
+    /// Returns the body of the request that moves an entry, as described in `move_item`.
+    pub(crate) fn move_item_body(item_id: &str, track_id: &str, before_item_id: Option<&str>) -> JSONValue {
+        let mut body = json!({
+            "data": [{
+                "id": track_id,
+                "type": "tracks",
+                "meta": { "itemId": item_id },
+            }],
+        });
+
+        if let Some(before_item_id) = before_item_id {
+            body["meta"] = json!({ "positionBefore": before_item_id });
+        }
+
+        body
+    }
+}
+
+impl PlaylistAttributes {
+    /// Parses the attributes from a `/playlists/{id}` API response.
+    pub(crate) fn from_response(mut json: JSONValue) -> Result<Self, String> {
+        let attributes_json = json["data"]["attributes"].take();
+
+        serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse playlist API response: {}", e))
+    }
+
+    /// Returns true if anyone with a link can see this playlist.
+    pub fn is_public(&self) -> bool {
+        self.access_type == "PUBLIC"
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Playlist {
+    /// Parses a page of a `/users/{id}/playlists` unofficial API response.
+    ///
+    /// Returns the id and attributes of each playlist on this page, and the total number of playlists.
+    pub(crate) fn parse_unofficial_page(res_json: &JSONValue) -> Result<(Vec<(String, PlaylistAttributes)>, usize), String> {
+        let total = res_json["totalNumberOfItems"]
+            .as_u64()
+            .ok_or(String::from("Unable to get playlists"))? as usize;
+
+        let items_array = res_json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get playlists"))?;
+
+        let mut playlists = Vec::with_capacity(items_array.len());
+
+        for json in items_array {
+            let playlist = Self::parse_unofficial(json)
+                .ok_or(String::from("Unable to get playlists"))?;
+            playlists.push(playlist);
+        }
+
+        Ok((playlists, total))
+    }
+
+    /// Parses a playlist as it is listed in unofficial API responses.
+    ///
+    /// Returns its id and attributes, or `None` if it has no id.
+    pub(crate) fn parse_unofficial(json: &JSONValue) -> Option<(String, PlaylistAttributes)> {
+        let id = json["uuid"].as_str()?.to_string();
+
+        let attributes = PlaylistAttributes {
+            name: json["title"].as_str().unwrap_or_default().to_string(),
+            description: json["description"].as_str().map(|description| description.to_string()),
+            number_of_items: json["numberOfTracks"].as_u64().unwrap_or_default() as u32,
+            access_type: match json["publicPlaylist"].as_bool() {
+                Some(true) => String::from("PUBLIC"),
+                _ => String::from("UNLISTED"),
+            },
+            playlist_type: json["type"].as_str().unwrap_or_default().to_string(),
+            collaborative: json["collaborative"].as_bool().unwrap_or_default(),
+        };
+
+        Some((id, attributes))
+    }
+
+    /// Creates a link that invites whoever opens it to collaborate on this playlist, which makes it collaborative.
+    ///
+    /// The user must own this playlist.
+    pub fn create_invite_link(&self) -> Result<String, String> {
+        let endpoint = format!("/playlists/{}/collaborators/invite", self.id);
+        let res_json = self.session.post_unofficial(&endpoint)?;
+
+        Self::parse_invite_link_response(&res_json)
+    }
+
+    /// Parses the link from a `/playlists/{id}/collaborators/invite` unofficial API response.
+    pub(crate) fn parse_invite_link_response(res_json: &JSONValue) -> Result<String, String> {
+        res_json["url"]
+            .as_str()
+            .map(|url| url.to_string())
+            .ok_or(String::from("Unable to get invite link"))
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    sync::Arc,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value as JSONValue;
+
+use super::{
+    album::AlbumAttributes,
+    artist::ArtistAttributes,
+    playlist::PlaylistAttributes,
+    track::TrackAttributes,
+    Album,
+    Artist,
+    Playlist,
+    Session,
+    Track,
+};
+
+/// What a search of Tidal's catalog found, in the order Tidal ranks them.
+#[derive(Clone, Debug, Default)]
+pub struct SearchResults {
+    pub tracks: Vec<Track>,
+    pub albums: Vec<Album>,
+    pub artists: Vec<Artist>,
+    pub playlists: Vec<Playlist>,
+}
+
+/// The id of each result of a search, and its attributes if they were included in the response.
+#[derive(Debug, Default)]
+pub(crate) struct SearchItems {
+    pub tracks: Vec<(String, Option<TrackAttributes>)>,
+    pub albums: Vec<(String, Option<AlbumAttributes>)>,
+    pub artists: Vec<(String, Option<ArtistAttributes>)>,
+    pub playlists: Vec<(String, Option<PlaylistAttributes>)>,
+}
+
+impl SearchResults {
+    /// Returns the results of a search from what was parsed out of the response.
+    ///
+    /// Albums, artists, and playlists whose attributes weren't included are left out, rather than fetched one at a time.
+    /// Tracks are kept either way, since their info is fetched as they are shown.
+    pub(crate) fn from_items(session: &Arc<Session>, items: SearchItems) -> Result<Self, String> {
+        let mut tracks = Vec::with_capacity(items.tracks.len());
+        for (id, attributes) in items.tracks {
+            let track = Track::new(Arc::clone(session), id)?;
+            tracks.push(match attributes {
+                Some(attributes) => track.with_attributes(attributes),
+                None => track,
+            });
+        }
+
+        let albums = items.albums.into_iter()
+            .filter_map(|(id, attributes)| Some(Album::from_attributes(Arc::clone(session), id, attributes?)))
+            .collect();
+        let artists = items.artists.into_iter()
+            .filter_map(|(id, attributes)| Some(Artist::from_attributes(Arc::clone(session), id, attributes?)))
+            .collect();
+        let playlists = items.playlists.into_iter()
+            .filter_map(|(id, attributes)| Some(Playlist::from_attributes(Arc::clone(session), id, attributes?)))
+            .collect();
+
+        Ok(Self {
+            tracks,
+            albums,
+            artists,
+            playlists,
+        })
+    }
+
+    /// Returns true if nothing was found.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty() && self.albums.is_empty() && self.artists.is_empty() && self.playlists.is_empty()
+    }
+
+    /// Parses a `/searchresults/{query}?include=tracks,albums,artists,playlists` API response.
+    pub(crate) fn parse_response(json: &JSONValue) -> Result<SearchItems, String> {
+        let relationships = &json["data"]["relationships"];
+        if !relationships.is_object() {
+            return Err(String::from("Unable to parse search API response"));
+        }
+
+        let included = json["included"].as_array().map(Vec::as_slice).unwrap_or_default();
+
+        Ok(SearchItems {
+            tracks: parse_relationship(relationships, included, "tracks"),
+            albums: parse_relationship(relationships, included, "albums"),
+            artists: parse_relationship(relationships, included, "artists"),
+            playlists: parse_relationship(relationships, included, "playlists"),
+        })
+    }
+}
+
+/// Returns the id of each resource in the `name` relationship of a search result, in order,
+/// along with its attributes if it is in `included` and they could be parsed.
+fn parse_relationship<T: DeserializeOwned>(relationships: &JSONValue, included: &[JSONValue], name: &str) -> Vec<(String, Option<T>)> {
+    let Some(items) = relationships[name]["data"].as_array() else {
+        return vec![];
+    };
+
+    items.iter()
+        .filter_map(|item| {
+            let id = item["id"].as_str()?.to_string();
+
+            let attributes = included.iter()
+                .find(|resource| resource["id"].as_str() == Some(&id) && resource["type"] == name)
+                .and_then(|resource| serde_json::from_value(resource["attributes"].clone()).ok());
+
+            Some((id, attributes))
+        })
+        .collect()
+}
@@ -4,7 +4,15 @@ use std::{
         Path,
         PathBuf,
     },
-    sync::Mutex,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+    },
+    time::Duration,
 };
 
 use base64::{
@@ -12,7 +20,15 @@ use base64::{
     Engine as _
 };
 use chrono::Utc;
-use reqwest::blocking::Client;
+use reqwest::{
+    blocking::{
+        Client,
+        RequestBuilder,
+        Response,
+    },
+    Method,
+    StatusCode,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JSONValue;
 use toml;
@@ -39,14 +55,26 @@ mod official_only_imports {
 #[cfg(not(feature = "unofficial"))]
 use official_only_imports::*;
 
-use super::AudioQuality;
+use super::{
+    AudioQuality,
+    SearchResults,
+};
+#[cfg(feature = "unofficial")]
+use super::{
+    HomeItem,
+    HomePage,
+    Mix,
+};
 
 /// Struct used to persist session info.
 #[derive(Debug, Deserialize, Serialize)]
-struct SessionInfo {
-    access_token: String,
-    refresh_token: String,
-    expires_at: i64,
+pub(crate) struct SessionInfo {
+    pub(crate) access_token: String,
+    pub(crate) refresh_token: String,
+    pub(crate) expires_at: i64,
+    /// When the access token was issued, used to notice the system clock going backwards.
+    #[serde(default)]
+    pub(crate) issued_at: i64,
 }
 
 /// A currently logged in Tidal session.
@@ -59,6 +87,9 @@ pub struct Session {
     session_file: PathBuf,
     request_client: Client,
     audio_quality: Mutex<AudioQuality>,
+    is_online: AtomicBool,
+    /// True if requests shouldn't be sent at all, even if Tidal can be reached.
+    is_offline_forced: AtomicBool,
 }
 
 impl Session {
@@ -68,14 +99,30 @@ impl Session {
     /// URL for the token endpoint.
     const TOKEN_URL: &str = "https://auth.tidal.com/v1/oauth2/token";
 
+    /// Access tokens are refreshed this many seconds before they expire, to allow for small clock differences with Tidal.
+    const EXPIRY_MARGIN_SECS: i64 = 60;
+
     /// Returns a new logged in `Session`.
     /// 
     /// If there is no existing previous session, the user must follow a link to login to Tidal. \
     /// `session_folder_path` is the directory path that the session info files will be stored.
     /// 
     /// If the `unofficial` feature is enabled, an unofficial session is created instead and `country_code` is ignored.
-    #[allow(unused_variables)]
     pub fn new(client_id: &str, client_secret: &str, country_code: &str, session_folder_path: &str) -> Result<Self, String> {
+        Self::create(client_id, client_secret, country_code, session_folder_path, true)
+    }
+
+    /// Returns the session saved in `session_folder_path`, without asking the user to log in.
+    ///
+    /// Fails if there is no saved session, or if it couldn't be refreshed.
+    pub fn restore(client_id: &str, client_secret: &str, country_code: &str, session_folder_path: &str) -> Result<Self, String> {
+        Self::create(client_id, client_secret, country_code, session_folder_path, false)
+    }
+
+    /// Returns a new logged in `Session`, asking the user to log in if the saved session can't be restored
+    /// and `allow_login` is true.
+    #[allow(unused_variables)]
+    fn create(client_id: &str, client_secret: &str, country_code: &str, session_folder_path: &str, allow_login: bool) -> Result<Self, String> {
         let request_client = Client::new();
 
         fs::create_dir_all(session_folder_path)
@@ -93,7 +140,8 @@ impl Session {
             &request_client,
             &session_file,
             &client_id,
-            &client_secret
+            &client_secret,
+            allow_login,
         )?;
 
         #[cfg(not(feature = "unofficial"))]
@@ -110,6 +158,8 @@ impl Session {
             session_file,
             request_client,
             audio_quality: Mutex::new(AudioQuality::Max),
+            is_online: AtomicBool::new(true),
+            is_offline_forced: AtomicBool::new(false),
         })
     }
 
@@ -117,7 +167,9 @@ impl Session {
     /// 
     /// If using the `unofficial` feature, a device auth session is used.
     /// Otherwise, a PKCE OAuth2 session is used.
-    fn get_session(request_client: &Client, session_file: &Path, client_id: &str, client_secret: &str) -> Result<SessionInfo, String> {
+    ///
+    /// If `allow_login` is false, a session that can't be restored is an error instead of a new login.
+    fn get_session(request_client: &Client, session_file: &Path, client_id: &str, client_secret: &str, allow_login: bool) -> Result<SessionInfo, String> {
         // Try to restore from file if it exists.
         if session_file.exists() {
             let toml_str = fs::read_to_string(session_file)
@@ -134,6 +186,7 @@ impl Session {
 
                         return Ok(session_info);
                     },
+                    Err(e) if !allow_login => return Err(format!("Failed to refresh access token: {e}")),
                     Err(e) => {
                         eprintln!("Failed to refresh access token, performing new login: {}", e);
                     },
@@ -141,6 +194,10 @@ impl Session {
             }
         }
 
+        if !allow_login {
+            return Err(String::from("There is no saved session to restore"));
+        }
+
         #[cfg(not(feature = "unofficial"))]
         // No valid session — perform new PKCE login.
         let new_session = Self::new_ouath_pkce_login(client_id, client_secret)
@@ -163,23 +220,116 @@ impl Session {
     pub fn refresh_if_needed(&self) -> Result<String, String> {
         let mut session_info = self.session_info.lock().unwrap();
 
-        if session_info.expires_at <= Utc::now().timestamp() {
-            let new_session_info = Self::refresh_access_token(
-                &self.request_client, 
-                &session_info.refresh_token, 
-                &self.client_id, 
-                &self.client_secret
-            )?;
+        if session_info.is_expiring(Utc::now().timestamp(), Self::EXPIRY_MARGIN_SECS) {
+            self.refresh(&mut session_info)?;
+        }
 
-            *session_info = new_session_info;
+        Ok(session_info.access_token.clone())
+    }
 
-            let toml_str = toml::to_string(&(*session_info))
-                .map_err(|e| format!("{e}"))?;
-            fs::write(&self.session_file, toml_str)
-                .map_err(|e| format!("{e}"))?;
+    /// Returns how long until the current access token expires, which is zero if it already has.
+    pub fn get_token_expires_in(&self) -> Duration {
+        let expires_at = self.session_info.lock().unwrap().expires_at;
+        let secs = expires_at.saturating_sub(Utc::now().timestamp()).max(0);
+
+        Duration::from_secs(secs as u64)
+    }
+
+    /// Refreshes this `Session`'s access token if it expires within `within`.
+    /// 
+    /// This is meant to be called periodically during long listening sessions, so that the token
+    /// is never left to expire between requests (e.g. while a long queue plays overnight).
+    pub fn keep_alive(&self, within: Duration) -> Result<(), String> {
+        let mut session_info = self.session_info.lock().unwrap();
+
+        if session_info.is_expiring(Utc::now().timestamp(), within.as_secs() as i64) {
+            self.refresh(&mut session_info)?;
         }
 
-        Ok(session_info.access_token.clone())
+        Ok(())
+    }
+
+    /// Refreshes the access token in `session_info` and saves it to the session file.
+    fn refresh(&self, session_info: &mut SessionInfo) -> Result<(), String> {
+        let new_session_info = Self::refresh_access_token(
+            &self.request_client, 
+            &session_info.refresh_token, 
+            &self.client_id, 
+            &self.client_secret
+        )?;
+
+        *session_info = new_session_info;
+
+        let toml_str = toml::to_string(&(*session_info))
+            .map_err(|e| format!("{e}"))?;
+        fs::write(&self.session_file, toml_str)
+            .map_err(|e| format!("{e}"))?;
+
+        Ok(())
+    }
+
+    /// Sends the request made by `build_request` with a valid access token.
+    /// 
+    /// If Tidal rejects the token anyway (e.g. because the system clock is off), it is refreshed
+    /// and the request is sent once more.
+    fn send_authorized<F>(&self, build_request: F) -> Result<Response, String>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        if self.is_offline_forced() {
+            return Err(String::from("Offline mode is on"));
+        }
+
+        let access_token = self.refresh_if_needed()?;
+        let res = self.send(build_request(&access_token))?;
+
+        if res.status() != StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+
+        let access_token = {
+            let mut session_info = self.session_info.lock().unwrap();
+            self.refresh(&mut session_info)?;
+            session_info.access_token.clone()
+        };
+
+        self.send(build_request(&access_token))
+    }
+
+    /// Sends `request`, keeping track of whether Tidal could be reached.
+    fn send(&self, request: RequestBuilder) -> Result<Response, String> {
+        let res = request.send();
+        self.is_online.store(res.is_ok(), Ordering::Relaxed);
+
+        res.map_err(|e| e.to_string())
+    }
+
+    /// Returns false if the last request to Tidal couldn't be sent (e.g. because there is no internet connection).
+    pub fn is_online(&self) -> bool {
+        self.is_online.load(Ordering::Relaxed)
+    }
+
+    /// Sends a request to Tidal that doesn't need to be logged in, only to find out whether Tidal can be reached.
+    ///
+    /// Returns the same as `is_online` afterwards. Nothing is sent while offline mode is forced.
+    pub fn check_connection(&self) -> bool {
+        if self.is_offline_forced() {
+            return self.is_online();
+        }
+
+        // Any response at all means that Tidal can be reached.
+        let _ = self.send(self.request_client.head(Self::BASE_URL));
+        self.is_online()
+    }
+
+    /// Forces offline mode on or off. While it's on, every request fails right away without being sent.
+    pub fn set_offline_forced(&self, is_offline_forced: bool) {
+        self.is_offline_forced.store(is_offline_forced, Ordering::Relaxed);
+    }
+
+    /// Returns true if offline mode has been forced on with `set_offline_forced`.
+    pub fn is_offline_forced(&self) -> bool {
+        self.is_offline_forced.load(Ordering::Relaxed)
     }
 
     /// Refreshes an access token using an existing refresh token.
@@ -209,12 +359,14 @@ impl Session {
             .to_string();
         let expires_in = json["expires_in"].as_i64()
                 .ok_or("No expires_in in token reponse")?;
-        let expires_at = Utc::now().timestamp() + expires_in;
+        let issued_at = Utc::now().timestamp();
+        let expires_at = issued_at + expires_in;
 
         Ok(SessionInfo {
             access_token,
             refresh_token: refresh_token.to_string(),
             expires_at,
+            issued_at,
         })
     }
 
@@ -224,24 +376,29 @@ impl Session {
     }
 
     /// Makes a GET request with headers to the Tidal API.
+    /// 
+    /// Endpoints that already contain a `countryCode` (e.g. pagination links) are used as is.
     pub(super) fn get_with_headers(&self, endpoint: &str, headers: Vec<(&str, &str)>) -> Result<JSONValue, String> {
-        let url = if endpoint.contains("?") {
+        let url = if endpoint.contains("countryCode=") {
+            format!("{}{}", Self::BASE_URL, endpoint)
+        } else if endpoint.contains("?") {
             format!("{}{}&countryCode={}", Self::BASE_URL, endpoint, self.country_code)
         } else {
             format!("{}{}?countryCode={}", Self::BASE_URL, endpoint, self.country_code)
         };
 
-        let access_token = self.refresh_if_needed()?;
-
-        let mut req = self.request_client.get(url)
-            .bearer_auth(&access_token);
+        let res = self
+            .send_authorized(|access_token| {
+                let mut req = self.request_client.get(&url)
+                    .bearer_auth(access_token);
 
-        for (key, val) in headers {
-            req = req.header(key, val);
-        }
+                for (key, val) in &headers {
+                    req = req.header(*key, *val);
+                }
 
-        let res = req.send()
-            .map_err(|e| format!("Unable to send GET request to {}: {}", endpoint, e.to_string()))?;
+                req
+            })
+            .map_err(|e| format!("Unable to send GET request to {}: {}", endpoint, e))?;
 
         if !res.status().is_success() {
             return Err(format!("GET request to {} failed with status code {}", endpoint, res.status()));
@@ -252,6 +409,65 @@ impl Session {
         Ok(json)
     }
 
+    /// Makes a POST request with a JSON:API body to the Tidal API.
+    pub(super) fn post(&self, endpoint: &str, body: &JSONValue) -> Result<(), String> {
+        self.send_json(Method::POST, endpoint, body)
+    }
+
+    /// Sends a PATCH request with a JSON:API `body` to an endpoint of the Tidal API.
+    ///
+    /// `endpoint` should begin with a slash (e.g. "/playlists/{id}/relationships/items").
+    pub(super) fn patch(&self, endpoint: &str, body: &JSONValue) -> Result<(), String> {
+        self.send_json(Method::PATCH, endpoint, body)
+    }
+
+    /// Sends a DELETE request with a JSON:API `body` to an endpoint of the Tidal API.
+    ///
+    /// `endpoint` should begin with a slash (e.g. "/userCollections/{id}/relationships/tracks").
+    pub(super) fn delete(&self, endpoint: &str, body: &JSONValue) -> Result<(), String> {
+        self.send_json(Method::DELETE, endpoint, body)
+    }
+
+    /// Sends a request using `method` with a JSON:API `body` to an endpoint of the Tidal API, ignoring the response body.
+    fn send_json(&self, method: Method, endpoint: &str, body: &JSONValue) -> Result<(), String> {
+        let url = if endpoint.contains("?") {
+            format!("{}{}&countryCode={}", Self::BASE_URL, endpoint, self.country_code)
+        } else {
+            format!("{}{}?countryCode={}", Self::BASE_URL, endpoint, self.country_code)
+        };
+
+        let body = serde_json::to_vec(body)
+            .map_err(|e| format!("Unable to serialize {} body for {}: {}", method, endpoint, e))?;
+
+        let res = self
+            .send_authorized(|access_token| {
+                self.request_client.request(method.clone(), &url)
+                    .bearer_auth(access_token)
+                    .header("Content-Type", "application/vnd.api+json")
+                    .body(body.clone())
+            })
+            .map_err(|e| format!("Unable to send {} request to {}: {}", method, endpoint, e))?;
+
+        if !res.status().is_success() {
+            return Err(format!("{} request to {} failed with status code {}", method, endpoint, res.status()));
+        }
+
+        Ok(())
+    }
+
+    /// Searches Tidal's catalog for `query`, returning the tracks, albums, artists, and playlists that match it.
+    pub fn search(self: &Arc<Self>, query: &str) -> Result<SearchResults, String> {
+        // The query is part of the path, where spaces can't be written as `+` like in a query string.
+        let encoded_query = url::form_urlencoded::byte_serialize(query.trim().as_bytes())
+            .collect::<String>()
+            .replace('+', "%20");
+        let endpoint = format!("/searchresults/{}?include=tracks,albums,artists,playlists", encoded_query);
+        let json = self.get(&endpoint)?;
+
+        let items = SearchResults::parse_response(&json)?;
+        SearchResults::from_items(self, items)
+    }
+
     // TODO: remove mutex
     /// Sets the audio quality setting used for playback.
     pub fn set_audio_quality(&self, quality: AudioQuality) -> Result<(), String> {
@@ -266,6 +482,16 @@ impl Session {
     }
 }
 
+impl SessionInfo {
+    /// Returns true if the access token expires within `within_secs` of `now`.
+    /// 
+    /// The token is also treated as expiring if `now` is before it was issued, since that means the
+    /// system clock has gone backwards and `expires_at` can no longer be trusted.
+    pub(crate) fn is_expiring(&self, now: i64, within_secs: i64) -> bool {
+        now < self.issued_at || self.expires_at - within_secs <= now
+    }
+}
+
 #[cfg(not(feature = "unofficial"))]
 impl Session {
     /// URL for the OAuth2 PKCE auth endpoint.
@@ -341,12 +567,14 @@ impl Session {
         let access_token = token_result.access_token().secret().to_string();
         let refresh_token = token_result.refresh_token().ok_or("No refresh_token")?.secret().to_string();
         let expires_in = token_result.expires_in().ok_or("No expires_in")?.as_secs() as i64;
-        let expires_at = Utc::now().timestamp() + expires_in;
+        let issued_at = Utc::now().timestamp();
+        let expires_at = issued_at + expires_in;
         
         Ok(SessionInfo {
             access_token,
             refresh_token,
             expires_at,
+            issued_at,
         })
     }
 }
@@ -465,12 +693,14 @@ impl Session {
                 .to_string();
             let expires_in = poll_json["expires_in"].as_i64()
                 .ok_or("No expires_in in token reponse")?;
-            let expires_at = Utc::now().timestamp() + expires_in; 
+            let issued_at = Utc::now().timestamp();
+            let expires_at = issued_at + expires_in;
 
             return Ok(SessionInfo {
                 access_token,
                 refresh_token,
                 expires_at,
+                issued_at,
             });
         }
     }
@@ -491,6 +721,32 @@ impl Session {
             .map(|s| s.to_string())
     }
 
+    /// Fetches the user's personalized home page, with their mixes, what they played recently, and suggestions.
+    pub fn get_home_page(self: &Arc<Self>) -> Result<HomePage, String> {
+        let json = self.get_unofficial("/pages/home?deviceType=BROWSER")?;
+
+        let sections = HomePage::parse_response(&json)?;
+        HomePage::from_sections(self, sections)
+    }
+
+    /// Fetches the mixes Tidal made for the user, like "My Daily Discovery" and "My Mix 1".
+    pub fn get_my_mixes(self: &Arc<Self>) -> Result<Vec<Mix>, String> {
+        let json = self.get_unofficial("/pages/my_collection_my_mixes?deviceType=BROWSER")?;
+
+        let sections = HomePage::parse_response(&json)?;
+        let mixes = HomePage::from_sections(self, sections)?
+            .sections
+            .into_iter()
+            .flat_map(|section| section.items)
+            .filter_map(|item| match item {
+                HomeItem::Mix(mix) => Some(mix),
+                _ => None,
+            })
+            .collect();
+
+        Ok(mixes)
+    }
+
     /// Makes a GET request to the unofficial Tidal API.
     pub(super) fn get_unofficial(&self, endpoint: &str) -> Result<JSONValue, String> {
         let url = if endpoint.contains("?") {
@@ -499,12 +755,9 @@ impl Session {
             format!("{}{}?countryCode={}", Self::UNOFFICIAL_BASE_URL, endpoint, self.country_code)
         };
 
-        let access_token = self.refresh_if_needed()?;
-
-        let res = self.request_client.get(url)
-            .bearer_auth(&access_token)
-            .send()
-            .map_err(|e| format!("Unable to send (unofficial) GET request to {}: {}", endpoint, e.to_string()))?;
+        let res = self
+            .send_authorized(|access_token| self.request_client.get(&url).bearer_auth(access_token))
+            .map_err(|e| format!("Unable to send (unofficial) GET request to {}: {}", endpoint, e))?;
 
         if !res.status().is_success() {
             return Err(format!("(unofficial) GET request to {} failed with status code {}", endpoint, res.status()));
@@ -515,4 +768,26 @@ impl Session {
 
         Ok(json)
     }
+
+    /// Makes a POST request without a body to the unofficial Tidal API.
+    pub(super) fn post_unofficial(&self, endpoint: &str) -> Result<JSONValue, String> {
+        let url = if endpoint.contains("?") {
+            format!("{}{}&countryCode={}", Self::UNOFFICIAL_BASE_URL, endpoint, self.country_code)
+        } else {
+            format!("{}{}?countryCode={}", Self::UNOFFICIAL_BASE_URL, endpoint, self.country_code)
+        };
+
+        let res = self
+            .send_authorized(|access_token| self.request_client.post(&url).bearer_auth(access_token))
+            .map_err(|e| format!("Unable to send (unofficial) POST request to {}: {}", endpoint, e))?;
+
+        if !res.status().is_success() {
+            return Err(format!("(unofficial) POST request to {} failed with status code {}", endpoint, res.status()));
+        }
+
+        let json: JSONValue = res.json()
+            .map_err(|e| format!("Unable to parse (unofficial) API response into JSON: {}", e))?;
+
+        Ok(json)
+    }
 }
@@ -0,0 +1,798 @@
+//! Deserialization tests against recorded API responses in `tests/fixtures`.
+//!
+//! When Tidal changes the shape of a response, update (or add) its fixture here first.
+
+use std::fmt::Debug;
+
+use chrono::NaiveDate;
+use serde::{
+    de::DeserializeOwned,
+    Serialize,
+};
+use serde_json::Value as JSONValue;
+
+use super::{
+    album::AlbumAttributes,
+    artist::{
+        format_display_name,
+        ArtistAttributes,
+        ArtistRole,
+    },
+    playlist::PlaylistAttributes,
+    session::SessionInfo,
+    track::{
+        TrackAttributes,
+        TrackManifest,
+    },
+    user::UserAttributes,
+    Album,
+    Artist,
+    AudioQuality,
+    MediaTag,
+    Playlist,
+    SearchResults,
+    Track,
+    TrackNumber,
+    User,
+};
+#[cfg(feature = "unofficial")]
+use super::{
+    home::ParsedHomeItem,
+    HomePage,
+    Lyrics,
+    LyricsLine,
+    Mix,
+    PublicUser,
+    Subscription,
+};
+
+/// Parses a fixture from `tests/fixtures`.
+fn fixture(contents: &str) -> JSONValue {
+    serde_json::from_str(contents).expect("fixture should be valid JSON")
+}
+
+/// Asserts that `value` survives a serde round-trip unchanged.
+fn assert_round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+    let json = serde_json::to_value(value).unwrap();
+    let parsed: T = serde_json::from_value(json).unwrap();
+    assert_eq!(&parsed, value);
+}
+
+mod tracks {
+    use super::*;
+
+    #[test]
+    fn parses_track_attributes() {
+        let attributes = TrackAttributes::from_response(fixture(include_str!("../tests/fixtures/track.json"))).unwrap();
+
+        assert_eq!(attributes.title, "Paranoid Android");
+        assert_eq!(attributes.version, None);
+        assert_eq!(attributes.get_full_title(), "Paranoid Android");
+        assert_eq!(attributes.isrc, "GBAYE9700138");
+        assert_eq!(attributes.duration, "PT6M27S");
+        assert_eq!(attributes.copyright.get("text").map(String::as_str), Some("(P) 1997 XL Recordings Ltd"));
+        assert!(!attributes.explicit);
+        assert_eq!(attributes.availability, vec!["STREAM", "DJ"]);
+        assert_eq!(attributes.media_tags, vec![MediaTag::Lossless, MediaTag::HiresLossless]);
+        assert_round_trip(&attributes);
+    }
+
+    #[test]
+    fn parses_track_attributes_with_version_and_no_copyright() {
+        let attributes = TrackAttributes::from_response(fixture(include_str!("../tests/fixtures/track_with_version.json"))).unwrap();
+
+        assert_eq!(attributes.title, "Let Down");
+        assert_eq!(attributes.version.as_deref(), Some("Remastered"));
+        assert_eq!(attributes.get_full_title(), "Let Down (Remastered)");
+        assert!(attributes.copyright.is_empty());
+        assert_round_trip(&attributes);
+    }
+
+    #[test]
+    fn splits_classical_titles_into_work_and_movement() {
+        let attributes = TrackAttributes::from_response(fixture(
+            r#"{"data": {"id": "1", "type": "tracks", "attributes": {"title": "Symphony No. 5 in C Minor, Op. 67: I. Allegro con brio", "version": "Live"}}}"#,
+        )).unwrap();
+        assert_eq!(
+            attributes.get_work_and_movement(),
+            (String::from("Symphony No. 5 in C Minor, Op. 67"), Some(String::from("I. Allegro con brio (Live)"))),
+        );
+
+        let attributes = TrackAttributes::from_response(fixture(include_str!("../tests/fixtures/track.json"))).unwrap();
+        assert_eq!(attributes.get_work_and_movement(), (String::from("Paranoid Android"), None));
+    }
+
+    #[test]
+    fn parses_track_with_only_a_title() {
+        let attributes = TrackAttributes::from_response(fixture(include_str!("../tests/fixtures/track_minimal.json"))).unwrap();
+
+        assert_eq!(attributes.title, "Demo 3");
+        assert_eq!(attributes.isrc, "");
+        assert_eq!(attributes.duration, "");
+        assert_eq!(attributes.popularity, 0.0);
+        assert!(!attributes.explicit);
+        assert!(attributes.availability.is_empty());
+        assert!(attributes.media_tags.is_empty());
+    }
+
+    #[test]
+    fn rejects_track_without_attributes() {
+        let json = fixture(r#"{"data": {"id": "1", "type": "tracks"}}"#);
+
+        assert!(TrackAttributes::from_response(json).is_err());
+    }
+
+    #[test]
+    fn parses_relationship_ids() {
+        let albums = fixture(include_str!("../tests/fixtures/track_relationships_albums.json"));
+        let artists = fixture(include_str!("../tests/fixtures/track_relationships_artists.json"));
+        let empty = fixture(include_str!("../tests/fixtures/track_relationships_empty.json"));
+
+        assert_eq!(Track::parse_relationship_id(&albums).as_deref(), Some("77646168"));
+        assert_eq!(Track::parse_relationship_id(&artists).as_deref(), Some("64518"));
+        assert_eq!(Track::parse_relationship_id(&empty), None);
+    }
+
+    #[test]
+    fn parses_track_manifest() {
+        let manifest = TrackManifest::from_response(fixture(include_str!("../tests/fixtures/track_manifest.json"))).unwrap();
+
+        assert_eq!(manifest.uri, include_str!("../tests/fixtures/track_manifest.xml"));
+        assert_eq!(manifest.formats, vec!["FLAC"]);
+        assert_eq!(manifest.preview_reason, None);
+        assert_eq!(manifest.track_presentation, "FULL");
+        assert_eq!(manifest.track_audio_normalization_data.replay_gain, -8.6);
+        assert_eq!(manifest.album_audio_normalization_data.peak_amplitude, 0.999969);
+        assert_eq!(manifest.get_expires_at(), Ok(1760659200));
+        assert_eq!(manifest.get_quality(), Some(AudioQuality::High));
+        assert!(!manifest.is_drm_protected());
+        assert_round_trip(&manifest);
+    }
+
+    #[test]
+    fn detects_drm_protected_manifests() {
+        let mut json = fixture(include_str!("../tests/fixtures/track_manifest.json"));
+        json["data"]["attributes"]["drmData"] = serde_json::json!({ "drmSystem": "WIDEVINE", "licenseUrl": "https://example.com" });
+        assert!(TrackManifest::from_response(json).unwrap().is_drm_protected());
+
+        let mut manifest = TrackManifest::from_response(fixture(include_str!("../tests/fixtures/track_manifest.json"))).unwrap();
+        manifest.uri = manifest.uri.replace("<SegmentTemplate", "<ContentProtection schemeIdUri=\"urn:mpeg:dash:mp4protection:2011\"/><SegmentTemplate");
+        assert!(manifest.is_drm_protected());
+    }
+
+    #[test]
+    fn skips_expiry_of_drm_protected_manifests() {
+        let manifest = TrackManifest::from_response(fixture(include_str!("../tests/fixtures/track_manifest_drm.json"))).unwrap();
+
+        assert_eq!(manifest.uri, include_str!("../tests/fixtures/track_manifest_drm.xml"));
+        assert!(manifest.is_drm_protected());
+        // Its segment urls have no token, so the expiry can't be parsed, but it isn't needed either.
+        assert!(manifest.get_expires_at().is_err());
+        assert_eq!(manifest.get_cache_expires_at(), Ok(None));
+
+        let manifest = TrackManifest::from_response(fixture(include_str!("../tests/fixtures/track_manifest.json"))).unwrap();
+        assert_eq!(manifest.get_cache_expires_at(), Ok(Some(1760659200)));
+    }
+
+    #[test]
+    fn picks_the_best_manifest_format() {
+        let mut manifest = TrackManifest::from_response(fixture(include_str!("../tests/fixtures/track_manifest.json"))).unwrap();
+
+        manifest.formats = vec![String::from("AACLC"), String::from("FLAC_HIRES"), String::from("SOMETHING_NEW")];
+        assert_eq!(manifest.get_quality(), Some(AudioQuality::Max));
+
+        manifest.formats = vec![];
+        assert_eq!(manifest.get_quality(), None);
+    }
+
+    #[test]
+    fn rejects_track_manifest_with_invalid_base64() {
+        let mut json = fixture(include_str!("../tests/fixtures/track_manifest.json"));
+        json["data"]["attributes"]["uri"] = JSONValue::from("data:application/dash+xml;base64,not base64!");
+
+        assert!(TrackManifest::from_response(json).is_err());
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_track_url() {
+        let json = fixture(include_str!("../tests/fixtures/urlpostpaywall.json"));
+
+        assert_eq!(
+            Track::parse_url_response(&json).as_deref(),
+            Some("https://lgf.audio.tidal.com/mediatracks/abc/0.flac?token=1760659200~ZmFrZQ"),
+        );
+        assert_eq!(Track::parse_url_response(&fixture(r#"{"urls": []}"#)), None);
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_radio_tracks() {
+        let tracks = Track::parse_radio_response(&fixture(include_str!("../tests/fixtures/track_radio.json"))).unwrap();
+
+        let ids: Vec<&str> = tracks.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["77646171", "1781873"]);
+        assert_eq!(tracks[0].1.duration, "PT383S");
+        assert!(Track::parse_radio_response(&fixture("{}")).is_err());
+    }
+}
+
+mod media_tags {
+    use super::*;
+
+    #[test]
+    fn parses_known_and_unknown_media_tags() {
+        let tags: Vec<MediaTag> = serde_json::from_str(r#"["LOSSLESS", "HIRES_LOSSLESS", "DOLBY_ATMOS", "SONY_360RA", "MQA", "SOMETHING_NEW"]"#).unwrap();
+
+        assert_eq!(
+            tags,
+            vec![MediaTag::Lossless, MediaTag::HiresLossless, MediaTag::DolbyAtmos, MediaTag::Sony360Ra, MediaTag::Mqa, MediaTag::Unknown],
+        );
+        assert_eq!(tags.iter().filter_map(|tag| tag.to_badge()).collect::<Vec<_>>(), vec!["HiRes", "Atmos", "360", "MQA"]);
+    }
+
+    #[test]
+    fn caps_quality_to_media_tags() {
+        assert_eq!(MediaTag::get_max_quality(&[MediaTag::Lossless, MediaTag::HiresLossless]), AudioQuality::Max);
+        assert_eq!(MediaTag::get_max_quality(&[MediaTag::Lossless]), AudioQuality::High);
+        assert_eq!(MediaTag::get_max_quality(&[MediaTag::DolbyAtmos]), AudioQuality::Low320);
+        assert_eq!(MediaTag::get_max_quality(&[]), AudioQuality::Max);
+    }
+
+    #[test]
+    fn lowers_qualities_one_step_at_a_time() {
+        assert_eq!(AudioQuality::Max.lower(), Some(AudioQuality::High));
+        assert_eq!(AudioQuality::Low320.lower(), Some(AudioQuality::Low96));
+        assert_eq!(AudioQuality::Low96.lower(), None);
+    }
+}
+
+mod albums {
+    use super::*;
+
+    #[test]
+    fn parses_album_with_cover_art() {
+        let (attributes, cover_art) = Album::parse_response(fixture(include_str!("../tests/fixtures/album.json"))).unwrap();
+
+        assert_eq!(attributes.title, "OK Computer");
+        assert_eq!(attributes.barcode_id, "634904078164");
+        assert_eq!(attributes.number_of_volumes, 1);
+        assert_eq!(attributes.number_of_items, 12);
+        assert_eq!(attributes.duration, "PT53M27S");
+        assert_eq!(attributes.release_date, "1997-05-28");
+        assert_eq!(attributes.get_release_date(), NaiveDate::from_ymd_opt(1997, 5, 28));
+        assert_eq!(attributes.get_release_year(), Some(1997));
+        assert_eq!(attributes.media_tags, vec![MediaTag::Lossless]);
+        assert_eq!(attributes.genres, vec![String::from("Rock")]);
+        assert_eq!(cover_art.len(), 2);
+        assert_eq!(cover_art[0].url, "https://resources.tidal.com/images/0e2a6a6a/1280x1280.jpg");
+        assert_eq!((cover_art[1].width, cover_art[1].height), (640, 640));
+        assert_round_trip(&attributes);
+    }
+
+    #[test]
+    fn picks_cover_art_size() {
+        let (_, cover_art) = Album::parse_response(fixture(include_str!("../tests/fixtures/album.json"))).unwrap();
+
+        assert_eq!(Album::pick_cover_art(&cover_art, 320).unwrap().width, 640);
+        assert_eq!(Album::pick_cover_art(&cover_art, 1000).unwrap().width, 1280);
+        assert_eq!(Album::pick_cover_art(&cover_art, 3000).unwrap().width, 1280);
+        assert_eq!(Album::pick_cover_art(&[], 320), None);
+    }
+
+    #[test]
+    fn parses_album_without_cover_art() {
+        let mut json = fixture(include_str!("../tests/fixtures/album.json"));
+        json["included"] = JSONValue::Array(vec![]);
+
+        let (attributes, cover_art) = Album::parse_response(json).unwrap();
+        assert_eq!(attributes.title, "OK Computer");
+        assert!(attributes.genres.is_empty());
+        assert!(cover_art.is_empty());
+    }
+
+    #[test]
+    fn finds_cover_art_listed_after_genres() {
+        let mut json = fixture(include_str!("../tests/fixtures/album.json"));
+        json["included"].as_array_mut().unwrap().reverse();
+
+        let (attributes, cover_art) = Album::parse_response(json).unwrap();
+        assert_eq!(attributes.genres, vec![String::from("Rock")]);
+        assert_eq!(cover_art.len(), 2);
+    }
+
+    #[test]
+    fn parses_album_with_only_a_title() {
+        let (attributes, _) = Album::parse_response(fixture(include_str!("../tests/fixtures/album_minimal.json"))).unwrap();
+
+        assert_eq!(attributes.title, "Untitled Sessions");
+        assert_eq!(attributes.barcode_id, "");
+        assert_eq!(attributes.release_date, "");
+        assert_eq!(attributes.get_release_date(), None);
+        assert_eq!(attributes.popularity, 0.0);
+        assert!(attributes.media_tags.is_empty());
+    }
+
+    #[test]
+    fn parses_year_only_release_dates() {
+        let mut json = fixture(include_str!("../tests/fixtures/album.json"));
+        json["data"]["attributes"]["releaseDate"] = JSONValue::from("1971");
+
+        let (attributes, _) = Album::parse_response(json).unwrap();
+        assert_eq!(attributes.get_release_date(), NaiveDate::from_ymd_opt(1971, 1, 1));
+        assert_eq!(attributes.get_release_year(), Some(1971));
+    }
+
+    #[test]
+    fn parses_album_items_pages() {
+        let page1 = fixture(include_str!("../tests/fixtures/album_items_page1.json"));
+        let page2 = fixture(include_str!("../tests/fixtures/album_items_page2.json"));
+
+        let (tracks, next) = Album::parse_items_page(&page1).unwrap();
+        // Videos are skipped.
+        assert_eq!(
+            tracks,
+            vec![
+                (String::from("77646169"), TrackNumber { volume: 1, track: 1 }),
+                (String::from("77646170"), TrackNumber { volume: 1, track: 2 }),
+            ],
+        );
+        assert_eq!(next.as_deref(), Some("/albums/77646168/relationships/items?countryCode=CA&page%5Bcursor%5D=3nI1Esi"));
+
+        let (tracks, next) = Album::parse_items_page(&page2).unwrap();
+        assert_eq!(tracks, vec![(String::from("77646171"), TrackNumber { volume: 1, track: 4 })]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn album_attributes_round_trip_through_api_names() {
+        let json = fixture(include_str!("../tests/fixtures/album.json"));
+        let attributes: AlbumAttributes = serde_json::from_value(json["data"]["attributes"].clone()).unwrap();
+
+        let serialized = serde_json::to_value(&attributes).unwrap();
+        assert_eq!(serialized["barcodeId"], json["data"]["attributes"]["barcodeId"]);
+        assert_eq!(serialized["numberOfItems"], json["data"]["attributes"]["numberOfItems"]);
+        assert_eq!(serialized["releaseDate"], json["data"]["attributes"]["releaseDate"]);
+    }
+}
+
+mod artists {
+    use super::*;
+
+    #[test]
+    fn parses_artist_attributes() {
+        let attributes = ArtistAttributes::from_response(fixture(include_str!("../tests/fixtures/artist.json"))).unwrap();
+
+        assert_eq!(attributes.name, "Radiohead");
+        assert_eq!(attributes.popularity, 0.86);
+        assert_round_trip(&attributes);
+    }
+
+    #[test]
+    fn parses_artist_without_popularity() {
+        let attributes = ArtistAttributes::from_response(fixture(r#"{"data": {"id": "1", "type": "artists", "attributes": {"name": "Unknown"}}}"#)).unwrap();
+
+        assert_eq!(attributes.name, "Unknown");
+        assert_eq!(attributes.popularity, 0.0);
+    }
+
+    #[test]
+    fn parses_artist_top_tracks() {
+        let track_ids = Artist::parse_tracks_response(&fixture(include_str!("../tests/fixtures/artist_tracks.json"))).unwrap();
+
+        assert_eq!(track_ids, ["77646170", "77646169"]);
+    }
+
+    #[test]
+    fn parses_artist_albums() {
+        let (albums, next) = Artist::parse_albums_page(&fixture(include_str!("../tests/fixtures/artist_albums.json"))).unwrap();
+
+        // The third album's attributes weren't included, so it is left out.
+        let titles: Vec<&str> = albums.iter().map(|(_, attributes)| attributes.title.as_str()).collect();
+        assert_eq!(titles, ["OK Computer", "In Rainbows"]);
+        assert_eq!(albums[1].0, "1781870");
+        assert_eq!(next.as_deref(), Some("/artists/64518/relationships/albums?countryCode=CA&include=albums&page%5Bcursor%5D=3nK2"));
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_collection_artists() {
+        let (artists, total) = Artist::parse_unofficial_page(&fixture(include_str!("../tests/fixtures/favorites_artists.json"))).unwrap();
+
+        let names: Vec<(&str, &str)> = artists.iter().map(|(id, attributes)| (id.as_str(), attributes.name.as_str())).collect();
+        assert_eq!(names, [("64518", "Radiohead"), ("3528326", "Björk")]);
+        assert_eq!(artists[1].1.popularity, 0.0);
+        assert_eq!(total, 2);
+    }
+
+    #[test]
+    fn parses_artist_credits_with_roles() {
+        let credits = ArtistAttributes::parse_credits(&fixture(include_str!("../tests/fixtures/track_relationships_artists_roles.json"))).unwrap();
+
+        let roles: Vec<(&str, &ArtistRole)> = credits.iter().map(|(id, role, _)| (id.as_str(), role)).collect();
+        assert_eq!(roles, [
+            ("3528326", &ArtistRole::Main),
+            ("7804", &ArtistRole::Featured),
+            ("4761957", &ArtistRole::Remixer),
+            ("1566", &ArtistRole::Other(String::from("PRODUCER"))),
+        ]);
+
+        assert_eq!(credits[1].2.as_ref().map(|attributes| attributes.name.as_str()), Some("Pharrell Williams"));
+        // Artists that weren't included have to be fetched separately.
+        assert_eq!(credits[3].2, None);
+        assert_eq!(ArtistRole::from_api_name("COMPOSER"), ArtistRole::Composer);
+    }
+
+    #[test]
+    fn credits_artists_without_roles_as_main() {
+        let credits = ArtistAttributes::parse_credits(&fixture(include_str!("../tests/fixtures/track_relationships_artists.json"))).unwrap();
+
+        assert_eq!(credits.len(), 2);
+        assert!(credits.iter().all(|(_, role, attributes)| *role == ArtistRole::Main && attributes.is_none()));
+    }
+
+    #[test]
+    fn formats_artist_credits() {
+        let main = ArtistRole::Main;
+        let featured = ArtistRole::Featured;
+        let remixer = ArtistRole::Remixer;
+        let producer = ArtistRole::Other(String::from("PRODUCER"));
+
+        assert_eq!(format_display_name(&[("Radiohead", &main)]), "Radiohead");
+        assert_eq!(
+            format_display_name(&[("Daft Punk", &main), ("Pharrell Williams", &featured), ("Nile Rodgers", &featured), ("Todd Terje", &remixer)]),
+            "Daft Punk (feat. Pharrell Williams & Nile Rodgers) [Todd Terje Remix]",
+        );
+        assert_eq!(format_display_name(&[("A", &main), ("B", &main), ("C", &main), ("D", &producer)]), "A, B & C");
+        // Someone is always shown, even if no one is credited as a main artist.
+        assert_eq!(format_display_name(&[("Todd Terje", &remixer), ("Lindstrøm", &remixer)]), "Todd Terje [Lindstrøm Remix]");
+        assert_eq!(format_display_name(&[]), "");
+    }
+}
+
+mod playlists {
+    use super::*;
+
+    #[test]
+    fn parses_playlist_attributes() {
+        let attributes = PlaylistAttributes::from_response(fixture(include_str!("../tests/fixtures/playlist.json"))).unwrap();
+
+        assert_eq!(
+            attributes,
+            PlaylistAttributes {
+                name: String::from("Late Night Drives"),
+                description: Some(String::from("Slow songs for empty highways")),
+                number_of_items: 31,
+                access_type: String::from("PUBLIC"),
+                playlist_type: String::from("USER"),
+                collaborative: false,
+            },
+        );
+        assert!(attributes.is_public());
+        assert_round_trip(&attributes);
+    }
+
+    #[test]
+    fn parses_playlist_items_page() {
+        let json = fixture(include_str!("../tests/fixtures/playlist_items.json"));
+        let (items, next) = Playlist::parse_items_page(&json).unwrap();
+
+        // Videos are skipped, and the same track can appear more than once with different item ids.
+        assert_eq!(
+            items,
+            vec![
+                (String::from("77646170"), String::from("a1f0c5d2-6e1b-4c39-9a57-3b8d2e7f9c01")),
+                (String::from("77646170"), String::from("e93b27c4-8f0d-4a62-b1c5-9d7e3f2a6b18")),
+            ],
+        );
+        assert_eq!(
+            next.as_deref(),
+            Some("/playlists/7c2b0a61-3f3e-4b8e-a0a4-55d9e4b1c2d3/relationships/items?countryCode=CA&page%5Bcursor%5D=8aQ2kLm"),
+        );
+    }
+
+    #[test]
+    fn builds_move_item_body() {
+        let body = Playlist::move_item_body("e93b27c4", "77646170", Some("a1f0c5d2"));
+        assert_eq!(
+            body.to_string(),
+            r#"{"data":[{"id":"77646170","meta":{"itemId":"e93b27c4"},"type":"tracks"}],"meta":{"positionBefore":"a1f0c5d2"}}"#,
+        );
+
+        let body = Playlist::move_item_body("e93b27c4", "77646170", None);
+        assert!(body.get("meta").is_none());
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_unofficial_playlists_page() {
+        let json = fixture(include_str!("../tests/fixtures/user_playlists.json"));
+        let (playlists, total) = Playlist::parse_unofficial_page(&json).unwrap();
+
+        assert_eq!(total, 2);
+        assert_eq!(playlists.len(), 2);
+
+        let (id, attributes) = &playlists[1];
+        assert_eq!(id, "7c2b0a61-3f3e-4b8e-a0a4-55d9e4b1c2d3");
+        assert_eq!(attributes.name, "Work in progress");
+        assert_eq!(attributes.description, None);
+        assert_eq!(attributes.number_of_items, 4);
+        assert!(!attributes.is_public());
+        assert!(attributes.collaborative);
+        assert!(!playlists[0].1.collaborative);
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_invite_link() {
+        let json = fixture(include_str!("../tests/fixtures/playlist_invite.json"));
+
+        assert_eq!(
+            Playlist::parse_invite_link_response(&json).unwrap(),
+            "https://tidal.com/playlist/7c2b0a61-3f3e-4b8e-a0a4-55d9e4b1c2d3/invite/5b9f0c2e8d",
+        );
+    }
+}
+
+mod search {
+    use super::*;
+
+    #[test]
+    fn parses_search_results() {
+        let items = SearchResults::parse_response(&fixture(include_str!("../tests/fixtures/search_results.json"))).unwrap();
+
+        let track_ids: Vec<&str> = items.tracks.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(track_ids, ["77646169", "77646170"]);
+        assert_eq!(items.tracks[0].1.as_ref().map(|attributes| attributes.title.as_str()), Some("Paranoid Android"));
+        // Results that weren't included still keep their place.
+        assert_eq!(items.tracks[1].1, None);
+
+        assert_eq!(items.albums.len(), 1);
+        assert_eq!(items.albums[0].1.as_ref().map(|attributes| attributes.title.as_str()), Some("OK Computer"));
+        assert_eq!(items.artists[0].1.as_ref().map(|attributes| attributes.name.as_str()), Some("Radiohead"));
+        assert!(items.playlists.is_empty());
+    }
+
+    #[test]
+    fn rejects_search_results_without_relationships() {
+        assert!(SearchResults::parse_response(&fixture(r#"{"errors": [{"status": "404"}]}"#)).is_err());
+    }
+}
+
+mod users {
+    use super::*;
+
+    #[test]
+    fn parses_current_user() {
+        let (id, attributes) = User::parse_response(fixture(include_str!("../tests/fixtures/user.json"))).unwrap();
+
+        assert_eq!(id, "192837465");
+        assert_eq!(
+            attributes,
+            UserAttributes {
+                username: String::from("listener"),
+                country: String::from("CA"),
+                email: String::from("listener@example.com"),
+                email_verified: true,
+            },
+        );
+        assert_round_trip(&attributes);
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_collection_tracks() {
+        let json = fixture(include_str!("../tests/fixtures/favorites_tracks.json"));
+
+        let (items, total) = User::parse_collection_tracks_response(&json).unwrap();
+        let track_ids: Vec<&str> = items.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(track_ids, vec!["77646169", "77646170"]);
+        assert_eq!(items[0].1.unwrap().to_rfc3339(), "2025-01-04T18:22:10+00:00");
+        assert_eq!(total, 2);
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn rejects_collection_tracks_with_string_ids() {
+        let mut json = fixture(include_str!("../tests/fixtures/favorites_tracks.json"));
+        json["items"][0]["item"]["id"] = JSONValue::from("77646169");
+
+        assert!(User::parse_collection_tracks_response(&json).is_err());
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_collection_albums() {
+        let json = fixture(include_str!("../tests/fixtures/favorites_albums.json"));
+
+        let (albums, total) = Album::parse_unofficial_page(&json).unwrap();
+        let album_ids: Vec<&str> = albums.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(album_ids, vec!["77646168", "1781870"]);
+        assert_eq!(total, 2);
+
+        let (_, attributes) = &albums[0];
+        assert_eq!(attributes.title, "OK Computer");
+        assert_eq!(attributes.number_of_items, 12);
+        assert_eq!(attributes.duration, "PT3207S");
+        assert_eq!(attributes.get_release_year(), Some(1997));
+        assert_eq!(albums[1].1.media_tags, vec![MediaTag::Lossless, MediaTag::HiresLossless]);
+        assert_eq!(albums[1].1.get_release_year(), Some(2007));
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn rejects_collection_albums_without_total() {
+        let mut json = fixture(include_str!("../tests/fixtures/favorites_albums.json"));
+        json["totalNumberOfItems"] = JSONValue::Null;
+
+        assert!(Album::parse_unofficial_page(&json).is_err());
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_subscription() {
+        let json = fixture(include_str!("../tests/fixtures/subscription.json"));
+        let subscription = User::parse_subscription_response(&json).unwrap();
+
+        assert_eq!(
+            subscription,
+            Subscription {
+                tier: String::from("HIFI"),
+                status: String::from("ACTIVE"),
+                highest_quality: AudioQuality::High,
+            },
+        );
+        assert_eq!(subscription.get_tier_name(), "HiFi");
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn assumes_max_quality_for_unknown_sound_quality() {
+        let mut json = fixture(include_str!("../tests/fixtures/subscription.json"));
+        json["highestSoundQuality"] = JSONValue::from("SPATIAL");
+
+        assert_eq!(User::parse_subscription_response(&json).unwrap().highest_quality, AudioQuality::Max);
+    }
+
+    #[cfg(feature = "unofficial")]
+    #[test]
+    fn parses_public_user_name() {
+        let mut json = fixture(include_str!("../tests/fixtures/public_user.json"));
+        assert_eq!(PublicUser::parse_response(&json, "184467302"), "Sam Okafor");
+
+        json["firstName"] = JSONValue::Null;
+        json["lastName"] = JSONValue::Null;
+        assert_eq!(PublicUser::parse_response(&json, "184467302"), "nightdriver");
+
+        json["username"] = JSONValue::Null;
+        assert_eq!(PublicUser::parse_response(&json, "184467302"), "User 184467302");
+    }
+}
+
+mod sessions {
+    use super::*;
+
+    /// Returns session info for a token issued at 1000 that expires at 4600.
+    fn session_info() -> SessionInfo {
+        SessionInfo {
+            access_token: String::from("access"),
+            refresh_token: String::from("refresh"),
+            expires_at: 4600,
+            issued_at: 1000,
+        }
+    }
+
+    #[test]
+    fn is_not_expiring_outside_margin() {
+        assert!(!session_info().is_expiring(1000, 60));
+        assert!(!session_info().is_expiring(4539, 60));
+    }
+
+    #[test]
+    fn is_expiring_inside_margin() {
+        assert!(session_info().is_expiring(4540, 60));
+        assert!(session_info().is_expiring(4600, 60));
+        assert!(session_info().is_expiring(5000, 0));
+    }
+
+    #[test]
+    fn is_expiring_when_clock_went_backwards() {
+        assert!(session_info().is_expiring(999, 60));
+        assert!(session_info().is_expiring(0, 0));
+    }
+}
+
+#[cfg(feature = "unofficial")]
+mod home {
+    use super::*;
+
+    #[test]
+    fn parses_home_page_sections() {
+        let sections = HomePage::parse_response(&fixture(include_str!("../tests/fixtures/home_page.json"))).unwrap();
+
+        let titles: Vec<&str> = sections.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, ["My Mixes", "Recently played", "Suggested new tracks"]);
+
+        let (_, mixes) = &sections[0];
+        assert!(matches!(
+            &mixes[1],
+            ParsedHomeItem::Mix { id, title, .. } if id == "fedcba9876543210fedcba98765432" && title == "My Daily Discovery",
+        ));
+
+        // The video in the mixed list is left out.
+        let (_, recent) = &sections[1];
+        assert_eq!(recent.len(), 3);
+        assert!(matches!(&recent[0], ParsedHomeItem::Album(id, attributes) if id == "77646168" && attributes.title == "OK Computer"));
+        assert!(matches!(&recent[1], ParsedHomeItem::Playlist(id, _) if id == "0f1e6d4c-8a54-4d1e-9d1b-3c3e2f1b7a90"));
+        assert!(matches!(&recent[2], ParsedHomeItem::Artist(id, attributes) if id == "64518" && attributes.name == "Radiohead"));
+
+        let (_, tracks) = &sections[2];
+        let ParsedHomeItem::Track(id, attributes) = &tracks[0] else {
+            panic!("expected a track");
+        };
+        assert_eq!(id, "372418853");
+        assert_eq!(attributes.version, None);
+        assert_eq!(attributes.duration, "PT187S");
+        assert!(attributes.media_tags.contains(&MediaTag::HiresLossless));
+    }
+
+    #[test]
+    fn rejects_home_page_without_rows() {
+        assert!(HomePage::parse_response(&fixture("{}")).is_err());
+    }
+
+    #[test]
+    fn parses_mix_tracks() {
+        let tracks = Mix::parse_items_response(&fixture(include_str!("../tests/fixtures/mix_items.json"))).unwrap();
+
+        let ids: Vec<&str> = tracks.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, ["77646170", "1781872"]);
+        assert_eq!(tracks[1].1.version.as_deref(), Some("Remastered"));
+    }
+}
+
+#[cfg(feature = "unofficial")]
+mod lyrics {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::lyrics::parse_timed_line;
+
+    #[test]
+    fn parses_timed_lyrics() {
+        let lyrics = Lyrics::parse_response(&fixture(include_str!("../tests/fixtures/lyrics.json"))).unwrap();
+
+        assert!(lyrics.is_synced());
+        assert_eq!(lyrics.provider.as_deref(), Some("MUSIXMATCH"));
+        assert_eq!(lyrics.lines.len(), 4);
+        assert_eq!(lyrics.lines[1], LyricsLine { start: Some(Duration::from_millis(15_920)), text: String::from("Come on rain down on me") });
+        assert_eq!(lyrics.lines[3].start, Some(Duration::from_millis(62_500)));
+    }
+
+    #[test]
+    fn falls_back_to_plain_lyrics() {
+        let mut json = fixture(include_str!("../tests/fixtures/lyrics.json"));
+        json["subtitles"] = JSONValue::from("");
+        let lyrics = Lyrics::parse_response(&json).unwrap();
+
+        assert!(!lyrics.is_synced());
+        assert_eq!(lyrics.lines.len(), 4);
+        assert_eq!(lyrics.get_current_line(Duration::from_secs(30)), None);
+    }
+
+    #[test]
+    fn finds_current_line() {
+        let lyrics = Lyrics::parse_response(&fixture(include_str!("../tests/fixtures/lyrics.json"))).unwrap();
+
+        assert_eq!(lyrics.get_current_line(Duration::from_secs(5)), None);
+        assert_eq!(lyrics.get_current_line(Duration::from_millis(15_920)), Some(1));
+        assert_eq!(lyrics.get_current_line(Duration::from_secs(300)), Some(3));
+    }
+
+    #[test]
+    fn skips_untimed_lines() {
+        assert_eq!(parse_timed_line("[ar: Radiohead]"), None);
+        assert_eq!(parse_timed_line("Rain down"), None);
+        assert_eq!(parse_timed_line("[00:01.00]").map(|line| line.text), Some(String::new()));
+    }
+}
@@ -0,0 +1,634 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+    },
+    time::Duration
+};
+
+use base64::{
+    engine::general_purpose::STANDARD as BASE64, 
+    Engine as _
+};
+use chrono::{
+    DateTime,
+    Utc,
+};
+use once_cell::sync::OnceCell;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{
+    self,
+    Value as JSONValue,
+};
+use uuid::Uuid;
+
+use super::Album;
+use super::artist::{
+    ArtistAttributes,
+    ArtistCredit,
+    ArtistCredits,
+};
+use super::Artist;
+use super::AudioQuality;
+#[cfg(feature = "unofficial")]
+use super::Lyrics;
+use super::MediaTag;
+use super::Session;
+
+/// A Tidal track.
+#[derive(Clone, Debug)]
+pub struct Track {
+    session: Arc<Session>,
+    pub id: String,
+
+    // Cache the duration regex result.
+    duration: OnceCell<Duration>,
+
+    // The following fields are used to cache API results.
+    attributes: OnceCell<TrackAttributes>,
+    album: OnceCell<Album>,
+    artist_credits: OnceCell<ArtistCredits>,
+    info_error: Arc<Mutex<Option<String>>>,
+    cached_manifest: Arc<Mutex<Option<CachedTrackManifest>>>,
+    #[cfg(feature = "unofficial")]
+    url_cache: Arc<Mutex<Option<(String, AudioQuality)>>>,
+    /// Whether the last manifest fetched for this track was DRM protected.
+    is_drm_protected: Arc<AtomicBool>,
+
+    /// When this track was added to the user's collection, if it was fetched as part of it.
+    added_at: Option<DateTime<Utc>>,
+}
+
+/// A track's API attributes.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackAttributes {
+    pub title: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub isrc: String,
+    #[serde(default)]
+    pub duration: String,
+    #[serde(default)]
+    pub copyright: HashMap<String, String>,
+    #[serde(default)]
+    pub explicit: bool,
+    #[serde(default)]
+    pub popularity: f32,
+    #[serde(default)]
+    pub availability: Vec<String>,
+    #[serde(default)]
+    pub media_tags: Vec<MediaTag>,
+}
+
+/// Normalization information used for both track and album normalization data.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationData {
+    pub peak_amplitude: f32,
+    pub replay_gain: f32,
+}
+
+/// A track's manifest.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackManifest {
+    pub album_audio_normalization_data: NormalizationData,
+    pub formats: Vec<String>,
+    pub hash: String,
+    pub preview_reason: Option<String>,
+    pub track_audio_normalization_data: NormalizationData,
+    pub track_presentation: String,
+    pub uri: String,
+    /// How to decrypt the stream, which is only there for DRM protected tracks.
+    #[serde(default)]
+    pub drm_data: Option<JSONValue>,
+}
+
+/// Wrapper used for `TrackManifest` caching.
+#[derive(Debug)]
+struct CachedTrackManifest {
+    manifest: TrackManifest,
+    quality: AudioQuality,
+    expires_at: i64,
+}
+
+impl Track {
+    /// Returns a new `Track` from a track's id.
+    pub fn new(session: Arc<Session>, id: String) -> Result<Self, String> {
+        Ok(Self {
+            session,
+            id,
+            duration: OnceCell::new(),
+            attributes: OnceCell::new(),
+            album: OnceCell::new(),
+            artist_credits: OnceCell::new(),
+            info_error: Arc::new(Mutex::new(None)),
+            cached_manifest: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "unofficial")]
+            url_cache: Arc::new(Mutex::new(None)),
+            is_drm_protected: Arc::new(AtomicBool::new(false)),
+            added_at: None,
+        })
+    }
+
+    /// Sets this track's attributes, when they were already fetched as part of another response (e.g. a search).
+    pub(crate) fn with_attributes(self, attributes: TrackAttributes) -> Self {
+        let _ = self.attributes.set(attributes);
+        self
+    }
+
+    /// Sets when this track was added to the user's collection.
+    #[cfg(feature = "unofficial")]
+    pub(crate) fn with_added_at(mut self, added_at: Option<DateTime<Utc>>) -> Self {
+        self.added_at = added_at;
+        self
+    }
+
+    /// Returns a reference to the `TrackAttributes` associated with this track.
+    /// 
+    /// This `TrackAttributes` is then cached within `self`.
+    pub fn get_attribtues(&self) -> Result<&TrackAttributes, String> {
+        self.attributes.get_or_try_init(|| -> Result<TrackAttributes, String> {
+            let endpoint = format!("/tracks/{}", self.id);
+            let json = self.session.get(&endpoint)?;
+
+            TrackAttributes::from_response(json)
+        })
+    }
+
+    /// Returns a reference to the `Album` associated with this track.
+    /// 
+    /// This `Album` is then cached within `self`.
+    pub fn get_album(&self) -> Result<&Album, String> {
+        self.album.get_or_try_init(|| -> Result<Album, String> {
+            let album_relationships_endpoint = format!("/tracks/{}/relationships/albums", self.id);
+            let json = self.session.get(&album_relationships_endpoint)?;
+
+            let album_id = Self::parse_relationship_id(&json)
+                .ok_or(String::from("Unable to parse album relationship API response"))?;
+            
+            let album = Album::new(Arc::clone(&self.session), album_id)?;
+            Ok(album)
+        })
+    }
+
+    /// Returns a reference to the main `Artist` associated with this track.
+    /// 
+    /// This `Artist` is then cached within `self`, along with the rest of the track's artist credits.
+    pub fn get_artist(&self) -> Result<&Artist, String> {
+        self.get_artist_credits()?
+            .get_main()
+            .ok_or(String::from("Unable to parse artist relationship API response"))
+    }
+
+    /// Returns every artist credited on this track with their roles (main, featured, remixer, etc.).
+    /// 
+    /// These `ArtistCredits` are then cached within `self`.
+    pub fn get_artist_credits(&self) -> Result<&ArtistCredits, String> {
+        self.artist_credits.get_or_try_init(|| -> Result<ArtistCredits, String> {
+            let artist_relationships_endpoint = format!("/tracks/{}/relationships/artists?include=artists", self.id);
+            let json = self.session.get(&artist_relationships_endpoint)?;
+
+            let mut credits = vec![];
+            for (artist_id, role, attributes) in ArtistAttributes::parse_credits(&json)? {
+                // Artists are usually included in the response, but are fetched on their own if they weren't.
+                let artist = match attributes {
+                    Some(attributes) => Artist::from_attributes(Arc::clone(&self.session), artist_id, attributes),
+                    None => Artist::new(Arc::clone(&self.session), artist_id)?,
+                };
+                credits.push(ArtistCredit { artist, role });
+            }
+
+            Ok(ArtistCredits::new(credits))
+        })
+    }
+
+    /// Returns a reference to the `TrackManifest` associated with this track.
+    /// 
+    /// If `prefetch = true`, `TrackManifest` is cached within `self`,
+    /// but Tidal will not count this request as a stream/play.
+    /// 
+    /// If `prefetch = false`, the request is not cached, and Tidal
+    /// will count it as a stream/play.
+    pub fn get_manifest(&self, prefetch: bool) -> Result<TrackManifest, String> {
+        if !prefetch {
+            return self._get_new_manifest_with_fallback(prefetch);
+        }
+
+        let mut cached_manifest = self.cached_manifest.lock().map_err(|e| format!("{e:#?}"))?;
+        let quality = self.session.get_audio_quality();
+
+        let is_missing = cached_manifest.is_none();
+        let is_stale = cached_manifest.as_ref().is_some_and(|m| {
+            m.quality != quality || m.expires_at <= Utc::now().timestamp()
+        });
+
+        if is_missing || is_stale {
+            let manifest = self._get_new_manifest_with_fallback(prefetch)?;
+            let Some(expires_at) = manifest.get_cache_expires_at()? else {
+                *cached_manifest = None;
+                return Ok(manifest);
+            };
+
+            *cached_manifest = Some(CachedTrackManifest { manifest, quality, expires_at });
+        }
+
+        Ok(cached_manifest.as_ref().unwrap().manifest.clone())
+    }
+
+    /// Fetches a new manifest at this track's playback quality, falling back to lower qualities
+    /// if Tidal refuses it (e.g. because the track isn't available in that quality in the user's country).
+    ///
+    /// The quality that is actually streamed can be checked with `TrackManifest::get_quality`.
+    fn _get_new_manifest_with_fallback(&self, prefetch: bool) -> Result<TrackManifest, String> {
+        let mut quality = self.get_playback_quality();
+
+        loop {
+            match self._get_new_manifest(prefetch, quality) {
+                Ok(manifest) => {
+                    self.is_drm_protected.store(manifest.is_drm_protected(), Ordering::Relaxed);
+                    return Ok(manifest);
+                },
+                // Lower qualities won't help if Tidal couldn't be reached at all.
+                Err(e) if !self.session.is_online() => return Err(e),
+                Err(e) => match quality.lower() {
+                    Some(lower_quality) => quality = lower_quality,
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+
+    fn _get_new_manifest(&self, prefetch: bool, quality: AudioQuality) -> Result<TrackManifest, String> {
+        let mut endpoint = format!(
+            "/trackManifests/{}?manifestType=MPEG_DASH&uriScheme=DATA&usage=PLAYBACK&adaptive=false",
+            self.id
+        );
+
+        if quality >= AudioQuality::Low96 {
+            endpoint.push_str("&formats=HEAACV1");
+        }
+        if quality >= AudioQuality::Low320 {
+            endpoint.push_str("&formats=AACLC");
+        }
+        if quality >= AudioQuality::High {
+            endpoint.push_str("&formats=FLAC");
+        }
+        if quality >= AudioQuality::Max {
+            endpoint.push_str("&formats=FLAC_HIRES");
+        }
+
+        let json = if prefetch {
+            let playback_session_id = Uuid::new_v4().to_string();
+
+            let headers = vec![
+                ("x-playback-session-id", playback_session_id.as_str())
+            ];
+
+            self.session.get_with_headers(&endpoint, headers)?
+        } else {
+            self.session.get(&endpoint)?
+        };
+
+        TrackManifest::from_response(json)
+    }
+
+    /// Returns the id of the first resource in a `/tracks/{id}/relationships/*` API response.
+    /// 
+    /// For now, we assume that there is only one album/artist associated with a track.
+    pub(crate) fn parse_relationship_id(json: &JSONValue) -> Option<String> {
+        json["data"]
+            .as_array()?
+            .first()?
+            ["id"]
+            .as_str()
+            .map(|id| id.to_string())
+    }
+
+    /// Returns the quality this track is played at, which is the session's audio quality setting
+    /// capped to the highest quality this track is available in.
+    pub fn get_playback_quality(&self) -> AudioQuality {
+        let quality = self.session.get_audio_quality();
+
+        match self.get_attribtues() {
+            Ok(attributes) => {
+                let max_quality = MediaTag::get_max_quality(&attributes.media_tags);
+                if quality > max_quality { max_quality } else { quality }
+            },
+            Err(_) => quality,
+        }
+    }
+
+    /// Returns true if the last manifest fetched for this track was DRM protected, which means it can't be played.
+    ///
+    /// This is only known once a manifest has been fetched, e.g. when the track is prefetched as the next in the queue.
+    pub fn is_drm_protected(&self) -> bool {
+        self.is_drm_protected.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if a manifest at the current audio quality is cached and hasn't expired,
+    /// so that `get_manifest(true)` returns it without sending a request.
+    pub fn has_cached_manifest(&self) -> bool {
+        let quality = self.session.get_audio_quality();
+
+        self.cached_manifest.lock().is_ok_and(|cached_manifest| {
+            cached_manifest.as_ref().is_some_and(|m| m.quality == quality && m.expires_at > Utc::now().timestamp())
+        })
+    }
+
+    /// Forgets the cached manifest, so that the next call to `get_manifest` fetches a new one.
+    ///
+    /// This is needed when Tidal revokes the segment urls in a manifest before it expires.
+    pub fn clear_cached_manifest(&self) {
+        if let Ok(mut cached_manifest) = self.cached_manifest.lock() {
+            *cached_manifest = None;
+        }
+    }
+
+    /// Returns true if this Track already contains its attributes, album, and artist information.
+    pub fn has_info(&self) -> bool {
+        self.attributes.get().is_some() && self.album.get().is_some() && self.artist_credits.get().is_some()
+    }
+
+    /// Fetches this track's attributes, album, and artist information.
+    /// 
+    /// Every part is attempted even if an earlier one fails, so that whatever could be fetched
+    /// is still available through the `get_cached_*` methods. The first error is returned,
+    /// and kept until the next call (see `get_info_error`).
+    pub fn fetch_info(&self) -> Result<(), String> {
+        let results = [
+            self.get_attribtues().map(|_| ()),
+            self.get_artist().map(|_| ()),
+            self.get_album().map(|_| ()),
+        ];
+        let result = results.into_iter().collect::<Result<(), String>>();
+
+        *self.info_error.lock().map_err(|e| format!("{e:#?}"))? = result.clone().err();
+
+        result
+    }
+
+    /// Returns the error from the last call to `fetch_info`, if it failed.
+    pub fn get_info_error(&self) -> Option<String> {
+        self.info_error.lock().ok()?.clone()
+    }
+
+    /// Returns the `TrackAttributes` of this track if they have already been fetched.
+    pub fn get_cached_attributes(&self) -> Option<&TrackAttributes> {
+        self.attributes.get()
+    }
+
+    /// Returns the `Album` of this track if it has already been fetched.
+    pub fn get_cached_album(&self) -> Option<&Album> {
+        self.album.get()
+    }
+
+    /// Returns the main `Artist` of this track if it has already been fetched.
+    pub fn get_cached_artist(&self) -> Option<&Artist> {
+        self.artist_credits.get()?.get_main()
+    }
+
+    /// Returns the `ArtistCredits` of this track if they have already been fetched.
+    pub fn get_cached_artist_credits(&self) -> Option<&ArtistCredits> {
+        self.artist_credits.get()
+    }
+
+    /// Returns when this track was added to the user's collection, which is only known for tracks from the collection.
+    pub fn get_added_at(&self) -> Option<DateTime<Utc>> {
+        self.added_at
+    }
+
+    /// Returns a `Duration` corresponding this `Track`'s duration attribute.
+    pub fn get_duration(&self) -> Result<&Duration, String> {
+        self.duration.get_or_try_init(|| -> Result<Duration, String> {
+            let re = Regex::new(r"^PT((?<hours>\d+)H)*((?<mins>\d+)M)*((?<secs>\d+)S)*$")
+                .map_err(|e| format!("{}", e.to_string()))?;
+            let Some(captures) = re.captures(&self.get_attribtues()?.duration) else {
+                return Ok(Duration::from_secs(0));
+            };
+
+            let hours: u64 = match captures.name("hours") {
+                None => 0,
+                Some(hours_match) => hours_match.as_str().parse().unwrap_or(0),
+            };
+            let mins: u64 = match captures.name("mins") {
+                None => 0,
+                Some(mins_match) => mins_match.as_str().parse().unwrap_or(0),
+            };
+            let secs: u64 = match captures.name("secs") {
+                None => 0,
+                Some(secs_match) => secs_match.as_str().parse().unwrap_or(0),
+            };
+        
+            Ok(Duration::from_secs((hours * 60 * 60) + (mins * 60) + (secs)))
+        })
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl Track {
+    /// The most tracks fetched for a radio, which is as many as Tidal generates.
+    pub(crate) const MAX_RADIO_TRACKS: usize = 100;
+
+    /// Gets the url used for playback for this track.
+    ///
+    /// Like manifests, lower qualities are tried if Tidal refuses the track's playback quality.
+    pub fn get_url(&self) -> Result<String, String> {
+        let mut cache = self.url_cache.lock().map_err(|e| format!("{e:#?}"))?;
+        let quality = self.get_playback_quality();
+
+        if cache.as_ref().map(|(_, quality)| quality) != Some(&quality) {
+            let mut url_quality = quality;
+            let url = loop {
+                match self._get_new_url(url_quality) {
+                    Ok(url) => break url,
+                    Err(e) if !self.session.is_online() => return Err(e),
+                    Err(e) => match url_quality.lower() {
+                        Some(lower_quality) => url_quality = lower_quality,
+                        None => return Err(e),
+                    },
+                }
+            };
+
+            *cache = Some((url, quality));
+        }
+
+        Ok(cache.as_ref().unwrap().0.clone())
+    }
+
+    fn _get_new_url(&self, quality: AudioQuality) -> Result<String, String> {
+        let endpoint = format!(
+            "/tracks/{}/urlpostpaywall?audioquality={}&urlusagemode=STREAM&assetpresentation=FULL",
+            self.id,
+            quality.to_api_string(),
+        );
+        let json = self.session.get_unofficial(&endpoint)?;
+
+        Self::parse_url_response(&json)
+            .ok_or(format!("Unable to get track url for track id {}", self.id))
+    }
+
+    /// Fetches the tracks of this track's radio, which Tidal generates from tracks like it.
+    pub fn get_radio(&self) -> Result<Vec<Track>, String> {
+        let endpoint = format!("/tracks/{}/radio?limit={}", self.id, Self::MAX_RADIO_TRACKS);
+        let json = self.session.get_unofficial(&endpoint)?;
+
+        Self::parse_radio_response(&json)?
+            .into_iter()
+            .map(|(id, attributes)| Ok(Track::new(Arc::clone(&self.session), id)?.with_attributes(attributes)))
+            .collect()
+    }
+
+    /// Fetches this track's lyrics, which are timed for many tracks.
+    pub fn get_lyrics(&self) -> Result<Lyrics, String> {
+        let endpoint = format!("/tracks/{}/lyrics", self.id);
+        let json = self.session.get_unofficial(&endpoint)?;
+
+        Lyrics::parse_response(&json)
+    }
+
+    /// Parses a `/tracks/{id}/radio` or `/artists/{id}/radio` unofficial API response into the id and attributes
+    /// of each of the radio's tracks.
+    pub(crate) fn parse_radio_response(json: &JSONValue) -> Result<Vec<(String, TrackAttributes)>, String> {
+        let items = json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get radio tracks"))?;
+
+        Ok(items.iter()
+            .filter_map(Self::parse_unofficial)
+            .collect())
+    }
+
+    /// Parses a track as it is listed in unofficial API responses.
+    ///
+    /// Returns its id and attributes, or `None` if it has no id.
+    pub(crate) fn parse_unofficial(json: &JSONValue) -> Option<(String, TrackAttributes)> {
+        let id = json["id"].as_u64()?.to_string();
+
+        // The unofficial API gives the duration in seconds, which is written the way the official API does.
+        let attributes = TrackAttributes {
+            title: json["title"].as_str().unwrap_or_default().to_string(),
+            version: json["version"].as_str().filter(|version| !version.is_empty()).map(String::from),
+            isrc: json["isrc"].as_str().unwrap_or_default().to_string(),
+            duration: json["duration"].as_u64().map(|secs| format!("PT{secs}S")).unwrap_or_default(),
+            copyright: HashMap::new(),
+            explicit: json["explicit"].as_bool().unwrap_or_default(),
+            popularity: json["popularity"].as_f64().unwrap_or_default() as f32,
+            availability: vec![],
+            media_tags: serde_json::from_value(json["mediaMetadata"]["tags"].clone()).unwrap_or_default(),
+        };
+
+        Some((id, attributes))
+    }
+
+    /// Returns the first playback url in a `/tracks/{id}/urlpostpaywall` API response.
+    pub(crate) fn parse_url_response(json: &JSONValue) -> Option<String> {
+        json["urls"][0]
+            .as_str()
+            .map(|url| url.to_string())
+    }
+}
+
+impl TrackAttributes {
+    /// Returns this track's title followed by its version in parentheses (e.g. "Song (Remastered 2011)"),
+    /// so that different versions of a track can be told apart.
+    pub fn get_full_title(&self) -> String {
+        match self.version.as_deref().map(str::trim) {
+            Some(version) if !version.is_empty() => format!("{} ({})", self.title, version),
+            _ => self.title.clone(),
+        }
+    }
+
+    /// Returns the work this track is part of and its movement, for classical tracks titled like
+    /// "Symphony No. 5 in C Minor, Op. 67: I. Allegro con brio".
+    ///
+    /// Tracks without a movement in their title are returned whole as the work.
+    pub fn get_work_and_movement(&self) -> (String, Option<String>) {
+        let full_title = self.get_full_title();
+
+        match full_title.split_once(": ") {
+            Some((work, movement)) if !work.trim().is_empty() && !movement.trim().is_empty() => {
+                (work.trim().to_string(), Some(movement.trim().to_string()))
+            },
+            _ => (full_title, None),
+        }
+    }
+
+    /// Parses a `TrackAttributes` from a `/tracks/{id}` API response.
+    pub(crate) fn from_response(mut json: JSONValue) -> Result<Self, String> {
+        let attributes_json = json["data"]["attributes"].take();
+
+        serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse track API response: {}", e.to_string()))
+    }
+}
+
+impl TrackManifest {
+    /// Parses a `TrackManifest` from a `/trackManifests/{id}` API response.
+    /// 
+    /// The manifest's `uri` is a base64 data URI, which is decoded into the DASH manifest XML.
+    pub(crate) fn from_response(mut json: JSONValue) -> Result<Self, String> {
+        let attributes_json = json["data"]["attributes"].take();
+
+        let mut manifest: TrackManifest = serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse track manifest API response: {}", e.to_string()))?;
+
+        let (_, encoded_xml) = manifest.uri.split_once(",")
+            .ok_or("Unable to parse manifest XML")?;
+        let decoded_xml = BASE64.decode(encoded_xml)
+            .map_err(|e| format!("Unable to parse manifest XML: {}", e.to_string()))?;
+        manifest.uri = String::from_utf8(decoded_xml)
+            .map_err(|e| format!("Unable to parse manifest XML: {}", e.to_string()))?;
+        
+        Ok(manifest)
+    }
+
+    /// Returns true if the stream is encrypted, either because Tidal sent DRM data with the manifest
+    /// or because the manifest itself declares content protection.
+    pub fn is_drm_protected(&self) -> bool {
+        self.drm_data.is_some() || self.uri.contains("<ContentProtection")
+    }
+
+    /// Returns the quality that is actually streamed with this manifest, which is the best of its formats.
+    ///
+    /// This can be lower than the quality asked for, if the track isn't available in it.
+    pub fn get_quality(&self) -> Option<AudioQuality> {
+        self.formats
+            .iter()
+            .filter_map(|format| AudioQuality::from_manifest_format(format))
+            .reduce(|best, quality| if quality > best { quality } else { best })
+    }
+
+    /// Returns when this manifest stops being cached, or `None` if it isn't cached at all.
+    ///
+    /// Encrypted manifests can't be played, so they aren't cached, and their segment urls may not have an expiry to parse.
+    pub(crate) fn get_cache_expires_at(&self) -> Result<Option<i64>, String> {
+        if self.is_drm_protected() {
+            return Ok(None);
+        }
+
+        self.get_expires_at().map(Some)
+    }
+
+    /// Returns the unix timestamp at which the segment urls in this manifest expire.
+    pub(crate) fn get_expires_at(&self) -> Result<i64, String> {
+        self.uri
+            .split("token=")
+            .nth(1)
+            .ok_or("Manifest URI has no expires_at")?
+            .split('~')
+            .next()
+            .ok_or("Manifest URI has no expires_at")?
+            .parse::<i64>()
+            .map_err(|e| format!("Unable to parse track manifest expires_at: {}", e.to_string()))
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    sync::Arc,
+};
+
+#[cfg(feature = "unofficial")]
+use chrono::{
+    DateTime,
+    Utc,
+};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::{
+    json,
+    Value as JSONValue,
+};
+
+use super::{
+    Album,
+    Artist,
+    AudioQuality,
+    Playlist,
+    Session,
+    Track,
+};
+
+/// A Tidal user.
+#[cfg_attr(not(feature = "unofficial"), allow(dead_code))]
+#[derive(Debug)]
+pub struct User {
+    session: Arc<Session>,
+    pub id: String,
+    pub attributes: UserAttributes,
+
+    // The following fields are used to cache API results.
+    collection_tracks: OnceCell<Vec<Track>>,
+    collection_albums: OnceCell<Vec<Album>>,
+    collection_artists: OnceCell<Vec<Artist>>,
+    subscription: OnceCell<Subscription>,
+    playlists: OnceCell<Vec<Playlist>>,
+}
+
+/// Another Tidal user, whose public profile and playlists can be browsed.
+#[cfg_attr(not(feature = "unofficial"), allow(dead_code))]
+#[derive(Debug)]
+pub struct PublicUser {
+    session: Arc<Session>,
+    pub id: String,
+    pub name: String,
+
+    // The following fields are used to cache API results.
+    playlists: OnceCell<Vec<Playlist>>,
+}
+
+/// An user's API attributes.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserAttributes {
+    pub username: String,
+    pub country: String,
+    pub email: String,
+    pub email_verified: bool,
+}
+
+/// A user's Tidal subscription.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Subscription {
+    /// The subscription's type as written by Tidal (e.g. "HIFI" or "HIFI_PLUS").
+    pub tier: String,
+    /// The subscription's status as written by Tidal (e.g. "ACTIVE").
+    pub status: String,
+    /// The highest audio quality this subscription can stream.
+    pub highest_quality: AudioQuality,
+}
+
+impl Subscription {
+    /// Returns the name of this subscription's tier similar to how it is written in Tidal (e.g. "HiFi Plus").
+    pub fn get_tier_name(&self) -> String {
+        match self.tier.as_str() {
+            "FREE" => String::from("Free"),
+            "HIFI" => String::from("HiFi"),
+            "HIFI_PLUS" | "PREMIUM_PLUS" => String::from("HiFi Plus"),
+            "PREMIUM" => String::from("Premium"),
+            tier => tier.to_string(),
+        }
+    }
+}
+
+impl User {
+    /// Number of tracks added to or removed from the collection per request.
+    const FAVORITE_TRACKS_BATCH_SIZE: usize = 20;
+
+    /// Gets the currently logged in user from a session.
+    pub fn get_current_user(session: Arc<Session>) -> Result<Self, String> {
+        let endpoint = "/users/me";
+        let json = session.get(&endpoint)?;
+
+        let (id, attributes) = Self::parse_response(json)?;
+
+        Ok(Self {
+            session,
+            id,
+            attributes,
+            collection_tracks: OnceCell::new(),
+            collection_albums: OnceCell::new(),
+            collection_artists: OnceCell::new(),
+            subscription: OnceCell::new(),
+            playlists: OnceCell::new(),
+        })
+    }
+
+    /// Adds the tracks with ids `track_ids` to the user's collection.
+    ///
+    /// Tracks already fetched with `get_collection_tracks` aren't updated.
+    pub fn add_favorite_tracks(&self, track_ids: &[String]) -> Result<(), String> {
+        let endpoint = format!("/userCollections/{}/relationships/tracks", self.id);
+
+        for batch in track_ids.chunks(Self::FAVORITE_TRACKS_BATCH_SIZE) {
+            self.session.post(&endpoint, &Self::favorite_tracks_body(batch))?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the tracks with ids `track_ids` from the user's collection.
+    ///
+    /// Tracks already fetched with `get_collection_tracks` aren't updated.
+    pub fn remove_favorite_tracks(&self, track_ids: &[String]) -> Result<(), String> {
+        let endpoint = format!("/userCollections/{}/relationships/tracks", self.id);
+
+        for batch in track_ids.chunks(Self::FAVORITE_TRACKS_BATCH_SIZE) {
+            self.session.delete(&endpoint, &Self::favorite_tracks_body(batch))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the JSON:API body listing the tracks with ids `track_ids`, for adding them to or removing them from the collection.
+    fn favorite_tracks_body(track_ids: &[String]) -> JSONValue {
+        let data: Vec<JSONValue> = track_ids
+            .iter()
+            .map(|id| json!({ "id": id, "type": "tracks" }))
+            .collect();
+
+        json!({ "data": data })
+    }
+
+    /// Returns the playlists this user has made if they have already been fetched.
+    ///
+    /// They can only be fetched with the unofficial API, so this is always `None` without it.
+    pub fn get_cached_playlists(&self) -> Option<&Vec<Playlist>> {
+        self.playlists.get()
+    }
+
+    /// Returns the user's subscription if it has already been fetched.
+    ///
+    /// It can only be fetched with the unofficial API, so this is always `None` without it.
+    pub fn get_cached_subscription(&self) -> Option<&Subscription> {
+        self.subscription.get()
+    }
+
+    /// Parses the id and attributes from a `/users/me` API response.
+    pub(crate) fn parse_response(mut json: JSONValue) -> Result<(String, UserAttributes), String> {
+        let mut data_json = json["data"].take();
+
+        let id = data_json["id"].as_str()
+            .ok_or(String::from("Unable to get current user"))?
+            .to_string();
+
+        let attributes_json = data_json["attributes"].take();
+        let attributes: UserAttributes = serde_json::from_value(attributes_json)
+            .map_err(|e| format!("Unable to parse user API response: {}", e.to_string()))?;
+
+        Ok((id, attributes))
+    }
+}
+
+/// The id of a track in the user's collection, and when it was added.
+#[cfg(feature = "unofficial")]
+type CollectionItem = (String, Option<DateTime<Utc>>);
+
+#[cfg(feature = "unofficial")]
+impl User {
+    /// Number of collection tracks fetched per request.
+    const COLLECTION_TRACKS_PAGE_SIZE: usize = 1000;
+
+    /// Returns a list of tracks in the user's collection.
+    pub fn get_collection_tracks(&self) -> Result<&Vec<Track>, String> {
+        self.get_collection_tracks_with_progress(|_, _| {})
+    }
+
+    /// Returns a list of tracks in the user's collection, calling `on_progress` with the number of
+    /// tracks fetched so far and the total number of tracks after each page is fetched.
+    /// 
+    /// This list is then cached within `self`, so `on_progress` is only called the first time.
+    pub fn get_collection_tracks_with_progress<F>(&self, mut on_progress: F) -> Result<&Vec<Track>, String>
+    where
+        F: FnMut(usize, usize),
+    {
+        self.collection_tracks.get_or_try_init(|| -> Result<Vec<Track>, String> {
+            let mut collection_tracks: Vec<Track> = vec![];
+
+            loop {
+                let endpoint = format!(
+                    "/users/{}/favorites/tracks?limit={}&offset={}",
+                    self.id,
+                    Self::COLLECTION_TRACKS_PAGE_SIZE,
+                    collection_tracks.len(),
+                );
+                let res_json = self.session.get_unofficial(&endpoint)?;
+
+                let (items, total) = Self::parse_collection_tracks_response(&res_json)?;
+                let is_last_page = items.is_empty();
+
+                collection_tracks.reserve(total.saturating_sub(collection_tracks.len()));
+                for (track_id, added_at) in items {
+                    let track = Track::new(Arc::clone(&self.session), track_id)?
+                        .with_added_at(added_at);
+                    collection_tracks.push(track);
+                }
+
+                on_progress(collection_tracks.len(), total);
+
+                if is_last_page || collection_tracks.len() >= total {
+                    break;
+                }
+            }
+
+            Ok(collection_tracks)
+        })
+    }
+
+    /// Returns the albums in the user's collection, most recently added first.
+    /// 
+    /// This list is then cached within `self`.
+    pub fn get_collection_albums(&self) -> Result<&Vec<Album>, String> {
+        const PAGE_SIZE: usize = 100;
+
+        self.collection_albums.get_or_try_init(|| -> Result<Vec<Album>, String> {
+            let mut collection_albums: Vec<Album> = vec![];
+
+            loop {
+                let endpoint = format!(
+                    "/users/{}/favorites/albums?limit={}&offset={}&order=DATE&orderDirection=DESC",
+                    self.id,
+                    PAGE_SIZE,
+                    collection_albums.len(),
+                );
+                let res_json = self.session.get_unofficial(&endpoint)?;
+
+                let (page, total) = Album::parse_unofficial_page(&res_json)?;
+                let is_last_page = page.is_empty();
+
+                for (id, attributes) in page {
+                    collection_albums.push(Album::from_attributes(Arc::clone(&self.session), id, attributes));
+                }
+
+                if is_last_page || collection_albums.len() >= total {
+                    break;
+                }
+            }
+
+            Ok(collection_albums)
+        })
+    }
+
+    /// Returns the artists the user follows, most recently followed first.
+    /// 
+    /// This list is then cached within `self`.
+    pub fn get_collection_artists(&self) -> Result<&Vec<Artist>, String> {
+        const PAGE_SIZE: usize = 100;
+
+        self.collection_artists.get_or_try_init(|| -> Result<Vec<Artist>, String> {
+            let mut collection_artists: Vec<Artist> = vec![];
+
+            loop {
+                let endpoint = format!(
+                    "/users/{}/favorites/artists?limit={}&offset={}&order=DATE&orderDirection=DESC",
+                    self.id,
+                    PAGE_SIZE,
+                    collection_artists.len(),
+                );
+                let res_json = self.session.get_unofficial(&endpoint)?;
+
+                let (page, total) = Artist::parse_unofficial_page(&res_json)?;
+                let is_last_page = page.is_empty();
+
+                for (id, attributes) in page {
+                    collection_artists.push(Artist::from_attributes(Arc::clone(&self.session), id, attributes));
+                }
+
+                if is_last_page || collection_artists.len() >= total {
+                    break;
+                }
+            }
+
+            Ok(collection_artists)
+        })
+    }
+
+    /// Returns the playlists this user has made.
+    /// 
+    /// This list is then cached within `self`.
+    pub fn get_playlists(&self) -> Result<&Vec<Playlist>, String> {
+        self.playlists.get_or_try_init(|| fetch_playlists(&self.session, &self.id))
+    }
+
+    /// Returns the user's subscription.
+    /// 
+    /// This subscription is then cached within `self`.
+    pub fn get_subscription(&self) -> Result<&Subscription, String> {
+        self.subscription.get_or_try_init(|| -> Result<Subscription, String> {
+            let endpoint = format!("/users/{}/subscription", self.id);
+            let res_json = self.session.get_unofficial(&endpoint)?;
+
+            Self::parse_subscription_response(&res_json)
+        })
+    }
+
+    /// Parses a `/users/{id}/subscription` unofficial API response.
+    /// 
+    /// If the highest sound quality is missing or unknown, it is assumed to be `AudioQuality::Max`
+    /// so that no quality options are hidden by mistake.
+    pub(crate) fn parse_subscription_response(res_json: &JSONValue) -> Result<Subscription, String> {
+        let tier = res_json["subscription"]["type"]
+            .as_str()
+            .ok_or(String::from("Unable to parse subscription API response"))?
+            .to_string();
+        let status = res_json["status"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let highest_quality = res_json["highestSoundQuality"]
+            .as_str()
+            .and_then(AudioQuality::from_api_string)
+            .unwrap_or(AudioQuality::Max);
+
+        Ok(Subscription {
+            tier,
+            status,
+            highest_quality,
+        })
+    }
+
+    /// Parses a page of a `/users/{id}/favorites/tracks` unofficial API response.
+    /// 
+    /// Returns the track ids on this page along with when each was added, and the total number of tracks in the collection.
+    pub(crate) fn parse_collection_tracks_response(res_json: &JSONValue) -> Result<(Vec<CollectionItem>, usize), String> {
+        let total = res_json["totalNumberOfItems"]
+            .as_u64()
+            .ok_or(String::from("Unable to get collection tracks"))? as usize;
+
+        let items_array = res_json["items"]
+            .as_array()
+            .ok_or(String::from("Unable to get collection tracks"))?;
+
+        let mut items: Vec<CollectionItem> = Vec::with_capacity(items_array.len());
+
+        for json in items_array {
+            let track_id = json["item"]["id"]
+                .as_u64()
+                .ok_or(String::from("Unable to get collection tracks"))?
+                .to_string();
+
+            // A missing or unexpected date shouldn't stop the collection from loading.
+            let added_at = json["created"]
+                .as_str()
+                .and_then(|created| DateTime::parse_from_str(created, "%Y-%m-%dT%H:%M:%S%.f%z").ok())
+                .map(|created| created.with_timezone(&Utc));
+
+            items.push((track_id, added_at));
+        }
+
+        Ok((items, total))
+    }
+}
+
+#[cfg(feature = "unofficial")]
+impl PublicUser {
+    /// Returns the public profile of the user with id `id`.
+    pub fn new(session: Arc<Session>, id: String) -> Result<Self, String> {
+        let endpoint = format!("/users/{}", id);
+        let res_json = session.get_unofficial(&endpoint)?;
+
+        let name = Self::parse_response(&res_json, &id);
+
+        Ok(Self {
+            session,
+            id,
+            name,
+            playlists: OnceCell::new(),
+        })
+    }
+
+    /// Parses the user's display name from a `/users/{id}` unofficial API response.
+    /// 
+    /// Users don't have to set their name, so this falls back to their username, and then their id.
+    pub(crate) fn parse_response(res_json: &JSONValue, id: &str) -> String {
+        let first_name = res_json["firstName"].as_str().unwrap_or_default();
+        let last_name = res_json["lastName"].as_str().unwrap_or_default();
+        let full_name = format!("{first_name} {last_name}").trim().to_string();
+
+        if !full_name.is_empty() {
+            return full_name;
+        }
+
+        match res_json["username"].as_str() {
+            Some(username) if !username.is_empty() => username.to_string(),
+            _ => format!("User {id}"),
+        }
+    }
+
+    /// Returns the public playlists this user has made.
+    /// 
+    /// This list is then cached within `self`.
+    pub fn get_playlists(&self) -> Result<&Vec<Playlist>, String> {
+        self.playlists.get_or_try_init(|| fetch_playlists(&self.session, &self.id))
+    }
+}
+
+/// Fetches every playlist made by the user with id `user_id`, following the pagination.
+/// 
+/// Only public playlists are returned when `user_id` isn't the logged in user.
+#[cfg(feature = "unofficial")]
+fn fetch_playlists(session: &Arc<Session>, user_id: &str) -> Result<Vec<Playlist>, String> {
+    const PAGE_SIZE: usize = 50;
+
+    let mut playlists: Vec<Playlist> = vec![];
+
+    loop {
+        let endpoint = format!("/users/{}/playlists?limit={}&offset={}", user_id, PAGE_SIZE, playlists.len());
+        let res_json = session.get_unofficial(&endpoint)?;
+
+        let (page, total) = Playlist::parse_unofficial_page(&res_json)?;
+        let is_last_page = page.is_empty();
+
+        for (id, attributes) in page {
+            playlists.push(Playlist::from_attributes(Arc::clone(session), id, attributes));
+        }
+
+        if is_last_page || playlists.len() >= total {
+            break;
+        }
+    }
+
+    Ok(playlists)
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use ratatui::{
+    buffer::Buffer,
+    style::Color,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    cover_cache::CoverCache,
+    AppEvent,
+};
+
+/// An accent color taken from the cover of the playing track's album, drawn in place of cyan throughout the UI.
+pub struct CoverAccent {
+    covers: CoverCache,
+    /// The url of the cover the accent color was last taken from.
+    url: Option<String>,
+    /// The accent color taken from that cover, or `None` if no color on it stands out.
+    color: Option<Color>,
+}
+
+impl CoverAccent {
+    /// Brightness the accent color is raised to if it is darker, so that it can be read on dark backgrounds.
+    const MIN_BRIGHTNESS: u8 = 160;
+
+    /// Returns an accent color taken from covers fetched through `covers`.
+    pub fn new(covers: CoverCache) -> Self {
+        Self {
+            covers,
+            url: None,
+            color: None,
+        }
+    }
+
+    /// Returns the accent color for the cover at `url` (that of the playing track's album),
+    /// or `None` if nothing is playing or the cover has no color that stands out.
+    ///
+    /// The cover is fetched if it hasn't been, and a rerender is sent through `tx` once it has.
+    /// Until then, the accent color of the last cover is kept, so that it doesn't flash back to cyan between tracks.
+    pub fn get(&mut self, url: Option<&str>, tx: &mpsc::Sender<AppEvent>) -> Option<Color> {
+        let Some(url) = url else {
+            self.url = None;
+            self.color = None;
+            return None;
+        };
+
+        if self.url.as_deref() != Some(url) && let Some(image) = self.covers.get(url, tx) {
+            self.url = Some(url.to_string());
+            self.color = get_dominant_color(&image);
+        }
+
+        self.color
+    }
+}
+
+/// Draws everything in `buf` that is cyan in `accent` instead.
+pub fn apply_accent(buf: &mut Buffer, accent: Color) {
+    for cell in &mut buf.content {
+        if cell.fg == Color::Cyan {
+            cell.fg = accent;
+        }
+        if cell.bg == Color::Cyan {
+            cell.bg = accent;
+        }
+    }
+}
+
+/// Returns the most common colorful color in the encoded `image`, or `None` if it can't be decoded or is mostly gray.
+#[cfg(feature = "images")]
+fn get_dominant_color(image: &[u8]) -> Option<Color> {
+    let image = image::load_from_memory(image).ok()?
+        .thumbnail(32, 32)
+        .to_rgb8();
+
+    pick_dominant_color(image.pixels().map(|pixel| pixel.0))
+}
+
+/// Covers can only be decoded with the images feature, so without it no color stands out.
+#[cfg(not(feature = "images"))]
+fn get_dominant_color(_image: &[u8]) -> Option<Color> {
+    None
+}
+
+/// Returns the most common color among `pixels`, leaving out grays and colors too dark to tell apart,
+/// brightened to at least `CoverAccent::MIN_BRIGHTNESS`.
+///
+/// Returns `None` if less than a twentieth of the pixels are colorful enough.
+#[cfg_attr(not(feature = "images"), allow(dead_code))]
+fn pick_dominant_color(pixels: impl Iterator<Item = [u8; 3]>) -> Option<Color> {
+    // Similar colors are counted together, by the top 3 bits of each channel.
+    let mut buckets: HashMap<[u8; 3], (usize, [usize; 3])> = HashMap::new();
+    let mut num_pixels = 0;
+
+    for pixel in pixels {
+        num_pixels += 1;
+
+        let max = *pixel.iter().max().unwrap_or(&0);
+        let min = *pixel.iter().min().unwrap_or(&0);
+        let is_colorful = max >= 40 && (max - min) as f32 / max as f32 >= 0.25;
+        if !is_colorful {
+            continue;
+        }
+
+        let (count, sums) = buckets.entry(pixel.map(|channel| channel >> 5)).or_default();
+        *count += 1;
+        for (sum, channel) in sums.iter_mut().zip(pixel) {
+            *sum += channel as usize;
+        }
+    }
+
+    let (count, sums) = buckets.into_values().max_by_key(|(count, _)| *count)?;
+    if count * 20 < num_pixels {
+        return None;
+    }
+
+    let average = sums.map(|sum| sum / count);
+    let max = *average.iter().max().unwrap_or(&0);
+    let scale = (CoverAccent::MIN_BRIGHTNESS as f32 / max as f32).max(1.0);
+    let [r, g, b] = average.map(|channel| (channel as f32 * scale).round().min(255.0) as u8);
+
+    Some(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_most_common_colorful_color() {
+        let pixels = [[200, 30, 30]; 10].into_iter()
+            .chain([[30, 30, 200]; 20])
+            // Grays and near blacks are left out, however common they are.
+            .chain([[128, 128, 128]; 50])
+            .chain([[10, 0, 20]; 50]);
+
+        assert_eq!(pick_dominant_color(pixels), Some(Color::Rgb(30, 30, 200)));
+    }
+
+    #[test]
+    fn brightens_dark_colors() {
+        let pixels = [[80, 20, 20]; 10].into_iter();
+
+        assert_eq!(pick_dominant_color(pixels), Some(Color::Rgb(160, 40, 40)));
+    }
+
+    #[test]
+    fn ignores_mostly_gray_covers() {
+        let pixels = [[200, 30, 30]; 1].into_iter().chain([[240, 240, 240]; 99]);
+
+        assert_eq!(pick_dominant_color(pixels), None);
+    }
+}
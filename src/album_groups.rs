@@ -0,0 +1,172 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+/// A row of My Collection - Tracks while its tracks are grouped by album.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GroupedRow {
+    /// The header of the group at this index.
+    Album(usize),
+    /// The track at this index of the collection.
+    Track(usize),
+}
+
+/// The tracks of the collection that are on the same album.
+#[derive(Debug, Eq, PartialEq)]
+pub struct AlbumGroup {
+    /// The album's id, or `None` for the tracks whose album hasn't been fetched yet.
+    pub album_id: Option<String>,
+    /// The indices of the group's tracks in the collection, in the order they were added.
+    pub track_indices: Vec<usize>,
+}
+
+/// The collection's tracks grouped by album, each group under a header row that can be collapsed to hide its tracks.
+///
+/// Groups are listed in the order their first track was added, followed by the tracks whose album isn't known yet.
+#[derive(Debug, Default)]
+pub struct AlbumGroups {
+    groups: Vec<AlbumGroup>,
+    rows: Vec<GroupedRow>,
+    /// The albums whose groups are collapsed, by id.
+    collapsed: HashSet<Option<String>>,
+}
+
+impl AlbumGroups {
+    /// Groups the tracks of the collection, given the album id of each one (if it is known yet).
+    pub fn regroup(&mut self, album_ids: &[Option<&str>]) {
+        let mut groups: Vec<AlbumGroup> = vec![];
+        let mut group_indices: HashMap<&str, usize> = HashMap::new();
+        let mut unknown = vec![];
+
+        for (track_idx, album_id) in album_ids.iter().enumerate() {
+            let Some(album_id) = album_id else {
+                unknown.push(track_idx);
+                continue;
+            };
+
+            let group_idx = *group_indices.entry(album_id).or_insert_with(|| {
+                groups.push(AlbumGroup { album_id: Some(album_id.to_string()), track_indices: vec![] });
+                groups.len() - 1
+            });
+            groups[group_idx].track_indices.push(track_idx);
+        }
+
+        if !unknown.is_empty() {
+            groups.push(AlbumGroup { album_id: None, track_indices: unknown });
+        }
+
+        self.groups = groups;
+        self.rebuild_rows();
+    }
+
+    /// Lays out the rows from the groups, leaving out the tracks of collapsed groups.
+    fn rebuild_rows(&mut self) {
+        self.rows = self.groups.iter()
+            .enumerate()
+            .flat_map(|(group_idx, group)| {
+                let tracks = if self.collapsed.contains(&group.album_id) { &[][..] } else { &group.track_indices[..] };
+
+                std::iter::once(GroupedRow::Album(group_idx))
+                    .chain(tracks.iter().map(|track_idx| GroupedRow::Track(*track_idx)))
+            })
+            .collect();
+    }
+
+    /// Returns the rows of the table, as of the last regroup.
+    pub fn get_rows(&self) -> &[GroupedRow] {
+        &self.rows
+    }
+
+    /// Returns the group at `group_idx`.
+    pub fn get_group(&self, group_idx: usize) -> Option<&AlbumGroup> {
+        self.groups.get(group_idx)
+    }
+
+    /// Returns the index of the group that the row at `row` is the header of, or is one of the tracks of.
+    pub fn get_group_of_row(&self, row: usize) -> Option<usize> {
+        match self.rows.get(row)? {
+            GroupedRow::Album(group_idx) => Some(*group_idx),
+            GroupedRow::Track(track_idx) => self.groups.iter()
+                .position(|group| group.track_indices.contains(track_idx)),
+        }
+    }
+
+    /// Returns true if the tracks of the group at `group_idx` are hidden.
+    pub fn is_collapsed(&self, group_idx: usize) -> bool {
+        self.groups.get(group_idx).is_some_and(|group| self.collapsed.contains(&group.album_id))
+    }
+
+    /// Collapses the group at `group_idx` if it is expanded, or expands it if it is collapsed.
+    ///
+    /// Returns the row of the group's header, which is where the selection should go.
+    pub fn toggle_collapsed(&mut self, group_idx: usize) -> Option<usize> {
+        let album_id = &self.groups.get(group_idx)?.album_id;
+
+        if !self.collapsed.remove(album_id) {
+            self.collapsed.insert(album_id.clone());
+        }
+        self.rebuild_rows();
+
+        self.rows.iter().position(|row| *row == GroupedRow::Album(group_idx))
+    }
+
+    /// Returns the row of the track at `track_idx` in the collection, expanding its group if it is collapsed.
+    pub fn reveal_track(&mut self, track_idx: usize) -> Option<usize> {
+        let group = self.groups.iter().find(|group| group.track_indices.contains(&track_idx))?;
+
+        if self.collapsed.remove(&group.album_id) {
+            self.rebuild_rows();
+        }
+
+        self.rows.iter().position(|row| *row == GroupedRow::Track(track_idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grouped(album_ids: &[Option<&str>]) -> AlbumGroups {
+        let mut groups = AlbumGroups::default();
+        groups.regroup(album_ids);
+        groups
+    }
+
+    #[test]
+    fn groups_tracks_in_the_order_albums_were_first_added() {
+        let groups = grouped(&[Some("kid-a"), Some("ok-computer"), None, Some("kid-a")]);
+
+        assert_eq!(groups.get_rows(), [
+            GroupedRow::Album(0),
+            GroupedRow::Track(0),
+            GroupedRow::Track(3),
+            GroupedRow::Album(1),
+            GroupedRow::Track(1),
+            // Tracks whose album isn't known yet come last.
+            GroupedRow::Album(2),
+            GroupedRow::Track(2),
+        ]);
+        assert_eq!(groups.get_group(2).unwrap().album_id, None);
+        assert_eq!(groups.get_group_of_row(2), Some(0));
+        assert_eq!(groups.get_group_of_row(3), Some(1));
+    }
+
+    #[test]
+    fn collapses_groups_across_regroups() {
+        let mut groups = grouped(&[Some("kid-a"), Some("ok-computer"), Some("kid-a")]);
+
+        assert_eq!(groups.toggle_collapsed(0), Some(0));
+        assert!(groups.is_collapsed(0));
+        assert_eq!(groups.get_rows(), [GroupedRow::Album(0), GroupedRow::Album(1), GroupedRow::Track(1)]);
+
+        // Collapsed albums stay collapsed when tracks are added in front of them.
+        groups.regroup(&[Some("amnesiac"), Some("kid-a"), Some("ok-computer"), Some("kid-a")]);
+        assert!(groups.is_collapsed(1));
+        assert_eq!(groups.get_rows().len(), 5);
+
+        // Revealing a track in a collapsed group expands it.
+        assert_eq!(groups.reveal_track(3), Some(4));
+        assert!(!groups.is_collapsed(1));
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::OpenOptions,
+    io::{
+        self,
+        Write,
+    },
+};
+
+use rtidalapi::Track;
+
+use crate::i18n::{
+    tr_with,
+    Message,
+};
+
+/// What an announcement is about, which starts each announced line (e.g. `track: Airbag – Radiohead`).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Announcement {
+    /// The track that started playing.
+    Track,
+    /// Whether playback is playing or paused.
+    State,
+    /// The pane that was focused.
+    Focus,
+    /// The selected row of the focused table.
+    Row,
+    /// The selected item of the open menu.
+    Menu,
+    /// A message shown in the OSD.
+    Message,
+    /// The volume, after it was changed.
+    Volume,
+    /// The position in the track, after seeking.
+    Position,
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Track => "track",
+            Self::State => "state",
+            Self::Focus => "focus",
+            Self::Row => "row",
+            Self::Menu => "menu",
+            Self::Message => "message",
+            Self::Volume => "volume",
+            Self::Position => "position",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Writes what is happening in the app as plain text, one `<kind>: <text>` line at a time, for screen readers and braille displays.
+pub struct Announcer {
+    output: Box<dyn Write + Send>,
+    /// The last text announced of each kind, so that what hasn't changed isn't announced again.
+    last: HashMap<Announcement, String>,
+}
+
+impl Announcer {
+    /// Returns a new `Announcer` that appends to the file at `output_file`, or writes to standard error if it is `None`.
+    pub fn open(output_file: Option<&str>) -> Result<Self, String> {
+        let output: Box<dyn Write + Send> = match output_file {
+            Some(path) => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Unable to open {path}: {e}"))?
+            ),
+            None => Box::new(io::stderr()),
+        };
+
+        Ok(Self {
+            output,
+            last: HashMap::new(),
+        })
+    }
+
+    /// Announces `text`, even if it is the same as the last announcement of its kind.
+    pub fn announce(&mut self, kind: Announcement, text: &str) {
+        // Announcements are one line each, so that they can be read line by line.
+        let text = text.replace(['\n', '\r'], " ");
+
+        // Nothing else in the app depends on announcements being written, so failures are ignored.
+        let _ = writeln!(self.output, "{kind}: {text}");
+        let _ = self.output.flush();
+
+        self.last.insert(kind, text);
+    }
+
+    /// Announces `text` if it is different from the last announcement of its kind.
+    pub fn announce_change(&mut self, kind: Announcement, text: &str) {
+        if self.last.get(&kind).is_some_and(|last| last == text) {
+            return;
+        }
+
+        self.announce(kind, text);
+    }
+}
+
+/// Returns the title, artist, and album of `track` as one line (e.g. `Airbag – Radiohead – OK Computer`),
+/// leaving out whatever hasn't been fetched.
+pub fn describe_track(track: &Track) -> String {
+    let title = track.get_cached_attributes()
+        .map_or(tr_with(Message::UnknownTrack, &[("id", &track.id)]), |attributes| attributes.get_full_title());
+
+    [Some(title), track.get_cached_artist_credits().map(|credits| credits.get_display_name()), track.get_cached_album().map(|album| album.attributes.title.clone())]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" – ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        Mutex,
+    };
+
+    use super::*;
+
+    /// A writer whose output can be read after it is given to an `Announcer`.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn announces_one_line_per_change() {
+        let buffer = SharedBuffer::default();
+        let mut announcer = Announcer {
+            output: Box::new(buffer.clone()),
+            last: HashMap::new(),
+        };
+
+        announcer.announce_change(Announcement::Track, "Airbag – Radiohead");
+        announcer.announce_change(Announcement::Track, "Airbag – Radiohead");
+        announcer.announce_change(Announcement::State, "paused");
+        announcer.announce(Announcement::Message, "Added to\nRoad trip");
+        announcer.announce(Announcement::Message, "Added to\nRoad trip");
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(output, "track: Airbag – Radiohead\nstate: paused\nmessage: Added to Road trip\nmessage: Added to Road trip\n");
+    }
+}
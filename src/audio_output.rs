@@ -0,0 +1,50 @@
+use cpal::traits::{
+    DeviceTrait,
+    HostTrait,
+};
+
+/// Returns the audio host named `name` (e.g. "ALSA", "PulseAudio", or "PipeWire"), ignoring case,
+/// or the system's default host if `name` is `None`.
+pub fn get_host(name: Option<&str>) -> Result<cpal::Host, String> {
+    let Some(name) = name else {
+        return Ok(cpal::default_host());
+    };
+
+    let host_ids = cpal::available_hosts();
+    let host_id = host_ids.iter()
+        .find(|host_id| host_id.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let available = host_ids.iter().map(|host_id| host_id.name()).collect::<Vec<_>>();
+            format!("Audio host \"{name}\" isn't available. Available hosts: {}", available.join(", "))
+        })?;
+
+    cpal::host_from_id(*host_id)
+        .map_err(|e| format!("Unable to use audio host {}: {e}", host_id.name()))
+}
+
+/// Returns the output device named `device_name` on the host named `host_name`,
+/// or the default output device of the host if `device_name` is `None`.
+pub fn get_output_device(host_name: Option<&str>, device_name: Option<&str>) -> Result<cpal::Device, String> {
+    let host = get_host(host_name)?;
+
+    let Some(name) = device_name else {
+        return host.default_output_device()
+            .ok_or(format!("No default output device ({})", host.id().name()));
+    };
+
+    host.output_devices()
+        .map_err(|e| format!("Unable to list the output devices of {}: {e}", host.id().name()))?
+        .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+        .ok_or_else(|| format!(
+            "No output device named \"{name}\" ({}). Available devices: {}",
+            host.id().name(),
+            get_output_device_names(&host).join(", "),
+        ))
+}
+
+/// Returns the names of `host`'s output devices.
+pub fn get_output_device_names(host: &cpal::Host) -> Vec<String> {
+    host.output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
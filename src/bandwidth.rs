@@ -0,0 +1,250 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use rtidalapi::{
+    AudioQuality,
+    Session,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    config::BandwidthConfig,
+    format::Locale,
+};
+
+/// How much audio has been streamed on each day (in UTC), for keeping an eye on metered connections.
+///
+/// The usage is stored in `bandwidth.toml` within the config directory.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct BandwidthUsage {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    /// Bytes streamed by day, written like 2024-05-31.
+    #[serde(default)]
+    days: BTreeMap<String, u64>,
+}
+
+/// Lowers the audio quality while more has been streamed than a cap in the config allows,
+/// and puts it back once the day or month the cap was reached in is over.
+pub struct BandwidthCap {
+    config: BandwidthConfig,
+    /// The quality before it was lowered, if it has been lowered.
+    quality_before_cap: Option<AudioQuality>,
+    is_over_cap: bool,
+}
+
+impl BandwidthUsage {
+    /// Name of the usage file within the config directory.
+    const FILE_NAME: &str = "bandwidth.toml";
+
+    /// Days older than this are dropped from the file, since they no longer count towards any cap.
+    const KEPT_DAYS: u64 = 62;
+
+    /// Loads the bandwidth usage from `config_folder_path`, which is empty if nothing has been streamed yet.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let usage_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut usage = if usage_file.exists() {
+            let toml_str = fs::read_to_string(&usage_file)
+                .map_err(|e| format!("Unable to read {}: {e}", usage_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", usage_file.display()))?
+        } else {
+            Self::default()
+        };
+
+        usage.file = Some(usage_file);
+
+        Ok(usage)
+    }
+
+    /// Saves the bandwidth usage to the file it was loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(usage_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the bandwidth usage: {e}"))?;
+
+        fs::write(usage_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", usage_file.display()))
+    }
+
+    /// Records that `bytes` were streamed at `at`, and forgets days too old to matter.
+    pub fn record(&mut self, bytes: u64, at: SystemTime) {
+        *self.days.entry(format_day(at)).or_default() += bytes;
+
+        let oldest_kept_day = format_day(at - Duration::from_secs(Self::KEPT_DAYS * 24 * 60 * 60));
+        self.days.retain(|day, _| *day >= oldest_kept_day);
+    }
+
+    /// Returns how many bytes were streamed on the day of `at`.
+    pub fn get_day_usage(&self, at: SystemTime) -> u64 {
+        self.days.get(&format_day(at)).copied().unwrap_or(0)
+    }
+
+    /// Returns how many bytes were streamed in the month of `at`.
+    pub fn get_month_usage(&self, at: SystemTime) -> u64 {
+        let day = format_day(at);
+        let month = &day[..day.len() - 2];
+
+        self.days.iter()
+            .filter(|(day, _)| day.starts_with(month))
+            .map(|(_, bytes)| bytes)
+            .sum()
+    }
+
+    /// Returns a line for today's and this month's usage, along with their caps in `config`, written in `locale`, for displaying.
+    pub fn get_lines(&self, config: &BandwidthConfig, at: SystemTime, locale: Locale) -> Vec<String> {
+        let describe = |bytes: u64, cap_mb: Option<u64>| match cap_mb {
+            Some(cap_mb) => format!("{} of {} cap", locale.format_bytes(bytes), locale.format_bytes(cap_mb * 1_000_000)),
+            None => locale.format_bytes(bytes),
+        };
+
+        vec![
+            format!("Data today: {}", describe(self.get_day_usage(at), config.daily_cap_mb)),
+            format!("Data this month: {}", describe(self.get_month_usage(at), config.monthly_cap_mb)),
+        ]
+    }
+
+    /// Returns "daily" or "monthly" if the usage at `at` is over that cap in `config`, or `None` if it is under both.
+    fn get_exceeded_cap(&self, config: &BandwidthConfig, at: SystemTime) -> Option<&'static str> {
+        let is_over = |bytes: u64, cap_mb: Option<u64>| cap_mb.is_some_and(|cap_mb| bytes >= cap_mb * 1_000_000);
+
+        if is_over(self.get_day_usage(at), config.daily_cap_mb) {
+            Some("daily")
+        } else if is_over(self.get_month_usage(at), config.monthly_cap_mb) {
+            Some("monthly")
+        } else {
+            None
+        }
+    }
+}
+
+impl BandwidthCap {
+    /// Returns a cap enforcing the limits in `config`.
+    pub fn new(config: &BandwidthConfig) -> Self {
+        Self {
+            config: config.clone(),
+            quality_before_cap: None,
+            is_over_cap: false,
+        }
+    }
+
+    /// Lowers `session`'s quality when `usage` first goes over a cap, and puts it back once it is under every cap again.
+    ///
+    /// Returns a message saying what changed, if anything did. Raising the quality by hand while over a cap is left alone.
+    pub fn update(&mut self, usage: &BandwidthUsage, session: &Session) -> Option<String> {
+        let exceeded_cap = usage.get_exceeded_cap(&self.config, SystemTime::now());
+
+        match (exceeded_cap, self.is_over_cap) {
+            (Some(cap), false) => {
+                self.is_over_cap = true;
+
+                let quality = session.get_audio_quality();
+                if quality <= self.config.capped_quality || session.set_audio_quality(self.config.capped_quality).is_err() {
+                    return None;
+                }
+                self.quality_before_cap = Some(quality);
+
+                Some(format!("Reached the {cap} data cap, quality lowered to {}", self.config.capped_quality.to_string()))
+            },
+            (None, true) => {
+                self.is_over_cap = false;
+
+                let quality = self.quality_before_cap.take()?;
+                session.set_audio_quality(quality).ok()?;
+
+                Some(format!("Under the data cap again, quality back to {}", quality.to_string()))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Returns the UTC date of `at`, written like 2024-05-31.
+fn format_day(at: SystemTime) -> String {
+    // Converts days since the Unix epoch to a civil date, from http://howardhinnant.github.io/date_algorithms.html.
+    let days = (at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / (24 * 60 * 60)) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn formats_days() {
+        assert_eq!(format_day(UNIX_EPOCH), "1970-01-01");
+        assert_eq!(format_day(UNIX_EPOCH + Duration::from_secs(1_700_000_000)), "2023-11-14");
+        assert_eq!(format_day(UNIX_EPOCH + Duration::from_secs(951_782_400)), "2000-02-29");
+    }
+
+    #[test]
+    fn totals_usage_by_day_and_month() {
+        // 2023-11-14
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut usage = BandwidthUsage::default();
+        usage.record(100, now);
+        usage.record(50, now);
+        usage.record(20, now - DAY);
+        usage.record(7, now - DAY * 30);
+
+        assert_eq!(usage.get_day_usage(now), 150);
+        assert_eq!(usage.get_month_usage(now), 170);
+
+        // Days this old are dropped.
+        usage.record(1, now - DAY * 100);
+        usage.record(1, now);
+        assert_eq!(usage.days.len(), 3);
+    }
+
+    #[test]
+    fn finds_exceeded_caps() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut usage = BandwidthUsage::default();
+        usage.record(2_000_000, now - DAY);
+        usage.record(1_000_000, now);
+
+        let config = BandwidthConfig {
+            daily_cap_mb: Some(2),
+            monthly_cap_mb: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(usage.get_exceeded_cap(&config, now), Some("monthly"));
+
+        usage.record(1_000_000, now);
+        assert_eq!(usage.get_exceeded_cap(&config, now), Some("daily"));
+        assert_eq!(usage.get_exceeded_cap(&BandwidthConfig::default(), now), None);
+    }
+}
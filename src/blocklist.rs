@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Tracks and artists that are never played automatically (e.g. while shuffling), although they can still be played by hand.
+///
+/// The blocklist is stored in `blocklist.toml` within the config directory.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Blocklist {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    /// Blocked track ids, along with the name shown for each.
+    #[serde(default)]
+    tracks: BTreeMap<String, String>,
+    /// Blocked artist ids, along with the name shown for each.
+    #[serde(default)]
+    artists: BTreeMap<String, String>,
+}
+
+/// A track or artist in the blocklist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BlockedItem {
+    Track(String),
+    Artist(String),
+}
+
+impl Blocklist {
+    /// Name of the blocklist file within the config directory.
+    const FILE_NAME: &str = "blocklist.toml";
+
+    /// Loads the blocklist from `config_folder_path`, which is empty if it hasn't been saved yet.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let blocklist_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut blocklist = if blocklist_file.exists() {
+            let toml_str = fs::read_to_string(&blocklist_file)
+                .map_err(|e| format!("Unable to read {}: {e}", blocklist_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", blocklist_file.display()))?
+        } else {
+            Self::default()
+        };
+
+        blocklist.file = Some(blocklist_file);
+
+        Ok(blocklist)
+    }
+
+    /// Saves the blocklist to the file it was loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(blocklist_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the blocklist: {e}"))?;
+
+        fs::write(blocklist_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", blocklist_file.display()))
+    }
+
+    /// Returns true if the track with id `track_id`, or its artist, is blocked.
+    pub fn is_blocked(&self, track_id: &str, artist_id: Option<&str>) -> bool {
+        self.tracks.contains_key(track_id) || artist_id.is_some_and(|artist_id| self.artists.contains_key(artist_id))
+    }
+
+    /// Blocks the track with id `id` and `name`, or unblocks it if it was already blocked.
+    ///
+    /// Returns true if the track is now blocked.
+    pub fn toggle_track(&mut self, id: &str, name: &str) -> bool {
+        Self::toggle(&mut self.tracks, id, name)
+    }
+
+    /// Blocks the artist with id `id` and `name`, or unblocks them if they were already blocked.
+    ///
+    /// Returns true if the artist is now blocked.
+    pub fn toggle_artist(&mut self, id: &str, name: &str) -> bool {
+        Self::toggle(&mut self.artists, id, name)
+    }
+
+    /// Returns every blocked item along with its name, artists first.
+    pub fn get_items(&self) -> Vec<(BlockedItem, String)> {
+        let artists = self.artists.iter()
+            .map(|(id, name)| (BlockedItem::Artist(id.clone()), format!("Artist: {name}")));
+        let tracks = self.tracks.iter()
+            .map(|(id, name)| (BlockedItem::Track(id.clone()), format!("Track: {name}")));
+
+        artists.chain(tracks).collect()
+    }
+
+    /// Unblocks `item`.
+    pub fn remove(&mut self, item: &BlockedItem) {
+        match item {
+            BlockedItem::Track(id) => self.tracks.remove(id),
+            BlockedItem::Artist(id) => self.artists.remove(id),
+        };
+    }
+
+    /// Adds `id` to `items`, or removes it if it was already there. Returns true if it was added.
+    fn toggle(items: &mut BTreeMap<String, String>, id: &str, name: &str) -> bool {
+        if items.remove(id).is_some() {
+            return false;
+        }
+
+        items.insert(id.to_string(), name.to_string());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_tracks_and_artists() {
+        let mut blocklist = Blocklist::default();
+        assert!(blocklist.toggle_track("77646170", "Hurt"));
+        assert!(blocklist.toggle_artist("3346", "Nine Inch Nails"));
+
+        assert!(blocklist.is_blocked("77646170", None));
+        assert!(blocklist.is_blocked("1234", Some("3346")));
+        assert!(!blocklist.is_blocked("1234", Some("5678")));
+        assert!(!blocklist.is_blocked("1234", None));
+
+        assert!(!blocklist.toggle_track("77646170", "Hurt"));
+        assert!(!blocklist.is_blocked("77646170", None));
+    }
+
+    #[test]
+    fn lists_and_removes_items() {
+        let mut blocklist = Blocklist::default();
+        blocklist.toggle_track("77646170", "Hurt");
+        blocklist.toggle_artist("3346", "Nine Inch Nails");
+
+        let items = blocklist.get_items();
+        assert_eq!(items, vec![
+            (BlockedItem::Artist(String::from("3346")), String::from("Artist: Nine Inch Nails")),
+            (BlockedItem::Track(String::from("77646170")), String::from("Track: Hurt")),
+        ]);
+
+        blocklist.remove(&items[0].0);
+        assert!(!blocklist.is_blocked("1234", Some("3346")));
+        assert!(blocklist.is_blocked("77646170", None));
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let mut blocklist = Blocklist::default();
+        blocklist.toggle_track("77646170", "Hurt");
+        blocklist.toggle_artist("3346", "Nine Inch Nails");
+
+        let toml_str = toml::to_string(&blocklist).unwrap();
+        assert_eq!(toml::from_str::<Blocklist>(&toml_str).unwrap(), blocklist);
+    }
+}
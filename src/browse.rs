@@ -0,0 +1,1112 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use ratatui::widgets::TableState;
+use rtidalapi::{
+    Album,
+    Artist,
+    HomeItem,
+    Mix,
+    Playlist,
+    PublicUser,
+    Session,
+    Track,
+    TrackNumber,
+    User,
+};
+use tokio::sync::mpsc;
+
+#[cfg(feature = "unofficial")]
+use crate::clipboard;
+use crate::{
+    config::PageConfig,
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+    links::TidalLink,
+    search::get_search_text,
+    sort::AlbumSort,
+    ui::OsdContent,
+    AppEvent,
+};
+
+/// Something opened in the main pane in place of My Collection, such as another user's profile or a playlist.
+pub struct BrowseView {
+    state: Arc<Mutex<BrowseState>>,
+    pub table_state: TableState,
+    /// The last invite link created for this playlist.
+    invite_link: Arc<Mutex<Option<String>>>,
+    /// Tracks moved in this playlist that haven't been saved to Tidal yet.
+    ///
+    /// To avoid deadlocks, this is only ever locked while `state` is already locked.
+    reorder: Arc<Mutex<ReorderSync>>,
+    /// Which kind of item is shown, if this view has tabs.
+    tab: BrowseTab,
+    /// The item selected in each section of the home page, by section.
+    columns: Vec<usize>,
+    /// How the albums this view lists are sorted.
+    album_sort: AlbumSort,
+    /// The albums this view lists in the order Tidal gave them, kept once they are first sorted.
+    unsorted_albums: Option<Vec<Arc<Album>>>,
+}
+
+/// A kind of item listed in its own tab, in views like the results of a search or an artist's page.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BrowseTab {
+    #[default]
+    Tracks,
+    Albums,
+    Artists,
+    Playlists,
+}
+
+impl BrowseTab {
+    /// Every tab, in the order they are shown.
+    pub const ALL: [BrowseTab; 4] = [Self::Tracks, Self::Albums, Self::Artists, Self::Playlists];
+
+    /// Returns the name shown on this tab.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Tracks => tr(Message::Tracks),
+            Self::Albums => tr(Message::Albums),
+            Self::Artists => tr(Message::Artists),
+            Self::Playlists => tr(Message::Playlists),
+        }
+    }
+}
+
+/// Moves made to a playlist's tracks, which are shown right away and saved to Tidal one at a time in the background.
+#[derive(Default)]
+struct ReorderSync {
+    /// Moves that haven't been sent yet, in the order they were made.
+    pending: VecDeque<ItemMove>,
+    /// True while moves are being sent.
+    is_syncing: bool,
+    /// The order saved to Tidal, which the playlist goes back to if a move fails.
+    synced_tracks: Vec<Arc<Track>>,
+    synced_item_ids: Vec<String>,
+}
+
+/// A track moved to just before another one, or to the end of the playlist if `before_item_id` is `None`.
+#[derive(Clone, Debug, PartialEq)]
+struct ItemMove {
+    item_id: String,
+    track_id: String,
+    before_item_id: Option<String>,
+}
+
+/// Whether a `BrowseView` has finished loading.
+pub enum BrowseState {
+    Loading,
+    Loaded(BrowseContent),
+    Failed(String),
+}
+
+/// The content of a `BrowseView` once it has loaded.
+#[derive(Clone)]
+pub enum BrowseContent {
+    /// A user's public profile, listing their playlists.
+    User {
+        user: Arc<PublicUser>,
+        playlists: Vec<Arc<Playlist>>,
+    },
+    /// A playlist, listing its tracks.
+    Playlist {
+        playlist: Arc<Playlist>,
+        tracks: Vec<Arc<Track>>,
+        /// The id of each track's entry in the playlist, which is needed to move it.
+        item_ids: Vec<String>,
+    },
+    /// Tracks that used to be played a lot but haven't been played recently.
+    Rediscover {
+        tracks: Vec<Arc<Track>>,
+    },
+    /// An album, listing its tracks.
+    Album {
+        album: Arc<Album>,
+        tracks: Vec<Arc<Track>>,
+        /// The disc and track number of each track.
+        track_numbers: Vec<TrackNumber>,
+    },
+    /// An artist, listing their most popular tracks and their albums in separate tabs.
+    Artist {
+        artist: Arc<Artist>,
+        tracks: Vec<Arc<Track>>,
+        albums: Vec<Arc<Album>>,
+    },
+    /// The albums in the user's collection.
+    CollectionAlbums {
+        albums: Vec<Arc<Album>>,
+    },
+    /// The artists the user follows.
+    CollectionArtists {
+        artists: Vec<Arc<Artist>>,
+    },
+    /// What a search of Tidal found, with each kind of result in its own tab.
+    Search {
+        query: String,
+        tracks: Vec<Arc<Track>>,
+        albums: Vec<Arc<Album>>,
+        artists: Vec<Arc<Artist>>,
+        playlists: Vec<Arc<Playlist>>,
+    },
+    /// A page of pinned items from the config.
+    Page {
+        name: String,
+        items: Vec<PageItem>,
+    },
+    /// Tidal's personalized home page, listing the items of each section side by side.
+    Home {
+        sections: Vec<HomeSection>,
+    },
+    /// A mix made for the user, listing its tracks.
+    Mix {
+        mix: Arc<Mix>,
+        tracks: Vec<Arc<Track>>,
+    },
+    /// The settings loaded from the config file, by their names there, with `None` for those that aren't set.
+    Settings {
+        settings: Vec<(&'static str, Option<String>)>,
+    },
+}
+
+/// A row of the home page, like "My Mixes" or "Recently played".
+#[derive(Clone)]
+pub struct HomeSection {
+    pub title: String,
+    pub items: Vec<PageItem>,
+}
+
+/// An item pinned to a page once what it links to has been fetched, or listed on the home page.
+#[derive(Clone)]
+pub enum PageItem {
+    Track(Arc<Track>),
+    Album(Arc<Album>),
+    Artist(Arc<Artist>),
+    Playlist(Arc<Playlist>),
+    User(Arc<PublicUser>),
+    Mix(Arc<Mix>),
+    /// An item that couldn't be fetched, so that one that was removed from Tidal doesn't keep the rest from showing.
+    Failed {
+        link: TidalLink,
+        error: String,
+    },
+}
+
+impl BrowseView {
+    /// Opens the public profile of the user with id `id`, loading it and their playlists in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn open_user(session: Arc<Session>, id: String, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let user = PublicUser::new(session, id)?;
+            Self::load_user_playlists(Arc::new(user))
+        })
+    }
+
+    /// Public profiles can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn open_user(_session: Arc<Session>, _id: String, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens `user`'s public profile, loading their playlists in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn from_user(user: Arc<PublicUser>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || Self::load_user_playlists(user))
+    }
+
+    /// Public playlists can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn from_user(_user: Arc<PublicUser>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens the playlist with id `id`, loading it and its tracks in the background.
+    pub fn open_playlist(session: Arc<Session>, id: String, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let playlist = Playlist::new(session, id)?;
+            Self::load_playlist_tracks(Arc::new(playlist))
+        })
+    }
+
+    /// Opens a list of `tracks` to rediscover, which is already loaded since their info is fetched as they are shown.
+    pub fn rediscover(tracks: Vec<Arc<Track>>) -> Self {
+        Self::new(BrowseState::Loaded(BrowseContent::Rediscover { tracks }))
+    }
+
+    /// Opens a list of `settings`, which is already loaded since they come from the config.
+    pub fn settings(settings: Vec<(&'static str, Option<String>)>) -> Self {
+        Self::new(BrowseState::Loaded(BrowseContent::Settings { settings }))
+    }
+
+    /// Searches Tidal for `query`, showing what it finds once the search is done in the background.
+    pub fn search(session: Arc<Session>, query: String, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let results = session.search(&query)?;
+
+            Ok(BrowseContent::Search {
+                query,
+                tracks: results.tracks.into_iter().map(Arc::new).collect(),
+                albums: results.albums.into_iter().map(Arc::new).collect(),
+                artists: results.artists.into_iter().map(Arc::new).collect(),
+                playlists: results.playlists.into_iter().map(Arc::new).collect(),
+            })
+        })
+    }
+
+    /// Opens the albums in `user`'s collection, loading them in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn collection_albums(user: Arc<User>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let albums = user.get_collection_albums()?
+                .iter()
+                .cloned()
+                .map(Arc::new)
+                .collect();
+
+            Ok(BrowseContent::CollectionAlbums { albums })
+        })
+    }
+
+    /// My Collection can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn collection_albums(_user: Arc<User>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens the artists `user` follows, loading them in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn collection_artists(user: Arc<User>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let artists = user.get_collection_artists()?
+                .iter()
+                .cloned()
+                .map(Arc::new)
+                .collect();
+
+            Ok(BrowseContent::CollectionArtists { artists })
+        })
+    }
+
+    /// My Collection can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn collection_artists(_user: Arc<User>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens the album with id `id`, loading it and its tracks in the background.
+    pub fn open_album(session: Arc<Session>, id: String, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let album = Album::new(session, id)?;
+            Self::load_album_tracks(Arc::new(album))
+        })
+    }
+
+    /// Opens the artist with id `id`, loading them, their most popular tracks, and their albums in the background.
+    pub fn open_artist(session: Arc<Session>, id: String, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let artist = Artist::new(session, id)?;
+            Self::load_artist(Arc::new(artist))
+        })
+    }
+
+    /// Opens `page`, fetching what each of its items links to in the background.
+    pub fn open_page(session: Arc<Session>, page: PageConfig, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let items = page.items
+                .into_iter()
+                .map(|link| PageItem::fetch(&session, link))
+                .collect();
+
+            Ok(BrowseContent::Page { name: page.name, items })
+        })
+    }
+
+    /// Opens the user's home page, loading it in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn home(session: Arc<Session>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let sections = session.get_home_page()?
+                .sections
+                .into_iter()
+                .map(|section| HomeSection {
+                    title: section.title,
+                    items: section.items.into_iter().map(PageItem::from).collect(),
+                })
+                .collect();
+
+            Ok(BrowseContent::Home { sections })
+        })
+    }
+
+    /// The home page can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn home(_session: Arc<Session>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens the mixes Tidal made for the user, loading them in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn my_mixes(session: Arc<Session>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let items = session.get_my_mixes()?
+                .into_iter()
+                .map(|mix| PageItem::Mix(Arc::new(mix)))
+                .collect();
+
+            Ok(BrowseContent::Page { name: String::from(tr(Message::MyMixes)), items })
+        })
+    }
+
+    /// Mixes can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn my_mixes(_session: Arc<Session>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens `mix`, loading its tracks in the background.
+    #[cfg(feature = "unofficial")]
+    pub fn from_mix(mix: Arc<Mix>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let tracks = mix.get_tracks()?.iter().cloned().map(Arc::new).collect();
+            Ok(BrowseContent::Mix { mix, tracks })
+        })
+    }
+
+    /// Mixes can only be fetched with the unofficial API, so this view always fails without it.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn from_mix(_mix: Arc<Mix>, _tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::unofficial_only()
+    }
+
+    /// Opens `album`, loading its tracks in the background.
+    pub fn from_album(album: Arc<Album>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || Self::load_album_tracks(album))
+    }
+
+    /// Opens the album `track` is on, loading it and its tracks in the background.
+    pub fn from_track_album(track: Arc<Track>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let album = track.get_album()?.clone();
+            Self::load_album_tracks(Arc::new(album))
+        })
+    }
+
+    /// Opens the artist of `track`, loading their most popular tracks and their albums in the background.
+    pub fn from_track_artist(track: Arc<Track>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || {
+            let artist = track.get_artist()?.clone();
+            Self::load_artist(Arc::new(artist))
+        })
+    }
+
+    /// Opens `artist`, loading their most popular tracks and their albums in the background.
+    pub fn from_artist(artist: Arc<Artist>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || Self::load_artist(artist))
+    }
+
+    /// Opens `playlist`, whose attributes have already been fetched, loading its tracks in the background.
+    pub fn from_playlist(playlist: Arc<Playlist>, tx: mpsc::Sender<AppEvent>) -> Self {
+        Self::load(tx, move || Self::load_playlist_tracks(playlist))
+    }
+
+    /// Returns the state of this view.
+    pub fn get_state(&self) -> &Mutex<BrowseState> {
+        &self.state
+    }
+
+    /// Returns the content of this view, or `None` if it hasn't loaded.
+    pub fn get_content(&self) -> Option<BrowseContent> {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => Some(content.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns this view's title.
+    pub fn get_title(&self) -> String {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(BrowseContent::User { user, .. }) => tr_with(Message::UserPlaylists, &[("user", &user.name)]),
+            BrowseState::Loaded(BrowseContent::Playlist { playlist, .. }) => {
+                tr_with(Message::PlaylistTitle, &[("playlist", &playlist.attributes.name)])
+            },
+            BrowseState::Loaded(BrowseContent::Rediscover { .. }) => String::from(tr(Message::Rediscover)),
+            BrowseState::Loaded(BrowseContent::Album { album, .. }) => tr_with(Message::AlbumTitle, &[("album", &album.attributes.title)]),
+            BrowseState::Loaded(BrowseContent::Artist { artist, .. }) => tr_with(Message::ArtistTitle, &[("artist", &artist.attributes.name)]),
+            BrowseState::Loaded(BrowseContent::CollectionAlbums { .. }) => String::from(tr(Message::MyCollectionAlbums)),
+            BrowseState::Loaded(BrowseContent::CollectionArtists { .. }) => String::from(tr(Message::MyCollectionArtists)),
+            BrowseState::Loaded(BrowseContent::Search { query, .. }) => tr_with(Message::SearchTitle, &[("query", query)]),
+            BrowseState::Loaded(BrowseContent::Page { name, .. }) => name.clone(),
+            BrowseState::Loaded(BrowseContent::Home { .. }) => String::from(tr(Message::Home)),
+            BrowseState::Loaded(BrowseContent::Mix { mix, .. }) => mix.title.clone(),
+            BrowseState::Loaded(BrowseContent::Settings { .. }) => String::from(tr(Message::Settings)),
+            BrowseState::Loading => String::from(tr(Message::Loading)),
+            BrowseState::Failed(_) => String::from(tr(Message::Error)),
+        }
+    }
+
+    /// Returns the key this view's tracks are remembered by when they are played,
+    /// or `None` if it isn't a loaded list of tracks.
+    pub fn get_context_key(&self) -> Option<String> {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(BrowseContent::Playlist { playlist, .. }) => Some(format!("playlist:{}", playlist.id)),
+            BrowseState::Loaded(BrowseContent::Rediscover { .. }) => Some(String::from("rediscover")),
+            BrowseState::Loaded(BrowseContent::Album { album, .. }) => Some(format!("album:{}", album.id)),
+            BrowseState::Loaded(BrowseContent::Artist { artist, .. }) => Some(format!("artist:{}", artist.id)),
+            BrowseState::Loaded(BrowseContent::Mix { mix, .. }) => Some(format!("mix:{}", mix.id)),
+            _ => None,
+        }
+    }
+
+    /// Returns the last invite link created for this playlist, if any.
+    pub fn get_invite_link(&self) -> Option<String> {
+        self.invite_link.lock().unwrap().clone()
+    }
+
+    /// Creates an invite link for this playlist in the background, copying it to the clipboard once it's ready.
+    ///
+    /// Whether it worked is shown in the OSD through `tx`.
+    #[cfg(feature = "unofficial")]
+    pub fn create_invite_link(&self, tx: mpsc::Sender<AppEvent>) {
+        let Some(BrowseContent::Playlist { playlist, .. }) = self.get_content() else {
+            return;
+        };
+
+        let invite_link_clone = Arc::clone(&self.invite_link);
+        tokio::task::spawn_blocking(move || {
+            let message = match playlist.create_invite_link() {
+                Ok(invite_link) => {
+                    // The link is also shown in the view, so it can still be copied by hand.
+                    let message = match clipboard::copy(&invite_link) {
+                        Ok(()) => String::from(tr(Message::InviteLinkCopied)),
+                        Err(_) => String::from(tr(Message::InviteLinkCreated)),
+                    };
+                    *invite_link_clone.lock().unwrap() = Some(invite_link);
+                    message
+                },
+                Err(e) => tr_with(Message::InviteLinkFailed, &[("error", &e)]),
+            };
+            let _ = tx.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+
+    /// Invite links can only be created with the unofficial API, so this only shows that in the OSD through `tx`.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn create_invite_link(&self, tx: mpsc::Sender<AppEvent>) {
+        let message = tr_with(Message::InviteLinkFailed, &[("error", tr(Message::NeedsUnofficialApi))]);
+        let _ = tx.try_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+    }
+
+    /// Moves the selected track of this playlist up (if `offset` is negative) or down by `offset` rows, keeping it selected.
+    ///
+    /// The new order is shown right away and saved to Tidal in the background. If saving fails, the playlist goes
+    /// back to the last order that was saved, and why is shown in the OSD through `tx`.
+    pub fn move_selected_track(&mut self, offset: isize, tx: mpsc::Sender<AppEvent>) {
+        let mut state = self.state.lock().unwrap();
+        let BrowseState::Loaded(BrowseContent::Playlist { playlist, tracks, item_ids }) = &mut *state else {
+            return;
+        };
+
+        let Some(index) = self.table_state.selected() else {
+            return;
+        };
+        // Moves past the start or end stop there.
+        let new_index = index.saturating_add_signed(offset).min(tracks.len().saturating_sub(1));
+        if new_index == index {
+            return;
+        }
+
+        // Moving down means going before the track after the one being passed.
+        let before_index = if new_index < index { new_index } else { new_index + 1 };
+        let item_move = ItemMove {
+            item_id: item_ids[index].clone(),
+            track_id: tracks[index].id.clone(),
+            before_item_id: item_ids.get(before_index).cloned(),
+        };
+
+        let mut reorder = self.reorder.lock().unwrap();
+        if !reorder.is_syncing {
+            // Nothing is waiting to be saved, so what is shown is what Tidal has.
+            reorder.synced_tracks = tracks.clone();
+            reorder.synced_item_ids = item_ids.clone();
+        }
+
+        apply_move(tracks, item_ids, &item_move);
+        self.table_state.select(Some(new_index));
+
+        reorder.pending.push_back(item_move);
+        if reorder.is_syncing {
+            return;
+        }
+        reorder.is_syncing = true;
+
+        let playlist = Arc::clone(playlist);
+        let state_clone = Arc::clone(&self.state);
+        let reorder_clone = Arc::clone(&self.reorder);
+        tokio::task::spawn_blocking(move || {
+            Self::sync_moves(playlist, state_clone, reorder_clone, tx);
+        });
+    }
+
+    /// Saves pending moves to Tidal one at a time, until there are none left or one fails.
+    fn sync_moves(
+        playlist: Arc<Playlist>,
+        state: Arc<Mutex<BrowseState>>,
+        reorder: Arc<Mutex<ReorderSync>>,
+        tx: mpsc::Sender<AppEvent>,
+    ) {
+        loop {
+            let item_move = {
+                let _state = state.lock().unwrap();
+                let mut reorder = reorder.lock().unwrap();
+                match reorder.pending.front() {
+                    Some(item_move) => item_move.clone(),
+                    None => {
+                        reorder.is_syncing = false;
+                        return;
+                    },
+                }
+            };
+
+            let result = playlist.move_item(&item_move.item_id, &item_move.track_id, item_move.before_item_id.as_deref());
+
+            let mut state = state.lock().unwrap();
+            let mut reorder = reorder.lock().unwrap();
+            let reorder = &mut *reorder;
+
+            if let Err(e) = result {
+                reorder.pending.clear();
+                reorder.is_syncing = false;
+                if let BrowseState::Loaded(BrowseContent::Playlist { tracks, item_ids, .. }) = &mut *state {
+                    *tracks = std::mem::take(&mut reorder.synced_tracks);
+                    *item_ids = std::mem::take(&mut reorder.synced_item_ids);
+                }
+                drop(state);
+
+                let message = tr_with(Message::MoveTrackFailed, &[("playlist", &playlist.attributes.name), ("error", &e)]);
+                let _ = tx.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                return;
+            }
+
+            reorder.pending.pop_front();
+            apply_move(&mut reorder.synced_tracks, &mut reorder.synced_item_ids, &item_move);
+        }
+    }
+
+    /// Returns true if this view is a playlist that has loaded.
+    pub fn is_playlist(&self) -> bool {
+        matches!(&*self.state.lock().unwrap(), BrowseState::Loaded(BrowseContent::Playlist { .. }))
+    }
+
+    /// Returns true if this view is a list of tracks that has loaded (e.g. a playlist), rather than a list of playlists.
+    ///
+    /// Views with tabs count while their tracks are shown.
+    pub fn has_tracks(&self) -> bool {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => content.get_tracks(self.tab).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if this view is an album that has loaded.
+    pub fn is_album(&self) -> bool {
+        matches!(&*self.state.lock().unwrap(), BrowseState::Loaded(BrowseContent::Album { .. }))
+    }
+
+    /// Returns true if this view is the home page, once it has loaded.
+    pub fn is_home(&self) -> bool {
+        matches!(&*self.state.lock().unwrap(), BrowseState::Loaded(BrowseContent::Home { .. }))
+    }
+
+    /// Returns the index of the item selected in the section of the home page at `row`.
+    pub fn get_column(&self, row: usize) -> usize {
+        self.columns.get(row).copied().unwrap_or(0)
+    }
+
+    /// Selects the item `offset` items after (or before, if negative) the one selected in the selected section
+    /// of the home page, stopping at either end.
+    pub fn move_column(&mut self, offset: isize) {
+        let Some(BrowseContent::Home { sections }) = self.get_content() else {
+            return;
+        };
+        let Some(row) = self.table_state.selected() else {
+            return;
+        };
+        let Some(section) = sections.get(row) else {
+            return;
+        };
+
+        let column = self.get_column(row).saturating_add_signed(offset).min(section.items.len().saturating_sub(1));
+        if self.columns.len() <= row {
+            self.columns.resize(row + 1, 0);
+        }
+        self.columns[row] = column;
+    }
+
+    /// Returns the selected item of a page, or of the selected section of the home page.
+    pub fn get_selected_item(&self) -> Option<PageItem> {
+        let row = self.table_state.selected()?;
+
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(BrowseContent::Page { items, .. }) => items.get(row).cloned(),
+            BrowseState::Loaded(BrowseContent::Home { sections }) => sections.get(row)?.items.get(self.get_column(row)).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this view is a page that has loaded.
+    pub fn is_page(&self) -> bool {
+        matches!(&*self.state.lock().unwrap(), BrowseState::Loaded(BrowseContent::Page { .. }))
+    }
+
+    /// Returns the tracks shown in this view, which is empty unless it is a loaded list of tracks.
+    pub fn get_tracks(&self) -> Vec<Arc<Track>> {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => content.get_tracks(self.tab).cloned().unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the text of each row in this view's table that a search looks through.
+    pub fn get_row_texts(&self) -> Vec<String> {
+        let BrowseState::Loaded(content) = &*self.state.lock().unwrap() else {
+            return vec![];
+        };
+
+        let playlist_names = |playlists: &[Arc<Playlist>]| playlists.iter()
+            .map(|playlist| playlist.attributes.name.clone())
+            .collect();
+        let album_titles = |albums: &[Arc<Album>]| albums.iter()
+            .map(|album| album.attributes.title.clone())
+            .collect();
+        let artist_names = |artists: &[Arc<Artist>]| artists.iter()
+            .map(|artist| artist.attributes.name.clone())
+            .collect();
+        let track_texts = |tracks: &[Arc<Track>]| tracks.iter()
+            .map(|track| get_search_text(track))
+            .collect();
+
+        match (content, self.tab) {
+            (BrowseContent::User { playlists, .. }, _) => playlist_names(playlists),
+            (BrowseContent::CollectionAlbums { albums }, _) => album_titles(albums),
+            (BrowseContent::CollectionArtists { artists }, _) => artist_names(artists),
+            (BrowseContent::Search { albums, .. } | BrowseContent::Artist { albums, .. }, BrowseTab::Albums) => album_titles(albums),
+            (BrowseContent::Search { artists, .. }, BrowseTab::Artists) => artist_names(artists),
+            (BrowseContent::Search { playlists, .. }, BrowseTab::Playlists) => playlist_names(playlists),
+            (BrowseContent::Page { items, .. }, _) => items.iter().map(PageItem::get_name).collect(),
+            (BrowseContent::Home { sections }, _) => sections.iter()
+                .map(|section| {
+                    let names = section.items.iter().map(PageItem::get_name).collect::<Vec<_>>().join(" ");
+                    format!("{} {names}", section.title)
+                })
+                .collect(),
+            (BrowseContent::Settings { settings }, _) => settings.iter().map(|(name, _)| name.to_string()).collect(),
+            (content, tab) => content.get_tracks(tab).map(|tracks| track_texts(tracks)).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the number of rows in this view's table.
+    pub fn get_len(&self) -> usize {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => content.get_tab_len(self.tab),
+            _ => 0,
+        }
+    }
+
+    /// Returns which kind of item is shown, if this view has tabs.
+    pub fn get_tab(&self) -> BrowseTab {
+        self.tab
+    }
+
+    /// Returns the tabs of this view, which is empty unless it is a loaded search or artist.
+    pub fn get_tabs(&self) -> &'static [BrowseTab] {
+        match &*self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => content.get_tabs(),
+            _ => &[],
+        }
+    }
+
+    /// Returns how the albums this view lists are sorted, or `None` if it isn't showing a loaded list of albums.
+    pub fn get_album_sort(&self) -> Option<AlbumSort> {
+        match &mut *self.state.lock().unwrap() {
+            BrowseState::Loaded(content) => content.get_albums_mut(self.tab).map(|_| self.album_sort),
+            _ => None,
+        }
+    }
+
+    /// Sorts the albums this view lists by `sort`, keeping the selected album selected.
+    pub fn set_album_sort(&mut self, sort: AlbumSort) {
+        let mut unlocked_state = self.state.lock().unwrap();
+        let BrowseState::Loaded(content) = &mut *unlocked_state else {
+            return;
+        };
+        let Some(albums) = content.get_albums_mut(self.tab) else {
+            return;
+        };
+
+        let selected_id = self.table_state.selected()
+            .and_then(|index| albums.get(index))
+            .map(|album| album.id.clone());
+
+        match &self.unsorted_albums {
+            Some(unsorted_albums) => albums.clone_from(unsorted_albums),
+            None => self.unsorted_albums = Some(albums.clone()),
+        }
+        sort.sort(albums);
+        self.album_sort = sort;
+
+        if let Some(index) = selected_id.and_then(|id| albums.iter().position(|album| album.id == id)) {
+            self.table_state.select(Some(index));
+        }
+    }
+
+    /// Shows the tab `offset` tabs after (or before, if negative) the one shown, wrapping around,
+    /// and selects its first row.
+    pub fn switch_tab(&mut self, offset: isize) {
+        let tabs = self.get_tabs();
+        if tabs.is_empty() {
+            return;
+        }
+
+        let index = tabs.iter().position(|tab| *tab == self.tab).unwrap_or(0) as isize;
+        self.tab = tabs[(index + offset).rem_euclid(tabs.len() as isize) as usize];
+
+        self.table_state = TableState::default();
+        if self.get_len() > 0 {
+            self.table_state.select_first();
+        }
+    }
+
+    /// Returns a new view whose content is loaded in the background by `load_content`,
+    /// requesting a rerender through `tx` once it's done.
+    fn load<F>(tx: mpsc::Sender<AppEvent>, load_content: F) -> Self
+    where
+        F: FnOnce() -> Result<BrowseContent, String> + Send + 'static,
+    {
+        let view = Self::new(BrowseState::Loading);
+        let state_clone = Arc::clone(&view.state);
+
+        tokio::task::spawn_blocking(move || {
+            *state_clone.lock().unwrap() = match load_content() {
+                Ok(content) => BrowseState::Loaded(content),
+                Err(e) => BrowseState::Failed(e),
+            };
+            let _ = tx.try_send(AppEvent::ReRender);
+        });
+
+        view
+    }
+
+    /// Returns a view that failed to load, since what it shows can only be fetched with the unofficial API.
+    #[cfg(not(feature = "unofficial"))]
+    fn unofficial_only() -> Self {
+        Self::new(BrowseState::Failed(String::from(tr(Message::NeedsUnofficialApi))))
+    }
+
+    /// Returns a new view in `state`.
+    fn new(state: BrowseState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            table_state: TableState::default(),
+            invite_link: Arc::new(Mutex::new(None)),
+            reorder: Arc::new(Mutex::new(ReorderSync::default())),
+            tab: BrowseTab::default(),
+            columns: vec![],
+            album_sort: AlbumSort::default(),
+            unsorted_albums: None,
+        }
+    }
+
+    /// Fetches the tracks of `album`, along with where each is on it.
+    fn load_album_tracks(album: Arc<Album>) -> Result<BrowseContent, String> {
+        let tracks = album.get_tracks()?
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect();
+        let track_numbers = album.get_track_numbers()?.clone();
+
+        Ok(BrowseContent::Album {
+            album,
+            tracks,
+            track_numbers,
+        })
+    }
+
+    /// Fetches the public playlists of `user`.
+    #[cfg(feature = "unofficial")]
+    fn load_user_playlists(user: Arc<PublicUser>) -> Result<BrowseContent, String> {
+        let playlists = user.get_playlists()?
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect();
+
+        Ok(BrowseContent::User { user, playlists })
+    }
+
+    /// Fetches the most popular tracks and the albums of `artist`.
+    fn load_artist(artist: Arc<Artist>) -> Result<BrowseContent, String> {
+        let tracks = artist.get_top_tracks()?
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect();
+        let albums = artist.get_albums()?
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect();
+
+        Ok(BrowseContent::Artist { artist, tracks, albums })
+    }
+
+    /// Fetches the tracks of `playlist`.
+    fn load_playlist_tracks(playlist: Arc<Playlist>) -> Result<BrowseContent, String> {
+        let tracks = playlist.get_tracks()?
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect();
+        let item_ids = playlist.get_item_ids()?.clone();
+
+        Ok(BrowseContent::Playlist {
+            playlist,
+            tracks,
+            item_ids,
+        })
+    }
+}
+
+impl BrowseContent {
+    /// The tabs of an artist's page.
+    const ARTIST_TABS: [BrowseTab; 2] = [BrowseTab::Tracks, BrowseTab::Albums];
+
+    /// Returns the tabs of this content, which is empty unless it is a search or artist.
+    pub fn get_tabs(&self) -> &'static [BrowseTab] {
+        match self {
+            Self::Search { .. } => &BrowseTab::ALL,
+            Self::Artist { .. } => &Self::ARTIST_TABS,
+            _ => &[],
+        }
+    }
+
+    /// Returns how many items are listed while `tab` is shown.
+    pub fn get_tab_len(&self, tab: BrowseTab) -> usize {
+        match (self, tab) {
+            (Self::User { playlists, .. }, _) => playlists.len(),
+            (Self::CollectionAlbums { albums }, _) => albums.len(),
+            (Self::CollectionArtists { artists }, _) => artists.len(),
+            (Self::Search { albums, .. } | Self::Artist { albums, .. }, BrowseTab::Albums) => albums.len(),
+            (Self::Search { artists, .. }, BrowseTab::Artists) => artists.len(),
+            (Self::Search { playlists, .. }, BrowseTab::Playlists) => playlists.len(),
+            (Self::Page { items, .. }, _) => items.len(),
+            (Self::Home { sections }, _) => sections.len(),
+            (Self::Settings { settings }, _) => settings.len(),
+            (content, tab) => content.get_tracks(tab).map_or(0, Vec::len),
+        }
+    }
+
+    /// Returns the albums listed while `tab` is shown, for sorting them, or `None` if it isn't a list of albums.
+    fn get_albums_mut(&mut self, tab: BrowseTab) -> Option<&mut Vec<Arc<Album>>> {
+        match self {
+            Self::CollectionAlbums { albums } => Some(albums),
+            Self::Search { albums, .. } | Self::Artist { albums, .. } if tab == BrowseTab::Albums => Some(albums),
+            _ => None,
+        }
+    }
+
+    /// Returns the tracks listed while `tab` is shown, or `None` if something else is listed (like playlists).
+    fn get_tracks(&self, tab: BrowseTab) -> Option<&Vec<Arc<Track>>> {
+        match self {
+            Self::Playlist { tracks, .. }
+            | Self::Rediscover { tracks }
+            | Self::Album { tracks, .. }
+            | Self::Mix { tracks, .. } => Some(tracks),
+            Self::Search { tracks, .. } | Self::Artist { tracks, .. } if tab == BrowseTab::Tracks => Some(tracks),
+            Self::User { .. }
+            | Self::CollectionAlbums { .. }
+            | Self::CollectionArtists { .. }
+            | Self::Search { .. }
+            | Self::Artist { .. }
+            | Self::Page { .. }
+            | Self::Home { .. }
+            | Self::Settings { .. } => None,
+        }
+    }
+}
+
+impl PageItem {
+    /// Fetches what `link` links to, and the info shown for it.
+    fn fetch(session: &Arc<Session>, link: TidalLink) -> Self {
+        let session = Arc::clone(session);
+        let item = match link.clone() {
+            TidalLink::Track(id) => Track::new(session, id).and_then(|track| {
+                track.fetch_info()?;
+                Ok(Self::Track(Arc::new(track)))
+            }),
+            TidalLink::Album(id) => Album::new(session, id).map(|album| Self::Album(Arc::new(album))),
+            TidalLink::Artist(id) => Artist::new(session, id).map(|artist| Self::Artist(Arc::new(artist))),
+            TidalLink::Playlist(id) => Playlist::new(session, id).map(|playlist| Self::Playlist(Arc::new(playlist))),
+            #[cfg(feature = "unofficial")]
+            TidalLink::User(id) => PublicUser::new(session, id).map(|user| Self::User(Arc::new(user))),
+            #[cfg(not(feature = "unofficial"))]
+            TidalLink::User(_) => Err(String::from(tr(Message::NeedsUnofficialApi))),
+        };
+
+        item.unwrap_or_else(|error| Self::Failed { link, error })
+    }
+
+    /// Returns the name shown for this item: the title of a track or album, or the name of anything else.
+    pub fn get_name(&self) -> String {
+        match self {
+            Self::Track(track) => track.get_cached_attributes().map_or(track.id.clone(), |attributes| attributes.get_full_title()),
+            Self::Album(album) => album.attributes.title.clone(),
+            Self::Artist(artist) => artist.attributes.name.clone(),
+            Self::Playlist(playlist) => playlist.attributes.name.clone(),
+            Self::User(user) => user.name.clone(),
+            Self::Mix(mix) => mix.title.clone(),
+            Self::Failed { link, .. } => link.to_string(),
+        }
+    }
+
+    /// Returns what kind of item this is, as shown next to its name.
+    pub fn get_kind_name(&self) -> &'static str {
+        match self {
+            Self::Track(_) => tr(Message::Track),
+            Self::Album(_) => tr(Message::Album),
+            Self::Artist(_) => tr(Message::Artist),
+            Self::Playlist(_) => tr(Message::Playlist),
+            Self::User(_) => tr(Message::User),
+            Self::Mix(_) => tr(Message::Mix),
+            Self::Failed { .. } => tr(Message::Error),
+        }
+    }
+
+    /// Returns a view of this item to open in the main pane, or `None` for tracks and items that couldn't be fetched.
+    pub fn open(&self, tx: mpsc::Sender<AppEvent>) -> Option<BrowseView> {
+        match self {
+            Self::Album(album) => Some(BrowseView::from_album(Arc::clone(album), tx)),
+            Self::Artist(artist) => Some(BrowseView::from_artist(Arc::clone(artist), tx)),
+            Self::Playlist(playlist) => Some(BrowseView::from_playlist(Arc::clone(playlist), tx)),
+            Self::User(user) => Some(BrowseView::from_user(Arc::clone(user), tx)),
+            Self::Mix(mix) => Some(BrowseView::from_mix(Arc::clone(mix), tx)),
+            Self::Track(_) | Self::Failed { .. } => None,
+        }
+    }
+
+    /// Fetches the tracks that playing this item plays: an artist's most popular tracks,
+    /// or the tracks of anything else. Users can't be played.
+    pub fn get_tracks(&self) -> Result<Vec<Arc<Track>>, String> {
+        let tracks = match self {
+            Self::Track(track) => return Ok(vec![Arc::clone(track)]),
+            Self::Album(album) => album.get_tracks()?,
+            Self::Artist(artist) => artist.get_top_tracks()?,
+            Self::Playlist(playlist) => playlist.get_tracks()?,
+            #[cfg(feature = "unofficial")]
+            Self::Mix(mix) => mix.get_tracks()?,
+            #[cfg(not(feature = "unofficial"))]
+            Self::Mix(_) => return Err(String::from(tr(Message::NeedsUnofficialApi))),
+            Self::User(_) => return Err(String::from(tr(Message::NothingToPlay))),
+            Self::Failed { error, .. } => return Err(error.clone()),
+        };
+
+        Ok(tracks.iter().cloned().map(Arc::new).collect())
+    }
+}
+
+impl From<HomeItem> for PageItem {
+    fn from(item: HomeItem) -> Self {
+        match item {
+            HomeItem::Track(track) => Self::Track(Arc::new(*track)),
+            HomeItem::Album(album) => Self::Album(Arc::new(album)),
+            HomeItem::Artist(artist) => Self::Artist(Arc::new(artist)),
+            HomeItem::Playlist(playlist) => Self::Playlist(Arc::new(playlist)),
+            HomeItem::Mix(mix) => Self::Mix(Arc::new(mix)),
+        }
+    }
+}
+
+/// Moves a track within `tracks` and `item_ids` (which are in the same order) as described by `item_move`.
+fn apply_move<T>(tracks: &mut Vec<T>, item_ids: &mut Vec<String>, item_move: &ItemMove) {
+    let Some(index) = item_ids.iter().position(|item_id| *item_id == item_move.item_id) else {
+        return;
+    };
+    let track = tracks.remove(index);
+    let item_id = item_ids.remove(index);
+
+    let new_index = item_move.before_item_id.as_ref()
+        .and_then(|before_item_id| item_ids.iter().position(|item_id| item_id == before_item_id))
+        .unwrap_or(item_ids.len());
+    tracks.insert(new_index, track);
+    item_ids.insert(new_index, item_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_move(item_id: &str, before_item_id: Option<&str>) -> ItemMove {
+        ItemMove {
+            item_id: String::from(item_id),
+            track_id: String::new(),
+            before_item_id: before_item_id.map(String::from),
+        }
+    }
+
+    #[test]
+    fn applies_moves() {
+        let mut tracks = vec![1, 2, 3];
+        let mut item_ids = vec![String::from("a"), String::from("b"), String::from("c")];
+
+        apply_move(&mut tracks, &mut item_ids, &item_move("c", Some("b")));
+        assert_eq!(tracks, [1, 3, 2]);
+        assert_eq!(item_ids, ["a", "c", "b"]);
+
+        apply_move(&mut tracks, &mut item_ids, &item_move("a", None));
+        assert_eq!(tracks, [3, 2, 1]);
+        assert_eq!(item_ids, ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn switches_tabs() {
+        let mut view = BrowseView::new(BrowseState::Loaded(BrowseContent::Search {
+            query: String::from("query"),
+            tracks: vec![],
+            albums: vec![],
+            artists: vec![],
+            playlists: vec![],
+        }));
+        assert!(view.has_tracks());
+
+        view.switch_tab(1);
+        assert_eq!(view.get_tab(), BrowseTab::Albums);
+        assert!(!view.has_tracks());
+
+        view.switch_tab(-2);
+        assert_eq!(view.get_tab(), BrowseTab::Playlists);
+
+        view.switch_tab(1);
+        assert_eq!(view.get_tab(), BrowseTab::Tracks);
+        assert_eq!(view.table_state.selected(), None);
+    }
+
+    #[test]
+    fn only_switches_between_tabs_of_view() {
+        let mut view = BrowseView::new(BrowseState::Loaded(BrowseContent::Rediscover { tracks: vec![] }));
+        view.switch_tab(1);
+        assert_eq!(view.get_tab(), BrowseTab::Tracks);
+
+        let mut view = BrowseView::new(BrowseState::Loaded(BrowseContent::CollectionAlbums { albums: vec![] }));
+        assert!(view.get_tabs().is_empty());
+        view.switch_tab(1);
+        assert_eq!(view.get_tab(), BrowseTab::Tracks);
+    }
+}
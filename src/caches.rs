@@ -0,0 +1,56 @@
+use crate::i18n::{
+    tr,
+    Message,
+};
+
+/// Something kept on disk between runs, which is listed with its size in the cache view and can be purged from it,
+/// or with its `cache purge <name>` command.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CacheKind {
+    /// Cover art, which is fetched again the next time it is drawn.
+    Covers,
+    /// The queue that is picked up again on launch.
+    PlayerState,
+    /// Changes to My Collection and playlists that haven't been sent to Tidal yet.
+    PendingWrites,
+}
+
+impl CacheKind {
+    /// Every cache, in the order they are listed.
+    pub const ALL: [Self; 3] = [Self::Covers, Self::PlayerState, Self::PendingWrites];
+
+    /// Returns the name of this cache, as it is written in its purge command.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Covers => "covers",
+            Self::PlayerState => "player_state",
+            Self::PendingWrites => "pending_writes",
+        }
+    }
+
+    /// Returns what this cache holds, in the language of the UI.
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            Self::Covers => tr(Message::CacheCovers),
+            Self::PlayerState => tr(Message::CachePlayerState),
+            Self::PendingWrites => tr(Message::CachePendingWrites),
+        }
+    }
+
+    /// Returns the command that purges this cache, as it is listed in the commands menu.
+    pub fn get_purge_command(&self) -> String {
+        format!("cache purge {}", self.get_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_purge_commands_after_caches() {
+        let commands: Vec<String> = CacheKind::ALL.iter().map(CacheKind::get_purge_command).collect();
+
+        assert_eq!(commands, ["cache purge covers", "cache purge player_state", "cache purge pending_writes"]);
+    }
+}
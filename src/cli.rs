@@ -0,0 +1,260 @@
+use crate::ipc::IpcCommand;
+
+/// Usage text printed for `--help` and invalid arguments.
+pub const USAGE: &str = "\
+Usage:
+  tidal-tui [--profile-startup]  Launch the TUI, printing how long each step of starting it took once it is closed.
+  tidal-tui play [OPTIONS]       Play without launching the TUI, or in the running instance if there is one.
+  tidal-tui diagnostics          Check the connection, login, audio output, and config directory.
+  tidal-tui status [--format F]  Print the running instance's status as json (default) or waybar JSON.
+  tidal-tui ctl <COMMAND>        Send a command to the running instance: play-pause, next, previous,
+                                 volume-up, volume-down, or raise.
+  tidal-tui lastfm-login         Allow the Last.fm API account in the config to scrobble for you.
+
+Play options (exactly one of --track, --album, or --collection):
+  --track <ID>        Play a single track.
+  --album <ID>        Play an album.
+  --collection        Play the tracks in your collection.
+  --shuffle           Shuffle the tracks before playing.
+  --volume <0-100>    Set the playback volume.
+
+  -h, --help          Print this message.";
+
+/// A command parsed from the command line arguments.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Launch the TUI, printing how long each step of starting it took once it is closed if `profile_startup` is true.
+    Tui {
+        profile_startup: bool,
+    },
+    /// Play something without launching the TUI.
+    Play(PlayArgs),
+    /// Print the results of the diagnostic checks.
+    Diagnostics,
+    /// Print the running instance's status.
+    Status(StatusFormat),
+    /// Send a command to the running instance.
+    Ctl(IpcCommand),
+    /// Log in to Last.fm for scrobbling.
+    LastfmLogin,
+    /// Print the usage text.
+    Help,
+}
+
+/// Arguments of the `play` command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayArgs {
+    pub source: PlaySource,
+    pub shuffle: bool,
+    pub volume: Option<u32>,
+}
+
+/// What the `play` command should play.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaySource {
+    Track(String),
+    Album(String),
+    Collection,
+}
+
+/// How the `status` command prints the status.
+#[derive(Debug, PartialEq)]
+pub enum StatusFormat {
+    /// The same JSON as the status endpoint.
+    Json,
+    /// JSON for a waybar custom module.
+    Waybar,
+}
+
+impl Command {
+    /// Parses a `Command` from the command line arguments (excluding the binary name).
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+
+        match args.next().as_deref() {
+            None => Ok(Self::Tui { profile_startup: false }),
+            Some("-h") | Some("--help") => Ok(Self::Help),
+            Some("--profile-startup") => match args.next() {
+                Some(other) => Err(format!("Unknown option: {other}")),
+                None => Ok(Self::Tui { profile_startup: true }),
+            },
+            Some("play") => Ok(Self::Play(PlayArgs::parse(args)?)),
+            Some("diagnostics") => Ok(Self::Diagnostics),
+            Some("status") => Ok(Self::Status(StatusFormat::parse(args)?)),
+            Some("ctl") => {
+                let name = args.next().ok_or(String::from("Missing command for ctl"))?;
+                match IpcCommand::parse(&name) {
+                    Some(IpcCommand::Status) | None => Err(format!("Unknown ctl command: {name}")),
+                    Some(command) => Ok(Self::Ctl(command)),
+                }
+            },
+            Some("lastfm-login") => Ok(Self::LastfmLogin),
+            Some(other) => Err(format!("Unknown command: {other}")),
+        }
+    }
+}
+
+impl StatusFormat {
+    /// Parses the options of the `status` command.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut format = Self::Json;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--format" => {
+                    format = match PlayArgs::value_of(&arg, args.next())?.as_str() {
+                        "json" => Self::Json,
+                        "waybar" => Self::Waybar,
+                        other => return Err(format!("Unknown format: {other}")),
+                    };
+                },
+                other => return Err(format!("Unknown option: {other}")),
+            }
+        }
+
+        Ok(format)
+    }
+}
+
+impl PlayArgs {
+    /// Parses the options of the `play` command.
+    pub(crate) fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut source = None;
+        let mut shuffle = false;
+        let mut volume = None;
+
+        while let Some(arg) = args.next() {
+            let new_source = match arg.as_str() {
+                "--track" => Some(PlaySource::Track(Self::value_of(&arg, args.next())?)),
+                "--album" => Some(PlaySource::Album(Self::value_of(&arg, args.next())?)),
+                "--collection" => Some(PlaySource::Collection),
+                "--shuffle" => {
+                    shuffle = true;
+                    None
+                },
+                "--volume" => {
+                    let value = Self::value_of(&arg, args.next())?;
+                    let parsed_volume = value.parse::<u32>()
+                        .ok()
+                        .filter(|v| *v <= 100)
+                        .ok_or(format!("Invalid volume: {value}"))?;
+                    volume = Some(parsed_volume);
+                    None
+                },
+                other => return Err(format!("Unknown option: {other}")),
+            };
+
+            if let Some(new_source) = new_source {
+                if source.is_some() {
+                    return Err(String::from("Only one of --track, --album, or --collection can be given"));
+                }
+                source = Some(new_source);
+            }
+        }
+
+        Ok(Self {
+            source: source.ok_or(String::from("One of --track, --album, or --collection is required"))?,
+            shuffle,
+            volume,
+        })
+    }
+
+    /// Returns these arguments as the options they were parsed from, to hand them off to the running instance.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = match &self.source {
+            PlaySource::Track(id) => vec![String::from("--track"), id.clone()],
+            PlaySource::Album(id) => vec![String::from("--album"), id.clone()],
+            PlaySource::Collection => vec![String::from("--collection")],
+        };
+        if self.shuffle {
+            args.push(String::from("--shuffle"));
+        }
+        if let Some(volume) = self.volume {
+            args.extend([String::from("--volume"), volume.to_string()]);
+        }
+
+        args
+    }
+
+    /// Returns the value given for the option `option`, or an error if it is missing.
+    fn value_of(option: &str, value: Option<String>) -> Result<String, String> {
+        value
+            .filter(|v| !v.starts_with("--"))
+            .ok_or(format!("Missing value for {option}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `args` as a command.
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// Parses `args` as the options of the `play` command.
+    fn parse_play(args: &[&str]) -> Result<PlayArgs, String> {
+        PlayArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_each_command() {
+        assert_eq!(parse(&[]), Ok(Command::Tui { profile_startup: false }));
+        assert_eq!(parse(&["--profile-startup"]), Ok(Command::Tui { profile_startup: true }));
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help));
+        assert_eq!(parse(&["diagnostics"]), Ok(Command::Diagnostics));
+        assert_eq!(parse(&["status"]), Ok(Command::Status(StatusFormat::Json)));
+        assert_eq!(parse(&["status", "--format", "waybar"]), Ok(Command::Status(StatusFormat::Waybar)));
+        assert_eq!(parse(&["ctl", "play-pause"]), Ok(Command::Ctl(IpcCommand::PlayPause)));
+        assert_eq!(parse(&["lastfm-login"]), Ok(Command::LastfmLogin));
+        assert_eq!(
+            parse(&["play", "--track", "42", "--shuffle"]),
+            Ok(Command::Play(PlayArgs {
+                source: PlaySource::Track(String::from("42")),
+                shuffle: true,
+                volume: None,
+            })),
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_options() {
+        assert_eq!(parse(&["stop"]), Err(String::from("Unknown command: stop")));
+        assert_eq!(parse(&["--profile-startup", "--verbose"]), Err(String::from("Unknown option: --verbose")));
+        assert_eq!(parse(&["status", "--format", "xml"]), Err(String::from("Unknown format: xml")));
+        assert_eq!(parse(&["status", "--verbose"]), Err(String::from("Unknown option: --verbose")));
+        assert_eq!(parse(&["ctl"]), Err(String::from("Missing command for ctl")));
+        assert_eq!(parse(&["ctl", "status"]), Err(String::from("Unknown ctl command: status")));
+        assert_eq!(parse_play(&["--collection", "--loop"]), Err(String::from("Unknown option: --loop")));
+    }
+
+    #[test]
+    fn parses_play_options() {
+        assert_eq!(
+            parse_play(&["--volume", "100", "--collection"]),
+            Ok(PlayArgs {
+                source: PlaySource::Collection,
+                shuffle: false,
+                volume: Some(100),
+            }),
+        );
+        assert_eq!(parse_play(&["--album", "7"]).map(|args| args.source), Ok(PlaySource::Album(String::from("7"))));
+        assert_eq!(parse_play(&["--shuffle"]), Err(String::from("One of --track, --album, or --collection is required")));
+        assert_eq!(
+            parse_play(&["--track", "1", "--album", "2"]),
+            Err(String::from("Only one of --track, --album, or --collection can be given")),
+        );
+    }
+
+    #[test]
+    fn rejects_missing_and_invalid_values() {
+        assert_eq!(parse_play(&["--collection", "--volume"]), Err(String::from("Missing value for --volume")));
+        assert_eq!(parse_play(&["--volume", "--collection"]), Err(String::from("Missing value for --volume")));
+        assert_eq!(parse_play(&["--collection", "--volume", "101"]), Err(String::from("Invalid volume: 101")));
+        assert_eq!(parse_play(&["--collection", "--volume", "loud"]), Err(String::from("Invalid volume: loud")));
+        assert_eq!(parse_play(&["--track"]), Err(String::from("Missing value for --track")));
+        assert_eq!(parse(&["status", "--format"]), Err(String::from("Missing value for --format")));
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    env,
+    io::{
+        self,
+        Write,
+    },
+    process::{
+        Command,
+        Stdio,
+    },
+};
+
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine,
+};
+
+use crate::is_ssh_session;
+
+/// Commands that copy their standard input to the system clipboard, tried in order until one works.
+#[cfg(target_os = "macos")]
+const COPY_COMMANDS: &[&[&str]] = &[&["pbcopy"]];
+
+/// Commands that copy their standard input to the system clipboard, tried in order until one works.
+#[cfg(target_os = "windows")]
+const COPY_COMMANDS: &[&[&str]] = &[&["clip"]];
+
+/// Commands that copy their standard input to the system clipboard, tried in order until one works.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const COPY_COMMANDS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copies `text` to the system clipboard using the platform's clipboard command.
+///
+/// Over SSH, or if no clipboard command works, the terminal is asked to copy it instead with an OSC 52
+/// escape sequence, which reaches the clipboard of the machine the terminal is running on.
+pub fn copy(text: &str) -> Result<(), String> {
+    // A clipboard command would copy to the remote machine's clipboard, which the user can't paste from.
+    if !is_ssh_session() {
+        for command in COPY_COMMANDS {
+            if run_copy_command(command, text).is_ok() {
+                return Ok(());
+            }
+        }
+    }
+
+    let sequence = get_osc52_sequence(text, env::var_os("TMUX").is_some());
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(sequence.as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(|e| format!("Unable to write to the terminal: {e}"))
+}
+
+/// Returns the OSC 52 escape sequence that asks the terminal to copy `text` to its clipboard.
+///
+/// tmux doesn't pass unknown escape sequences through to the terminal it is running in,
+/// so inside it (`is_tmux`) the sequence is wrapped in tmux's passthrough sequence, with each escape doubled.
+fn get_osc52_sequence(text: &str, is_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    if is_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Runs `command`, writing `text` to its standard input.
+fn run_copy_command(command: &[&str], text: &str) -> Result<(), String> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    child.stdin
+        .take()
+        .ok_or(String::from("Unable to open stdin"))?
+        .write_all(text.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", command[0], status));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_osc52_sequences() {
+        assert_eq!(get_osc52_sequence("Airbag", false), "\x1b]52;c;QWlyYmFn\x07");
+        assert_eq!(get_osc52_sequence("Airbag", true), "\x1bPtmux;\x1b\x1b]52;c;QWlyYmFn\x07\x1b\\");
+    }
+}
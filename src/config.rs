@@ -0,0 +1,714 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+};
+
+use rtidalapi::AudioQuality;
+use serde::Deserialize;
+
+use crate::{
+    format::Locale,
+    is_ssh_session,
+    keymap::{
+        Action,
+        KeySequence,
+    },
+    links::TidalLink,
+    resample::ResamplerQuality,
+    shuffle::ShuffleMode,
+    template::Template,
+    ui::Theme,
+};
+
+/// User configuration, loaded from `config.toml` in the config directory.
+///
+/// Every field has a default, so the config file (and any section within it) is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub now_playing: NowPlayingConfig,
+    pub player: PlayerConfig,
+    pub layout: LayoutConfig,
+    pub hydration: HydrationConfig,
+    pub formats: FormatsConfig,
+    pub status_server: StatusServerConfig,
+    pub ipc: IpcConfig,
+    pub mqtt: MqttConfig,
+    pub hooks: HooksConfig,
+    pub scrobbling: ScrobblingConfig,
+    pub bandwidth: BandwidthConfig,
+    pub keyboard: KeyboardConfig,
+    pub low_bandwidth: LowBandwidthConfig,
+    pub accessibility: AccessibilityConfig,
+    pub theme: ThemeConfig,
+    /// How numbers, long durations, and dates are written.
+    pub locale: Locale,
+    /// The language of the UI's text (e.g. "de"), which is the system's language if it isn't set.
+    pub language: Option<String>,
+    /// Keys bound to actions, replacing the default keys of each action listed.
+    pub keymap: HashMap<Action, Vec<KeySequence>>,
+    /// Pages of pinned items, written as `[[pages]]` sections.
+    pub pages: Vec<PageConfig>,
+    /// What each quick slot plays, by its number from 1 to 9, written in a `[slots]` section.
+    pub slots: HashMap<String, SlotConfig>,
+}
+
+/// A page of pinned playlists, albums, artists, users, and tracks, opened from the pages menu or at startup.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PageConfig {
+    pub name: String,
+    /// The page's items, in the order they are listed.
+    pub items: Vec<TidalLink>,
+}
+
+/// What a quick slot swaps the queue to.
+///
+/// In the config file, slots are written as `"collection"`, or a playlist or album's share link or id.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub enum SlotConfig {
+    /// My Collection - Tracks.
+    Collection,
+    /// A playlist or album.
+    Link(TidalLink),
+}
+
+impl TryFrom<String> for SlotConfig {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.trim() == "collection" {
+            return Ok(Self::Collection);
+        }
+
+        match TidalLink::parse(&value) {
+            Some(link @ (TidalLink::Playlist(_) | TidalLink::Album(_))) => Ok(Self::Link(link)),
+            _ => Err(format!("\"{value}\" isn't \"collection\" or a link to a playlist or album")),
+        }
+    }
+}
+
+/// Layout of the Now Playing bar.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct NowPlayingConfig {
+    /// Whether the "playing from" context is shown in the block's title.
+    pub playing_from_in_title: bool,
+    /// Whether the playing track's album cover is shown at the left of the bar,
+    /// drawn with half blocks in terminals that can't draw images.
+    pub show_cover: bool,
+    /// How the track and album titles scroll when they are too long for their column.
+    pub marquee: MarqueeConfig,
+    /// The columns of the bar, from left to right.
+    pub columns: Vec<NowPlayingColumn>,
+}
+
+/// How keys are read from the terminal.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct KeyboardConfig {
+    /// Whether the kitty keyboard protocol is used when the terminal supports it, to tell more key combinations apart.
+    pub enhanced: bool,
+}
+
+/// Settings for using the app with a screen reader or braille display.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    /// Whether what happens (e.g. track changes and menu selections) is written out as plain text lines,
+    /// and state shown by color is also shown some other way.
+    pub screen_reader: bool,
+    /// The file that screen reader announcements are appended to, instead of standard error.
+    pub output_file: Option<String>,
+}
+
+/// Drawing less often and more simply, to send less to terminals in remote sessions.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LowBandwidthConfig {
+    /// When drawing is cut down.
+    pub mode: LowBandwidthMode,
+    /// While drawing is cut down, the screen is redrawn at most once in this many milliseconds,
+    /// except right after a key is pressed.
+    pub redraw_interval_ms: u64,
+}
+
+/// When drawing is cut down to send less to the terminal.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LowBandwidthMode {
+    /// Only over SSH.
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+/// The colors the UI is drawn in.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: ThemeName,
+    /// Whether the accent color (cyan) is taken from the cover of the playing track's album instead.
+    /// Only the default theme has an accent color.
+    pub accent_from_cover: bool,
+}
+
+/// Which theme the UI is drawn in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    /// `Monochrome` if the `NO_COLOR` environment variable is set or the terminal can't show colors,
+    /// and `Default` otherwise.
+    #[default]
+    Auto,
+    Default,
+    HighContrast,
+    Monochrome,
+}
+
+/// Scrolling of the track and album titles in the Now Playing bar.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MarqueeConfig {
+    /// Whether titles too long for their column scroll back and forth, instead of being cut short.
+    pub enabled: bool,
+    /// How many columns per second titles scroll.
+    pub speed: f64,
+    /// How many milliseconds titles wait at each end before scrolling back.
+    pub pause_ms: u64,
+}
+
+/// Playback behaviour settings.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct PlayerConfig {
+    /// Pressing previous after this many seconds into a track restarts it instead of going back a track.
+    pub previous_restart_threshold_secs: u64,
+    /// Pressing previous twice within this many milliseconds always goes back a track.
+    pub previous_double_press_ms: u64,
+    /// How many upcoming tracks have their stream prefetched.
+    pub prefetch_lookahead: usize,
+    /// How many upcoming tracks have their stream prefetched while shuffling.
+    pub shuffle_prefetch_lookahead: usize,
+    /// How the queue is ordered when shuffling.
+    pub shuffle_mode: ShuffleMode,
+    /// Whether to ask before playing something else replaces a queue that hasn't finished.
+    pub confirm_replace_queue: bool,
+    /// The audio host to play through (e.g. "ALSA", "PulseAudio", or "PipeWire"), instead of the system default.
+    pub output_host: Option<String>,
+    /// The name of the output device (or sink) to play to, instead of the host's default device.
+    pub output_device: Option<String>,
+    /// How audio is resampled when the output device can't play the track's sample rate.
+    pub resampler: ResamplerQuality,
+    /// Whether dither is added when the output device has a lower bit depth than the track, or when resampling.
+    pub dither: bool,
+    /// Whether the queue the app was closed with is picked up again on launch, and whether it starts playing.
+    pub resume_on_launch: ResumeOnLaunch,
+    /// How often the queue and where it is up to are saved while it changes, so that a crash doesn't lose them.
+    /// They are also saved whenever the track changes or playback is paused or stopped, and when the app is closed.
+    pub state_save_interval_secs: u64,
+    /// Whether downloading the current track is retried from where it left off whenever the network drops,
+    /// until all of it is buffered, instead of giving up on the rest of it.
+    pub download_full_track: bool,
+    /// Genres skipped while shuffling or playing a radio, once that is turned on with the genre filter toggle.
+    pub excluded_genres: Vec<String>,
+}
+
+/// What is done with the queue the app was last closed with when it is launched again.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResumeOnLaunch {
+    /// It isn't restored.
+    #[default]
+    Off,
+    /// It is restored where it was left off, paused.
+    Paused,
+    /// It is restored where it was left off, and starts playing.
+    Playing,
+    /// It is restored where it was left off, playing only if it was playing when the app was closed.
+    Last,
+}
+
+/// Layout of the panes above the Now Playing bar.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Whether a header with the logged in user, their subscription, and the connection status is shown at the top.
+    pub show_header: bool,
+    /// Whether the queue is shown next to the collection on startup.
+    pub show_queue: bool,
+    /// The name of the page opened on startup in place of My Collection, which can still be gone back to.
+    pub start_page: Option<String>,
+    /// The queue is only shown when the terminal is at least this many columns wide.
+    pub queue_min_width: u16,
+    /// Percentage of the width taken up by the queue when it is shown.
+    pub queue_width_percent: u16,
+    /// Whether the tracks played before the current one are shown above it in the queue on startup.
+    pub show_queue_history: bool,
+    /// Whether track tables have a column with how many times each track has been played.
+    pub show_play_count: bool,
+    /// Whether track tables have a column with when each track was last played.
+    pub show_last_played: bool,
+    /// Whether track tables show a small album cover next to each track, in terminals that can draw images.
+    pub show_thumbnails: bool,
+    /// Whether track tables show each track's composer, work, and movement instead of its artist and title,
+    /// for classical music.
+    pub classical_mode: bool,
+    /// Whether My Collection - Tracks starts out grouped by album.
+    pub group_by_album: bool,
+}
+
+/// Settings for fetching track info in the background.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct HydrationConfig {
+    /// How many tracks' info can be fetched at once. Lower this if Tidal starts rate limiting requests.
+    pub concurrency: usize,
+    /// Whether the info of every track in the collection is fetched in the background, not just what is on screen.
+    pub background: bool,
+}
+
+/// How what is playing is described by each place that shows it, as templates like `"{artist} — {title} [{quality}]"`.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct FormatsConfig {
+    /// The terminal's title while the TUI is running. The title is left alone if this isn't set.
+    pub terminal_title: Option<Template>,
+    /// The status line printed by `tidal-tui play` whenever playback changes.
+    pub headless_status: Template,
+}
+
+/// A local HTTP endpoint (`GET /status`) returning the player's state as JSON, for status bars and scripts.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct StatusServerConfig {
+    pub enabled: bool,
+    /// The address to listen on. Only listen on localhost unless other machines should see what is playing.
+    pub address: String,
+}
+
+/// A local socket that `tidal-tui status` and `tidal-tui ctl` use to talk to the running instance.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct IpcConfig {
+    pub enabled: bool,
+}
+
+/// Publishing the player's state to an MQTT broker and taking commands from it, for home automation.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// The client id to connect with, which must be different for each instance connected to the same broker.
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The topic the player's state is published to as JSON (the same as the status endpoint's), whenever it changes.
+    pub state_topic: String,
+    /// The topic commands are read from: `play-pause`, `next`, `previous`, `volume-up`, or `volume-down`.
+    pub command_topic: String,
+    /// The topic that says whether the app is running, as `online` or `offline`.
+    pub availability_topic: String,
+}
+
+/// Shell commands run when playback changes, with what is playing in `TIDAL_TUI_*` environment variables.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run when a different track starts playing.
+    pub track_changed: Option<String>,
+    /// Run when playback is paused.
+    pub paused: Option<String>,
+    /// Run when playback stops, because the queue finished or was cleared.
+    pub stopped: Option<String>,
+}
+
+/// Scrobbling what is listened to to Last.fm and ListenBrainz. Each service is used once its section is filled in.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct ScrobblingConfig {
+    /// A track is scrobbled once this percentage of it has been listened to, or `threshold_secs` have been,
+    /// whichever comes first. Tracks shorter than 30 seconds are never scrobbled.
+    pub threshold_percent: u32,
+    pub threshold_secs: u64,
+    /// Whether adding a track to My Collection loves it, and removing it takes the love back.
+    pub love_favorites: bool,
+    pub lastfm: Option<LastfmConfig>,
+    pub listenbrainz: Option<ListenBrainzConfig>,
+}
+
+/// A Last.fm API account, and the session key that lets it scrobble for the user.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LastfmConfig {
+    pub api_key: String,
+    pub api_secret: String,
+    /// Printed by `tidal-tui lastfm-login`. Nothing is sent to Last.fm until it is set.
+    #[serde(default)]
+    pub session_key: Option<String>,
+}
+
+/// A ListenBrainz account.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ListenBrainzConfig {
+    /// The user token from the ListenBrainz settings page.
+    pub token: String,
+}
+
+/// Limits on how much audio is streamed, for metered connections.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BandwidthConfig {
+    /// Once this many megabytes have been streamed in a day (UTC), the quality is lowered to `capped_quality`.
+    pub daily_cap_mb: Option<u64>,
+    /// Once this many megabytes have been streamed in a month (UTC), the quality is lowered to `capped_quality`.
+    pub monthly_cap_mb: Option<u64>,
+    /// The quality used while over a cap.
+    pub capped_quality: AudioQuality,
+}
+
+/// A single column of the Now Playing bar.
+#[derive(Debug, Deserialize)]
+pub struct NowPlayingColumn {
+    /// Relative width of this column compared to the other columns.
+    #[serde(default = "NowPlayingColumn::default_width")]
+    pub width: u16,
+    #[serde(default)]
+    pub align: Align,
+    /// The elements in this column, one per line, from top to bottom.
+    pub elements: Vec<NowPlayingElement>,
+}
+
+/// Horizontal alignment of text within a Now Playing column.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// An element that can be placed in the Now Playing bar.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NowPlayingElement {
+    /// The current track's title.
+    Title,
+    /// The current track's artist.
+    Artist,
+    /// The current track's album.
+    Album,
+    /// Shuffle, play/pause, and repeat status.
+    Controls,
+    /// Progress bar with the current position and the track's duration.
+    Progress,
+    /// The player's volume.
+    Volume,
+    /// The audio quality setting.
+    Quality,
+    /// The actual quality of the current stream (e.g. 24-Bit 96kHz FLAC).
+    StreamQuality,
+    /// Where the current queue is playing from (e.g. Tracks).
+    PlayingFrom,
+    /// The next track in the queue.
+    UpNext,
+    /// An empty line.
+    Empty,
+}
+
+impl Config {
+    /// Name of the config file within the config directory.
+    const FILE_NAME: &str = "config.toml";
+
+    /// Loads the config file from `config_folder_path`.
+    ///
+    /// If no config file exists, the default config is returned.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let config_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        if !config_file.exists() {
+            return Ok(Self::default());
+        }
+
+        let toml_str = fs::read_to_string(&config_file)
+            .map_err(|e| format!("Unable to read {}: {e}", config_file.display()))?;
+
+        toml::from_str(&toml_str)
+            .map_err(|e| format!("Unable to parse {}: {e}", config_file.display()))
+    }
+
+    /// Returns the settings listed in the settings view, by their names in the config file,
+    /// with `None` for those that aren't set.
+    pub fn get_settings(&self) -> Vec<(&'static str, Option<String>)> {
+        let set = |value: &dyn ToString| Some(value.to_string());
+
+        vec![
+            ("language", self.language.clone()),
+            ("player.previous_restart_threshold_secs", set(&self.player.previous_restart_threshold_secs)),
+            ("player.prefetch_lookahead", set(&self.player.prefetch_lookahead)),
+            ("player.confirm_replace_queue", set(&self.player.confirm_replace_queue)),
+            ("player.output_host", self.player.output_host.clone()),
+            ("player.output_device", self.player.output_device.clone()),
+            ("player.dither", set(&self.player.dither)),
+            ("player.download_full_track", set(&self.player.download_full_track)),
+            ("layout.show_header", set(&self.layout.show_header)),
+            ("layout.show_queue", set(&self.layout.show_queue)),
+            ("layout.start_page", self.layout.start_page.clone()),
+            ("layout.queue_min_width", set(&self.layout.queue_min_width)),
+            ("layout.queue_width_percent", set(&self.layout.queue_width_percent)),
+            ("layout.show_queue_history", set(&self.layout.show_queue_history)),
+            ("layout.show_play_count", set(&self.layout.show_play_count)),
+            ("layout.show_last_played", set(&self.layout.show_last_played)),
+            ("layout.show_thumbnails", set(&self.layout.show_thumbnails)),
+            ("now_playing.show_cover", set(&self.now_playing.show_cover)),
+            ("layout.classical_mode", set(&self.layout.classical_mode)),
+            ("layout.group_by_album", set(&self.layout.group_by_album)),
+            ("hydration.concurrency", set(&self.hydration.concurrency)),
+            ("hydration.background", set(&self.hydration.background)),
+            ("status_server.enabled", set(&self.status_server.enabled)),
+            ("ipc.enabled", set(&self.ipc.enabled)),
+            ("mqtt.enabled", set(&self.mqtt.enabled)),
+            ("accessibility.screen_reader", set(&self.accessibility.screen_reader)),
+        ]
+    }
+}
+
+impl Default for NowPlayingConfig {
+    fn default() -> Self {
+        Self {
+            playing_from_in_title: true,
+            show_cover: false,
+            marquee: MarqueeConfig::default(),
+            columns: vec![
+                NowPlayingColumn {
+                    width: 2,
+                    align: Align::Left,
+                    elements: vec![
+                        NowPlayingElement::Title,
+                        NowPlayingElement::Artist,
+                        NowPlayingElement::Album,
+                    ],
+                },
+                NowPlayingColumn {
+                    width: 3,
+                    align: Align::Center,
+                    elements: vec![
+                        NowPlayingElement::Controls,
+                        NowPlayingElement::UpNext,
+                        NowPlayingElement::Progress,
+                    ],
+                },
+                NowPlayingColumn {
+                    width: 2,
+                    align: Align::Right,
+                    elements: vec![
+                        NowPlayingElement::Volume,
+                        NowPlayingElement::Quality,
+                        NowPlayingElement::StreamQuality,
+                    ],
+                },
+            ],
+        }
+    }
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            previous_restart_threshold_secs: 3,
+            previous_double_press_ms: 1000,
+            prefetch_lookahead: 1,
+            shuffle_prefetch_lookahead: 3,
+            shuffle_mode: ShuffleMode::Uniform,
+            confirm_replace_queue: true,
+            output_host: None,
+            output_device: None,
+            resampler: ResamplerQuality::Sinc,
+            dither: true,
+            resume_on_launch: ResumeOnLaunch::Off,
+            state_save_interval_secs: 10,
+            download_full_track: false,
+            excluded_genres: vec![],
+        }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            show_header: false,
+            show_queue: false,
+            start_page: None,
+            queue_min_width: 140,
+            queue_width_percent: 35,
+            show_queue_history: true,
+            show_play_count: false,
+            show_last_played: false,
+            show_thumbnails: false,
+            classical_mode: false,
+            group_by_album: false,
+        }
+    }
+}
+
+impl Default for HydrationConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            background: true,
+        }
+    }
+}
+
+impl Default for FormatsConfig {
+    fn default() -> Self {
+        Self {
+            terminal_title: None,
+            headless_status: "{state}: {title} - {artist} ({album})".parse().expect("default template should be valid"),
+        }
+    }
+}
+
+impl Default for KeyboardConfig {
+    fn default() -> Self {
+        Self {
+            enhanced: true,
+        }
+    }
+}
+
+impl Default for LowBandwidthConfig {
+    fn default() -> Self {
+        Self {
+            mode: LowBandwidthMode::Auto,
+            redraw_interval_ms: 2000,
+        }
+    }
+}
+
+impl Default for MarqueeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            speed: 4.0,
+            pause_ms: 2000,
+        }
+    }
+}
+
+impl Default for BandwidthConfig {
+    fn default() -> Self {
+        Self {
+            daily_cap_mb: None,
+            monthly_cap_mb: None,
+            capped_quality: AudioQuality::Low320,
+        }
+    }
+}
+
+impl Default for StatusServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: String::from("127.0.0.1:7890"),
+        }
+    }
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::from("localhost"),
+            port: 1883,
+            client_id: String::from("tidal-tui"),
+            username: None,
+            password: None,
+            state_topic: String::from("tidal-tui/state"),
+            command_topic: String::from("tidal-tui/command"),
+            availability_topic: String::from("tidal-tui/availability"),
+        }
+    }
+}
+
+impl Default for ScrobblingConfig {
+    fn default() -> Self {
+        Self {
+            threshold_percent: 50,
+            threshold_secs: 240,
+            love_favorites: true,
+            lastfm: None,
+            listenbrainz: None,
+        }
+    }
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+        }
+    }
+}
+
+impl NowPlayingConfig {
+    /// Returns the number of lines needed to fit the tallest column.
+    pub fn num_lines(&self) -> u16 {
+        self.columns
+            .iter()
+            .map(|c| c.elements.len() as u16)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl LowBandwidthConfig {
+    /// Returns true if drawing should be cut down, which with `Auto` is when running over SSH.
+    pub fn is_enabled(&self) -> bool {
+        match self.mode {
+            LowBandwidthMode::Auto => is_ssh_session(),
+            LowBandwidthMode::On => true,
+            LowBandwidthMode::Off => false,
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Returns the theme to draw the UI in, detecting it from the environment with `Auto`.
+    pub fn get_theme(&self) -> Theme {
+        match self.name {
+            ThemeName::Auto => Theme::detect(),
+            ThemeName::Default => Theme::Default,
+            ThemeName::HighContrast => Theme::HighContrast,
+            ThemeName::Monochrome => Theme::Monochrome,
+        }
+    }
+}
+
+impl NowPlayingColumn {
+    fn default_width() -> u16 {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_slots() {
+        let config: Config = toml::from_str(r#"
+            [slots]
+            1 = "collection"
+            2 = "https://tidal.com/browse/album/77646168"
+        "#).unwrap();
+
+        assert_eq!(config.slots.get("1"), Some(&SlotConfig::Collection));
+        assert_eq!(config.slots.get("2"), Some(&SlotConfig::Link(TidalLink::Album(String::from("77646168")))));
+        assert!(toml::from_str::<Config>("[slots]\n3 = \"https://tidal.com/browse/track/77646170\"").is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use std::io::{
+    self,
+    Write,
+};
+
+#[cfg(feature = "images")]
+use image::{
+    imageops::FilterType,
+    RgbImage,
+};
+#[cfg(feature = "images")]
+use ratatui::style::Color;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    cover_cache::CoverCache,
+    graphics::{
+        GraphicsProtocol,
+        Thumbnails,
+    },
+    AppEvent,
+};
+
+/// The playing track's album cover, drawn at the left of the Now Playing bar.
+///
+/// Terminals that can draw images get the cover as it is, and the rest get it drawn with half blocks,
+/// two pixels to a cell, which needs the images feature.
+pub struct CoverArt {
+    #[cfg(feature = "images")]
+    covers: CoverCache,
+    /// Draws the cover in terminals that can draw images, or `None` to draw it with half blocks.
+    images: Option<Thumbnails>,
+    /// The cover last drawn with half blocks, scaled to fit where it was drawn.
+    #[cfg(feature = "images")]
+    scaled: Option<ScaledCover>,
+}
+
+/// A cover decoded and scaled to a number of cells.
+#[cfg(feature = "images")]
+struct ScaledCover {
+    url: String,
+    width: u16,
+    height: u16,
+    /// The cover's pixels, or `None` if it couldn't be decoded.
+    pixels: Option<RgbImage>,
+}
+
+impl CoverArt {
+    /// The smallest cover size fetched for the Now Playing bar, in pixels.
+    pub const COVER_SIZE: u32 = 320;
+
+    /// Returns the cover drawn with `protocol`, or with half blocks if there is none, fetching it through `covers`.
+    pub fn new(protocol: Option<GraphicsProtocol>, covers: CoverCache) -> Self {
+        Self {
+            images: protocol.map(|protocol| Thumbnails::new(protocol, covers.clone())),
+            #[cfg(feature = "images")]
+            covers,
+            #[cfg(feature = "images")]
+            scaled: None,
+        }
+    }
+
+    /// Returns where the cover goes in a Now Playing bar drawn in `area`: at its left, as tall as it is inside its
+    /// borders and twice as wide, since cells are about twice as tall as they are wide.
+    pub fn get_area(area: Rect) -> Rect {
+        let height = area.height.saturating_sub(2);
+        let width = (height * 2).min(area.width.saturating_sub(4));
+
+        Rect::new(area.x + 2, area.y + 1, width, height)
+    }
+
+    /// Draws the cover at `url` in `area`, either into `buf` with half blocks, or placing it to be written after the frame.
+    ///
+    /// The cover is fetched if it hasn't been, and a rerender is sent through `tx` once it has.
+    pub fn draw(&mut self, buf: &mut Buffer, area: Rect, url: &str, tx: &mpsc::Sender<AppEvent>) {
+        if let Some(images) = &mut self.images {
+            images.place_in(area, url);
+            return;
+        }
+
+        self.draw_scaled(buf, area, url, tx);
+    }
+
+    /// Draws the cover at `url` into `area` of `buf` with half blocks, decoding and scaling it if it hasn't been
+    /// for this area.
+    #[cfg(feature = "images")]
+    fn draw_scaled(&mut self, buf: &mut Buffer, area: Rect, url: &str, tx: &mpsc::Sender<AppEvent>) {
+        let is_scaled = self.scaled.as_ref()
+            .is_some_and(|scaled| scaled.url == url && scaled.width == area.width && scaled.height == area.height);
+        if !is_scaled {
+            let Some(image) = self.covers.get(url, tx) else {
+                return;
+            };
+            // Each cell has a pixel in its top half and one in its bottom half.
+            let pixels = image::load_from_memory(&image).ok()
+                .map(|image| image.resize(u32::from(area.width), u32::from(area.height) * 2, FilterType::Triangle).to_rgb8());
+
+            self.scaled = Some(ScaledCover {
+                url: url.to_string(),
+                width: area.width,
+                height: area.height,
+                pixels,
+            });
+        }
+
+        if let Some(pixels) = self.scaled.as_ref().and_then(|scaled| scaled.pixels.as_ref()) {
+            draw_half_blocks(buf, area, pixels);
+        }
+    }
+
+    /// Covers can only be decoded with the images feature, so without it there is nothing to draw with half blocks.
+    #[cfg(not(feature = "images"))]
+    fn draw_scaled(&mut self, _buf: &mut Buffer, _area: Rect, _url: &str, _tx: &mpsc::Sender<AppEvent>) {}
+
+    /// Writes the cover placed while drawing the last frame to `out`, in terminals that can draw images.
+    pub fn flush(&mut self, out: &mut impl Write, tx: &mpsc::Sender<AppEvent>) -> io::Result<()> {
+        match &mut self.images {
+            Some(images) => images.flush(out, tx),
+            None => Ok(()),
+        }
+    }
+
+    /// Forgets what is on screen, so that the cover is drawn again on the next flush.
+    pub fn invalidate(&mut self) {
+        if let Some(images) = &mut self.images {
+            images.invalidate();
+        }
+    }
+}
+
+/// Draws `pixels` into `area` of `buf`, with the colors of two pixels in each cell: one in the top half of a "▀",
+/// and one behind it.
+#[cfg(feature = "images")]
+fn draw_half_blocks(buf: &mut Buffer, area: Rect, pixels: &RgbImage) {
+    let color = |x: u32, y: u32| {
+        let [r, g, b] = pixels.get_pixel(x, y).0;
+        Color::Rgb(r, g, b)
+    };
+
+    for y in 0..area.height.min((pixels.height() / 2) as u16) {
+        for x in 0..area.width.min(pixels.width() as u16) {
+            let (px, py) = (u32::from(x), u32::from(y) * 2);
+            buf[(area.x + x, area.y + y)]
+                .set_symbol("▀")
+                .set_fg(color(px, py))
+                .set_bg(color(px, py + 1));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "images"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_two_pixels_per_cell() {
+        let pixels = RgbImage::from_fn(1, 2, |_, y| image::Rgb(if y == 0 { [255, 0, 0] } else { [0, 0, 255] }));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+
+        draw_half_blocks(&mut buf, Rect::new(1, 0, 1, 1), &pixels);
+
+        let cell = &buf[(1, 0)];
+        assert_eq!(cell.symbol(), "▀");
+        assert_eq!((cell.fg, cell.bg), (Color::Rgb(255, 0, 0), Color::Rgb(0, 0, 255)));
+        assert_eq!(buf[(0, 0)].symbol(), " ");
+    }
+}
@@ -0,0 +1,514 @@
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+
+#[cfg(feature = "images")]
+use image::{
+    imageops::FilterType,
+    ImageFormat,
+};
+#[cfg(feature = "images")]
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+
+use crate::{
+    bandwidth::BandwidthUsage,
+    AppEvent,
+};
+
+/// Cover art images fetched from Tidal, kept in memory and in the `covers` directory within the cache directory,
+/// so that each one is only downloaded once. Clones share the same covers.
+///
+/// Both are capped in size, dropping the covers that were used the longest time ago first.
+#[derive(Clone)]
+pub struct CoverCache {
+    covers: Arc<Mutex<Covers>>,
+    /// Where covers are saved between runs, or `None` if the directory couldn't be created.
+    dir: Option<PathBuf>,
+    /// How many bytes of covers are saved in `dir`, or `None` until it has been added up.
+    disk_usage: Arc<Mutex<Option<u64>>>,
+    client: reqwest::Client,
+    /// Downloaded covers count towards the bandwidth caps, like streamed audio does.
+    bandwidth_usage: Arc<Mutex<BandwidthUsage>>,
+    /// The thumbnails being made, and the ones that failed, which aren't tried again until the covers are purged.
+    thumbnails: Arc<Mutex<HashSet<PathBuf>>>,
+    /// The runtime thumbnails are made on, so that they can be asked for from any thread.
+    #[cfg(feature = "images")]
+    runtime: Handle,
+}
+
+enum CoverState {
+    Loading,
+    Loaded(Arc<Vec<u8>>),
+    /// The cover couldn't be fetched. It isn't tried again until the app is restarted.
+    Failed,
+}
+
+/// The covers kept in memory, which drops the least recently used loaded ones once they take up more than `max_bytes`.
+struct Covers {
+    /// Each cover's state by url, along with when it was last used.
+    states: HashMap<String, (CoverState, u64)>,
+    /// Counts up each time a cover is used, standing in for the time it was.
+    clock: u64,
+    /// How many bytes the loaded covers take up.
+    loaded_bytes: usize,
+    max_bytes: usize,
+}
+
+impl CoverCache {
+    /// Name of the directory covers are saved in within the cache directory.
+    const DIR_NAME: &str = "covers";
+
+    /// How many bytes of covers are kept in memory at most.
+    const MAX_MEMORY_BYTES: usize = 32 * 1024 * 1024;
+
+    /// How many bytes of covers are saved on disk at most.
+    const MAX_DISK_BYTES: u64 = 256 * 1024 * 1024;
+
+    /// Returns a cache saving covers within `cache_folder_path`, recording what it downloads in `bandwidth_usage`.
+    ///
+    /// Covers saved in `config_folder_path` by earlier versions are deleted in the background,
+    /// and the saved covers are trimmed down to size. This has to be called from within the Tokio runtime.
+    pub fn new(cache_folder_path: &str, config_folder_path: &str, bandwidth_usage: Arc<Mutex<BandwidthUsage>>) -> Self {
+        let dir = Path::new(cache_folder_path).join(Self::DIR_NAME);
+
+        let cache = Self {
+            covers: Arc::new(Mutex::new(Covers::new(Self::MAX_MEMORY_BYTES))),
+            dir: fs::create_dir_all(&dir).is_ok().then(|| dir.clone()),
+            disk_usage: Arc::new(Mutex::new(None)),
+            client: reqwest::Client::new(),
+            bandwidth_usage,
+            thumbnails: Arc::new(Mutex::new(HashSet::new())),
+            #[cfg(feature = "images")]
+            runtime: Handle::current(),
+        };
+
+        let legacy_dir = Path::new(config_folder_path).join(Self::DIR_NAME);
+        let cache_clone = cache.clone();
+        thread::spawn(move || {
+            // The cache directory can be the config directory (e.g. with `XDG_CACHE_HOME=~/.config`),
+            // in which case the legacy covers are the ones in use.
+            if legacy_dir.is_dir() && !is_same_dir(&legacy_dir, &dir) {
+                let _ = fs::remove_dir_all(&legacy_dir);
+            }
+            cache_clone.record_saved(0);
+        });
+
+        cache
+    }
+
+    /// Returns the image at `url` if it has been fetched, and starts fetching it otherwise.
+    ///
+    /// A rerender is sent through `tx` once it has been fetched.
+    pub fn get(&self, url: &str, tx: &mpsc::Sender<AppEvent>) -> Option<Arc<Vec<u8>>> {
+        let mut unlocked_covers = self.covers.lock().unwrap();
+
+        match unlocked_covers.get(url) {
+            Some(CoverState::Loaded(image)) => return Some(Arc::clone(image)),
+            Some(CoverState::Loading | CoverState::Failed) => return None,
+            None => {},
+        }
+        unlocked_covers.insert(url.to_string(), CoverState::Loading);
+        drop(unlocked_covers);
+
+        let cache = self.clone();
+        let url = url.to_string();
+        let tx_clone = tx.clone();
+        tokio::spawn(async move {
+            let state = match cache.fetch(&url).await {
+                Ok(image) => CoverState::Loaded(Arc::new(image)),
+                Err(_) => CoverState::Failed,
+            };
+            cache.covers.lock().unwrap().insert(url, state);
+
+            let _ = tx_clone.try_send(AppEvent::ReRender);
+        });
+
+        None
+    }
+
+    /// Returns the path of the image at `url` scaled down to fit in `size` by `size` pixels if it has been saved,
+    /// and starts making it in the background otherwise.
+    ///
+    /// The image is fetched like any other cover, so one that was already drawn isn't downloaded again, and it is only
+    /// resized once. Thumbnails are saved with the covers, so they are trimmed and purged along with them.
+    #[cfg(feature = "images")]
+    pub fn get_thumbnail(&self, url: &str, size: u32) -> Option<PathBuf> {
+        let path = self.dir.as_ref()?.join(format!("{size}px_{}", get_file_name(url)));
+        if path.is_file() {
+            return Some(path);
+        }
+        if !self.thumbnails.lock().unwrap().insert(path.clone()) {
+            return None;
+        }
+
+        let cache = self.clone();
+        let url = url.to_string();
+        self.runtime.spawn(async move {
+            let Ok(image) = cache.fetch(&url).await else {
+                return;
+            };
+            let _ = tokio::task::spawn_blocking(move || {
+                if let Ok(len) = save_thumbnail(&image, size, &path) {
+                    cache.record_saved(len);
+                    // Letting it be started again means it is remade if it gets trimmed.
+                    cache.thumbnails.lock().unwrap().remove(&path);
+                }
+            }).await;
+        });
+
+        None
+    }
+
+    /// Covers can only be scaled down with the images feature, so without it there are never any thumbnails.
+    #[cfg(not(feature = "images"))]
+    pub fn get_thumbnail(&self, _url: &str, _size: u32) -> Option<PathBuf> {
+        None
+    }
+
+    /// Reads the image at `url` from the covers directory, or downloads and saves it if it isn't there.
+    ///
+    /// The files are read and written on the blocking threads, so that they don't hold up the other tasks.
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>, String> {
+        let file = self.dir.as_ref().map(|dir| dir.join(get_file_name(url)));
+        if let Some(file) = file.clone()
+            && let Ok(Some(image)) = tokio::task::spawn_blocking(move || read_saved(&file)).await
+        {
+            return Ok(image);
+        }
+
+        let image = self.client.get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Unable to fetch cover art: {e}"))?
+            .bytes()
+            .await
+            .map_err(|e| format!("Unable to fetch cover art: {e}"))?
+            .to_vec();
+
+        self.bandwidth_usage.lock().unwrap().record(image.len() as u64, SystemTime::now());
+
+        let Some(file) = file else {
+            return Ok(image);
+        };
+        let cache = self.clone();
+        tokio::task::spawn_blocking(move || {
+            // Not saving the cover only means it is downloaded again next time.
+            if fs::write(&file, &image).is_ok() {
+                cache.record_saved(image.len() as u64);
+            }
+            image
+        })
+            .await
+            .map_err(|e| format!("Unable to save cover art: {e}"))
+    }
+
+    /// Returns how many bytes of covers are saved on disk, or `None` until it has been added up.
+    pub fn get_disk_usage(&self) -> Option<u64> {
+        *self.disk_usage.lock().unwrap()
+    }
+
+    /// Returns how many bytes of covers are saved on disk at most, past which the least recently used are deleted.
+    pub fn get_max_disk_usage(&self) -> u64 {
+        Self::MAX_DISK_BYTES
+    }
+
+    /// Deletes every saved cover and drops the ones in memory, so that each is fetched again the next time it is drawn.
+    pub fn purge(&self) -> Result<(), String> {
+        self.covers.lock().unwrap().clear();
+        self.thumbnails.lock().unwrap().clear();
+
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let mut disk_usage = self.disk_usage.lock().unwrap();
+        let left = trim_dir(dir, 0);
+        *disk_usage = Some(left);
+
+        if left > 0 {
+            return Err(format!("Unable to delete some of the covers in {}", dir.display()));
+        }
+
+        Ok(())
+    }
+
+    /// Adds `bytes` to the size of the saved covers, deleting the least recently used ones if that goes over the cap.
+    ///
+    /// The size is added up from the directory the first time.
+    fn record_saved(&self, bytes: u64) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let mut disk_usage = self.disk_usage.lock().unwrap();
+        let total = disk_usage.unwrap_or_else(|| get_saved_files(dir).iter().map(|(_, len, _)| len).sum()) + bytes;
+
+        *disk_usage = Some(if total > Self::MAX_DISK_BYTES {
+            // Trimming a bit further than the cap keeps it from happening again with the next cover.
+            trim_dir(dir, Self::MAX_DISK_BYTES / 10 * 9)
+        } else {
+            total
+        });
+    }
+}
+
+impl Covers {
+    /// Returns an empty set of covers, keeping at most `max_bytes` of loaded ones.
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            states: HashMap::new(),
+            clock: 0,
+            loaded_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// Returns the state of the cover at `url`, marking it as just used.
+    fn get(&mut self, url: &str) -> Option<&CoverState> {
+        self.clock += 1;
+        let (state, last_used) = self.states.get_mut(url)?;
+        *last_used = self.clock;
+
+        Some(state)
+    }
+
+    /// Drops every loaded cover, and every one that failed so that it is tried again.
+    ///
+    /// Covers that are still loading are kept, since they are about to be inserted anyway.
+    fn clear(&mut self) {
+        self.states.retain(|_, (state, _)| matches!(state, CoverState::Loading));
+        self.loaded_bytes = 0;
+    }
+
+    /// Sets the state of the cover at `url`, dropping the least recently used loaded covers if they now take up too much.
+    fn insert(&mut self, url: String, state: CoverState) {
+        self.clock += 1;
+        self.loaded_bytes += get_loaded_len(&state);
+        if let Some((old_state, _)) = self.states.insert(url.clone(), (state, self.clock)) {
+            self.loaded_bytes -= get_loaded_len(&old_state);
+        }
+
+        while self.loaded_bytes > self.max_bytes {
+            // The cover just inserted is kept even if it is bigger than the cap on its own.
+            let Some(oldest) = self.states.iter()
+                .filter(|(other_url, (state, _))| **other_url != url && matches!(state, CoverState::Loaded(_)))
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(other_url, _)| other_url.clone())
+            else {
+                break;
+            };
+
+            if let Some((state, _)) = self.states.remove(&oldest) {
+                self.loaded_bytes -= get_loaded_len(&state);
+            }
+        }
+    }
+}
+
+/// Returns how many bytes of memory `state` holds on to.
+fn get_loaded_len(state: &CoverState) -> usize {
+    match state {
+        CoverState::Loaded(image) => image.len(),
+        CoverState::Loading | CoverState::Failed => 0,
+    }
+}
+
+/// Returns the cover saved at `file`, or `None` if it isn't saved.
+///
+/// Saved covers are trimmed by when they were last written, so reading one counts as using it.
+fn read_saved(file: &Path) -> Option<Vec<u8>> {
+    let image = fs::read(file).ok()?;
+    let _ = fs::File::options().write(true).open(file).and_then(|file| file.set_modified(SystemTime::now()));
+
+    Some(image)
+}
+
+/// Saves `image` scaled down to fit in `size` by `size` pixels as a JPEG at `path`, and returns how many bytes it takes up.
+///
+/// It is written to a temporary file that then replaces `path`, so that a thumbnail is never read half written.
+#[cfg(feature = "images")]
+fn save_thumbnail(image: &[u8], size: u32, path: &Path) -> Result<u64, String> {
+    let mut image = image::load_from_memory(image).map_err(|e| e.to_string())?;
+    if image.width() > size || image.height() > size {
+        image = image.resize(size, size, FilterType::Triangle);
+    }
+
+    let temp_path = path.with_extension("tmp");
+    image.to_rgb8()
+        .save_with_format(&temp_path, ImageFormat::Jpeg)
+        .map_err(|e| e.to_string())?;
+    fs::rename(&temp_path, path).map_err(|e| e.to_string())?;
+
+    fs::metadata(path).map(|metadata| metadata.len()).map_err(|e| e.to_string())
+}
+
+/// Returns true if `a` and `b` are the same directory once symlinks and `..` are resolved.
+/// Directories that don't exist aren't the same as anything.
+fn is_same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns the path, size, and last modified time of each file in `dir`.
+fn get_saved_files(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(fs::Metadata::is_file)?;
+            Some((entry.path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+/// Deletes the least recently modified files in `dir` until the rest add up to at most `max_bytes`,
+/// and returns how many bytes are left.
+fn trim_dir(dir: &Path, max_bytes: u64) -> u64 {
+    let mut files = get_saved_files(dir);
+    files.sort_unstable_by_key(|(_, _, modified)| *modified);
+
+    let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+    for (path, len, _) in files {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(path).is_ok() {
+            total -= len;
+        }
+    }
+
+    total
+}
+
+/// Returns the name of the file that the image at `url` is saved as, made of its url without the scheme.
+fn get_file_name(url: &str) -> String {
+    let path = url.split_once("://").map_or(url, |(_, path)| path);
+
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn names_files_after_urls() {
+        assert_eq!(
+            get_file_name("https://resources.tidal.com/images/3e1a/9b2c/80x80.jpg"),
+            "resources.tidal.com_images_3e1a_9b2c_80x80.jpg",
+        );
+        assert_eq!(get_file_name("a/../b?c=d"), "a_.._b_c_d");
+    }
+
+    #[test]
+    fn drops_least_recently_used_covers_over_the_cap() {
+        let image = || CoverState::Loaded(Arc::new(vec![0; 40]));
+        let mut covers = Covers::new(100);
+
+        covers.insert(String::from("a"), image());
+        covers.insert(String::from("b"), image());
+        covers.insert(String::from("loading"), CoverState::Loading);
+        assert!(covers.get("a").is_some());
+        covers.insert(String::from("c"), image());
+
+        assert!(covers.get("a").is_some());
+        assert!(covers.get("b").is_none());
+        assert!(covers.get("c").is_some());
+        assert!(covers.get("loading").is_some());
+        assert_eq!(covers.loaded_bytes, 80);
+    }
+
+    #[test]
+    fn clears_all_but_loading_covers() {
+        let mut covers = Covers::new(100);
+        covers.insert(String::from("a"), CoverState::Loaded(Arc::new(vec![0; 40])));
+        covers.insert(String::from("failed"), CoverState::Failed);
+        covers.insert(String::from("loading"), CoverState::Loading);
+
+        covers.clear();
+
+        assert!(covers.get("a").is_none());
+        assert!(covers.get("failed").is_none());
+        assert!(covers.get("loading").is_some());
+        assert_eq!(covers.loaded_bytes, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn saves_thumbnails_scaled_to_fit() {
+        let dir = std::env::temp_dir().join(format!("tidal-tui-thumbnail-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut cover = vec![];
+        image::RgbImage::new(40, 20)
+            .write_to(&mut std::io::Cursor::new(&mut cover), ImageFormat::Jpeg)
+            .unwrap();
+        let path = dir.join("10px_cover.jpg");
+
+        let len = save_thumbnail(&cover, 10, &path).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+        let thumbnail = image::open(&path).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (10, 5));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn compares_resolved_dirs() {
+        let dir = std::env::temp_dir().join(format!("tidal-tui-same-dir-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("covers")).unwrap();
+        fs::create_dir_all(dir.join("other")).unwrap();
+
+        assert!(is_same_dir(&dir.join("covers"), &dir.join("other").join("..").join("covers")));
+        assert!(!is_same_dir(&dir.join("covers"), &dir.join("other")));
+        assert!(!is_same_dir(&dir.join("covers"), &dir.join("missing")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn trims_oldest_files_first() {
+        let dir = std::env::temp_dir().join(format!("tidal-tui-cover-cache-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = SystemTime::now();
+        for (name, age) in [("old.jpg", 30), ("middle.jpg", 20), ("new.jpg", 10)] {
+            let path = dir.join(name);
+            fs::write(&path, [0u8; 10]).unwrap();
+            fs::File::options().write(true).open(&path).unwrap().set_modified(now - Duration::from_secs(age)).unwrap();
+        }
+
+        assert_eq!(trim_dir(&dir, 20), 20);
+        assert!(!dir.join("old.jpg").exists());
+        assert!(dir.join("middle.jpg").exists());
+        assert!(dir.join("new.jpg").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
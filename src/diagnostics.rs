@@ -0,0 +1,213 @@
+use std::{
+    env,
+    error::Error,
+    fmt,
+    fs,
+    path::Path,
+    sync::Arc,
+    time::Instant,
+};
+
+use cpal::traits::DeviceTrait;
+use rtidalapi::{
+    Session,
+    User,
+};
+
+use crate::{
+    audio_output,
+    config::{
+        Config,
+        PlayerConfig,
+    },
+    format::format_duration,
+    get_config_path,
+    init_session,
+};
+
+/// The result of one diagnostic check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+/// Whether a diagnostic check passed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CheckStatus {
+    Ok,
+    /// The check couldn't be run, or found something that might cause problems.
+    Warning,
+    Failed,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self.status {
+            CheckStatus::Ok => "✓",
+            CheckStatus::Warning => "!",
+            CheckStatus::Failed => "✗",
+        };
+
+        write!(f, "{symbol} {}: {}", self.name, self.detail)
+    }
+}
+
+/// Runs every check that doesn't need a session, and every check that does if `session` is given.
+///
+/// This blocks on requests to Tidal, so it should be run in the background.
+pub fn run_checks(session: Option<&Arc<Session>>, config_path: &str) -> Vec<Check> {
+    let mut checks = vec![];
+
+    if let Some(session) = session {
+        checks.push(check_api(session));
+        checks.push(check_login(session));
+    }
+    // The defaults are checked if the config can't be loaded, since that is what would be used.
+    let config = Config::load(config_path).unwrap_or_default();
+    checks.push(check_audio_output(&config.player));
+    checks.push(check_writable("Config directory", Path::new(config_path)));
+
+    checks
+}
+
+/// Runs every check, logging in first, and prints the report to stdout.
+pub fn print_report() -> Result<(), Box<dyn Error>> {
+    let config_path = get_config_path()?;
+
+    let checks = match init_session(&config_path, || {}) {
+        Ok(session) => run_checks(Some(&session), &config_path),
+        Err(e) => {
+            let mut checks = vec![Check::new("Login", CheckStatus::Failed, e.to_string())];
+            checks.extend(run_checks(None, &config_path));
+            checks
+        },
+    };
+
+    print!("{}", format_report(&checks));
+
+    Ok(())
+}
+
+/// Returns a plain text report of `checks`, along with the version and platform, for pasting into bug reports.
+pub fn format_report(checks: &[Check]) -> String {
+    let mut report = format!(
+        "tidal-tui {} ({} {})\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+    );
+
+    for check in checks {
+        report.push_str(&format!("{check}\n"));
+    }
+
+    report
+}
+
+/// Checks whether Tidal can be reached, and how long it takes.
+fn check_api(session: &Session) -> Check {
+    const NAME: &str = "Tidal API";
+
+    if session.is_offline_forced() {
+        return Check::new(NAME, CheckStatus::Warning, "Not checked, offline mode is on");
+    }
+
+    let started_at = Instant::now();
+    if session.check_connection() {
+        Check::new(NAME, CheckStatus::Ok, format!("Reachable in {} ms", started_at.elapsed().as_millis()))
+    } else {
+        Check::new(NAME, CheckStatus::Failed, "Unreachable, check your internet connection")
+    }
+}
+
+/// Checks whether the session's access token is accepted by Tidal.
+fn check_login(session: &Arc<Session>) -> Check {
+    #[cfg(feature = "unofficial")]
+    const NAME: &str = "Login (unofficial API)";
+    #[cfg(not(feature = "unofficial"))]
+    const NAME: &str = "Login";
+
+    if session.is_offline_forced() {
+        return Check::new(NAME, CheckStatus::Warning, "Not checked, offline mode is on");
+    }
+
+    match User::get_current_user(Arc::clone(session)) {
+        Ok(user) => Check::new(
+            NAME,
+            CheckStatus::Ok,
+            format!(
+                "Logged in as {}, token expires in {}",
+                user.attributes.username,
+                format_duration(session.get_token_expires_in()),
+            ),
+        ),
+        Err(e) => Check::new(NAME, CheckStatus::Failed, e),
+    }
+}
+
+/// Checks whether the output device set in `config`, or the default one, is there to play to.
+fn check_audio_output(config: &PlayerConfig) -> Check {
+    const NAME: &str = "Audio output";
+
+    let host = match audio_output::get_host(config.output_host.as_deref()) {
+        Ok(host) => host,
+        Err(e) => return Check::new(NAME, CheckStatus::Failed, e),
+    };
+    let device = match audio_output::get_output_device(config.output_host.as_deref(), config.output_device.as_deref()) {
+        Ok(device) => device,
+        Err(e) => return Check::new(NAME, CheckStatus::Failed, e),
+    };
+
+    let name = device.name().unwrap_or(String::from("Unknown device"));
+    let device_count = audio_output::get_output_device_names(&host).len();
+
+    Check::new(NAME, CheckStatus::Ok, format!("{name} ({}, {device_count} devices)", host.id().name()))
+}
+
+/// Checks whether files can be written to the directory at `path`.
+fn check_writable(name: &'static str, path: &Path) -> Check {
+    let test_file = path.join(".tidal-tui-write-test");
+
+    match fs::write(&test_file, b"").and_then(|_| fs::remove_file(&test_file)) {
+        Ok(()) => Check::new(name, CheckStatus::Ok, format!("{} is writable", path.display())),
+        Err(e) => Check::new(name, CheckStatus::Failed, format!("Can't write to {}: {e}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_writable_directories() {
+        let check = check_writable("Temp", &env::temp_dir());
+        assert_eq!(check.status, CheckStatus::Ok);
+
+        let check = check_writable("Missing", &env::temp_dir().join("tidal-tui-missing").join("nested"));
+        assert_eq!(check.status, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn formats_reports() {
+        let checks = [
+            Check::new("Tidal API", CheckStatus::Ok, "Reachable in 120 ms"),
+            Check::new("Audio output", CheckStatus::Failed, "No default output device (ALSA)"),
+        ];
+
+        let report = format_report(&checks);
+        assert!(report.starts_with("tidal-tui "));
+        assert!(report.ends_with("✓ Tidal API: Reachable in 120 ms\n✗ Audio output: No default output device (ALSA)\n"));
+    }
+}
@@ -0,0 +1,212 @@
+use ratatui::layout::Rect;
+
+use crate::keymap::Context;
+
+/// A pane of the UI that can be focused.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pane {
+    /// The main pane, showing My Collection or whatever is being browsed.
+    Collection,
+    Queue,
+    NowPlaying,
+}
+
+/// A direction to move focus in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FocusDirection {
+    Left,
+    Down,
+    Up,
+    Right,
+}
+
+impl Pane {
+    /// Returns the keymap contexts that are active while this pane is focused, in priority order.
+    pub fn get_contexts(&self) -> Vec<Context> {
+        match self {
+            Self::Collection => vec![Context::Collection, Context::Table],
+            Self::Queue => vec![Context::Queue, Context::Table],
+            Self::NowPlaying => vec![Context::NowPlaying],
+        }
+    }
+}
+
+/// Keeps track of which pane is focused, and where every pane was last drawn so focus can move between them.
+#[derive(Debug)]
+pub struct Focus {
+    focused: Pane,
+    areas: Vec<(Pane, Rect)>,
+}
+
+impl Focus {
+    /// Returns a new `Focus` with `pane` focused.
+    pub fn new(pane: Pane) -> Self {
+        Self {
+            focused: pane,
+            areas: Vec::new(),
+        }
+    }
+
+    /// Returns the focused pane.
+    pub fn get_focused(&self) -> Pane {
+        self.focused
+    }
+
+    /// Focuses `pane`.
+    pub fn set_focused(&mut self, pane: Pane) {
+        self.focused = pane;
+    }
+
+    /// Returns true if `pane` is focused.
+    pub fn is_focused(&self, pane: Pane) -> bool {
+        self.focused == pane
+    }
+
+    /// Forgets where panes were drawn. This should be called at the start of every frame.
+    pub fn clear_areas(&mut self) {
+        self.areas.clear();
+    }
+
+    /// Records that `pane` was drawn in `area` this frame.
+    ///
+    /// Panes should be recorded in the order that `focus_next` should cycle through them.
+    pub fn set_area(&mut self, pane: Pane, area: Rect) {
+        self.areas.push((pane, area));
+    }
+
+    /// Focuses the first pane that was drawn if the focused pane wasn't drawn this frame (e.g. it was hidden).
+    pub fn ensure_visible(&mut self) {
+        if !self.areas.iter().any(|(pane, _)| *pane == self.focused) && let Some((pane, _)) = self.areas.first() {
+            self.focused = *pane;
+        }
+    }
+
+    /// Focuses the next pane that was drawn, wrapping around to the first.
+    pub fn focus_next(&mut self) {
+        self.focus_offset(1);
+    }
+
+    /// Focuses the previous pane that was drawn, wrapping around to the last.
+    pub fn focus_prev(&mut self) {
+        self.focus_offset(self.areas.len().saturating_sub(1));
+    }
+
+    fn focus_offset(&mut self, offset: usize) {
+        if self.areas.is_empty() {
+            return;
+        }
+
+        let index = self.areas.iter()
+            .position(|(pane, _)| *pane == self.focused)
+            .map_or(0, |index| (index + offset) % self.areas.len());
+
+        self.focused = self.areas[index].0;
+    }
+
+    /// Focuses the nearest pane in `direction` from the focused pane, if there is one.
+    pub fn focus_direction(&mut self, direction: FocusDirection) {
+        let Some((_, current)) = self.areas.iter().find(|(pane, _)| *pane == self.focused) else {
+            return;
+        };
+        let current = *current;
+
+        let nearest = self.areas.iter()
+            .filter(|(pane, _)| *pane != self.focused)
+            .filter_map(|(pane, area)| Some((*pane, Self::distance(current, *area, direction)?)))
+            .min_by_key(|(_, distance)| *distance);
+
+        if let Some((pane, _)) = nearest {
+            self.focused = pane;
+        }
+    }
+
+    /// Returns how far `to` is from `from` in `direction`, or `None` if it isn't in that direction.
+    ///
+    /// Panes are compared first by the gap between them, then by how far apart their centers are across `direction`.
+    fn distance(from: Rect, to: Rect, direction: FocusDirection) -> Option<(u16, u16)> {
+        let gap = match direction {
+            FocusDirection::Left if to.right() <= from.left() => from.left() - to.right(),
+            FocusDirection::Right if to.left() >= from.right() => to.left() - from.right(),
+            FocusDirection::Up if to.bottom() <= from.top() => from.top() - to.bottom(),
+            FocusDirection::Down if to.top() >= from.bottom() => to.top() - from.bottom(),
+            _ => return None,
+        };
+
+        let center = |start: u16, len: u16| start + len / 2;
+        let offset = match direction {
+            FocusDirection::Left | FocusDirection::Right => center(from.y, from.height).abs_diff(center(to.y, to.height)),
+            FocusDirection::Up | FocusDirection::Down => center(from.x, from.width).abs_diff(center(to.x, to.width)),
+        };
+
+        Some((gap, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `Focus` on `pane` with the collection on the left, and the queue above now playing on the right.
+    fn focus_on(pane: Pane) -> Focus {
+        let mut focus = Focus::new(pane);
+        focus.set_area(Pane::Collection, Rect::new(0, 0, 60, 30));
+        focus.set_area(Pane::Queue, Rect::new(60, 0, 40, 20));
+        focus.set_area(Pane::NowPlaying, Rect::new(60, 20, 40, 10));
+        focus
+    }
+
+    /// Returns the pane focused after moving from `pane` in `direction`.
+    fn move_from(pane: Pane, direction: FocusDirection) -> Pane {
+        let mut focus = focus_on(pane);
+        focus.focus_direction(direction);
+        focus.get_focused()
+    }
+
+    #[test]
+    fn focuses_nearest_pane_in_each_direction() {
+        // The queue's center is closer to the collection's than now playing's is.
+        assert_eq!(move_from(Pane::Collection, FocusDirection::Right), Pane::Queue);
+        assert_eq!(move_from(Pane::Queue, FocusDirection::Left), Pane::Collection);
+        assert_eq!(move_from(Pane::Queue, FocusDirection::Down), Pane::NowPlaying);
+        assert_eq!(move_from(Pane::NowPlaying, FocusDirection::Up), Pane::Queue);
+        assert_eq!(move_from(Pane::NowPlaying, FocusDirection::Left), Pane::Collection);
+    }
+
+    #[test]
+    fn stays_when_there_is_no_pane_in_direction() {
+        assert_eq!(move_from(Pane::Collection, FocusDirection::Left), Pane::Collection);
+        assert_eq!(move_from(Pane::Collection, FocusDirection::Up), Pane::Collection);
+        assert_eq!(move_from(Pane::Queue, FocusDirection::Up), Pane::Queue);
+        assert_eq!(move_from(Pane::NowPlaying, FocusDirection::Right), Pane::NowPlaying);
+
+        let mut focus = Focus::new(Pane::Queue);
+        focus.focus_direction(FocusDirection::Right);
+        assert_eq!(focus.get_focused(), Pane::Queue);
+    }
+
+    #[test]
+    fn cycles_through_panes_wrapping_around() {
+        let mut focus = focus_on(Pane::Queue);
+        focus.focus_next();
+        assert_eq!(focus.get_focused(), Pane::NowPlaying);
+        focus.focus_next();
+        assert_eq!(focus.get_focused(), Pane::Collection);
+        focus.focus_prev();
+        assert_eq!(focus.get_focused(), Pane::NowPlaying);
+        focus.focus_prev();
+        assert_eq!(focus.get_focused(), Pane::Queue);
+    }
+
+    #[test]
+    fn cycles_from_first_pane_when_focused_pane_was_not_drawn() {
+        let mut focus = Focus::new(Pane::NowPlaying);
+        focus.set_area(Pane::Collection, Rect::new(0, 0, 60, 30));
+        focus.set_area(Pane::Queue, Rect::new(60, 0, 40, 30));
+        focus.focus_next();
+        assert_eq!(focus.get_focused(), Pane::Collection);
+
+        let mut focus = Focus::new(Pane::Queue);
+        focus.focus_prev();
+        assert_eq!(focus.get_focused(), Pane::Queue);
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use chrono::{
+    Datelike,
+    NaiveDate,
+};
+use serde::Deserialize;
+
+/// The conventions that numbers, long durations, and dates are written in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    /// 4,312 · 312.4 · 2 hr 14 min · May 28, 1997
+    #[default]
+    En,
+    /// 4,312 · 312.4 · 2 hr 14 min · 28 May 1997
+    EnGb,
+    /// 4.312 · 312,4 · 2 Std. 14 Min. · 28.05.1997
+    De,
+    /// 4 312 · 312,4 · 2 h 14 min · 28/05/1997
+    Fr,
+}
+
+impl Locale {
+    /// Returns the character between each group of three digits.
+    fn get_thousands_separator(&self) -> char {
+        match self {
+            Self::En | Self::EnGb => ',',
+            Self::De => '.',
+            // A no-break space, so that numbers aren't split across lines.
+            Self::Fr => '\u{a0}',
+        }
+    }
+
+    /// Returns the character before the fractional digits of a number.
+    fn get_decimal_separator(&self) -> char {
+        match self {
+            Self::En | Self::EnGb => '.',
+            Self::De | Self::Fr => ',',
+        }
+    }
+
+    /// Returns the abbreviations of hours, minutes, and seconds.
+    fn get_time_units(&self) -> [&'static str; 3] {
+        match self {
+            Self::En | Self::EnGb => ["hr", "min", "sec"],
+            Self::De => ["Std.", "Min.", "Sek."],
+            Self::Fr => ["h", "min", "s"],
+        }
+    }
+
+    /// Formats a count with its digits grouped in threes (e.g. 4,312).
+    pub fn format_count(&self, count: usize) -> String {
+        let digits = count.to_string();
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+                formatted.push(self.get_thousands_separator());
+            }
+            formatted.push(digit);
+        }
+
+        formatted
+    }
+
+    /// Formats a count followed by `singular` or `plural` to match it (e.g. 4,312 tracks).
+    pub fn format_count_of(&self, count: usize, singular: &str, plural: &str) -> String {
+        format!("{} {}", self.format_count(count), if count == 1 { singular } else { plural })
+    }
+
+    /// Formats a number with one fractional digit (e.g. 312.4).
+    pub fn format_decimal(&self, value: f64) -> String {
+        let formatted = format!("{value:.1}");
+        let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "0"));
+
+        match whole.parse::<usize>() {
+            Ok(whole) => format!("{}{}{fraction}", self.format_count(whole), self.get_decimal_separator()),
+            Err(_) => formatted,
+        }
+    }
+
+    /// Formats a length of time too long for minutes and seconds in hours and minutes (e.g. 2 hr 14 min),
+    /// or just minutes or seconds if it is shorter.
+    pub fn format_long_duration(&self, duration: Duration) -> String {
+        let [hours_unit, minutes_unit, seconds_unit] = self.get_time_units();
+        let secs = duration.as_secs();
+        let (hours, minutes) = ((secs / (60 * 60)) as usize, secs / 60 % 60);
+
+        match secs {
+            _ if secs < 60 => format!("{secs} {seconds_unit}"),
+            _ if hours == 0 => format!("{minutes} {minutes_unit}"),
+            _ if minutes == 0 => format!("{} {hours_unit}", self.format_count(hours)),
+            _ => format!("{} {hours_unit} {minutes} {minutes_unit}", self.format_count(hours)),
+        }
+    }
+
+    /// Formats a date (e.g. May 28, 1997).
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+        let (year, month, day) = (date.year(), date.month(), date.day());
+        let month_name = MONTHS[date.month0() as usize];
+
+        match self {
+            Self::En => format!("{month_name} {day}, {year}"),
+            Self::EnGb => format!("{day} {month_name} {year}"),
+            Self::De => format!("{day:02}.{month:02}.{year}"),
+            Self::Fr => format!("{day:02}/{month:02}/{year}"),
+        }
+    }
+
+    /// Formats a number of bytes in the largest unit that keeps it above 1 (e.g. 312.4 MB).
+    pub fn format_bytes(&self, bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+        if bytes < 1000 {
+            return format!("{bytes} B");
+        }
+
+        let mut value = bytes as f64 / 1000.0;
+        let mut unit = 0;
+        while value >= 1000.0 && unit < UNITS.len() - 1 {
+            value /= 1000.0;
+            unit += 1;
+        }
+
+        format!("{} {}", self.format_decimal(value), UNITS[unit])
+    }
+}
+
+/// Formats a `Duration` into a `String` for displaying.
+pub fn format_duration(duration: Duration) -> String {
+    format!("{}:{:02}", (duration.as_secs_f64().round() as u64) / 60, (duration.as_secs_f64().round() as u64) % 60)
+}
+
+/// Formats how long ago something happened (e.g. 3d ago) for displaying.
+pub fn format_time_ago(elapsed: Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    let secs = elapsed.as_secs();
+
+    match secs {
+        _ if secs < MINUTE => String::from("just now"),
+        _ if secs < HOUR => format!("{}m ago", secs / MINUTE),
+        _ if secs < DAY => format!("{}h ago", secs / HOUR),
+        _ if secs < WEEK => format!("{}d ago", secs / DAY),
+        _ if secs < MONTH => format!("{}w ago", secs / WEEK),
+        _ if secs < YEAR => format!("{}mo ago", secs / MONTH),
+        _ => format!("{}y ago", secs / YEAR),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_counts() {
+        assert_eq!(Locale::En.format_count(7), "7");
+        assert_eq!(Locale::En.format_count(4312), "4,312");
+        assert_eq!(Locale::De.format_count(1_234_567), "1.234.567");
+        assert_eq!(Locale::Fr.format_count(4312), "4\u{a0}312");
+        assert_eq!(Locale::En.format_count_of(1, "track", "tracks"), "1 track");
+        assert_eq!(Locale::En.format_count_of(4312, "track", "tracks"), "4,312 tracks");
+    }
+
+    #[test]
+    fn formats_long_durations() {
+        assert_eq!(Locale::En.format_long_duration(Duration::from_secs(42)), "42 sec");
+        assert_eq!(Locale::En.format_long_duration(Duration::from_secs(14 * 60 + 5)), "14 min");
+        assert_eq!(Locale::En.format_long_duration(Duration::from_secs(2 * 60 * 60)), "2 hr");
+        assert_eq!(Locale::En.format_long_duration(Duration::from_secs(2 * 60 * 60 + 14 * 60)), "2 hr 14 min");
+        assert_eq!(Locale::De.format_long_duration(Duration::from_secs(2 * 60 * 60 + 14 * 60)), "2 Std. 14 Min.");
+    }
+
+    #[test]
+    fn formats_dates() {
+        let date = NaiveDate::from_ymd_opt(1997, 5, 28).unwrap();
+
+        assert_eq!(Locale::En.format_date(date), "May 28, 1997");
+        assert_eq!(Locale::EnGb.format_date(date), "28 May 1997");
+        assert_eq!(Locale::De.format_date(date), "28.05.1997");
+        assert_eq!(Locale::Fr.format_date(date), "28/05/1997");
+    }
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(Locale::En.format_bytes(512), "512 B");
+        assert_eq!(Locale::En.format_bytes(312_400_000), "312.4 MB");
+        assert_eq!(Locale::En.format_bytes(2_500_000_000), "2.5 GB");
+        assert_eq!(Locale::De.format_bytes(1_312_400_000_000_000), "1.312,4 TB");
+    }
+}
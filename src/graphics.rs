@@ -0,0 +1,408 @@
+#[cfg(feature = "images")]
+use std::collections::{
+    BTreeMap,
+    HashSet,
+};
+use std::{
+    collections::HashMap,
+    env,
+    io::{
+        self,
+        Write,
+    },
+    sync::Arc,
+};
+
+use base64::{
+    engine::general_purpose::STANDARD,
+    Engine,
+};
+#[cfg(feature = "images")]
+use crossterm::terminal;
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+};
+#[cfg(feature = "images")]
+use image::{
+    imageops::FilterType,
+    RgbImage,
+};
+use ratatui::layout::Rect;
+use rtidalapi::Track;
+use tokio::sync::mpsc;
+
+use crate::{
+    cover_cache::CoverCache,
+    AppEvent,
+};
+
+/// A way for the terminal to draw images between its text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GraphicsProtocol {
+    /// iTerm2's inline images, which WezTerm and mintty also support.
+    Iterm2,
+    /// Kitty's graphics protocol, which Ghostty also supports.
+    Kitty,
+    /// DEC sixel graphics, which foot, mlterm, and contour support.
+    Sixel,
+}
+
+/// Album covers drawn over cells that are left blank for them, like next to the rows of a table.
+///
+/// Images aren't part of ratatui's buffer, so they are written to the terminal after each frame,
+/// and only where they changed, since terminals redraw every image that is written.
+pub struct Thumbnails {
+    protocol: GraphicsProtocol,
+    covers: CoverCache,
+    /// What to draw at the top left cell of each image this frame.
+    slots: HashMap<(u16, u16), Placement>,
+    /// What is on screen at the top left cell of each image, as of the last frame.
+    drawn: HashMap<(u16, u16), Placement>,
+}
+
+/// A cover drawn over a number of cells.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Placement {
+    url: String,
+    width: u16,
+    height: u16,
+}
+
+impl GraphicsProtocol {
+    /// Returns the protocol of the terminal the app is running in, if it supports one.
+    pub fn detect() -> Option<Self> {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+
+        Self::from_env(&var("TERM"), &var("TERM_PROGRAM"), env::var_os("KITTY_WINDOW_ID").is_some())
+    }
+
+    /// Returns the protocol of a terminal with the `TERM` and `TERM_PROGRAM` environment variables,
+    /// and `KITTY_WINDOW_ID` set or not.
+    fn from_env(term: &str, term_program: &str, is_kitty_window: bool) -> Option<Self> {
+        match term_program {
+            "iTerm.app" | "WezTerm" | "mintty" => return Some(Self::Iterm2),
+            "ghostty" => return Some(Self::Kitty),
+            _ => {},
+        }
+
+        if term.starts_with("foot") || matches!(term, "mlterm" | "contour") {
+            return Some(Self::Sixel);
+        }
+
+        (is_kitty_window || term == "xterm-kitty" || term == "xterm-ghostty").then_some(Self::Kitty)
+    }
+
+    /// Returns the escape sequence that draws `image` at the cursor, scaled to fit `width` by `height` cells,
+    /// or `None` if this protocol can't draw its format.
+    ///
+    /// iTerm2 draws any format macOS can open, and Kitty takes PNGs as they are.
+    /// Other images are decoded and scaled on our side, which only JPEGs can be, and only with the images feature.
+    pub fn encode_image(&self, image: &[u8], width: u16, height: u16) -> Option<String> {
+        match self {
+            Self::Iterm2 => Some(format!(
+                "\x1b]1337;File=inline=1;size={};width={width};height={height};preserveAspectRatio=1:{}\x07",
+                image.len(),
+                STANDARD.encode(image),
+            )),
+            Self::Kitty if image.starts_with(b"\x89PNG\r\n\x1a\n") => {
+                Some(encode_kitty(image, &format!("a=T,f=100,c={width},r={height},C=1,q=2")))
+            },
+            #[cfg(feature = "images")]
+            Self::Kitty => {
+                let pixels = decode_to_fit(image, width, height)?;
+                let control = format!("a=T,f=24,s={},v={},c={width},r={height},C=1,q=2", pixels.width(), pixels.height());
+                Some(encode_kitty(pixels.as_raw(), &control))
+            },
+            #[cfg(feature = "images")]
+            Self::Sixel => Some(encode_sixel(&decode_to_fit(image, width, height)?)),
+            #[cfg(not(feature = "images"))]
+            Self::Kitty | Self::Sixel => None,
+        }
+    }
+
+    /// Returns the escape sequence that removes every image on screen, without touching the text around them.
+    ///
+    /// Inline images and sixels are replaced by whatever text is drawn over them, so there is nothing to do for them.
+    pub fn encode_clear(&self) -> &'static str {
+        match self {
+            Self::Iterm2 | Self::Sixel => "",
+            Self::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+        }
+    }
+
+    /// Returns the escape sequence that removes an image drawn at the cursor, `width` cells wide.
+    pub fn encode_erase(&self, width: u16) -> String {
+        let blank = " ".repeat(width as usize);
+
+        match self {
+            // Text written over an inline image or a sixel replaces it.
+            Self::Iterm2 | Self::Sixel => blank,
+            // Kitty draws images on a layer of their own, so they have to be deleted.
+            Self::Kitty => format!("\x1b_Ga=d,d=c,q=2\x1b\\{blank}"),
+        }
+    }
+}
+
+impl Thumbnails {
+    /// How many columns each thumbnail takes up. Cells are about twice as tall as they are wide, so this is square.
+    pub const WIDTH: u16 = 2;
+
+    /// The smallest cover size fetched for thumbnails, in pixels.
+    pub const COVER_SIZE: u32 = 80;
+
+    /// Returns thumbnails drawn with `protocol`, fetching covers through `covers`.
+    pub fn new(protocol: GraphicsProtocol, covers: CoverCache) -> Self {
+        Self {
+            protocol,
+            covers,
+            slots: HashMap::new(),
+            drawn: HashMap::new(),
+        }
+    }
+
+    /// Puts the cover at `url` at column `x` and row `y` in this frame.
+    pub fn place(&mut self, x: u16, y: u16, url: &str) {
+        self.place_in(Rect::new(x, y, Self::WIDTH, 1), url);
+    }
+
+    /// Puts the cover at `url` in `area` in this frame, scaled to fit it.
+    pub fn place_in(&mut self, area: Rect, url: &str) {
+        let placement = Placement {
+            url: url.to_string(),
+            width: area.width,
+            height: area.height,
+        };
+        self.slots.insert((area.x, area.y), placement);
+    }
+
+    /// Puts the album covers of `tracks` next to the rows of a track table drawn in `area`,
+    /// which is scrolled down by `offset` rows and has a header with a blank line under it.
+    pub fn place_tracks(&mut self, tracks: &[Arc<Track>], area: Rect, offset: usize) {
+        let urls = tracks.iter().map(|track| track.get_cached_album().and_then(|album| album.get_cover_art_url(Self::COVER_SIZE)));
+        self.place_rows(urls, area, offset);
+    }
+
+    /// Puts covers next to the rows of a table drawn like in `place_tracks`, given the cover url of each row, if it has one.
+    pub fn place_rows<'a>(&mut self, urls: impl Iterator<Item = Option<&'a str>>, area: Rect, offset: usize) {
+        let first_row_y = area.y + 2;
+        let num_rows = area.height.saturating_sub(2) as usize;
+
+        for (row, url) in urls.skip(offset).take(num_rows).enumerate() {
+            if let Some(url) = url {
+                self.place(area.x, first_row_y + row as u16, url);
+            }
+        }
+    }
+
+    /// Forgets what is on screen, so that every thumbnail is drawn again on the next flush.
+    ///
+    /// This is needed whenever something may have been drawn over them, like after the terminal is resized.
+    pub fn invalidate(&mut self) {
+        self.slots.clear();
+        self.drawn.clear();
+    }
+
+    /// Removes every thumbnail from the screen until the next flush, for while a popup is drawn over them.
+    ///
+    /// Returns true if any were on screen, in which case other images drawn with the same protocol may have been
+    /// removed along with them.
+    pub fn hide(&mut self, out: &mut impl Write) -> io::Result<bool> {
+        let was_drawn = !self.drawn.is_empty();
+        if was_drawn {
+            write!(out, "{}", self.protocol.encode_clear())?;
+            out.flush()?;
+        }
+
+        self.invalidate();
+        Ok(was_drawn)
+    }
+
+    /// Writes the thumbnails placed since the last flush to `out`, and blanks the ones that are no longer placed.
+    ///
+    /// Covers that haven't been fetched yet are requested, and a rerender is sent through `tx` once they arrive.
+    pub fn flush(&mut self, out: &mut impl Write, tx: &mpsc::Sender<AppEvent>) -> io::Result<()> {
+        let slots = std::mem::take(&mut self.slots);
+
+        let stale = self.drawn.keys()
+            .filter(|position| slots.get(*position) != self.drawn.get(*position))
+            .copied()
+            .collect::<Vec<_>>();
+        for (x, y) in stale {
+            let Some(placement) = self.drawn.remove(&(x, y)) else {
+                continue;
+            };
+            for row in 0..placement.height {
+                queue!(out, MoveTo(x, y + row))?;
+                write!(out, "{}", self.protocol.encode_erase(placement.width))?;
+            }
+        }
+
+        for ((x, y), placement) in slots {
+            if self.drawn.contains_key(&(x, y)) {
+                continue;
+            }
+
+            let Some(image) = self.covers.get(&placement.url, tx) else {
+                continue;
+            };
+            let Some(sequence) = self.protocol.encode_image(&image, placement.width, placement.height) else {
+                continue;
+            };
+
+            queue!(out, MoveTo(x, y))?;
+            write!(out, "{sequence}")?;
+            self.drawn.insert((x, y), placement);
+        }
+
+        out.flush()
+    }
+}
+
+/// Returns the escape sequence that sends `data` to Kitty with the keys in `control`,
+/// in chunks of at most 4096 bytes of base64, as large images have to be sent.
+fn encode_kitty(data: &[u8], control: &str) -> String {
+    let encoded = STANDARD.encode(data);
+    let chunks = encoded.as_bytes().chunks(4096).collect::<Vec<_>>();
+
+    let mut sequence = String::new();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(idx < chunks.len() - 1);
+        let control = if idx == 0 { format!("{control},m={more}") } else { format!("m={more}") };
+        sequence.push_str(&format!("\x1b_G{control};{}\x1b\\", String::from_utf8_lossy(chunk)));
+    }
+
+    sequence
+}
+
+/// Returns `image` decoded and scaled to fit `width` by `height` cells, keeping its aspect ratio,
+/// or `None` if it can't be decoded.
+#[cfg(feature = "images")]
+fn decode_to_fit(image: &[u8], width: u16, height: u16) -> Option<RgbImage> {
+    let (cell_width, cell_height) = get_cell_size();
+    let image = image::load_from_memory(image).ok()?;
+
+    Some(image.resize(u32::from(width) * cell_width, u32::from(height) * cell_height, FilterType::Triangle).to_rgb8())
+}
+
+/// Returns the size of a cell of the terminal in pixels, or a typical size if the terminal doesn't say.
+#[cfg(feature = "images")]
+fn get_cell_size() -> (u32, u32) {
+    match terminal::window_size() {
+        Ok(size) if size.columns > 0 && size.rows > 0 && size.width > 0 && size.height > 0 => {
+            (u32::from(size.width / size.columns), u32::from(size.height / size.rows))
+        },
+        _ => (10, 20),
+    }
+}
+
+/// Returns the sixel escape sequence that draws `image`, with its colors rounded to a palette of 6 levels of each of
+/// red, green, and blue.
+#[cfg(feature = "images")]
+fn encode_sixel(image: &RgbImage) -> String {
+    let level = |value: u8| (u32::from(value) * 5 + 127) / 255;
+    let get_color = |x: u32, y: u32| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        level(r) * 36 + level(g) * 6 + level(b)
+    };
+
+    let (width, height) = image.dimensions();
+    let mut sequence = format!("\x1bPq\"1;1;{width};{height}");
+    let mut defined_colors = HashSet::new();
+
+    // Sixels are written 6 rows at a time, in a pass over each color in them.
+    for top in (0..height).step_by(6) {
+        let mut bands: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+        for x in 0..width {
+            for dy in 0..(height - top).min(6) {
+                bands.entry(get_color(x, top + dy))
+                    .or_insert_with(|| vec![0; width as usize])[x as usize] |= 1 << dy;
+            }
+        }
+
+        for (idx, (color, band)) in bands.iter().enumerate() {
+            // Each color is defined the first time it is used, in percentages.
+            if defined_colors.insert(*color) {
+                let [r, g, b] = [color / 36, color / 6 % 6, color % 6].map(|level| level * 20);
+                sequence.push_str(&format!("#{color};2;{r};{g};{b}"));
+            }
+            if idx > 0 {
+                sequence.push('$');
+            }
+            sequence.push_str(&format!("#{color}"));
+            push_sixel_runs(&mut sequence, band);
+        }
+        sequence.push('-');
+    }
+
+    sequence.push_str("\x1b\\");
+    sequence
+}
+
+/// Appends the sixels of a band, with runs of the same sixel shortened to a repeat count.
+#[cfg(feature = "images")]
+fn push_sixel_runs(sequence: &mut String, band: &[u8]) {
+    let mut idx = 0;
+    while idx < band.len() {
+        let run = band[idx..].iter().take_while(|sixel| **sixel == band[idx]).count();
+        let sixel = char::from(63 + band[idx]);
+
+        if run > 3 {
+            sequence.push_str(&format!("!{run}{sixel}"));
+        } else {
+            sequence.extend(std::iter::repeat_n(sixel, run));
+        }
+        idx += run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_protocols() {
+        assert_eq!(GraphicsProtocol::from_env("xterm-256color", "iTerm.app", false), Some(GraphicsProtocol::Iterm2));
+        assert_eq!(GraphicsProtocol::from_env("xterm-256color", "WezTerm", false), Some(GraphicsProtocol::Iterm2));
+        assert_eq!(GraphicsProtocol::from_env("xterm-kitty", "", false), Some(GraphicsProtocol::Kitty));
+        assert_eq!(GraphicsProtocol::from_env("xterm-256color", "", true), Some(GraphicsProtocol::Kitty));
+        assert_eq!(GraphicsProtocol::from_env("foot", "", false), Some(GraphicsProtocol::Sixel));
+        assert_eq!(GraphicsProtocol::from_env("xterm-256color", "Apple_Terminal", false), None);
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn encodes_sixels() {
+        // A red pixel above a blue one, next to two white ones.
+        let image = RgbImage::from_fn(2, 2, |x, y| match (x, y) {
+            (0, 0) => image::Rgb([255, 0, 0]),
+            (0, 1) => image::Rgb([0, 0, 255]),
+            _ => image::Rgb([255, 255, 255]),
+        });
+
+        assert_eq!(
+            encode_sixel(&image),
+            "\x1bPq\"1;1;2;2#5;2;0;0;100#5A?#180;2;100;0;0$#180@?#215;2;100;100;100$#215?B-\x1b\\",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "images")]
+    fn shortens_runs_of_sixels() {
+        let mut sequence = String::new();
+        push_sixel_runs(&mut sequence, &[1, 1, 1, 1, 1, 0, 0]);
+        assert_eq!(sequence, "!5@??");
+    }
+
+    #[test]
+    fn only_encodes_supported_formats() {
+        let jpeg = b"\xff\xd8\xff\xe0";
+
+        let sequence = GraphicsProtocol::Iterm2.encode_image(jpeg, 2, 1).unwrap();
+        assert!(sequence.starts_with("\x1b]1337;File=inline=1;size=4;width=2;height=1;"));
+        assert!(sequence.ends_with(":/9j/4A==\x07"));
+
+        assert_eq!(GraphicsProtocol::Kitty.encode_image(jpeg, 2, 1), None);
+        assert!(GraphicsProtocol::Kitty.encode_image(b"\x89PNG\r\n\x1a\n", 2, 1).is_some());
+    }
+}
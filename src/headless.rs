@@ -0,0 +1,182 @@
+use std::{
+    error::Error,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "unofficial")]
+use rtidalapi::User;
+use rtidalapi::{
+    Album,
+    Session,
+    Track,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    bandwidth::{
+        BandwidthCap,
+        BandwidthUsage,
+    },
+    blocklist::Blocklist,
+    cli::{
+        PlayArgs,
+        PlaySource,
+    },
+    config::{
+        Config,
+        FormatsConfig,
+    },
+    cover_cache::CoverCache,
+    get_cache_path,
+    get_config_path,
+    history::PlayHistory,
+    hooks,
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+    init_session,
+    ipc,
+    mqtt,
+    player::Player,
+    scrobbler::Scrobbler,
+    status_server,
+    template::TemplateValues,
+    AppEvent,
+};
+
+/// Plays the tracks given by `args` without launching the TUI.
+///
+/// A status line is printed every time playback changes, and this returns once the last track has finished.
+pub fn play(args: &PlayArgs) -> Result<(), Box<dyn Error>> {
+    let config_path = get_config_path()?;
+    let config = Config::load(&config_path)?;
+    let session = init_session(&config_path, || {})?;
+
+    let (tracks, _) = load_tracks(&session, &args.source)?;
+
+    // The player and MQTT client only use this channel to request rerenders, which we can ignore.
+    // The IPC server also hands off what another `tidal-tui play` was asked to play.
+    let (tx, mut rx) = mpsc::channel::<AppEvent>(1);
+
+    let blocklist = Arc::new(Mutex::new(Blocklist::load(&config_path)?));
+
+    let history = Arc::new(Mutex::new(PlayHistory::load(&config_path)?));
+
+    let bandwidth_usage = Arc::new(Mutex::new(BandwidthUsage::load(&config_path)?));
+    let mut bandwidth_cap = BandwidthCap::new(&config.bandwidth);
+
+    let covers = CoverCache::new(&get_cache_path()?, &config_path, Arc::clone(&bandwidth_usage));
+
+    let player = Arc::new(Mutex::new(Player::new(&config.player, blocklist, history, Arc::clone(&bandwidth_usage), covers)?));
+    Player::start_polling_thread(Arc::clone(&player), tx.clone())?;
+
+    if config.status_server.enabled {
+        status_server::start(&config.status_server.address, Arc::clone(&player))?;
+    }
+
+    if config.ipc.enabled {
+        let _ = ipc::start(Arc::clone(&player), Arc::clone(&session), tx.clone());
+    }
+
+    if config.mqtt.enabled {
+        mqtt::start(&config.mqtt, Arc::clone(&player), tx)?;
+    }
+
+    hooks::start(&config.hooks, &config_path, Arc::clone(&player));
+
+    if let Some(scrobbler) = Scrobbler::new(&config.scrobbling) {
+        scrobbler.start(Arc::clone(&player));
+    }
+
+    {
+        let mut unlocked_player = player.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+
+        if let Some(volume) = args.volume {
+            unlocked_player.set_volume(volume);
+        }
+
+        unlocked_player.set_queue(tracks);
+        if args.shuffle {
+            unlocked_player.shuffle_queue();
+        }
+        unlocked_player.play()?;
+    }
+
+    let mut last_status = String::new();
+
+    loop {
+        thread::sleep(Duration::from_millis(250));
+
+        let mut unlocked_player = player.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+
+        while let Ok(app_event) = rx.try_recv() {
+            if let AppEvent::PlayHandoff(tracks, _, shuffle) = app_event {
+                unlocked_player.set_queue(tracks);
+                if shuffle {
+                    unlocked_player.shuffle_queue();
+                }
+                unlocked_player.play()?;
+            }
+        }
+
+        if unlocked_player.has_finished_queue() {
+            println!("Finished");
+            return Ok(());
+        }
+
+        if let Some(message) = bandwidth_cap.update(&bandwidth_usage.lock().unwrap(), &session) {
+            println!("{message}");
+        }
+
+        let status = status_line(&unlocked_player, &config.formats);
+        if status != last_status {
+            println!("{status}");
+            last_status = status;
+        }
+    }
+}
+
+/// Fetches the tracks that `source` refers to, and returns them with where they are playing from.
+///
+/// Fails if there are none, since there would be nothing to play.
+pub(crate) fn load_tracks(session: &Arc<Session>, source: &PlaySource) -> Result<(Vec<Arc<Track>>, String), String> {
+    let (tracks, playing_from) = match source {
+        PlaySource::Track(id) => (vec![Arc::new(Track::new(Arc::clone(session), id.clone())?)], String::from(tr(Message::CommandLine))),
+        PlaySource::Album(id) => {
+            let album = Album::new(Arc::clone(session), id.clone())?;
+            let tracks: Vec<Arc<Track>> = album.get_tracks()?.iter().cloned().map(Arc::new).collect();
+            (tracks, tr_with(Message::AlbumTitle, &[("album", &album.attributes.title)]))
+        },
+        #[cfg(feature = "unofficial")]
+        PlaySource::Collection => {
+            let user = User::get_current_user(Arc::clone(session))?;
+            let tracks: Vec<Arc<Track>> = user.get_collection_tracks()?.iter().cloned().map(Arc::new).collect();
+            (tracks, String::from(tr(Message::Tracks)))
+        },
+        #[cfg(not(feature = "unofficial"))]
+        PlaySource::Collection => return Err(String::from(tr(Message::NeedsUnofficialApi))),
+    };
+
+    if tracks.is_empty() {
+        return Err(String::from("Nothing to play"));
+    }
+
+    Ok((tracks, playing_from))
+}
+
+/// Returns a line describing what `player` is currently doing, using the headless status format.
+fn status_line(player: &Player, formats: &FormatsConfig) -> String {
+    match TemplateValues::from_player(player) {
+        Some(values) => formats.headless_status.render(&values),
+        None => String::from("Loading..."),
+    }
+}
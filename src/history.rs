@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// How often, and when last, each track has been played.
+///
+/// The history is stored in `history.toml` within the config directory.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PlayHistory {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    /// Play records by track id.
+    #[serde(default)]
+    tracks: HashMap<String, PlayRecord>,
+}
+
+/// The play count and last played time of a track.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayRecord {
+    pub play_count: u32,
+    /// When the track was last played, in seconds since the Unix epoch.
+    last_played_secs: u64,
+}
+
+impl PlayHistory {
+    /// Name of the history file within the config directory.
+    const FILE_NAME: &str = "history.toml";
+
+    /// Loads the play history from `config_folder_path`, which is empty if nothing has been played yet.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let history_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut history = if history_file.exists() {
+            let toml_str = fs::read_to_string(&history_file)
+                .map_err(|e| format!("Unable to read {}: {e}", history_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", history_file.display()))?
+        } else {
+            Self::default()
+        };
+
+        history.file = Some(history_file);
+
+        Ok(history)
+    }
+
+    /// Saves the play history to the file it was loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(history_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the play history: {e}"))?;
+
+        fs::write(history_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", history_file.display()))
+    }
+
+    /// Records that the track with id `track_id` was played at `played_at`.
+    pub fn record_play(&mut self, track_id: &str, played_at: SystemTime) {
+        let last_played_secs = played_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        self.tracks
+            .entry(track_id.to_string())
+            .and_modify(|record| {
+                record.play_count += 1;
+                record.last_played_secs = last_played_secs;
+            })
+            .or_insert(PlayRecord {
+                play_count: 1,
+                last_played_secs,
+            });
+    }
+
+    /// Returns the ids of tracks that have been played at least `min_play_count` times, but not within `not_played_for` of `now`,
+    /// most played first.
+    pub fn get_rediscoveries(&self, now: SystemTime, min_play_count: u32, not_played_for: Duration) -> Vec<String> {
+        let mut rediscoveries: Vec<(&String, &PlayRecord)> = self.tracks
+            .iter()
+            .filter(|(_, record)| record.play_count >= min_play_count)
+            .filter(|(_, record)| now.duration_since(record.last_played()).is_ok_and(|elapsed| elapsed >= not_played_for))
+            .collect();
+
+        // Break ties by id so that the order doesn't change between calls.
+        rediscoveries.sort_by(|(a_id, a), (b_id, b)| b.play_count.cmp(&a.play_count).then(a_id.cmp(b_id)));
+
+        rediscoveries.into_iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    /// Returns the play record of the track with id `track_id`, or `None` if it has never been played.
+    pub fn get(&self, track_id: &str) -> Option<&PlayRecord> {
+        self.tracks.get(track_id)
+    }
+}
+
+impl PlayRecord {
+    /// Returns when the track was last played.
+    pub fn last_played(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.last_played_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_plays() {
+        let mut history = PlayHistory::default();
+        let first_play = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let second_play = first_play + Duration::from_secs(60);
+
+        history.record_play("77646170", first_play);
+        history.record_play("77646170", second_play);
+
+        let record = history.get("77646170").unwrap();
+        assert_eq!(record.play_count, 2);
+        assert_eq!(record.last_played(), second_play);
+        assert!(history.get("77646169").is_none());
+    }
+
+    #[test]
+    fn finds_rediscoveries() {
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut history = PlayHistory::default();
+
+        // Played a lot, but not for two months.
+        for _ in 0..8 {
+            history.record_play("1", now - DAY * 60);
+        }
+        // Played a lot, and yesterday.
+        for _ in 0..12 {
+            history.record_play("2", now - DAY);
+        }
+        // Played a bit, a long time ago.
+        history.record_play("3", now - DAY * 90);
+        // Played even more, but not for a month.
+        for _ in 0..10 {
+            history.record_play("4", now - DAY * 30);
+        }
+
+        assert_eq!(history.get_rediscoveries(now, 5, DAY * 30), vec!["4", "1"]);
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let mut history = PlayHistory::default();
+        history.record_play("77646170", UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+
+        let toml_str = toml::to_string(&history).unwrap();
+        assert_eq!(toml::from_str::<PlayHistory>(&toml_str).unwrap(), history);
+    }
+}
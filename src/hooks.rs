@@ -0,0 +1,294 @@
+use std::{
+    error::Error,
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    process::{
+        Command,
+        Output,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+
+use crate::{
+    config::HooksConfig,
+    player::Player,
+    status_server::{
+        PlaybackState,
+        PlayerStatus,
+    },
+};
+
+/// A change in playback that a hook can be run on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HookEvent {
+    /// A different track started playing.
+    TrackChanged,
+    /// Playback was paused.
+    Paused,
+    /// Playback stopped, because the queue finished or was cleared.
+    Stopped,
+}
+
+/// What is playing at one moment, compared with the last to tell which events happened.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Snapshot {
+    track_id: Option<String>,
+    state: PlaybackState,
+}
+
+impl Snapshot {
+    /// Returns the snapshot of nothing playing, to compare the first one taken with.
+    pub(crate) fn stopped() -> Self {
+        Self {
+            track_id: None,
+            state: PlaybackState::Stopped,
+        }
+    }
+
+    /// Returns what `player` is playing now.
+    pub(crate) fn from_player(player: &Player) -> Self {
+        Self {
+            track_id: player.get_current_track().map(|track| track.id.clone()),
+            state: PlaybackState::from_player(player),
+        }
+    }
+}
+
+impl HookEvent {
+    /// Returns the name of this event, as it is written in the config and `TIDAL_TUI_EVENT`.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::TrackChanged => "track_changed",
+            Self::Paused => "paused",
+            Self::Stopped => "stopped",
+        }
+    }
+
+    /// Returns the command configured to run on this event, if any.
+    fn get_command<'a>(&self, config: &'a HooksConfig) -> Option<&'a str> {
+        match self {
+            Self::TrackChanged => config.track_changed.as_deref(),
+            Self::Paused => config.paused.as_deref(),
+            Self::Stopped => config.stopped.as_deref(),
+        }
+        .filter(|command| !command.trim().is_empty())
+    }
+}
+
+/// Watches `player` from a background thread, running the commands in `config` as playback changes.
+///
+/// Each command runs in its own thread so that a slow one doesn't hold up the others, and what it prints
+/// is appended to `hooks.log` in the config directory at `config_folder_path`.
+pub fn start(config: &HooksConfig, config_folder_path: &str, player: Arc<Mutex<Player>>) {
+    /// How often the player is checked for changes.
+    const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    let events = [HookEvent::TrackChanged, HookEvent::Paused, HookEvent::Stopped];
+    let hooks: Vec<(HookEvent, String)> = events
+        .into_iter()
+        .filter_map(|event| Some((event, event.get_command(config)?.to_string())))
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let log_path = Path::new(config_folder_path).join("hooks.log");
+
+    thread::spawn(move || {
+        let mut last = Snapshot::stopped();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let Some((current, track)) = player.lock().ok().map(|unlocked_player| {
+                (Snapshot::from_player(&unlocked_player), unlocked_player.get_current_track().cloned())
+            }) else {
+                continue;
+            };
+
+            let events = get_events(&last, &current);
+            last = current;
+            if events.is_empty() {
+                continue;
+            }
+
+            // Make sure the new track's info has been fetched, so that hooks can use it.
+            if events.contains(&HookEvent::TrackChanged) && let Some(track) = track {
+                let _ = track.fetch_info();
+            }
+            let Ok(status) = player.lock().map(|unlocked_player| PlayerStatus::from_player(&unlocked_player)) else {
+                continue;
+            };
+
+            for event in events {
+                let Some((_, command)) = hooks.iter().find(|(hook_event, _)| *hook_event == event) else {
+                    continue;
+                };
+
+                let command = command.clone();
+                let env = get_env(event, &status);
+                let log_path = log_path.clone();
+                thread::spawn(move || run_hook(event, &command, env, &log_path));
+            }
+        }
+    });
+}
+
+/// Returns the events that happened between `last` and `current`, in the order their hooks are run.
+pub(crate) fn get_events(last: &Snapshot, current: &Snapshot) -> Vec<HookEvent> {
+    let mut events = vec![];
+
+    if current.track_id.is_some() && current.track_id != last.track_id {
+        events.push(HookEvent::TrackChanged);
+    }
+    if current.state == PlaybackState::Paused && last.state == PlaybackState::Playing {
+        events.push(HookEvent::Paused);
+    }
+    if current.state == PlaybackState::Stopped && last.state != PlaybackState::Stopped {
+        events.push(HookEvent::Stopped);
+    }
+
+    events
+}
+
+/// Returns the environment variables a hook for `event` is run with, describing the player's `status`.
+/// Info that isn't known (e.g. the track, once stopped) is empty.
+fn get_env(event: HookEvent, status: &PlayerStatus) -> Vec<(&'static str, String)> {
+    let track = status.track.as_ref();
+
+    vec![
+        ("TIDAL_TUI_EVENT", String::from(event.get_name())),
+        ("TIDAL_TUI_STATE", String::from(status.state.get_name())),
+        ("TIDAL_TUI_TRACK_ID", track.map(|track| track.id.clone()).unwrap_or_default()),
+        ("TIDAL_TUI_TITLE", track.and_then(|track| track.title.clone()).unwrap_or_default()),
+        ("TIDAL_TUI_ARTIST", track.and_then(|track| track.artist.clone()).unwrap_or_default()),
+        ("TIDAL_TUI_ALBUM", track.and_then(|track| track.album.clone()).unwrap_or_default()),
+        ("TIDAL_TUI_DURATION", track.and_then(|track| track.duration_secs).map(|secs| secs.to_string()).unwrap_or_default()),
+        ("TIDAL_TUI_POSITION", status.position_secs.to_string()),
+        ("TIDAL_TUI_VOLUME", status.volume.to_string()),
+    ]
+}
+
+/// Runs `command` in the shell with `env`, appending what it printed and how it exited to the log at `log_path`.
+fn run_hook(event: HookEvent, command: &str, env: Vec<(&'static str, String)>, log_path: &Path) {
+    let output = shell_command(command)
+        .envs(env)
+        .output();
+
+    // Nothing can be shown from a background thread while the TUI is drawn, so a log that can't be written is ignored.
+    let _ = append_to_log(log_path, &format_log_entry(event, command, &output));
+}
+
+/// Returns a command that runs `command` in the platform's shell.
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    shell.arg(command);
+    shell
+}
+
+/// Returns the log entry for a hook for `event` that ran `command` with `output`.
+fn format_log_entry(event: HookEvent, command: &str, output: &std::io::Result<Output>) -> String {
+    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let mut entry = format!("[{time}] {}: {command}\n", event.get_name());
+
+    match output {
+        Ok(output) => {
+            for stream in [&output.stdout, &output.stderr] {
+                let text = String::from_utf8_lossy(stream);
+                for line in text.lines() {
+                    entry.push_str(&format!("  {line}\n"));
+                }
+            }
+            entry.push_str(&format!("  ({})\n", output.status));
+        },
+        Err(e) => entry.push_str(&format!("  (failed to run: {e})\n")),
+    }
+
+    entry
+}
+
+/// Appends `entry` to the log at `log_path`, creating it if it doesn't exist.
+fn append_to_log(log_path: &Path, entry: &str) -> Result<(), Box<dyn Error>> {
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)?;
+    // Written all at once, so that the entries of hooks running at the same time don't interleave.
+    log.write_all(entry.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::status_server::TrackStatus;
+
+    use super::*;
+
+    fn snapshot(track_id: Option<&str>, state: PlaybackState) -> Snapshot {
+        Snapshot {
+            track_id: track_id.map(String::from),
+            state,
+        }
+    }
+
+    #[test]
+    fn detects_events() {
+        let stopped = snapshot(None, PlaybackState::Stopped);
+        let playing_a = snapshot(Some("a"), PlaybackState::Playing);
+        let paused_a = snapshot(Some("a"), PlaybackState::Paused);
+        let playing_b = snapshot(Some("b"), PlaybackState::Playing);
+
+        assert_eq!(get_events(&stopped, &playing_a), vec![HookEvent::TrackChanged]);
+        assert_eq!(get_events(&playing_a, &playing_a), vec![]);
+        assert_eq!(get_events(&playing_a, &paused_a), vec![HookEvent::Paused]);
+        assert_eq!(get_events(&paused_a, &playing_a), vec![]);
+        assert_eq!(get_events(&playing_a, &playing_b), vec![HookEvent::TrackChanged]);
+        assert_eq!(get_events(&playing_b, &stopped), vec![HookEvent::Stopped]);
+        assert_eq!(get_events(&stopped, &stopped), vec![]);
+    }
+
+    #[test]
+    fn describes_status_in_env() {
+        let status = PlayerStatus {
+            state: PlaybackState::Playing,
+            track: Some(TrackStatus {
+                id: String::from("77646170"),
+                title: Some(String::from("Hurt")),
+                artist: Some(String::from("Nine Inch Nails")),
+                album: None,
+                duration_secs: Some(373),
+            }),
+            position_secs: 0,
+            queue_length: 3,
+            volume: 80,
+        };
+
+        let env = get_env(HookEvent::TrackChanged, &status);
+
+        assert!(env.contains(&("TIDAL_TUI_EVENT", String::from("track_changed"))));
+        assert!(env.contains(&("TIDAL_TUI_TITLE", String::from("Hurt"))));
+        assert!(env.contains(&("TIDAL_TUI_ALBUM", String::new())));
+        assert!(env.contains(&("TIDAL_TUI_DURATION", String::from("373"))));
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    cmp::Reverse,
+    collections::{
+        BTreeSet,
+        HashMap,
+        HashSet,
+    },
+    sync::{
+        Arc,
+        Condvar,
+        Mutex,
+    },
+    thread,
+};
+
+use rtidalapi::Track;
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// How urgently a track's info is needed, from lowest to highest.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Priority {
+    /// Tracks that aren't on screen, fetched so that the whole collection is eventually loaded.
+    Background,
+    /// Tracks just outside the visible rows, fetched so that they are ready when scrolled to.
+    Margin,
+    /// Tracks in the visible rows.
+    Visible,
+    /// The currently playing track and the one up next.
+    Playing,
+}
+
+/// Fetches track info (attributes, album, and artist) in the background, highest priority first,
+/// using a fixed number of worker threads so that Tidal's rate limits aren't hit.
+///
+/// Each track is only fetched once at a time.
+#[derive(Clone)]
+pub struct Hydrator {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    condvar: Condvar,
+    tx: mpsc::Sender<AppEvent>,
+}
+
+/// The requests the workers take from, along with the tracks they are for.
+#[derive(Default)]
+struct State {
+    queue: PendingQueue,
+    /// The tracks of the pending requests, by id.
+    tracks: HashMap<String, Arc<Track>>,
+    /// True while nothing should be fetched (e.g. while offline), so that tracks aren't marked as failed for no reason.
+    is_paused: bool,
+}
+
+/// The ids of the pending and in flight requests, which decides which one is fetched next.
+#[derive(Default)]
+struct PendingQueue {
+    /// Pending requests ordered by highest priority, then by oldest.
+    pending: BTreeSet<(Reverse<Priority>, u64, String)>,
+    requests: HashMap<String, Request>,
+    in_flight: HashSet<String>,
+    next_seq: u64,
+}
+
+struct Request {
+    priority: Priority,
+    seq: u64,
+}
+
+impl Hydrator {
+    /// Returns a new `Hydrator` that fetches up to `concurrency` tracks at once,
+    /// and requests a rerender through `tx` whenever a track finishes fetching.
+    pub fn new(tx: mpsc::Sender<AppEvent>, concurrency: usize) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            condvar: Condvar::new(),
+            tx,
+        });
+
+        for _ in 0..concurrency.max(1) {
+            let shared_clone = Arc::clone(&shared);
+            thread::spawn(move || Self::run_worker(shared_clone));
+        }
+
+        Self { shared }
+    }
+
+    /// Fetches `track`'s info in the background with `priority`, unless it has already been fetched or failed.
+    ///
+    /// If `track` is already waiting to be fetched with a lower priority, its priority is raised.
+    pub fn request(&self, track: &Arc<Track>, priority: Priority) {
+        if track.has_info() {
+            return;
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        // Tracks that failed are only fetched again through `retry`.
+        if track.get_info_error().is_some() && !state.queue.is_pending(&track.id) {
+            return;
+        }
+
+        if state.request(track, priority) {
+            self.shared.condvar.notify_one();
+        }
+    }
+
+    /// Fetches `track`'s info in the background again, even if it failed before.
+    pub fn retry(&self, track: &Arc<Track>) {
+        if track.has_info() {
+            return;
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        if state.request(track, Priority::Visible) {
+            self.shared.condvar.notify_one();
+        }
+    }
+
+    /// Stops fetching tracks until this is called again with `is_paused` set to false. Requests are kept in the meantime.
+    pub fn set_paused(&self, is_paused: bool) {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.is_paused == is_paused {
+            return;
+        }
+
+        state.is_paused = is_paused;
+        if !is_paused {
+            self.shared.condvar.notify_all();
+        }
+    }
+
+    /// Returns true if `track`'s info is waiting to be fetched or is currently being fetched.
+    pub fn is_loading(&self, track: &Track) -> bool {
+        self.shared.state.lock().unwrap().queue.is_loading(&track.id)
+    }
+
+    /// Lowers every pending request to background priority.
+    ///
+    /// This is called at the start of each frame, so that only the tracks that are still on screen
+    /// (and requested again while drawing) keep their priority.
+    pub fn reset_priorities(&self) {
+        self.shared.state.lock().unwrap().queue.reset_priorities();
+    }
+
+    /// Fetches the highest priority track, one at a time, forever.
+    fn run_worker(shared: Arc<Shared>) {
+        loop {
+            let track = {
+                let mut state = shared.state.lock().unwrap();
+
+                loop {
+                    if !state.is_paused && let Some(track) = state.pop() {
+                        break track;
+                    }
+                    state = shared.condvar.wait(state).unwrap();
+                }
+            };
+
+            let _ = track.fetch_info();
+
+            shared.state.lock().unwrap().queue.finish(&track.id);
+            let _ = shared.tx.try_send(AppEvent::ReRender);
+        }
+    }
+}
+
+impl State {
+    /// Adds a pending request for `track` with `priority`, or raises the priority of the one already pending for it.
+    ///
+    /// Returns true if a new request was added, which isn't the case while `track` is pending or in flight.
+    fn request(&mut self, track: &Arc<Track>, priority: Priority) -> bool {
+        if !self.queue.request(&track.id, priority) {
+            return false;
+        }
+
+        self.tracks.insert(track.id.clone(), Arc::clone(track));
+        true
+    }
+
+    /// Removes the highest priority pending track and marks it as in flight.
+    fn pop(&mut self) -> Option<Arc<Track>> {
+        let id = self.queue.pop()?;
+        self.tracks.remove(&id)
+    }
+}
+
+impl PendingQueue {
+    /// Adds a pending request for `id` with `priority`, or raises the priority of the one already pending for it.
+    ///
+    /// Returns true if a new request was added, which isn't the case while `id` is pending or in flight.
+    fn request(&mut self, id: &str, priority: Priority) -> bool {
+        if self.requests.contains_key(id) {
+            self.raise_priority(id, priority);
+            return false;
+        }
+
+        if self.in_flight.contains(id) {
+            return false;
+        }
+
+        self.push(id, priority);
+        true
+    }
+
+    /// Adds a pending request for `id`.
+    fn push(&mut self, id: &str, priority: Priority) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.pending.insert((Reverse(priority), seq, id.to_string()));
+        self.requests.insert(id.to_string(), Request {
+            priority,
+            seq,
+        });
+    }
+
+    /// Returns true if `id` is waiting to be fetched.
+    fn is_pending(&self, id: &str) -> bool {
+        self.requests.contains_key(id)
+    }
+
+    /// Returns true if `id` is waiting to be fetched or is currently being fetched.
+    fn is_loading(&self, id: &str) -> bool {
+        self.is_pending(id) || self.in_flight.contains(id)
+    }
+
+    /// Raises the priority of the pending request for `id` to `priority`, if it is lower.
+    fn raise_priority(&mut self, id: &str, priority: Priority) {
+        let Some(request) = self.requests.get_mut(id) else {
+            return;
+        };
+
+        if request.priority >= priority {
+            return;
+        }
+
+        self.pending.remove(&(Reverse(request.priority), request.seq, id.to_string()));
+        request.priority = priority;
+        self.pending.insert((Reverse(request.priority), request.seq, id.to_string()));
+    }
+
+    /// Lowers every pending request to background priority.
+    fn reset_priorities(&mut self) {
+        for (id, request) in self.requests.iter_mut() {
+            if request.priority == Priority::Background {
+                continue;
+            }
+
+            self.pending.remove(&(Reverse(request.priority), request.seq, id.clone()));
+            request.priority = Priority::Background;
+            self.pending.insert((Reverse(request.priority), request.seq, id.clone()));
+        }
+    }
+
+    /// Removes the highest priority pending request and marks it as in flight, returning its id.
+    fn pop(&mut self) -> Option<String> {
+        let (_, _, id) = self.pending.pop_first()?;
+        self.requests.remove(&id);
+        self.in_flight.insert(id.clone());
+
+        Some(id)
+    }
+
+    /// Marks the request for `id` as no longer in flight, once it has been fetched.
+    fn finish(&mut self, id: &str) {
+        self.in_flight.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the ids of every pending request in the order they would be fetched.
+    fn pop_all(queue: &mut PendingQueue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn pops_highest_priority_then_oldest_first() {
+        let mut queue = PendingQueue::default();
+        queue.push("background", Priority::Background);
+        queue.push("visible", Priority::Visible);
+        queue.push("margin", Priority::Margin);
+        queue.push("playing", Priority::Playing);
+        queue.push("second visible", Priority::Visible);
+
+        assert_eq!(pop_all(&mut queue), ["playing", "visible", "second visible", "margin", "background"]);
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn only_raises_priority() {
+        let mut queue = PendingQueue::default();
+        queue.push("a", Priority::Margin);
+        queue.push("b", Priority::Margin);
+        queue.push("c", Priority::Visible);
+
+        queue.raise_priority("b", Priority::Playing);
+        queue.raise_priority("c", Priority::Background);
+        queue.raise_priority("missing", Priority::Playing);
+
+        assert_eq!(pop_all(&mut queue), ["b", "c", "a"]);
+        assert!(queue.requests.is_empty());
+    }
+
+    #[test]
+    fn resets_priorities_keeping_order_of_requests() {
+        let mut queue = PendingQueue::default();
+        queue.push("a", Priority::Background);
+        queue.push("b", Priority::Visible);
+        queue.push("c", Priority::Playing);
+
+        queue.reset_priorities();
+        assert!(queue.requests.values().all(|request| request.priority == Priority::Background));
+        assert_eq!(queue.pending.len(), 3);
+
+        // Tracks requested again while drawing the next frame get their priority back.
+        queue.raise_priority("c", Priority::Visible);
+        assert_eq!(pop_all(&mut queue), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn dedupes_pending_and_in_flight_requests() {
+        let mut queue = PendingQueue::default();
+        assert!(queue.request("a", Priority::Margin));
+        assert!(!queue.request("a", Priority::Visible));
+        assert_eq!(queue.pending.len(), 1);
+        assert_eq!(queue.requests["a"].priority, Priority::Visible);
+
+        assert!(queue.pop().is_some());
+        assert!(queue.is_loading("a"));
+        assert!(!queue.request("a", Priority::Playing));
+        assert!(queue.pending.is_empty());
+
+        queue.finish("a");
+        assert!(!queue.is_loading("a"));
+        assert!(queue.request("a", Priority::Playing));
+    }
+}
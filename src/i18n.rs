@@ -0,0 +1,658 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs,
+    path::Path,
+    sync::OnceLock,
+};
+
+use serde::Deserialize;
+
+/// The catalog used by `tr`, set once on startup.
+///
+/// Messages are shown from the UI, the player's polling thread, and background tasks alike,
+/// so the catalog is global instead of being passed to each of them.
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Catalogs that come with the app, by language code.
+const BUILT_IN_CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("../messages/de.toml")),
+    ("fr", include_str!("../messages/fr.toml")),
+];
+
+/// A piece of text shown in the UI.
+///
+/// Each is written as its snake_case name in catalog files (e.g. `now_playing = "Wiedergabe"`).
+/// Words in braces (e.g. `{count}`) are filled in when the text is shown, and have to be kept by translations.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Message {
+    // Panes
+    MyCollectionTracks,
+    MyCollectionAlbums,
+    MyCollectionArtists,
+    Queue,
+    Settings,
+    NotSet,
+    NowPlaying,
+    TrackInfo,
+    Loading,
+    Error,
+    FetchingFavorites,
+    FetchFavoritesFailed,
+    FetchedFavorites,
+    NothingQueued,
+    NothingPlaying,
+    Lyrics,
+    LoadingLyrics,
+    NoLyrics,
+    LyricsProvider,
+    NoPublicPlaylists,
+    PlaylistEmpty,
+    NothingToRediscover,
+    UserPlaylists,
+    PlaylistTitle,
+    Rediscover,
+    Tracks,
+    Album,
+    AlbumTitle,
+    CommandLine,
+    AlbumNotLoaded,
+    Albums,
+    Artists,
+    Playlists,
+    SearchTitle,
+    ArtistTitle,
+    NothingFound,
+    NoTracks,
+    NoCollectionAlbums,
+    NoCollectionArtists,
+    TrackCountOne,
+    TrackCountOther,
+    DiscCountOne,
+    DiscCountOther,
+    CoverSize,
+    NoCoverArt,
+
+    // Table columns
+    ColumnTitle,
+    ColumnArtist,
+    ColumnAlbum,
+    ColumnTime,
+    ColumnComposer,
+    ColumnWork,
+    ColumnMovement,
+    ColumnPlays,
+    ColumnLastPlayed,
+    ColumnName,
+    ColumnTracks,
+    ColumnVisibility,
+    ColumnYear,
+    ColumnType,
+    ColumnSetting,
+    ColumnValue,
+
+    // Tracks and playlists
+    UnknownTrack,
+    UnknownArtist,
+    UnknownAlbum,
+    UnknownComposer,
+    Unknown,
+    Track,
+    LoadFailed,
+    TheRetryKey,
+    Collaborative,
+    CollaborativeTag,
+    AlreadyAddedTag,
+    Public,
+    Unlisted,
+    InviteLink,
+    NextTab,
+    History,
+
+    // Now Playing
+    ShuffleOn,
+    ShuffleOnClean,
+    ShuffleOnFiltered,
+    ShuffleOff,
+    RepeatOff,
+    PlayingOnAnotherDevice,
+    Volume,
+    Quality,
+    PlayingInQuality,
+    PlayingFrom,
+    UpNext,
+
+    // Header
+    Online,
+    ConnectingToTidal,
+    Offline,
+    OfflineMode,
+    PendingChanges,
+
+    // Menus and prompts
+    AddToPlaylist,
+    AddToQueue,
+    PlayNow,
+    PlayFromHere,
+    PlayNext,
+    GoToAlbum,
+    GoToArtist,
+    AddToCollection,
+    RemoveFromCollection,
+    NoPlaylists,
+    LoadingPlaylists,
+    Blocklist,
+    NothingBlocked,
+    QueueNotFinished,
+    ReplaceTheQueue,
+    Cancel,
+    PreviousQueues,
+    PreviousQueue,
+    ShuffledTag,
+    NoPreviousQueues,
+    Diagnostics,
+    RunningChecks,
+    Caches,
+    CacheCovers,
+    CachePlayerState,
+    CachePendingWrites,
+    CacheSizeOfMax,
+    Stats,
+    Artist,
+    Playlist,
+    User,
+    Mix,
+    Pages,
+    NoPages,
+    PageEmpty,
+    Home,
+    HomeEmpty,
+    NothingToPlay,
+    PlayItemFailed,
+    MyMixes,
+    Help,
+    HelpGlobal,
+    HelpPlayback,
+    HelpLayout,
+    HelpTables,
+    HelpTrackLists,
+    HelpBrowsing,
+    HelpTabs,
+    HelpTrackInfo,
+    HelpMenus,
+    RadioTitle,
+    LoadingRadio,
+    Slot,
+    SlotEmpty,
+    NoSlots,
+    ScriptCommands,
+    NoScriptCommands,
+    OpenLinkPrompt,
+    ImportM3uPrompt,
+    SearchPrompt,
+    Open,
+    Import,
+    Search,
+
+    // Keybind hints
+    Play,
+    Shuffle,
+    Info,
+    Back,
+    Close,
+    Select,
+
+    // Track info
+    Released,
+    Duration,
+    Explicit,
+    Popularity,
+    Isrc,
+    Copyright,
+    Qualities,
+    InPlaylists,
+    StreamHost,
+    TrackId,
+    Yes,
+    No,
+    InNoPlaylists,
+    NotPlaying,
+    AndMore,
+
+    // OSD messages
+    PlaybackTakenOver,
+    ThePlayKey,
+    DiagnosticsReportCopied,
+    CopyReportFailed,
+    OfflineModeOn,
+    OfflineModeOff,
+    NotUserOrPlaylistLink,
+    NotTrackOrPlaylistLink,
+    AddedTrackToQueue,
+    AddedTracksToQueue,
+    PlayingNext,
+    AddToQueueFailed,
+    NothingToExport,
+    Exported,
+    NoTrackLinks,
+    ImportDeferred,
+    Imported,
+    ImportFailed,
+    AddedToPlaylist,
+    AddToPlaylistDeferred,
+    AddToPlaylistFailed,
+    MoveTrackFailed,
+    OnlyOwnPlaylistsReordered,
+    ArtistNotLoaded,
+    Blocked,
+    Unblocked,
+    QueueTooNarrow,
+    NothingSearched,
+    TheSearchKey,
+    NoMatches,
+    OnlyCollectionGrouped,
+    OnlyCollectionSorted,
+    SortedBy,
+    SortDateAdded,
+    SortReleaseDate,
+    SortTidalOrder,
+    RestoreQueueFailed,
+    ResumeFailed,
+    AddedToCollection,
+    RemovedFromCollection,
+    AddedToCollectionDeferred,
+    RemovedFromCollectionDeferred,
+    UpdateCollectionFailed,
+    SkippingExplicit,
+    PlayingExplicit,
+    SkippingGenres,
+    PlayingAllGenres,
+    NoExcludedGenres,
+    InviteLinkCopied,
+    InviteLinkCreated,
+    InviteLinkFailed,
+    SyncedChange,
+    SyncedChanges,
+    SyncFailed,
+    PendingAddToPlaylist,
+    PendingAddToCollection,
+    PendingRemoveFromCollection,
+    NoLongerYourPlaylist,
+    SkippedDrmProtected,
+    AudioDeviceReconnected,
+    AudioDeviceChanged,
+    AudioDeviceDisconnected,
+    PausedAfterSleep,
+    FullyBuffered,
+    NothingPlayableOffline,
+    Purged,
+    PurgeFailed,
+    NeedsUnofficialApi,
+}
+
+impl Message {
+    /// Returns the English text of this message, which is used when a catalog doesn't have it.
+    fn get_english(&self) -> &'static str {
+        match self {
+            Self::MyCollectionTracks => "My Collection - Tracks",
+            Self::MyCollectionAlbums => "My Collection - Albums",
+            Self::MyCollectionArtists => "My Collection - Artists",
+            Self::Queue => "Queue",
+            Self::Settings => "Settings",
+            Self::NotSet => "not set",
+            Self::NowPlaying => "Now Playing",
+            Self::TrackInfo => "Track Info",
+            Self::Loading => "Loading...",
+            Self::Error => "Error",
+            Self::FetchingFavorites => "Fetching favorites…",
+            Self::FetchFavoritesFailed => "Couldn't load your collection: {error}",
+            Self::FetchedFavorites => "Fetched {fetched} / {total} favorites…",
+            Self::NothingQueued => "Nothing queued",
+            Self::NothingPlaying => "Nothing playing",
+            Self::Lyrics => "Lyrics",
+            Self::LoadingLyrics => "Loading lyrics...",
+            Self::NoLyrics => "No lyrics for this track",
+            Self::LyricsProvider => "Lyrics by {provider}",
+            Self::NoPublicPlaylists => "No public playlists",
+            Self::PlaylistEmpty => "This playlist is empty",
+            Self::NothingToRediscover => "Nothing to rediscover yet. Tracks played at least {plays} times, but not in the last {days} days, show up here.",
+            Self::UserPlaylists => "{user} - Playlists",
+            Self::PlaylistTitle => "Playlist - {playlist}",
+            Self::Rediscover => "Rediscover",
+            Self::Tracks => "Tracks",
+            Self::Album => "Album",
+            Self::AlbumTitle => "Album - {album}",
+            Self::CommandLine => "Command line",
+            Self::AlbumNotLoaded => "Album not loaded yet",
+            Self::Albums => "Albums",
+            Self::Artists => "Artists",
+            Self::Playlists => "Playlists",
+            Self::SearchTitle => "Search - {query}",
+            Self::ArtistTitle => "Artist - {artist}",
+            Self::NothingFound => "Nothing found",
+            Self::NoTracks => "No tracks",
+            Self::NoCollectionAlbums => "No albums in your collection yet",
+            Self::NoCollectionArtists => "No followed artists yet",
+            Self::TrackCountOne => "track",
+            Self::TrackCountOther => "tracks",
+            Self::DiscCountOne => "disc",
+            Self::DiscCountOther => "discs",
+            Self::CoverSize => "Cover {width}×{height}",
+            Self::NoCoverArt => "No cover art",
+
+            Self::ColumnTitle => "Title",
+            Self::ColumnArtist => "Artist",
+            Self::ColumnAlbum => "Album",
+            Self::ColumnTime => "Time",
+            Self::ColumnComposer => "Composer",
+            Self::ColumnWork => "Work",
+            Self::ColumnMovement => "Movement",
+            Self::ColumnPlays => "Plays",
+            Self::ColumnLastPlayed => "Last played",
+            Self::ColumnName => "Name",
+            Self::ColumnTracks => "Tracks",
+            Self::ColumnVisibility => "Visibility",
+            Self::ColumnYear => "Year",
+            Self::ColumnType => "Type",
+            Self::ColumnSetting => "Setting",
+            Self::ColumnValue => "Value",
+
+            Self::UnknownTrack => "Unknown track ({id})",
+            Self::UnknownArtist => "Unknown artist",
+            Self::UnknownAlbum => "Unknown album",
+            Self::UnknownComposer => "Unknown composer",
+            Self::Unknown => "Unknown",
+            Self::Track => "Track",
+            Self::LoadFailed => "failed, press {key} to retry",
+            Self::TheRetryKey => "the retry key",
+            Self::Collaborative => "Collaborative",
+            Self::CollaborativeTag => "(collaborative)",
+            Self::AlreadyAddedTag => "(already added)",
+            Self::Public => "Public",
+            Self::Unlisted => "Unlisted",
+            Self::InviteLink => "Invite link",
+            Self::NextTab => "Next tab",
+            Self::History => "History",
+
+            Self::ShuffleOn => "Shuffle: On",
+            Self::ShuffleOnClean => "Shuffle: On (clean)",
+            Self::ShuffleOnFiltered => "Shuffle: On (filtered)",
+            Self::ShuffleOff => "Shuffle: Off",
+            Self::RepeatOff => "Repeat: Off",
+            Self::PlayingOnAnotherDevice => "Playing on another device",
+            Self::Volume => "Volume: {volume}%",
+            Self::Quality => "Quality: {quality}",
+            Self::PlayingInQuality => "(playing {quality})",
+            Self::PlayingFrom => "Playing from: {playing_from}",
+            Self::UpNext => "Up next:",
+
+            Self::Online => "Online",
+            Self::ConnectingToTidal => "Connecting to Tidal...",
+            Self::Offline => "Offline",
+            Self::OfflineMode => "Offline mode",
+            Self::PendingChanges => "{count} pending",
+
+            Self::AddToPlaylist => "Add to playlist",
+            Self::AddToQueue => "Add to queue",
+            Self::PlayNow => "Play now",
+            Self::PlayFromHere => "Play from here",
+            Self::PlayNext => "Play next",
+            Self::GoToAlbum => "Go to album",
+            Self::GoToArtist => "Go to artist",
+            Self::AddToCollection => "Add to My Collection",
+            Self::RemoveFromCollection => "Remove from My Collection",
+            Self::NoPlaylists => "You don't have any playlists",
+            Self::LoadingPlaylists => "Loading playlists…",
+            Self::Blocklist => "Blocklist - never auto-played",
+            Self::NothingBlocked => "Nothing is blocked",
+            Self::QueueNotFinished => "The queue hasn't finished",
+            Self::ReplaceTheQueue => "Replace the queue",
+            Self::Cancel => "Cancel",
+            Self::PreviousQueues => "Previous queues",
+            Self::PreviousQueue => "{playing_from} - {count} tracks left",
+            Self::ShuffledTag => "(shuffled)",
+            Self::NoPreviousQueues => "No queues have been replaced yet",
+            Self::Diagnostics => "Diagnostics - select to copy the report",
+            Self::RunningChecks => "Running checks…",
+            Self::Caches => "Caches - select one to purge it",
+            Self::CacheCovers => "Cover art",
+            Self::CachePlayerState => "Saved queue",
+            Self::CachePendingWrites => "Changes waiting to be sent",
+            Self::CacheSizeOfMax => "{size} of {max}",
+            Self::Stats => "Stats",
+            Self::Artist => "Artist",
+            Self::Playlist => "Playlist",
+            Self::User => "User",
+            Self::Mix => "Mix",
+            Self::Pages => "Pages",
+            Self::NoPages => "No pages in the config",
+            Self::PageEmpty => "This page has no items",
+            Self::Home => "Home",
+            Self::HomeEmpty => "Nothing on the home page yet",
+            Self::NothingToPlay => "Nothing to play",
+            Self::PlayItemFailed => "Unable to play: {error}",
+            Self::MyMixes => "My Mixes",
+            Self::Help => "Keybinds",
+            Self::HelpGlobal => "Global",
+            Self::HelpPlayback => "Playback",
+            Self::HelpLayout => "Layout and navigation",
+            Self::HelpTables => "Tables",
+            Self::HelpTrackLists => "Lists of tracks",
+            Self::HelpBrowsing => "Browsing",
+            Self::HelpTabs => "Tabs",
+            Self::HelpTrackInfo => "Track info",
+            Self::HelpMenus => "Menus",
+            Self::RadioTitle => "{name} Radio",
+            Self::LoadingRadio => "Loading radio…",
+            Self::Slot => "Slot {slot}",
+            Self::SlotEmpty => "Slot {slot} is empty",
+            Self::NoSlots => "No slots in the config",
+            Self::ScriptCommands => "Commands",
+            Self::NoScriptCommands => "No scripts have added commands",
+            Self::OpenLinkPrompt => "Open a user or playlist, or queue a track (id or link)",
+            Self::ImportM3uPrompt => "Add the tracks of an M3U playlist to My Collection (path)",
+            Self::SearchPrompt => "Search Tidal for tracks, albums, artists, and playlists",
+            Self::Open => "Open",
+            Self::Import => "Import",
+            Self::Search => "Search",
+
+            Self::Play => "Play",
+            Self::Shuffle => "Shuffle",
+            Self::Info => "Info",
+            Self::Back => "Back",
+            Self::Close => "Close",
+            Self::Select => "Select",
+
+            Self::Released => "Released",
+            Self::Duration => "Duration",
+            Self::Explicit => "Explicit",
+            Self::Popularity => "Popularity",
+            Self::Isrc => "ISRC",
+            Self::Copyright => "Copyright",
+            Self::Qualities => "Qualities",
+            Self::InPlaylists => "In playlists",
+            Self::StreamHost => "Stream host",
+            Self::TrackId => "Track ID",
+            Self::Yes => "Yes",
+            Self::No => "No",
+            Self::InNoPlaylists => "None",
+            Self::NotPlaying => "Not playing",
+            Self::AndMore => "and {count} more",
+
+            Self::PlaybackTakenOver => "Playback taken over on another device, press {key} to resume here",
+            Self::ThePlayKey => "play",
+            Self::DiagnosticsReportCopied => "Diagnostics report copied",
+            Self::CopyReportFailed => "Couldn't copy the report: {error}",
+            Self::OfflineModeOn => "Offline mode on",
+            Self::OfflineModeOff => "Offline mode off",
+            Self::NotUserOrPlaylistLink => "Not a user or playlist link",
+            Self::NotTrackOrPlaylistLink => "Not a track or playlist link",
+            Self::AddedTrackToQueue => "Added 1 track to the queue",
+            Self::AddedTracksToQueue => "Added {count} tracks to the queue",
+            Self::PlayingNext => "{title} plays next",
+            Self::AddToQueueFailed => "Couldn't add to the queue: {error}",
+            Self::NothingToExport => "Nothing to export",
+            Self::Exported => "Exported {count} tracks to {path}",
+            Self::NoTrackLinks => "No Tidal track links found",
+            Self::ImportDeferred => "Offline: will add {count} tracks to My Collection once reconnected",
+            Self::Imported => "Added {count} tracks to My Collection. Restart to see them",
+            Self::ImportFailed => "Couldn't import: {error}",
+            Self::AddedToPlaylist => "Added to {playlist}",
+            Self::AddToPlaylistDeferred => "Offline: will add to {playlist} once reconnected",
+            Self::AddToPlaylistFailed => "Couldn't add to {playlist}: {error}",
+            Self::MoveTrackFailed => "Couldn't move the track in {playlist}: {error}",
+            Self::OnlyOwnPlaylistsReordered => "Only your own playlists can be reordered",
+            Self::ArtistNotLoaded => "Artist hasn't loaded yet",
+            Self::Blocked => "Won't auto-play {name}",
+            Self::Unblocked => "Unblocked {name}",
+            Self::QueueTooNarrow => "Terminal too narrow for the queue",
+            Self::NothingSearched => "Nothing searched for yet, press {key} to search",
+            Self::TheSearchKey => "the search key",
+            Self::NoMatches => "No matches for \"{query}\"",
+            Self::OnlyCollectionGrouped => "Only My Collection can be grouped by album",
+            Self::OnlyCollectionSorted => "Only My Collection and lists of albums can be sorted",
+            Self::SortedBy => "Sorted by {sort}",
+            Self::SortDateAdded => "Date added",
+            Self::SortReleaseDate => "Release date",
+            Self::SortTidalOrder => "Tidal's order",
+            Self::RestoreQueueFailed => "Couldn't restore the queue: {error}",
+            Self::ResumeFailed => "Unable to resume playback: {error}",
+            Self::AddedToCollection => "Added {title} to My Collection",
+            Self::RemovedFromCollection => "Removed {title} from My Collection",
+            Self::AddedToCollectionDeferred => "Offline: Added {title} to My Collection",
+            Self::RemovedFromCollectionDeferred => "Offline: Removed {title} from My Collection",
+            Self::UpdateCollectionFailed => "Couldn't update My Collection: {error}",
+            Self::SkippingExplicit => "Skipping explicit tracks while shuffling",
+            Self::PlayingExplicit => "Playing explicit tracks while shuffling",
+            Self::SkippingGenres => "Skipping genres while shuffling: {genres}",
+            Self::PlayingAllGenres => "Playing every genre while shuffling",
+            Self::NoExcludedGenres => "No genres to skip: list them in excluded_genres in the [player] section of the config",
+            Self::InviteLinkCopied => "Invite link copied",
+            Self::InviteLinkCreated => "Invite link created",
+            Self::InviteLinkFailed => "Couldn't create an invite link: {error}",
+            Self::SyncedChange => "Synced 1 pending change",
+            Self::SyncedChanges => "Synced {count} pending changes",
+            Self::SyncFailed => "Couldn't sync \"{change}\": {error}",
+            Self::PendingAddToPlaylist => "Add {tracks} to {playlist}",
+            Self::PendingAddToCollection => "Add {tracks} to My Collection",
+            Self::PendingRemoveFromCollection => "Remove {tracks} from My Collection",
+            Self::NoLongerYourPlaylist => "{playlist} is no longer one of your playlists",
+            Self::SkippedDrmProtected => "Skipped {title}, it is DRM protected and can't be played",
+            Self::AudioDeviceReconnected => "Audio device reconnected",
+            Self::AudioDeviceChanged => "Audio device changed, switched to the default output",
+            Self::AudioDeviceDisconnected => "Audio device disconnected, paused until another is available",
+            Self::PausedAfterSleep => "Paused after the computer woke from sleep",
+            Self::FullyBuffered => "Fully buffered",
+            Self::NothingPlayableOffline => "Nothing else in the queue can be played offline",
+            Self::Purged => "Purged {name}",
+            Self::PurgeFailed => "Unable to purge {name}: {error}",
+            Self::NeedsUnofficialApi => "Only available in builds with the unofficial feature",
+        }
+    }
+}
+
+/// The text of each message in one language.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    messages: HashMap<Message, String>,
+}
+
+impl Catalog {
+    /// Name of the folder within the config directory with the user's catalogs, one `<language>.toml` per language.
+    const FOLDER_NAME: &str = "messages";
+
+    /// Loads the catalog of `language` (e.g. "de"), or of the system's language if it is `None`.
+    ///
+    /// The catalog that comes with the app for the language (if any) is overridden by the user's catalog
+    /// in `config_folder_path`, and anything missing from both is left in English.
+    pub fn load(language: Option<&str>, config_folder_path: &str) -> Result<Self, String> {
+        let language = language.map_or_else(get_system_language, |language| language.to_lowercase());
+
+        let mut messages = HashMap::new();
+
+        if let Some((_, toml_str)) = BUILT_IN_CATALOGS.iter().find(|(code, _)| *code == language) {
+            let built_in: HashMap<Message, String> = toml::from_str(toml_str)
+                .map_err(|e| format!("Unable to parse the built-in {language} catalog: {e}"))?;
+            messages.extend(built_in);
+        }
+
+        let catalog_file = Path::new(config_folder_path).join(Self::FOLDER_NAME).join(format!("{language}.toml"));
+        if catalog_file.exists() {
+            let toml_str = fs::read_to_string(&catalog_file)
+                .map_err(|e| format!("Unable to read {}: {e}", catalog_file.display()))?;
+            let user: HashMap<Message, String> = toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", catalog_file.display()))?;
+            messages.extend(user);
+        }
+
+        Ok(Self { messages })
+    }
+
+    /// Returns the text of `message`.
+    pub fn get(&self, message: Message) -> &str {
+        self.messages.get(&message).map_or(message.get_english(), |text| text.as_str())
+    }
+}
+
+/// Makes `catalog` the one that `tr` and `tr_with` use. Only the first catalog set is used.
+pub fn init(catalog: Catalog) {
+    let _ = CATALOG.set(catalog);
+}
+
+/// Returns the text of `message` in the chosen language, or in English if no catalog was loaded.
+pub fn tr(message: Message) -> &'static str {
+    match CATALOG.get() {
+        Some(catalog) => catalog.get(message),
+        None => message.get_english(),
+    }
+}
+
+/// Returns the text of `message` in the chosen language, with each `{name}` filled in by its value in `args`.
+pub fn tr_with(message: Message, args: &[(&str, &str)]) -> String {
+    fill_in(tr(message), args)
+}
+
+/// Returns `text` with each `{name}` replaced by its value in `args`.
+fn fill_in(text: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(text.to_string(), |text, (name, value)| text.replace(&format!("{{{name}}}"), value))
+}
+
+/// Returns the language code of the system's language (e.g. "de" for `LANG=de_DE.UTF-8`), or "en" if it isn't set.
+fn get_system_language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| value.split(['_', '.', '@']).next().unwrap_or_default().to_lowercase())
+        .filter(|language| !language.is_empty() && language != "c" && language != "posix")
+        .unwrap_or(String::from("en"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_built_in_catalogs() {
+        for (language, toml_str) in BUILT_IN_CATALOGS {
+            let catalog = toml::from_str::<HashMap<Message, String>>(toml_str);
+            assert!(catalog.is_ok(), "{language}: {catalog:?}");
+        }
+    }
+
+    #[test]
+    fn fills_in_args() {
+        assert_eq!(fill_in("Added {count} tracks to {playlist}", &[("count", "3"), ("playlist", "OK Computer")]), "Added 3 tracks to OK Computer");
+        assert_eq!(fill_in("No matches", &[("query", "Airbag")]), "No matches");
+    }
+
+    #[test]
+    fn falls_back_to_english() {
+        let catalog = Catalog {
+            messages: HashMap::from([(Message::NowPlaying, String::from("Wiedergabe"))]),
+        };
+
+        assert_eq!(catalog.get(Message::NowPlaying), "Wiedergabe");
+        assert_eq!(catalog.get(Message::Queue), "Queue");
+    }
+}
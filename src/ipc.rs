@@ -0,0 +1,337 @@
+use std::{
+    env,
+    error::Error,
+    path::PathBuf,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use rtidalapi::Session;
+use tokio::sync::mpsc;
+
+use crate::{
+    cli::{
+        PlayArgs,
+        StatusFormat,
+    },
+    headless,
+    player::Player,
+    status_server::PlayerStatus,
+    waybar::WaybarStatus,
+    AppEvent,
+};
+
+/// A request sent to the running instance over its IPC socket, one per connection, as a single line.
+///
+/// The reply is a single line too: the player's status as JSON for `Status`, and "ok" or "error: <reason>" otherwise.
+/// A `tidal-tui play` handed off to the running instance is sent as "play" followed by its options, and replied to the same way.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IpcCommand {
+    Status,
+    PlayPause,
+    Next,
+    Previous,
+    VolumeUp,
+    VolumeDown,
+    /// Brings the running instance's terminal to the front.
+    Raise,
+}
+
+impl IpcCommand {
+    /// All commands, in the order they are listed in the usage text.
+    pub const ALL: [Self; 7] = [
+        Self::Status,
+        Self::PlayPause,
+        Self::Next,
+        Self::Previous,
+        Self::VolumeUp,
+        Self::VolumeDown,
+        Self::Raise,
+    ];
+
+    /// How much `VolumeUp` and `VolumeDown` change the volume by, the same as the volume keys.
+    const VOLUME_STEP: u32 = 5;
+
+    /// Returns the command named `name` (e.g. "play-pause"), or `None` if there is no such command.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.get_name() == name)
+    }
+
+    /// Returns the name this command is sent as.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::PlayPause => "play-pause",
+            Self::Next => "next",
+            Self::Previous => "previous",
+            Self::VolumeUp => "volume-up",
+            Self::VolumeDown => "volume-down",
+            Self::Raise => "raise",
+        }
+    }
+
+    /// Runs this command on `player` and returns the reply.
+    pub(crate) fn run(&self, player: &Mutex<Player>) -> Result<String, Box<dyn Error>> {
+        let mut unlocked_player = player.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+
+        match self {
+            Self::Status => return Ok(serde_json::to_string(&PlayerStatus::from_player(&unlocked_player))?),
+            Self::PlayPause => {
+                if unlocked_player.is_playing() {
+                    unlocked_player.pause()?;
+                } else {
+                    unlocked_player.play()?;
+                }
+            },
+            Self::Next => unlocked_player.next()?,
+            Self::Previous => unlocked_player.prev()?,
+            Self::VolumeUp => {
+                let volume = unlocked_player.get_volume();
+                unlocked_player.set_volume(volume.saturating_add(Self::VOLUME_STEP));
+            },
+            Self::VolumeDown => {
+                let volume = unlocked_player.get_volume();
+                unlocked_player.set_volume(volume.saturating_sub(Self::VOLUME_STEP));
+            },
+            // The terminal is raised by the app, which the server asks to once this returns.
+            Self::Raise => {},
+        }
+
+        Ok(String::from("ok"))
+    }
+}
+
+/// Returns the path of the IPC socket: in `$XDG_RUNTIME_DIR` if it is set, and in the temporary directory otherwise.
+pub fn get_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("tidal-tui.sock"),
+        // The temporary directory is shared between users, so keep their sockets apart.
+        None => env::temp_dir().join(format!("tidal-tui-{}.sock", env::var("USER").unwrap_or_default())),
+    }
+}
+
+/// Returns true if another instance is listening on the IPC socket.
+#[cfg(unix)]
+pub fn is_running() -> bool {
+    std::os::unix::net::UnixStream::connect(get_socket_path()).is_ok()
+}
+
+/// IPC is only available on Unix, so other instances can't be found elsewhere.
+#[cfg(not(unix))]
+pub fn is_running() -> bool {
+    false
+}
+
+/// Listens for commands on the IPC socket from a background thread, running them on `player`.
+/// A rerender is sent through `tx` after each command that changes playback,
+/// and what a handed off `tidal-tui play` should play is fetched with `session` and sent through it.
+///
+/// Fails if another instance is already listening.
+#[cfg(unix)]
+pub fn start(player: Arc<Mutex<Player>>, session: Arc<Session>, tx: mpsc::Sender<AppEvent>) -> Result<(), Box<dyn Error>> {
+    use std::{
+        os::unix::net::UnixListener,
+        thread,
+    };
+
+    let path = get_socket_path();
+
+    if is_running() {
+        return Err(format!("Another instance is already listening on {}", path.display()).into());
+    }
+    // Nothing is listening, so the socket was left behind by an instance that didn't exit cleanly.
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path)
+        .map_err(|e| format!("Unable to listen on {}: {e}", path.display()))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A client that hangs up early shouldn't stop the server.
+            let _ = handle_connection(stream, &player, &session, &tx);
+        }
+    });
+
+    Ok(())
+}
+
+/// IPC is only available on Unix, so there is nothing to listen on elsewhere.
+#[cfg(not(unix))]
+pub fn start(_player: Arc<Mutex<Player>>, _session: Arc<Session>, _tx: mpsc::Sender<AppEvent>) -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+/// Reads a command from `stream`, runs it, and writes the reply.
+#[cfg(unix)]
+fn handle_connection(
+    mut stream: std::os::unix::net::UnixStream,
+    player: &Mutex<Player>,
+    session: &Arc<Session>,
+    tx: &mpsc::Sender<AppEvent>,
+) -> Result<(), Box<dyn Error>> {
+    use std::{
+        io::{
+            BufRead,
+            BufReader,
+            Write,
+        },
+        time::Duration,
+    };
+
+    // Commands are handled one at a time, so don't let a client that never sends anything hold up the others.
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let reply = match (line.trim().strip_prefix("play "), IpcCommand::parse(line.trim())) {
+        (Some(options), _) => match play_handoff(options, player, session, tx) {
+            Ok(()) => String::from("ok"),
+            Err(e) => format!("error: {e}"),
+        },
+        (None, Some(command)) => {
+            let reply = command.run(player).unwrap_or_else(|e| format!("error: {e}"));
+            match command {
+                IpcCommand::Status => {},
+                IpcCommand::Raise => {
+                    let _ = tx.blocking_send(AppEvent::Raise);
+                },
+                _ => {
+                    let _ = tx.try_send(AppEvent::ReRender);
+                },
+            }
+            reply
+        },
+        (None, None) => format!("error: unknown command: {}", line.trim()),
+    };
+
+    stream.write_all(format!("{reply}\n").as_bytes())?;
+
+    Ok(())
+}
+
+/// Fetches the tracks that the `tidal-tui play` options `options` refer to with `session`,
+/// and hands them to the app through `tx` to replace the queue.
+#[cfg(unix)]
+fn play_handoff(options: &str, player: &Mutex<Player>, session: &Arc<Session>, tx: &mpsc::Sender<AppEvent>) -> Result<(), Box<dyn Error>> {
+    let args = PlayArgs::parse(options.split_whitespace().map(String::from))?;
+    let (tracks, playing_from) = headless::load_tracks(session, &args.source)?;
+
+    if let Some(volume) = args.volume {
+        player.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .set_volume(volume);
+    }
+
+    tx.blocking_send(AppEvent::PlayHandoff(tracks, playing_from, args.shuffle))
+        .map_err(|_| "The app is closing")?;
+
+    Ok(())
+}
+
+/// Sends `command` to the running instance and returns its reply.
+///
+/// Fails if no instance is running, or if the command fails there.
+#[cfg(unix)]
+pub fn send(command: IpcCommand) -> Result<String, Box<dyn Error>> {
+    request(command.get_name(), std::time::Duration::from_secs(5))
+}
+
+/// Hands `args` off to the running instance, which plays them in place of its queue.
+///
+/// Fails if no instance is running, or if what `args` refer to can't be played there.
+#[cfg(unix)]
+pub fn hand_off(args: &PlayArgs) -> Result<(), Box<dyn Error>> {
+    // The running instance fetches the tracks before replying, which takes a while for a big collection.
+    let timeout = std::time::Duration::from_secs(120);
+
+    request(&format!("play {}", args.to_args().join(" ")), timeout).map(|_| ())
+}
+
+/// IPC is only available on Unix.
+#[cfg(not(unix))]
+pub fn hand_off(_args: &PlayArgs) -> Result<(), Box<dyn Error>> {
+    Err("Controlling a running instance is only supported on Unix".into())
+}
+
+/// Sends `line` to the running instance, and returns its reply once it comes within `timeout`.
+///
+/// Fails if no instance is running, or if the reply is an error.
+#[cfg(unix)]
+fn request(line: &str, timeout: std::time::Duration) -> Result<String, Box<dyn Error>> {
+    use std::{
+        io::{
+            BufRead,
+            BufReader,
+            Write,
+        },
+        os::unix::net::UnixStream,
+    };
+
+    let path = get_socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|e| format!("tidal-tui doesn't seem to be running ({}: {e})", path.display()))?;
+    stream.set_read_timeout(Some(timeout))?;
+
+    stream.write_all(format!("{line}\n").as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    let reply = reply.trim_end().to_string();
+
+    match reply.strip_prefix("error: ") {
+        Some(error) => Err(error.into()),
+        None => Ok(reply),
+    }
+}
+
+/// IPC is only available on Unix.
+#[cfg(not(unix))]
+pub fn send(_command: IpcCommand) -> Result<String, Box<dyn Error>> {
+    Err("Controlling a running instance is only supported on Unix".into())
+}
+
+/// Prints the running instance's status in `format`.
+///
+/// For waybar, a status that hides the module is printed if no instance is running, instead of failing,
+/// so that waybar doesn't show an error while the app is closed.
+pub fn print_status(format: &StatusFormat) -> Result<(), Box<dyn Error>> {
+    let reply = send(IpcCommand::Status);
+
+    match format {
+        StatusFormat::Json => println!("{}", reply?),
+        StatusFormat::Waybar => {
+            let waybar_status = match reply {
+                Ok(reply) => WaybarStatus::from_status(&serde_json::from_str::<PlayerStatus>(&reply)?),
+                Err(_) => WaybarStatus::not_running(),
+            };
+            println!("{}", serde_json::to_string(&waybar_status)?);
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_command_names() {
+        for command in IpcCommand::ALL {
+            assert_eq!(IpcCommand::parse(command.get_name()), Some(command));
+        }
+        assert_eq!(IpcCommand::parse("play-pause"), Some(IpcCommand::PlayPause));
+        assert_eq!(IpcCommand::parse("stop"), None);
+    }
+
+    #[test]
+    fn hands_off_play_options() {
+        let args = PlayArgs::parse(["--album", "123", "--shuffle", "--volume", "40"].map(String::from).into_iter()).unwrap();
+        assert_eq!(args.to_args().join(" "), "--album 123 --shuffle --volume 40");
+        assert_eq!(PlayArgs::parse(args.to_args().into_iter()), Ok(args));
+    }
+}
@@ -0,0 +1,1066 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::i18n::{
+    tr,
+    Message,
+};
+
+/// Something the user can do with a keybind.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+
+    // Focus
+    FocusNext,
+    FocusPrev,
+    FocusLeft,
+    FocusDown,
+    FocusUp,
+    FocusRight,
+    ToggleQueue,
+    ToggleLyrics,
+    OpenLink,
+    OpenMyPlaylists,
+    OpenBlocklist,
+    OpenRediscover,
+    ImportM3u,
+    OpenPreviousQueues,
+    ToggleOffline,
+    OpenDiagnostics,
+    OpenSessionStats,
+    OpenCaches,
+    OpenScriptCommands,
+    OpenPages,
+    OpenHelp,
+    OpenMixes,
+    SwitchToSlot,
+    SearchTidal,
+    SwitchCollectionView,
+
+    // Routes
+    GoToHome,
+    GoToTracks,
+    GoToAlbums,
+    GoToArtists,
+    GoToPlaylists,
+    GoToSearch,
+    GoToQueue,
+    GoToSettings,
+    NextRoute,
+    PrevRoute,
+
+    // Tables
+    PrevRow,
+    NextRow,
+    GoToTop,
+    GoToBottom,
+    OpenTrackInfo,
+    OpenAlbum,
+    StartTrackRadio,
+    StartArtistRadio,
+    RetryRow,
+    AddToPlaylist,
+    OpenTrackMenu,
+    BlockTrack,
+    BlockArtist,
+    Search,
+    NextMatch,
+    PrevMatch,
+
+    // Collection
+    GoToCurrentlyPlaying,
+    PlayAll,
+    ShuffleAll,
+    Replay,
+    ExportM3u,
+    ToggleAlbumGrouping,
+    ToggleAlbumCollapsed,
+    PlayAlbum,
+    CycleSort,
+    ReverseSort,
+
+    // Browsing users and playlists
+    OpenSelected,
+    GoBack,
+    CreateInviteLink,
+    MoveTrackUp,
+    MoveTrackDown,
+
+    // Browsing albums
+    QueueAlbum,
+
+    // Pages
+    PlayItem,
+
+    // Home
+    PrevHomeItem,
+    NextHomeItem,
+
+    // Queue
+    ToggleQueueHistory,
+
+    // Views with tabs
+    NextBrowseTab,
+    PrevBrowseTab,
+
+    // Track info popup
+    CloseTrackInfo,
+
+    // Menus
+    MenuUp,
+    MenuDown,
+    MenuSelect,
+    MenuClose,
+
+    // Player
+    VolumeDown,
+    VolumeUp,
+    TogglePlayPause,
+    ResumeHere,
+    PreviousTrack,
+    NextTrack,
+    SeekBackward,
+    SeekForward,
+    CycleAudioQuality,
+    ToggleExplicitFilter,
+    ToggleGenreFilter,
+    ToggleFavorite,
+}
+
+/// Where an action can be used. Only the actions of the active contexts respond to keys.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Context {
+    /// Always active, even while a popup is open.
+    App,
+    /// Moving focus between panes, changing the layout, and opening links, active whenever no popup is open.
+    Layout,
+    /// Any table of tracks, active while one is focused.
+    Table,
+    /// The tracks in the main pane (My Collection - Tracks, or a playlist being browsed), active while it is focused.
+    Collection,
+    /// A user or playlist being browsed in the main pane, active while it is focused.
+    Browse,
+    /// A playlist being browsed in the main pane, active while it is focused.
+    Playlist,
+    /// An album being browsed in the main pane, active while it is focused.
+    Album,
+    /// A page of pinned items from the config, or the home page, active while it is focused.
+    Page,
+    /// The home page, whose sections list their items side by side, active while it is focused.
+    Home,
+    /// A view with a tab for each kind of item in the main pane (the results of a search, or an artist's page),
+    /// active while it is focused.
+    Tabbed,
+    /// The queue, active while it is focused.
+    Queue,
+    /// The Now Playing bar, active while it is focused.
+    NowPlaying,
+    /// The track info popup.
+    TrackInfo,
+    /// A popup menu, such as the list of playlists to add a track to.
+    Menu,
+    /// Playback controls, active whenever no popup is open.
+    Player,
+}
+
+impl Context {
+    /// Every context, in the order they are listed in the help.
+    pub const ALL: [Context; 15] = [
+        Self::App,
+        Self::Player,
+        Self::Layout,
+        Self::Table,
+        Self::Collection,
+        Self::Browse,
+        Self::Playlist,
+        Self::Album,
+        Self::Page,
+        Self::Home,
+        Self::Tabbed,
+        Self::Queue,
+        Self::NowPlaying,
+        Self::TrackInfo,
+        Self::Menu,
+    ];
+
+    /// Returns the heading this context's keybinds are listed under in the help.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::App => tr(Message::HelpGlobal),
+            Self::Layout => tr(Message::HelpLayout),
+            Self::Table => tr(Message::HelpTables),
+            Self::Collection => tr(Message::HelpTrackLists),
+            Self::Browse => tr(Message::HelpBrowsing),
+            Self::Playlist => tr(Message::Playlist),
+            Self::Album => tr(Message::Album),
+            Self::Page => tr(Message::Pages),
+            Self::Home => tr(Message::Home),
+            Self::Tabbed => tr(Message::HelpTabs),
+            Self::Queue => tr(Message::Queue),
+            Self::NowPlaying => tr(Message::NowPlaying),
+            Self::TrackInfo => tr(Message::HelpTrackInfo),
+            Self::Menu => tr(Message::HelpMenus),
+            Self::Player => tr(Message::HelpPlayback),
+        }
+    }
+}
+
+impl Action {
+    /// Every action, in the order they are listed in footers.
+    pub const ALL: [Action; 91] = [
+        Self::Quit,
+        Self::FocusNext,
+        Self::FocusPrev,
+        Self::FocusLeft,
+        Self::FocusDown,
+        Self::FocusUp,
+        Self::FocusRight,
+        Self::ToggleQueue,
+        Self::ToggleLyrics,
+        Self::OpenLink,
+        Self::OpenMyPlaylists,
+        Self::OpenBlocklist,
+        Self::OpenRediscover,
+        Self::ImportM3u,
+        Self::OpenPreviousQueues,
+        Self::ToggleOffline,
+        Self::OpenDiagnostics,
+        Self::OpenSessionStats,
+        Self::OpenCaches,
+        Self::OpenScriptCommands,
+        Self::OpenPages,
+        Self::OpenHelp,
+        Self::OpenMixes,
+        Self::SwitchToSlot,
+        Self::SearchTidal,
+        Self::SwitchCollectionView,
+        Self::GoToHome,
+        Self::GoToTracks,
+        Self::GoToAlbums,
+        Self::GoToArtists,
+        Self::GoToPlaylists,
+        Self::GoToSearch,
+        Self::GoToQueue,
+        Self::GoToSettings,
+        Self::NextRoute,
+        Self::PrevRoute,
+        Self::OpenSelected,
+        Self::GoBack,
+        Self::CreateInviteLink,
+        Self::MoveTrackUp,
+        Self::MoveTrackDown,
+        Self::QueueAlbum,
+        Self::PlayItem,
+        Self::PrevHomeItem,
+        Self::NextHomeItem,
+        Self::NextBrowseTab,
+        Self::PrevBrowseTab,
+        Self::ToggleQueueHistory,
+        Self::PlayAll,
+        Self::ShuffleAll,
+        Self::Replay,
+        Self::ExportM3u,
+        Self::ToggleAlbumGrouping,
+        Self::ToggleAlbumCollapsed,
+        Self::PlayAlbum,
+        Self::CycleSort,
+        Self::ReverseSort,
+        Self::OpenTrackInfo,
+        Self::OpenAlbum,
+        Self::StartTrackRadio,
+        Self::StartArtistRadio,
+        Self::AddToPlaylist,
+        Self::OpenTrackMenu,
+        Self::BlockTrack,
+        Self::BlockArtist,
+        Self::RetryRow,
+        Self::PrevRow,
+        Self::NextRow,
+        Self::GoToTop,
+        Self::GoToBottom,
+        Self::Search,
+        Self::NextMatch,
+        Self::PrevMatch,
+        Self::GoToCurrentlyPlaying,
+        Self::CloseTrackInfo,
+        Self::MenuSelect,
+        Self::MenuClose,
+        Self::MenuUp,
+        Self::MenuDown,
+        Self::TogglePlayPause,
+        Self::ResumeHere,
+        Self::PreviousTrack,
+        Self::NextTrack,
+        Self::SeekBackward,
+        Self::SeekForward,
+        Self::VolumeDown,
+        Self::VolumeUp,
+        Self::CycleAudioQuality,
+        Self::ToggleExplicitFilter,
+        Self::ToggleGenreFilter,
+        Self::ToggleFavorite,
+    ];
+
+    /// Returns the context this action can be used in.
+    pub fn context(&self) -> Context {
+        match self {
+            Self::Quit => Context::App,
+            Self::FocusNext
+            | Self::FocusPrev
+            | Self::FocusLeft
+            | Self::FocusDown
+            | Self::FocusUp
+            | Self::FocusRight
+            | Self::ToggleQueue
+            | Self::ToggleLyrics
+            | Self::OpenLink
+            | Self::OpenMyPlaylists
+            | Self::OpenBlocklist
+            | Self::OpenRediscover
+            | Self::ImportM3u
+            | Self::OpenPreviousQueues
+            | Self::ToggleOffline
+            | Self::OpenDiagnostics
+            | Self::OpenSessionStats
+            | Self::OpenCaches
+            | Self::OpenScriptCommands
+            | Self::OpenPages
+            | Self::OpenHelp
+            | Self::OpenMixes
+            | Self::SwitchToSlot
+            | Self::SearchTidal
+            | Self::SwitchCollectionView
+            | Self::GoToHome
+            | Self::GoToTracks
+            | Self::GoToAlbums
+            | Self::GoToArtists
+            | Self::GoToPlaylists
+            | Self::GoToSearch
+            | Self::GoToQueue
+            | Self::GoToSettings
+            | Self::NextRoute
+            | Self::PrevRoute => Context::Layout,
+            Self::PrevRow
+            | Self::NextRow
+            | Self::GoToTop
+            | Self::GoToBottom
+            | Self::OpenTrackInfo
+            | Self::OpenAlbum
+            | Self::StartTrackRadio
+            | Self::StartArtistRadio
+            | Self::RetryRow
+            | Self::AddToPlaylist
+            | Self::OpenTrackMenu
+            | Self::BlockTrack
+            | Self::BlockArtist
+            | Self::Search
+            | Self::NextMatch
+            | Self::PrevMatch => Context::Table,
+            Self::GoToCurrentlyPlaying
+            | Self::PlayAll
+            | Self::ShuffleAll
+            | Self::Replay
+            | Self::ExportM3u
+            | Self::ToggleAlbumGrouping
+            | Self::ToggleAlbumCollapsed
+            | Self::PlayAlbum
+            | Self::CycleSort
+            | Self::ReverseSort => Context::Collection,
+            Self::OpenSelected
+            | Self::GoBack => Context::Browse,
+            Self::CreateInviteLink
+            | Self::MoveTrackUp
+            | Self::MoveTrackDown => Context::Playlist,
+            Self::QueueAlbum => Context::Album,
+            Self::PlayItem => Context::Page,
+            Self::PrevHomeItem
+            | Self::NextHomeItem => Context::Home,
+            Self::NextBrowseTab
+            | Self::PrevBrowseTab => Context::Tabbed,
+            Self::ToggleQueueHistory => Context::Queue,
+            Self::CloseTrackInfo => Context::TrackInfo,
+            Self::MenuUp
+            | Self::MenuDown
+            | Self::MenuSelect
+            | Self::MenuClose => Context::Menu,
+            Self::VolumeDown
+            | Self::VolumeUp
+            | Self::TogglePlayPause
+            | Self::ResumeHere
+            | Self::PreviousTrack
+            | Self::NextTrack
+            | Self::SeekBackward
+            | Self::SeekForward
+            | Self::CycleAudioQuality
+            | Self::ToggleExplicitFilter
+            | Self::ToggleGenreFilter
+            | Self::ToggleFavorite => Context::Player,
+        }
+    }
+
+    /// Returns true if this action can't work without reaching Tidal, so that it is hidden while offline.
+    pub fn needs_network(&self) -> bool {
+        matches!(
+            self,
+            Self::OpenLink
+                | Self::OpenMyPlaylists
+                | Self::SearchTidal
+                | Self::SwitchCollectionView
+                | Self::GoToHome
+                | Self::GoToAlbums
+                | Self::GoToArtists
+                | Self::GoToPlaylists
+                | Self::GoToSearch
+                | Self::OpenAlbum
+                | Self::OpenPages
+                | Self::OpenMixes
+                | Self::StartTrackRadio
+                | Self::StartArtistRadio
+                | Self::PlayItem
+                | Self::RetryRow
+                | Self::CreateInviteLink
+                | Self::MoveTrackUp
+                | Self::MoveTrackDown
+                | Self::ResumeHere,
+        )
+    }
+
+    /// Returns the name of this action as it is written in the `[keymap]` section of the config (e.g. "next_track").
+    pub fn get_name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|name| name.as_str().map(String::from))
+            .unwrap_or_default()
+    }
+
+    /// Returns the short label shown for this action in footers, or `None` if it isn't shown in footers.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::PlayAll => Some(tr(Message::Play)),
+            Self::ShuffleAll => Some(tr(Message::Shuffle)),
+            Self::OpenTrackInfo => Some(tr(Message::Info)),
+            Self::AddToPlaylist => Some(tr(Message::AddToPlaylist)),
+            Self::OpenSelected => Some(tr(Message::Open)),
+            Self::GoBack => Some(tr(Message::Back)),
+            Self::CreateInviteLink => Some(tr(Message::InviteLink)),
+            Self::QueueAlbum => Some(tr(Message::AddToQueue)),
+            Self::PlayItem => Some(tr(Message::Play)),
+            Self::NextBrowseTab => Some(tr(Message::NextTab)),
+            Self::ToggleQueueHistory => Some(tr(Message::History)),
+            Self::CloseTrackInfo => Some(tr(Message::Close)),
+            Self::MenuSelect => Some(tr(Message::Select)),
+            Self::MenuClose => Some(tr(Message::Close)),
+            _ => None,
+        }
+    }
+
+    /// Returns the keys bound to this action by default.
+    fn default_keys(&self) -> Vec<KeySequence> {
+        let keys: &[&str] = match self {
+            Self::Quit => &["Q"],
+            Self::FocusNext => &["tab"],
+            Self::FocusPrev => &["backtab"],
+            Self::FocusLeft => &["ctrl+h"],
+            Self::FocusDown => &["ctrl+j"],
+            Self::FocusUp => &["ctrl+k"],
+            Self::FocusRight => &["ctrl+l"],
+            Self::ToggleQueue => &["q"],
+            Self::ToggleLyrics => &["L"],
+            Self::OpenLink => &["o"],
+            Self::OpenMyPlaylists => &["m"],
+            Self::OpenBlocklist => &["B"],
+            Self::OpenRediscover => &["d"],
+            Self::ImportM3u => &["I"],
+            Self::OpenPreviousQueues => &["H"],
+            Self::ToggleOffline => &["O"],
+            Self::OpenDiagnostics => &["D"],
+            Self::OpenSessionStats => &["T"],
+            Self::OpenCaches => &["g c"],
+            Self::OpenScriptCommands => &[":"],
+            Self::OpenPages => &["g p"],
+            Self::OpenHelp => &["?"],
+            Self::OpenMixes => &["g m"],
+            Self::SwitchToSlot => &["'"],
+            Self::SearchTidal => &["s"],
+            Self::SwitchCollectionView => &["A"],
+            Self::GoToHome => &["alt+1"],
+            Self::GoToTracks => &["alt+2"],
+            Self::GoToAlbums => &["alt+3"],
+            Self::GoToArtists => &["alt+4"],
+            Self::GoToPlaylists => &["alt+5"],
+            Self::GoToSearch => &["alt+6"],
+            Self::GoToQueue => &["alt+7"],
+            Self::GoToSettings => &["alt+8"],
+            Self::NextRoute => &["g t"],
+            Self::PrevRoute => &["g T"],
+            Self::PrevRow => &["up", "k"],
+            Self::NextRow => &["down", "j"],
+            Self::GoToTop => &["t", "g g"],
+            Self::GoToBottom => &["b"],
+            Self::GoToCurrentlyPlaying => &["c", "ctrl+space"],
+            Self::PlayAll => &["P"],
+            Self::ShuffleAll => &["S"],
+            Self::Replay => &["p"],
+            Self::ExportM3u => &["E"],
+            Self::ToggleAlbumGrouping => &["g a"],
+            Self::ToggleAlbumCollapsed => &["z"],
+            Self::PlayAlbum => &["enter"],
+            Self::CycleSort => &["g s"],
+            Self::ReverseSort => &["g S"],
+            Self::OpenTrackInfo => &["i"],
+            Self::OpenAlbum => &["v"],
+            Self::StartTrackRadio => &["g r"],
+            Self::StartArtistRadio => &["g R"],
+            Self::RetryRow => &["r"],
+            Self::AddToPlaylist => &["a"],
+            Self::OpenTrackMenu => &["enter", "M"],
+            Self::BlockTrack => &["x"],
+            Self::BlockArtist => &["X"],
+            Self::Search => &["/"],
+            Self::NextMatch => &["n"],
+            Self::PrevMatch => &["N"],
+            Self::OpenSelected => &["enter"],
+            Self::GoBack => &["backspace", "esc"],
+            Self::CreateInviteLink => &["l"],
+            Self::MoveTrackUp => &["shift+up", "K"],
+            Self::MoveTrackDown => &["shift+down", "J"],
+            Self::QueueAlbum => &["+"],
+            Self::PlayItem => &["P"],
+            Self::PrevHomeItem => &["h"],
+            Self::NextHomeItem => &["l"],
+            Self::NextBrowseTab => &["l"],
+            Self::PrevBrowseTab => &["h"],
+            Self::ToggleQueueHistory => &["h"],
+            Self::CloseTrackInfo => &["esc", "i"],
+            Self::MenuUp => &["up"],
+            Self::MenuDown => &["down"],
+            Self::MenuSelect => &["enter"],
+            Self::MenuClose => &["esc"],
+            Self::VolumeDown => &["-"],
+            Self::VolumeUp => &["="],
+            Self::TogglePlayPause => &["space"],
+            Self::ResumeHere => &["R"],
+            Self::PreviousTrack => &["[", "shift+left"],
+            Self::NextTrack => &["]", "shift+right"],
+            Self::SeekBackward => &["left"],
+            Self::SeekForward => &["right"],
+            Self::CycleAudioQuality => &[","],
+            Self::ToggleExplicitFilter => &["e"],
+            Self::ToggleGenreFilter => &["g e"],
+            Self::ToggleFavorite => &["F"],
+        };
+
+        keys.iter()
+            .map(|key| key.parse().expect("default keys should be valid"))
+            .collect()
+    }
+}
+
+/// A key, along with the modifiers that must be held, that can be bound to an action.
+///
+/// In the config file, keys are written like `"P"`, `"space"`, `"ctrl+h"`, or `"alt+left"`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Returns a new `Key`.
+    ///
+    /// Shift is ignored for characters and back tab, since it is already part of the key (e.g. `P` vs `p`).
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(key_event: KeyEvent) -> Self {
+        Self::new(key_event.code, key_event.modifiers)
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+
+        // A "+" on its own is the plus key, not a modifier separator.
+        while let Some((modifier, key)) = rest.split_once('+').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier in key \"{s}\": {modifier}")),
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    // Keys past F12 are what media keys without a standard code (e.g. heart keys) are usually remapped to.
+                    Some(n) if (1..=24).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!("Unknown key: \"{s}\"")),
+                },
+            },
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+/// One or more keys, pressed one after another, that are bound to an action.
+///
+/// In the config file, the keys of a sequence (or chord) are separated by spaces, like `"g g"` or `"g a"`.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(try_from = "String")]
+pub struct KeySequence(Vec<Key>);
+
+impl KeySequence {
+    /// Returns true if `keys` are the start of this sequence, but not the whole of it.
+    fn is_continued_by(&self, keys: &[Key]) -> bool {
+        self.0.len() > keys.len() && self.0.starts_with(keys)
+    }
+}
+
+impl TryFrom<String> for KeySequence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys = s.split_whitespace()
+            .map(Key::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if keys.is_empty() {
+            return Err(String::from("Keys can't be empty"));
+        }
+
+        Ok(Self(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, key) in self.0.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{key}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "C-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "A-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "S-")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "S-Tab"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Insert => write!(f, "Ins"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// The keys bound to every action, made from the defaults and the user's `[keymap]` config.
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeySequence>>,
+    /// True while offline, which hides the actions that need to reach Tidal.
+    is_offline: bool,
+    /// The keys pressed so far of a sequence that hasn't been finished yet.
+    pending: Vec<Key>,
+    /// When the last of the pending keys was pressed.
+    pending_at: Option<Instant>,
+    /// The count typed before an action (e.g. the 25 of `25j`), which is how many times to do it.
+    count: Option<usize>,
+}
+
+impl Keymap {
+    /// How long to wait for the next key of a sequence before giving up on it.
+    pub const SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+    /// Returns a new `Keymap` from the default bindings, replacing the keys of any action in `overrides`.
+    pub fn new(overrides: &HashMap<Action, Vec<KeySequence>>) -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys = overrides.get(action)
+                    .cloned()
+                    .unwrap_or_else(|| action.default_keys());
+                (*action, keys)
+            })
+            .collect();
+
+        Self {
+            bindings,
+            is_offline: false,
+            pending: vec![],
+            pending_at: None,
+            count: None,
+        }
+    }
+
+    /// Sets whether Tidal can't be reached, which hides the actions that need it from `get_action` and `get_hints`.
+    pub fn set_offline(&mut self, is_offline: bool) {
+        self.is_offline = is_offline;
+    }
+
+    /// Returns true if `action` can currently be used.
+    fn is_available(&self, action: Action) -> bool {
+        !(self.is_offline && action.needs_network())
+    }
+
+    /// Returns the keys bound to `action`.
+    pub fn get_keys(&self, action: Action) -> &[KeySequence] {
+        self.bindings.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Returns the actions that can currently be used within `contexts`, in priority order.
+    fn get_available_actions(&self, contexts: &[Context]) -> impl Iterator<Item = Action> {
+        contexts.iter()
+            .flat_map(|context| Action::ALL.iter().filter(move |action| action.context() == *context))
+            .copied()
+            .filter(|action| self.is_available(*action))
+    }
+
+    /// Returns the action bound to exactly `keys` within `contexts`, if any.
+    ///
+    /// Contexts are searched in order, so earlier contexts take priority when a key is bound more than once.
+    fn find_action(&self, keys: &[Key], contexts: &[Context]) -> Option<Action> {
+        self.get_available_actions(contexts)
+            .find(|action| self.get_keys(*action).iter().any(|sequence| sequence.0 == keys))
+    }
+
+    /// Returns true if a sequence bound within `contexts` starts with `keys`, and has more keys after them.
+    fn is_continued(&self, keys: &[Key], contexts: &[Context]) -> bool {
+        self.get_available_actions(contexts)
+            .any(|action| self.get_keys(action).iter().any(|sequence| sequence.is_continued_by(keys)))
+    }
+
+    /// Returns the action that pressing `key_event` finishes within `contexts`, if any,
+    /// along with the count typed before it.
+    ///
+    /// Keys that start a longer sequence are held on to until it is finished, it times out, or a key that doesn't
+    /// continue it is pressed (which then starts over on its own). Digits that aren't bound to anything are typed
+    /// into the count instead. Escape gives up on a sequence and its count.
+    pub fn get_action(&mut self, key_event: KeyEvent, contexts: &[Context]) -> Option<(Action, Option<usize>)> {
+        let key = Key::from(key_event);
+
+        if self.is_pending_timed_out() {
+            self.clear_pending();
+        }
+        if (!self.pending.is_empty() || self.count.is_some()) && key.code == KeyCode::Esc {
+            self.clear_pending();
+            self.count = None;
+            return None;
+        }
+        if self.pending.is_empty() && self.type_count(key, contexts) {
+            return None;
+        }
+
+        self.pending.push(key);
+        self.pending_at = Some(Instant::now());
+
+        if self.is_continued(&self.pending, contexts) {
+            return None;
+        }
+
+        let action = self.find_action(&self.pending, contexts);
+        let is_first_key = self.pending.len() == 1;
+        self.clear_pending();
+
+        match action {
+            Some(action) => Some((action, self.count.take())),
+            None if !is_first_key => self.get_action(key_event, contexts),
+            None => {
+                self.count = None;
+                None
+            },
+        }
+    }
+
+    /// Adds `key` to the end of the count if it is a digit that isn't bound within `contexts`,
+    /// returning true if it was. A count can't start with 0.
+    fn type_count(&mut self, key: Key, contexts: &[Context]) -> bool {
+        const MAX_COUNT: usize = 99_999;
+
+        let KeyCode::Char(c) = key.code else {
+            return false;
+        };
+        let Some(digit) = c.to_digit(10).filter(|_| key.modifiers.is_empty()) else {
+            return false;
+        };
+        if (digit == 0 && self.count.is_none()) || self.find_action(&[key], contexts).is_some() || self.is_continued(&[key], contexts) {
+            return false;
+        }
+
+        let count = self.count.unwrap_or(0) * 10 + digit as usize;
+        self.count = Some(count.min(MAX_COUNT));
+
+        true
+    }
+
+    /// Returns the count typed so far for the next action.
+    pub fn get_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Returns the keys pressed so far of a sequence that hasn't been finished yet.
+    pub fn get_pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// Returns true if the next key of a sequence hasn't been pressed in time.
+    pub fn is_pending_timed_out(&self) -> bool {
+        self.pending_at.is_some_and(|pending_at| pending_at.elapsed() >= Self::SEQUENCE_TIMEOUT)
+    }
+
+    /// Gives up on the pending sequence, returning the action bound to the keys pressed so far within `contexts`
+    /// (along with its count), if any.
+    ///
+    /// This is how a key bound on its own, as well as being the start of a longer sequence, is used.
+    pub fn take_pending_action(&mut self, contexts: &[Context]) -> Option<(Action, Option<usize>)> {
+        let action = self.find_action(&self.pending, contexts);
+        self.clear_pending();
+
+        let count = self.count.take();
+        action.map(|action| (action, count))
+    }
+
+    /// Forgets the keys pressed so far of a sequence.
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+        self.pending_at = None;
+    }
+
+    /// Returns every action that is bound to a key, grouped by the context it is used in.
+    /// Contexts with no bound actions are left out.
+    pub fn get_bound_actions_by_context(&self) -> Vec<(Context, Vec<Action>)> {
+        Context::ALL
+            .iter()
+            .map(|context| {
+                let actions: Vec<Action> = Action::ALL
+                    .iter()
+                    .filter(|action| action.context() == *context && !self.get_keys(**action).is_empty())
+                    .copied()
+                    .collect();
+                (*context, actions)
+            })
+            .filter(|(_, actions)| !actions.is_empty())
+            .collect()
+    }
+
+    /// Returns the footer hints for `contexts` (e.g. " <P>: Play  <S>: Shuffle "), using the first key bound to each action.
+    pub fn get_hints(&self, contexts: &[Context]) -> String {
+        let hints: Vec<String> = Action::ALL
+            .iter()
+            .filter(|action| contexts.contains(&action.context()) && self.is_available(**action))
+            .filter_map(|action| {
+                let hint = action.hint()?;
+                let key = self.get_keys(*action).first()?;
+                Some(format!("<{key}>: {hint}"))
+            })
+            .collect();
+
+        if hints.is_empty() {
+            return String::new();
+        }
+
+        format!(" {} ", hints.join("  "))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keymap: &mut Keymap, key: char) -> Option<Action> {
+        press_counted(keymap, key).map(|(action, _)| action)
+    }
+
+    fn press_counted(keymap: &mut Keymap, key: char) -> Option<(Action, Option<usize>)> {
+        keymap.get_action(KeyEvent::new(KeyCode::Char(key), KeyModifiers::NONE), &[Context::Collection, Context::Table])
+    }
+
+    #[test]
+    fn parses_key_sequences() {
+        let sequence: KeySequence = "g  ctrl+a".parse().unwrap();
+
+        assert_eq!(sequence.0, [Key::new(KeyCode::Char('g'), KeyModifiers::NONE), Key::new(KeyCode::Char('a'), KeyModifiers::CONTROL)]);
+        assert_eq!(sequence.to_string(), "g C-a");
+        assert!("".parse::<KeySequence>().is_err());
+    }
+
+    #[test]
+    fn waits_for_the_rest_of_a_sequence() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(keymap.get_pending().len(), 1);
+        assert_eq!(press(&mut keymap, 'g'), Some(Action::GoToTop));
+        assert!(keymap.get_pending().is_empty());
+
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(press(&mut keymap, 'a'), Some(Action::ToggleAlbumGrouping));
+
+        // A key that doesn't continue the sequence starts over on its own.
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(press(&mut keymap, 'b'), Some(Action::GoToBottom));
+
+        // So does escape, without doing anything.
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(keymap.get_action(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), &[Context::Table]), None);
+        assert!(keymap.get_pending().is_empty());
+    }
+
+    #[test]
+    fn uses_keys_bound_on_their_own_once_a_sequence_is_given_up_on() {
+        let overrides = HashMap::from([
+            (Action::GoToTop, vec!["g g".parse().unwrap()]),
+            (Action::ToggleAlbumGrouping, vec!["g".parse().unwrap()]),
+        ]);
+        let mut keymap = Keymap::new(&overrides);
+
+        assert_eq!(press(&mut keymap, 'g'), None);
+        assert_eq!(keymap.take_pending_action(&[Context::Collection, Context::Table]), Some((Action::ToggleAlbumGrouping, None)));
+        assert!(keymap.get_pending().is_empty());
+    }
+
+    #[test]
+    fn counts_actions() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(press_counted(&mut keymap, 'j'), Some((Action::NextRow, None)));
+
+        assert_eq!(press_counted(&mut keymap, '2'), None);
+        assert_eq!(press_counted(&mut keymap, '0'), None);
+        assert_eq!(keymap.get_count(), Some(20));
+        assert_eq!(press_counted(&mut keymap, 'j'), Some((Action::NextRow, Some(20))));
+        assert_eq!(keymap.get_count(), None);
+
+        // Counts carry over the keys of a sequence.
+        assert_eq!(press_counted(&mut keymap, '5'), None);
+        assert_eq!(press_counted(&mut keymap, 'g'), None);
+        assert_eq!(press_counted(&mut keymap, 'g'), Some((Action::GoToTop, Some(5))));
+
+        // A count can't start with 0, and is dropped by a key that isn't bound.
+        assert_eq!(press_counted(&mut keymap, '0'), None);
+        assert_eq!(keymap.get_count(), None);
+        assert_eq!(press_counted(&mut keymap, '3'), None);
+        assert_eq!(press_counted(&mut keymap, 'w'), None);
+        assert_eq!(press_counted(&mut keymap, 'j'), Some((Action::NextRow, None)));
+    }
+
+    #[test]
+    fn lists_every_binding_once_in_the_help() {
+        let overrides = HashMap::from([(Action::OpenRediscover, vec![])]);
+        let keymap = Keymap::new(&overrides);
+
+        let listed: Vec<Action> = keymap.get_bound_actions_by_context()
+            .into_iter()
+            .flat_map(|(context, actions)| {
+                assert!(actions.iter().all(|action| action.context() == context));
+                actions
+            })
+            .collect();
+
+        assert_eq!(listed.len(), Action::ALL.len() - 1);
+        assert!(!listed.contains(&Action::OpenRediscover));
+        assert_eq!(Action::NextTrack.get_name(), "next_track");
+    }
+}
@@ -1,6 +1,20 @@
 use std::{
+    cell::Cell,
+    collections::{
+        HashMap,
+        HashSet,
+    },
     env,
     error::Error,
+    fs,
+    io::{
+        self,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
     sync::{
         atomic::{
             AtomicBool,
@@ -10,18 +24,33 @@ use std::{
         Arc,
         Mutex,
     },
-    time::Duration,
+    thread,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
 };
 
 use color_eyre::{
     eyre::eyre,
     Result,
 };
-use crossterm::event::{
-    self,
-    Event,
-    KeyCode,
-    KeyEventKind,
+use crossterm::{
+    event::{
+        self,
+        Event,
+        KeyboardEnhancementFlags,
+        KeyCode,
+        KeyEvent,
+        KeyEventKind,
+        PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    terminal::{
+        self as crossterm_terminal,
+        SetTitle,
+    },
 };
 use dotenv::dotenv;
 use ratatui::{
@@ -37,14 +66,15 @@ use ratatui::{
         Stylize,
     },
     text::{
-        Line, 
+        Line,
         Span,
+        Text,
     },
     widgets::{
         Block,
         BorderType,
         Borders,
-        Gauge,
+        ListState,
         Paragraph,
         Row,
         Table,
@@ -53,63 +83,387 @@ use ratatui::{
     DefaultTerminal,
     Frame,
 };
-use tokio::sync::mpsc;
-
-pub mod player;
-pub mod rtidalapi;
-
 use rtidalapi::{
+    Album,
+    Artist,
     AudioQuality,
+    Playlist,
     Session,
     Track,
+    TrackNumber,
     User,
 };
+use tokio::sync::mpsc;
+use unicode_width::UnicodeWidthStr;
+
+pub mod accent;
+pub mod album_groups;
+pub mod announcer;
+pub mod audio_output;
+pub mod bandwidth;
+pub mod blocklist;
+pub mod browse;
+pub mod caches;
+pub mod cli;
+pub mod clipboard;
+pub mod config;
+pub mod cover_art;
+pub mod cover_cache;
+pub mod diagnostics;
+pub mod focus;
+pub mod format;
+pub mod graphics;
+pub mod headless;
+pub mod hooks;
+pub mod history;
+pub mod hydration;
+pub mod i18n;
+pub mod ipc;
+pub mod keymap;
+pub mod links;
+pub mod lyrics;
+pub mod m3u;
+pub mod media_controls;
+pub mod menu;
+pub mod mqtt;
+pub mod pending_writes;
+pub mod play_modes;
+pub mod player;
+pub mod player_state;
+pub mod playlist_index;
+pub mod prompt;
+pub mod resample;
+pub mod route;
+pub mod scripting;
+pub mod scrobbler;
+pub mod search;
+pub mod segments;
+pub mod session_stats;
+pub mod shuffle;
+pub mod shutdown;
+pub mod sort;
+pub mod startup;
+pub mod status_server;
+pub mod template;
+pub mod ui;
+pub mod waybar;
+
+use accent::{
+    apply_accent,
+    CoverAccent,
+};
+use album_groups::{
+    AlbumGroups,
+    GroupedRow,
+};
+use announcer::{
+    describe_track,
+    Announcement,
+    Announcer,
+};
+use bandwidth::{
+    BandwidthCap,
+    BandwidthUsage,
+};
+use blocklist::Blocklist;
+use browse::{
+    BrowseContent,
+    BrowseState,
+    BrowseView,
+    BrowseTab,
+    HomeSection,
+    PageItem,
+};
+use caches::CacheKind;
+use config::{
+    Align,
+    Config,
+    NowPlayingElement,
+    PageConfig,
+    ResumeOnLaunch,
+    SlotConfig,
+};
+use cover_art::CoverArt;
+use cover_cache::CoverCache;
+use focus::{
+    Focus,
+    FocusDirection,
+    Pane,
+};
+use format::{
+    format_duration,
+    format_time_ago,
+};
+use graphics::{
+    GraphicsProtocol,
+    Thumbnails,
+};
+use history::PlayHistory;
+use hooks::Snapshot;
+use hydration::{
+    Hydrator,
+    Priority,
+};
+use i18n::{
+    tr,
+    tr_with,
+    Catalog,
+    Message,
+};
+use keymap::{
+    Action,
+    Context,
+    Keymap,
+    KeySequence,
+};
+use links::TidalLink;
+use lyrics::{
+    LyricsCache,
+    LyricsState,
+};
+use m3u::M3uEntry;
+use menu::{
+    AddToPlaylistMenu,
+    DiagnosticsMenu,
+    Menu,
+    QueueReplacement,
+    ReplaceQueueMenu,
+    ScriptMenu,
+    TrackMenu,
+    TrackMenuItem,
+};
+use pending_writes::{
+    PendingWrite,
+    PendingWrites,
+};
+use play_modes::{
+    PlayMode,
+    PlayModes,
+};
 use player::{
-    ParsedManifest,
     Player,
+    SavedQueue,
+};
+use player_state::PlayerState;
+use playlist_index::PlaylistIndex;
+use prompt::Prompt;
+use route::Route;
+use scripting::{
+    ScriptRequest,
+    Scripts,
+};
+use scrobbler::Scrobbler;
+use search::{
+    get_search_text,
+    SearchDirection,
+    TableSearch,
+};
+use sort::{
+    SortField,
+    TrackSort,
+};
+use startup::StartupProfile;
+use template::TemplateValues;
+use ui::{
+    fit_cells,
+    get_column_widths,
+    get_first_visible,
+    media_tag_badges,
+    Header,
+    ListMenu,
+    LyricsView,
+    Marquee,
+    Osd,
+    OsdContent,
+    ProgressBar,
+    BrowseTabs,
+    simplify_styles,
+    spinner_frame,
+    TextPrompt,
+    Theme,
+    TrackCells,
+    TrackInfoPopup,
+    truncate_line,
 };
 
 pub enum AppEvent {
     ReRender,
+    /// Shows a message in the OSD, for background tasks that finish after the key that started them.
+    ShowOsd(OsdContent),
+    /// Brings the terminal window to the front, if the terminal supports it.
+    Raise,
+    /// Adds the track or playlist that a link points to to the queue.
+    QueueLink(String),
+    /// Replaces the queue with tracks that were fetched in the background, showing where they are playing from.
+    PlayTracks(Vec<Arc<Track>>, String),
+    /// Replaces the queue with a radio's tracks once they were fetched in the background, showing which radio it is.
+    PlayRadio(Vec<Arc<Track>>, String),
+    /// Replaces the queue with the tracks of a quick slot, by its number, once they were fetched in the background.
+    PlaySlot(usize, Vec<Arc<Track>>, String),
+    /// Replaces the queue with what another `tidal-tui play` handed off to this instance, shuffled if the bool is true.
+    PlayHandoff(Vec<Arc<Track>>, String, bool),
+    /// My Collection's tracks couldn't be fetched, with the error.
+    CollectionFetchFailed(String),
+}
+
+/// A queue that was replaced by playing something else, which can be restored from the previous queues menu.
+struct PreviousQueue {
+    playing_from: Option<String>,
+    is_shuffle: bool,
+    saved: SavedQueue,
 }
 
+/// Tracks to be played, along with what they are playing from.
+type TracksFrom = (Vec<Arc<Track>>, String);
+
 /// App state.
 pub struct App {
     exit: bool,
+    /// Set when the process is asked to close by a signal, to close the same way as when the user quits.
+    shutdown_requested: Arc<AtomicBool>,
+    config: Config,
+    keymap: Keymap,
+    focus: Focus,
     player: Arc<Mutex<Player>>,
     session: Arc<Session>,
     user: Arc<User>,
     rx: mpsc::Receiver<AppEvent>,
     tx: mpsc::Sender<AppEvent>,
+    hydrator: Hydrator,
     playing_from: Option<String>,
     collection_tracks: Arc<Mutex<Vec<Arc<Track>>>>,
     collection_tracks_len: Arc<AtomicUsize>,
     collection_tracks_fetched: Arc<AtomicBool>,
+    collection_tracks_progress: Arc<Mutex<(usize, usize)>>,
+    collection_tracks_fetch_started_at: Option<Instant>,
+    /// Why My Collection's tracks couldn't be fetched, until they are fetched again once Tidal can be reached.
+    collection_tracks_error: Option<String>,
     collection_tracks_table_state: TableState,
+    /// Whether My Collection - Tracks is grouped by album, in which case its rows are those of `album_groups`.
+    is_grouped_by_album: bool,
+    album_groups: AlbumGroups,
+    /// How My Collection - Tracks is ordered, which its rows and playing it follow.
+    collection_sort: TrackSort,
+    /// How many of the collection's tracks there were, and how many of them had their info, when it was last sorted,
+    /// to sort it again once more of them do.
+    collection_sorted_with: (usize, usize),
+    /// The route shown in the main pane.
+    route: Route,
+    /// Views opened in the main pane in the route shown, most recent last. My Collection is shown when this is empty.
+    browse_stack: Vec<BrowseView>,
+    /// The views that were open in each of the other routes when they were last shown.
+    route_stacks: HashMap<Route, Vec<BrowseView>>,
+    queue_table_state: TableState,
+    show_queue: bool,
+    /// Whether the tracks played before the current one are shown above it in the queue.
+    show_queue_history: bool,
+    /// Whether the playing track's lyrics are shown at the right of the main pane.
+    show_lyrics: bool,
+    lyrics: LyricsCache,
     is_shuffle: bool,
+    info_popup_track: Option<Arc<Track>>,
+    prompt: Option<Prompt>,
+    /// The last search within a table, whose matches stay highlighted until another search is started.
+    search: Option<TableSearch>,
+    menu: Option<Menu>,
+    /// Queues that were replaced, most recent first.
+    previous_queues: Vec<PreviousQueue>,
+    /// The queue as of when the app was last closed (or it was last saved), to pick it up again on the next launch.
+    player_state: PlayerState,
+    /// What was playing when the player state was last saved, to save it again as soon as that changes.
+    player_state_snapshot: Snapshot,
+    /// When the player state was last saved.
+    player_state_saved_at: Instant,
+    /// The quick slot the queue was last swapped to, until something else replaces it.
+    current_slot: Option<usize>,
+    play_modes: PlayModes,
+    blocklist: Arc<Mutex<Blocklist>>,
+    history: Arc<Mutex<PlayHistory>>,
+    playlist_index: PlaylistIndex,
+    /// Changes made while offline or that failed to send, sent once Tidal can be reached again.
+    pending_writes: PendingWrites,
+    /// Sends what is listened to, and loved, to Last.fm and ListenBrainz, if either is set up.
+    scrobbler: Option<Scrobbler>,
+    /// Whether Tidal couldn't be reached (or offline mode was on) as of the last frame, to notice reconnecting.
+    was_offline: bool,
+    /// Whether playback was taken over on another device as of the last frame, to notice it happening.
+    was_taken_over: bool,
+    /// Tracks added to (`true`) or removed from (`false`) My Collection since it was fetched, by id.
+    favorite_changes: Arc<Mutex<HashMap<String, bool>>>,
+    bandwidth_usage: Arc<Mutex<BandwidthUsage>>,
+    bandwidth_cap: BandwidthCap,
+    /// Cover art saved on disk and kept in memory, shared with what draws it.
+    covers: CoverCache,
+    /// Album covers drawn in track tables, if they are turned on and the terminal can draw images.
+    thumbnails: Option<Thumbnails>,
+    /// The playing track's album cover drawn in the Now Playing bar, if it is turned on.
+    cover_art: Option<CoverArt>,
+    osd: Option<(OsdContent, Instant)>,
+    /// The terminal title last set from `config.formats.terminal_title`, to only set it when it changes.
+    terminal_title: Option<String>,
+    /// Whether the last draw scrolled a marquee, which needs to keep being redrawn.
+    is_marquee_scrolling: Cell<bool>,
+    /// Whether the kitty keyboard protocol was turned on, and so needs to be turned off before exiting.
+    is_keyboard_enhanced: bool,
+    /// Whether drawing is cut down to send less to the terminal, as set by `config.low_bandwidth`.
+    is_low_bandwidth: bool,
+    /// The colors everything is drawn in, as set by `config.theme`.
+    theme: Theme,
+    /// The accent color taken from the playing track's album cover, if `config.theme.accent_from_cover` is on.
+    cover_accent: Option<CoverAccent>,
+    /// When the last frame was drawn, to limit how often frames are drawn in low bandwidth mode.
+    last_draw: Instant,
+    /// Writes plain text updates for screen readers, if `config.accessibility.screen_reader` is on.
+    announcer: Option<Announcer>,
+    /// The user's scripts, run on playback changes, actions, and their commands.
+    scripts: Scripts,
 }
 
 impl App {
-    const DEFAULT_COUNTRY_CODE: &str = "CA";
-
-    /// Initializes a new app.
-    pub fn init() -> Result<Self, Box<dyn Error>> {
-        dotenv().ok();
-
-        let home = env::var("HOME")
-            .or_else(|_| env::var("USERPROFILE"))?;
-        let full_config_path = format!("{}/.config/tidal-tui", home);
-
-        let session = Arc::new(
-            Session::new(
-                &env::var("TIDAL_CLIENT_ID")?,
-                &env::var("TIDAL_CLIENT_SECRET")?,
-                Self::DEFAULT_COUNTRY_CODE,
-                &full_config_path,
-            ).unwrap()
-        );
+    /// How long the OSD is shown for after a change.
+    const OSD_DURATION: Duration = Duration::from_millis(1000);
+
+    /// How long before disappearing the OSD is drawn dimmed, to fade it out.
+    const OSD_FADE_DURATION: Duration = Duration::from_millis(300);
+
+    /// How many replaced queues are kept to be restored.
+    const MAX_PREVIOUS_QUEUES: usize = 10;
+
+    /// Tracks need to have been played at least this many times to be rediscovered.
+    const REDISCOVER_MIN_PLAY_COUNT: u32 = 5;
+
+    /// Tracks that were played within this long aren't rediscovered.
+    const REDISCOVER_NOT_PLAYED_FOR: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    /// How many of the tracks played before the current one are shown above it in the queue,
+    /// until it is scrolled up or a row is selected.
+    const QUEUE_HISTORY_ROWS: usize = 3;
+
+    /// Spacing between the columns of tables of tracks.
+    const TRACKS_COLUMN_SPACING: u16 = 3;
+
+    /// Initializes a new app, recording how long each step took in `profile`.
+    ///
+    /// Logging in waits on Tidal, so everything that doesn't need the session is set up while it does.
+    /// If the user has to log in again, `before_login` is called first, from another thread.
+    pub fn init(profile: &StartupProfile, before_login: impl FnOnce() + Send + 'static) -> Result<Self, Box<dyn Error>> {
+        let full_config_path = get_config_path()?;
+
+        let config = profile.time("Load the config", || -> Result<Config, Box<dyn Error>> {
+            let config = Config::load(&full_config_path)?;
+            i18n::init(Catalog::load(config.language.as_deref(), &full_config_path)?);
+            Ok(config)
+        })?;
 
-        let user = Arc::new(User::get_current_user(Arc::clone(&session))?);
+        let login = {
+            let full_config_path = full_config_path.clone();
+            let profile = profile.clone();
+            thread::spawn(move || -> Result<(Arc<Session>, Arc<User>), String> {
+                let session = profile.time("Log in", || init_session(&full_config_path, before_login).map_err(|e| e.to_string()))?;
+                let user = profile.time("Fetch the user", || User::get_current_user(Arc::clone(&session)))?;
+                Ok((session, Arc::new(user)))
+            })
+        };
 
         // Set the AppEvent buffer to 2 to ignore multiple stored rerender events.
         const MAX_APP_EVENTS: usize = 2;
@@ -117,33 +471,260 @@ impl App {
         let (tx, rx) = mpsc::channel::<AppEvent>(MAX_APP_EVENTS);
         let tx_clone = tx.clone();
 
-        let player = Arc::new(Mutex::new(Player::new()?));
-        Player::start_polling_thread(Arc::clone(&player), tx_clone)?;
+        let (blocklist, history, pending_writes, play_modes, player_state, bandwidth_usage) = profile.time(
+            "Load saved state",
+            || -> Result<_, Box<dyn Error>> {
+                Ok((
+                    Arc::new(Mutex::new(Blocklist::load(&full_config_path)?)),
+                    Arc::new(Mutex::new(PlayHistory::load(&full_config_path)?)),
+                    PendingWrites::load(&full_config_path)?,
+                    PlayModes::load(&full_config_path)?,
+                    PlayerState::load(&full_config_path)?,
+                    Arc::new(Mutex::new(BandwidthUsage::load(&full_config_path)?)),
+                ))
+            },
+        )?;
+        let is_grouped_by_album = config.layout.group_by_album;
+        let bandwidth_cap = BandwidthCap::new(&config.bandwidth);
+
+        let is_low_bandwidth = config.low_bandwidth.is_enabled();
+        let theme = config.theme.get_theme();
+
+        let announcer = config.accessibility.screen_reader
+            .then(|| Announcer::open(config.accessibility.output_file.as_deref()))
+            .transpose()?;
+
+        let covers = CoverCache::new(&get_cache_path()?, &full_config_path, Arc::clone(&bandwidth_usage));
+
+        // Terminals that can't draw images get the same tables as with thumbnails turned off,
+        // and images are too much to send in low bandwidth mode.
+        let thumbnails = (config.layout.show_thumbnails && !is_low_bandwidth)
+            .then(GraphicsProtocol::detect)
+            .flatten()
+            .map(|protocol| Thumbnails::new(protocol, covers.clone()));
+
+        // Covers drawn with half blocks are nothing but color, which the monochrome theme strips,
+        // and they have to be decoded, which needs the images feature.
+        let graphics_protocol = GraphicsProtocol::detect();
+        let can_draw_half_blocks = cfg!(feature = "images") && theme != Theme::Monochrome;
+        let cover_art = (config.now_playing.show_cover && !is_low_bandwidth && (graphics_protocol.is_some() || can_draw_half_blocks))
+            .then(|| CoverArt::new(graphics_protocol, covers.clone()));
+
+        // Colors are stripped in low bandwidth mode, and the other themes have colors of their own.
+        // Covers can only be decoded with the images feature, so without it there is no color to take.
+        let cover_accent = (cfg!(feature = "images") && config.theme.accent_from_cover && !is_low_bandwidth && theme == Theme::Default)
+            .then(|| CoverAccent::new(covers.clone()));
+
+        let player = profile.time("Open the audio output", || -> Result<_, Box<dyn Error>> {
+            let player = Arc::new(Mutex::new(Player::new(
+                &config.player,
+                Arc::clone(&blocklist),
+                Arc::clone(&history),
+                Arc::clone(&bandwidth_usage),
+                covers.clone(),
+            )?));
+            Player::start_polling_thread(Arc::clone(&player), tx_clone)?;
+            Ok(player)
+        })?;
+
+        let (session, user) = profile.time("Wait for the login", || login.join())
+            .map_err(|_| String::from("Logging in panicked"))??;
+
+        // Picking up the last queue fetches the track it was up to, so it is done in the background like restoring one.
+        let resumed_queue = (config.player.resume_on_launch != ResumeOnLaunch::Off)
+            .then(|| player_state.get_saved_queue(&session))
+            .flatten();
+        let playing_from = resumed_queue.as_ref().and(player_state.playing_from.clone());
+        let is_shuffle = resumed_queue.as_ref().is_some_and(|saved| saved.is_shuffled);
+        if let Some(saved) = resumed_queue {
+            let should_play = match config.player.resume_on_launch {
+                ResumeOnLaunch::Playing => true,
+                ResumeOnLaunch::Last => player_state.is_playing,
+                ResumeOnLaunch::Off | ResumeOnLaunch::Paused => false,
+            };
+
+            let player_clone = Arc::clone(&player);
+            let tx_clone = tx.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut unlocked_player = player_clone.lock().unwrap();
+                let result = unlocked_player.restore_queue(saved)
+                    .and_then(|_| if should_play { Ok(()) } else { unlocked_player.pause() });
+                drop(unlocked_player);
+
+                if let Err(e) = result {
+                    let message = tr_with(Message::RestoreQueueFailed, &[("error", &e.to_string())]);
+                    let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                }
+            });
+        }
+
+        if config.status_server.enabled {
+            status_server::start(&config.status_server.address, Arc::clone(&player))?;
+        }
+
+        // Launching while another instance is listening exits before this, so it only fails if both were launched at once.
+        if config.ipc.enabled {
+            let _ = ipc::start(Arc::clone(&player), Arc::clone(&session), tx.clone());
+        }
+
+        if config.mqtt.enabled {
+            mqtt::start(&config.mqtt, Arc::clone(&player), tx.clone())?;
+        }
+
+        hooks::start(&config.hooks, &full_config_path, Arc::clone(&player));
+
+        let scrobbler = Scrobbler::new(&config.scrobbling);
+        if let Some(scrobbler) = &scrobbler {
+            scrobbler.start(Arc::clone(&player));
+        }
+
+        let scripts = profile.time("Load scripts", || Scripts::load(&full_config_path, Arc::clone(&player)))?;
 
         let collection_tracks_table_state = TableState::default();
 
+        // Fetch the user's subscription in the background, since it is only needed to limit the quality options.
+        // It can only be fetched with the unofficial API, so without it every quality is offered.
+        #[cfg(feature = "unofficial")]
+        {
+            let session_clone = Arc::clone(&session);
+            let user_clone = Arc::clone(&user);
+            let tx_clone = tx.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Ok(subscription) = user_clone.get_subscription() {
+                    // Don't ask for a quality that the subscription can't stream.
+                    if session_clone.get_audio_quality() > subscription.highest_quality {
+                        let _ = session_clone.set_audio_quality(subscription.highest_quality);
+                    }
+                    let _ = tx_clone.try_send(AppEvent::ReRender);
+                }
+            });
+        }
+
+        // The start page is opened over My Collection, so that going back from it shows My Collection.
+        let browse_stack = match &config.layout.start_page {
+            Some(name) => {
+                let page = config.pages.iter()
+                    .find(|page| page.name == *name)
+                    .cloned()
+                    .ok_or_else(|| format!("The start page \"{name}\" isn't one of the pages in the config"))?;
+                vec![BrowseView::open_page(Arc::clone(&session), page, tx.clone())]
+            },
+            None => vec![],
+        };
+
+        let keymap = Keymap::new(&config.keymap);
+        let show_queue = config.layout.show_queue;
+        let show_queue_history = config.layout.show_queue_history;
+        let hydrator = Hydrator::new(tx.clone(), config.hydration.concurrency);
+
         Ok(Self {
             exit: false,
+            shutdown_requested: Arc::new(AtomicBool::new(false)),
+            config,
+            keymap,
+            focus: Focus::new(Pane::Collection),
             player,
             session,
             user: user,
             tx,
             rx,
-            playing_from: None,
+            hydrator,
+            playing_from,
             collection_tracks: Arc::new(Mutex::new(vec![])),
             collection_tracks_len: Arc::new(AtomicUsize::new(0)),
             collection_tracks_fetched: Arc::new(AtomicBool::new(false)),
+            collection_tracks_progress: Arc::new(Mutex::new((0, 0))),
+            collection_tracks_fetch_started_at: None,
+            collection_tracks_error: None,
             collection_tracks_table_state,
-            is_shuffle: false,
+            is_grouped_by_album,
+            album_groups: AlbumGroups::default(),
+            collection_sort: TrackSort::default(),
+            collection_sorted_with: (0, 0),
+            route: Route::default(),
+            browse_stack,
+            route_stacks: HashMap::new(),
+            queue_table_state: TableState::default(),
+            show_queue,
+            show_queue_history,
+            show_lyrics: false,
+            lyrics: LyricsCache::default(),
+            is_shuffle,
+            info_popup_track: None,
+            prompt: None,
+            search: None,
+            menu: None,
+            previous_queues: vec![],
+            current_slot: None,
+            player_state,
+            player_state_snapshot: Snapshot::stopped(),
+            player_state_saved_at: Instant::now(),
+            play_modes,
+            blocklist,
+            history,
+            playlist_index: PlaylistIndex::new(),
+            pending_writes,
+            scrobbler,
+            // Changes left from the last run are sent on the first frame that Tidal can be reached.
+            was_offline: true,
+            was_taken_over: false,
+            favorite_changes: Arc::new(Mutex::new(HashMap::new())),
+            bandwidth_usage,
+            bandwidth_cap,
+            covers,
+            thumbnails,
+            cover_art,
+            osd: None,
+            terminal_title: None,
+            is_marquee_scrolling: Cell::new(false),
+            is_keyboard_enhanced: false,
+            is_low_bandwidth,
+            theme,
+            cover_accent,
+            last_draw: Instant::now(),
+            announcer,
+            scripts,
         })
     }
 
-    /// Runs the application's main loop until the user quits.
+    /// Returns the flag that closes the app once it is set, for the signal handlers.
+    pub fn get_shutdown_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.shutdown_requested)
+    }
+
+    /// Runs the application's main loop until the user quits or the app is asked to close,
+    /// then saves what is needed to pick up where it left off and stops playback.
+    ///
+    /// This is done even if the loop failed, which it can once the terminal is closed.
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let result = self.run_loop(terminal);
+        self.shut_down();
+        result
+    }
+
+    /// Runs the application's main loop until the user quits or the app is asked to close.
+    fn run_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
         while !self.exit {
+            self.update_offline_state();
+            self.update_taken_over();
+            self.update_bandwidth_cap();
+            self.update_scripts()?;
+            self.update_player_state();
             terminal.draw(|frame| self.draw(frame))?;
+            self.last_draw = Instant::now();
+            self.update_terminal_title()?;
+            self.draw_thumbnails()?;
+            self.announce_changes();
+
+            // Redraws asked for before another frame is due in low bandwidth mode are put off until it is.
+            let mut is_redraw_pending = false;
 
             loop {
+                if self.shutdown_requested.load(Ordering::Relaxed) {
+                    self.exit();
+                    break;
+                }
+
                 // Terminal events
                 if event::poll(Duration::from_millis(100))? {
                     self.handle_terminal_event(event::read()?)?;
@@ -153,408 +734,3892 @@ impl App {
                 // Internal app events
                 if let Ok(app_event) = self.rx.try_recv() {
                     match app_event {
-                        AppEvent::ReRender => break,
+                        AppEvent::ReRender if self.is_redraw_due() => break,
+                        AppEvent::ReRender => is_redraw_pending = true,
+                        AppEvent::ShowOsd(content) => {
+                            self.show_osd(content);
+                            break;
+                        },
+                        AppEvent::Raise => raise_terminal()?,
+                        AppEvent::QueueLink(link) => {
+                            self.queue_link(&link);
+                            break;
+                        },
+                        AppEvent::PlayTracks(tracks, playing_from) => {
+                            self.play_tracks(tracks, playing_from).map_err(|e| eyre!(format!("{e}")))?;
+                            break;
+                        },
+                        AppEvent::PlayRadio(tracks, playing_from) => {
+                            let replacement = QueueReplacement::Tracks {
+                                tracks,
+                                playing_from,
+                                shuffle: false,
+                                is_auto_generated: true,
+                                slot: None,
+                            };
+                            self.confirm_replace_queue(replacement).map_err(|e| eyre!(format!("{e}")))?;
+                            break;
+                        },
+                        AppEvent::PlaySlot(slot, tracks, playing_from) => {
+                            let replacement = QueueReplacement::Tracks {
+                                tracks,
+                                playing_from,
+                                shuffle: false,
+                                is_auto_generated: false,
+                                slot: Some(slot),
+                            };
+                            self.confirm_replace_queue(replacement).map_err(|e| eyre!(format!("{e}")))?;
+                            break;
+                        },
+                        AppEvent::PlayHandoff(tracks, playing_from, shuffle) => {
+                            self.start_playing(tracks, playing_from, shuffle, false).map_err(|e| eyre!(format!("{e}")))?;
+                            break;
+                        },
+                        AppEvent::CollectionFetchFailed(e) => {
+                            self.collection_tracks_error = Some(e);
+                            break;
+                        },
+                    }
+                }
+
+                if is_redraw_pending && self.is_redraw_due() {
+                    break;
+                }
+
+                // Redraw while the OSD is shown so that it fades out and disappears on time.
+                // In low bandwidth mode it doesn't fade, so it is only redrawn once it is time for it to disappear.
+                if let Some((_, shown_at)) = &self.osd
+                    && (!self.is_low_bandwidth || shown_at.elapsed() >= Self::OSD_DURATION)
+                {
+                    break;
+                }
+
+                // Redraw while the collection is loading to animate its spinner.
+                if !self.collection_tracks_fetched.load(Ordering::Relaxed)
+                    && self.collection_tracks_error.is_none()
+                    && self.is_redraw_due()
+                {
+                    break;
+                }
+
+                // Give up on a key sequence once its next key hasn't been pressed in time,
+                // using the keys pressed so far if they are bound on their own.
+                if self.keymap.is_pending_timed_out() {
+                    if let Some((action, count)) = self.keymap.take_pending_action(&self.get_active_contexts()) {
+                        self.handle_action(action, count)?;
                     }
+                    break;
+                }
+
+                // Redraw on each tick while a title is scrolling.
+                if self.is_marquee_scrolling.get() {
+                    break;
                 }
             }
         }
+
         Ok(())
     }
 
-    /// Draws a frame.
-    fn draw(&mut self, f: &mut Frame) {
-        let main_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Fill(1),
-                Constraint::Length(7),
-            ])
-            .split(f.area());
-        let main_area = main_layout[0];
-        let now_playing_area = main_layout[1];
+    /// Saves the player state, then stops playback and releases the media controls.
+    fn shut_down(&mut self) {
+        self.save_player_state();
 
-        self.draw_my_collections_tracks(f, main_area);
-        self.draw_now_playing(f, now_playing_area);
+        // There is nowhere to show an error once the app is closing.
+        if let Ok(mut unlocked_player) = self.player.lock() {
+            let _ = unlocked_player.shut_down();
+        }
     }
 
-    /// Draws the My Collections - Tracks table.
-    fn draw_my_collections_tracks(&mut self, f: &mut Frame, area: Rect) {
-        let my_collection_block = Block::new()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Color::Cyan)
-            .title(" My Collection - Tracks ".bold())
-            .title_bottom(Line::from(" <P>: Play  <S>: Shuffle ").right_aligned());
-        f.render_widget(my_collection_block, area);
-        
-        let inner_area = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Fill(1),
-            ])
-            .vertical_margin(1)
-            .horizontal_margin(2)
-            .split(area)
-            [0];
+    /// Saves the player state if the track changed or playback was paused or stopped since it was last saved,
+    /// or every `config.player.state_save_interval_secs` otherwise, so that little is lost if the app is killed.
+    fn update_player_state(&mut self) {
+        let Ok(snapshot) = self.player.lock().map(|unlocked_player| Snapshot::from_player(&unlocked_player)) else {
+            return;
+        };
 
-        if self.collection_tracks_fetched.load(Ordering::Relaxed) {
-            let unlocked_collection_tracks = self.collection_tracks.lock().unwrap();
-            let collection_tracks_rows: Vec<Row> = unlocked_collection_tracks
-                .iter()
-                .enumerate()
-                .map(|(idx, track)| {
-                    let current_position = self.collection_tracks_table_state.selected().unwrap_or(0);
-                    let num_rows = inner_area.height as usize;
-                    let render_window_amount = num_rows + 10;
-
-                    // Only render certain number of rows.
-                    if idx >= current_position.saturating_sub(render_window_amount) && idx <= current_position.saturating_add(render_window_amount) {
-                        if track.has_info() {
-                            let number = (idx + 1).to_string();
-                            let title = track.get_attribtues().unwrap().title.clone();
-                            let artist = track.get_artist().unwrap().attributes.name.clone();
-                            let album = track.get_album().unwrap().attributes.title.clone();
-                            let duration = track.get_duration().unwrap().clone();
-                            let time = format_duration(duration);
-
-                            Row::new([number, title, artist, album, time])
-                        } else {
-                            let tx_clone = self.tx.clone();
-                            let track_clone = Arc::clone(&track);
-
-                            tokio::task::spawn_blocking(move || {
-                                track_clone.get_attribtues().unwrap();
-                                track_clone.get_artist().unwrap();
-                                track_clone.get_album().unwrap();
-                                let _ = tx_clone.try_send(AppEvent::ReRender);
-                            });
-
-                            Row::new(["".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()])
-                        }
-                    } else {
-                        Row::new(["".to_string(), "".to_string(), "".to_string(), "".to_string(), "".to_string()])
-                    }
-                })
-                .collect();
-            drop(unlocked_collection_tracks);
+        let interval = Duration::from_secs(self.config.player.state_save_interval_secs);
+        if snapshot != self.player_state_snapshot || self.player_state_saved_at.elapsed() >= interval {
+            self.save_player_state();
+            self.player_state_snapshot = snapshot;
+        }
+    }
 
-            let collection_tracks_table = Table::default()
-                .header(
-                    Row::new(["#", "Title", "Artist", "Album", "Time"])
-                        .bottom_margin(1)
-                )
-                .widths([Constraint::Max(6), Constraint::Min(10), Constraint::Min(10), Constraint::Min(10), Constraint::Max(9)])
-                .column_spacing(3)
-                .rows(collection_tracks_rows)
-                .row_highlight_style(Style::new().cyan().bold());
+    /// Records the queue and where it is up to in the player state file, to pick it up again on the next launch.
+    ///
+    /// The file is only written if something changed since it was last saved.
+    fn save_player_state(&mut self) {
+        let Ok(unlocked_player) = self.player.lock() else {
+            return;
+        };
+        let saved = unlocked_player.save_queue();
+        let is_playing = unlocked_player.is_playing();
+        drop(unlocked_player);
 
-            f.render_stateful_widget(collection_tracks_table, inner_area, &mut self.collection_tracks_table_state);
-        } else {
-            f.render_widget(Paragraph::new("Loading..."), inner_area);
+        self.player_state_saved_at = Instant::now();
+        if !self.player_state.set_queue(saved.as_ref(), is_playing, self.playing_from.clone()) {
+            return;
+        }
+        // Failing to save the state shouldn't stop playback, and there is nowhere to show an error once the app is closing.
+        let _ = self.player_state.save();
+    }
 
-            let tx_clone = self.tx.clone();
-            let collection_tracks_clone = Arc::clone(&self.collection_tracks);
-            let collection_tracks_len_clone = Arc::clone(&self.collection_tracks_len);
-            let collection_tracks_fetched_clone = Arc::clone(&self.collection_tracks_fetched);
-            let user_clone = Arc::clone(&self.user);
+    /// Returns true if enough time has passed since the last frame to draw another one,
+    /// which is only limited in low bandwidth mode.
+    fn is_redraw_due(&self) -> bool {
+        !self.is_low_bandwidth
+            || self.last_draw.elapsed() >= Duration::from_millis(self.config.low_bandwidth.redraw_interval_ms)
+    }
 
-            tokio::task::spawn_blocking(move || {
-                let collection_tracks = user_clone.get_collection_tracks().unwrap().to_vec();
-                collection_tracks_len_clone.store(collection_tracks.len(), Ordering::Relaxed);
+    /// Returns true if Tidal can't be reached, or offline mode is on.
+    fn is_offline(&self) -> bool {
+        self.session.is_offline_forced() || !self.session.is_online()
+    }
 
-                {
-                    *collection_tracks_clone.lock().unwrap() = collection_tracks
-                        .into_iter()
-                        .map(|t| Arc::new(t))
-                        .collect();  
-                }
+    /// Hides network actions, pauses fetching track info, and keeps the player to tracks it can play while offline,
+    /// and sends the changes made while offline once Tidal can be reached again.
+    fn update_offline_state(&mut self) {
+        let is_offline = self.is_offline();
 
-                collection_tracks_fetched_clone.store(true, Ordering::Relaxed);
-                let _ = tx_clone.try_send(AppEvent::ReRender);
-            });
+        self.keymap.set_offline(is_offline);
+        self.hydrator.set_paused(is_offline);
+        if let Ok(mut unlocked_player) = self.player.lock() {
+            unlocked_player.set_offline(is_offline);
+        }
+
+        if self.was_offline && !is_offline {
+            self.pending_writes.flush(Arc::clone(&self.session), Arc::clone(&self.user), self.tx.clone());
+
+            // Fetch the collection again if it failed while offline.
+            if self.collection_tracks_error.take().is_some() {
+                self.collection_tracks_fetch_started_at = None;
+            }
         }
+        self.was_offline = is_offline;
     }
 
-    /// Draws the now playing block.
-    fn draw_now_playing(&mut self, f: &mut Frame, area: Rect) {
-        let mut title = Line::from(" Now Playing ".bold());
+    /// Shows how to resume here once playback has been taken over on another device.
+    ///
+    /// This is checked on each frame rather than sent by the player, so that the message can't be dropped
+    /// when the app's event channel is full.
+    fn update_taken_over(&mut self) {
+        let Ok(is_taken_over) = self.player.lock().map(|unlocked_player| unlocked_player.is_taken_over()) else {
+            return;
+        };
 
-        if let Some(playing_from) = &self.playing_from {
-            title.push_span(format!("- {} ", playing_from));
+        if is_taken_over && !self.was_taken_over {
+            let resume_key = self.keymap.get_keys(Action::ResumeHere)
+                .first()
+                .map_or(String::from(tr(Message::ThePlayKey)), |key| key.to_string());
+            self.show_osd(OsdContent::Message(tr_with(Message::PlaybackTakenOver, &[("key", &resume_key)])));
         }
+        self.was_taken_over = is_taken_over;
+    }
 
-        let now_playing_block = Block::new()
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Color::Cyan)
-            .title(title);
-        f.render_widget(now_playing_block, area);
+    /// Lowers the quality while over a bandwidth cap, and puts it back once under every cap again.
+    fn update_bandwidth_cap(&mut self) {
+        let message = self.bandwidth_cap.update(&self.bandwidth_usage.lock().unwrap(), &self.session);
 
-        let sections = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Fill(2),
-                Constraint::Fill(3),
-                Constraint::Fill(2),
-            ])
-            .vertical_margin(2)
-            .horizontal_margin(2)
-            .spacing(1)
-            .split(area);
+        if let Some(message) = message {
+            self.show_osd(OsdContent::Message(message));
+        }
+    }
 
-        let left_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
-            .split(sections[0]);
+    /// Tells the scripts about playback changes, and does what they asked for.
+    fn update_scripts(&mut self) -> Result<()> {
+        self.scripts.update();
+        self.handle_script_requests()
+    }
 
-        let middle_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
-            .split(sections[1]);
-        let progress_layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Length(5),
-                Constraint::Fill(1),
-                Constraint::Length(5),
-            ])
-            .spacing(1)
-            .split(middle_layout[2]);
+    /// Does what the scripts have asked for since this was last called.
+    ///
+    /// Actions asked for by scripts are run without being passed back to the scripts, so that they can't loop.
+    fn handle_script_requests(&mut self) -> Result<()> {
+        for request in self.scripts.take_requests() {
+            match request {
+                ScriptRequest::RunAction(action) => self.run_action(action, None)?,
+                ScriptRequest::ShowOsd(message) => self.show_osd(OsdContent::Message(message)),
+                ScriptRequest::ShowView { title, lines } => {
+                    self.menu = Some(Menu::Script(ScriptMenu {
+                        title,
+                        lines,
+                        list_state: ListState::default(),
+                    }));
+                },
+            }
+        }
 
-        let right_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(1),
-                Constraint::Length(1),
-                Constraint::Length(1),
-            ])
-            .split(sections[2]);
+        Ok(())
+    }
 
-        let unlocked_player = self.player.lock().unwrap(); 
+    /// Opens the diagnostics menu, running the checks in the background.
+    fn open_diagnostics(&mut self) -> Result<(), Box<dyn Error>> {
+        let config_path = get_config_path()?;
+        let checks = Arc::new(Mutex::new(None));
 
-        let progress_bar_label = Span::styled("", Color::LightCyan);
-        let mut progress_bar = Gauge::default()
-            .gauge_style(
-                Style::default()
-                    .fg(Color::Cyan)
-                    .bg(Color::DarkGray)
-            )
-            .ratio(0.0)
-            .label(progress_bar_label);
+        let checks_clone = Arc::clone(&checks);
+        let session_clone = Arc::clone(&self.session);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            *checks_clone.lock().unwrap() = Some(diagnostics::run_checks(Some(&session_clone), &config_path));
+            let _ = tx_clone.try_send(AppEvent::ReRender);
+        });
 
-        match unlocked_player.get_current_track() {
-            Some(current_track) if current_track.has_info() => {
-                let track_title = current_track.get_attribtues().unwrap().title.clone();
-                let artist_title = current_track.get_artist().unwrap().attributes.name.clone();
-                let album_title = current_track.get_album().unwrap().attributes.title.clone();
+        self.menu = Some(Menu::Diagnostics(DiagnosticsMenu {
+            checks,
+            list_state: ListState::default(),
+        }));
 
-                f.render_widget(Line::from(track_title.bold()), left_layout[0]);
-                f.render_widget(Line::from(artist_title), left_layout[1]);
-                f.render_widget(Line::from(album_title), left_layout[2]);
+        Ok(())
+    }
 
-                let position = unlocked_player.get_position();
-                let track_duration = current_track.get_duration().unwrap().clone();
-                let position_progress = (position.as_secs() as f64) / (track_duration.as_secs() as f64);
+    /// Copies the diagnostics report to the clipboard once the checks have finished.
+    fn copy_diagnostics_report(&mut self) {
+        let Some(Menu::Diagnostics(menu)) = &self.menu else {
+            return;
+        };
+        let Some(checks) = menu.checks.lock().unwrap().clone() else {
+            return;
+        };
 
-                progress_bar = progress_bar.ratio(position_progress);
+        let message = match clipboard::copy(&diagnostics::format_report(&checks)) {
+            Ok(()) => String::from(tr(Message::DiagnosticsReportCopied)),
+            Err(e) => tr_with(Message::CopyReportFailed, &[("error", &e)]),
+        };
+        self.show_osd(OsdContent::Message(message));
+    }
 
-                f.render_widget(Line::from(format_duration(position)).right_aligned(), progress_layout[0]);
-                f.render_widget(Line::from(format_duration(track_duration)).left_aligned(), progress_layout[2]);
+    /// Turns offline mode on or off.
+    fn toggle_offline(&mut self) {
+        let is_offline_forced = !self.session.is_offline_forced();
+        self.session.set_offline_forced(is_offline_forced);
 
-                if let Some(parsed_manifest) = unlocked_player.get_parsed_manifest() {
-                    f.render_widget(
-                        Line::from(self.get_quality_string(&parsed_manifest)).right_aligned(),
-                        right_layout[2]
-                    );
-                }
-            },
-            _ => {
-                f.render_widget(Line::from("Nothing playing").dark_gray(), left_layout[0]);
+        let message = if is_offline_forced { Message::OfflineModeOn } else { Message::OfflineModeOff };
+        self.show_osd(OsdContent::Message(String::from(tr(message))));
+    }
 
-                f.render_widget(Line::from("0:00").right_aligned(), progress_layout[0]);
-                f.render_widget(Line::from("0:00").left_aligned(), progress_layout[2]);
-            },
+    /// Turns on the kitty keyboard protocol if the terminal supports it (and it isn't turned off in the config),
+    /// so that more combinations of modifiers (e.g. `ctrl+space` and `shift+left`) can be told apart.
+    pub fn enable_keyboard_enhancement(&mut self) -> Result<()> {
+        if !self.config.keyboard.enhanced || !crossterm_terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            return Ok(());
         }
 
-        f.render_widget(progress_bar, progress_layout[1]);
+        crossterm::execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES | KeyboardEnhancementFlags::REPORT_EVENT_TYPES),
+        )?;
+        self.is_keyboard_enhanced = true;
 
-        let shuffle_str = if self.is_shuffle { "Shuffle: On    " } else { "Shuffle: Off    " };
-        let playing_status_str = if unlocked_player.is_playing() { "||" } else { "> " };
-        
-        f.render_widget(
-            Line::default().spans(
-                vec![
-                    shuffle_str.dark_gray(),
-                    playing_status_str.into(),
-                    "    Repeat: Off".dark_gray(),
-                ]
-            ).centered(),
-            middle_layout[0]);
+        Ok(())
+    }
 
-        let volume = unlocked_player.get_volume();
-        let quality = self.session.get_audio_quality();
+    /// Turns the kitty keyboard protocol back off, if it was turned on.
+    pub fn disable_keyboard_enhancement(&mut self) -> Result<()> {
+        if self.is_keyboard_enhanced {
+            crossterm::execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+            self.is_keyboard_enhanced = false;
+        }
 
-        f.render_widget(Line::from(format!("Volume: {}%", volume)).right_aligned(), right_layout[0]);
-        f.render_widget(Line::from(format!("Quality: {}", quality.to_string())).right_aligned(), right_layout[1]);
+        Ok(())
     }
 
-    /// Returns a string displaying the quality of a track, based on its parsed manifest.
-    fn get_quality_string(&self, parsed_manifest: &ParsedManifest) -> String {
-        let codec = parsed_manifest.codec.to_uppercase();
+    /// Sets the terminal's title to what is playing, if a terminal title format is configured.
+    fn update_terminal_title(&mut self) -> Result<()> {
+        let Some(template) = &self.config.formats.terminal_title else {
+            return Ok(());
+        };
 
-        if codec != "FLAC" {
-            return "".to_string();
-        }
+        let player = self.player.lock()
+            .map_err(|e| eyre!(format!("{e:#?}")))?;
+        let title = match TemplateValues::from_player(&player) {
+            Some(values) => template.render(&values),
+            None => String::from("tidal-tui"),
+        };
+        drop(player);
 
-        let sample_rate = (parsed_manifest.sample_rate as f32 / 1000.0).to_string();
+        if self.terminal_title.as_ref() != Some(&title) {
+            crossterm::execute!(io::stdout(), SetTitle(&title))?;
+            self.terminal_title = Some(title);
+        }
 
-        format!("{}-Bit {}kHz {}", parsed_manifest.bit_depth, sample_rate, codec)
+        Ok(())
     }
 
-    /// Handles user input events and updates application state accordingly.
-    fn handle_terminal_event(&mut self, event: Event) -> Result<()> {
-        match event {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                match key_event.code {
-                    KeyCode::Char('Q') => self.exit(),
-
-                    // My Collection - Tracks keybinds
-                    KeyCode::Up => self.prev_row(),
-                    KeyCode::Down => self.next_row(),
-                    KeyCode::Char('t') => self.go_to_top(),
-                    KeyCode::Char('b') => self.go_to_bottom(),
-                    KeyCode::Char('c') => self.go_to_currently_playing().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char('P') => self.play_all().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char('S') => self.shuffle_all().map_err(|e| eyre!(format!("{e}")))?,
-
-                    // Player keybinds
-                    KeyCode::Char('-') => self.volume_down().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char('=') => self.volume_up().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char(' ') => self.toggle_play_pause().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char('[') => self.previous_track().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char(']') => self.next_track().map_err(|e| eyre!(format!("{e}")))?,
-                    KeyCode::Char(',') => self.cycle_audio_quality().map_err(|e| eyre!(format!("{e}")))?,
-                    _ => {},
+    /// Draws the thumbnails and the cover placed while drawing the last frame, which have to be written after it.
+    fn draw_thumbnails(&mut self) -> Result<()> {
+        if let Some(thumbnails) = &mut self.thumbnails {
+            // Popups are drawn over the tables, so thumbnails are hidden until they are closed.
+            if self.info_popup_track.is_some() || self.prompt.is_some() || self.menu.is_some() {
+                // Hiding the thumbnails can take the cover with them.
+                if thumbnails.hide(&mut io::stdout())? && let Some(cover_art) = &mut self.cover_art {
+                    cover_art.invalidate();
                 }
+            } else {
+                thumbnails.flush(&mut io::stdout(), &self.tx)?;
             }
-            _ => {},
-        };
+        }
+
+        if let Some(cover_art) = &mut self.cover_art {
+            cover_art.flush(&mut io::stdout(), &self.tx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws a frame.
+    fn draw(&mut self, f: &mut Frame) {
+        self.is_marquee_scrolling.set(false);
+
+        // Borders and margins take up 4 lines of the Now Playing block.
+        let now_playing_height = self.config.now_playing.num_lines() + 4;
+
+        let header_height = if self.config.layout.show_header { 1 } else { 0 };
+
+        let main_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(header_height),
+                Constraint::Fill(1),
+                Constraint::Length(now_playing_height),
+            ])
+            .split(f.area());
+        let header_area = main_layout[0];
+        let main_area = main_layout[1];
+        let now_playing_area = main_layout[2];
+
+        // The lyrics are as wide as the queue, at the right of everything else in the main pane.
+        let (panes_area, lyrics_area) = if self.show_lyrics {
+            let split_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Fill(1),
+                    Constraint::Percentage(self.config.layout.queue_width_percent),
+                ])
+                .split(main_area);
+            (split_layout[0], Some(split_layout[1]))
+        } else {
+            (main_area, None)
+        };
+
+        // The queue route shows the queue in place of the main pane.
+        let (collection_area, queue_area) = if self.route == Route::Queue {
+            (None, Some(panes_area))
+        } else if self.is_queue_visible(panes_area) {
+            let split_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Fill(1),
+                    Constraint::Percentage(self.config.layout.queue_width_percent),
+                ])
+                .split(panes_area);
+            (Some(split_layout[0]), Some(split_layout[1]))
+        } else {
+            (Some(panes_area), None)
+        };
+
+        self.focus.clear_areas();
+        if let Some(collection_area) = collection_area {
+            self.focus.set_area(Pane::Collection, collection_area);
+        }
+        if let Some(queue_area) = queue_area {
+            self.focus.set_area(Pane::Queue, queue_area);
+        }
+        self.focus.set_area(Pane::NowPlaying, now_playing_area);
+        self.focus.ensure_visible();
+
+        // Only what is drawn in this frame keeps its hydration priority.
+        self.hydrator.reset_priorities();
+        let (queue_tracks, current_row) = self.get_queue_rows();
+        for track in queue_tracks.iter().skip(current_row).take(2) {
+            self.hydrator.request(track, Priority::Playing);
+        }
+
+        if self.config.layout.show_header {
+            let header = Header::new(&self.user.attributes)
+                .tier(self.user.get_cached_subscription().map(|subscription| subscription.get_tier_name()))
+                .online(self.session.is_online())
+                .offline_forced(self.session.is_offline_forced())
+                .pending_changes(self.pending_writes.len())
+                .quality(self.session.get_audio_quality())
+                .slot(self.current_slot)
+                .sort((!self.collection_sort.is_default()).then(|| self.collection_sort.get_name()));
+            f.render_widget(header, header_area);
+        }
+
+        if let Some(collection_area) = collection_area {
+            if self.browse_stack.is_empty() {
+                self.draw_my_collections_tracks(f, collection_area);
+            } else {
+                self.draw_browse_view(f, collection_area);
+            }
+        }
+        if let Some(queue_area) = queue_area {
+            self.draw_queue(f, queue_area);
+        }
+        if let Some(lyrics_area) = lyrics_area {
+            self.draw_lyrics(f, lyrics_area);
+        }
+        self.draw_now_playing(f, now_playing_area);
+        self.draw_osd(f, now_playing_area);
+
+        if let Some(track) = &self.info_popup_track {
+            let unlocked_player = self.player.lock().unwrap();
+
+            // The stream url is only known for the track that is currently playing.
+            let is_current_track = unlocked_player.get_current_track().is_some_and(|t| t.id == track.id);
+            let stream_url = unlocked_player.get_parsed_manifest()
+                .filter(|_| is_current_track)
+                .and_then(|parsed_manifest| parsed_manifest.urls.first())
+                .map(|url| url.as_str());
+
+            let popup = TrackInfoPopup::new(track)
+                .stream_url(stream_url)
+                .in_playlists(self.get_playlist_names_containing(&track.id), self.playlist_index.is_complete())
+                .footer(self.keymap.get_hints(&[Context::TrackInfo]))
+                .locale(self.config.locale);
+            f.render_widget(popup, main_area);
+        }
+
+        if let Some(prompt) = &self.prompt {
+            let text_prompt = TextPrompt::new(prompt.get_title(), prompt.get_input())
+                .footer(prompt.get_footer());
+            f.render_widget(text_prompt, main_area);
+        }
+
+        if self.menu.is_some() {
+            self.draw_menu(f, main_area);
+        }
+
+        if let Some(cover_accent) = &mut self.cover_accent {
+            let cover_url = self.player.lock().unwrap()
+                .get_current_track()
+                .and_then(|track| track.get_cached_album())
+                .and_then(|album| album.get_cover_art_url(Thumbnails::COVER_SIZE).map(String::from));
+
+            if let Some(accent) = cover_accent.get(cover_url.as_deref(), &self.tx) {
+                apply_accent(f.buffer_mut(), accent);
+            }
+        }
+
+        self.theme.apply(f.buffer_mut());
+        if self.is_low_bandwidth {
+            simplify_styles(f.buffer_mut());
+        }
+    }
+
+    /// Returns the style of the selected row of a table.
+    ///
+    /// Colors are stripped in low bandwidth mode and the monochrome theme, so the row is reversed instead.
+    fn get_row_highlight_style(&self) -> Style {
+        if self.is_low_bandwidth || self.theme == Theme::Monochrome {
+            Style::new().reversed()
+        } else {
+            Style::new().cyan().bold()
+        }
+    }
+
+    /// Returns true if the queue should be shown next to the collection in `area`,
+    /// which needs the queue to be toggled on and the terminal to be wide enough.
+    fn is_queue_visible(&self, area: Rect) -> bool {
+        self.show_queue && area.width >= self.config.layout.queue_min_width
+    }
+
+    /// Draws the My Collections - Tracks table.
+    fn draw_my_collections_tracks(&mut self, f: &mut Frame, area: Rect) {
+        let my_collection_block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.get_border_color(Pane::Collection))
+            .title(self.get_pane_title(Pane::Collection, tr(Message::MyCollectionTracks)).bold())
+            .title(self.get_route_tabs(area, tr(Message::MyCollectionTracks)))
+            .title_bottom(Line::from(self.keymap.get_hints(&Pane::Collection.get_contexts())).right_aligned())
+            .title_bottom(self.get_search_line(Pane::Collection));
+        f.render_widget(my_collection_block, area);
+        
+        let inner_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+            ])
+            .vertical_margin(1)
+            .horizontal_margin(2)
+            .split(area)
+            [0];
+
+        if self.collection_tracks_fetched.load(Ordering::Relaxed) {
+            self.update_collection_sort();
+
+            let collection_tracks = Arc::clone(&self.collection_tracks);
+            let unlocked_collection_tracks = collection_tracks.lock().unwrap();
+
+            let collection_tracks_rows = if self.is_grouped_by_album {
+                self.regroup_albums(&unlocked_collection_tracks);
+                self.get_grouped_track_rows(&unlocked_collection_tracks, &self.collection_tracks_table_state, inner_area)
+            } else {
+                self.get_track_rows(&unlocked_collection_tracks, &self.collection_tracks_table_state, inner_area)
+            };
+
+            let collection_tracks_table = self.get_tracks_table(collection_tracks_rows);
+
+            f.render_stateful_widget(collection_tracks_table, inner_area, &mut self.collection_tracks_table_state);
+
+            if let Some(thumbnails) = &mut self.thumbnails {
+                let offset = self.collection_tracks_table_state.offset();
+
+                // While grouped, the album covers are only drawn next to the album headers.
+                if self.is_grouped_by_album {
+                    let urls = self.album_groups.get_rows().iter().map(|row| match row {
+                        GroupedRow::Album(group_idx) => self.album_groups.get_group(*group_idx)?
+                            .track_indices.iter()
+                            .find_map(|idx| unlocked_collection_tracks[*idx].get_cached_album())?
+                            .get_cover_art_url(Thumbnails::COVER_SIZE),
+                        GroupedRow::Track(_) => None,
+                    });
+                    thumbnails.place_rows(urls, inner_area, offset);
+                } else {
+                    thumbnails.place_tracks(&unlocked_collection_tracks, inner_area, offset);
+                }
+            }
+        } else if let Some(e) = &self.collection_tracks_error {
+            f.render_widget(Paragraph::new(tr_with(Message::FetchFavoritesFailed, &[("error", e)])).red(), inner_area);
+        } else if let Some(fetch_started_at) = self.collection_tracks_fetch_started_at {
+            let (fetched, total) = *self.collection_tracks_progress.lock().unwrap();
+
+            let progress = if total == 0 {
+                String::from(tr(Message::FetchingFavorites))
+            } else {
+                let (fetched, total) = (self.config.locale.format_count(fetched), self.config.locale.format_count(total));
+                tr_with(Message::FetchedFavorites, &[("fetched", &fetched), ("total", &total)])
+            };
+
+            let loading_line = Line::from(vec![
+                spinner_frame(fetch_started_at.elapsed()).cyan(),
+                " ".into(),
+                progress.into(),
+            ]);
+            f.render_widget(Paragraph::new(loading_line), inner_area);
+        } else {
+            self.collection_tracks_fetch_started_at = Some(Instant::now());
+
+            let tx_clone = self.tx.clone();
+            let collection_tracks_clone = Arc::clone(&self.collection_tracks);
+            let collection_tracks_len_clone = Arc::clone(&self.collection_tracks_len);
+            let collection_tracks_fetched_clone = Arc::clone(&self.collection_tracks_fetched);
+            let collection_tracks_progress_clone = Arc::clone(&self.collection_tracks_progress);
+            let user_clone = Arc::clone(&self.user);
+            let hydrator_clone = self.config.hydration.background.then(|| self.hydrator.clone());
+
+            tokio::task::spawn_blocking(move || {
+                let result = fetch_collection_tracks(&user_clone, |fetched, total| {
+                    *collection_tracks_progress_clone.lock().unwrap() = (fetched, total);
+                    let _ = tx_clone.try_send(AppEvent::ReRender);
+                });
+                let collection_tracks = match result {
+                    Ok(collection_tracks) => collection_tracks,
+                    Err(e) => {
+                        let _ = tx_clone.blocking_send(AppEvent::CollectionFetchFailed(e));
+                        return;
+                    },
+                };
+                collection_tracks_len_clone.store(collection_tracks.len(), Ordering::Relaxed);
+
+                {
+                    *collection_tracks_clone.lock().unwrap() = collection_tracks
+                        .into_iter()
+                        .map(|t| Arc::new(t))
+                        .collect();  
+                }
+
+                // Load the rest of the collection in the background, so that it is ready when scrolled to.
+                if let Some(hydrator) = hydrator_clone {
+                    for track in collection_tracks_clone.lock().unwrap().iter() {
+                        hydrator.request(track, Priority::Background);
+                    }
+                }
+
+                collection_tracks_fetched_clone.store(true, Ordering::Relaxed);
+                let _ = tx_clone.try_send(AppEvent::ReRender);
+            });
+        }
+    }
+
+    /// Groups the collection's tracks by album again, to place the tracks whose album has been fetched since the last frame.
+    fn regroup_albums(&mut self, collection_tracks: &[Arc<Track>]) {
+        let album_ids = collection_tracks.iter()
+            .map(|track| track.get_cached_album().map(|album| album.id.as_str()))
+            .collect::<Vec<_>>();
+
+        self.album_groups.regroup(&album_ids);
+    }
+
+    /// Returns the rows of a table of `tracks` drawn in `area`, where only the rows around the selected one are filled in.
+    ///
+    /// The info of the tracks on screen is fetched first, followed by the ones just off screen.
+    fn get_track_rows(&self, tracks: &[Arc<Track>], table_state: &TableState, area: Rect) -> Vec<Row<'static>> {
+        self.get_numbered_track_rows(tracks, |idx| (idx + 1).to_string(), table_state, area)
+    }
+
+    /// Returns the rows of a table of `tracks` like `get_track_rows`, numbering the track at each index with `get_number`.
+    fn get_numbered_track_rows(
+        &self,
+        tracks: &[Arc<Track>],
+        get_number: impl Fn(usize) -> String,
+        table_state: &TableState,
+        area: Rect,
+    ) -> Vec<Row<'static>> {
+        let unlocked_history = self.history.lock().unwrap();
+        let now = SystemTime::now();
+        let (_, constraints) = self.get_track_columns();
+        let widths = get_column_widths(&constraints, Self::TRACKS_COLUMN_SPACING, area.width);
+
+        tracks
+            .iter()
+            .enumerate()
+            .map(|(idx, track)| match Self::get_row_priority(idx, table_state, area.height) {
+                Some(priority) => self.get_track_row(get_number(idx), track, priority, &widths, &unlocked_history, now),
+                None => Row::default(),
+            })
+            .collect()
+    }
+
+    /// Returns the rows of My Collection - Tracks grouped by album, with a header above each album's tracks.
+    /// Like `get_track_rows`, only the rows around the selected one are filled in.
+    fn get_grouped_track_rows(&self, tracks: &[Arc<Track>], table_state: &TableState, area: Rect) -> Vec<Row<'static>> {
+        let unlocked_history = self.history.lock().unwrap();
+        let now = SystemTime::now();
+        let (_, constraints) = self.get_track_columns();
+        let widths = get_column_widths(&constraints, Self::TRACKS_COLUMN_SPACING, area.width);
+
+        self.album_groups.get_rows()
+            .iter()
+            .enumerate()
+            .map(|(row, grouped_row)| {
+                let Some(priority) = Self::get_row_priority(row, table_state, area.height) else {
+                    return Row::default();
+                };
+
+                match *grouped_row {
+                    GroupedRow::Album(group_idx) => self.get_album_header_row(group_idx, tracks, &widths),
+                    GroupedRow::Track(idx) => self.get_track_row((idx + 1).to_string(), &tracks[idx], priority, &widths, &unlocked_history, now),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the priority to fetch the info of the track at `row` with, or `None` if the row is too far from the selected one to be filled in.
+    fn get_row_priority(row: usize, table_state: &TableState, height: u16) -> Option<Priority> {
+        let current_position = table_state.selected().unwrap_or(0);
+        let num_rows = height as usize;
+        let render_window_amount = num_rows + 10;
+
+        // Rows from the last drawn offset are (roughly) the ones on screen.
+        let first_visible_row = table_state.offset();
+        let is_visible = row >= first_visible_row && row < first_visible_row + num_rows;
+
+        // Only render certain number of rows.
+        if row >= current_position.saturating_sub(render_window_amount) && row <= current_position.saturating_add(render_window_amount) {
+            Some(if is_visible { Priority::Visible } else { Priority::Margin })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the row of `track`, numbered `number` in its table, with each cell cut to fit its column in `widths`.
+    fn get_track_row(&self, number: String, track: &Arc<Track>, priority: Priority, widths: &[u16], unlocked_history: &PlayHistory, now: SystemTime) -> Row<'static> {
+        let cells = self.get_track_cells(track, priority);
+        let number = Line::from(number);
+        let mut row_cells = if self.config.layout.classical_mode {
+            vec![number, Line::from(cells.artist), cells.title, Line::from(cells.movement), Line::from(cells.album), Line::from(cells.time)]
+        } else {
+            vec![number, cells.title, Line::from(cells.artist), Line::from(cells.album), Line::from(cells.time)]
+        };
+        // Thumbnails are drawn over a blank first column after the frame.
+        if self.thumbnails.is_some() {
+            row_cells.insert(0, Line::default());
+        }
+
+        let play_record = unlocked_history.get(&track.id);
+        if self.config.layout.show_play_count {
+            row_cells.push(Line::from(self.config.locale.format_count(play_record.map_or(0, |record| record.play_count) as usize)));
+        }
+        if self.config.layout.show_last_played {
+            let last_played = play_record.map_or(String::from("-"), |record| {
+                format_time_ago(now.duration_since(record.last_played()).unwrap_or_default())
+            });
+            row_cells.push(Line::from(last_played));
+        }
+
+        let style = self.get_search_style(Pane::Collection, cells.style, &get_search_text(track));
+        Row::new(fit_cells(row_cells, widths))
+            .style(style)
+    }
+
+    /// Returns the header row of the album group at `group_idx`, with the album's title, year, and the total time of its tracks.
+    fn get_album_header_row(&self, group_idx: usize, tracks: &[Arc<Track>], widths: &[u16]) -> Row<'static> {
+        let Some(group) = self.album_groups.get_group(group_idx) else {
+            return Row::default();
+        };
+
+        let marker = if self.album_groups.is_collapsed(group_idx) { "▸" } else { "▾" };
+        let group_tracks = group.track_indices.iter().map(|idx| &tracks[*idx]);
+
+        let album = group_tracks.clone().find_map(|track| track.get_cached_album());
+        let title = match album {
+            Some(album) => album.attributes.title.clone(),
+            None => String::from(tr(Message::AlbumNotLoaded)),
+        };
+        let year = album.and_then(|album| album.attributes.get_release_year());
+
+        let total_time = group_tracks
+            .filter(|track| track.get_cached_attributes().is_some())
+            .filter_map(|track| track.get_duration().ok())
+            .sum::<Duration>();
+        let num_tracks = self.config.locale.format_count_of(group.track_indices.len(), tr(Message::TrackCountOne), tr(Message::TrackCountOther));
+        let details = match year {
+            Some(year) => format!("{year} · {num_tracks}"),
+            None => num_tracks,
+        };
+
+        let mut row_cells = vec![
+            Line::from(marker),
+            Line::from(title).bold(),
+            Line::from(details).dark_gray(),
+            Line::default(),
+            Line::from(self.config.locale.format_long_duration(total_time)),
+        ];
+        if self.config.layout.classical_mode {
+            row_cells.insert(3, Line::default());
+        }
+        if self.thumbnails.is_some() {
+            row_cells.insert(0, Line::default());
+        }
+
+        let style = self.get_search_style(Pane::Collection, Style::new(), album.map_or("", |album| &album.attributes.title));
+        Row::new(fit_cells(row_cells, widths))
+            .style(style)
+    }
+
+    /// Returns a table of tracks with `rows`, with the same columns as My Collection - Tracks,
+    /// followed by the play history columns that are turned on.
+    fn get_tracks_table<'a>(&self, rows: Vec<Row<'a>>) -> Table<'a> {
+        let (header, widths) = self.get_track_columns();
+
+        Table::default()
+            .header(
+                Row::new(header)
+                    .bottom_margin(1)
+            )
+            .widths(widths)
+            .column_spacing(Self::TRACKS_COLUMN_SPACING)
+            .rows(rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns the header and width of each column of a table of tracks.
+    fn get_track_columns(&self) -> (Vec<&'static str>, Vec<Constraint>) {
+        let (mut header, mut widths) = if self.config.layout.classical_mode {
+            (
+                vec!["#", tr(Message::ColumnComposer), tr(Message::ColumnWork), tr(Message::ColumnMovement), tr(Message::ColumnAlbum), tr(Message::ColumnTime)],
+                vec![Constraint::Max(6), Constraint::Min(10), Constraint::Min(10), Constraint::Min(10), Constraint::Min(10), Constraint::Max(9)],
+            )
+        } else {
+            (
+                vec!["#", tr(Message::ColumnTitle), tr(Message::ColumnArtist), tr(Message::ColumnAlbum), tr(Message::ColumnTime)],
+                vec![Constraint::Max(6), Constraint::Min(10), Constraint::Min(10), Constraint::Min(10), Constraint::Max(9)],
+            )
+        };
+
+        if self.thumbnails.is_some() {
+            header.insert(0, "");
+            widths.insert(0, Constraint::Length(Thumbnails::WIDTH));
+        }
+        if self.config.layout.show_play_count {
+            header.push(tr(Message::ColumnPlays));
+            widths.push(Constraint::Max(6));
+        }
+        if self.config.layout.show_last_played {
+            header.push(tr(Message::ColumnLastPlayed));
+            widths.push(Constraint::Max(11));
+        }
+
+        (header, widths)
+    }
+
+    /// Draws the user or playlist at the top of the browse stack in the main pane.
+    fn draw_browse_view(&mut self, f: &mut Frame, area: Rect) {
+        let Some(view) = self.browse_stack.last() else {
+            return;
+        };
+
+        let title = view.get_title();
+        let browse_block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.get_border_color(Pane::Collection))
+            .title(self.get_pane_title(Pane::Collection, &title).bold())
+            .title(self.get_route_tabs(area, &title))
+            .title_bottom(Line::from(self.keymap.get_hints(&self.get_main_contexts())).right_aligned())
+            .title_bottom(self.get_search_line(Pane::Collection));
+        f.render_widget(browse_block, area);
+
+        let inner_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+            ])
+            .vertical_margin(1)
+            .horizontal_margin(2)
+            .split(area)
+            [0];
+
+        let (table, table_area) = match &*view.get_state().lock().unwrap() {
+            BrowseState::Loading => {
+                f.render_widget(Paragraph::new(tr(Message::Loading)).dark_gray(), inner_area);
+                return;
+            },
+            BrowseState::Failed(e) => {
+                f.render_widget(Paragraph::new(e.as_str()).red(), inner_area);
+                return;
+            },
+            BrowseState::Loaded(BrowseContent::User { playlists, .. }) => {
+                if playlists.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::NoPublicPlaylists)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_playlists_table(playlists, inner_area), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::Playlist { playlist, tracks, .. }) => {
+                let invite_link = view.get_invite_link();
+
+                // Collaborative playlists have a line above their tracks, showing the invite link once one is created.
+                let table_area = if playlist.attributes.collaborative || invite_link.is_some() {
+                    let [collaboration_area, table_area] = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)])
+                        .areas(inner_area);
+
+                    let collaboration_line = match invite_link {
+                        Some(invite_link) => Line::from(vec![
+                            tr(Message::Collaborative).cyan(),
+                            format!("  {}: ", tr(Message::InviteLink)).dark_gray(),
+                            invite_link.into(),
+                        ]),
+                        None => Line::from(tr(Message::Collaborative).cyan()),
+                    };
+                    f.render_widget(collaboration_line, collaboration_area);
+
+                    table_area
+                } else {
+                    inner_area
+                };
+
+                if tracks.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::PlaylistEmpty)).dark_gray(), table_area);
+                    return;
+                }
+
+                (self.get_tracks_table(self.get_track_rows(tracks, &view.table_state, table_area)), table_area)
+            },
+            BrowseState::Loaded(BrowseContent::Rediscover { tracks }) => {
+                if tracks.is_empty() {
+                    let message = tr_with(Message::NothingToRediscover, &[
+                        ("plays", &Self::REDISCOVER_MIN_PLAY_COUNT.to_string()),
+                        ("days", &(Self::REDISCOVER_NOT_PLAYED_FOR.as_secs() / (24 * 60 * 60)).to_string()),
+                    ]);
+                    f.render_widget(Paragraph::new(message).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_tracks_table(self.get_track_rows(tracks, &view.table_state, inner_area)), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::Album { album, tracks, track_numbers }) => {
+                let [details_area, table_area] = Layout::vertical([Constraint::Length(3), Constraint::Fill(1)])
+                    .areas(inner_area);
+                f.render_widget(self.get_album_details(album, track_numbers), details_area);
+
+                if tracks.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::NoTracks)).dark_gray(), table_area);
+                    return;
+                }
+
+                // Tracks are numbered by disc too (e.g. 2-05) when there is more than one.
+                let is_multi_disc = track_numbers.iter().any(|number| number.volume > 1);
+                let get_number = |idx: usize| match track_numbers.get(idx) {
+                    Some(number) if is_multi_disc => format!("{}-{:02}", number.volume, number.track),
+                    Some(number) => number.track.to_string(),
+                    None => (idx + 1).to_string(),
+                };
+
+                (self.get_tracks_table(self.get_numbered_track_rows(tracks, get_number, &view.table_state, table_area)), table_area)
+            },
+            BrowseState::Loaded(BrowseContent::CollectionAlbums { albums }) => {
+                if albums.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::NoCollectionAlbums)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_albums_table(albums, inner_area), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::CollectionArtists { artists }) => {
+                if artists.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::NoCollectionArtists)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_artists_table(artists, inner_area), inner_area)
+            },
+            BrowseState::Loaded(content @ BrowseContent::Artist { tracks, albums, .. }) => {
+                let Some(table_area) = self.draw_browse_tabs(f, content, view.get_tab(), inner_area) else {
+                    return;
+                };
+
+                let table = match view.get_tab() {
+                    BrowseTab::Albums => self.get_albums_table(albums, table_area),
+                    _ => self.get_tracks_table(self.get_track_rows(tracks, &view.table_state, table_area)),
+                };
+
+                (table, table_area)
+            },
+            BrowseState::Loaded(BrowseContent::Page { items, .. }) => {
+                if items.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::PageEmpty)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_page_table(items, inner_area), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::Home { sections }) => {
+                if sections.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::HomeEmpty)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_home_table(sections, view, inner_area), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::Mix { tracks, .. }) => {
+                if tracks.is_empty() {
+                    f.render_widget(Paragraph::new(tr(Message::NoTracks)).dark_gray(), inner_area);
+                    return;
+                }
+
+                (self.get_tracks_table(self.get_track_rows(tracks, &view.table_state, inner_area)), inner_area)
+            },
+            BrowseState::Loaded(BrowseContent::Settings { settings }) => (self.get_settings_table(settings), inner_area),
+            BrowseState::Loaded(content @ BrowseContent::Search { tracks, albums, artists, playlists, .. }) => {
+                let Some(table_area) = self.draw_browse_tabs(f, content, view.get_tab(), inner_area) else {
+                    return;
+                };
+
+                let table = match view.get_tab() {
+                    BrowseTab::Tracks => self.get_tracks_table(self.get_track_rows(tracks, &view.table_state, table_area)),
+                    BrowseTab::Albums => self.get_albums_table(albums, table_area),
+                    BrowseTab::Artists => self.get_artists_table(artists, table_area),
+                    BrowseTab::Playlists => self.get_playlists_table(playlists, table_area),
+                };
+
+                (table, table_area)
+            },
+        };
+
+        if let Some(view) = self.browse_stack.last_mut() {
+            f.render_stateful_widget(table, table_area, &mut view.table_state);
+
+            if let Some(thumbnails) = &mut self.thumbnails && view.has_tracks() {
+                thumbnails.place_tracks(&view.get_tracks(), table_area, view.table_state.offset());
+            }
+        }
+    }
+
+    /// Draws the tabs of `content` at the top of `area`, with how many items each lists, with `tab` shown.
+    ///
+    /// Returns the area left below the tabs, or `None` if nothing is listed in `tab`, in which case that is drawn instead.
+    fn draw_browse_tabs(&self, f: &mut Frame, content: &BrowseContent, tab: BrowseTab, area: Rect) -> Option<Rect> {
+        let [tabs_area, table_area] = Layout::vertical([Constraint::Length(2), Constraint::Fill(1)])
+            .areas(area);
+
+        let tabs = content.get_tabs();
+        let names = tabs.iter().map(|tab| (tab.get_name(), content.get_tab_len(*tab))).collect();
+        let selected = tabs.iter().position(|shown| *shown == tab).unwrap_or(0);
+        f.render_widget(BrowseTabs::new(names, selected), tabs_area);
+
+        if content.get_tab_len(tab) == 0 {
+            f.render_widget(Paragraph::new(tr(Message::NothingFound)).dark_gray(), table_area);
+            return None;
+        }
+
+        Some(table_area)
+    }
+
+    /// Returns the lines above an album's tracks: its title, then its year, how many tracks and discs it has,
+    /// its total time, and the size of its cover art.
+    fn get_album_details(&self, album: &Album, track_numbers: &[TrackNumber]) -> Paragraph<'static> {
+        let mut title = Line::from(album.attributes.title.clone()).bold();
+        title.extend(media_tag_badges(&album.attributes.media_tags));
+
+        let num_tracks = match track_numbers.len() {
+            0 => album.attributes.number_of_items as usize,
+            len => len,
+        };
+        let num_discs = track_numbers.iter()
+            .map(|number| number.volume)
+            .max()
+            .unwrap_or(album.attributes.number_of_volumes) as usize;
+
+        let mut details = vec![];
+        if let Some(year) = album.attributes.get_release_year() {
+            details.push(year.to_string());
+        }
+        details.push(self.config.locale.format_count_of(num_tracks, tr(Message::TrackCountOne), tr(Message::TrackCountOther)));
+        if num_discs > 1 {
+            details.push(self.config.locale.format_count_of(num_discs, tr(Message::DiscCountOne), tr(Message::DiscCountOther)));
+        }
+        if let Ok(duration) = album.get_duration() && !duration.is_zero() {
+            details.push(self.config.locale.format_long_duration(*duration));
+        }
+        details.push(match album.cover_art.first() {
+            Some(cover) => tr_with(Message::CoverSize, &[("width", &cover.width.to_string()), ("height", &cover.height.to_string())]),
+            None => String::from(tr(Message::NoCoverArt)),
+        });
+
+        Paragraph::new(vec![title, Line::from(details.join(" · ")).dark_gray()])
+    }
+
+    /// Returns a table of `playlists` drawn in `area`, with how many tracks each has and who can see it.
+    fn get_playlists_table(&self, playlists: &[Arc<Playlist>], area: Rect) -> Table<'static> {
+        let constraints = [Constraint::Max(6), Constraint::Min(10), Constraint::Max(8), Constraint::Max(10)];
+        let widths = get_column_widths(&constraints, 3, area.width);
+
+        let playlist_rows: Vec<Row> = playlists
+            .iter()
+            .enumerate()
+            .map(|(idx, playlist)| {
+                let mut name = Line::from(playlist.attributes.name.clone());
+                if playlist.attributes.collaborative {
+                    name.push_span(format!(" {}", tr(Message::CollaborativeTag)).dark_gray());
+                }
+
+                let visibility = tr(if playlist.attributes.is_public() { Message::Public } else { Message::Unlisted });
+                let row_cells = vec![
+                    Line::from((idx + 1).to_string()),
+                    name,
+                    Line::from(self.config.locale.format_count(playlist.attributes.number_of_items as usize)),
+                    Line::from(visibility),
+                ];
+                Row::new(fit_cells(row_cells, &widths))
+                .style(self.get_search_style(Pane::Collection, Style::new(), &playlist.attributes.name))
+            })
+            .collect();
+
+        Table::default()
+            .header(
+                Row::new(["#", tr(Message::ColumnName), tr(Message::ColumnTracks), tr(Message::ColumnVisibility)])
+                    .bottom_margin(1)
+            )
+            .widths(constraints)
+            .column_spacing(3)
+            .rows(playlist_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns a table of `albums` drawn in `area`, with the year each was released and how many tracks it has.
+    fn get_albums_table(&self, albums: &[Arc<Album>], area: Rect) -> Table<'static> {
+        let constraints = [Constraint::Max(6), Constraint::Min(10), Constraint::Max(6), Constraint::Max(8)];
+        let widths = get_column_widths(&constraints, 3, area.width);
+
+        let album_rows: Vec<Row> = albums
+            .iter()
+            .enumerate()
+            .map(|(idx, album)| {
+                let mut title = Line::from(album.attributes.title.clone());
+                title.extend(media_tag_badges(&album.attributes.media_tags));
+
+                let row_cells = vec![
+                    Line::from((idx + 1).to_string()),
+                    title,
+                    Line::from(album.attributes.get_release_year().map_or(String::new(), |year| year.to_string())),
+                    Line::from(self.config.locale.format_count(album.attributes.number_of_items as usize)),
+                ];
+                Row::new(fit_cells(row_cells, &widths))
+                .style(self.get_search_style(Pane::Collection, Style::new(), &album.attributes.title))
+            })
+            .collect();
+
+        Table::default()
+            .header(
+                Row::new(["#", tr(Message::ColumnTitle), tr(Message::ColumnYear), tr(Message::ColumnTracks)])
+                    .bottom_margin(1)
+            )
+            .widths(constraints)
+            .column_spacing(3)
+            .rows(album_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns a table of `artists` drawn in `area`.
+    fn get_artists_table(&self, artists: &[Arc<Artist>], area: Rect) -> Table<'static> {
+        let constraints = [Constraint::Max(6), Constraint::Min(10)];
+        let widths = get_column_widths(&constraints, 3, area.width);
+
+        let artist_rows: Vec<Row> = artists
+            .iter()
+            .enumerate()
+            .map(|(idx, artist)| {
+                let row_cells = vec![
+                    Line::from((idx + 1).to_string()),
+                    Line::from(artist.attributes.name.clone()),
+                ];
+                Row::new(fit_cells(row_cells, &widths))
+                .style(self.get_search_style(Pane::Collection, Style::new(), &artist.attributes.name))
+            })
+            .collect();
+
+        Table::default()
+            .header(
+                Row::new(["#", tr(Message::ColumnName)])
+                    .bottom_margin(1)
+            )
+            .widths(constraints)
+            .column_spacing(3)
+            .rows(artist_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns a table of a page's `items` drawn in `area`, with what kind of item each is
+    /// and a detail about it (a track's artist, an album's year, or a playlist's number of tracks).
+    fn get_page_table(&self, items: &[PageItem], area: Rect) -> Table<'static> {
+        let constraints = [Constraint::Max(6), Constraint::Max(10), Constraint::Min(10), Constraint::Min(10)];
+        let widths = get_column_widths(&constraints, 3, area.width);
+
+        let item_rows: Vec<Row> = items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| {
+                let mut name = Line::from(item.get_name());
+                let detail = match item {
+                    PageItem::Track(track) => Line::from(track.get_cached_artist().map_or(String::new(), |artist| artist.attributes.name.clone())),
+                    PageItem::Album(album) => {
+                        name.extend(media_tag_badges(&album.attributes.media_tags));
+                        Line::from(album.attributes.get_release_year().map_or(String::new(), |year| year.to_string()))
+                    },
+                    PageItem::Playlist(playlist) => Line::from(self.config.locale.format_count_of(
+                        playlist.attributes.number_of_items as usize,
+                        tr(Message::TrackCountOne),
+                        tr(Message::TrackCountOther),
+                    )),
+                    PageItem::Mix(mix) => Line::from(mix.subtitle.clone()),
+                    PageItem::Artist(_) | PageItem::User(_) => Line::default(),
+                    PageItem::Failed { error, .. } => Line::from(error.clone()).red(),
+                };
+
+                let row_cells = vec![
+                    Line::from((idx + 1).to_string()),
+                    Line::from(item.get_kind_name()).dark_gray(),
+                    name,
+                    detail,
+                ];
+                Row::new(fit_cells(row_cells, &widths))
+                .style(self.get_search_style(Pane::Collection, Style::new(), &item.get_name()))
+            })
+            .collect();
+
+        Table::default()
+            .header(
+                Row::new(["#", tr(Message::ColumnType), tr(Message::ColumnName), ""])
+                    .bottom_margin(1)
+            )
+            .widths(constraints)
+            .column_spacing(3)
+            .rows(item_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns the table of the home page's `sections` in `view`, each with its title above its items side by side.
+    ///
+    /// The selected item of each section is kept in view, and highlighted in the selected section.
+    fn get_home_table(&self, sections: &[HomeSection], view: &BrowseView, area: Rect) -> Table<'static> {
+        /// Items are cut to this many columns, so that a long name doesn't push the rest out of view.
+        const ITEM_MAX_WIDTH: usize = 32;
+        /// The columns between items.
+        const ITEM_GAP: usize = 3;
+
+        let selected_row = view.table_state.selected();
+        // The highlight symbol is drawn before each row in screen reader mode.
+        let width = (area.width as usize).saturating_sub(self.get_row_highlight_symbol().len());
+
+        let section_rows: Vec<Row> = sections
+            .iter()
+            .enumerate()
+            .map(|(row, section)| {
+                let column = view.get_column(row);
+                let names: Vec<Line> = section.items
+                    .iter()
+                    .map(|item| truncate_line(Line::from(item.get_name()), ITEM_MAX_WIDTH))
+                    .collect();
+                let widths: Vec<usize> = names.iter().map(Line::width).collect();
+                let first = get_first_visible(&widths, column, ITEM_GAP, width);
+
+                let mut items = Line::default();
+                for (idx, name) in names.into_iter().enumerate().skip(first) {
+                    if idx > first {
+                        items.push_span(Span::from(" ".repeat(ITEM_GAP)));
+                    }
+                    let name = if selected_row == Some(row) && idx == column { name.reversed() } else { name };
+                    items.extend(name.spans);
+                }
+
+                let title = Line::from(format!("{} ({})", section.title, section.items.len())).bold();
+                let search_text = format!("{} {}", section.title, section.items.iter().map(PageItem::get_name).collect::<Vec<_>>().join(" "));
+                Row::new([Text::from(vec![title, truncate_line(items, width)])])
+                    .height(2)
+                    .bottom_margin(1)
+                    .style(self.get_search_style(Pane::Collection, Style::new(), &search_text))
+            })
+            .collect();
+
+        Table::default()
+            .widths([Constraint::Fill(1)])
+            .rows(section_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Returns the table of `settings`, by their names in the config file, with their values.
+    fn get_settings_table(&self, settings: &[(&'static str, Option<String>)]) -> Table<'static> {
+        let setting_rows: Vec<Row> = settings
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Some(value) => Line::from(value.clone()),
+                    None => Line::from(tr(Message::NotSet)).dark_gray(),
+                };
+
+                Row::new([Line::from(*name), value])
+                    .style(self.get_search_style(Pane::Collection, Style::new(), name))
+            })
+            .collect();
+
+        Table::default()
+            .header(
+                Row::new([tr(Message::ColumnSetting), tr(Message::ColumnValue)])
+                    .bottom_margin(1)
+            )
+            .widths([Constraint::Max(40), Constraint::Fill(1)])
+            .column_spacing(3)
+            .rows(setting_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol())
+    }
+
+    /// Draws the open menu.
+    fn draw_menu(&mut self, f: &mut Frame, area: Rect) {
+        let Some((title, items, placeholder)) = self.get_menu_contents() else {
+            return;
+        };
+        let footer = self.keymap.get_hints(&[Context::Menu]);
+
+        let list_menu = ListMenu::new(&title, items)
+            .placeholder(&placeholder)
+            .footer(footer);
+        if let Some(menu) = &mut self.menu {
+            f.render_stateful_widget(list_menu, area, menu.get_list_state_mut());
+        }
+    }
+
+    /// Returns the title and items of the open menu, and what to show instead while it has no items.
+    fn get_menu_contents(&self) -> Option<(String, Vec<String>, String)> {
+        let contents = match self.menu.as_ref()? {
+            Menu::Track(menu) => {
+                let title = menu.track.get_cached_attributes()
+                    .map_or(String::from(tr(Message::Track)), |attributes| attributes.get_full_title());
+                let items = menu.items.iter().map(|item| String::from(item.get_name())).collect();
+                (title, items, String::new())
+            },
+            Menu::AddToPlaylist(menu) => {
+                let (items, placeholder) = Self::get_add_to_playlist_items(&self.user, &self.playlist_index, menu);
+                (String::from(tr(Message::AddToPlaylist)), items, placeholder)
+            },
+            Menu::Blocklist(_) => {
+                let items = self.blocklist.lock().unwrap()
+                    .get_items()
+                    .into_iter()
+                    .map(|(_, name)| name)
+                    .collect();
+                (String::from(tr(Message::Blocklist)), items, String::from(tr(Message::NothingBlocked)))
+            },
+            Menu::ReplaceQueue(_) => {
+                let items = vec![String::from(tr(Message::ReplaceTheQueue)), String::from(tr(Message::Cancel))];
+                (String::from(tr(Message::QueueNotFinished)), items, String::new())
+            },
+            Menu::PreviousQueues(_) => {
+                let items = self.previous_queues
+                    .iter()
+                    .map(|previous_queue| {
+                        let mut playing_from = String::from(previous_queue.playing_from.as_deref().unwrap_or(tr(Message::Unknown)));
+                        if previous_queue.is_shuffle {
+                            playing_from.push_str(&format!(" {}", tr(Message::ShuffledTag)));
+                        }
+                        let count = self.config.locale.format_count(previous_queue.saved.get_tracks_left());
+                        tr_with(Message::PreviousQueue, &[("playing_from", &playing_from), ("count", &count)])
+                    })
+                    .collect();
+                (String::from(tr(Message::PreviousQueues)), items, String::from(tr(Message::NoPreviousQueues)))
+            },
+            Menu::Diagnostics(menu) => {
+                let items = menu.checks.lock().unwrap()
+                    .iter()
+                    .flatten()
+                    .map(|check| check.to_string())
+                    .collect();
+                (String::from(tr(Message::Diagnostics)), items, String::from(tr(Message::RunningChecks)))
+            },
+            Menu::SessionStats(_) => {
+                let mut items = self.player.lock().unwrap().get_session_stats().get_lines(self.config.locale);
+                items.extend(self.bandwidth_usage.lock().unwrap().get_lines(&self.config.bandwidth, SystemTime::now(), self.config.locale));
+                (String::from(tr(Message::Stats)), items, String::new())
+            },
+            Menu::Pages(_) => {
+                let items = self.config.pages.iter().map(|page| page.name.clone()).collect();
+                (String::from(tr(Message::Pages)), items, String::from(tr(Message::NoPages)))
+            },
+            Menu::Caches(_) => {
+                let items = CacheKind::ALL.iter().map(|kind| format!("{}: {}", kind.get_title(), self.get_cache_size(*kind))).collect();
+                (String::from(tr(Message::Caches)), items, String::new())
+            },
+            Menu::ScriptCommands(_) => {
+                let mut items: Vec<String> = CacheKind::ALL.iter().map(CacheKind::get_purge_command).collect();
+                items.extend(self.scripts.get_command_names());
+                (String::from(tr(Message::ScriptCommands)), items, String::from(tr(Message::NoScriptCommands)))
+            },
+            Menu::Script(menu) => (menu.title.clone(), menu.lines.clone(), String::new()),
+            Menu::Help(_) => (String::from(tr(Message::Help)), self.get_help_lines(), String::new()),
+        };
+
+        Some(contents)
+    }
+
+    /// Returns the lines of the help, listing the keys bound to each action under a heading for where it can be used.
+    fn get_help_lines(&self) -> Vec<String> {
+        let groups = self.keymap.get_bound_actions_by_context();
+        let keys_width = groups.iter()
+            .flat_map(|(_, actions)| actions)
+            .map(|action| Self::format_help_keys(self.keymap.get_keys(*action)).width())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![];
+        for (context, actions) in groups {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(String::from(context.get_name()));
+
+            for action in actions {
+                let keys = Self::format_help_keys(self.keymap.get_keys(action));
+                let padding = " ".repeat(keys_width - keys.width());
+                lines.push(format!("  {keys}{padding}   {}", action.get_name()));
+            }
+        }
+
+        lines
+    }
+
+    /// Returns `keys` as they are listed in the help, separated by commas.
+    fn format_help_keys(keys: &[KeySequence]) -> String {
+        keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Returns the names of `user`'s playlists to show in `menu`, marking the ones that `playlist_index` says
+    /// already contain its track, and what to show instead while there aren't any.
+    fn get_add_to_playlist_items(user: &User, playlist_index: &PlaylistIndex, menu: &AddToPlaylistMenu) -> (Vec<String>, String) {
+        let containing = playlist_index.get_playlists_containing(&menu.track.id);
+
+        let playlist_names: Vec<String> = user.get_cached_playlists()
+            .map(|playlists| {
+                playlists.iter()
+                    .map(|playlist| {
+                        let mut name = playlist.attributes.name.clone();
+                        if playlist.attributes.collaborative {
+                            name.push_str(&format!(" {}", tr(Message::CollaborativeTag)));
+                        }
+                        if containing.contains(&playlist.id) {
+                            name.push_str(&format!(" {}", tr(Message::AlreadyAddedTag)));
+                        }
+                        name
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let error = menu.error.lock().unwrap().clone();
+        let placeholder = match (&error, user.get_cached_playlists()) {
+            (Some(e), _) => e.clone(),
+            (None, Some(_)) => String::from(tr(Message::NoPlaylists)),
+            (None, None) => String::from(tr(Message::LoadingPlaylists)),
+        };
+
+        (playlist_names, placeholder)
+    }
+
+    /// Returns the names of the user's playlists that the track with id `track_id` is in, in the order of their playlists.
+    fn get_playlist_names_containing(&self, track_id: &str) -> Vec<String> {
+        let containing = self.playlist_index.get_playlists_containing(track_id);
+
+        self.user.get_cached_playlists()
+            .map(|playlists| {
+                playlists.iter()
+                    .filter(|playlist| containing.contains(&playlist.id))
+                    .map(|playlist| playlist.attributes.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the border color of `pane`, which is highlighted when it is focused.
+    fn get_border_color(&self, pane: Pane) -> Color {
+        if self.focus.is_focused(pane) { Color::Cyan } else { Color::DarkGray }
+    }
+
+    /// Returns `title` padded for the top of `pane`'s border.
+    ///
+    /// In screen reader mode, the focused pane's title is marked, since its border color can't be read out.
+    fn get_pane_title(&self, pane: Pane, title: &str) -> String {
+        if self.announcer.is_some() && self.focus.is_focused(pane) {
+            format!(" » {title} ")
+        } else {
+            format!(" {title} ")
+        }
+    }
+
+    /// Returns the names of the routes for the top right of the main pane, with the one shown highlighted,
+    /// or only the one shown if they don't all fit next to `title` in `area`.
+    fn get_route_tabs(&self, area: Rect, title: &str) -> Line<'static> {
+        let tab = |route: Route| {
+            let name = format!(" {} ", route.get_name());
+            if route == self.route { name.bold().reversed() } else { name.dark_gray() }
+        };
+
+        let tabs = Line::from(Route::ALL.map(tab).to_vec()).right_aligned();
+        // Borders and the spaces around the title take up 4 columns.
+        if tabs.width() + Line::from(title).width() + 4 <= area.width as usize {
+            tabs
+        } else {
+            Line::from(tab(self.route)).right_aligned()
+        }
+    }
+
+    /// Returns what is drawn before the selected row of a table, which in screen reader mode marks it without color.
+    fn get_row_highlight_symbol(&self) -> &'static str {
+        if self.announcer.is_some() { "> " } else { "" }
+    }
+
+    /// Draws the playing track's lyrics, following along with the track if they are timed.
+    fn draw_lyrics(&mut self, f: &mut Frame, area: Rect) {
+        let (current_track, position) = {
+            let unlocked_player = self.player.lock().unwrap();
+            (unlocked_player.get_current_track().cloned(), unlocked_player.get_position())
+        };
+        let state = current_track.as_ref().map(|track| self.lyrics.get(track, &self.tx));
+
+        let mut lyrics_block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Color::DarkGray)
+            .title(format!(" {} ", tr(Message::Lyrics)).bold());
+        // Tidal's lyrics come from other providers, who are credited under them.
+        if let Some(LyricsState::Loaded(lyrics)) = &state && let Some(provider) = &lyrics.provider {
+            lyrics_block = lyrics_block
+                .title_bottom(Line::from(format!(" {} ", tr_with(Message::LyricsProvider, &[("provider", provider)]))).dark_gray().right_aligned());
+        }
+        f.render_widget(lyrics_block, area);
+
+        let inner_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+            ])
+            .vertical_margin(1)
+            .horizontal_margin(2)
+            .split(area)
+            [0];
+
+        let message = match state {
+            Some(LyricsState::Loaded(lyrics)) => {
+                let view = LyricsView::new(&lyrics).current_line(lyrics.get_current_line(position));
+                f.render_widget(view, inner_area);
+                return;
+            },
+            Some(LyricsState::Loading) => tr(Message::LoadingLyrics),
+            Some(LyricsState::Missing) => tr(Message::NoLyrics),
+            None => tr(Message::NothingPlaying),
+        };
+        f.render_widget(Paragraph::new(message).dark_gray().centered(), inner_area);
+    }
+
+    /// Draws the queue, starting with the currently playing track.
+    fn draw_queue(&mut self, f: &mut Frame, area: Rect) {
+        let queue_block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.get_border_color(Pane::Queue))
+            .title(self.get_pane_title(Pane::Queue, tr(Message::Queue)).bold())
+            .title_bottom(Line::from(self.keymap.get_hints(&Pane::Queue.get_contexts())).right_aligned())
+            .title_bottom(self.get_search_line(Pane::Queue));
+        let queue_block = if self.route == Route::Queue {
+            queue_block.title(self.get_route_tabs(area, tr(Message::Queue)))
+        } else {
+            queue_block
+        };
+        f.render_widget(queue_block, area);
+
+        let inner_area = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+            ])
+            .vertical_margin(1)
+            .horizontal_margin(2)
+            .split(area)
+            [0];
+
+        let (queue_tracks, current_row) = self.get_queue_rows();
+        let has_current_track = self.player.lock().unwrap().get_current_track().is_some();
+
+        if queue_tracks.is_empty() {
+            f.render_widget(Paragraph::new(tr(Message::NothingQueued)).dark_gray(), inner_area);
+            return;
+        }
+
+        let constraints = [Constraint::Max(4), Constraint::Min(10), Constraint::Min(10), Constraint::Max(6)];
+        let widths = get_column_widths(&constraints, 2, inner_area.width);
+
+        let queue_rows: Vec<Row> = queue_tracks
+            .iter()
+            .enumerate()
+            .map(|(idx, track)| {
+                // Played tracks are numbered back from the current one, and upcoming tracks forward from it.
+                let (number, row_style) = if idx < current_row {
+                    (format!("-{}", current_row - idx), Style::new().dark_gray())
+                } else if idx == current_row && has_current_track {
+                    (String::from("▶"), Style::new().cyan().bold())
+                } else {
+                    ((idx - current_row + usize::from(!has_current_track)).to_string(), Style::new())
+                };
+
+                let cells = self.get_track_cells(track, Priority::Visible);
+                let style = self.get_search_style(Pane::Queue, cells.style.patch(row_style), &get_search_text(track));
+                let row_cells = if self.config.layout.classical_mode {
+                    vec![Line::from(number), Line::from(cells.artist), cells.title, Line::from(cells.time)]
+                } else {
+                    vec![Line::from(number), cells.title, Line::from(cells.artist), Line::from(cells.time)]
+                };
+                Row::new(fit_cells(row_cells, &widths))
+                    .style(style)
+            })
+            .collect();
+
+        let queue_table = Table::default()
+            .header(
+                Row::new(if self.config.layout.classical_mode {
+                    ["#", tr(Message::ColumnComposer), tr(Message::ColumnWork), tr(Message::ColumnTime)]
+                } else {
+                    ["#", tr(Message::ColumnTitle), tr(Message::ColumnArtist), tr(Message::ColumnTime)]
+                })
+                    .bottom_margin(1)
+            )
+            .widths(constraints)
+            .column_spacing(2)
+            .rows(queue_rows)
+            .row_highlight_style(self.get_row_highlight_style())
+            .highlight_symbol(self.get_row_highlight_symbol());
+
+        // Until a row is selected, keep the current track near the top, with the tracks played just before it above.
+        if self.queue_table_state.selected().is_none() {
+            *self.queue_table_state.offset_mut() = current_row.saturating_sub(Self::QUEUE_HISTORY_ROWS);
+        }
+
+        f.render_stateful_widget(queue_table, inner_area, &mut self.queue_table_state);
+    }
+
+    /// Returns the rows of the queue, which are the tracks played before the current one if they are shown,
+    /// the currently playing track, and the upcoming tracks.
+    fn get_queue_tracks(&self) -> Vec<Arc<Track>> {
+        self.get_queue_rows().0
+    }
+
+    /// Returns the rows of the queue (see `get_queue_tracks`), along with the row of the currently playing track,
+    /// or of the next track if nothing is playing.
+    fn get_queue_rows(&self) -> (Vec<Arc<Track>>, usize) {
+        let unlocked_player = self.player.lock().unwrap();
+
+        let history = unlocked_player.get_queue_history()
+            .iter()
+            .filter(|_| self.show_queue_history);
+        let current_row = history.clone().count();
+
+        let tracks = history
+            .chain(unlocked_player.get_current_track())
+            .chain(unlocked_player.get_queue())
+            .cloned()
+            .collect();
+
+        (tracks, current_row)
+    }
+
+    /// Returns the table cells of `track`, or a placeholder if its info hasn't been fetched yet.
+    ///
+    /// In that case, its info is fetched in the background with `priority` and the app is rerendered once it's done.
+    fn get_track_cells(&self, track: &Arc<Track>, priority: Priority) -> TrackCells {
+        let retry_key = self.keymap.get_keys(Action::RetryRow)
+            .first()
+            .map_or(String::from(tr(Message::TheRetryKey)), |key| key.to_string());
+
+        match TrackCells::from_track(track, &retry_key, self.config.layout.classical_mode) {
+            Some(mut cells) if !self.hydrator.is_loading(track) => {
+                let artist_id = track.get_cached_artist().map(|artist| artist.id.as_str());
+                if self.blocklist.lock().unwrap().is_blocked(&track.id, artist_id) {
+                    cells.title.push_span(" ⊘".dark_gray());
+                }
+                cells
+            },
+            _ => {
+                self.hydrator.request(track, priority);
+                TrackCells::loading(track)
+            },
+        }
+    }
+
+    /// Draws the now playing block.
+    fn draw_now_playing(&mut self, f: &mut Frame, area: Rect) {
+        let mut title = Line::from(self.get_pane_title(Pane::NowPlaying, tr(Message::NowPlaying)).bold());
+
+        if let Some(playing_from) = &self.playing_from && self.config.now_playing.playing_from_in_title {
+            title.push_span(format!("- {} ", playing_from));
+        }
+
+        let now_playing_block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.get_border_color(Pane::NowPlaying))
+            .title(title);
+        // The tier is shown in the header instead when it is enabled.
+        let now_playing_block = match self.user.get_cached_subscription().filter(|_| !self.config.layout.show_header) {
+            Some(subscription) => now_playing_block.title(Line::from(format!(" {} ", subscription.get_tier_name()).dark_gray()).right_aligned()),
+            None => now_playing_block,
+        };
+        // The count and keys of a sequence typed so far, while waiting for the rest of it.
+        let pending = self.keymap.get_count()
+            .map(|count| count.to_string())
+            .into_iter()
+            .chain(self.keymap.get_pending().iter().map(|key| key.to_string()))
+            .collect::<Vec<_>>();
+        let now_playing_block = if pending.is_empty() {
+            now_playing_block
+        } else {
+            now_playing_block.title_bottom(Line::from(format!(" {} … ", pending.join(" ")).cyan().bold()).right_aligned())
+        };
+        f.render_widget(now_playing_block, area);
+
+        // The cover takes up the left of the bar whenever it is shown, so that the columns don't move between tracks.
+        let columns_area = match &mut self.cover_art {
+            Some(cover_art) => {
+                let cover_area = CoverArt::get_area(area);
+                let cover_url = self.player.lock().unwrap()
+                    .get_current_track()
+                    .and_then(|track| track.get_cached_album())
+                    .and_then(|album| album.get_cover_art_url(CoverArt::COVER_SIZE).map(String::from));
+                if let Some(cover_url) = cover_url {
+                    cover_art.draw(f.buffer_mut(), cover_area, &cover_url, &self.tx);
+                }
+
+                let [_, columns_area] = Layout::horizontal([Constraint::Length(cover_area.width + 1), Constraint::Fill(1)])
+                    .areas(area);
+                columns_area
+            },
+            None => area,
+        };
+
+        let columns = &self.config.now_playing.columns;
+
+        let sections = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(columns.iter().map(|c| Constraint::Fill(c.width)))
+            .vertical_margin(2)
+            .horizontal_margin(2)
+            .spacing(1)
+            .split(columns_area);
+
+        let unlocked_player = self.player.lock().unwrap();
+
+        for (column, section) in columns.iter().zip(sections.iter()) {
+            let lines = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(column.elements.iter().map(|_| Constraint::Length(1)))
+                .split(*section);
+
+            for (element, line_area) in column.elements.iter().zip(lines.iter()) {
+                self.draw_now_playing_element(f, *line_area, *element, column.align, &unlocked_player);
+            }
+        }
+    }
+
+    /// Draws the OSD over the now playing block if it was recently shown, and hides it once it has expired.
+    fn draw_osd(&mut self, f: &mut Frame, area: Rect) {
+        let Some((content, shown_at)) = &self.osd else {
+            return;
+        };
+
+        let elapsed = shown_at.elapsed();
+        if elapsed >= Self::OSD_DURATION {
+            self.osd = None;
+            return;
+        }
+
+        let dimmed = !self.is_low_bandwidth && elapsed >= Self::OSD_DURATION - Self::OSD_FADE_DURATION;
+        f.render_widget(Osd::new(content).dimmed(dimmed), area);
+    }
+
+    /// Shows `content` in the OSD, replacing whatever it was showing.
+    fn show_osd(&mut self, content: OsdContent) {
+        if let Some(announcer) = &mut self.announcer {
+            match &content {
+                OsdContent::Volume(volume) => announcer.announce(Announcement::Volume, &format!("{volume}%")),
+                OsdContent::Seek { position, duration } => {
+                    announcer.announce(Announcement::Position, &format!("{} / {}", format_duration(*position), format_duration(*duration)));
+                },
+                OsdContent::Message(message) => announcer.announce(Announcement::Message, message),
+            }
+        }
+
+        self.osd = Some((content, Instant::now()));
+    }
+
+    /// Announces what changed since the last frame in screen reader mode:
+    /// the playing track, whether it is paused, the focused pane, and the selected row or menu item.
+    fn announce_changes(&mut self) {
+        if self.announcer.is_none() {
+            return;
+        }
+
+        let (track, state) = {
+            let unlocked_player = self.player.lock().unwrap();
+            let track = unlocked_player.get_current_track()
+                .map_or(String::from(tr(Message::NothingPlaying)), |track| describe_track(track));
+            (track, if unlocked_player.is_playing() { "playing" } else { "paused" })
+        };
+        let focus = self.get_pane_name(self.focus.get_focused());
+        // An open menu takes the keys that would otherwise move through the focused table.
+        let selection = match self.get_menu_contents() {
+            Some((title, items, placeholder)) => {
+                let text = match self.menu.as_ref().and_then(|menu| menu.get_list_state().selected()) {
+                    _ if items.is_empty() => format!("{title}: {placeholder}"),
+                    Some(idx) => format!("{title}: {} of {}: {}", idx + 1, items.len(), items.get(idx).map_or("", String::as_str)),
+                    None => title,
+                };
+                Some((Announcement::Menu, text))
+            },
+            None => self.get_selected_row_description().map(|text| (Announcement::Row, text)),
+        };
+
+        let Some(announcer) = &mut self.announcer else {
+            return;
+        };
+        announcer.announce_change(Announcement::Track, &track);
+        announcer.announce_change(Announcement::State, state);
+        announcer.announce_change(Announcement::Focus, &focus);
+        if let Some((kind, text)) = selection {
+            announcer.announce_change(kind, &text);
+        }
+    }
+
+    /// Returns the name of `pane`, as shown in its title.
+    fn get_pane_name(&self, pane: Pane) -> String {
+        match pane {
+            Pane::Collection => match self.browse_stack.last() {
+                Some(view) => view.get_title(),
+                None => String::from(tr(Message::MyCollectionTracks)),
+            },
+            Pane::Queue => String::from(tr(Message::Queue)),
+            Pane::NowPlaying => String::from(tr(Message::NowPlaying)),
+        }
+    }
+
+    /// Returns the position and text of the selected row in the focused table (e.g. `3 of 120: Airbag – Radiohead`),
+    /// or `None` if nothing is selected.
+    fn get_selected_row_description(&self) -> Option<String> {
+        let selected = match self.focus.get_focused() {
+            Pane::Collection => match self.browse_stack.last() {
+                Some(view) => view.table_state.selected(),
+                None => self.collection_tracks_table_state.selected(),
+            },
+            Pane::Queue => self.queue_table_state.selected(),
+            Pane::NowPlaying => None,
+        }?;
+
+        // Rows that aren't tracks (like album headers and playlists) are described by their search text.
+        let text = match self.get_selected_track().ok().flatten() {
+            Some(track) => describe_track(&track),
+            None => self.get_focused_row_texts().into_iter().nth(selected).unwrap_or_default(),
+        };
+
+        Some(format!("{} of {}: {text}", selected + 1, self.get_focused_table_len()))
+    }
+
+    /// Draws a single element of the now playing block.
+    fn draw_now_playing_element(&self, f: &mut Frame, area: Rect, element: NowPlayingElement, align: Align, player: &Player) {
+        // A track can only be played once its attributes are fetched, but its album or artist may be missing.
+        let current_track = player.get_current_track()
+            .filter(|t| t.get_cached_attributes().is_some());
+
+        let line = match element {
+            NowPlayingElement::Title => match current_track {
+                Some(track) => {
+                    let attributes = track.get_cached_attributes().unwrap();
+
+                    let mut spans = vec![attributes.title.clone().bold()];
+                    if let Some(version) = attributes.version.as_deref().filter(|v| !v.trim().is_empty()) {
+                        spans.push(format!(" ({})", version.trim()).dark_gray());
+                    }
+                    spans.extend(media_tag_badges(&attributes.media_tags));
+                    if self.is_favorite(&track.id) {
+                        spans.push(" ♥".red());
+                    }
+
+                    Line::default().spans(spans)
+                },
+                None => Line::from(tr(Message::NothingPlaying)).dark_gray(),
+            },
+            NowPlayingElement::Artist => match current_track.and_then(|t| t.get_cached_artist_credits()) {
+                Some(credits) => Line::from(credits.get_display_name()),
+                None => Line::default(),
+            },
+            NowPlayingElement::Album => match current_track.and_then(|t| t.get_cached_album()) {
+                Some(album) => {
+                    let mut spans = vec![album.attributes.title.clone().into()];
+                    if let Some(year) = album.attributes.get_release_year() {
+                        spans.push(format!(" ({})", year).dark_gray());
+                    }
+
+                    Line::default().spans(spans)
+                },
+                None => Line::default(),
+            },
+            NowPlayingElement::Controls => {
+                let queue_filters = player.get_queue_filters();
+                let shuffle_str = match (self.is_shuffle, queue_filters.exclude_explicit, queue_filters.excluded_genres.is_empty()) {
+                    (true, _, false) => tr(Message::ShuffleOnFiltered),
+                    (true, true, true) => tr(Message::ShuffleOnClean),
+                    (true, false, true) => tr(Message::ShuffleOn),
+                    (false, _, _) => tr(Message::ShuffleOff),
+                };
+                let playing_status_str = if player.is_playing() { "||" } else { "> " };
+
+                let mut spans = vec![
+                    format!("{shuffle_str}    ").dark_gray(),
+                    playing_status_str.into(),
+                    format!("    {}", tr(Message::RepeatOff)).dark_gray(),
+                ];
+                if player.is_taken_over() {
+                    spans.push(format!("    {}", tr(Message::PlayingOnAnotherDevice)).yellow());
+                }
+
+                Line::default().spans(spans)
+            },
+            NowPlayingElement::Progress => {
+                self.draw_progress(f, area, player);
+                return;
+            },
+            NowPlayingElement::Volume => Line::from(tr_with(Message::Volume, &[("volume", &player.get_volume().to_string())])),
+            NowPlayingElement::Quality => {
+                let quality = self.session.get_audio_quality();
+                let mut line = Line::from(tr_with(Message::Quality, &[("quality", &quality.to_string())]));
+
+                // Point out tracks that are streamed in a lower quality than the setting, since that happens silently.
+                if let Some(stream_quality) = player.get_stream_quality()
+                    && current_track.is_some()
+                    && stream_quality < quality
+                {
+                    line.push_span(format!(" {}", tr_with(Message::PlayingInQuality, &[("quality", &stream_quality.to_string())])).yellow());
+                }
+
+                line
+            },
+            NowPlayingElement::StreamQuality => match player.get_parsed_manifest() {
+                Some(parsed_manifest) if current_track.is_some() => {
+                    let mut line = Line::from(parsed_manifest.get_quality_string());
+                    if player.is_fully_buffered() {
+                        line.push_span(format!(" · {}", tr(Message::FullyBuffered)).dark_gray());
+                    }
+                    line
+                },
+                _ => Line::default(),
+            },
+            NowPlayingElement::PlayingFrom => match &self.playing_from {
+                Some(playing_from) => Line::from(tr_with(Message::PlayingFrom, &[("playing_from", playing_from)])),
+                None => Line::default(),
+            },
+            NowPlayingElement::UpNext => match player.get_next_track() {
+                Some(next_track) if next_track.has_info() || next_track.get_info_error().is_some() => {
+                    let title = next_track.get_cached_attributes()
+                        .map(|attributes| attributes.get_full_title())
+                        .unwrap_or(tr_with(Message::UnknownTrack, &[("id", &next_track.id)]));
+
+                    let next_str = match next_track.get_cached_artist_credits() {
+                        Some(credits) => format!("{} – {}", credits.get_display_name(), title),
+                        None => title,
+                    };
+
+                    Line::default().spans(vec![format!("{} ", tr(Message::UpNext)).dark_gray(), next_str.into()])
+                },
+                Some(_) => Line::from(format!("{} ...", tr(Message::UpNext))).dark_gray(),
+                None => Line::default(),
+            },
+            NowPlayingElement::Empty => return,
+        };
+
+        let line = match align {
+            Align::Left => line.left_aligned(),
+            Align::Center => line.centered(),
+            Align::Right => line.right_aligned(),
+        };
+
+        let width = area.width as usize;
+        let marquee_config = &self.config.now_playing.marquee;
+        let is_title = matches!(element, NowPlayingElement::Title | NowPlayingElement::Album);
+        // Scrolling would redraw the title many times a second, which is too much in low bandwidth mode,
+        // and would keep changing what a screen reader reads.
+        let line = if is_title && marquee_config.enabled && !self.is_low_bandwidth && self.announcer.is_none() && line.width() > width {
+            let marquee = Marquee {
+                speed: marquee_config.speed,
+                pause: Duration::from_millis(marquee_config.pause_ms),
+            };
+
+            // Titles scroll along with the track, so they stay put while paused.
+            if player.is_playing() && marquee.speed > 0.0 {
+                self.is_marquee_scrolling.set(true);
+            }
+            marquee.get_line(line, width, player.get_position())
+        } else {
+            truncate_line(line, width)
+        };
+
+        f.render_widget(line, area);
+    }
+
+    /// Draws the progress bar of the current track, along with its position and duration.
+    fn draw_progress(&self, f: &mut Frame, area: Rect, player: &Player) {
+        let progress_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Fill(1),
+                Constraint::Length(5),
+            ])
+            .spacing(1)
+            .split(area);
+
+        let mut progress_bar = ProgressBar::default()
+            .colors(Color::Cyan, Color::Gray, Color::DarkGray);
+
+        match player.get_current_track() {
+            Some(current_track) if current_track.get_cached_attributes().is_some() => {
+                let position = player.get_position();
+                let track_duration = current_track.get_duration().unwrap().clone();
+                let position_progress = (position.as_secs() as f64) / (track_duration.as_secs() as f64);
+
+                progress_bar = progress_bar
+                    .ratio(position_progress)
+                    .buffered_ratio(player.get_buffered_ratio());
+
+                f.render_widget(Line::from(format_duration(position)).right_aligned(), progress_layout[0]);
+                f.render_widget(Line::from(format_duration(track_duration)).left_aligned(), progress_layout[2]);
+            },
+            _ => {
+                f.render_widget(Line::from("0:00").right_aligned(), progress_layout[0]);
+                f.render_widget(Line::from("0:00").left_aligned(), progress_layout[2]);
+            },
+        }
+
+        f.render_widget(progress_bar, progress_layout[1]);
+    }
+
+    /// Handles user input events and updates application state accordingly.
+    fn handle_terminal_event(&mut self, event: Event) -> Result<()> {
+        match event {
+            // The prompt captures all keys (including ones bound to actions) while it is open.
+            // Releases are only reported by some terminals (e.g. with the kitty keyboard protocol), and nothing is bound to them.
+            // Repeats, from holding a key down, are handled like presses.
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Release => {},
+            Event::Key(key_event) if self.prompt.is_some() => {
+                self.handle_prompt_key(key_event);
+            },
+            // So does a search while it is being typed.
+            Event::Key(key_event) if self.search.as_ref().is_some_and(TableSearch::is_typing) => {
+                self.handle_search_key(key_event);
+            },
+            Event::Key(key_event) => {
+                if let Some((action, count)) = self.keymap.get_action(key_event, &self.get_active_contexts()) {
+                    self.handle_action(action, count)?;
+                }
+            },
+            // The terminal clears the screen when it is resized, taking the thumbnails with it.
+            Event::Resize(..) => {
+                if let Some(thumbnails) = &mut self.thumbnails {
+                    thumbnails.invalidate();
+                }
+                if let Some(cover_art) = &mut self.cover_art {
+                    cover_art.invalidate();
+                }
+            },
+            _ => {},
+        };
+        Ok(())
+    }
+
+    /// Passes `action` to the scripts, then does what it is bound to unless one of them handled it.
+    fn handle_action(&mut self, action: Action, count: Option<usize>) -> Result<()> {
+        let is_handled_by_script = self.scripts.on_action(action);
+        self.handle_script_requests()?;
+
+        if is_handled_by_script {
+            return Ok(());
+        }
+        self.run_action(action, count)
+    }
+
+    /// Does what `action` is bound to, `count` times for actions that move or skip (e.g. `25j` or `5]`).
+    ///
+    /// A count before going to the top or bottom goes to that row instead, like in vim.
+    fn run_action(&mut self, action: Action, count: Option<usize>) -> Result<()> {
+        let times = count.unwrap_or(1);
+
+        match action {
+            Action::Quit => self.exit(),
+
+            // Focus keybinds
+            Action::FocusNext => self.focus.focus_next(),
+            Action::FocusPrev => self.focus.focus_prev(),
+            Action::FocusLeft => self.focus.focus_direction(FocusDirection::Left),
+            Action::FocusDown => self.focus.focus_direction(FocusDirection::Down),
+            Action::FocusUp => self.focus.focus_direction(FocusDirection::Up),
+            Action::FocusRight => self.focus.focus_direction(FocusDirection::Right),
+            Action::ToggleQueue => self.toggle_queue(),
+            Action::ToggleLyrics => self.show_lyrics = !self.show_lyrics,
+            Action::ToggleQueueHistory => self.toggle_queue_history(),
+            Action::OpenLink => self.prompt = Some(Prompt::OpenLink(String::new())),
+            Action::ImportM3u => self.prompt = Some(Prompt::ImportM3u(String::new())),
+            Action::SearchTidal => self.prompt = Some(Prompt::Search(String::new())),
+            Action::OpenPreviousQueues => self.open_previous_queues(),
+            Action::ToggleOffline => self.toggle_offline(),
+            Action::OpenDiagnostics => self.open_diagnostics().map_err(|e| eyre!(format!("{e}")))?,
+            Action::OpenSessionStats => self.menu = Some(Menu::SessionStats(ListState::default())),
+            Action::OpenCaches => self.menu = Some(Menu::Caches(ListState::default())),
+            Action::OpenScriptCommands => self.menu = Some(Menu::ScriptCommands(ListState::default())),
+            Action::OpenHelp => self.menu = Some(Menu::Help(ListState::default())),
+            Action::OpenPages => self.menu = Some(Menu::Pages(ListState::default())),
+            Action::OpenMixes => self.open_my_mixes(),
+            Action::SwitchToSlot => self.switch_to_slot(count).map_err(|e| eyre!(format!("{e}")))?,
+            Action::OpenMyPlaylists => self.open_my_playlists(),
+            Action::SwitchCollectionView => self.switch_collection_view(),
+
+            // Route keybinds
+            Action::GoToHome => self.navigate(Route::Home),
+            Action::GoToTracks => self.navigate(Route::Tracks),
+            Action::GoToAlbums => self.navigate(Route::Albums),
+            Action::GoToArtists => self.navigate(Route::Artists),
+            Action::GoToPlaylists => self.navigate(Route::Playlists),
+            Action::GoToSearch => self.navigate(Route::Search),
+            Action::GoToQueue => self.navigate(Route::Queue),
+            Action::GoToSettings => self.navigate(Route::Settings),
+            Action::NextRoute => self.cycle_route(times as isize),
+            Action::PrevRoute => self.cycle_route(-(times as isize)),
+            Action::OpenBlocklist => self.open_blocklist(),
+            Action::OpenRediscover => self.open_rediscover().map_err(|e| eyre!(format!("{e}")))?,
+
+            // Table keybinds
+            Action::PrevRow => self.prev_row(times),
+            Action::NextRow => self.next_row(times),
+            Action::GoToTop | Action::GoToBottom if count.is_some() => self.go_to_row(times - 1),
+            Action::GoToTop => self.go_to_top(),
+            Action::GoToBottom => self.go_to_bottom(),
+            Action::OpenTrackInfo => self.open_info_popup().map_err(|e| eyre!(format!("{e}")))?,
+            Action::OpenAlbum => self.open_album().map_err(|e| eyre!(format!("{e}")))?,
+            Action::StartTrackRadio => self.start_radio(false).map_err(|e| eyre!(format!("{e}")))?,
+            Action::StartArtistRadio => self.start_radio(true).map_err(|e| eyre!(format!("{e}")))?,
+            Action::RetryRow => self.retry_row().map_err(|e| eyre!(format!("{e}")))?,
+            Action::AddToPlaylist => self.open_add_to_playlist_menu().map_err(|e| eyre!(format!("{e}")))?,
+            Action::OpenTrackMenu => self.open_track_menu().map_err(|e| eyre!(format!("{e}")))?,
+            Action::BlockTrack => self.block_track().map_err(|e| eyre!(format!("{e}")))?,
+            Action::BlockArtist => self.block_artist().map_err(|e| eyre!(format!("{e}")))?,
+            Action::Search => self.start_search(),
+            Action::NextMatch => (0..times).for_each(|_| self.select_match(SearchDirection::Forward)),
+            Action::PrevMatch => (0..times).for_each(|_| self.select_match(SearchDirection::Backward)),
+
+            // My Collection - Tracks keybinds
+            Action::GoToCurrentlyPlaying => self.go_to_currently_playing().map_err(|e| eyre!(format!("{e}")))?,
+            Action::PlayAll => self.play_all().map_err(|e| eyre!(format!("{e}")))?,
+            Action::ShuffleAll => self.shuffle_all().map_err(|e| eyre!(format!("{e}")))?,
+            Action::Replay => self.replay().map_err(|e| eyre!(format!("{e}")))?,
+            Action::ExportM3u => self.export_m3u().map_err(|e| eyre!(format!("{e}")))?,
+            Action::ToggleAlbumGrouping => self.toggle_album_grouping(),
+            Action::ToggleAlbumCollapsed => self.toggle_album_collapsed(),
+            Action::PlayAlbum => self.play_album().map_err(|e| eyre!(format!("{e}")))?,
+            Action::CycleSort => self.change_sort(false),
+            Action::ReverseSort => self.change_sort(true),
+
+            // Browse keybinds
+            Action::OpenSelected => self.open_selected().map_err(|e| eyre!(format!("{e}")))?,
+            Action::GoBack => {
+                // The first view of a route stays open, since there would be nothing to show without it.
+                if self.browse_stack.len() > usize::from(self.route.has_root_view()) {
+                    self.browse_stack.pop();
+                }
+            },
+            Action::CreateInviteLink => {
+                if let Some(view) = self.browse_stack.last() {
+                    view.create_invite_link(self.tx.clone());
+                }
+            },
+            Action::MoveTrackUp => self.move_selected_track(-(times as isize)),
+            Action::MoveTrackDown => self.move_selected_track(times as isize),
+            Action::QueueAlbum => self.queue_album().map_err(|e| eyre!(format!("{e}")))?,
+            Action::PlayItem => self.play_page_item(),
+            Action::PrevHomeItem => self.move_home_column(-(times as isize)),
+            Action::NextHomeItem => self.move_home_column(times as isize),
+
+            // Search result keybinds
+            Action::NextBrowseTab => self.switch_tab(times as isize),
+            Action::PrevBrowseTab => self.switch_tab(-(times as isize)),
+
+            // Track info popup keybinds
+            Action::CloseTrackInfo => self.close_info_popup(),
+
+            // Menu keybinds
+            Action::MenuUp => (0..times).for_each(|_| self.select_prev_menu_item()),
+            Action::MenuDown => (0..times).for_each(|_| self.select_next_menu_item()),
+            Action::MenuSelect => self.select_menu_item().map_err(|e| eyre!(format!("{e}")))?,
+            Action::MenuClose => self.menu = None,
+
+            // Player keybinds
+            Action::VolumeDown => self.volume_down(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::VolumeUp => self.volume_up(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::TogglePlayPause => self.toggle_play_pause().map_err(|e| eyre!(format!("{e}")))?,
+            Action::ResumeHere => self.resume_here().map_err(|e| eyre!(format!("{e}")))?,
+            Action::PreviousTrack => self.previous_track(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::NextTrack => self.next_track(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::SeekBackward => self.seek_backward(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::SeekForward => self.seek_forward(times).map_err(|e| eyre!(format!("{e}")))?,
+            Action::CycleAudioQuality => self.cycle_audio_quality().map_err(|e| eyre!(format!("{e}")))?,
+            Action::ToggleExplicitFilter => self.toggle_explicit_filter(),
+            Action::ToggleGenreFilter => self.toggle_genre_filter(),
+            Action::ToggleFavorite => self.toggle_favorite(),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the keymap contexts that currently respond to keys, in priority order.
+    fn get_active_contexts(&self) -> Vec<Context> {
+        // Popups capture all input until they are closed.
+        if self.menu.is_some() {
+            return vec![Context::Menu, Context::App];
+        }
+        if self.info_popup_track.is_some() {
+            return vec![Context::TrackInfo, Context::App];
+        }
+
+        let mut contexts = match self.focus.get_focused() {
+            Pane::Collection => self.get_main_contexts(),
+            pane => pane.get_contexts(),
+        };
+        contexts.extend([Context::Layout, Context::Player, Context::App]);
+        contexts
+    }
+
+    /// Returns the keymap contexts of the main pane, which depend on what it is showing.
+    fn get_main_contexts(&self) -> Vec<Context> {
+        match self.browse_stack.last() {
+            Some(view) if view.is_playlist() => vec![Context::Browse, Context::Playlist, Context::Collection, Context::Table],
+            Some(view) if view.is_album() => vec![Context::Browse, Context::Album, Context::Collection, Context::Table],
+            Some(view) if view.is_home() => vec![Context::Browse, Context::Home, Context::Page, Context::Table],
+            Some(view) if view.is_page() => vec![Context::Browse, Context::Page, Context::Table],
+            Some(view) if !view.get_tabs().is_empty() && view.has_tracks() => vec![Context::Tabbed, Context::Browse, Context::Collection, Context::Table],
+            Some(view) if !view.get_tabs().is_empty() => vec![Context::Tabbed, Context::Browse, Context::Table],
+            Some(view) if view.has_tracks() => vec![Context::Browse, Context::Collection, Context::Table],
+            Some(_) => vec![Context::Browse, Context::Table],
+            None => Pane::Collection.get_contexts(),
+        }
+    }
+
+    /// Returns the search of the table in `pane`, if the last search was in it.
+    fn get_search_in(&self, pane: Pane) -> Option<&TableSearch> {
+        self.search.as_ref().filter(|search| search.get_pane() == pane)
+    }
+
+    /// Returns `style` highlighted if `text` matches the search of the table in `pane`.
+    fn get_search_style(&self, pane: Pane, style: Style, text: &str) -> Style {
+        match self.get_search_in(pane) {
+            // Matches are also underlined in screen reader mode, so that they don't stand out by color alone.
+            Some(search) if search.is_match(text) && self.announcer.is_some() => style.yellow().underlined(),
+            Some(search) if search.is_match(text) => style.yellow(),
+            _ => style,
+        }
+    }
+
+    /// Returns the search of the table in `pane` as it is typed, for the bottom left of the pane's border.
+    fn get_search_line(&self, pane: Pane) -> Line<'static> {
+        let Some(search) = self.get_search_in(pane) else {
+            return Line::default();
+        };
+
+        let mut line = Line::from(format!(" /{}", search.get_query()).yellow());
+        if search.is_typing() {
+            line.push_span("█".yellow());
+        }
+        line.push_span(" ");
+        line
+    }
+
+    /// Edits the prompt's input, or submits it when Enter is pressed.
+    fn handle_prompt_key(&mut self, key_event: KeyEvent) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => match self.prompt.take() {
+                Some(Prompt::OpenLink(input)) => self.open_link(&input),
+                Some(Prompt::ImportM3u(input)) => self.import_m3u(&input),
+                Some(Prompt::Search(input)) => self.search_tidal(&input),
+                None => {},
+            },
+            KeyCode::Backspace => {
+                prompt.get_input_mut().pop();
+            },
+            KeyCode::Char(c) => prompt.get_input_mut().push(c),
+            _ => {},
+        }
+    }
+
+    /// Opens the user or playlist that `input` links to in the main pane.
+    fn open_link(&mut self, input: &str) {
+        let view = match TidalLink::parse(input) {
+            Some(TidalLink::User(id)) => BrowseView::open_user(Arc::clone(&self.session), id, self.tx.clone()),
+            Some(TidalLink::Playlist(id)) => BrowseView::open_playlist(Arc::clone(&self.session), id, self.tx.clone()),
+            Some(TidalLink::Album(id)) => BrowseView::open_album(Arc::clone(&self.session), id, self.tx.clone()),
+            Some(TidalLink::Artist(id)) => BrowseView::open_artist(Arc::clone(&self.session), id, self.tx.clone()),
+            Some(TidalLink::Track(_)) => {
+                self.queue_link(input);
+                return;
+            },
+            None => {
+                self.show_osd(OsdContent::Message(String::from(tr(Message::NotUserOrPlaylistLink))));
+                return;
+            },
+        };
+
+        self.browse_stack.push(view);
+        self.focus.set_focused(Pane::Collection);
+    }
+
+    /// Adds the track or playlist that `input` links to to the end of the queue in the background.
+    fn queue_link(&mut self, input: &str) {
+        let session = Arc::clone(&self.session);
+        let load_tracks = match TidalLink::parse(input) {
+            Some(TidalLink::Track(id)) => Box::new(move || {
+                Ok(vec![Arc::new(Track::new(session, id)?)])
+            }) as Box<dyn FnOnce() -> Result<Vec<Arc<Track>>, String> + Send>,
+            Some(TidalLink::Playlist(id)) => Box::new(move || {
+                Ok(Playlist::new(session, id)?.get_tracks()?.iter().cloned().map(Arc::new).collect())
+            }),
+            Some(TidalLink::Album(id)) => Box::new(move || {
+                Ok(Album::new(session, id)?.get_tracks()?.iter().cloned().map(Arc::new).collect())
+            }),
+            Some(TidalLink::User(_) | TidalLink::Artist(_)) | None => {
+                self.show_osd(OsdContent::Message(String::from(tr(Message::NotTrackOrPlaylistLink))));
+                return;
+            },
+        };
+
+        let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = load_tracks().and_then(|tracks| {
+                let count = tracks.len();
+                player_clone.lock()
+                    .map_err(|e| format!("{e:#?}"))?
+                    .add_to_queue(tracks)
+                    .map_err(|e| e.to_string())?;
+                Ok(count)
+            });
+
+            let message = match result {
+                Ok(1) => String::from(tr(Message::AddedTrackToQueue)),
+                Ok(count) => tr_with(Message::AddedTracksToQueue, &[("count", &count.to_string())]),
+                Err(e) => tr_with(Message::AddToQueueFailed, &[("error", &e)]),
+            };
+            let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+
+    /// Writes the main pane's tracks to an M3U playlist of Tidal links in the exports folder of the config directory.
+    fn export_m3u(&mut self) -> Result<(), Box<dyn Error>> {
+        let tracks = self.get_main_tracks();
+        if tracks.is_empty() {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::NothingToExport))));
+            return Ok(());
+        }
+
+        // Only use info that has already been fetched, so that exporting a large collection doesn't take ages.
+        let entries: Vec<M3uEntry> = tracks
+            .iter()
+            .map(|track| {
+                let title = track.get_cached_attributes().map(|attributes| match track.get_cached_artist() {
+                    Some(artist) => format!("{} - {}", artist.attributes.name, attributes.get_full_title()),
+                    None => attributes.get_full_title(),
+                });
+
+                M3uEntry {
+                    track_id: track.id.clone(),
+                    duration: title.as_ref().and_then(|_| track.get_duration().ok().copied()),
+                    title,
+                }
+            })
+            .collect();
+
+        let exports_path = Path::new(&get_config_path()?).join("exports");
+        fs::create_dir_all(&exports_path)?;
+
+        let file_name: String = self.get_main_playing_from()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || " -_()".contains(c) { c } else { '_' })
+            .collect();
+        let export_path = exports_path.join(format!("{}.m3u8", file_name.trim()));
+        fs::write(&export_path, m3u::to_m3u(&entries))?;
+
+        let message = tr_with(Message::Exported, &[
+            ("count", &entries.len().to_string()),
+            ("path", &export_path.display().to_string()),
+        ]);
+        self.show_osd(OsdContent::Message(message));
+
+        Ok(())
+    }
+
+    /// Adds every Tidal track linked to in the M3U playlist at `path` to the user's collection in the background.
+    fn import_m3u(&mut self, path: &str) {
+        let path = match path.trim().strip_prefix("~/") {
+            Some(rest) => match env::var("HOME") {
+                Ok(home) => Path::new(&home).join(rest),
+                Err(_) => PathBuf::from(path.trim()),
+            },
+            None => PathBuf::from(path.trim()),
+        };
+
+        let is_offline = self.is_offline();
+        let pending_writes_clone = self.pending_writes.clone();
+        let session_clone = Arc::clone(&self.session);
+        let user_clone = Arc::clone(&self.user);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            // Returns how many tracks were found, and whether they were kept to be added once Tidal can be reached.
+            let result = fs::read_to_string(&path)
+                .map_err(|e| format!("Unable to read {}: {e}", path.display()))
+                .and_then(|m3u| {
+                    let track_ids = m3u::parse_track_ids(&m3u);
+                    let count = track_ids.len();
+                    if count == 0 {
+                        return Ok((0, false));
+                    }
+
+                    if !is_offline {
+                        match user_clone.add_favorite_tracks(&track_ids) {
+                            Ok(()) => return Ok((count, false)),
+                            Err(_) if !session_clone.is_online() => {},
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    pending_writes_clone.push(PendingWrite::AddFavoriteTracks { track_ids })?;
+                    Ok((count, true))
+                });
+
+            let message = match result {
+                Ok((0, _)) => String::from(tr(Message::NoTrackLinks)),
+                Ok((count, true)) => tr_with(Message::ImportDeferred, &[("count", &count.to_string())]),
+                Ok((count, false)) => tr_with(Message::Imported, &[("count", &count.to_string())]),
+                Err(e) => tr_with(Message::ImportFailed, &[("error", &e.to_string())]),
+            };
+            let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+
+    /// Opens the user's own playlists in the main pane.
+    fn open_my_playlists(&mut self) {
+        self.navigate(Route::Playlists);
+    }
+
+    /// Switches the main pane to the next route of My Collection (Tracks, then Albums, then Artists).
+    fn switch_collection_view(&mut self) {
+        let next_route = match self.route {
+            Route::Albums => Route::Artists,
+            Route::Artists => Route::Tracks,
+            _ => Route::Albums,
+        };
+
+        self.navigate(next_route);
+    }
+
+    /// Shows `route` in the main pane, with the views that were open in it when it was last shown.
+    ///
+    /// Going to the route that is already shown closes what was opened in it, back to its first view.
+    /// Going to search before anything has been searched for asks what to search for instead.
+    fn navigate(&mut self, route: Route) {
+        if route == self.route {
+            self.browse_stack.truncate(usize::from(route.has_root_view()));
+        } else if route == Route::Search && !self.has_searched() {
+            self.prompt = Some(Prompt::Search(String::new()));
+            return;
+        } else {
+            self.switch_route(route);
+        }
+
+        let pane = if route == Route::Queue { Pane::Queue } else { Pane::Collection };
+        self.focus.set_focused(pane);
+    }
+
+    /// Shows the route `offset` routes after (or before, if negative) the one shown.
+    ///
+    /// Search is skipped until something has been searched for, instead of asking what to search for.
+    fn cycle_route(&mut self, offset: isize) {
+        let mut route = self.route.cycle(offset);
+        if route == Route::Search && !self.has_searched() {
+            route = route.cycle(offset.signum());
+        }
+
+        self.navigate(route);
+    }
+
+    /// Returns true if the search route has any searches open.
+    fn has_searched(&self) -> bool {
+        match self.route {
+            Route::Search => !self.browse_stack.is_empty(),
+            _ => self.route_stacks.get(&Route::Search).is_some_and(|views| !views.is_empty()),
+        }
+    }
+
+    /// Swaps the views of the route shown for those of `route`, opening its first view if it has none open.
+    fn switch_route(&mut self, route: Route) {
+        let views = self.route_stacks.remove(&route).unwrap_or_default();
+        let previous_views = std::mem::replace(&mut self.browse_stack, views);
+        self.route_stacks.insert(self.route, previous_views);
+        self.route = route;
+
+        if self.browse_stack.is_empty() {
+            let user = Arc::clone(&self.user);
+            let tx = self.tx.clone();
+            let root_view = match route {
+                Route::Home => Some(BrowseView::home(Arc::clone(&self.session), tx)),
+                Route::Albums => Some(BrowseView::collection_albums(user, tx)),
+                Route::Artists => Some(BrowseView::collection_artists(user, tx)),
+                Route::Playlists => Some(BrowseView::open_user(Arc::clone(&self.session), user.id.clone(), tx)),
+                Route::Settings => Some(BrowseView::settings(self.config.get_settings())),
+                Route::Tracks | Route::Search | Route::Queue => None,
+            };
+            self.browse_stack.extend(root_view);
+        }
+    }
+
+    /// Opens the tracks that used to be played a lot but haven't been played recently in the main pane.
+    fn open_rediscover(&mut self) -> Result<(), Box<dyn Error>> {
+        let track_ids = self.history.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .get_rediscoveries(SystemTime::now(), Self::REDISCOVER_MIN_PLAY_COUNT, Self::REDISCOVER_NOT_PLAYED_FOR);
+
+        let tracks = track_ids
+            .into_iter()
+            .map(|id| Track::new(Arc::clone(&self.session), id).map(Arc::new))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        self.browse_stack.push(BrowseView::rediscover(tracks));
+        self.focus.set_focused(Pane::Collection);
+
+        Ok(())
+    }
+
+    /// Opens the selected playlist of the user being browsed, the selected album or artist of the collection,
+    /// the selected album of an artist, or the selected result of a search.
+    ///
+    /// The menu of the selected track is opened instead if a track is selected.
+    fn open_selected(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(view) = self.browse_stack.last() else {
+            return Ok(());
+        };
+        let Some(index) = view.table_state.selected() else {
+            return Ok(());
+        };
+
+        let tx = self.tx.clone();
+        let opened_view = match view.get_content() {
+            Some(BrowseContent::User { playlists, .. }) => playlists.get(index).cloned()
+                .map(|playlist| BrowseView::from_playlist(playlist, tx)),
+            Some(BrowseContent::CollectionAlbums { albums }) => albums.get(index).cloned()
+                .map(|album| BrowseView::from_album(album, tx)),
+            Some(BrowseContent::CollectionArtists { artists }) => artists.get(index).cloned()
+                .map(|artist| BrowseView::from_artist(artist, tx)),
+            Some(BrowseContent::Artist { albums, .. }) if view.get_tab() == BrowseTab::Albums => albums.get(index).cloned()
+                .map(|album| BrowseView::from_album(album, tx)),
+            Some(BrowseContent::Page { .. } | BrowseContent::Home { .. }) => match view.get_selected_item() {
+                Some(PageItem::Track(track)) => {
+                    self.open_track_menu_for(track, None);
+                    return Ok(());
+                },
+                Some(item) => item.open(tx),
+                None => None,
+            },
+            Some(BrowseContent::Search { albums, artists, playlists, .. }) => match view.get_tab() {
+                BrowseTab::Tracks => return self.open_track_menu(),
+                BrowseTab::Albums => albums.get(index).cloned().map(|album| BrowseView::from_album(album, tx)),
+                BrowseTab::Artists => artists.get(index).cloned().map(|artist| BrowseView::from_artist(artist, tx)),
+                BrowseTab::Playlists => playlists.get(index).cloned().map(|playlist| BrowseView::from_playlist(playlist, tx)),
+            },
+            // The rest list tracks, like an album's, an artist's most popular ones, or a playlist's.
+            _ => return self.open_track_menu(),
+        };
+
+        if let Some(opened_view) = opened_view {
+            self.browse_stack.push(opened_view);
+        }
+
+        Ok(())
+    }
+
+    /// Opens `page` in the main pane.
+    fn open_page(&mut self, page: PageConfig) {
+        self.browse_stack.push(BrowseView::open_page(Arc::clone(&self.session), page, self.tx.clone()));
+        self.focus.set_focused(Pane::Collection);
+    }
+
+    /// Swaps the queue to what quick slot number `slot` plays, or to the next assigned slot after the current one
+    /// if no slot is given.
+    ///
+    /// The collection's tracks are played right away, while a playlist or album's are fetched in the background first.
+    fn switch_to_slot(&mut self, slot: Option<usize>) -> Result<(), Box<dyn Error>> {
+        let mut assigned: Vec<usize> = self.config.slots
+            .keys()
+            .filter_map(|key| key.parse().ok())
+            .collect();
+        assigned.sort_unstable();
+
+        let slot = match slot {
+            Some(slot) => slot,
+            None => match assigned.iter().find(|&&assigned_slot| Some(assigned_slot) > self.current_slot).or(assigned.first()) {
+                Some(&slot) => slot,
+                None => {
+                    self.show_osd(OsdContent::Message(String::from(tr(Message::NoSlots))));
+                    return Ok(());
+                },
+            },
+        };
+        let Some(slot_config) = self.config.slots.get(&slot.to_string()).cloned() else {
+            self.show_osd(OsdContent::Message(tr_with(Message::SlotEmpty, &[("slot", &slot.to_string())])));
+            return Ok(());
+        };
+
+        let session = Arc::clone(&self.session);
+        let load_tracks = match slot_config {
+            SlotConfig::Collection => {
+                let tracks = self.collection_tracks.lock().unwrap().clone();
+                if tracks.is_empty() {
+                    self.show_osd(OsdContent::Message(String::from(tr(Message::NothingToPlay))));
+                    return Ok(());
+                }
+
+                return self.confirm_replace_queue(QueueReplacement::Tracks {
+                    tracks,
+                    playing_from: String::from(tr(Message::Tracks)),
+                    shuffle: false,
+                    is_auto_generated: false,
+                    slot: Some(slot),
+                });
+            },
+            SlotConfig::Link(TidalLink::Playlist(id)) => Box::new(move || {
+                let playlist = Playlist::new(session, id)?;
+                let tracks = playlist.get_tracks()?.iter().cloned().map(Arc::new).collect();
+                Ok((tracks, playlist.attributes.name.clone()))
+            }) as Box<dyn FnOnce() -> Result<(Vec<Arc<Track>>, String), String> + Send>,
+            SlotConfig::Link(TidalLink::Album(id)) => Box::new(move || {
+                let album = Album::new(session, id)?;
+                let tracks = album.get_tracks()?.iter().cloned().map(Arc::new).collect();
+                Ok((tracks, album.attributes.title.clone()))
+            }),
+            // Slots can only be parsed from playlist and album links.
+            SlotConfig::Link(_) => return Ok(()),
+        };
+
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let event = match load_tracks() {
+                Ok((tracks, playing_from)) => AppEvent::PlaySlot(slot, tracks, playing_from),
+                Err(e) => AppEvent::ShowOsd(OsdContent::Message(tr_with(Message::PlayItemFailed, &[("error", &e)]))),
+            };
+            let _ = tx_clone.blocking_send(event);
+        });
+
+        Ok(())
+    }
+
+    /// Plays the selected item of the page open in the main pane, once its tracks have been fetched in the background.
+    fn play_page_item(&mut self) {
+        let Some(item) = self.browse_stack.last().and_then(BrowseView::get_selected_item) else {
+            return;
+        };
+
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let event = match item.get_tracks() {
+                Ok(tracks) => AppEvent::PlayTracks(tracks, item.get_name()),
+                Err(e) => AppEvent::ShowOsd(OsdContent::Message(tr_with(Message::PlayItemFailed, &[("error", &e)]))),
+            };
+            let _ = tx_clone.blocking_send(event);
+        });
+    }
+
+    /// Selects the item `offset` items after (or before, if negative) the selected one in the selected section
+    /// of the home page.
+    fn move_home_column(&mut self, offset: isize) {
+        if let Some(view) = self.browse_stack.last_mut() {
+            view.move_column(offset);
+        }
+    }
+
+    /// Opens the album of the selected track in the main pane.
+    fn open_album(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(track) = self.get_selected_track()? else {
+            return Ok(());
+        };
+
+        self.browse_stack.push(BrowseView::from_track_album(track, self.tx.clone()));
+        self.focus.set_focused(Pane::Collection);
+
+        Ok(())
+    }
+
+    /// Replaces the queue with the radio of the selected track, or of its artist if `from_artist` is true,
+    /// once its tracks have been fetched in the background.
+    ///
+    /// A track's radio starts with the track itself. Blocked tracks and ones excluded by the queue filters are skipped,
+    /// as they are while shuffling.
+    fn start_radio(&mut self, from_artist: bool) -> Result<(), Box<dyn Error>> {
+        let Some(track) = self.get_selected_track()? else {
+            return Ok(());
+        };
+
+        self.show_osd(OsdContent::Message(String::from(tr(Message::LoadingRadio))));
+
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = fetch_radio(&track, from_artist);
+
+            let event = match result {
+                Ok((radio, name)) => {
+                    let seed = (!from_artist).then(|| Arc::clone(&track));
+                    let tracks = seed
+                        .into_iter()
+                        .chain(radio.into_iter().filter(|radio_track| radio_track.id != track.id).map(Arc::new))
+                        .collect();
+                    AppEvent::PlayRadio(tracks, tr_with(Message::RadioTitle, &[("name", &name)]))
+                },
+                Err(e) => AppEvent::ShowOsd(OsdContent::Message(tr_with(Message::PlayItemFailed, &[("error", &e)]))),
+            };
+            let _ = tx_clone.blocking_send(event);
+        });
+
+        Ok(())
+    }
+
+    /// Opens the mixes Tidal made for the user in the main pane.
+    fn open_my_mixes(&mut self) {
+        self.browse_stack.push(BrowseView::my_mixes(Arc::clone(&self.session), self.tx.clone()));
+        self.focus.set_focused(Pane::Collection);
+    }
+
+    /// Adds the tracks of the album open in the main pane to the end of the queue.
+    fn queue_album(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(BrowseContent::Album { tracks, .. }) = self.browse_stack.last().and_then(|view| view.get_content()) else {
+            return Ok(());
+        };
+
+        let count = tracks.len();
+        self.player.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .add_to_queue(tracks)?;
+
+        self.show_osd(OsdContent::Message(tr_with(Message::AddedTracksToQueue, &[("count", &count.to_string())])));
+
+        Ok(())
+    }
+
+    /// Searches Tidal for `query`, opening the results in the main pane.
+    fn search_tidal(&mut self, query: &str) {
+        if query.trim().is_empty() {
+            return;
+        }
+
+        if self.route != Route::Search {
+            self.switch_route(Route::Search);
+        }
+        self.browse_stack.push(BrowseView::search(Arc::clone(&self.session), query.trim().to_string(), self.tx.clone()));
+        self.focus.set_focused(Pane::Collection);
+    }
+
+    /// Shows the search result tab `offset` tabs after (or before, if negative) the one shown.
+    fn switch_tab(&mut self, offset: isize) {
+        if let Some(view) = self.browse_stack.last_mut() {
+            view.switch_tab(offset);
+        }
+    }
+
+    /// Opens the menu for adding the selected track to one of the user's playlists,
+    /// fetching the playlists in the background if they haven't been yet.
+    fn open_add_to_playlist_menu(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(track) = self.get_selected_track()? {
+            self.open_add_to_playlist_menu_for(track);
+        }
+
+        Ok(())
+    }
+
+    /// Opens the menu for choosing which of the user's playlists to add `track` to, fetching them in the background.
+    fn open_add_to_playlist_menu_for(&mut self, track: Arc<Track>) {
+        let error = Arc::new(Mutex::new(None));
+
+        let error_clone = Arc::clone(&error);
+        let user_clone = Arc::clone(&self.user);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = fetch_playlists(&user_clone) {
+                *error_clone.lock().unwrap() = Some(e);
+            }
+            let _ = tx_clone.try_send(AppEvent::ReRender);
+        });
+
+        self.playlist_index.build(Arc::clone(&self.user), self.tx.clone());
+
+        let mut list_state = ListState::default();
+        list_state.select_first();
+
+        self.menu = Some(Menu::AddToPlaylist(AddToPlaylistMenu {
+            track,
+            list_state,
+            error,
+        }));
+    }
+
+    /// Opens the menu of what can be done with the selected track.
+    fn open_track_menu(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(track) = self.get_selected_track()? {
+            let from_here = self.get_tracks_from_selected()?;
+            self.open_track_menu_for(track, from_here);
+        }
+
+        Ok(())
+    }
+
+    /// Opens the menu of what can be done with `track`, which can be played along with the tracks after it
+    /// if `from_here` is given.
+    fn open_track_menu_for(&mut self, track: Arc<Track>, from_here: Option<(Vec<Arc<Track>>, String)>) {
+        let is_favorite = self.is_favorite(&track.id);
+        self.menu = Some(Menu::Track(TrackMenu::new(track, is_favorite, from_here)));
+    }
+
+    /// Returns the tracks of the main pane from the selected one onward, and what they would be playing from.
+    ///
+    /// While My Collection - Tracks is grouped by album, only the rest of the selected track's album is returned.
+    fn get_tracks_from_selected(&self) -> Result<Option<TracksFrom>, Box<dyn Error>> {
+        if self.focus.get_focused() != Pane::Collection {
+            return Ok(None);
+        }
+
+        let from_here = match self.browse_stack.last() {
+            Some(view) => view.table_state.selected()
+                .and_then(|index| view.get_tracks().get(index..).map(<[_]>::to_vec))
+                .map(|tracks| (tracks, self.get_main_playing_from())),
+            None => match self.collection_tracks_table_state.selected() {
+                Some(row) if self.is_grouped_by_album => self.get_album_group_tracks(row)?,
+                Some(row) => self.collection_tracks.lock()
+                    .map_err(|e| format!("{e:#?}"))?
+                    .get(row..)
+                    .map(|tracks| (tracks.to_vec(), self.get_main_playing_from())),
+                None => None,
+            },
+        };
+
+        Ok(from_here.filter(|(tracks, _)| !tracks.is_empty()))
+    }
+
+    /// Does what `item` of `menu` is for.
+    fn run_track_menu_item(&mut self, menu: TrackMenu, item: TrackMenuItem) -> Result<(), Box<dyn Error>> {
+        let track = menu.track;
+        match item {
+            TrackMenuItem::PlayFromHere => {
+                if let Some((tracks, playing_from)) = menu.from_here {
+                    self.play_tracks(tracks, playing_from)?;
+                }
+            },
+            TrackMenuItem::PlayNow => {
+                let player_clone = Arc::clone(&self.player);
+                let tx_clone = self.tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = player_clone.lock().unwrap().play_now(track) {
+                        let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                        let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                    }
+                });
+            },
+            TrackMenuItem::PlayNext | TrackMenuItem::AddToQueue => {
+                let title = track.get_cached_attributes()
+                    .map_or(String::from(tr(Message::Track)), |attributes| attributes.get_full_title());
+
+                let player_clone = Arc::clone(&self.player);
+                let tx_clone = self.tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut player = player_clone.lock().unwrap();
+                    let result = if item == TrackMenuItem::PlayNext {
+                        player.play_next(vec![track])
+                    } else {
+                        player.add_to_queue(vec![track])
+                    };
+                    drop(player);
+
+                    let message = match result {
+                        Ok(()) if item == TrackMenuItem::PlayNext => tr_with(Message::PlayingNext, &[("title", &title)]),
+                        Ok(()) => String::from(tr(Message::AddedTrackToQueue)),
+                        Err(e) => tr_with(Message::AddToQueueFailed, &[("error", &e.to_string())]),
+                    };
+                    let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                });
+            },
+            TrackMenuItem::GoToAlbum => {
+                self.browse_stack.push(BrowseView::from_track_album(track, self.tx.clone()));
+                self.focus.set_focused(Pane::Collection);
+            },
+            TrackMenuItem::GoToArtist => {
+                self.browse_stack.push(BrowseView::from_track_artist(track, self.tx.clone()));
+                self.focus.set_focused(Pane::Collection);
+            },
+            TrackMenuItem::AddToPlaylist => self.open_add_to_playlist_menu_for(track),
+            TrackMenuItem::AddToCollection | TrackMenuItem::RemoveFromCollection => self.toggle_track_favorite(track),
+        }
+
+        Ok(())
+    }
+
+    /// Selects the previous item in the open menu.
+    fn select_prev_menu_item(&mut self) {
+        if let Some(menu) = &mut self.menu {
+            menu.get_list_state_mut().select_previous();
+        }
+    }
+
+    /// Selects the next item in the open menu.
+    fn select_next_menu_item(&mut self) {
+        if let Some(menu) = &mut self.menu {
+            menu.get_list_state_mut().select_next();
+        }
+    }
+
+    /// Does whatever the selected item in the open menu is for.
+    fn select_menu_item(&mut self) -> Result<(), Box<dyn Error>> {
+        match &self.menu {
+            Some(Menu::Track(_)) => {
+                if let Some(Menu::Track(menu)) = self.menu.take()
+                    && let Some(item) = menu.get_selected()
+                {
+                    self.run_track_menu_item(menu, item)?;
+                }
+            },
+            Some(Menu::AddToPlaylist(_)) => self.add_to_selected_playlist(),
+            Some(Menu::Blocklist(_)) => self.unblock_selected()?,
+            Some(Menu::ReplaceQueue(_)) => {
+                if let Some(Menu::ReplaceQueue(menu)) = self.menu.take()
+                    && menu.list_state.selected() == Some(0)
+                {
+                    self.run_queue_replacement(menu.replacement)?;
+                }
+            },
+            Some(Menu::PreviousQueues(_)) => self.restore_selected_queue()?,
+            Some(Menu::Diagnostics(_)) => self.copy_diagnostics_report(),
+            Some(Menu::Pages(list_state)) => {
+                let page = list_state.selected().and_then(|index| self.config.pages.get(index)).cloned();
+                self.menu = None;
+                if let Some(page) = page {
+                    self.open_page(page);
+                }
+            },
+            Some(Menu::Caches(list_state)) => {
+                if let Some(kind) = list_state.selected().and_then(|index| CacheKind::ALL.get(index).copied()) {
+                    self.purge_cache(kind);
+                }
+            },
+            Some(Menu::ScriptCommands(list_state)) => {
+                let selected = list_state.selected();
+                self.menu = None;
+                // The commands to purge each cache are listed before the ones added by scripts.
+                match selected {
+                    Some(index) if index < CacheKind::ALL.len() => self.purge_cache(CacheKind::ALL[index]),
+                    Some(index) => {
+                        self.scripts.run_command(index - CacheKind::ALL.len());
+                        self.handle_script_requests().map_err(|e| format!("{e}"))?;
+                    },
+                    None => {},
+                }
+            },
+            Some(Menu::SessionStats(_) | Menu::Script(_) | Menu::Help(_)) | None => {},
+        }
+
+        Ok(())
+    }
+
+    /// Adds the menu's track to the selected playlist in the background, and closes the menu.
+    fn add_to_selected_playlist(&mut self) {
+        let Some(Menu::AddToPlaylist(menu)) = &self.menu else {
+            return;
+        };
+
+        let Some(playlist) = menu.list_state.selected()
+            .and_then(|index| self.user.get_cached_playlists()?.get(index).cloned())
+        else {
+            return;
+        };
+
+        let track = Arc::clone(&menu.track);
+        self.menu = None;
+
+        if self.is_offline() {
+            let message = Self::defer_add_to_playlist(&self.pending_writes, &self.playlist_index, &playlist, &track);
+            self.show_osd(OsdContent::Message(message));
+            return;
+        }
+
+        let pending_writes_clone = self.pending_writes.clone();
+        let playlist_index_clone = self.playlist_index.clone();
+        let session_clone = Arc::clone(&self.session);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let message = match playlist.add_tracks(&[&track]) {
+                Ok(()) => {
+                    playlist_index_clone.insert(&playlist.id, &track.id);
+                    tr_with(Message::AddedToPlaylist, &[("playlist", &playlist.attributes.name)])
+                },
+                Err(_) if !session_clone.is_online() => {
+                    Self::defer_add_to_playlist(&pending_writes_clone, &playlist_index_clone, &playlist, &track)
+                },
+                Err(e) => tr_with(Message::AddToPlaylistFailed, &[("playlist", &playlist.attributes.name), ("error", &e)]),
+            };
+            let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+
+    /// Keeps adding `track` to `playlist` in `pending_writes` to be sent once Tidal can be reached,
+    /// and returns the message to show.
+    fn defer_add_to_playlist(pending_writes: &PendingWrites, playlist_index: &PlaylistIndex, playlist: &Playlist, track: &Track) -> String {
+        let write = PendingWrite::AddToPlaylist {
+            playlist_id: playlist.id.clone(),
+            playlist_name: playlist.attributes.name.clone(),
+            track_ids: vec![track.id.clone()],
+        };
+
+        match pending_writes.push(write) {
+            Ok(()) => {
+                playlist_index.insert(&playlist.id, &track.id);
+                tr_with(Message::AddToPlaylistDeferred, &[("playlist", &playlist.attributes.name)])
+            },
+            Err(e) => tr_with(Message::AddToPlaylistFailed, &[("playlist", &playlist.attributes.name), ("error", &e)]),
+        }
+    }
+
+    /// Moves the selected track of the playlist being browsed up (if `offset` is -1) or down (if it's 1).
+    fn move_selected_track(&mut self, offset: isize) {
+        let Some(view) = self.browse_stack.last_mut() else {
+            return;
+        };
+        let Some(BrowseContent::Playlist { playlist, .. }) = view.get_content() else {
+            return;
+        };
+
+        // Tidal would refuse the move anyway, but this avoids showing a new order only to undo it.
+        // Collaborative playlists the user has joined aren't among their own, so those are left to Tidal.
+        let is_others = self.user.get_cached_playlists()
+            .is_some_and(|playlists| playlists.iter().all(|own_playlist| own_playlist.id != playlist.id));
+        if is_others && !playlist.attributes.collaborative {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::OnlyOwnPlaylistsReordered))));
+            return;
+        }
+
+        view.move_selected_track(offset, self.tx.clone());
+    }
+
+    /// Blocks the selected track from being auto-played, or unblocks it if it was already blocked.
+    fn block_track(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(track) = self.get_selected_track()? else {
+            return Ok(());
+        };
+
+        let name = track.get_cached_attributes()
+            .map(|attributes| attributes.get_full_title())
+            .unwrap_or(format!("{} {}", tr(Message::Track), track.id));
+
+        let mut unlocked_blocklist = self.blocklist.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+        let is_blocked = unlocked_blocklist.toggle_track(&track.id, &name);
+        unlocked_blocklist.save()?;
+        drop(unlocked_blocklist);
+
+        self.show_blocked_osd(&name, is_blocked);
+
+        Ok(())
+    }
+
+    /// Blocks the selected track's artist from being auto-played, or unblocks them if they were already blocked.
+    fn block_artist(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(track) = self.get_selected_track()? else {
+            return Ok(());
+        };
+
+        let Some(artist) = track.get_cached_artist() else {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::ArtistNotLoaded))));
+            return Ok(());
+        };
+
+        let mut unlocked_blocklist = self.blocklist.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+        let is_blocked = unlocked_blocklist.toggle_artist(&artist.id, &artist.attributes.name);
+        unlocked_blocklist.save()?;
+        drop(unlocked_blocklist);
+
+        self.show_blocked_osd(&artist.attributes.name, is_blocked);
+
+        Ok(())
+    }
+
+    /// Shows whether `name` was just blocked or unblocked in the OSD.
+    fn show_blocked_osd(&mut self, name: &str, is_blocked: bool) {
+        let message = tr_with(if is_blocked { Message::Blocked } else { Message::Unblocked }, &[("name", name)]);
+        self.show_osd(OsdContent::Message(message));
+    }
+
+    /// Opens the menu of blocked tracks and artists.
+    fn open_blocklist(&mut self) {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+
+        self.menu = Some(Menu::Blocklist(list_state));
+    }
+
+    /// Unblocks the selected item in the blocklist menu.
+    fn unblock_selected(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(Menu::Blocklist(list_state)) = &self.menu else {
+            return Ok(());
+        };
+
+        let mut unlocked_blocklist = self.blocklist.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+
+        if let Some((item, _)) = list_state.selected().and_then(|index| unlocked_blocklist.get_items().get(index).cloned()) {
+            unlocked_blocklist.remove(&item);
+            unlocked_blocklist.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns how much the `kind` cache takes up on disk, for the cache view.
+    fn get_cache_size(&self, kind: CacheKind) -> String {
+        let locale = self.config.locale;
+
+        match kind {
+            CacheKind::Covers => match self.covers.get_disk_usage() {
+                Some(bytes) => {
+                    let size = locale.format_bytes(bytes);
+                    let max = locale.format_bytes(self.covers.get_max_disk_usage());
+                    tr_with(Message::CacheSizeOfMax, &[("size", &size), ("max", &max)])
+                },
+                // The saved covers are added up in the background when the app starts.
+                None => String::from("…"),
+            },
+            CacheKind::PlayerState => locale.format_bytes(self.player_state.get_size()),
+            CacheKind::PendingWrites => {
+                let count = locale.format_count(self.pending_writes.len());
+                format!(
+                    "{}, {}",
+                    locale.format_bytes(self.pending_writes.get_size()),
+                    tr_with(Message::PendingChanges, &[("count", &count)]),
+                )
+            },
+        }
+    }
+
+    /// Purges the `kind` cache and shows how it went in the OSD.
+    ///
+    /// Covers are deleted in the background, since there can be thousands of them.
+    fn purge_cache(&mut self, kind: CacheKind) {
+        let get_message = move |result: Result<(), String>| match result {
+            Ok(()) => tr_with(Message::Purged, &[("name", kind.get_title())]),
+            Err(e) => tr_with(Message::PurgeFailed, &[("name", kind.get_title()), ("error", &e)]),
+        };
+
+        let result = match kind {
+            CacheKind::Covers => {
+                let covers_clone = self.covers.clone();
+                let tx_clone = self.tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    let message = get_message(covers_clone.purge());
+                    let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                });
+                return;
+            },
+            CacheKind::PlayerState => self.player_state.clear(),
+            CacheKind::PendingWrites => self.pending_writes.clear(),
+        };
+
+        self.show_osd(OsdContent::Message(get_message(result)));
+    }
+
+    /// Exit this application's main loop.
+    fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    /// Toggles whether the queue is shown next to the collection.
+    fn toggle_queue(&mut self) {
+        self.show_queue = !self.show_queue;
+
+        let terminal_width = crossterm::terminal::size().map_or(u16::MAX, |(width, _)| width);
+        if self.show_queue && terminal_width < self.config.layout.queue_min_width {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::QueueTooNarrow))));
+        }
+    }
+
+    /// Shows or hides the tracks played before the current one in the queue, keeping the same track selected.
+    fn toggle_queue_history(&mut self) {
+        let history_len = self.player.lock().unwrap().get_queue_history().len();
+        self.show_queue_history = !self.show_queue_history;
+
+        let selected = self.queue_table_state.selected()
+            .map(|row| if self.show_queue_history { row + history_len } else { row.saturating_sub(history_len) });
+        self.queue_table_state.select(selected);
+    }
+
+    /// Starts a search within the focused table.
+    fn start_search(&mut self) {
+        let pane = self.focus.get_focused();
+        let start_row = self.get_focused_table_state().and_then(|table_state| table_state.selected());
+
+        self.search = Some(TableSearch::new(pane, start_row));
+    }
+
+    /// Edits the search as it is typed, selecting the first match after each change.
+    ///
+    /// Enter keeps the search for jumping between matches, and Esc cancels it, going back to the row it started from.
+    fn handle_search_key(&mut self, key_event: KeyEvent) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Esc => {
+                let start_row = search.get_start_row();
+                self.search = None;
+                if let Some(table_state) = self.get_focused_table_state() {
+                    table_state.select(start_row);
+                }
+            },
+            KeyCode::Enter if search.get_query().is_empty() => self.search = None,
+            KeyCode::Enter => search.finish_typing(),
+            KeyCode::Backspace => {
+                search.get_query_mut().pop();
+                self.select_first_match();
+            },
+            KeyCode::Char(c) => {
+                search.get_query_mut().push(c);
+                self.select_first_match();
+            },
+            _ => {},
+        }
+    }
+
+    /// Selects the first row matching the search from the row it started from, or that row if nothing matches.
+    fn select_first_match(&mut self) {
+        let Some(search) = &self.search else {
+            return;
+        };
+
+        let row = search.find_first_match(&self.get_focused_row_texts()).or(search.get_start_row());
+        if let Some(table_state) = self.get_focused_table_state() {
+            table_state.select(row);
+        }
+    }
+
+    /// Selects the next or previous row matching the last search in the focused table.
+    fn select_match(&mut self, direction: SearchDirection) {
+        let from = self.get_focused_table_state().and_then(|table_state| table_state.selected()).unwrap_or(0);
+
+        let Some(search) = self.get_search_in(self.focus.get_focused()) else {
+            let search_key = self.keymap.get_keys(Action::Search)
+                .first()
+                .map_or(String::from(tr(Message::TheSearchKey)), |key| key.to_string());
+            self.show_osd(OsdContent::Message(tr_with(Message::NothingSearched, &[("key", &search_key)])));
+            return;
+        };
+
+        match search.find_match(&self.get_focused_row_texts(), from, direction) {
+            Some(row) => {
+                if let Some(table_state) = self.get_focused_table_state() {
+                    table_state.select(Some(row));
+                }
+            },
+            None => {
+                let message = tr_with(Message::NoMatches, &[("query", search.get_query())]);
+                self.show_osd(OsdContent::Message(message));
+            },
+        }
+    }
+
+    /// Returns the text of each row in the focused table that a search looks through.
+    ///
+    /// Tracks whose info hasn't been fetched yet have no text, so they can't be found.
+    fn get_focused_row_texts(&self) -> Vec<String> {
+        match self.focus.get_focused() {
+            Pane::Collection => match self.browse_stack.last() {
+                Some(view) => view.get_row_texts(),
+                None if self.is_grouped_by_album => {
+                    let unlocked_collection_tracks = self.collection_tracks.lock().unwrap();
+
+                    self.album_groups.get_rows()
+                        .iter()
+                        .map(|row| match row {
+                            GroupedRow::Album(group_idx) => self.album_groups.get_group(*group_idx)
+                                .and_then(|group| group.track_indices.iter().find_map(|idx| unlocked_collection_tracks[*idx].get_cached_album()))
+                                .map(|album| album.attributes.title.clone())
+                                .unwrap_or_default(),
+                            GroupedRow::Track(idx) => get_search_text(&unlocked_collection_tracks[*idx]),
+                        })
+                        .collect()
+                },
+                None => self.collection_tracks.lock().unwrap()
+                    .iter()
+                    .map(|track| get_search_text(track))
+                    .collect(),
+            },
+            Pane::Queue => self.get_queue_tracks()
+                .iter()
+                .map(|track| get_search_text(track))
+                .collect(),
+            Pane::NowPlaying => vec![],
+        }
+    }
+
+    /// Returns the state of the focused table, or `None` if the focused pane isn't a table.
+    fn get_focused_table_state(&mut self) -> Option<&mut TableState> {
+        match self.focus.get_focused() {
+            Pane::Collection => Some(self.get_main_table_state()),
+            Pane::Queue => Some(&mut self.queue_table_state),
+            Pane::NowPlaying => None,
+        }
+    }
+
+    /// Returns the state of the main pane's table.
+    fn get_main_table_state(&mut self) -> &mut TableState {
+        match self.browse_stack.last_mut() {
+            Some(view) => &mut view.table_state,
+            None => &mut self.collection_tracks_table_state,
+        }
+    }
+
+    /// Returns the tracks in the main pane, which are the collection's unless a playlist is being browsed.
+    fn get_main_tracks(&self) -> Vec<Arc<Track>> {
+        match self.browse_stack.last() {
+            Some(view) => view.get_tracks(),
+            None => self.collection_tracks.lock().unwrap().clone(),
+        }
+    }
+
+    /// Returns the key the main pane's tracks are remembered by when they are played, if it is a list of tracks.
+    fn get_main_context_key(&self) -> Option<String> {
+        match self.browse_stack.last() {
+            Some(view) => view.get_context_key(),
+            None => Some(String::from("collection")),
+        }
+    }
+
+    /// Returns the name shown as what is playing from when playing the main pane's tracks.
+    fn get_main_playing_from(&self) -> String {
+        match self.browse_stack.last() {
+            Some(view) => view.get_title(),
+            None => String::from(tr(Message::Tracks)),
+        }
+    }
+
+    /// Returns the number of rows in the focused table.
+    fn get_focused_table_len(&self) -> usize {
+        match self.focus.get_focused() {
+            Pane::Collection => match self.browse_stack.last() {
+                Some(view) => view.get_len(),
+                None if self.is_grouped_by_album => self.album_groups.get_rows().len(),
+                None => self.collection_tracks_len.load(Ordering::Relaxed),
+            },
+            Pane::Queue => self.get_queue_tracks().len(),
+            Pane::NowPlaying => 0,
+        }
+    }
+
+    /// Selects the row `count` rows below the selected one in the focused table.
+    fn next_row(&mut self, count: usize) {
+        if let Some(table_state) = self.get_focused_table_state() {
+            match table_state.selected() {
+                Some(_) => table_state.scroll_down_by(count.try_into().unwrap_or(u16::MAX)),
+                None => table_state.select_first(),
+            }
+        }
+    }
+
+    /// Selects the row `count` rows above the selected one in the focused table.
+    fn prev_row(&mut self, count: usize) {
+        if let Some(table_state) = self.get_focused_table_state() {
+            match table_state.selected() {
+                Some(_) => table_state.scroll_up_by(count.try_into().unwrap_or(u16::MAX)),
+                None => table_state.select_last(),
+            }
+        }
+    }
+
+    /// Selects the row at `row` in the focused table, or the last row if there aren't that many.
+    fn go_to_row(&mut self, row: usize) {
+        let len = self.get_focused_table_len();
+        if let Some(table_state) = self.get_focused_table_state() {
+            table_state.select(Some(row.min(len.saturating_sub(1))));
+        }
+    }
+
+    /// Selects the first row in the focused table.
+    fn go_to_top(&mut self) {
+        if let Some(table_state) = self.get_focused_table_state() {
+            table_state.select_first();
+        }
+    }
+
+    /// Selects the last row in the focused table.
+    fn go_to_bottom(&mut self) {
+        let len = self.get_focused_table_len();
+        if let Some(table_state) = self.get_focused_table_state() {
+            table_state.select(Some(len.saturating_sub(1)));
+        }
+    }
+
+    /// Selects the currently playing track's row in the main pane's table.
+    fn go_to_currently_playing(&mut self) -> Result<(), Box<dyn Error>> {
+        let current_track_id = self.player.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .get_current_track()
+            .map(|t| t.id.clone());
+
+        if let Some(current_track_id) = current_track_id
+            && let Some(index) = self.get_main_tracks().iter().position(|t| t.id == current_track_id)
+        {
+            let row = if self.is_grouped_by_album && self.browse_stack.is_empty() {
+                self.album_groups.reveal_track(index)
+            } else {
+                Some(index)
+            };
+            self.get_main_table_state().select(row);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the selected track in the focused table, if any.
+    fn get_selected_track(&self) -> Result<Option<Arc<Track>>, Box<dyn Error>> {
+        let selected_track = match self.focus.get_focused() {
+            Pane::Collection => match self.browse_stack.last() {
+                Some(view) => view.table_state.selected()
+                    .and_then(|index| view.get_tracks().get(index).cloned()),
+                None => match self.collection_tracks_table_state.selected().and_then(|row| self.get_collection_track_index(row)) {
+                    Some(index) => self.collection_tracks.lock()
+                        .map_err(|e| format!("{e:#?}"))?
+                        .get(index)
+                        .cloned(),
+                    None => None,
+                },
+            },
+            Pane::Queue => self.queue_table_state.selected()
+                .and_then(|index| self.get_queue_tracks().get(index).cloned()),
+            Pane::NowPlaying => None,
+        };
+
+        Ok(selected_track)
+    }
+
+    /// Returns the index in the collection of the track at `row` of My Collection - Tracks, or `None` if it is an album header.
+    fn get_collection_track_index(&self, row: usize) -> Option<usize> {
+        if !self.is_grouped_by_album {
+            return Some(row);
+        }
+
+        match self.album_groups.get_rows().get(row)? {
+            GroupedRow::Album(_) => None,
+            GroupedRow::Track(idx) => Some(*idx),
+        }
+    }
+
+    /// Fetches the selected track's info again if it failed to load.
+    fn retry_row(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(track) = self.get_selected_track()? {
+            self.hydrator.retry(&track);
+        }
+
         Ok(())
     }
 
-    /// Exit this application's main loop.
-    fn exit(&mut self) {
-        self.exit = true;
+    /// Opens the track info popup for the selected track in the focused table,
+    /// or the currently playing track if no row is selected.
+    fn open_info_popup(&mut self) -> Result<(), Box<dyn Error>> {
+        let track = match self.get_selected_track()? {
+            Some(track) => Some(track),
+            None => self.player.lock()
+                .map_err(|e| format!("{e:#?}"))?
+                .get_current_track()
+                .cloned(),
+        };
+
+        if track.is_some() {
+            self.playlist_index.build(Arc::clone(&self.user), self.tx.clone());
+        }
+        self.info_popup_track = track;
+
+        Ok(())
     }
 
-    /// Selects the next row in the table.
-    fn next_row(&mut self) {
-        self.collection_tracks_table_state.select_next();
+    /// Closes the track info popup.
+    fn close_info_popup(&mut self) {
+        self.info_popup_track = None;
     }
 
-    /// Selects the previous row in the table.
-    fn prev_row(&mut self) {
-        self.collection_tracks_table_state.select_previous();
+    /// Starts playing the main pane's tracks from the beginning.
+    fn play_all(&mut self) -> Result<(), Box<dyn Error>> {
+        self.replace_queue(false)
     }
 
-    /// Selects the first row in the table.
-    fn go_to_top(&mut self) {
-        self.collection_tracks_table_state.select_first();
+    /// Starts playing the main pane's tracks in a shuffled order.
+    fn shuffle_all(&mut self) -> Result<(), Box<dyn Error>> {
+        self.replace_queue(true)
     }
 
-    /// Selects the last row in the table.
-    fn go_to_bottom(&mut self) {
-        self.collection_tracks_table_state.select(Some(self.collection_tracks_len.load(Ordering::Relaxed)));
+    /// Plays the main pane's tracks the way they were last played, shuffled or in order.
+    fn replay(&mut self) -> Result<(), Box<dyn Error>> {
+        let shuffle = self.get_main_context_key().is_some_and(|key| self.play_modes.get(&key).shuffle);
+
+        self.replace_queue(shuffle)
     }
 
-    /// Selects the currently playing track's row in the table.
-    fn go_to_currently_playing(&mut self) -> Result<(), Box<dyn Error>> {
-        let unlocked_player = self.player.lock()
-            .map_err(|e| format!("{e:#?}"))?;
+    /// Plays the main pane's tracks, shuffled if `shuffle` is true, confirming first if that replaces an unfinished queue.
+    fn replace_queue(&mut self, shuffle: bool) -> Result<(), Box<dyn Error>> {
+        self.confirm_replace_queue(QueueReplacement::MainTracks { shuffle })
+    }
 
-        if let Some(current_track) = unlocked_player.get_current_track() {
-            let unlocked_collections_tracks = self.collection_tracks.lock()
-                .map_err(|e| format!("{e:#?}"))?;
+    /// Replaces the queue with `replacement`.
+    ///
+    /// If that would replace a queue from somewhere else that hasn't finished, a menu asks to confirm first
+    /// (unless `player.confirm_replace_queue` is off).
+    fn confirm_replace_queue(&mut self, replacement: QueueReplacement) -> Result<(), Box<dyn Error>> {
+        let playing_from = match &replacement {
+            QueueReplacement::MainTracks { .. } => self.get_main_playing_from(),
+            QueueReplacement::Tracks { tracks, .. } if tracks.is_empty() => return Ok(()),
+            QueueReplacement::Tracks { playing_from, .. } => playing_from.clone(),
+        };
 
-            if let Some(index) = unlocked_collections_tracks.iter().position(|t| t.id == current_track.id) {
-                self.collection_tracks_table_state.select(Some(index));
-            }
+        let has_unfinished_queue = self.player.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .save_queue()
+            .is_some();
+        let is_same_context = self.playing_from.as_ref() == Some(&playing_from);
+
+        if self.config.player.confirm_replace_queue && has_unfinished_queue && !is_same_context {
+            let mut list_state = ListState::default();
+            list_state.select_first();
+
+            self.menu = Some(Menu::ReplaceQueue(ReplaceQueueMenu {
+                replacement,
+                list_state,
+            }));
+            return Ok(());
         }
 
-        Ok(())
+        self.run_queue_replacement(replacement)
     }
 
-    /// Starts playing the collection's tracks from the beginning.
-    fn play_all(&mut self) -> Result<(), Box<dyn Error>> {
-        let collection_tracks_copy = self.collection_tracks.lock().unwrap().clone();
+    /// Replaces the queue with `replacement` and starts playing it, without asking first.
+    fn run_queue_replacement(&mut self, replacement: QueueReplacement) -> Result<(), Box<dyn Error>> {
+        match replacement {
+            QueueReplacement::MainTracks { shuffle } => self.play_main_tracks(shuffle),
+            QueueReplacement::Tracks { tracks, playing_from, shuffle, is_auto_generated, slot } => {
+                self.start_playing(tracks, playing_from, shuffle, is_auto_generated)?;
+                if slot.is_some() {
+                    self.current_slot = slot;
+                }
+
+                Ok(())
+            },
+        }
+    }
+
+    /// Replaces the queue with the main pane's tracks and starts playing them, shuffled if `shuffle` is true.
+    ///
+    /// The replaced queue is kept so that it can be restored from the previous queues menu.
+    fn play_main_tracks(&mut self, shuffle: bool) -> Result<(), Box<dyn Error>> {
+        let collection_tracks_copy = self.get_main_tracks();
 
         let mut unlocked_player = self.player.lock()
             .map_err(|e| format!("{e:#?}"))?;
+        let saved = unlocked_player.save_queue();
         unlocked_player.set_queue(collection_tracks_copy);
+        if shuffle {
+            unlocked_player.shuffle_queue();
+        }
         drop(unlocked_player);
 
-        let player_clone = Arc::clone(&self.player);
-        tokio::task::spawn_blocking(move || {
-            player_clone.lock().unwrap().play().unwrap();
-        });
+        self.push_previous_queue(saved);
+
+        self.play_in_background();
+
+        self.playing_from = Some(self.get_main_playing_from());
+        self.is_shuffle = shuffle;
+        self.current_slot = None;
 
-        self.playing_from = Some("Tracks".to_string());
-        self.is_shuffle = false;
+        if let Some(key) = self.get_main_context_key() {
+            self.play_modes.set(&key, PlayMode { shuffle });
+            self.play_modes.save()?;
+        }
 
         Ok(())
     }
 
-    /// Starts playing the collection's tracks in a shuffled order.
-    fn shuffle_all(&mut self) -> Result<(), Box<dyn Error>> {
-        let collection_tracks_copy = self.collection_tracks.lock().unwrap().clone();
+    /// Groups My Collection - Tracks by album, or lists its tracks on their own again, keeping the selected track selected.
+    fn toggle_album_grouping(&mut self) {
+        if !self.browse_stack.is_empty() {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::OnlyCollectionGrouped))));
+            return;
+        }
+
+        let selected = self.collection_tracks_table_state.selected()
+            .and_then(|row| self.get_collection_track_index(row));
+        self.is_grouped_by_album = !self.is_grouped_by_album;
+
+        let row = if self.is_grouped_by_album {
+            let collection_tracks = Arc::clone(&self.collection_tracks);
+            let unlocked_collection_tracks = collection_tracks.lock().unwrap();
+
+            // Every track's album is needed to group it, not just the ones on screen.
+            for track in unlocked_collection_tracks.iter() {
+                self.hydrator.request(track, Priority::Background);
+            }
+
+            self.regroup_albums(&unlocked_collection_tracks);
+            selected.and_then(|idx| self.album_groups.reveal_track(idx))
+        } else {
+            selected
+        };
+        self.collection_tracks_table_state.select(row.or(Some(0)));
+    }
+
+    /// Sorts the albums listed in the main pane by the other sort, or in the other order if `reverse` is true.
+    /// My Collection - Tracks is sorted by the next field instead while it is shown.
+    fn change_sort(&mut self, reverse: bool) {
+        if let Some(view) = self.browse_stack.last_mut()
+            && let Some(album_sort) = view.get_album_sort()
+        {
+            let album_sort = if reverse { album_sort.reverse() } else { album_sort.cycle() };
+            view.set_album_sort(album_sort);
+            self.show_osd(OsdContent::Message(tr_with(Message::SortedBy, &[("sort", &album_sort.get_name())])));
+            return;
+        }
+
+        let sort = if reverse { self.collection_sort.reverse() } else { self.collection_sort.cycle() };
+        self.set_collection_sort(sort);
+    }
+
+    /// Sorts My Collection - Tracks by `sort`, keeping the selected track selected.
+    fn set_collection_sort(&mut self, sort: TrackSort) {
+        if !self.browse_stack.is_empty() {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::OnlyCollectionSorted))));
+            return;
+        }
+
+        self.collection_sort = sort;
+        // Every track's info is needed to sort it, not just the ones on screen.
+        if sort.field != SortField::DateAdded {
+            for track in self.collection_tracks.lock().unwrap().iter() {
+                self.hydrator.request(track, Priority::Background);
+            }
+        }
+        self.sort_collection();
+
+        self.show_osd(OsdContent::Message(tr_with(Message::SortedBy, &[("sort", &sort.get_name())])));
+    }
+
+    /// Sorts My Collection - Tracks again if tracks were fetched, or got their info, since it was last sorted,
+    /// so that they move to where they belong.
+    fn update_collection_sort(&mut self) {
+        if self.collection_sort.is_default() {
+            return;
+        }
+
+        let sorted_with = {
+            let unlocked_collection_tracks = self.collection_tracks.lock().unwrap();
+
+            // The collection was sorted before its tracks were fetched, so their info wasn't asked for yet.
+            if unlocked_collection_tracks.len() != self.collection_sorted_with.0 && self.collection_sort.field != SortField::DateAdded {
+                for track in unlocked_collection_tracks.iter() {
+                    self.hydrator.request(track, Priority::Background);
+                }
+            }
+
+            (unlocked_collection_tracks.len(), unlocked_collection_tracks.iter().filter(|track| track.has_info()).count())
+        };
+        if sorted_with != self.collection_sorted_with {
+            self.sort_collection();
+        }
+    }
+
+    /// Sorts My Collection - Tracks by `collection_sort`, keeping the selected track selected.
+    fn sort_collection(&mut self) {
+        let collection_tracks = Arc::clone(&self.collection_tracks);
+        let mut unlocked_collection_tracks = collection_tracks.lock().unwrap();
+
+        let selected_id = self.collection_tracks_table_state.selected()
+            .and_then(|row| self.get_collection_track_index(row))
+            .and_then(|idx| unlocked_collection_tracks.get(idx))
+            .map(|track| track.id.clone());
+
+        self.collection_sort.sort(&mut unlocked_collection_tracks);
+        self.collection_sorted_with = (
+            unlocked_collection_tracks.len(),
+            unlocked_collection_tracks.iter().filter(|track| track.has_info()).count(),
+        );
+
+        let Some(idx) = selected_id.and_then(|id| unlocked_collection_tracks.iter().position(|track| track.id == id)) else {
+            return;
+        };
+        let row = if self.is_grouped_by_album {
+            self.regroup_albums(&unlocked_collection_tracks);
+            self.album_groups.reveal_track(idx)
+        } else {
+            Some(idx)
+        };
+        if row.is_some() {
+            self.collection_tracks_table_state.select(row);
+        }
+    }
+
+    /// Collapses or expands the album group of the selected row in My Collection - Tracks, while it is grouped by album.
+    fn toggle_album_collapsed(&mut self) {
+        if !self.is_grouped_by_album || !self.browse_stack.is_empty() {
+            return;
+        }
+
+        let Some(group_idx) = self.collection_tracks_table_state.selected()
+            .and_then(|row| self.album_groups.get_group_of_row(row))
+        else {
+            return;
+        };
+
+        if let Some(row) = self.album_groups.toggle_collapsed(group_idx) {
+            self.collection_tracks_table_state.select(Some(row));
+        }
+    }
+
+    /// Plays the album group of the selected album header in My Collection - Tracks, while it is grouped by album.
+    ///
+    /// Opens the selected track's menu instead if a track is selected.
+    fn play_album(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.is_grouped_by_album || !self.browse_stack.is_empty() {
+            return self.open_track_menu();
+        }
+
+        let Some(row) = self.collection_tracks_table_state.selected() else {
+            return Ok(());
+        };
+        if self.get_collection_track_index(row).is_some() {
+            return self.open_track_menu();
+        }
+        let Some((album_tracks, playing_from)) = self.get_album_group_tracks(row)? else {
+            return Ok(());
+        };
+
+        self.play_tracks(album_tracks, playing_from)
+    }
+
+    /// Returns the tracks of the album group of `row` in My Collection - Tracks, while it is grouped by album,
+    /// starting from the track at `row` (or the first one if it is the album's header), along with the album's title.
+    fn get_album_group_tracks(&self, row: usize) -> Result<Option<TracksFrom>, Box<dyn Error>> {
+        let Some(group) = self.album_groups.get_group_of_row(row).and_then(|group_idx| self.album_groups.get_group(group_idx)) else {
+            return Ok(None);
+        };
+
+        let start = self.get_collection_track_index(row)
+            .and_then(|idx| group.track_indices.iter().position(|track_idx| *track_idx == idx))
+            .unwrap_or(0);
+        let unlocked_collection_tracks = self.collection_tracks.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+        let album_tracks: Vec<Arc<Track>> = group.track_indices[start..].iter()
+            .map(|idx| Arc::clone(&unlocked_collection_tracks[*idx]))
+            .collect();
+        let playing_from = album_tracks.first()
+            .and_then(|track| track.get_cached_album())
+            .map_or(String::from(tr(Message::Album)), |album| tr_with(Message::AlbumTitle, &[("album", &album.attributes.title)]));
+
+        Ok(Some((album_tracks, playing_from)))
+    }
+
+    /// Replaces the queue with `tracks` and starts playing them in order, showing that they are playing from `playing_from`.
+    ///
+    /// Like playing the main pane's tracks, this asks to confirm first if it replaces an unfinished queue.
+    fn play_tracks(&mut self, tracks: Vec<Arc<Track>>, playing_from: String) -> Result<(), Box<dyn Error>> {
+        self.confirm_replace_queue(QueueReplacement::Tracks {
+            tracks,
+            playing_from,
+            shuffle: false,
+            is_auto_generated: false,
+            slot: None,
+        })
+    }
+
+    /// Replaces the queue with `tracks` and starts playing them, shuffled if `shuffle` is true,
+    /// showing that they are playing from `playing_from`.
+    ///
+    /// `is_auto_generated` is true for tracks Tidal chose, like a radio's, so that blocked and filtered ones are skipped.
+    /// The replaced queue is kept so that it can be restored from the previous queues menu.
+    fn start_playing(&mut self, tracks: Vec<Arc<Track>>, playing_from: String, shuffle: bool, is_auto_generated: bool) -> Result<(), Box<dyn Error>> {
+        if tracks.is_empty() {
+            return Ok(());
+        }
 
         let mut unlocked_player = self.player.lock()
             .map_err(|e| format!("{e:#?}"))?;
-        unlocked_player.set_queue(collection_tracks_copy);
-        unlocked_player.shuffle_queue();
+        let saved = unlocked_player.save_queue();
+        unlocked_player.set_queue(tracks);
+        if shuffle {
+            unlocked_player.shuffle_queue();
+        }
+        unlocked_player.set_auto_generated(is_auto_generated);
         drop(unlocked_player);
 
+        self.push_previous_queue(saved);
+
+        self.play_in_background();
+
+        self.playing_from = Some(playing_from);
+        self.is_shuffle = shuffle;
+        self.current_slot = None;
+
+        Ok(())
+    }
+
+    /// Keeps `saved`, the queue that was just replaced, so that it can be restored later.
+    fn push_previous_queue(&mut self, saved: Option<SavedQueue>) {
+        let Some(saved) = saved else {
+            return;
+        };
+
+        self.previous_queues.insert(0, PreviousQueue {
+            playing_from: self.playing_from.clone(),
+            is_shuffle: self.is_shuffle,
+            saved,
+        });
+        self.previous_queues.truncate(Self::MAX_PREVIOUS_QUEUES);
+    }
+
+    /// Opens the menu of queues that were replaced.
+    fn open_previous_queues(&mut self) {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+
+        self.menu = Some(Menu::PreviousQueues(list_state));
+    }
+
+    /// Restores the selected queue in the previous queues menu, keeping the current queue in its place, and closes the menu.
+    fn restore_selected_queue(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(Menu::PreviousQueues(list_state)) = &self.menu else {
+            return Ok(());
+        };
+
+        let Some(index) = list_state.selected().filter(|&index| index < self.previous_queues.len()) else {
+            return Ok(());
+        };
+
+        let previous_queue = self.previous_queues.remove(index);
+        self.menu = None;
+
+        let playing_from = previous_queue.playing_from;
+        let is_shuffle = previous_queue.is_shuffle;
+
+        let saved = self.player.lock()
+            .map_err(|e| format!("{e:#?}"))?
+            .save_queue();
+        self.push_previous_queue(saved);
+
+        // Restoring fetches the track it was up to, so it is done in the background like starting playback.
         let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
         tokio::task::spawn_blocking(move || {
-            player_clone.lock().unwrap().play().unwrap();
+            if let Err(e) = player_clone.lock().unwrap().restore_queue(previous_queue.saved) {
+                let message = tr_with(Message::RestoreQueueFailed, &[("error", &e.to_string())]);
+                let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+            }
         });
 
-        self.playing_from = Some("Tracks".to_string());
-        self.is_shuffle = true;
+        self.playing_from = playing_from;
+        self.is_shuffle = is_shuffle;
+        self.current_slot = None;
 
         Ok(())
     }
 
-    /// Decreases the volume of the player.
-    fn volume_down(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Decreases the volume of the player by `times` steps.
+    fn volume_down(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
         const DECREASE_AMOUNT: u32 = 5;
 
         let mut unlocked_player = self.player.lock()
             .map_err(|e| format!("{e:#?}"))?;
 
         let current_volume = unlocked_player.get_volume();
-        unlocked_player.set_volume(current_volume.saturating_sub(DECREASE_AMOUNT));
+        unlocked_player.set_volume(current_volume.saturating_sub(DECREASE_AMOUNT.saturating_mul(times as u32)));
+
+        let new_volume = unlocked_player.get_volume();
+        drop(unlocked_player);
+        self.show_osd(OsdContent::Volume(new_volume));
 
         Ok(())
     }
 
-    /// Increase the volume of the player.
-    fn volume_up(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Increase the volume of the player by `times` steps.
+    fn volume_up(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
         const INCREASE_AMOUNT: u32 = 5;
 
         let mut unlocked_player = self.player.lock()
             .map_err(|e| format!("{e:#?}"))?;
 
         let current_volume = unlocked_player.get_volume();
-        unlocked_player.set_volume(current_volume.saturating_add(INCREASE_AMOUNT));
+        unlocked_player.set_volume(current_volume.saturating_add(INCREASE_AMOUNT.saturating_mul(times as u32)));
+
+        let new_volume = unlocked_player.get_volume();
+        drop(unlocked_player);
+        self.show_osd(OsdContent::Volume(new_volume));
 
         Ok(())
     }
@@ -567,46 +4632,402 @@ impl App {
         if unlocked_player.is_playing() {
             unlocked_player.pause()?;
         } else {
-            unlocked_player.play()?;
+            drop(unlocked_player);
+            self.play_in_background();
         }
 
         Ok(())
     }
 
-    /// Goes back to play the previous track.
-    fn previous_track(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Resumes or starts playback in the background, since it can wait on Tidal (e.g. for a new stream after the computer slept).
+    ///
+    /// If it fails, the error is shown in the OSD.
+    fn play_in_background(&self) {
+        let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = player_clone.lock().unwrap().play() {
+                let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+            }
+        });
+    }
+
+    /// Resumes playback on this device after it was taken over on another one.
+    fn resume_here(&mut self) -> Result<(), Box<dyn Error>> {
+        let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = player_clone.lock().unwrap().resume_here() {
+                let message = tr_with(Message::ResumeFailed, &[("error", &e.to_string())]);
+                let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Goes back to play the previous track, or the one `times` tracks back.
+    fn previous_track(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
+        let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut unlocked_player = player_clone.lock().unwrap();
+            let result = if times > 1 {
+                unlocked_player.skip_back(times)
+            } else {
+                unlocked_player.prev()
+            };
+            drop(unlocked_player);
+            if let Err(e) = result {
+                let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Skips to play the next track, or the one `times` tracks ahead.
+    fn next_track(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
+        let player_clone = Arc::clone(&self.player);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = player_clone.lock().unwrap().skip_forward(times);
+            if let Err(e) = result {
+                let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Seeks backward in the current track, `times` steps at once.
+    fn seek_backward(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
+        const SEEK_STEP: Duration = Duration::from_secs(10);
+        let seek_amount = SEEK_STEP.saturating_mul(times as u32);
+
+        if let Some((position, duration)) = self.get_position_and_duration()? {
+            self.show_osd(OsdContent::Seek { position: position.saturating_sub(seek_amount), duration });
+        }
+
         let player_clone = Arc::clone(&self.player);
         tokio::task::spawn_blocking(move || {
-            player_clone.lock().unwrap().prev().unwrap();
+            player_clone.lock().unwrap().seek_backward(seek_amount).unwrap();
         });
 
         Ok(())
     }
 
-    /// Skips to play the next track.
-    fn next_track(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Seeks forward in the current track, `times` steps at once.
+    fn seek_forward(&mut self, times: usize) -> Result<(), Box<dyn Error>> {
+        const SEEK_STEP: Duration = Duration::from_secs(10);
+        let seek_amount = SEEK_STEP.saturating_mul(times as u32);
+
+        if let Some((position, duration)) = self.get_position_and_duration()? {
+            self.show_osd(OsdContent::Seek { position: (position + seek_amount).min(duration), duration });
+        }
+
         let player_clone = Arc::clone(&self.player);
         tokio::task::spawn_blocking(move || {
-            player_clone.lock().unwrap().next().unwrap();
+            player_clone.lock().unwrap().seek_forward(seek_amount).unwrap();
         });
 
         Ok(())
     }
 
-    /// Cycles the audio quality settings.
+    /// Returns the position and duration of the current track, if one is playing.
+    fn get_position_and_duration(&self) -> Result<Option<(Duration, Duration)>, Box<dyn Error>> {
+        let unlocked_player = self.player.lock()
+            .map_err(|e| format!("{e:#?}"))?;
+
+        let duration = match unlocked_player.get_current_track() {
+            Some(track) if track.get_cached_attributes().is_some() => *track.get_duration()?,
+            _ => return Ok(None),
+        };
+
+        Ok(Some((unlocked_player.get_position(), duration)))
+    }
+
+    /// Returns true if the track with id `track_id` is in My Collection, counting changes made since it was fetched.
+    fn is_favorite(&self, track_id: &str) -> bool {
+        if let Some(is_favorite) = self.favorite_changes.lock().unwrap().get(track_id) {
+            return *is_favorite;
+        }
+
+        self.collection_tracks.lock().unwrap().iter().any(|track| track.id == track_id)
+    }
+
+    /// Adds the current track to My Collection, or removes it if it is already there, in the background.
+    ///
+    /// While offline, the change is kept to be sent once Tidal can be reached again.
+    fn toggle_favorite(&mut self) {
+        let Some(track) = self.player.lock().unwrap().get_current_track().cloned() else {
+            return;
+        };
+
+        self.toggle_track_favorite(track);
+    }
+
+    /// Adds `track` to My Collection, or removes it if it is already there, in the background.
+    fn toggle_track_favorite(&mut self, track: Arc<Track>) {
+        let is_favorite = !self.is_favorite(&track.id);
+        self.favorite_changes.lock().unwrap().insert(track.id.clone(), is_favorite);
+
+        let track_ids = vec![track.id.clone()];
+        let write = if is_favorite {
+            PendingWrite::AddFavoriteTracks { track_ids }
+        } else {
+            PendingWrite::RemoveFavoriteTracks { track_ids }
+        };
+        let title = track.get_cached_attributes()
+            .map_or(String::from(tr(Message::Track)), |attributes| attributes.get_full_title());
+        // Only tracks whose info has been fetched can be loved, since they are looked up by name.
+        let love = self.scrobbler.clone()
+            .zip(track.get_cached_artist_credits().and_then(|credits| credits.get_main()))
+            .zip(track.get_cached_attributes())
+            .map(|((scrobbler, artist), attributes)| (scrobbler, artist.attributes.name.clone(), attributes.get_full_title()));
+
+        let is_offline = self.is_offline();
+        let favorite_changes_clone = Arc::clone(&self.favorite_changes);
+        let pending_writes_clone = self.pending_writes.clone();
+        let session_clone = Arc::clone(&self.session);
+        let user_clone = Arc::clone(&self.user);
+        let tx_clone = self.tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = if is_offline { Err(String::from("Offline")) } else { write.send(&session_clone, &user_clone) };
+
+            let result = match result {
+                Ok(()) => Ok(false),
+                Err(_) if is_offline || !session_clone.is_online() => pending_writes_clone.push(write).map(|_| true),
+                Err(e) => Err(e),
+            };
+
+            let message = match result {
+                Ok(is_deferred) => {
+                    if let Some((scrobbler, artist, title)) = &love {
+                        scrobbler.set_loved(artist, title, is_favorite);
+                    }
+
+                    let message = match (is_favorite, is_deferred) {
+                        (true, false) => Message::AddedToCollection,
+                        (false, false) => Message::RemovedFromCollection,
+                        (true, true) => Message::AddedToCollectionDeferred,
+                        (false, true) => Message::RemovedFromCollectionDeferred,
+                    };
+                    tr_with(message, &[("title", &title)])
+                },
+                Err(e) => {
+                    favorite_changes_clone.lock().unwrap().insert(track.id.clone(), !is_favorite);
+                    tr_with(Message::UpdateCollectionFailed, &[("error", &e)])
+                },
+            };
+            let _ = tx_clone.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+
+    /// Turns skipping explicit tracks while shuffling on or off.
+    fn toggle_explicit_filter(&mut self) {
+        let mut player = self.player.lock().unwrap();
+        let mut queue_filters = player.get_queue_filters().clone();
+        queue_filters.exclude_explicit = !queue_filters.exclude_explicit;
+        let is_excluding_explicit = queue_filters.exclude_explicit;
+        player.set_queue_filters(queue_filters);
+        drop(player);
+
+        let message = if is_excluding_explicit { Message::SkippingExplicit } else { Message::PlayingExplicit };
+        self.show_osd(OsdContent::Message(String::from(tr(message))));
+    }
+
+    /// Turns skipping the genres in the `excluded_genres` setting while shuffling on or off.
+    fn toggle_genre_filter(&mut self) {
+        let genres = &self.config.player.excluded_genres;
+        if genres.is_empty() {
+            self.show_osd(OsdContent::Message(String::from(tr(Message::NoExcludedGenres))));
+            return;
+        }
+
+        let mut player = self.player.lock().unwrap();
+        let mut queue_filters = player.get_queue_filters().clone();
+        queue_filters.excluded_genres = if queue_filters.excluded_genres.is_empty() {
+            genres.iter().map(|genre| genre.to_lowercase()).collect()
+        } else {
+            HashSet::new()
+        };
+        let is_excluding_genres = !queue_filters.excluded_genres.is_empty();
+        player.set_queue_filters(queue_filters);
+        drop(player);
+
+        let message = if is_excluding_genres {
+            tr_with(Message::SkippingGenres, &[("genres", &genres.join(", "))])
+        } else {
+            String::from(tr(Message::PlayingAllGenres))
+        };
+        self.show_osd(OsdContent::Message(message));
+    }
+
+    /// Cycles the audio quality settings, skipping any that the user's subscription can't stream.
     fn cycle_audio_quality(&mut self) -> Result<(), Box<dyn Error>> {
-        match self.session.get_audio_quality() {
-            AudioQuality::Low96 => self.session.set_audio_quality(AudioQuality::Low320)?,
-            AudioQuality::Low320 => self.session.set_audio_quality(AudioQuality::High)?,
-            AudioQuality::High => self.session.set_audio_quality(AudioQuality::Max)?,
-            AudioQuality::Max => self.session.set_audio_quality(AudioQuality::Low96)?,
+        let next_quality = match self.session.get_audio_quality() {
+            AudioQuality::Low96 => AudioQuality::Low320,
+            AudioQuality::Low320 => AudioQuality::High,
+            AudioQuality::High => AudioQuality::Max,
+            AudioQuality::Max => AudioQuality::Low96,
+        };
+
+        let highest_quality = self.user.get_cached_subscription()
+            .map_or(AudioQuality::Max, |subscription| subscription.highest_quality);
+
+        if next_quality > highest_quality {
+            self.session.set_audio_quality(AudioQuality::Low96)?;
+        } else {
+            self.session.set_audio_quality(next_quality)?;
         }
 
+        let quality = self.session.get_audio_quality().to_string();
+        self.show_osd(OsdContent::Message(tr_with(Message::Quality, &[("quality", &quality)])));
+
         Ok(())
     }
 }
 
-/// Formats a `Duration` into a `String` for displaying.
-fn format_duration(duration: Duration) -> String {
-    format!("{}:{:02}", (duration.as_secs_f64().round() as u64) / 60, (duration.as_secs_f64().round() as u64) % 60)
+/// Country code used for the session when the `unofficial` feature is disabled.
+const DEFAULT_COUNTRY_CODE: &str = "CA";
+
+/// Returns the path of the directory where the config and session files are stored.
+pub fn get_config_path() -> Result<String, Box<dyn Error>> {
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))?;
+
+    Ok(format!("{}/.config/tidal-tui", home))
+}
+
+/// Returns the path of the directory where files that can be fetched again, like covers, are stored:
+/// in `$XDG_CACHE_HOME` if it is set, and in `~/.cache` otherwise.
+pub fn get_cache_path() -> Result<String, Box<dyn Error>> {
+    if let Ok(cache_home) = env::var("XDG_CACHE_HOME") && !cache_home.is_empty() {
+        return Ok(format!("{}/tidal-tui", cache_home));
+    }
+
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))?;
+
+    Ok(format!("{}/.cache/tidal-tui", home))
+}
+
+/// How often the session's access token is checked by the keep-alive thread.
+const SESSION_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The keep-alive thread refreshes the session's access token when it expires within this long.
+const SESSION_KEEP_ALIVE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// How often Tidal is checked for while it can't be reached, so that the app notices when it is back online.
+const RECONNECT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Returns a new logged in `Session`, using the client credentials from the environment.
+///
+/// The saved session is restored if it can be. Otherwise `before_login` is called before the user is asked to log in
+/// in the terminal, so that the terminal can be made ready for it.
+/// 
+/// The session is kept alive by a background thread, so that its access token doesn't expire during long listening sessions.
+pub fn init_session(config_path: &str, before_login: impl FnOnce()) -> Result<Arc<Session>, Box<dyn Error>> {
+    dotenv().ok();
+
+    let client_id = env::var("TIDAL_CLIENT_ID")?;
+    let client_secret = env::var("TIDAL_CLIENT_SECRET")?;
+    let session = match Session::restore(&client_id, &client_secret, DEFAULT_COUNTRY_CODE, config_path) {
+        Ok(session) => Arc::new(session),
+        Err(_) => {
+            before_login();
+            Arc::new(Session::new(&client_id, &client_secret, DEFAULT_COUNTRY_CODE, config_path)?)
+        },
+    };
+
+    let session_clone = Arc::clone(&session);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(SESSION_KEEP_ALIVE_INTERVAL);
+
+            // A failed refresh is retried on the next check, or by the next request that needs the token.
+            let _ = session_clone.keep_alive(SESSION_KEEP_ALIVE_WINDOW);
+        }
+    });
+
+    // Nothing else may be sent while offline (e.g. while paused), so check for a connection on a timer.
+    let session_clone = Arc::clone(&session);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(RECONNECT_CHECK_INTERVAL);
+
+            if !session_clone.is_online() {
+                session_clone.check_connection();
+            }
+        }
+    });
+
+    Ok(session)
+}
+
+/// Returns true if the app is running over SSH, where the terminal is on another machine.
+pub fn is_ssh_session() -> bool {
+    env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Fetches the tracks in `user`'s collection, calling `on_progress` with how many have been fetched out of the total
+/// as they come in.
+#[cfg(feature = "unofficial")]
+fn fetch_collection_tracks<F>(user: &User, on_progress: F) -> Result<Vec<Track>, String>
+where
+    F: FnMut(usize, usize),
+{
+    user.get_collection_tracks_with_progress(on_progress).map(|tracks| tracks.to_vec())
+}
+
+/// My Collection can only be fetched with the unofficial API, so this always fails without it.
+#[cfg(not(feature = "unofficial"))]
+fn fetch_collection_tracks<F>(_user: &User, _on_progress: F) -> Result<Vec<Track>, String>
+where
+    F: FnMut(usize, usize),
+{
+    Err(String::from(tr(Message::NeedsUnofficialApi)))
+}
+
+/// Fetches the radio of `track`, or of its artist if `from_artist` is true, along with the name it is shown with.
+#[cfg(feature = "unofficial")]
+fn fetch_radio(track: &Track, from_artist: bool) -> Result<(Vec<Track>, String), String> {
+    if from_artist {
+        let artist = track.get_artist()?;
+        Ok((artist.get_radio()?, artist.attributes.name.clone()))
+    } else {
+        let title = track.get_attribtues()?.get_full_title();
+        Ok((track.get_radio()?, title))
+    }
+}
+
+/// Radios can only be fetched with the unofficial API, so this always fails without it.
+#[cfg(not(feature = "unofficial"))]
+fn fetch_radio(_track: &Track, _from_artist: bool) -> Result<(Vec<Track>, String), String> {
+    Err(String::from(tr(Message::NeedsUnofficialApi)))
+}
+
+/// Fetches `user`'s playlists, which are cached in `user` for the add to playlist menu.
+#[cfg(feature = "unofficial")]
+fn fetch_playlists(user: &User) -> Result<(), String> {
+    user.get_playlists().map(|_| ())
+}
+
+/// Playlists can only be fetched with the unofficial API, so this always fails without it.
+#[cfg(not(feature = "unofficial"))]
+fn fetch_playlists(_user: &User) -> Result<(), String> {
+    Err(String::from(tr(Message::NeedsUnofficialApi)))
+}
+
+/// Asks the terminal to bring its window to the front (the xterm "raise window" sequence).
+///
+/// Terminals that don't support it ignore the request.
+fn raise_terminal() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(b"\x1b[5t")?;
+    stdout.flush()
 }
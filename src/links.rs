@@ -0,0 +1,157 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// Something on Tidal that can be opened from an id or a share link.
+///
+/// In the config file, links are written as share links or bare ids, the same as when opening one.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(try_from = "String")]
+pub enum TidalLink {
+    /// A track, by track id. Only share links (e.g. `https://tidal.com/browse/track/<id>` or `tidal://track/<id>`)
+    /// are tracks, since bare numeric ids are users.
+    Track(String),
+    /// A user's public profile, by user id.
+    User(String),
+    /// A playlist, by playlist id (a UUID).
+    Playlist(String),
+    /// An album, by album id. Only share links are albums, like tracks.
+    Album(String),
+    /// An artist, by artist id. Only share links are artists, like tracks.
+    Artist(String),
+}
+
+impl TidalLink {
+    /// Parses a Tidal share link (e.g. `https://tidal.com/browse/playlist/<id>` or `https://tidal.com/user/<id>`),
+    /// or a bare id, in which case numeric ids are users and UUIDs are playlists.
+    ///
+    /// Returns `None` if `input` isn't a link to a track, user, playlist, album, or artist.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+
+        if input.contains('/') {
+            // Ignore any query string (e.g. "?u") that share links sometimes have.
+            let path = input.split(['?', '#']).next()?;
+            let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+
+            while let Some(segment) = segments.next() {
+                match segment {
+                    "track" => return segments.next().filter(|id| is_track_id(id)).map(|id| Self::Track(id.to_string())),
+                    "user" | "profile" => return segments.next().filter(|id| is_user_id(id)).map(|id| Self::User(id.to_string())),
+                    "playlist" => return segments.next().filter(|id| is_playlist_id(id)).map(|id| Self::Playlist(id.to_string())),
+                    "album" => return segments.next().filter(|id| is_track_id(id)).map(|id| Self::Album(id.to_string())),
+                    "artist" => return segments.next().filter(|id| is_track_id(id)).map(|id| Self::Artist(id.to_string())),
+                    _ => {},
+                }
+            }
+
+            return None;
+        }
+
+        if is_user_id(input) {
+            Some(Self::User(input.to_string()))
+        } else if is_playlist_id(input) {
+            Some(Self::Playlist(input.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl TryFrom<String> for TidalLink {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| format!("\"{value}\" isn't a link to a track, user, playlist, album, or artist"))
+    }
+}
+
+impl fmt::Display for TidalLink {
+    /// Writes this as a share link, which `parse` reads back.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, id) = match self {
+            Self::Track(id) => ("track", id),
+            Self::User(id) => ("user", id),
+            Self::Playlist(id) => ("playlist", id),
+            Self::Album(id) => ("album", id),
+            Self::Artist(id) => ("artist", id),
+        };
+
+        write!(f, "https://tidal.com/browse/{kind}/{id}")
+    }
+}
+
+/// Returns true if `id` looks like a Tidal track (or album, or artist) id.
+fn is_track_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns true if `id` looks like a Tidal user id.
+fn is_user_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns true if `id` looks like a Tidal playlist id (e.g. `0f1e6d4c-8a54-4d1e-9d1b-3c3e2f1b7a90`).
+fn is_playlist_id(id: &str) -> bool {
+    let groups: Vec<&str> = id.split('-').collect();
+
+    groups.len() == 5
+        && groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups.iter().all(|group| group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYLIST_ID: &str = "0f1e6d4c-8a54-4d1e-9d1b-3c3e2f1b7a90";
+
+    #[test]
+    fn parses_bare_ids() {
+        assert_eq!(TidalLink::parse("184467302"), Some(TidalLink::User(String::from("184467302"))));
+        assert_eq!(TidalLink::parse(PLAYLIST_ID), Some(TidalLink::Playlist(String::from(PLAYLIST_ID))));
+    }
+
+    #[test]
+    fn parses_share_links() {
+        assert_eq!(
+            TidalLink::parse(&format!("https://tidal.com/browse/playlist/{PLAYLIST_ID}?u")),
+            Some(TidalLink::Playlist(String::from(PLAYLIST_ID))),
+        );
+        assert_eq!(
+            TidalLink::parse("https://listen.tidal.com/user/184467302"),
+            Some(TidalLink::User(String::from("184467302"))),
+        );
+        assert_eq!(
+            TidalLink::parse("https://tidal.com/browse/track/77646170?u"),
+            Some(TidalLink::Track(String::from("77646170"))),
+        );
+        assert_eq!(TidalLink::parse("tidal://track/77646170"), Some(TidalLink::Track(String::from("77646170"))));
+        assert_eq!(
+            TidalLink::parse("https://tidal.com/browse/album/77646168"),
+            Some(TidalLink::Album(String::from("77646168"))),
+        );
+        assert_eq!(TidalLink::parse("https://tidal.com/artist/3634"), Some(TidalLink::Artist(String::from("3634"))));
+    }
+
+    #[test]
+    fn writes_share_links() {
+        for link in [
+            TidalLink::Track(String::from("77646170")),
+            TidalLink::User(String::from("184467302")),
+            TidalLink::Playlist(String::from(PLAYLIST_ID)),
+            TidalLink::Album(String::from("77646168")),
+            TidalLink::Artist(String::from("3634")),
+        ] {
+            assert_eq!(TidalLink::parse(&link.to_string()), Some(link));
+        }
+    }
+
+    #[test]
+    fn rejects_other_links() {
+        assert_eq!(TidalLink::parse("https://tidal.com/browse/mix/0123abc"), None);
+        assert_eq!(TidalLink::parse("https://tidal.com/browse/playlist/not-a-playlist"), None);
+        assert_eq!(TidalLink::parse("radiohead"), None);
+        assert_eq!(TidalLink::parse(""), None);
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use rtidalapi::{
+    Lyrics,
+    Track,
+};
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// Lyrics fetched from Tidal, kept in memory by track id so that each track's are only fetched once.
+/// Clones share the same lyrics.
+#[derive(Clone, Default)]
+pub struct LyricsCache {
+    lyrics: Arc<Mutex<HashMap<String, LyricsState>>>,
+}
+
+/// Whether a track's lyrics have been fetched.
+#[derive(Clone)]
+pub enum LyricsState {
+    Loading,
+    Loaded(Arc<Lyrics>),
+    /// The track has no lyrics, or they couldn't be fetched. They aren't tried again until the app is restarted.
+    Missing,
+}
+
+impl LyricsCache {
+    /// Returns the lyrics of `track`, and starts fetching them if they haven't been.
+    ///
+    /// A rerender is sent through `tx` once they have been fetched.
+    pub fn get(&self, track: &Arc<Track>, tx: &mpsc::Sender<AppEvent>) -> LyricsState {
+        let mut unlocked_lyrics = self.lyrics.lock().unwrap();
+
+        if let Some(state) = unlocked_lyrics.get(&track.id) {
+            return state.clone();
+        }
+        unlocked_lyrics.insert(track.id.clone(), LyricsState::Loading);
+        drop(unlocked_lyrics);
+
+        let cache = self.clone();
+        let track = Arc::clone(track);
+        let tx_clone = tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let state = fetch_lyrics(&track);
+            cache.lyrics.lock().unwrap().insert(track.id.clone(), state);
+
+            let _ = tx_clone.try_send(AppEvent::ReRender);
+        });
+
+        LyricsState::Loading
+    }
+}
+
+/// Fetches the lyrics of `track`, which are missing if it has none or they couldn't be fetched.
+#[cfg(feature = "unofficial")]
+fn fetch_lyrics(track: &Track) -> LyricsState {
+    match track.get_lyrics() {
+        Ok(lyrics) if !lyrics.lines.is_empty() => LyricsState::Loaded(Arc::new(lyrics)),
+        _ => LyricsState::Missing,
+    }
+}
+
+/// Lyrics can only be fetched with the unofficial API, so they are always missing without it.
+#[cfg(not(feature = "unofficial"))]
+fn fetch_lyrics(_track: &Track) -> LyricsState {
+    LyricsState::Missing
+}
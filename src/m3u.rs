@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use crate::links::TidalLink;
+
+/// A track to write to an M3U playlist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct M3uEntry {
+    pub track_id: String,
+    /// The title shown for the track (e.g. "Artist - Title"), if known.
+    pub title: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+/// Returns an extended M3U playlist of `entries`, where each track is a Tidal share link.
+pub fn to_m3u(entries: &[M3uEntry]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+
+    for entry in entries {
+        if let Some(title) = &entry.title {
+            // Players use -1 for an unknown duration.
+            let duration = entry.duration.map_or(-1, |duration| duration.as_secs() as i64);
+            m3u.push_str(&format!("#EXTINF:{duration},{title}\n"));
+        }
+        m3u.push_str(&track_url(&entry.track_id));
+        m3u.push('\n');
+    }
+
+    m3u
+}
+
+/// Returns the ids of every track linked to in an M3U playlist, in order, skipping anything that isn't a Tidal track link.
+pub fn parse_track_ids(m3u: &str) -> Vec<String> {
+    m3u.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match TidalLink::parse(line) {
+            Some(TidalLink::Track(id)) => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the share link of the track with id `track_id`.
+fn track_url(track_id: &str) -> String {
+    format!("https://tidal.com/browse/track/{track_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_extended_m3u() {
+        let entries = [
+            M3uEntry {
+                track_id: String::from("77646170"),
+                title: Some(String::from("Nine Inch Nails - Hurt")),
+                duration: Some(Duration::from_secs(373)),
+            },
+            M3uEntry {
+                track_id: String::from("1234"),
+                title: None,
+                duration: None,
+            },
+        ];
+
+        assert_eq!(
+            to_m3u(&entries),
+            "#EXTM3U\n\
+             #EXTINF:373,Nine Inch Nails - Hurt\n\
+             https://tidal.com/browse/track/77646170\n\
+             https://tidal.com/browse/track/1234\n",
+        );
+    }
+
+    #[test]
+    fn reads_track_ids() {
+        let m3u = "#EXTM3U\n\
+                   #EXTINF:373,Nine Inch Nails - Hurt\n\
+                   https://tidal.com/browse/track/77646170\n\
+                   \n\
+                   /home/me/Music/local.flac\n\
+                   tidal://track/1234\r\n";
+
+        assert_eq!(parse_track_ids(m3u), vec!["77646170", "1234"]);
+    }
+
+    #[test]
+    fn round_trips() {
+        let entries = [M3uEntry {
+            track_id: String::from("77646170"),
+            title: Some(String::from("Nine Inch Nails - Hurt")),
+            duration: None,
+        }];
+
+        assert_eq!(parse_track_ids(&to_m3u(&entries)), vec!["77646170"]);
+    }
+}
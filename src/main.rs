@@ -1,39 +1,199 @@
-use std::process;
+use std::{
+    env,
+    process,
+    time::Duration,
+};
 
-use color_eyre::Result;
+use color_eyre::{
+    eyre::eyre,
+    Result,
+};
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyModifiers,
+};
+use ratatui::DefaultTerminal;
+use tokio::task::JoinHandle;
 
-use tidal_tui::App;
+use tidal_tui::{
+    cli::{
+        Command,
+        PlayArgs,
+        StatusFormat,
+        USAGE,
+    },
+    diagnostics,
+    headless,
+    ipc::{
+        self,
+        IpcCommand,
+    },
+    scrobbler,
+    shutdown,
+    startup::{
+        StartupProfile,
+        TerminalHandoff,
+    },
+    ui::ConnectingScreen,
+    App,
+};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
+
+    let command = Command::parse(env::args().skip(1))
+        .unwrap_or_else(|e| {
+            eprintln!("{e}\n\n{USAGE}");
+            process::exit(2);
+        });
     
-    #[cfg(not(target_os = "macos"))]
-    return run_tui().await;
+    #[cfg(not(all(target_os = "macos", feature = "media-controls")))]
+    return run(command).await;
 
-    #[cfg(target_os = "macos")]
-    return run_macos().await;
+    #[cfg(all(target_os = "macos", feature = "media-controls"))]
+    return run_macos(command).await;
 }
 
-async fn run_tui() -> Result<()> {
-    let mut app = tokio::task::spawn_blocking(|| {
-        App::init()
-        .unwrap_or_else(|e| {
+async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Tui { profile_startup } => run_tui(profile_startup).await,
+        Command::Play(args) => run_headless(args).await,
+        Command::Diagnostics => run_diagnostics().await,
+        Command::Status(format) => run_status(format).await,
+        Command::Ctl(command) => run_ctl(command).await,
+        Command::LastfmLogin => scrobbler::log_in_to_lastfm().await.map_err(|e| eyre!(e)),
+        Command::Help => {
+            println!("{USAGE}");
+            Ok(())
+        },
+    }
+}
+
+async fn run_tui(profile_startup: bool) -> Result<()> {
+    // Two instances would fight over the audio device and the session files, so the running one is brought forward instead.
+    if ipc::is_running() {
+        let _ = ipc::send(IpcCommand::Raise);
+        eprintln!(
+            "tidal-tui is already running (listening on {}). Use `tidal-tui ctl` or `tidal-tui play` to control it.",
+            ipc::get_socket_path().display(),
+        );
+        process::exit(1);
+    }
+
+    let profile = StartupProfile::start();
+    let handoff = TerminalHandoff::default();
+    let mut terminal = ratatui::init();
+
+    let profile_clone = profile.clone();
+    let handoff_clone = handoff.clone();
+    let init = tokio::task::spawn_blocking(move || {
+        App::init(&profile_clone, move || handoff_clone.take()).map_err(|e| e.to_string())
+    });
+    let was_handed_over = show_connecting_screen(&mut terminal, &init, &profile, &handoff)?;
+    let mut app = match init.await? {
+        Ok(app) => app,
+        Err(e) => {
+            ratatui::restore();
             println!("{e}");
             process::exit(1);
-        })
-    }).await?;
-    let mut terminal = ratatui::init();
+        },
+    };
+    if was_handed_over {
+        terminal = ratatui::init();
+    }
+
+    shutdown::listen_for_signals(app.get_shutdown_flag())?;
+    app.enable_keyboard_enhancement()?;
+    profile.mark("Start the main loop");
     let result = app.run(&mut terminal);
+    // The terminal is restored even if the keyboard enhancement can't be turned off, like when it was closed.
+    let keyboard_result = app.disable_keyboard_enhancement();
     ratatui::restore();
-    result
+
+    if profile_startup {
+        print!("{}", profile.get_report());
+    }
+    result.and(keyboard_result)
+}
+
+/// Shows that the app is connecting until `init` has finished, so that launching it doesn't leave the terminal blank
+/// while it logs in. Pressing `ctrl+c` in the meantime closes it.
+///
+/// Returns true if the terminal was restored and handed over through `handoff` for the user to log in again,
+/// in which case it has to be set up again for the app.
+fn show_connecting_screen<T>(
+    terminal: &mut DefaultTerminal,
+    init: &JoinHandle<T>,
+    profile: &StartupProfile,
+    handoff: &TerminalHandoff,
+) -> Result<bool> {
+    profile.time("Show the connecting screen", || terminal.draw(|f| f.render_widget(ConnectingScreen::new(profile.elapsed()), f.area())))?;
+
+    while !init.is_finished() {
+        if handoff.is_requested() {
+            ratatui::restore();
+            handoff.hand_over();
+            return Ok(true);
+        }
+
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.code == KeyCode::Char('c')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            ratatui::restore();
+            process::exit(130);
+        }
+
+        terminal.draw(|f| f.render_widget(ConnectingScreen::new(profile.elapsed()), f.area()))?;
+    }
+
+    Ok(false)
+}
+
+async fn run_headless(args: PlayArgs) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        // What to play is handed off to the running instance, rather than playing over it.
+        if ipc::is_running() {
+            ipc::hand_off(&args).map_err(|e| e.to_string())?;
+            println!("Playing in the running instance");
+            return Ok(());
+        }
+
+        headless::play(&args).map_err(|e| e.to_string())
+    }).await?
+        .map_err(|e| eyre!(e))
+}
+
+async fn run_diagnostics() -> Result<()> {
+    tokio::task::spawn_blocking(|| {
+        diagnostics::print_report().map_err(|e| e.to_string())
+    }).await?
+        .map_err(|e| eyre!(e))
+}
+
+async fn run_status(format: StatusFormat) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        ipc::print_status(&format).map_err(|e| e.to_string())
+    }).await?
+        .map_err(|e| eyre!(e))
+}
+
+async fn run_ctl(command: IpcCommand) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        ipc::send(command).map(|_| ()).map_err(|e| e.to_string())
+    }).await?
+        .map_err(|e| eyre!(e))
 }
 
 /// On macOS, souvlaki's media controls require AppKit's event loop to be
 /// running on the main thread. We pump a headless winit event loop here
 /// to satisfy that requirement, while the TUI runs on a Tokio worker thread.
-#[cfg(target_os = "macos")]
-async fn run_macos() -> Result<()> {
+#[cfg(all(target_os = "macos", feature = "media-controls"))]
+async fn run_macos(command: Command) -> Result<()> {
     use winit::application::ApplicationHandler;
     use winit::event::WindowEvent;
     use winit::event_loop::{ActiveEventLoop, EventLoop};
@@ -44,7 +204,7 @@ async fn run_macos() -> Result<()> {
     let proxy = event_loop.create_proxy();
 
     tokio::spawn(async move {
-        run_tui().await.unwrap_or_else(|e| eprintln!("{e}"));
+        run(command).await.unwrap_or_else(|e| eprintln!("{e}"));
         let _ = proxy.send_event(());
     });
 
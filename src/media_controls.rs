@@ -0,0 +1,242 @@
+use std::{
+    error::Error,
+    time::Duration,
+};
+
+/// A playback position reported to or requested by the OS media controls.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MediaPosition(pub Duration);
+
+/// Which way to seek.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SeekDirection {
+    Forward,
+    Backward,
+}
+
+/// An event sent by the OS media controls (e.g. from media keys or a desktop widget).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaControlEvent {
+    Play,
+    Pause,
+    Toggle,
+    Next,
+    Previous,
+    Stop,
+    SeekBy(SeekDirection, Duration),
+    SetPosition(MediaPosition),
+    /// Asks for the player's window to be brought to the front.
+    Raise,
+    /// Asks for the track or playlist at a URI (e.g. a Tidal share link) to be queued.
+    OpenUri(String),
+}
+
+/// The playback state reported to the OS media controls.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaPlayback {
+    Stopped,
+    Paused { progress: Option<MediaPosition> },
+    Playing { progress: Option<MediaPosition> },
+}
+
+/// Metadata of the current track reported to the OS media controls.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaMetadata<'a> {
+    pub title: Option<&'a str>,
+    pub album: Option<&'a str>,
+    pub artist: Option<&'a str>,
+    pub cover_url: Option<&'a str>,
+    pub duration: Option<Duration>,
+}
+
+/// Integration with the OS media controls (MPRIS on Linux, Now Playing on macOS, SMTC on Windows).
+///
+/// Every method defaults to a no-op, so builds without the `media-controls` feature can use `NoopMediaControls`.
+pub trait MediaControls: Send {
+    /// Registers `handler` to be called with every event sent by the OS media controls.
+    fn attach(&mut self, _handler: Box<dyn Fn(MediaControlEvent) + Send>) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Reports the current playback state.
+    fn set_playback(&mut self, _playback: MediaPlayback) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Reports that playback jumped to `position` (e.g. after a seek), so that desktop widgets can show it right away
+    /// rather than waiting for the next progress update.
+    fn set_position(&mut self, position: MediaPosition, is_playing: bool) -> Result<(), Box<dyn Error>> {
+        let progress = Some(position);
+
+        if is_playing {
+            self.set_playback(MediaPlayback::Playing { progress })
+        } else {
+            self.set_playback(MediaPlayback::Paused { progress })
+        }
+    }
+
+    /// Reports the current track's metadata.
+    fn set_metadata(&mut self, _metadata: MediaMetadata) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Stops handling events from the OS media controls and removes this player from them.
+    fn detach(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Media controls that do nothing, used when the `media-controls` feature is disabled.
+pub struct NoopMediaControls;
+
+impl MediaControls for NoopMediaControls {}
+
+/// Returns the media controls for this platform, or `NoopMediaControls` if the `media-controls` feature is disabled.
+pub fn new_media_controls() -> Result<Box<dyn MediaControls>, Box<dyn Error>> {
+    #[cfg(feature = "media-controls")]
+    return Ok(Box::new(os::OsMediaControls::new()?));
+
+    #[cfg(not(feature = "media-controls"))]
+    return Ok(Box::new(NoopMediaControls));
+}
+
+#[cfg(feature = "media-controls")]
+mod os {
+    use std::error::Error;
+
+    use souvlaki::PlatformConfig;
+
+    use super::{
+        MediaControlEvent,
+        MediaControls,
+        MediaMetadata,
+        MediaPlayback,
+        MediaPosition,
+        SeekDirection,
+    };
+
+    /// OS media controls backed by souvlaki.
+    pub struct OsMediaControls {
+        controls: souvlaki::MediaControls,
+
+        #[cfg(target_os = "windows")]
+        /// Keeps the hidden window alive for the lifetime of the media controls.
+        _hwnd_window: winit::window::Window,
+    }
+
+    impl OsMediaControls {
+        /// Returns new `OsMediaControls`.
+        pub fn new() -> Result<Self, Box<dyn Error>> {
+            #[cfg(not(target_os = "windows"))]
+            let hwnd = None;
+
+            #[cfg(target_os = "windows")]
+            let (hwnd, hwnd_window) = Self::init_windows_hwnd();
+
+            let platform_config = PlatformConfig {
+                dbus_name: "tidal-tui",
+                display_name: "tidal-tui",
+                hwnd,
+            };
+            let controls = souvlaki::MediaControls::new(platform_config)?;
+
+            Ok(Self {
+                controls,
+
+                #[cfg(target_os = "windows")]
+                _hwnd_window: hwnd_window,
+            })
+        }
+
+        /// Initializes an invisible window to allow Souvlaki to work on Windows.
+        #[cfg(target_os = "windows")]
+        fn init_windows_hwnd() -> (Option<*mut std::ffi::c_void>, winit::window::Window) {
+            use winit::event_loop::EventLoop;
+            use winit::platform::windows::EventLoopBuilderExtWindows;
+            use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+            use winit::window::Window;
+
+            let event_loop = EventLoop::builder()
+                .with_any_thread(true)
+                .build()
+                .unwrap();
+
+            let window = event_loop
+                .create_window(Window::default_attributes().with_visible(false))
+                .unwrap();
+
+            let hwnd = match window.window_handle().unwrap().as_raw() {
+                RawWindowHandle::Win32(handle) => handle.hwnd.get() as *mut std::ffi::c_void,
+                _ => panic!("not running on Windows"),
+            };
+
+            (Some(hwnd), window)
+        }
+    }
+
+    impl MediaControls for OsMediaControls {
+        fn attach(&mut self, handler: Box<dyn Fn(MediaControlEvent) + Send>) -> Result<(), Box<dyn Error>> {
+            self.controls.attach(move |event| {
+                let event = match event {
+                    souvlaki::MediaControlEvent::Play => MediaControlEvent::Play,
+                    souvlaki::MediaControlEvent::Pause => MediaControlEvent::Pause,
+                    souvlaki::MediaControlEvent::Toggle => MediaControlEvent::Toggle,
+                    souvlaki::MediaControlEvent::Next => MediaControlEvent::Next,
+                    souvlaki::MediaControlEvent::Previous => MediaControlEvent::Previous,
+                    souvlaki::MediaControlEvent::Stop => MediaControlEvent::Stop,
+                    souvlaki::MediaControlEvent::SeekBy(direction, amount) => {
+                        let direction = match direction {
+                            souvlaki::SeekDirection::Forward => SeekDirection::Forward,
+                            souvlaki::SeekDirection::Backward => SeekDirection::Backward,
+                        };
+                        MediaControlEvent::SeekBy(direction, amount)
+                    },
+                    souvlaki::MediaControlEvent::SetPosition(souvlaki::MediaPosition(position)) => {
+                        MediaControlEvent::SetPosition(MediaPosition(position))
+                    },
+                    souvlaki::MediaControlEvent::Raise => MediaControlEvent::Raise,
+                    souvlaki::MediaControlEvent::OpenUri(uri) => MediaControlEvent::OpenUri(uri),
+                    _ => return,
+                };
+
+                handler(event);
+            })?;
+
+            Ok(())
+        }
+
+        fn set_playback(&mut self, playback: MediaPlayback) -> Result<(), Box<dyn Error>> {
+            let to_souvlaki_position = |progress: Option<MediaPosition>| {
+                progress.map(|MediaPosition(position)| souvlaki::MediaPosition(position))
+            };
+
+            let playback = match playback {
+                MediaPlayback::Stopped => souvlaki::MediaPlayback::Stopped,
+                MediaPlayback::Paused { progress } => souvlaki::MediaPlayback::Paused { progress: to_souvlaki_position(progress) },
+                MediaPlayback::Playing { progress } => souvlaki::MediaPlayback::Playing { progress: to_souvlaki_position(progress) },
+            };
+
+            self.controls.set_playback(playback)?;
+
+            Ok(())
+        }
+
+        fn set_metadata(&mut self, metadata: MediaMetadata) -> Result<(), Box<dyn Error>> {
+            self.controls.set_metadata(souvlaki::MediaMetadata {
+                title: metadata.title,
+                album: metadata.album,
+                artist: metadata.artist,
+                cover_url: metadata.cover_url,
+                duration: metadata.duration,
+            })?;
+
+            Ok(())
+        }
+
+        fn detach(&mut self) -> Result<(), Box<dyn Error>> {
+            self.controls.detach()?;
+
+            Ok(())
+        }
+    }
+}
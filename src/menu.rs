@@ -0,0 +1,206 @@
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+use ratatui::widgets::ListState;
+use rtidalapi::Track;
+
+use crate::{
+    diagnostics::Check,
+    i18n::{
+        tr,
+        Message,
+    },
+};
+
+/// A popup menu open over the main pane.
+pub enum Menu {
+    /// What can be done with a track.
+    Track(TrackMenu),
+    /// Choosing which of the user's playlists to add a track to.
+    AddToPlaylist(AddToPlaylistMenu),
+    /// The blocked tracks and artists, where selecting one unblocks it.
+    Blocklist(ListState),
+    /// Asking whether to replace a queue that hasn't finished with something else.
+    ReplaceQueue(ReplaceQueueMenu),
+    /// Queues that were replaced, where selecting one restores it.
+    PreviousQueues(ListState),
+    /// The results of the diagnostic checks, where selecting any copies the report.
+    Diagnostics(DiagnosticsMenu),
+    /// What has been played since the app was started.
+    SessionStats(ListState),
+    /// What is kept on disk and how much it takes up, where selecting one purges it.
+    Caches(ListState),
+    /// The pages in the config, where selecting one opens it.
+    Pages(ListState),
+    /// The commands added by scripts, where selecting one runs it.
+    ScriptCommands(ListState),
+    /// Every keybind, grouped by where it can be used.
+    Help(ListState),
+    /// A view opened by a script.
+    Script(ScriptMenu),
+}
+
+/// A menu of what can be done with a track.
+pub struct TrackMenu {
+    pub track: Arc<Track>,
+    /// The tracks listed from this one onward and what they are playing from, if it was opened from a list of tracks.
+    pub from_here: Option<(Vec<Arc<Track>>, String)>,
+    pub items: Vec<TrackMenuItem>,
+    pub list_state: ListState,
+}
+
+/// Something that can be done with a track from its menu.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TrackMenuItem {
+    /// Plays the track straight away, carrying on with the queue after it.
+    PlayNow,
+    /// Replaces the queue with the list the track was opened from, starting at the track.
+    PlayFromHere,
+    /// Plays the track after the current one.
+    PlayNext,
+    AddToQueue,
+    GoToAlbum,
+    GoToArtist,
+    AddToPlaylist,
+    AddToCollection,
+    RemoveFromCollection,
+}
+
+/// A popup opened by a script, showing lines of text.
+pub struct ScriptMenu {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub list_state: ListState,
+}
+
+/// A menu showing the results of the diagnostic checks.
+pub struct DiagnosticsMenu {
+    /// The results, which are `None` until every check has finished.
+    pub checks: Arc<Mutex<Option<Vec<Check>>>>,
+    pub list_state: ListState,
+}
+
+/// A menu asking whether to replace a queue that hasn't finished with something else.
+pub struct ReplaceQueueMenu {
+    /// What replaces the queue if it is confirmed.
+    pub replacement: QueueReplacement,
+    pub list_state: ListState,
+}
+
+/// Tracks that are about to replace the queue.
+pub enum QueueReplacement {
+    /// The main pane's tracks, shuffled if `shuffle` is true.
+    MainTracks {
+        shuffle: bool,
+    },
+    /// Tracks from somewhere else, like an album, a radio, a quick slot, or another instance.
+    Tracks {
+        tracks: Vec<Arc<Track>>,
+        playing_from: String,
+        shuffle: bool,
+        /// Whether Tidal chose the tracks, like a radio's, so that blocked and filtered ones are skipped.
+        is_auto_generated: bool,
+        /// The quick slot the tracks were swapped in from, if any.
+        slot: Option<usize>,
+    },
+}
+
+/// A menu for choosing which of the user's playlists to add a track to.
+pub struct AddToPlaylistMenu {
+    pub track: Arc<Track>,
+    pub list_state: ListState,
+    /// Set if the user's playlists couldn't be fetched.
+    pub error: Arc<Mutex<Option<String>>>,
+}
+
+impl TrackMenu {
+    /// Returns a menu for `track`, offering to remove it from My Collection if `is_favorite` is true,
+    /// or to add it otherwise.
+    ///
+    /// `from_here` is the list of tracks starting at `track` and what they are playing from,
+    /// which can then be played from it.
+    pub fn new(track: Arc<Track>, is_favorite: bool, from_here: Option<(Vec<Arc<Track>>, String)>) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+
+        let items = [
+            Some(TrackMenuItem::PlayNow),
+            from_here.as_ref().map(|_| TrackMenuItem::PlayFromHere),
+            Some(TrackMenuItem::PlayNext),
+            Some(TrackMenuItem::AddToQueue),
+            Some(TrackMenuItem::GoToAlbum),
+            Some(TrackMenuItem::GoToArtist),
+            Some(TrackMenuItem::AddToPlaylist),
+            Some(if is_favorite { TrackMenuItem::RemoveFromCollection } else { TrackMenuItem::AddToCollection }),
+        ];
+
+        Self {
+            track,
+            from_here,
+            items: items.into_iter().flatten().collect(),
+            list_state,
+        }
+    }
+
+    /// Returns the selected item.
+    pub fn get_selected(&self) -> Option<TrackMenuItem> {
+        self.list_state.selected().and_then(|index| self.items.get(index).copied())
+    }
+}
+
+impl TrackMenuItem {
+    /// Returns the name shown for this item.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::PlayNow => tr(Message::PlayNow),
+            Self::PlayFromHere => tr(Message::PlayFromHere),
+            Self::PlayNext => tr(Message::PlayNext),
+            Self::AddToQueue => tr(Message::AddToQueue),
+            Self::GoToAlbum => tr(Message::GoToAlbum),
+            Self::GoToArtist => tr(Message::GoToArtist),
+            Self::AddToPlaylist => tr(Message::AddToPlaylist),
+            Self::AddToCollection => tr(Message::AddToCollection),
+            Self::RemoveFromCollection => tr(Message::RemoveFromCollection),
+        }
+    }
+}
+
+impl Menu {
+    /// Returns the state of this menu's list.
+    pub fn get_list_state(&self) -> &ListState {
+        match self {
+            Self::Track(menu) => &menu.list_state,
+            Self::AddToPlaylist(menu) => &menu.list_state,
+            Self::Blocklist(list_state)
+            | Self::PreviousQueues(list_state)
+            | Self::SessionStats(list_state)
+            | Self::Caches(list_state)
+            | Self::Pages(list_state)
+            | Self::ScriptCommands(list_state)
+            | Self::Help(list_state) => list_state,
+            Self::ReplaceQueue(menu) => &menu.list_state,
+            Self::Diagnostics(menu) => &menu.list_state,
+            Self::Script(menu) => &menu.list_state,
+        }
+    }
+
+    /// Returns the state of this menu's list, for changing it.
+    pub fn get_list_state_mut(&mut self) -> &mut ListState {
+        match self {
+            Self::Track(menu) => &mut menu.list_state,
+            Self::AddToPlaylist(menu) => &mut menu.list_state,
+            Self::Blocklist(list_state)
+            | Self::PreviousQueues(list_state)
+            | Self::SessionStats(list_state)
+            | Self::Caches(list_state)
+            | Self::Pages(list_state)
+            | Self::ScriptCommands(list_state)
+            | Self::Help(list_state) => list_state,
+            Self::ReplaceQueue(menu) => &mut menu.list_state,
+            Self::Diagnostics(menu) => &mut menu.list_state,
+            Self::Script(menu) => &mut menu.list_state,
+        }
+    }
+}
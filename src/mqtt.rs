@@ -0,0 +1,112 @@
+use std::{
+    error::Error,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    config::MqttConfig,
+    player::Player,
+    AppEvent,
+};
+
+/// Connects to the MQTT broker in `config` from background threads, publishing the player's status whenever it
+/// changes and running the commands received on the command topic on `player`.
+///
+/// The connection is retried until it works, so this only fails if the broker's address is invalid.
+#[cfg(feature = "mqtt")]
+pub fn start(config: &MqttConfig, player: Arc<Mutex<Player>>, tx: mpsc::Sender<AppEvent>) -> Result<(), Box<dyn Error>> {
+    use std::{
+        thread,
+        time::Duration,
+    };
+
+    use rumqttc::{
+        Client,
+        Event,
+        LastWill,
+        MqttOptions,
+        Packet,
+        QoS,
+    };
+
+    use crate::{
+        ipc::IpcCommand,
+        status_server::PlayerStatus,
+    };
+
+    /// How often the player's status is checked for changes to publish.
+    const PUBLISH_INTERVAL: Duration = Duration::from_secs(1);
+    /// How long to wait before reconnecting after the connection fails.
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    // The broker marks the player as offline if the app exits without saying so, or the connection drops.
+    options.set_last_will(LastWill::new(&config.availability_topic, "offline", QoS::AtLeastOnce, true));
+
+    let (client, mut connection) = Client::new(options, 10);
+
+    let client_clone = client.clone();
+    let command_topic = config.command_topic.clone();
+    let availability_topic = config.availability_topic.clone();
+    let player_clone = Arc::clone(&player);
+    thread::spawn(move || {
+        for event in connection.iter() {
+            match event {
+                // Subscriptions don't outlive the session, so they are made again on every connection.
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    let _ = client_clone.try_subscribe(&command_topic, QoS::AtLeastOnce);
+                    let _ = client_clone.try_publish(&availability_topic, QoS::AtLeastOnce, true, "online");
+                },
+                Ok(Event::Incoming(Packet::Publish(publish))) if publish.topic == command_topic => {
+                    let name = String::from_utf8_lossy(&publish.payload);
+                    // Commands that fail (or aren't known) are ignored, since there is no one to reply to.
+                    if let Some(command) = IpcCommand::parse(name.trim()).filter(|command| *command != IpcCommand::Status) {
+                        let _ = command.run(&player_clone);
+                        let _ = tx.try_send(AppEvent::ReRender);
+                    }
+                },
+                Ok(_) => (),
+                // The next iteration reconnects, so wait a bit instead of retrying a broker that is down right away.
+                Err(_) => thread::sleep(RECONNECT_DELAY),
+            }
+        }
+    });
+
+    let state_topic = config.state_topic.clone();
+    thread::spawn(move || {
+        let mut last_state = String::new();
+
+        loop {
+            let state = player.lock()
+                .ok()
+                .and_then(|unlocked_player| serde_json::to_string(&PlayerStatus::from_player(&unlocked_player)).ok());
+
+            // Only publish changes, which are kept by the broker for new subscribers.
+            if let Some(state) = state
+                && state != last_state
+                && client.try_publish(&state_topic, QoS::AtLeastOnce, true, state.as_bytes()).is_ok()
+            {
+                last_state = state;
+            }
+
+            thread::sleep(PUBLISH_INTERVAL);
+        }
+    });
+
+    Ok(())
+}
+
+/// MQTT support was left out of this build, so the publisher can't be started.
+#[cfg(not(feature = "mqtt"))]
+pub fn start(_config: &MqttConfig, _player: Arc<Mutex<Player>>, _tx: mpsc::Sender<AppEvent>) -> Result<(), Box<dyn Error>> {
+    Err("MQTT is enabled in the config, but tidal-tui was built without the mqtt feature".into())
+}
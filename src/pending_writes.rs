@@ -0,0 +1,289 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+#[cfg(feature = "unofficial")]
+use rtidalapi::Track;
+use rtidalapi::{
+    Session,
+    User,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tokio::sync::mpsc;
+
+use crate::{
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+    ui::OsdContent,
+    AppEvent,
+};
+
+/// A change to the user's collection or playlists that couldn't be sent to Tidal yet.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PendingWrite {
+    /// Adds tracks to the end of one of the user's playlists.
+    AddToPlaylist {
+        playlist_id: String,
+        /// The playlist's name when the change was made, for messages.
+        playlist_name: String,
+        track_ids: Vec<String>,
+    },
+    /// Adds tracks to the user's collection.
+    AddFavoriteTracks {
+        track_ids: Vec<String>,
+    },
+    /// Removes tracks from the user's collection.
+    RemoveFavoriteTracks {
+        track_ids: Vec<String>,
+    },
+}
+
+/// Changes made while offline, or that failed because Tidal couldn't be reached,
+/// which are sent to Tidal in the order they were made once it can be reached again.
+///
+/// The changes are stored in `pending_writes.toml` within the config directory, so that they aren't lost when quitting.
+#[derive(Clone, Default)]
+pub struct PendingWrites {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+struct State {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    #[serde(default)]
+    writes: VecDeque<PendingWrite>,
+    /// True while writes are being sent.
+    #[serde(skip)]
+    is_flushing: bool,
+}
+
+impl PendingWrite {
+    /// Returns a short description of this change for messages (e.g. "Add 2 tracks to Road Trip").
+    pub fn describe(&self) -> String {
+        match self {
+            Self::AddToPlaylist { playlist_name, track_ids, .. } => {
+                tr_with(Message::PendingAddToPlaylist, &[("tracks", &describe_tracks(track_ids.len())), ("playlist", playlist_name)])
+            },
+            Self::AddFavoriteTracks { track_ids } => {
+                tr_with(Message::PendingAddToCollection, &[("tracks", &describe_tracks(track_ids.len()))])
+            },
+            Self::RemoveFavoriteTracks { track_ids } => {
+                tr_with(Message::PendingRemoveFromCollection, &[("tracks", &describe_tracks(track_ids.len()))])
+            },
+        }
+    }
+
+    /// Sends this change to Tidal.
+    ///
+    /// Playlists can only be fetched with the unofficial API, so adding to one always fails without it.
+    #[cfg_attr(not(feature = "unofficial"), allow(unused_variables))]
+    pub fn send(&self, session: &Arc<Session>, user: &User) -> Result<(), String> {
+        match self {
+            #[cfg(feature = "unofficial")]
+            Self::AddToPlaylist { playlist_id, playlist_name, track_ids } => {
+                let playlist = user.get_playlists()?
+                    .iter()
+                    .find(|playlist| playlist.id == *playlist_id)
+                    .ok_or_else(|| tr_with(Message::NoLongerYourPlaylist, &[("playlist", playlist_name)]))?;
+
+                let tracks = track_ids.iter()
+                    .map(|track_id| Track::new(Arc::clone(session), track_id.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                playlist.add_tracks(&tracks.iter().collect::<Vec<_>>())
+            },
+            #[cfg(not(feature = "unofficial"))]
+            Self::AddToPlaylist { .. } => Err(String::from(tr(Message::NeedsUnofficialApi))),
+            Self::AddFavoriteTracks { track_ids } => user.add_favorite_tracks(track_ids),
+            Self::RemoveFavoriteTracks { track_ids } => user.remove_favorite_tracks(track_ids),
+        }
+    }
+}
+
+impl PendingWrites {
+    const FILE_NAME: &str = "pending_writes.toml";
+
+    /// Loads the changes that haven't been sent from the config directory, or returns an empty queue if there are none.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let pending_writes_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut state = if pending_writes_file.exists() {
+            let toml_str = fs::read_to_string(&pending_writes_file)
+                .map_err(|e| format!("Unable to read {}: {e}", pending_writes_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", pending_writes_file.display()))?
+        } else {
+            State::default()
+        };
+
+        state.file = Some(pending_writes_file);
+
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Adds `write` to the end of the queue and saves the queue.
+    pub fn push(&self, write: PendingWrite) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.writes.push_back(write);
+        state.save()
+    }
+
+    /// Returns how many changes haven't been sent yet.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().writes.len()
+    }
+
+    /// Returns true if every change has been sent.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns how many bytes the saved changes take up on disk.
+    pub fn get_size(&self) -> u64 {
+        self.state.lock().unwrap()
+            .file
+            .as_ref()
+            .and_then(|pending_writes_file| fs::metadata(pending_writes_file).ok())
+            .map_or(0, |metadata| metadata.len())
+    }
+
+    /// Drops every change that hasn't been sent yet, and saves the empty queue.
+    pub fn clear(&self) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.writes.clear();
+        state.save()
+    }
+
+    /// Sends the queued changes in the background, one at a time, unless they are already being sent.
+    ///
+    /// If Tidal can't be reached, sending stops and the rest are kept to be tried again.
+    /// Changes that Tidal rejects are dropped, since sending them again wouldn't help.
+    /// The result is shown in the OSD through `tx`.
+    pub fn flush(&self, session: Arc<Session>, user: Arc<User>, tx: mpsc::Sender<AppEvent>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.is_flushing || state.writes.is_empty() {
+                return;
+            }
+            state.is_flushing = true;
+        }
+
+        let state = Arc::clone(&self.state);
+        tokio::task::spawn_blocking(move || {
+            let mut sent = 0;
+            let mut error = None;
+
+            loop {
+                let Some(write) = state.lock().unwrap().writes.front().cloned() else {
+                    break;
+                };
+
+                let result = write.send(&session, &user);
+                if let Err(e) = &result {
+                    error = Some(tr_with(Message::SyncFailed, &[("change", &write.describe()), ("error", e)]));
+                    if !session.is_online() {
+                        break;
+                    }
+                }
+
+                let mut state = state.lock().unwrap();
+                state.writes.pop_front();
+                // The change was sent, so saving failing only means that it might be sent again after a restart.
+                let _ = state.save();
+                drop(state);
+
+                if result.is_ok() {
+                    sent += 1;
+                }
+            }
+
+            state.lock().unwrap().is_flushing = false;
+
+            let message = match error {
+                Some(message) => message,
+                None if sent == 1 => String::from(tr(Message::SyncedChange)),
+                None => tr_with(Message::SyncedChanges, &[("count", &sent.to_string())]),
+            };
+            let _ = tx.blocking_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+        });
+    }
+}
+
+impl State {
+    /// Saves the queue to the file it was loaded from.
+    fn save(&self) -> Result<(), String> {
+        let Some(pending_writes_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the pending changes: {e}"))?;
+
+        fs::write(pending_writes_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", pending_writes_file.display()))
+    }
+}
+
+/// Returns "1 track" or "N tracks", in the language of the UI.
+fn describe_tracks(count: usize) -> String {
+    format!("{count} {}", tr(if count == 1 { Message::TrackCountOne } else { Message::TrackCountOther }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_writes() {
+        let write = PendingWrite::AddToPlaylist {
+            playlist_id: String::from("7c2b0a61"),
+            playlist_name: String::from("Road Trip"),
+            track_ids: vec![String::from("77646170")],
+        };
+        assert_eq!(write.describe(), "Add 1 track to Road Trip");
+
+        let write = PendingWrite::AddFavoriteTracks { track_ids: vec![String::new(); 3] };
+        assert_eq!(write.describe(), "Add 3 tracks to My Collection");
+
+        let write = PendingWrite::RemoveFavoriteTracks { track_ids: vec![String::new()] };
+        assert_eq!(write.describe(), "Remove 1 track from My Collection");
+    }
+
+    #[test]
+    fn round_trips_through_toml() {
+        let state = State {
+            writes: VecDeque::from([
+                PendingWrite::AddToPlaylist {
+                    playlist_id: String::from("7c2b0a61"),
+                    playlist_name: String::from("Road Trip"),
+                    track_ids: vec![String::from("77646170")],
+                },
+                PendingWrite::AddFavoriteTracks { track_ids: vec![String::from("1234")] },
+            ]),
+            ..Default::default()
+        };
+
+        let toml_str = toml::to_string(&state).unwrap();
+        assert_eq!(toml::from_str::<State>(&toml_str).unwrap(), state);
+    }
+}
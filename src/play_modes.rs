@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// How each context (My Collection, a playlist, Rediscover) was last played, so that replaying it plays it the same way.
+///
+/// The modes are stored in `play_modes.toml` within the config directory.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PlayModes {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    /// The mode last used for each context, by its key (e.g. `playlist:{id}`).
+    #[serde(default)]
+    contexts: BTreeMap<String, PlayMode>,
+}
+
+/// How the tracks of a context are played.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PlayMode {
+    pub shuffle: bool,
+}
+
+impl PlayModes {
+    /// Name of the play modes file within the config directory.
+    const FILE_NAME: &str = "play_modes.toml";
+
+    /// Loads the play modes from `config_folder_path`, which is empty if nothing has been played yet.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let modes_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut modes = if modes_file.exists() {
+            let toml_str = fs::read_to_string(&modes_file)
+                .map_err(|e| format!("Unable to read {}: {e}", modes_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", modes_file.display()))?
+        } else {
+            Self::default()
+        };
+
+        modes.file = Some(modes_file);
+
+        Ok(modes)
+    }
+
+    /// Saves the play modes to the file they were loaded from.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(modes_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the play modes: {e}"))?;
+
+        fs::write(modes_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", modes_file.display()))
+    }
+
+    /// Returns the mode the context with `key` was last played in, or the default (in order) if it hasn't been played.
+    pub fn get(&self, key: &str) -> PlayMode {
+        self.contexts.get(key).copied().unwrap_or_default()
+    }
+
+    /// Records that the context with `key` was played in `mode`.
+    pub fn set(&mut self, key: &str, mode: PlayMode) {
+        // Contexts played in order are left out, since that is the default.
+        if mode == PlayMode::default() {
+            self.contexts.remove(key);
+        } else {
+            self.contexts.insert(key.to_string(), mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_modes_by_context() {
+        let mut modes = PlayModes::default();
+        modes.set("playlist:7f2c", PlayMode { shuffle: true });
+        modes.set("collection", PlayMode { shuffle: false });
+
+        let toml_str = toml::to_string(&modes).unwrap();
+        let modes: PlayModes = toml::from_str(&toml_str).unwrap();
+
+        assert_eq!(modes.get("playlist:7f2c"), PlayMode { shuffle: true });
+        assert_eq!(modes.get("collection"), PlayMode::default());
+        assert_eq!(modes.get("rediscover"), PlayMode::default());
+        assert_eq!(modes.contexts.len(), 1);
+    }
+}
@@ -1,49 +1,79 @@
 use std::{
-    collections::VecDeque,
+    collections::{
+        HashSet,
+        VecDeque,
+    },
     error::Error,
     num::NonZero,
     sync::{
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering,
+        },
         mpsc,
         Arc,
         Mutex
     },
     thread,
-    time::Duration
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
 };
 
 use dash_mpd::{MPD, parse};
-use futures_util::StreamExt;
-use rand::{
-    seq::SliceRandom,
-    rng
-};
+use rand::rng;
 use rodio::{
     Decoder,
     DeviceSinkBuilder,
     MixerDeviceSink,
     Player as RodioPlayer
 };
-use souvlaki::{
-    MediaControlEvent,
-    MediaControls,
-    MediaMetadata,
-    MediaPlayback,
-    MediaPosition,
-    PlatformConfig
-};
 use stream_download::{
     async_read::AsyncReadStream,
     storage::memory::MemoryStorageProvider,
     Settings,
     StreamDownload
 };
-use tokio::{
-    io::AsyncWriteExt,
-    task::JoinHandle,
+use rtidalapi::{
+    AudioQuality,
+    Track,
 };
+use tokio::task::JoinHandle;
 
 use crate::{
-    rtidalapi::Track,
+    audio_output,
+    bandwidth::BandwidthUsage,
+    blocklist::Blocklist,
+    config::PlayerConfig,
+    cover_cache::CoverCache,
+    history::PlayHistory,
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+    media_controls::{
+        new_media_controls,
+        MediaControlEvent,
+        MediaControls,
+        MediaMetadata,
+        MediaPlayback,
+        MediaPosition,
+        SeekDirection,
+    },
+    resample::{
+        get_dither_bit_depth,
+        Dithered,
+        Resampled,
+        ResamplerQuality,
+    },
+    session_stats::SessionStats,
+    segments::SegmentAssembler,
+    shuffle::ShuffleStrategy,
+    ui::OsdContent,
     AppEvent,
 };
 
@@ -74,13 +104,69 @@ pub struct ParsedManifest {
     pub content_length: u64,
 }
 
+/// A queue that was replaced, along with where it was up to, so that it can be restored later.
+pub struct SavedQueue {
+    pub current_track: Option<Arc<Track>>,
+    pub position: Duration,
+    pub queue: VecDeque<Arc<Track>>,
+    pub queue_history: VecDeque<Arc<Track>>,
+    pub is_shuffled: bool,
+    pub is_auto_generated: bool,
+}
+
+impl SavedQueue {
+    /// Returns how many tracks are left to play, including the one it was up to.
+    pub fn get_tracks_left(&self) -> usize {
+        self.queue.len() + usize::from(self.current_track.is_some())
+    }
+}
+
+/// Filters that temporarily keep tracks out of the shuffled queue and radio, without changing the collection or the blocklist.
+#[derive(Clone, Debug, Default)]
+pub struct QueueFilters {
+    /// Whether explicit tracks are skipped.
+    pub exclude_explicit: bool,
+    /// The genres whose tracks are skipped, in lowercase. A track is in the genres of its album.
+    pub excluded_genres: HashSet<String>,
+}
+
+impl QueueFilters {
+    /// Returns true if `track` should be skipped. Tracks whose info can't be fetched are never skipped.
+    fn excludes(&self, track: &Track) -> bool {
+        let is_excluded_explicit = self.exclude_explicit && track.get_attribtues().is_ok_and(|attributes| attributes.explicit);
+
+        is_excluded_explicit || self.excludes_genres(track)
+    }
+
+    /// Returns true if `track`'s album is in any of the excluded genres, fetching the album if it hasn't been.
+    fn excludes_genres(&self, track: &Track) -> bool {
+        !self.excluded_genres.is_empty() && track.get_album().is_ok_and(|album| {
+            album.attributes.genres.iter().any(|genre| self.excluded_genres.contains(&genre.to_lowercase()))
+        })
+    }
+}
+
 /// Object responsible for playing audio and handling playback.
 pub struct Player {
     output_stream: MixerDeviceSinkWrapper,
     sink: RodioPlayer,
     async_request_client: reqwest::Client,
     tokio_rt: tokio::runtime::Runtime,
-    controls: MediaControls,
+    controls: Box<dyn MediaControls>,
+    /// Set by the output stream when its device disappears (e.g. headphones are unplugged).
+    is_device_lost: Arc<AtomicBool>,
+    /// When the output stream was last reopened after its device disappeared, if it hasn't been reopened successfully yet.
+    last_device_recovery: Option<Instant>,
+    resume_after_device_recovery: bool,
+    /// The audio host and output device set in the config, which are used instead of the defaults.
+    output_host: Option<String>,
+    output_device: Option<String>,
+    /// Whether playback fell back to the host's default device because the configured one disappeared.
+    is_on_fallback_device: bool,
+    /// When the configured device was last looked for while playing on the default one.
+    last_fallback_check: Option<Instant>,
+    resampler_quality: ResamplerQuality,
+    is_dither_enabled: bool,
 
     // Player state
     current_track: Option<Arc<Track>>,
@@ -90,58 +176,104 @@ pub struct Player {
     volume: u32,
     normalization_mode: NormalizationMode,
     track_fetch_task_handle: Option<JoinHandle<()>>,
+    previous_restart_threshold: Duration,
+    previous_double_press_window: Duration,
+    last_previous_press: Option<Instant>,
+    has_finished_queue: bool,
+    is_shuffled: bool,
+    /// Whether the queue was made by Tidal (like a radio) rather than chosen by hand,
+    /// so that blocked and filtered tracks are skipped in it as they are while shuffling.
+    is_auto_generated: bool,
+    prefetch_lookahead: usize,
+    shuffle_prefetch_lookahead: usize,
+    /// Whether downloading the current track is retried until all of it is buffered.
+    download_full_track: bool,
+    blocklist: Arc<Mutex<Blocklist>>,
+    queue_filters: QueueFilters,
+    /// Whether Tidal can't be reached (or offline mode is on), in which case only tracks that can be played
+    /// without it are taken from the queue.
+    is_offline: bool,
+    history: Arc<Mutex<PlayHistory>>,
+    shuffle_strategy: Box<dyn ShuffleStrategy>,
+    session_stats: SessionStats,
+    bandwidth_usage: Arc<Mutex<BandwidthUsage>>,
+    /// Scales down the covers sent to the OS media controls, which are given a local file rather than a url.
+    covers: CoverCache,
+    /// Whether the current track's cover is still being scaled down, in which case the polling thread sends it
+    /// to the media controls once it is ready.
+    is_media_cover_pending: bool,
 
     // Information about the current track.
     position: Duration,
     replay_gain: f32,
     parsed_manifest: Option<ParsedManifest>,
+    /// The quality the current track is actually streamed in, which is lower than the audio quality setting
+    /// if the track isn't available in it.
+    stream_quality: Option<AudioQuality>,
+    /// Why the last track was skipped without playing (or why playback stopped early), to be shown once by the polling thread.
+    skip_message: Option<String>,
     has_confirmed_play: bool,
-
-    #[cfg(target_os = "windows")]
-    /// Keeps the hidden window alive for the lifetime of the player.
-    _hwnd_window: winit::window::Window,
+    downloaded_bytes: Arc<AtomicU64>,
+    /// Set by the fetch task if Tidal revoked the current track's stream, e.g. because the account started playing elsewhere.
+    is_stream_revoked: Arc<AtomicBool>,
+    /// Set by the fetch task once every segment of the current track has been downloaded.
+    is_fully_downloaded: Arc<AtomicBool>,
+    /// Whether playback was paused because it was taken over on another device, until it is resumed here.
+    is_taken_over: bool,
+    /// Whether the current track's stream has likely expired because the computer slept, so that a new one
+    /// is requested when it is resumed.
+    is_stream_stale: bool,
+    /// How many of the current track's downloaded bytes have been added to the bandwidth usage.
+    recorded_bytes: u64,
 }
 
 impl Player {
     /// Set max volume for rodio because otherwise it is way too loud.
     const MAX_VOLUME: f32 = 0.5;
 
+    /// The size (in pixels wide) of the cover art sent to the OS media controls, which only show it as a thumbnail.
+    /// It is the same size the Now Playing cover is drawn from, so the cover is only downloaded once.
+    const MEDIA_CONTROLS_COVER_SIZE: u32 = 320;
+
     /// Duration of playback required before sending a play event to Tidal.
     #[allow(unused)]
     const VALID_PLAYBACK_SESSION_DURATION: Duration = Duration::from_secs(30);
 
-    /// Returns a new `Player`.
-    pub fn new() -> Result<Self, Box<dyn Error>> {
+    /// How long to wait between attempts to reopen the output stream after its device disappears.
+    const DEVICE_RECOVERY_INTERVAL: Duration = Duration::from_secs(2);
+
+    /// How often the bandwidth used while streaming the current track is recorded.
+    const BANDWIDTH_RECORD_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// How long the polling thread has to have been frozen for before the computer is assumed to have slept.
+    const SLEEP_THRESHOLD: Duration = Duration::from_secs(10);
+
+    /// Returns a new `Player`, which skips the tracks in `blocklist` while shuffling, records what it plays in `history`,
+    /// records how much it streams in `bandwidth_usage`, and gets the covers it sends to the media controls from `covers`.
+    pub fn new(
+        config: &PlayerConfig,
+        blocklist: Arc<Mutex<Blocklist>>,
+        history: Arc<Mutex<PlayHistory>>,
+        bandwidth_usage: Arc<Mutex<BandwidthUsage>>,
+        covers: CoverCache,
+    ) -> Result<Self, Box<dyn Error>> {
         let tokio_rt = tokio::runtime::Builder::new_multi_thread()
             .worker_threads(4)
             .enable_all()
             .build()?;
 
-        let builder = DeviceSinkBuilder::from_default_device()?
-            .with_sample_rate(NonZero::new(44100).unwrap());
-
-        #[cfg(target_os = "macos")]
-        // Silence error messages when device sample rate changes.
-        let builder = builder.with_error_callback(|_| {});
-        
-        let mut output_stream = builder.open_sink_or_fallback()?;
-        output_stream.log_on_drop(false);
+        let is_device_lost = Arc::new(AtomicBool::new(false));
+        let output_stream = Self::build_output_stream(
+            44100,
+            Arc::clone(&is_device_lost),
+            config.output_host.as_deref(),
+            config.output_device.as_deref(),
+        )?;
 
         let sink = RodioPlayer::connect_new(output_stream.mixer());
         sink.set_volume(Self::MAX_VOLUME / 2.0);
 
-        #[cfg(not(target_os = "windows"))]
-        let hwnd = None;
-
-        #[cfg(target_os = "windows")]
-        let (hwnd, hwnd_window) = Self::init_windows_hwnd();
-
-        let config = PlatformConfig {
-            dbus_name: "tidal-tui",
-            display_name: "tidal-tui",
-            hwnd,
-        };
-        let controls = MediaControls::new(config)?;
+        let controls = new_media_controls()?;
 
         Ok(Self {
             output_stream: MixerDeviceSinkWrapper(output_stream),
@@ -149,6 +281,15 @@ impl Player {
             async_request_client: reqwest::Client::new(),
             tokio_rt,
             controls,
+            is_device_lost,
+            last_device_recovery: None,
+            resume_after_device_recovery: false,
+            output_host: config.output_host.clone(),
+            output_device: config.output_device.clone(),
+            is_on_fallback_device: false,
+            last_fallback_check: None,
+            resampler_quality: config.resampler,
+            is_dither_enabled: config.dither,
 
             current_track: None,
             queue: VecDeque::new(),
@@ -157,29 +298,81 @@ impl Player {
             volume: 50,
             normalization_mode: NormalizationMode::Track,
             track_fetch_task_handle: None,
+            previous_restart_threshold: Duration::from_secs(config.previous_restart_threshold_secs),
+            previous_double_press_window: Duration::from_millis(config.previous_double_press_ms),
+            last_previous_press: None,
+            has_finished_queue: false,
+            is_shuffled: false,
+            is_auto_generated: false,
+            prefetch_lookahead: config.prefetch_lookahead,
+            shuffle_prefetch_lookahead: config.shuffle_prefetch_lookahead,
+            download_full_track: config.download_full_track,
+            blocklist,
+            queue_filters: QueueFilters::default(),
+            is_offline: false,
+            shuffle_strategy: config.shuffle_mode.strategy(Arc::clone(&history)),
+            history,
+            session_stats: SessionStats::new(),
+            bandwidth_usage,
+            covers,
+            is_media_cover_pending: false,
 
             position: Duration::from_secs(0),
             replay_gain: 0.0,
             parsed_manifest: None,
+            stream_quality: None,
+            skip_message: None,
             has_confirmed_play: false,
-
-            #[cfg(target_os = "windows")]
-            _hwnd_window: hwnd_window,
+            downloaded_bytes: Arc::new(AtomicU64::new(0)),
+            is_stream_revoked: Arc::new(AtomicBool::new(false)),
+            is_fully_downloaded: Arc::new(AtomicBool::new(false)),
+            is_taken_over: false,
+            is_stream_stale: false,
+            recorded_bytes: 0,
         })
     }
 
+    /// Opens an output stream on the output device named `device_name` of the audio host named `host_name`,
+    /// or on the default device if neither is given. The stream sets `is_device_lost` if the device disappears.
+    fn build_output_stream(
+        sample_rate: u32,
+        is_device_lost: Arc<AtomicBool>,
+        host_name: Option<&str>,
+        device_name: Option<&str>,
+    ) -> Result<MixerDeviceSink, Box<dyn Error>> {
+        let builder = if host_name.is_none() && device_name.is_none() {
+            DeviceSinkBuilder::from_default_device()?
+        } else {
+            DeviceSinkBuilder::from_device(audio_output::get_output_device(host_name, device_name)?)?
+        };
+
+        let mut output_stream = builder
+            .with_sample_rate(NonZero::new(sample_rate).unwrap())
+            // Other errors are silenced, since printing them would garble the TUI
+            // (e.g. macOS reports one whenever the device sample rate changes).
+            .with_error_callback(move |e| {
+                if let cpal::StreamError::DeviceNotAvailable = e {
+                    is_device_lost.store(true, Ordering::Relaxed);
+                }
+            })
+            .open_sink_or_fallback()?;
+        output_stream.log_on_drop(false);
+
+        Ok(output_stream)
+    }
+
+    /// Opens a new output stream at `sample_rate` on the configured device,
+    /// or on the host's default device while the configured one is gone.
     fn open_new_output_stream(&mut self, sample_rate: u32) -> Result<(), Box<dyn Error>> {
         self.sink.stop();
 
-        let builder = DeviceSinkBuilder::from_default_device()?
-            .with_sample_rate(NonZero::new(sample_rate).unwrap());
-
-        #[cfg(target_os = "macos")]
-        // Silence error messages when device sample rate changes.
-        let builder = builder.with_error_callback(|_| {});
-        
-        let mut output_stream = builder.open_sink_or_fallback()?;
-        output_stream.log_on_drop(false);
+        let device_name = if self.is_on_fallback_device { None } else { self.output_device.as_deref() };
+        let output_stream = Self::build_output_stream(
+            sample_rate,
+            Arc::clone(&self.is_device_lost),
+            self.output_host.as_deref(),
+            device_name,
+        )?;
 
         let sink = RodioPlayer::connect_new(output_stream.mixer());
 
@@ -189,29 +382,84 @@ impl Player {
         Ok(())
     }
 
-    /// Initializes an invisible window to allow Souvlaki to work on Windows.
-    #[cfg(target_os = "windows")]
-    fn init_windows_hwnd() -> (Option<*mut std::ffi::c_void>, winit::window::Window) {
-        use winit::event_loop::EventLoop;
-        use winit::platform::windows::EventLoopBuilderExtWindows;
-        use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
-        use winit::window::Window;
+    /// Pauses playback and reopens the output stream on the configured device, falling back to the host's default device
+    /// while the configured one is gone, then resumes the current track from where it was if it was playing.
+    ///
+    /// Returns true if it fell back to the default device.
+    /// If there is no device to switch to yet, this is retried from the polling thread until there is.
+    fn recover_output_device(&mut self) -> Result<bool, Box<dyn Error>> {
+        if self.last_device_recovery.is_none() {
+            self.resume_after_device_recovery = self.is_playing;
+            self.pause()?;
+        }
+        self.last_device_recovery = Some(Instant::now());
 
-        let event_loop = EventLoop::builder()
-            .with_any_thread(true)
-            .build()
-            .unwrap();
+        let position = self.position;
+        let sample_rate = self.output_stream.config().sample_rate().get();
+        self.is_on_fallback_device = false;
+        if let Err(e) = self.open_new_output_stream(sample_rate) {
+            if self.output_device.is_none() {
+                return Err(e);
+            }
+            self.is_on_fallback_device = true;
+            self.open_new_output_stream(sample_rate)?;
+        }
+        self.is_device_lost.store(false, Ordering::Relaxed);
+        self.last_device_recovery = None;
 
-        let window = event_loop
-            .create_window(Window::default_attributes().with_visible(false))
-            .unwrap();
+        let should_resume = self.resume_after_device_recovery || self.is_playing;
+        if let Some(track) = self.current_track.take() {
+            self.play_new_track(track)?;
+            self.set_position(position)?;
 
-        let hwnd = match window.window_handle().unwrap().as_raw() {
-            RawWindowHandle::Win32(handle) => handle.hwnd.get() as *mut std::ffi::c_void,
-            _ => panic!("not running on Windows"),
-        };
+            if !should_resume {
+                self.pause()?;
+            }
+        }
+
+        Ok(self.is_on_fallback_device)
+    }
+
+    /// Returns true if the computer seems to have slept between polls, given how much time passed on the monotonic clock
+    /// and on the wall clock (`None` if the wall clock went backwards).
+    ///
+    /// The monotonic clock stops while asleep on Linux and macOS, so the wall clock jumps ahead of it on waking.
+    /// On Windows it keeps counting instead, so the gap between polls is long.
+    fn has_slept(elapsed: Duration, wall_clock_elapsed: Option<Duration>) -> bool {
+        elapsed >= Self::SLEEP_THRESHOLD
+            || wall_clock_elapsed.is_some_and(|wall_clock_elapsed| wall_clock_elapsed.saturating_sub(elapsed) >= Self::SLEEP_THRESHOLD)
+    }
+
+    /// Pauses playback after the computer woke from sleep, keeping its position.
+    ///
+    /// The manifests of the current and upcoming tracks are forgotten, since their segment urls have likely expired,
+    /// and the current track's stream is requested again once it is resumed.
+    fn handle_wake(&mut self) -> Result<(), Box<dyn Error>> {
+        for track in self.current_track.iter().chain(&self.queue) {
+            track.clear_cached_manifest();
+        }
+        self.is_stream_stale = true;
 
-        (Some(hwnd), window)
+        self.pause()
+    }
+
+    /// Returns true if the output device has disappeared and it is time to try reopening the output stream.
+    fn should_recover_output_device(&self) -> bool {
+        self.is_device_lost.load(Ordering::Relaxed)
+            && self.last_device_recovery.is_none_or(|last_recovery| last_recovery.elapsed() >= Self::DEVICE_RECOVERY_INTERVAL)
+    }
+
+    /// Returns true if playback fell back to the default device and the configured one is back,
+    /// looking for it every `DEVICE_RECOVERY_INTERVAL`.
+    fn is_output_device_back(&mut self) -> bool {
+        if !self.is_on_fallback_device
+            || self.last_fallback_check.is_some_and(|last_check| last_check.elapsed() < Self::DEVICE_RECOVERY_INTERVAL)
+        {
+            return false;
+        }
+        self.last_fallback_check = Some(Instant::now());
+
+        audio_output::get_output_device(self.output_host.as_deref(), self.output_device.as_deref()).is_ok()
     }
 
     /// Spawns another thread to poll for playback position updates and media control events.
@@ -221,14 +469,73 @@ impl Player {
         {
             let mut unlocked_player = player.lock()
                 .map_err(|e| format!("{e:#?}"))?;
-            unlocked_player.controls.attach(move |event| { tx.send(event).unwrap(); })?;
+            unlocked_player.controls.attach(Box::new(move |event| { tx.send(event).unwrap(); }))?;
         }
 
         thread::spawn(move || {
+            let mut last_poll = Instant::now();
+            let mut last_bandwidth_record = Instant::now();
+            // Taken just before sleeping between polls, so that waiting for the lock isn't mistaken for the computer sleeping.
+            let mut last_sleep = (Instant::now(), SystemTime::now());
+
             loop {
+                let has_slept = Player::has_slept(last_sleep.0.elapsed(), last_sleep.1.elapsed().ok());
+
                 {
                     let mut unlocked_player = player.lock().unwrap();
 
+                    let now = Instant::now();
+                    if unlocked_player.is_playing && !has_slept {
+                        unlocked_player.session_stats.record_time_listened(now - last_poll);
+                    }
+                    last_poll = now;
+
+                    if has_slept && unlocked_player.current_track.is_some() {
+                        let was_playing = unlocked_player.is_playing;
+                        unlocked_player.handle_wake().unwrap();
+                        if was_playing {
+                            let _ = app_tx.try_send(AppEvent::ShowOsd(OsdContent::Message(tr(Message::PausedAfterSleep).to_string())));
+                        }
+                        let _ = app_tx.try_send(AppEvent::ReRender);
+                    }
+
+                    unlocked_player.update_media_cover();
+
+                    if now.duration_since(last_bandwidth_record) >= Player::BANDWIDTH_RECORD_INTERVAL {
+                        unlocked_player.record_bandwidth_usage();
+                        last_bandwidth_record = now;
+                    }
+
+                    // Switching back to the configured device goes through the same recovery as losing a device.
+                    if unlocked_player.is_output_device_back() {
+                        unlocked_player.is_device_lost.store(true, Ordering::Relaxed);
+                    }
+
+                    if unlocked_player.should_recover_output_device() {
+                        let is_first_attempt = unlocked_player.last_device_recovery.is_none();
+
+                        let message = match unlocked_player.recover_output_device() {
+                            Ok(false) if unlocked_player.output_device.is_some() => Some(Message::AudioDeviceReconnected),
+                            Ok(_) => Some(Message::AudioDeviceChanged),
+                            Err(_) if is_first_attempt => Some(Message::AudioDeviceDisconnected),
+                            Err(_) => None,
+                        };
+                        if let Some(message) = message {
+                            let _ = app_tx.try_send(AppEvent::ShowOsd(OsdContent::Message(tr(message).to_string())));
+                        }
+                        let _ = app_tx.try_send(AppEvent::ReRender);
+                    }
+
+                    if let Some(message) = unlocked_player.skip_message.take() {
+                        let _ = app_tx.try_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                    }
+
+                    // Pause before the rest of the stream is played out, which would otherwise skip to the next track.
+                    if unlocked_player.is_stream_revoked.swap(false, Ordering::Relaxed) {
+                        unlocked_player.take_over().unwrap();
+                        let _ = app_tx.try_send(AppEvent::ReRender);
+                    }
+
                     if unlocked_player.is_playing {
                         let position = unlocked_player.sink.get_pos();
 
@@ -247,7 +554,12 @@ impl Player {
 
                         // Update player state.
                         if unlocked_player.sink.empty() {
-                            unlocked_player.next().unwrap();
+                            // Stay on the finished track, so that playing again retries the next one.
+                            if let Err(e) = unlocked_player.next() {
+                                let _ = unlocked_player.pause();
+                                let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                                let _ = app_tx.try_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                            }
                             let _ = app_tx.try_send(AppEvent::ReRender);
                         } else {
                             if position.as_secs_f64().round() != unlocked_player.position.as_secs_f64().round() {
@@ -261,37 +573,44 @@ impl Player {
 
                 // Handle OS media key events.
                 if let Ok(event) = rx.try_recv() {
-                    let mut unlocked_player = player.lock().unwrap();
-
+                    // Events about the app rather than playback are handled by the app.
                     match event {
-                        MediaControlEvent::Pause => {
-                            unlocked_player.pause().unwrap();
+                        MediaControlEvent::Raise => {
+                            let _ = app_tx.blocking_send(AppEvent::Raise);
+                            continue;
                         },
-                        MediaControlEvent::Play => {
-                            unlocked_player.play().unwrap();
-                        },
-                        MediaControlEvent::Next => {
-                            unlocked_player.next().unwrap();
-                        },
-                        MediaControlEvent::Previous => {
-                            unlocked_player.prev().unwrap();
-                        },
-                        MediaControlEvent::SetPosition(MediaPosition(position)) => {
-                            unlocked_player.set_position(position).unwrap();
-                        },
-                        MediaControlEvent::Toggle => {
-                            if unlocked_player.is_playing {
-                                unlocked_player.pause().unwrap();
-                            } else {
-                                unlocked_player.play().unwrap();
-                            }
+                        MediaControlEvent::OpenUri(uri) => {
+                            let _ = app_tx.blocking_send(AppEvent::QueueLink(uri));
+                            continue;
                         },
                         _ => {},
                     }
 
+                    let mut unlocked_player = player.lock().unwrap();
+
+                    // Playing a track can fail (e.g. while offline), which is shown rather than stopping the polling thread.
+                    let result = match event {
+                        MediaControlEvent::Pause => unlocked_player.pause(),
+                        MediaControlEvent::Play => unlocked_player.play(),
+                        MediaControlEvent::Next => unlocked_player.next(),
+                        MediaControlEvent::Previous => unlocked_player.prev(),
+                        MediaControlEvent::Stop => unlocked_player.stop(),
+                        MediaControlEvent::SeekBy(SeekDirection::Forward, amount) => unlocked_player.seek_forward(amount),
+                        MediaControlEvent::SeekBy(SeekDirection::Backward, amount) => unlocked_player.seek_backward(amount),
+                        MediaControlEvent::SetPosition(MediaPosition(position)) => unlocked_player.set_position(position),
+                        MediaControlEvent::Toggle if unlocked_player.is_playing => unlocked_player.pause(),
+                        MediaControlEvent::Toggle => unlocked_player.play(),
+                        MediaControlEvent::Raise | MediaControlEvent::OpenUri(_) => Ok(()),
+                    };
+                    if let Err(e) = result {
+                        let message = tr_with(Message::PlayItemFailed, &[("error", &e.to_string())]);
+                        let _ = app_tx.try_send(AppEvent::ShowOsd(OsdContent::Message(message)));
+                    }
+
                     let _ = app_tx.try_send(AppEvent::ReRender);
                 }
 
+                last_sleep = (Instant::now(), SystemTime::now());
                 thread::sleep(Duration::from_millis(50));
             }
         });
@@ -304,11 +623,31 @@ impl Player {
         self.current_track.as_ref()
     }
 
+    /// Returns the upcoming tracks in the queue, in the order they will be played.
+    pub fn get_queue(&self) -> &VecDeque<Arc<Track>> {
+        &self.queue
+    }
+
+    /// Returns the tracks played before the current one, oldest first, which going back passes through.
+    pub fn get_queue_history(&self) -> &VecDeque<Arc<Track>> {
+        &self.queue_history
+    }
+
+    /// Returns a reference to the next track in the queue if one exists.
+    pub fn get_next_track(&self) -> Option<&Arc<Track>> {
+        self.queue.front()
+    }
+
     /// Returns the position of the current track.
     pub fn get_position(&self) -> Duration {
         self.position
     }
 
+    /// Returns true iff the last track in the queue has finished playing (or was skipped).
+    pub fn has_finished_queue(&self) -> bool {
+        self.has_finished_queue
+    }
+
     /// Returns true iff this player is currently playing.
     pub fn is_playing(&self) -> bool {
         self.is_playing
@@ -326,6 +665,29 @@ impl Player {
         self.volume
     }
 
+    /// Returns what has been played since the app was started.
+    pub fn get_session_stats(&self) -> SessionStats {
+        let mut session_stats = self.session_stats.clone();
+        // The current track's stream is only added to the stats once the next track replaces it.
+        session_stats.record_bytes_streamed(self.downloaded_bytes.load(Ordering::Relaxed));
+        session_stats
+    }
+
+    /// Adds the bytes of the current track downloaded since this was last called to the bandwidth usage, and saves it.
+    fn record_bandwidth_usage(&mut self) {
+        let downloaded_bytes = self.downloaded_bytes.load(Ordering::Relaxed);
+        if downloaded_bytes <= self.recorded_bytes {
+            return;
+        }
+
+        let mut unlocked_bandwidth_usage = self.bandwidth_usage.lock().unwrap();
+        unlocked_bandwidth_usage.record(downloaded_bytes - self.recorded_bytes, SystemTime::now());
+        // Failing to save the usage shouldn't stop playback.
+        let _ = unlocked_bandwidth_usage.save();
+
+        self.recorded_bytes = downloaded_bytes;
+    }
+
     /// Returns this player's current ReplayGain value.
     pub fn get_replay_gain(&self) -> f32 {
         self.replay_gain
@@ -336,6 +698,27 @@ impl Player {
         self.parsed_manifest.as_ref()
     }
 
+    /// Returns the quality the current track is actually streamed in, if it is known.
+    pub fn get_stream_quality(&self) -> Option<AudioQuality> {
+        self.stream_quality
+    }
+
+    /// Returns the ratio of the current track that has been downloaded so far, between 0 and 1.
+    pub fn get_buffered_ratio(&self) -> f64 {
+        match &self.parsed_manifest {
+            Some(parsed_manifest) if parsed_manifest.content_length > 0 => {
+                let downloaded_bytes = self.downloaded_bytes.load(Ordering::Relaxed);
+                (downloaded_bytes as f64 / parsed_manifest.content_length as f64).min(1.0)
+            },
+            _ => 0.0,
+        }
+    }
+
+    /// Returns true if all of the current track has been downloaded, so that it plays to the end without the network.
+    pub fn is_fully_buffered(&self) -> bool {
+        self.parsed_manifest.is_some() && self.is_fully_downloaded.load(Ordering::Relaxed)
+    }
+
     fn db_to_linear(db: f32) -> f32 {
         10f32.powf(db / 20.0)
     }
@@ -354,27 +737,177 @@ impl Player {
         self.queue = tracks.into();
         self.queue_history.clear();
         self.sink.clear();
+        self.is_shuffled = false;
+        self.is_auto_generated = false;
+    }
+
+    /// Sets whether this player's queue was made by Tidal rather than chosen by hand,
+    /// in which case blocked and filtered tracks are skipped in it.
+    pub fn set_auto_generated(&mut self, is_auto_generated: bool) {
+        self.is_auto_generated = is_auto_generated;
+    }
+
+    /// Adds `tracks` to the end of this player's queue, and starts playing them if nothing is playing.
+    pub fn add_to_queue(&mut self, tracks: Vec<Arc<Track>>) -> Result<(), Box<dyn Error>> {
+        self.queue.extend(tracks);
+
+        if self.current_track.is_none() {
+            self.play()?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds `tracks` to the front of this player's queue, so that they play after the current track,
+    /// and starts playing them if nothing is playing.
+    pub fn play_next(&mut self, tracks: Vec<Arc<Track>>) -> Result<(), Box<dyn Error>> {
+        for track in tracks.into_iter().rev() {
+            self.queue.push_front(track);
+        }
+
+        if self.current_track.is_none() {
+            self.play()?;
+        }
+
+        Ok(())
+    }
+
+    /// Skips to playing `track` straight away, carrying on with the rest of the queue after it.
+    ///
+    /// `track` was chosen by hand, so it is played even if it is blocked or excluded by the queue filters.
+    pub fn play_now(&mut self, track: Arc<Track>) -> Result<(), Box<dyn Error>> {
+        let previous_track = self.current_track.take();
+        if let Err(e) = self.play_new_track(track) {
+            self.current_track = previous_track;
+            return Err(e);
+        }
+
+        if let Some(previous_track) = previous_track {
+            self.queue_history.push_back(previous_track);
+        }
+        self.has_confirmed_play = false;
+        self.session_stats.record_track_played();
+
+        Ok(())
+    }
+
+    /// Returns this player's queue and where it is up to, or `None` if there is nothing left to play.
+    pub fn save_queue(&self) -> Option<SavedQueue> {
+        if self.current_track.is_none() || self.has_finished_queue {
+            return None;
+        }
+
+        Some(SavedQueue {
+            current_track: self.current_track.clone(),
+            position: self.position,
+            queue: self.queue.clone(),
+            queue_history: self.queue_history.clone(),
+            is_shuffled: self.is_shuffled,
+            is_auto_generated: self.is_auto_generated,
+        })
+    }
+
+    /// Replaces this player's queue with `saved`, resuming the track it was up to where it left off.
+    pub fn restore_queue(&mut self, saved: SavedQueue) -> Result<(), Box<dyn Error>> {
+        self.set_queue(vec![]);
+        self.queue = saved.queue;
+        self.queue_history = saved.queue_history;
+        self.is_shuffled = saved.is_shuffled;
+        self.is_auto_generated = saved.is_auto_generated;
+
+        if let Some(track) = saved.current_track {
+            self.play_new_track(track)?;
+            self.set_position(saved.position)?;
+        }
+
+        Ok(())
     }
 
-    /// Randomly shuffles this player's queue and queue history into a new queue.
+    /// Randomly shuffles this player's queue and queue history into a new queue, using the shuffle strategy.
     pub fn shuffle_queue(&mut self) {
         self.queue.append(&mut self.queue_history);
-        self.queue.make_contiguous().shuffle(&mut rng());
+        self.shuffle_strategy.shuffle(self.queue.make_contiguous(), &mut rng());
+        self.is_shuffled = true;
+    }
+
+    /// Returns the filters keeping tracks out of the shuffled queue.
+    pub fn get_queue_filters(&self) -> &QueueFilters {
+        &self.queue_filters
+    }
+
+    /// Replaces the filters keeping tracks out of the shuffled queue, which apply from the next track.
+    pub fn set_queue_filters(&mut self, queue_filters: QueueFilters) {
+        self.queue_filters = queue_filters;
+    }
+
+    /// Sets whether Tidal can't be reached (or offline mode is on). While it can't, tracks that can't be played
+    /// without it are left in the queue for later instead of being tried.
+    pub fn set_offline(&mut self, is_offline: bool) {
+        self.is_offline = is_offline;
+    }
+
+    /// Replaces how the queue is ordered when it is next shuffled.
+    pub fn set_shuffle_strategy(&mut self, shuffle_strategy: Box<dyn ShuffleStrategy>) {
+        self.shuffle_strategy = shuffle_strategy;
+    }
+
+    /// Sends the title, album, artist, and cover of `track` to the OS media controls.
+    ///
+    /// The cover is sent as a file once the cover cache has scaled it down. Until then it is left out,
+    /// and the polling thread sends the metadata again when it is ready.
+    fn set_media_metadata(&mut self, track: &Track) -> Result<(), Box<dyn Error>> {
+        let full_title = track.get_attribtues()?.get_full_title();
+        // A missing album or artist shouldn't stop the track from playing.
+        let album = track.get_album().ok();
+        let artist_name = track.get_artist_credits().ok().map(|credits| credits.get_display_name());
+
+        let cover_url = album.and_then(|album| album.get_cover_art_url(Self::MEDIA_CONTROLS_COVER_SIZE));
+        let cover_path = cover_url.and_then(|url| self.covers.get_thumbnail(url, Self::MEDIA_CONTROLS_COVER_SIZE));
+        self.is_media_cover_pending = cover_url.is_some() && cover_path.is_none();
+        let cover_file_url = cover_path.map(|path| format!("file://{}", path.display()));
+
+        self.controls.set_metadata(MediaMetadata {
+            title: Some(&full_title),
+            album: album.map(|album| album.attributes.title.as_str()),
+            artist: artist_name.as_deref(),
+            duration: track.get_duration().ok().copied(),
+            cover_url: cover_file_url.as_deref(),
+        })?;
+
+        Ok(())
+    }
+
+    /// Sends the current track's metadata to the media controls again if its cover has been scaled down since.
+    fn update_media_cover(&mut self) {
+        if !self.is_media_cover_pending {
+            return;
+        }
+
+        let Some(track) = self.current_track.clone() else {
+            self.is_media_cover_pending = false;
+            return;
+        };
+
+        let is_cover_ready = track.get_album().ok()
+            .and_then(|album| album.get_cover_art_url(Self::MEDIA_CONTROLS_COVER_SIZE))
+            .and_then(|url| self.covers.get_thumbnail(url, Self::MEDIA_CONTROLS_COVER_SIZE))
+            .is_some();
+        if is_cover_ready {
+            let _ = self.set_media_metadata(&track);
+        }
     }
 
     /// Replaces the current track with the given `Track` and starts playback.
     pub fn play_new_track(&mut self, track: Arc<Track>) -> Result<(), Box<dyn Error>> {
-        let track_attributes = track.get_attribtues()?;
-        let album = track.get_album()?;
+        track.get_attribtues()?;
 
         let manifest = track.get_manifest(true)?;
+        if manifest.is_drm_protected() {
+            return self.skip_drm_protected_track(track);
+        }
         let parsed_manifest = Self::parse_manifest(&manifest.uri)?;
 
-        let track_title = &track_attributes.title;
-        let album_title = &album.attributes.title;
-        let artist_name = &track.get_artist()?.attributes.name;
-        let duration = track.get_duration()?.clone();
-        let cover_url = &album.cover_art_url;
+        track.get_duration()?;
 
         if let Some(handle) = self.track_fetch_task_handle.take() {
             handle.abort();
@@ -393,36 +926,31 @@ impl Player {
         };
         self.apply_volume_to_sink();
 
-        self.controls.set_metadata(MediaMetadata {
-            title: Some(track_title),
-            album: Some(album_title),
-            artist: Some(artist_name),
-            duration: Some(duration),
-            cover_url: Some(cover_url),
-        })?;
+        self.set_media_metadata(&track)?;
         self.controls.set_playback(MediaPlayback::Playing { progress: None })?;
 
-        let (mut writer, reader) = tokio::io::duplex(512 * 1024);
-
-        let client = self.async_request_client.clone();
-        let urls = parsed_manifest.urls.clone();
-
-        let handle = self.tokio_rt.spawn(async move {
-            for url in urls {
-                match client.get(&url).send().await {
-                    Ok(resp) => {
-                        let mut stream = resp.bytes_stream();
-                        while let Some(chunk) = stream.next().await {
-                            match chunk {
-                                Ok(bytes) => { let _ = writer.write_all(&bytes).await; }
-                                Err(e) => { eprintln!("Error: {e}"); break; }
-                            }
-                        }
-                    }
-                    Err(e) => { eprintln!("Error: {e}"); break; }
-                }
-            }
-        });
+        let (writer, reader) = tokio::io::duplex(512 * 1024);
+
+        // Use a new counter for each track so an aborted fetch task can't update the new track's progress.
+        let downloaded_bytes = Arc::new(AtomicU64::new(0));
+        self.session_stats.record_bytes_streamed(self.downloaded_bytes.load(Ordering::Relaxed));
+        self.record_bandwidth_usage();
+        self.downloaded_bytes = Arc::clone(&downloaded_bytes);
+        self.recorded_bytes = 0;
+        let is_stream_revoked = Arc::new(AtomicBool::new(false));
+        self.is_stream_revoked = Arc::clone(&is_stream_revoked);
+        let is_fully_downloaded = Arc::new(AtomicBool::new(false));
+        self.is_fully_downloaded = Arc::clone(&is_fully_downloaded);
+
+        let assembler = SegmentAssembler::new(
+            self.async_request_client.clone(),
+            parsed_manifest.urls.clone(),
+            self.download_full_track,
+            downloaded_bytes,
+            is_stream_revoked,
+            is_fully_downloaded,
+        );
+        let handle = self.tokio_rt.spawn(assembler.run(writer));
         self.track_fetch_task_handle = Some(handle);
 
         let stream = self.tokio_rt.block_on(async {
@@ -433,16 +961,42 @@ impl Player {
             ).await
         })?;
 
-        let source = Decoder::new_mp4(stream)?;
-        self.sink.append(source);
+        // The output stream falls back to the device's own sample rate and format if it can't match the track's,
+        // in which case the track is resampled and dithered here rather than left to rodio.
+        let output_config = self.output_stream.config();
+        let source = Resampled::new(Decoder::new_mp4(stream)?, output_config.sample_rate(), self.resampler_quality)?;
+        let dither_bit_depth = get_dither_bit_depth(output_config.sample_format(), parsed_manifest.bit_depth, source.is_resampling())
+            .filter(|_| self.is_dither_enabled);
+        self.sink.append(Dithered::new(source, dither_bit_depth));
         self.sink.play();
 
+        {
+            let mut unlocked_history = self.history.lock().unwrap();
+            unlocked_history.record_play(&track.id, SystemTime::now());
+            // Failing to save the history shouldn't stop playback.
+            let _ = unlocked_history.save();
+        }
+
         self.current_track = Some(track);
         self.parsed_manifest = Some(parsed_manifest);
+        self.stream_quality = manifest.get_quality();
         self.is_playing = true;
+        self.is_taken_over = false;
+        self.is_stream_stale = false;
+        self.has_finished_queue = false;
+
+        self.prefetch_upcoming_tracks();
+
+        Ok(())
+    }
+
+    /// Prefetches the info and manifests of the next few tracks in the queue to reduce delay between tracks.
+    /// 
+    /// More tracks are prefetched while shuffling, so that skipping several tracks in a row is still instant.
+    fn prefetch_upcoming_tracks(&self) {
+        let lookahead = if self.is_shuffled { self.shuffle_prefetch_lookahead } else { self.prefetch_lookahead };
 
-        // Prefetch the next track's info to reduce delay between tracks.
-        if let Some(next_track) = self.queue.get(0) {
+        for next_track in self.queue.iter().take(lookahead) {
             let next_track = Arc::clone(next_track);
 
             self.tokio_rt.spawn_blocking(move || {
@@ -452,6 +1006,27 @@ impl Player {
                 let _ = next_track.get_manifest(true);
             });
         }
+    }
+
+    /// Makes `track` the current track without playing it, since its stream is encrypted and can't be decoded.
+    ///
+    /// The track is left with nothing to play, so the polling thread skips to the next one as if it had finished.
+    fn skip_drm_protected_track(&mut self, track: Arc<Track>) -> Result<(), Box<dyn Error>> {
+        if let Some(handle) = self.track_fetch_task_handle.take() {
+            handle.abort();
+        }
+        self.sink.clear();
+
+        let title = track.get_cached_attributes()
+            .map_or(format!("{} {}", tr(Message::Track), track.id), |attributes| attributes.get_full_title());
+        self.skip_message = Some(tr_with(Message::SkippedDrmProtected, &[("title", &title)]));
+
+        self.position = Duration::from_secs(0);
+        self.parsed_manifest = None;
+        self.stream_quality = None;
+        self.current_track = Some(track);
+        self.is_playing = true;
+        self.has_finished_queue = false;
 
         Ok(())
     }
@@ -537,19 +1112,67 @@ impl Player {
 
     /// Resumes playback if a track is paused, or starts playing the first track in the queue (if non-empty).
     pub fn play(&mut self) -> Result<(), Box<dyn Error>> {
-        if self.current_track.is_some() && !self.is_playing {
+        // The rest of a taken over or expired stream can't be fetched, so it has to be requested again.
+        if self.is_taken_over || self.is_stream_stale {
+            self.request_new_stream()?;
+        } else if self.current_track.is_some() && !self.is_playing {
             let position = self.position;
             self.is_playing = true;
+            self.has_finished_queue = false;
             self.controls.set_playback(MediaPlayback::Playing { progress: Some(MediaPosition(position)) })?;
             self.sink.play();
-        } else if self.current_track.is_none() && self.queue.len() > 0 {
-            let track = self.queue.pop_front().unwrap();
+        } else if self.current_track.is_none() && let Some(track) = self.pop_next_track() {
             self.play_new_track(track)?;
+            self.session_stats.record_track_played();
         }
 
         Ok(())
     }
 
+    /// Pauses playback because the current track's stream was taken over on another device.
+    ///
+    /// Its manifest is forgotten, since its segment urls no longer work.
+    fn take_over(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(track) = &self.current_track {
+            track.clear_cached_manifest();
+        }
+        self.is_taken_over = true;
+
+        self.pause()
+    }
+
+    /// Returns true if playback was taken over on another device and hasn't been resumed here since.
+    pub fn is_taken_over(&self) -> bool {
+        self.is_taken_over
+    }
+
+    /// Requests a new stream for the current track and resumes it from where it was taken over on another device.
+    ///
+    /// Playing on this device in turn stops playback on the other one.
+    pub fn resume_here(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.is_taken_over {
+            return Ok(());
+        }
+
+        self.request_new_stream()
+    }
+
+    /// Requests a new stream for the current track and plays it from the position it was at.
+    ///
+    /// If that fails, the track is kept where it was so that it can be tried again.
+    fn request_new_stream(&mut self) -> Result<(), Box<dyn Error>> {
+        let position = self.position;
+        let Some(track) = self.current_track.clone() else {
+            return Ok(());
+        };
+
+        if let Err(e) = self.play_new_track(track) {
+            self.position = position;
+            return Err(e);
+        }
+        self.set_position(position)
+    }
+
     /// Pauses playback is a track is playing.
     pub fn pause(&mut self) -> Result<(), Box<dyn Error>> {
         let position = self.position;
@@ -560,57 +1183,300 @@ impl Player {
         Ok(())
     }
 
+    /// Stops playback, leaving the current track paused at its start.
+    pub fn stop(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.current_track.is_some() {
+            self.set_position(Duration::from_secs(0))?;
+            self.pause()?;
+        }
+        self.controls.set_playback(MediaPlayback::Stopped)?;
+
+        Ok(())
+    }
+
+    /// Silences playback, records the last of the bandwidth usage, and lets go of the OS media controls,
+    /// for when the app is closing.
+    pub fn shut_down(&mut self) -> Result<(), Box<dyn Error>> {
+        self.record_bandwidth_usage();
+        self.is_playing = false;
+        self.sink.stop();
+        self.controls.set_playback(MediaPlayback::Stopped)?;
+        self.controls.detach()?;
+
+        Ok(())
+    }
+
     /// Skips to playing the next track in the queue.
+    ///
+    /// If the next track can't be played, it is put back in the queue and the current track is kept.
     pub fn next(&mut self) -> Result<(), Box<dyn Error>> {
         if let Some(current_track) = self.current_track.take() {
-            if let Some(next_track) = self.queue.pop_front() {
+            if let Some(next_track) = self.pop_next_track() {
+                if let Err(e) = self.play_new_track(Arc::clone(&next_track)) {
+                    self.queue.push_front(next_track);
+                    self.current_track = Some(current_track);
+                    return Err(e);
+                }
                 self.queue_history.push_back(current_track);
-                self.play_new_track(next_track)?;
                 self.has_confirmed_play = false;
+                self.session_stats.record_track_played();
             } else {
-                // No next tracks. Just start the same track over again (same as Tidal).
+                // No next tracks. Start the same track over again, paused (same as Tidal).
                 self.current_track = Some(current_track);
                 self.set_position(Duration::from_secs(0))?;
-                self.controls.set_playback(MediaPlayback::Paused { progress: Some(MediaPosition(Duration::from_secs(0))) })?;
+                self.pause()?;
+                // Tracks that were left in the queue while offline still need to be played once back online.
+                self.has_finished_queue = self.queue.is_empty();
+                if !self.has_finished_queue {
+                    self.skip_message = Some(String::from(tr(Message::NothingPlayableOffline)));
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Removes the next track to play from the queue.
+    ///
+    /// Tracks in the blocklist or excluded by the queue filters are skipped (and dropped from the queue)
+    /// while shuffling or playing a queue made by Tidal, since they were chosen automatically rather than by hand.
+    ///
+    /// While offline, the next track that can be played without Tidal is taken instead, and the ones before it
+    /// are left in the queue.
+    fn pop_next_track(&mut self) -> Option<Arc<Track>> {
+        let is_filtered = self.is_shuffled || self.is_auto_generated;
+        loop {
+            let idx = if self.is_offline { self.queue.iter().position(|track| Self::is_playable_offline(track))? } else { 0 };
+            let track = self.queue.remove(idx)?;
+
+            if !is_filtered || (!self.is_blocked(&track) && !self.queue_filters.excludes(&track)) {
+                return Some(track);
+            }
+        }
+    }
+
+    /// Returns true if everything needed to start playing `track` has already been fetched,
+    /// which is the case for the tracks that were prefetched.
+    fn is_playable_offline(track: &Track) -> bool {
+        track.get_cached_attributes().is_some() && track.has_cached_manifest()
+    }
+
+    /// Returns true if `track` or its artist is in the blocklist.
+    fn is_blocked(&self, track: &Track) -> bool {
+        // The artist of an upcoming track has usually been prefetched already.
+        let artist_id = track.get_artist().ok().map(|artist| artist.id.as_str());
+
+        self.blocklist.lock().unwrap().is_blocked(&track.id, artist_id)
+    }
+
     /// Goes back to play the previous track in the queue history.
+    ///
+    /// If the current track has played past the restart threshold, it is restarted instead,
+    /// unless previous was pressed twice in quick succession.
     pub fn prev(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = Instant::now();
+        let is_double_press = self.last_previous_press
+            .is_some_and(|last_press| now.duration_since(last_press) <= self.previous_double_press_window);
+        self.last_previous_press = Some(now);
+
+        if self.position > self.previous_restart_threshold && !is_double_press {
+            return self.set_position(Duration::from_secs(0));
+        }
+
         if let Some(current_track) = self.current_track.take() {
             if let Some(prev_track) = self.queue_history.pop_back() {
                 self.queue.push_front(current_track);
                 self.play_new_track(prev_track)?;
                 self.has_confirmed_play = false;
+                self.session_stats.record_track_played();
             } else {
                 // No previous tracks. Just start the same track over again (same as Tidal).
                 self.current_track = Some(current_track);
                 self.set_position(Duration::from_secs(0))?;
-                self.controls.set_playback(MediaPlayback::Paused { progress: Some(MediaPosition(Duration::from_secs(0))) })?;
             }
         }
 
         Ok(())
     }
 
+    /// Skips ahead `count` tracks, only playing the last of them. The tracks skipped over are added to the history,
+    /// so that going back passes through them. If there are fewer tracks left, the last one is played.
+    pub fn skip_forward(&mut self, count: usize) -> Result<(), Box<dyn Error>> {
+        let mut skipped = vec![];
+        while skipped.len() + 1 < count && self.queue.len() > 1 {
+            if let Some(track) = self.pop_next_track() {
+                skipped.push(track);
+            }
+        }
+        // Tracks left out of the queue may have used up the rest of it.
+        if self.queue.is_empty() && let Some(track) = skipped.pop() {
+            self.queue.push_front(track);
+        }
+
+        let history_len = self.queue_history.len();
+        self.next()?;
+        // The track that was playing is added to the history once the next one starts, before the skipped ones.
+        if self.queue_history.len() > history_len {
+            self.queue_history.extend(skipped);
+        }
+
+        Ok(())
+    }
+
+    /// Goes back `count` tracks, only playing the last of them. The tracks gone back over are put back in the queue.
+    /// If there are fewer tracks in the history, the first one is played.
+    pub fn skip_back(&mut self, count: usize) -> Result<(), Box<dyn Error>> {
+        let count = count.min(self.queue_history.len());
+        if count == 0 {
+            return self.set_position(Duration::from_secs(0));
+        }
+        let Some(current_track) = self.current_track.take() else {
+            return Ok(());
+        };
+
+        self.queue.push_front(current_track);
+        for _ in 1..count {
+            if let Some(track) = self.queue_history.pop_back() {
+                self.queue.push_front(track);
+            }
+        }
+
+        if let Some(prev_track) = self.queue_history.pop_back() {
+            self.play_new_track(prev_track)?;
+            self.has_confirmed_play = false;
+            self.session_stats.record_track_played();
+        }
+
+        Ok(())
+    }
+
     /// Sets the position of playback in the player if there is a current track.
+    ///
+    /// Seeking to or past the end of the current track skips to the next track.
     pub fn set_position(&mut self, position: Duration) -> Result<(), Box<dyn Error>> {
-        if self.current_track.is_some() {
-            // WORKAROUND: current rodio decoder creation does not allow backwards seeking
-            // unless we allow a large delay on Decoder creation. So, this hack performs
-            // backwards seeks by refetching and rebuilding the track's Decoder
-            if position < self.sink.get_pos() {
-                let track = self.current_track.take().unwrap();
-                self.play_new_track(track)?;
-            }
+        let Some(current_track) = &self.current_track else {
+            return Ok(());
+        };
+        let duration = *current_track.get_duration()?;
+
+        match SeekTarget::resolve(position, duration) {
+            SeekTarget::Skip => self.next()?,
+            SeekTarget::Position(position) => {
+                // WORKAROUND: current rodio decoder creation does not allow backwards seeking
+                // unless we allow a large delay on Decoder creation. So, this hack performs
+                // backwards seeks by refetching and rebuilding the track's Decoder.
+                // The Decoder also needs to be rebuilt if the track has already finished playing,
+                // or if its stream has expired.
+                if position < self.sink.get_pos() || self.sink.empty() || self.is_stream_stale {
+                    let was_playing = self.is_playing;
+                    let track = self.current_track.take().unwrap();
+                    self.play_new_track(track)?;
+
+                    if !was_playing {
+                        self.is_playing = false;
+                        self.sink.pause();
+                    }
+                }
 
-            self.sink.try_seek(position)?;
-            self.position = self.sink.get_pos();
+                self.sink.try_seek(position)?;
+                self.position = self.sink.get_pos();
+
+                self.controls.set_position(MediaPosition(self.position), self.is_playing)?;
+            },
         }
 
         Ok(())
     }
+
+    /// Seeks forward in the current track by `amount`.
+    pub fn seek_forward(&mut self, amount: Duration) -> Result<(), Box<dyn Error>> {
+        self.set_position(self.position.saturating_add(amount))
+    }
+
+    /// Seeks backward in the current track by `amount`, stopping at the start of the track.
+    pub fn seek_backward(&mut self, amount: Duration) -> Result<(), Box<dyn Error>> {
+        self.set_position(self.position.saturating_sub(amount))
+    }
+}
+
+impl ParsedManifest {
+    /// Returns a string displaying the quality of the stream (e.g. 24-Bit 96kHz FLAC), which is empty unless it is FLAC.
+    pub fn get_quality_string(&self) -> String {
+        let codec = self.codec.to_uppercase();
+
+        if codec != "FLAC" {
+            return "".to_string();
+        }
+
+        let sample_rate = (self.sample_rate as f32 / 1000.0).to_string();
+
+        format!("{}-Bit {}kHz {}", self.bit_depth, sample_rate, codec)
+    }
+}
+
+/// Where playback should go after a seek.
+#[derive(Debug, PartialEq)]
+enum SeekTarget {
+    /// Seek to a position within the current track.
+    Position(Duration),
+    /// The seek reached the end of the current track, so skip to the next track.
+    Skip,
+}
+
+impl SeekTarget {
+    /// Resolves a requested seek `position` against the current track's `duration`.
+    ///
+    /// A zero `duration` means the duration is unknown, so the position is used as is.
+    fn resolve(position: Duration, duration: Duration) -> Self {
+        if !duration.is_zero() && position >= duration {
+            Self::Skip
+        } else {
+            Self::Position(position)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_sleep_from_either_clock() {
+        let poll = Duration::from_millis(50);
+        assert!(!Player::has_slept(poll, Some(poll)));
+        assert!(!Player::has_slept(poll, None));
+
+        assert!(Player::has_slept(poll, Some(Duration::from_secs(600))));
+        assert!(Player::has_slept(Duration::from_secs(600), Some(Duration::from_secs(600))));
+    }
+
+    #[test]
+    fn seek_within_track() {
+        let target = SeekTarget::resolve(Duration::from_secs(30), Duration::from_secs(180));
+        assert_eq!(target, SeekTarget::Position(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn seek_to_start() {
+        let target = SeekTarget::resolve(Duration::ZERO, Duration::from_secs(180));
+        assert_eq!(target, SeekTarget::Position(Duration::ZERO));
+    }
+
+    #[test]
+    fn seek_to_end_skips() {
+        let target = SeekTarget::resolve(Duration::from_secs(180), Duration::from_secs(180));
+        assert_eq!(target, SeekTarget::Skip);
+    }
+
+    #[test]
+    fn seek_past_end_skips() {
+        let target = SeekTarget::resolve(Duration::from_secs(500), Duration::from_secs(180));
+        assert_eq!(target, SeekTarget::Skip);
+    }
+
+    #[test]
+    fn seek_with_unknown_duration() {
+        let target = SeekTarget::resolve(Duration::from_secs(500), Duration::ZERO);
+        assert_eq!(target, SeekTarget::Position(Duration::from_secs(500)));
+    }
 }
@@ -0,0 +1,207 @@
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::Arc,
+    time::Duration,
+};
+
+use rtidalapi::{
+    Session,
+    Track,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::player::SavedQueue;
+
+/// The queue the app was last closed with and where it was up to, so that it can be picked up again on launch.
+///
+/// The state is stored in `player_state.toml` within the config directory.
+#[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PlayerState {
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    /// The id of the track it was up to, or `None` if there was nothing left to play.
+    current_track_id: Option<String>,
+    /// How far into the current track it was, in milliseconds.
+    position_ms: u64,
+    /// The ids of the tracks left to play after the current one, in order.
+    queue: Vec<String>,
+    /// The ids of the tracks played before the current one, most recent first.
+    queue_history: Vec<String>,
+    is_shuffled: bool,
+    /// Whether the queue was made by Tidal, like a radio.
+    is_auto_generated: bool,
+    /// Whether it was playing, rather than paused.
+    pub is_playing: bool,
+    /// Where the queue was playing from, as shown in the Now Playing bar.
+    pub playing_from: Option<String>,
+}
+
+impl PlayerState {
+    /// Name of the player state file within the config directory.
+    const FILE_NAME: &str = "player_state.toml";
+
+    /// Loads the player state from `config_folder_path`, which is empty if the app hasn't been closed yet.
+    pub fn load(config_folder_path: &str) -> Result<Self, String> {
+        let state_file = Path::new(config_folder_path).join(Self::FILE_NAME);
+
+        let mut state = if state_file.exists() {
+            let toml_str = fs::read_to_string(&state_file)
+                .map_err(|e| format!("Unable to read {}: {e}", state_file.display()))?;
+
+            toml::from_str(&toml_str)
+                .map_err(|e| format!("Unable to parse {}: {e}", state_file.display()))?
+        } else {
+            Self::default()
+        };
+
+        state.file = Some(state_file);
+
+        Ok(state)
+    }
+
+    /// Saves the player state to the file it was loaded from.
+    ///
+    /// It is written to a temporary file that then replaces the old one, so that being killed partway through
+    /// leaves the last state saved rather than a cut off file.
+    pub fn save(&self) -> Result<(), String> {
+        let Some(state_file) = &self.file else {
+            return Ok(());
+        };
+
+        let toml_str = toml::to_string(self)
+            .map_err(|e| format!("Unable to serialize the player state: {e}"))?;
+
+        let temp_file = state_file.with_extension("toml.tmp");
+        fs::write(&temp_file, toml_str)
+            .map_err(|e| format!("Unable to write {}: {e}", temp_file.display()))?;
+        fs::rename(&temp_file, state_file)
+            .map_err(|e| format!("Unable to write {}: {e}", state_file.display()))
+    }
+
+    /// Returns how many bytes the player state file takes up, which is 0 if it hasn't been saved.
+    pub fn get_size(&self) -> u64 {
+        self.file.as_ref()
+            .and_then(|state_file| fs::metadata(state_file).ok())
+            .map_or(0, |metadata| metadata.len())
+    }
+
+    /// Forgets the recorded queue and deletes the player state file, which is saved again the next time
+    /// the queue changes.
+    pub fn clear(&mut self) -> Result<(), String> {
+        *self = Self {
+            file: self.file.clone(),
+            ..Self::default()
+        };
+
+        match &self.file {
+            Some(state_file) if state_file.exists() => fs::remove_file(state_file)
+                .map_err(|e| format!("Unable to delete {}: {e}", state_file.display())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records `saved` as the queue and where it is up to, or that there is nothing left to play if it is `None`.
+    ///
+    /// Returns true if that is different from what was recorded before.
+    pub fn set_queue(&mut self, saved: Option<&SavedQueue>, is_playing: bool, playing_from: Option<String>) -> bool {
+        let state = match saved {
+            Some(saved) => Self {
+                file: self.file.clone(),
+                current_track_id: saved.current_track.as_ref().map(|track| track.id.clone()),
+                position_ms: saved.position.as_millis() as u64,
+                queue: get_ids(&saved.queue),
+                queue_history: get_ids(&saved.queue_history),
+                is_shuffled: saved.is_shuffled,
+                is_auto_generated: saved.is_auto_generated,
+                is_playing,
+                playing_from,
+            },
+            None => Self {
+                file: self.file.clone(),
+                ..Self::default()
+            },
+        };
+
+        let is_changed = state != *self;
+        *self = state;
+        is_changed
+    }
+
+    /// Returns the queue that was recorded, with its tracks' info left to be fetched,
+    /// or `None` if there was nothing left to play.
+    pub fn get_saved_queue(&self, session: &Arc<Session>) -> Option<SavedQueue> {
+        let current_track_id = self.current_track_id.clone()?;
+        let track = |id: &String| Track::new(Arc::clone(session), id.clone()).ok().map(Arc::new);
+
+        Some(SavedQueue {
+            current_track: track(&current_track_id),
+            position: Duration::from_millis(self.position_ms),
+            queue: self.queue.iter().filter_map(track).collect(),
+            queue_history: self.queue_history.iter().filter_map(track).collect(),
+            is_shuffled: self.is_shuffled,
+            is_auto_generated: self.is_auto_generated,
+        })
+    }
+}
+
+/// Returns the ids of `tracks`, in order.
+fn get_ids(tracks: &VecDeque<Arc<Track>>) -> Vec<String> {
+    tracks.iter().map(|track| track.id.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let state = PlayerState {
+            file: None,
+            current_track_id: Some(String::from("77646170")),
+            position_ms: 61_500,
+            queue: vec![String::from("77646171"), String::from("77646172")],
+            queue_history: vec![String::from("77646169")],
+            is_shuffled: true,
+            is_auto_generated: false,
+            is_playing: false,
+            playing_from: Some(String::from("OK Computer")),
+        };
+
+        let toml_str = toml::to_string(&state).unwrap();
+        assert_eq!(toml::from_str::<PlayerState>(&toml_str).unwrap(), state);
+    }
+
+    #[test]
+    fn clears_once_nothing_is_left_to_play() {
+        let mut state = PlayerState {
+            file: Some(PathBuf::from("player_state.toml")),
+            ..PlayerState::default()
+        };
+
+        assert!(!state.set_queue(None, false, None));
+
+        state.current_track_id = Some(String::from("77646170"));
+        state.is_playing = true;
+        assert!(state.set_queue(None, true, Some(String::from("Tracks"))));
+        assert_eq!(state.current_track_id, None);
+        assert!(!state.is_playing);
+        assert_eq!(state.file, Some(PathBuf::from("player_state.toml")));
+    }
+
+    #[test]
+    fn loads_nothing_to_resume_from_an_empty_file() {
+        let state: PlayerState = toml::from_str("").unwrap();
+
+        assert_eq!(state.current_track_id, None);
+        assert!(!state.is_playing);
+    }
+}
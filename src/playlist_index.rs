@@ -0,0 +1,136 @@
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use rtidalapi::User;
+use tokio::sync::mpsc;
+
+use crate::AppEvent;
+
+/// Which of the user's playlists each track is in, built in the background from the playlists' contents.
+///
+/// This is used to show where a track already is, so that it isn't added to the same playlist twice.
+#[derive(Clone, Default)]
+pub struct PlaylistIndex {
+    state: Arc<Mutex<IndexState>>,
+}
+
+#[derive(Default)]
+struct IndexState {
+    #[cfg_attr(not(feature = "unofficial"), allow(dead_code))]
+    is_started: bool,
+    /// The ids of the playlists that have been indexed, mapped to the ids of their tracks.
+    track_ids: HashMap<String, HashSet<String>>,
+    /// How many playlists have yet to be indexed, or `None` if the list of playlists hasn't been fetched.
+    playlists_left: Option<usize>,
+}
+
+impl PlaylistIndex {
+    /// Returns a new empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes `user`'s playlists in the background if that hasn't been started yet,
+    /// requesting a rerender through `tx` as each one is done.
+    ///
+    /// Playlists whose tracks can't be fetched are left out.
+    #[cfg(feature = "unofficial")]
+    pub fn build(&self, user: Arc<User>, tx: mpsc::Sender<AppEvent>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.is_started {
+                return;
+            }
+            state.is_started = true;
+        }
+
+        let state = Arc::clone(&self.state);
+        tokio::task::spawn_blocking(move || {
+            let Ok(playlists) = user.get_playlists() else {
+                // Try again the next time the index is needed.
+                state.lock().unwrap().is_started = false;
+                return;
+            };
+            state.lock().unwrap().playlists_left = Some(playlists.len());
+
+            for playlist in playlists {
+                let track_ids = playlist.get_tracks()
+                    .map(|tracks| tracks.iter().map(|track| track.id.clone()).collect())
+                    .ok();
+
+                let mut state = state.lock().unwrap();
+                if let Some(track_ids) = track_ids {
+                    state.add_playlist(playlist.id.clone(), track_ids);
+                }
+                state.playlists_left = state.playlists_left.map(|left| left.saturating_sub(1));
+                drop(state);
+
+                let _ = tx.try_send(AppEvent::ReRender);
+            }
+        });
+    }
+
+    /// Playlists can only be fetched with the unofficial API, so without it there is nothing to index.
+    #[cfg(not(feature = "unofficial"))]
+    pub fn build(&self, _user: Arc<User>, _tx: mpsc::Sender<AppEvent>) {}
+
+    /// Returns the ids of the indexed playlists that contain the track with id `track_id`.
+    pub fn get_playlists_containing(&self, track_id: &str) -> Vec<String> {
+        self.state.lock().unwrap().get_playlists_containing(track_id)
+    }
+
+    /// Returns true if every playlist has been indexed, so that a track missing from the index is in none of them.
+    pub fn is_complete(&self) -> bool {
+        self.state.lock().unwrap().playlists_left == Some(0)
+    }
+
+    /// Records that the track with id `track_id` was added to the playlist with id `playlist_id`.
+    pub fn insert(&self, playlist_id: &str, track_id: &str) {
+        if let Some(track_ids) = self.state.lock().unwrap().track_ids.get_mut(playlist_id) {
+            track_ids.insert(track_id.to_string());
+        }
+    }
+}
+
+impl IndexState {
+    /// Adds the playlist with id `playlist_id` containing `track_ids` to the index.
+    #[cfg_attr(not(feature = "unofficial"), allow(dead_code))]
+    fn add_playlist(&mut self, playlist_id: String, track_ids: HashSet<String>) {
+        self.track_ids.insert(playlist_id, track_ids);
+    }
+
+    /// Returns the ids of the indexed playlists that contain the track with id `track_id`.
+    fn get_playlists_containing(&self, track_id: &str) -> Vec<String> {
+        self.track_ids.iter()
+            .filter(|(_, track_ids)| track_ids.contains(track_id))
+            .map(|(playlist_id, _)| playlist_id.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_playlists_containing_a_track() {
+        let mut state = IndexState::default();
+        state.add_playlist(String::from("a"), HashSet::from([String::from("1"), String::from("2")]));
+        state.add_playlist(String::from("b"), HashSet::from([String::from("2")]));
+
+        assert_eq!(state.get_playlists_containing("1"), vec!["a"]);
+        assert!(state.get_playlists_containing("3").is_empty());
+
+        let mut found = state.get_playlists_containing("2");
+        found.sort();
+        assert_eq!(found, vec!["a", "b"]);
+    }
+}
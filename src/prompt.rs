@@ -0,0 +1,50 @@
+use crate::i18n::{
+    tr,
+    Message,
+};
+
+/// A text prompt open over the main pane, along with what has been typed into it.
+pub enum Prompt {
+    /// An id or link of a user or playlist to open, or of a track to queue.
+    OpenLink(String),
+    /// The path of an M3U playlist whose tracks are added to the collection.
+    ImportM3u(String),
+    /// What to search Tidal for.
+    Search(String),
+}
+
+impl Prompt {
+    /// Returns the title of this prompt.
+    pub fn get_title(&self) -> &'static str {
+        match self {
+            Self::OpenLink(_) => tr(Message::OpenLinkPrompt),
+            Self::ImportM3u(_) => tr(Message::ImportM3uPrompt),
+            Self::Search(_) => tr(Message::SearchPrompt),
+        }
+    }
+
+    /// Returns the hints shown at the bottom of this prompt.
+    pub fn get_footer(&self) -> String {
+        let submit = match self {
+            Self::OpenLink(_) => tr(Message::Open),
+            Self::ImportM3u(_) => tr(Message::Import),
+            Self::Search(_) => tr(Message::Search),
+        };
+
+        format!(" <Enter>: {submit}  <Esc>: {} ", tr(Message::Cancel))
+    }
+
+    /// Returns what has been typed into this prompt.
+    pub fn get_input(&self) -> &str {
+        match self {
+            Self::OpenLink(input) | Self::ImportM3u(input) | Self::Search(input) => input,
+        }
+    }
+
+    /// Returns what has been typed into this prompt, for editing.
+    pub fn get_input_mut(&mut self) -> &mut String {
+        match self {
+            Self::OpenLink(input) | Self::ImportM3u(input) | Self::Search(input) => input,
+        }
+    }
+}
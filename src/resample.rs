@@ -0,0 +1,453 @@
+use std::{
+    error::Error,
+    time::Duration,
+};
+
+use rand::{
+    rngs::StdRng,
+    Rng,
+    SeedableRng,
+};
+use rodio::{
+    source::SeekError,
+    ChannelCount,
+    SampleRate,
+    Source,
+};
+use rubato::{
+    FastFixedIn,
+    PolynomialDegree,
+    Resampler,
+    SincFixedIn,
+    SincInterpolationParameters,
+    SincInterpolationType,
+    WindowFunction,
+};
+use serde::Deserialize;
+
+/// How audio is resampled when the output device can't play the track's sample rate.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResamplerQuality {
+    /// rodio's own linear interpolation, which is the cheapest but can add audible aliasing.
+    Linear,
+    /// Cubic interpolation, a middle ground for slower machines.
+    Cubic,
+    /// Band-limited sinc interpolation, the most accurate.
+    #[default]
+    Sinc,
+}
+
+/// A `Source` resampled to a different sample rate with rubato, or passed through as is
+/// if it is already at that rate or rodio's own resampling was chosen.
+pub struct Resampled<S> {
+    source: S,
+    resampler: Option<ResamplerKind>,
+    sample_rate: SampleRate,
+    /// Output frames per input frame.
+    ratio: f64,
+    /// One buffer of input frames per channel.
+    input: Vec<Vec<f32>>,
+    /// Interleaved resampled samples that haven't been returned yet.
+    output: Vec<f32>,
+    output_index: usize,
+    /// How many frames to drop from the start of the output, which come before the start of the input.
+    frames_to_skip: usize,
+    /// How many frames have been read from the source and returned since the start or the last seek,
+    /// which the length of the last chunk is worked out from.
+    input_frames: usize,
+    output_frames: usize,
+    is_source_done: bool,
+}
+
+enum ResamplerKind {
+    Sinc(Box<SincFixedIn<f32>>),
+    Cubic(Box<FastFixedIn<f32>>),
+}
+
+/// A `Source` with TPDF dither added, so that reducing it to a lower bit depth doesn't add distortion.
+pub struct Dithered<S> {
+    source: S,
+    /// The size of one quantization step at the output bit depth, or `None` if no dither is added.
+    step: Option<f32>,
+    rng: StdRng,
+}
+
+impl<S: Source> Resampled<S> {
+    /// How many frames are resampled at a time.
+    const CHUNK_FRAMES: usize = 1024;
+
+    /// Returns `source` resampled to `sample_rate` using `quality`.
+    pub fn new(source: S, sample_rate: SampleRate, quality: ResamplerQuality) -> Result<Self, Box<dyn Error>> {
+        let channels = usize::from(source.channels().get());
+        let ratio = f64::from(sample_rate.get()) / f64::from(source.sample_rate().get());
+
+        let resampler = if source.sample_rate() == sample_rate {
+            None
+        } else {
+            match quality {
+                ResamplerQuality::Linear => None,
+                ResamplerQuality::Cubic => Some(ResamplerKind::Cubic(Box::new(
+                    FastFixedIn::new(ratio, 1.0, PolynomialDegree::Cubic, Self::CHUNK_FRAMES, channels)?,
+                ))),
+                ResamplerQuality::Sinc => {
+                    let parameters = SincInterpolationParameters {
+                        sinc_len: 256,
+                        f_cutoff: 0.95,
+                        oversampling_factor: 256,
+                        interpolation: SincInterpolationType::Linear,
+                        window: WindowFunction::BlackmanHarris2,
+                    };
+                    Some(ResamplerKind::Sinc(Box::new(
+                        SincFixedIn::new(ratio, 1.0, parameters, Self::CHUNK_FRAMES, channels)?,
+                    )))
+                },
+            }
+        };
+
+        let frames_to_skip = resampler.as_ref().map_or(0, ResamplerKind::get_start_delay);
+
+        Ok(Self {
+            source,
+            resampler,
+            sample_rate,
+            ratio,
+            input: vec![Vec::with_capacity(Self::CHUNK_FRAMES); channels],
+            output: vec![],
+            output_index: 0,
+            frames_to_skip,
+            input_frames: 0,
+            output_frames: 0,
+            is_source_done: false,
+        })
+    }
+
+    /// Returns true if the source is being resampled by rubato.
+    pub fn is_resampling(&self) -> bool {
+        self.resampler.is_some()
+    }
+
+    /// Resamples the next chunk of the source into `output`, returning false if there is nothing left.
+    fn refill(&mut self) -> bool {
+        let Some(resampler) = &mut self.resampler else {
+            return false;
+        };
+        if self.is_source_done {
+            return false;
+        }
+
+        for channel in &mut self.input {
+            channel.clear();
+        }
+
+        let frames_needed = resampler.input_frames_next();
+        'frames: while self.input[0].len() < frames_needed {
+            for channel in 0..self.input.len() {
+                match self.source.next() {
+                    Some(sample) => self.input[channel].push(sample),
+                    None => {
+                        self.is_source_done = true;
+                        break 'frames;
+                    },
+                }
+            }
+        }
+
+        let result = if self.is_source_done {
+            // Drop a partial last frame, then flush what is left in the resampler after the last chunk.
+            let frames = self.input.iter().map(Vec::len).min().unwrap_or(0);
+            for channel in &mut self.input {
+                channel.truncate(frames);
+            }
+
+            // The last chunk is padded with silence, so the output is cut off where the input ends.
+            self.input_frames += frames;
+            let len = ((self.input_frames as f64 * self.ratio).round() as usize + self.frames_to_skip)
+                .saturating_sub(self.output_frames);
+            resampler.process_partial(Some(&self.input)).and_then(|mut last| {
+                let flushed = resampler.process_partial::<Vec<f32>>(None)?;
+                for (channel, flushed) in last.iter_mut().zip(flushed) {
+                    channel.extend(flushed);
+                    channel.truncate(len);
+                }
+                Ok(last)
+            })
+        } else {
+            self.input_frames += frames_needed;
+            resampler.process(&self.input)
+        };
+
+        let Ok(resampled) = result else {
+            self.is_source_done = true;
+            return false;
+        };
+
+        self.output.clear();
+        self.output_index = 0;
+
+        let frames = resampled.first().map_or(0, Vec::len);
+        let skipped = self.frames_to_skip.min(frames);
+        self.frames_to_skip -= skipped;
+        for frame in skipped..frames {
+            self.output.extend(resampled.iter().map(|channel| channel[frame]));
+        }
+        self.output_frames += frames - skipped;
+
+        true
+    }
+}
+
+impl<S: Source> Iterator for Resampled<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.resampler.is_none() {
+            return self.source.next();
+        }
+
+        while self.output_index >= self.output.len() {
+            if !self.refill() {
+                return None;
+            }
+        }
+
+        self.output_index += 1;
+        Some(self.output[self.output_index - 1])
+    }
+}
+
+impl<S: Source> Source for Resampled<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        if self.resampler.is_some() {
+            None
+        } else {
+            self.source.current_span_len()
+        }
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        if self.resampler.is_some() {
+            self.sample_rate
+        } else {
+            self.source.sample_rate()
+        }
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.source.try_seek(position)?;
+
+        if let Some(resampler) = &mut self.resampler {
+            resampler.reset();
+            self.frames_to_skip = resampler.get_start_delay();
+        }
+        self.input_frames = 0;
+        self.output_frames = 0;
+        self.output.clear();
+        self.output_index = 0;
+        self.is_source_done = false;
+
+        Ok(())
+    }
+}
+
+impl ResamplerKind {
+    fn process(&mut self, input: &[Vec<f32>]) -> rubato::ResampleResult<Vec<Vec<f32>>> {
+        match self {
+            Self::Sinc(resampler) => resampler.process(input, None),
+            Self::Cubic(resampler) => resampler.process(input, None),
+        }
+    }
+
+    fn process_partial<V: AsRef<[f32]>>(&mut self, input: Option<&[V]>) -> rubato::ResampleResult<Vec<Vec<f32>>> {
+        match self {
+            Self::Sinc(resampler) => resampler.process_partial(input, None),
+            Self::Cubic(resampler) => resampler.process_partial(input, None),
+        }
+    }
+
+    fn input_frames_next(&self) -> usize {
+        match self {
+            Self::Sinc(resampler) => resampler.input_frames_next(),
+            Self::Cubic(resampler) => resampler.input_frames_next(),
+        }
+    }
+
+    /// Returns how many frames of the output come before the start of the input.
+    ///
+    /// The sinc resampler's output starts in line with the input, and its delay only shows up at the end,
+    /// while the polynomial one's starts out that far behind.
+    fn get_start_delay(&self) -> usize {
+        match self {
+            Self::Sinc(_) => 0,
+            Self::Cubic(resampler) => resampler.output_delay(),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Self::Sinc(resampler) => resampler.reset(),
+            Self::Cubic(resampler) => resampler.reset(),
+        }
+    }
+}
+
+impl<S: Source> Dithered<S> {
+    /// Returns `source` with dither added for an output of `bit_depth` bits, or unchanged if `bit_depth` is `None`.
+    pub fn new(source: S, bit_depth: Option<u32>) -> Self {
+        Self {
+            source,
+            step: bit_depth.map(|bit_depth| 1.0 / 2f32.powi(bit_depth as i32 - 1)),
+            rng: StdRng::from_rng(&mut rand::rng()),
+        }
+    }
+}
+
+impl<S: Source> Iterator for Dithered<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.source.next()?;
+
+        let Some(step) = self.step else {
+            return Some(sample);
+        };
+
+        // The difference of two uniform random values has a triangular distribution of up to one step either way.
+        let noise = self.rng.random::<f32>() - self.rng.random::<f32>();
+        Some(sample + noise * step)
+    }
+}
+
+impl<S: Source> Source for Dithered<S> {
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+
+    fn try_seek(&mut self, position: Duration) -> Result<(), SeekError> {
+        self.source.try_seek(position)
+    }
+}
+
+/// Returns the bit depth to dither to when playing audio of `source_bit_depth` bits to an output of `sample_format`,
+/// or `None` if the output can represent it without losing precision.
+///
+/// Resampled audio always needs dithering for integer outputs, since it no longer fits the original bit depth exactly.
+pub fn get_dither_bit_depth(sample_format: cpal::SampleFormat, source_bit_depth: u32, is_resampling: bool) -> Option<u32> {
+    if sample_format.is_float() {
+        return None;
+    }
+
+    let output_bit_depth = sample_format.sample_size() as u32 * 8;
+    (is_resampling || output_bit_depth < source_bit_depth).then_some(output_bit_depth)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::*;
+
+    /// Returns `frames` frames of a stereo sine wave at 44.1 kHz.
+    fn sine(frames: usize) -> SamplesBuffer {
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|frame| {
+                let sample = (frame as f32 * 440.0 * std::f32::consts::TAU / 44_100.0).sin() * 0.5;
+                [sample, sample]
+            })
+            .collect();
+
+        SamplesBuffer::new(NonZero::new(2).unwrap(), NonZero::new(44_100).unwrap(), samples)
+    }
+
+    /// Returns `source` resampled to 48 kHz using `quality`.
+    fn resample(source: SamplesBuffer, quality: ResamplerQuality) -> Resampled<SamplesBuffer> {
+        let resampled = Resampled::new(source, NonZero::new(48_000).unwrap(), quality).unwrap();
+        assert!(resampled.is_resampling());
+        resampled
+    }
+
+    #[test]
+    fn output_length_follows_ratio() {
+        // Not a whole number of chunks, so that the last one is partial.
+        let frames = 10_000;
+        let expected_frames = frames as f64 * 48_000.0 / 44_100.0;
+
+        for quality in [ResamplerQuality::Sinc, ResamplerQuality::Cubic] {
+            let output: Vec<f32> = resample(sine(frames), quality).collect();
+
+            assert_eq!(output.len() % 2, 0);
+            let output_frames = (output.len() / 2) as f64;
+            assert!((output_frames - expected_frames).abs() <= 1.0, "{quality:?}: {output_frames} frames");
+        }
+    }
+
+    #[test]
+    fn keeps_start_of_stream() {
+        for quality in [ResamplerQuality::Sinc, ResamplerQuality::Cubic] {
+            let output: Vec<f32> = resample(sine(10_000), quality).take(200).collect();
+
+            // Past the first few frames, which the resampler fades in, the output follows the same sine wave at 48 kHz.
+            for frame in 20..100 {
+                let expected = (frame as f32 * 440.0 * std::f32::consts::TAU / 48_000.0).sin() * 0.5;
+                assert!((output[frame * 2] - expected).abs() < 0.02, "{quality:?}: frame {frame}");
+            }
+        }
+    }
+
+    #[test]
+    fn flushes_tail_at_end_of_stream() {
+        let frames = 10_000;
+        let output: Vec<f32> = resample(sine(frames), ResamplerQuality::Sinc).collect();
+
+        // The end of the input is still there rather than cut off by the resampler's delay.
+        let tail = &output[output.len() - 200..output.len() - 100];
+        assert!(tail.iter().any(|sample| sample.abs() > 0.1));
+    }
+
+    #[test]
+    fn seeking_resets_resampler() {
+        for quality in [ResamplerQuality::Sinc, ResamplerQuality::Cubic] {
+            let fresh: Vec<f32> = resample(sine(10_000), quality).take(4000).collect();
+
+            let mut resampled = resample(sine(10_000), quality);
+            resampled.by_ref().take(5000).for_each(drop);
+            resampled.try_seek(Duration::ZERO).unwrap();
+            let after_seek: Vec<f32> = resampled.take(4000).collect();
+
+            assert_eq!(after_seek.len(), fresh.len());
+            for (a, b) in after_seek.iter().zip(&fresh) {
+                assert!((a - b).abs() < 1e-5, "{quality:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn dithers_only_when_precision_is_lost() {
+        assert_eq!(get_dither_bit_depth(cpal::SampleFormat::I16, 24, false), Some(16));
+        assert_eq!(get_dither_bit_depth(cpal::SampleFormat::I16, 16, false), None);
+        assert_eq!(get_dither_bit_depth(cpal::SampleFormat::I16, 16, true), Some(16));
+        assert_eq!(get_dither_bit_depth(cpal::SampleFormat::F32, 24, true), None);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::i18n::{
+    tr,
+    Message,
+};
+
+/// One of the screens the main pane can show, switched between like tabs.
+///
+/// Each route keeps its own stack of opened views, so switching away and back returns to where it was left.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Route {
+    /// Tidal's personalized home page.
+    Home,
+    /// My Collection - Tracks.
+    #[default]
+    Tracks,
+    /// The albums in the user's collection.
+    Albums,
+    /// The artists the user follows.
+    Artists,
+    /// The user's own playlists.
+    Playlists,
+    /// Searches of Tidal, most recent last.
+    Search,
+    /// The queue, taking up the whole main pane.
+    Queue,
+    /// The settings loaded from the config file.
+    Settings,
+}
+
+impl Route {
+    /// All routes, in the order they are shown and numbered.
+    pub const ALL: [Self; 8] = [
+        Self::Home,
+        Self::Tracks,
+        Self::Albums,
+        Self::Artists,
+        Self::Playlists,
+        Self::Search,
+        Self::Queue,
+        Self::Settings,
+    ];
+
+    /// Returns the name shown for this route.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Home => tr(Message::Home),
+            Self::Tracks => tr(Message::Tracks),
+            Self::Albums => tr(Message::Albums),
+            Self::Artists => tr(Message::Artists),
+            Self::Playlists => tr(Message::Playlists),
+            Self::Search => tr(Message::Search),
+            Self::Queue => tr(Message::Queue),
+            Self::Settings => tr(Message::Settings),
+        }
+    }
+
+    /// Returns the route `offset` routes after (or before, if negative) this one, wrapping around.
+    pub fn cycle(&self, offset: isize) -> Self {
+        let index = Self::ALL.iter().position(|route| route == self).unwrap_or(0) as isize;
+        let len = Self::ALL.len() as isize;
+
+        Self::ALL[(index + offset).rem_euclid(len) as usize]
+    }
+
+    /// Returns true if this route always has a view open once it is shown, so going back stops at its first one.
+    /// For search, that is the first search.
+    pub fn has_root_view(&self) -> bool {
+        matches!(self, Self::Home | Self::Albums | Self::Artists | Self::Playlists | Self::Search | Self::Settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_routes() {
+        assert_eq!(Route::Tracks.cycle(1), Route::Albums);
+        assert_eq!(Route::Tracks.cycle(-1), Route::Home);
+        assert_eq!(Route::Settings.cycle(1), Route::Home);
+        assert_eq!(Route::Albums.cycle(3), Route::Search);
+        assert_eq!(Route::Albums.cycle(-9), Route::Tracks);
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    error::Error,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use crate::{
+    keymap::Action,
+    player::Player,
+};
+
+/// Something a script asked the app to do, which is done once the script returns.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptRequest {
+    /// Runs an action, as if its keybind was pressed.
+    RunAction(Action),
+    /// Shows a message in the OSD.
+    ShowOsd(String),
+    /// Opens a popup with a title and lines of text.
+    ShowView {
+        title: String,
+        lines: Vec<String>,
+    },
+}
+
+/// A command added by a script, listed in the script commands menu.
+#[cfg(feature = "scripting")]
+#[derive(Clone, Debug)]
+pub struct ScriptCommand {
+    pub name: String,
+    /// The index of the script that added the command.
+    script: usize,
+    /// The name of the script's function that is called when the command is run.
+    function: String,
+}
+
+/// Returns the path of the folder that scripts are loaded from, in the config folder at `config_folder_path`.
+pub fn get_scripts_path(config_folder_path: &str) -> PathBuf {
+    Path::new(config_folder_path).join("scripts")
+}
+
+/// Returns the paths of the scripts in the folder at `scripts_path`, sorted by name so that they load in a set order.
+/// If there is no such folder, there are no scripts.
+fn find_scripts(scripts_path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !scripts_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(scripts_path)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rhai"))
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// The user's Rhai scripts, which can add commands, change what keybinds do, react to playback changes,
+/// and open simple views, with read-only access to the player's state.
+#[cfg(feature = "scripting")]
+pub struct Scripts {
+    engine: rhai::Engine,
+    /// Each script's file name and compiled code, in the order they were loaded.
+    scripts: Vec<(String, rhai::AST)>,
+    commands: Arc<Mutex<Vec<ScriptCommand>>>,
+    /// What the scripts have asked for since they were last taken.
+    requests: Arc<Mutex<Vec<ScriptRequest>>>,
+    player: Arc<Mutex<Player>>,
+    /// What was playing when the scripts were last told about playback changes.
+    last: crate::hooks::Snapshot,
+}
+
+#[cfg(feature = "scripting")]
+impl Scripts {
+    /// Scripts are stopped after this many operations, so that one stuck in a loop doesn't freeze the app.
+    const MAX_OPERATIONS: u64 = 1_000_000;
+
+    /// Loads and runs the scripts in the config folder at `config_folder_path`, with `player` as the state they can read.
+    ///
+    /// Fails if a script can't be read or has a syntax error, naming the script.
+    pub fn load(config_folder_path: &str, player: Arc<Mutex<Player>>) -> Result<Self, Box<dyn Error>> {
+        use std::sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        };
+
+        use rhai::{
+            Array,
+            Dynamic,
+            EvalAltResult,
+            Position,
+        };
+
+        use crate::status_server::PlayerStatus;
+
+        let commands = Arc::new(Mutex::new(vec![]));
+        let requests = Arc::new(Mutex::new(vec![]));
+        // Which script is being run, to know which one a command was added by.
+        let current_script = Arc::new(AtomicUsize::new(0));
+
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(Self::MAX_OPERATIONS);
+
+        // Anything printed would be drawn over the TUI, so it is shown in the OSD instead.
+        let requests_clone = Arc::clone(&requests);
+        engine.on_print(move |text| requests_clone.lock().unwrap().push(ScriptRequest::ShowOsd(text.to_string())));
+        engine.on_debug(|_, _, _| {});
+
+        let player_clone = Arc::clone(&player);
+        engine.register_fn("state", move || -> Result<Dynamic, Box<EvalAltResult>> {
+            let status = PlayerStatus::from_player(&player_clone.lock().unwrap());
+            rhai::serde::to_dynamic(status)
+        });
+
+        let requests_clone = Arc::clone(&requests);
+        engine.register_fn("run_action", move |name: &str| -> Result<(), Box<EvalAltResult>> {
+            let action = parse_action(name)
+                .ok_or_else(|| EvalAltResult::ErrorRuntime(format!("Unknown action: {name}").into(), Position::NONE))?;
+            requests_clone.lock().unwrap().push(ScriptRequest::RunAction(action));
+            Ok(())
+        });
+
+        let requests_clone = Arc::clone(&requests);
+        engine.register_fn("osd", move |text: &str| {
+            requests_clone.lock().unwrap().push(ScriptRequest::ShowOsd(text.to_string()));
+        });
+
+        let requests_clone = Arc::clone(&requests);
+        engine.register_fn("show_view", move |title: &str, lines: Array| {
+            let lines = lines.into_iter().map(|line| line.to_string()).collect();
+            requests_clone.lock().unwrap().push(ScriptRequest::ShowView { title: title.to_string(), lines });
+        });
+
+        let commands_clone = Arc::clone(&commands);
+        let current_script_clone = Arc::clone(&current_script);
+        engine.register_fn("register_command", move |name: &str, function: &str| {
+            commands_clone.lock().unwrap().push(ScriptCommand {
+                name: name.to_string(),
+                script: current_script_clone.load(Ordering::Relaxed),
+                function: function.to_string(),
+            });
+        });
+
+        let mut scripts = vec![];
+        for path in find_scripts(&get_scripts_path(config_folder_path))? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let ast = engine.compile_file(path)
+                .map_err(|e| format!("Unable to load script {name}: {e}"))?;
+
+            // The top level of each script is run once, which is where commands are added.
+            current_script.store(scripts.len(), Ordering::Relaxed);
+            if let Err(e) = engine.run_ast(&ast) {
+                requests.lock().unwrap().push(ScriptRequest::ShowOsd(format!("{name}: {e}")));
+            }
+
+            scripts.push((name, ast));
+        }
+
+        let last = crate::hooks::Snapshot::from_player(&player.lock().unwrap());
+
+        Ok(Self {
+            engine,
+            scripts,
+            commands,
+            requests,
+            player,
+            last,
+        })
+    }
+
+    /// Calls the `on_<event>` functions of the scripts for the playback changes since this was last called,
+    /// passing them the player's state.
+    pub fn update(&mut self) {
+        use crate::{
+            hooks::{
+                get_events,
+                Snapshot,
+            },
+            status_server::PlayerStatus,
+        };
+
+        if self.scripts.is_empty() {
+            return;
+        }
+
+        let current = Snapshot::from_player(&self.player.lock().unwrap());
+        let events = get_events(&self.last, &current);
+        self.last = current;
+        if events.is_empty() {
+            return;
+        }
+
+        let status = PlayerStatus::from_player(&self.player.lock().unwrap());
+        let Ok(state) = rhai::serde::to_dynamic(status) else {
+            return;
+        };
+        for event in events {
+            self.call_all(&format!("on_{}", event.get_name()), state.clone());
+        }
+    }
+
+    /// Calls the `on_action` functions of the scripts with the name of `action`, before it is run.
+    ///
+    /// Returns true if one of them returned true, in which case the script has handled the action and it isn't run.
+    pub fn on_action(&mut self, action: Action) -> bool {
+        let Ok(name) = rhai::serde::to_dynamic(action) else {
+            return false;
+        };
+
+        self.call_all("on_action", name)
+            .into_iter()
+            .any(|result| result.as_bool().unwrap_or(false))
+    }
+
+    /// Returns the names of the commands the scripts added, in the order they were added.
+    pub fn get_command_names(&self) -> Vec<String> {
+        self.commands.lock().unwrap()
+            .iter()
+            .map(|command| command.name.clone())
+            .collect()
+    }
+
+    /// Runs the command at `index` in the commands the scripts added.
+    pub fn run_command(&mut self, index: usize) {
+        let Some(command) = self.commands.lock().unwrap().get(index).cloned() else {
+            return;
+        };
+        let Some((name, ast)) = self.scripts.get(command.script) else {
+            return;
+        };
+
+        if let Err(e) = self.engine.call_fn::<rhai::Dynamic>(&mut rhai::Scope::new(), ast, &command.function, ()) {
+            self.requests.lock().unwrap().push(ScriptRequest::ShowOsd(format!("{name}: {e}")));
+        }
+    }
+
+    /// Returns what the scripts have asked for since this was last called, in the order they asked.
+    pub fn take_requests(&self) -> Vec<ScriptRequest> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+
+    /// Calls the function named `function` with `arg` in each script that has one, returning what each returned.
+    /// Errors are shown in the OSD.
+    fn call_all(&self, function: &str, arg: rhai::Dynamic) -> Vec<rhai::Dynamic> {
+        let mut results = vec![];
+
+        for (name, ast) in &self.scripts {
+            if !ast.iter_functions().any(|metadata| metadata.name == function && metadata.params.len() == 1) {
+                continue;
+            }
+
+            match self.engine.call_fn::<rhai::Dynamic>(&mut rhai::Scope::new(), ast, function, (arg.clone(),)) {
+                Ok(result) => results.push(result),
+                Err(e) => self.requests.lock().unwrap().push(ScriptRequest::ShowOsd(format!("{name}: {e}"))),
+            }
+        }
+
+        results
+    }
+}
+
+/// Returns the action named `name` as it is written in the keybinds config (e.g. "next_track"),
+/// or `None` if there is no such action.
+#[cfg(feature = "scripting")]
+fn parse_action(name: &str) -> Option<Action> {
+    use serde::{
+        de::{
+            value::Error as ValueError,
+            IntoDeserializer,
+        },
+        Deserialize,
+    };
+
+    Action::deserialize(IntoDeserializer::<ValueError>::into_deserializer(name)).ok()
+}
+
+/// Scripting was left out of this build, so there are never any scripts.
+#[cfg(not(feature = "scripting"))]
+pub struct Scripts;
+
+#[cfg(not(feature = "scripting"))]
+impl Scripts {
+    /// Fails if there are scripts in the config folder at `config_folder_path`, since they can't be run.
+    pub fn load(config_folder_path: &str, _player: Arc<Mutex<Player>>) -> Result<Self, Box<dyn Error>> {
+        if !find_scripts(&get_scripts_path(config_folder_path))?.is_empty() {
+            return Err("There are scripts in the config folder, but tidal-tui was built without the scripting feature".into());
+        }
+
+        Ok(Self)
+    }
+
+    pub fn update(&mut self) {}
+
+    pub fn on_action(&mut self, _action: Action) -> bool {
+        false
+    }
+
+    pub fn get_command_names(&self) -> Vec<String> {
+        vec![]
+    }
+
+    pub fn run_command(&mut self, _index: usize) {}
+
+    pub fn take_requests(&self) -> Vec<ScriptRequest> {
+        vec![]
+    }
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_action_names_to_scripts() {
+        assert_eq!(rhai::serde::to_dynamic(Action::NextTrack).unwrap().into_string().unwrap(), "next_track");
+    }
+
+    #[test]
+    fn parses_action_names() {
+        assert_eq!(parse_action("next_track"), Some(Action::NextTrack));
+        assert_eq!(parse_action("toggle_play_pause"), Some(Action::TogglePlayPause));
+        assert_eq!(parse_action("NextTrack"), None);
+        assert_eq!(parse_action("fly"), None);
+    }
+}
@@ -0,0 +1,564 @@
+use std::{
+    io,
+    mem,
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use chrono::Utc;
+use rtidalapi::Track;
+use serde_json::{
+    json,
+    Value,
+};
+use tokio::runtime::Handle;
+
+use crate::{
+    config::{
+        Config,
+        ScrobblingConfig,
+    },
+    get_config_path,
+    player::Player,
+};
+
+/// The Last.fm API's endpoint, which every method is posted to.
+const LASTFM_API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+/// The page where the user allows an API account to scrobble for them.
+const LASTFM_AUTH_URL: &str = "https://www.last.fm/api/auth/";
+const LISTENBRAINZ_API_URL: &str = "https://api.listenbrainz.org/1";
+/// Tracks shorter than this are never scrobbled, as Last.fm asks.
+const MIN_SCROBBLE_DURATION: Duration = Duration::from_secs(30);
+/// The most scrobbles that are kept to retry after they fail to be sent, which is also the most that Last.fm takes
+/// at once. The oldest are dropped first.
+const MAX_UNSENT_SCROBBLES: usize = 50;
+
+/// A track that was listened to, as it is sent to the scrobbling services.
+#[derive(Clone, Debug, PartialEq)]
+struct Listen {
+    artist: String,
+    title: String,
+    album: Option<String>,
+    isrc: Option<String>,
+    duration: Duration,
+    /// When the track started playing, as a Unix timestamp.
+    started_at: i64,
+}
+
+impl Listen {
+    /// Returns the listen of `track` that started at `started_at`, or `None` if its info hasn't been fetched yet.
+    fn from_track(track: &Track, started_at: i64) -> Option<Self> {
+        let attributes = track.get_cached_attributes()?;
+
+        Some(Self {
+            artist: track.get_cached_artist_credits()?.get_main()?.attributes.name.clone(),
+            title: attributes.get_full_title(),
+            album: track.get_cached_album().map(|album| album.attributes.title.clone()),
+            isrc: Some(attributes.isrc.clone()).filter(|isrc| !isrc.is_empty()),
+            duration: *track.get_duration().ok()?,
+            started_at,
+        })
+    }
+}
+
+/// How much of the current track has been listened to, to tell when to scrobble it.
+#[derive(Debug)]
+struct Listening {
+    track_id: String,
+    /// When the track started playing, as a Unix timestamp.
+    started_at: i64,
+    /// How long the track has been playing, not counting time spent paused or seeked over.
+    listened: Duration,
+    last_position: Duration,
+    is_now_playing_sent: bool,
+    is_scrobbled: bool,
+}
+
+impl Listening {
+    /// Starts listening to the track with `track_id`, which is at `position` at the time `now`.
+    fn new(track_id: String, position: Duration, now: i64) -> Self {
+        Self {
+            track_id,
+            started_at: now - position.as_secs() as i64,
+            listened: Duration::ZERO,
+            last_position: position,
+            is_now_playing_sent: false,
+            is_scrobbled: false,
+        }
+    }
+
+    /// Returns whether the track with `track_id` at `position` is a new listen, rather than this one going on:
+    /// either a different track, or this one started over after it was scrobbled, like when it is on repeat.
+    fn is_new_listen(&self, track_id: &str, position: Duration) -> bool {
+        self.track_id != track_id
+            || (self.is_scrobbled && position < self.last_position && position < Duration::from_secs(2))
+    }
+}
+
+/// A service that listens and loves are sent to.
+#[derive(Clone, Debug)]
+enum Service {
+    Lastfm {
+        api_key: String,
+        api_secret: String,
+        session_key: String,
+    },
+    ListenBrainz {
+        token: String,
+    },
+}
+
+/// A service along with the scrobbles that failed to be sent to it, which are retried with the next one.
+#[derive(Clone, Debug)]
+struct Account {
+    service: Service,
+    unsent: Arc<Mutex<Vec<Listen>>>,
+}
+
+/// Sends what is listened to, and which tracks are loved, to Last.fm and ListenBrainz.
+#[derive(Clone, Debug)]
+pub struct Scrobbler {
+    accounts: Vec<Account>,
+    threshold_percent: u32,
+    threshold_secs: u64,
+    love_favorites: bool,
+    client: reqwest::Client,
+    /// The runtime that the requests are sent from, so that they can be sent from any thread.
+    runtime: Handle,
+}
+
+impl Scrobbler {
+    /// Returns a scrobbler for the services set up in `config`, or `None` if there are none.
+    ///
+    /// This has to be called from within the Tokio runtime.
+    pub fn new(config: &ScrobblingConfig) -> Option<Self> {
+        let lastfm = config.lastfm.as_ref().and_then(|lastfm| Some(Service::Lastfm {
+            api_key: lastfm.api_key.clone(),
+            api_secret: lastfm.api_secret.clone(),
+            session_key: lastfm.session_key.clone().filter(|key| !key.is_empty())?,
+        }));
+        let listenbrainz = config.listenbrainz.as_ref()
+            .filter(|listenbrainz| !listenbrainz.token.is_empty())
+            .map(|listenbrainz| Service::ListenBrainz { token: listenbrainz.token.clone() });
+
+        let accounts: Vec<Account> = [lastfm, listenbrainz]
+            .into_iter()
+            .flatten()
+            .map(|service| Account { service, unsent: Arc::new(Mutex::new(vec![])) })
+            .collect();
+        if accounts.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            accounts,
+            threshold_percent: config.threshold_percent,
+            threshold_secs: config.threshold_secs,
+            love_favorites: config.love_favorites,
+            client: reqwest::Client::new(),
+            runtime: Handle::try_current().ok()?,
+        })
+    }
+
+    /// Watches `player` from a background thread, sending each track as now playing when it starts, and scrobbling
+    /// it once enough of it has been listened to.
+    pub fn start(&self, player: Arc<Mutex<Player>>) {
+        /// How often the player is checked, which is also how precisely the time listened is counted.
+        const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+        let scrobbler = self.clone();
+        thread::spawn(move || {
+            let mut listening: Option<Listening> = None;
+            let mut last_poll = Instant::now();
+
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                // Time spent asleep, or waiting for the player, isn't counted as listened.
+                let elapsed = last_poll.elapsed().min(POLL_INTERVAL * 2);
+                last_poll = Instant::now();
+
+                let Ok(unlocked_player) = player.lock() else {
+                    continue;
+                };
+                let Some(track) = unlocked_player.get_current_track().cloned() else {
+                    listening = None;
+                    continue;
+                };
+                let is_playing = unlocked_player.is_playing();
+                let position = unlocked_player.get_position();
+                drop(unlocked_player);
+
+                if listening.as_ref().is_none_or(|listening| listening.is_new_listen(&track.id, position)) {
+                    listening = Some(Listening::new(track.id.clone(), position, Utc::now().timestamp()));
+                }
+                let Some(listening) = listening.as_mut() else {
+                    continue;
+                };
+                if is_playing {
+                    listening.listened += elapsed;
+                }
+                listening.last_position = position;
+
+                // The track's info may only be fetched a moment after it starts playing.
+                let Some(listen) = Listen::from_track(&track, listening.started_at) else {
+                    continue;
+                };
+
+                if is_playing && !listening.is_now_playing_sent {
+                    listening.is_now_playing_sent = true;
+                    scrobbler.send_now_playing(listen.clone());
+                }
+
+                let threshold = get_scrobble_threshold(listen.duration, scrobbler.threshold_percent, scrobbler.threshold_secs);
+                if !listening.is_scrobbled && threshold.is_some_and(|threshold| listening.listened >= threshold) {
+                    listening.is_scrobbled = true;
+                    scrobbler.scrobble(listen);
+                }
+            }
+        });
+    }
+
+    /// Loves the track `title` by `artist` on each service, or takes the love back if `is_loved` is false,
+    /// if favorites are synced as loves.
+    ///
+    /// This is sent in the background, and a love that fails isn't retried.
+    pub fn set_loved(&self, artist: &str, title: &str, is_loved: bool) {
+        if !self.love_favorites {
+            return;
+        }
+
+        let scrobbler = self.clone();
+        let artist = artist.to_string();
+        let title = title.to_string();
+        self.runtime.spawn(async move {
+            for account in &scrobbler.accounts {
+                let _ = account.service.set_loved(&scrobbler.client, &artist, &title, is_loved).await;
+            }
+        });
+    }
+
+    /// Sends `listen` as what is playing now in the background. It is only shown while the track plays, so it isn't
+    /// retried if it fails.
+    fn send_now_playing(&self, listen: Listen) {
+        let scrobbler = self.clone();
+        self.runtime.spawn(async move {
+            for account in &scrobbler.accounts {
+                let _ = account.service.send_now_playing(&scrobbler.client, &listen).await;
+            }
+        });
+    }
+
+    /// Scrobbles `listen` in the background, along with any scrobbles that failed to be sent before.
+    fn scrobble(&self, listen: Listen) {
+        let scrobbler = self.clone();
+        self.runtime.spawn(async move {
+            for account in &scrobbler.accounts {
+                let mut listens = account.unsent.lock()
+                    .map(|mut unsent| mem::take(&mut *unsent))
+                    .unwrap_or_default();
+                listens.push(listen.clone());
+                let excess = listens.len().saturating_sub(MAX_UNSENT_SCROBBLES);
+                listens.drain(..excess);
+
+                if account.service.scrobble(&scrobbler.client, &listens).await.is_err()
+                    && let Ok(mut unsent) = account.unsent.lock()
+                {
+                    // Anything that failed to be sent while this one was being sent is newer.
+                    listens.append(&mut unsent);
+                    *unsent = listens;
+                }
+            }
+        });
+    }
+}
+
+impl Service {
+    /// Sends `listen` as what is playing now.
+    async fn send_now_playing(&self, client: &reqwest::Client, listen: &Listen) -> Result<(), String> {
+        match self {
+            Self::Lastfm { .. } => {
+                let mut params = vec![
+                    (String::from("artist"), listen.artist.clone()),
+                    (String::from("track"), listen.title.clone()),
+                    (String::from("duration"), listen.duration.as_secs().to_string()),
+                ];
+                if let Some(album) = &listen.album {
+                    params.push((String::from("album"), album.clone()));
+                }
+
+                self.call_lastfm(client, "track.updateNowPlaying", params).await.map(|_| ())
+            },
+            Self::ListenBrainz { .. } => {
+                let payload = json!({
+                    "listen_type": "playing_now",
+                    "payload": [{ "track_metadata": get_listenbrainz_metadata(listen) }],
+                });
+
+                self.submit_listenbrainz(client, &payload).await
+            },
+        }
+    }
+
+    /// Scrobbles `listens`, which are at most `MAX_UNSENT_SCROBBLES`.
+    async fn scrobble(&self, client: &reqwest::Client, listens: &[Listen]) -> Result<(), String> {
+        match self {
+            Self::Lastfm { .. } => {
+                let mut params = vec![];
+                for (i, listen) in listens.iter().enumerate() {
+                    params.extend([
+                        (format!("artist[{i}]"), listen.artist.clone()),
+                        (format!("track[{i}]"), listen.title.clone()),
+                        (format!("timestamp[{i}]"), listen.started_at.to_string()),
+                        (format!("duration[{i}]"), listen.duration.as_secs().to_string()),
+                    ]);
+                    if let Some(album) = &listen.album {
+                        params.push((format!("album[{i}]"), album.clone()));
+                    }
+                }
+
+                self.call_lastfm(client, "track.scrobble", params).await.map(|_| ())
+            },
+            Self::ListenBrainz { .. } => {
+                let payload = listens.iter()
+                    .map(|listen| json!({
+                        "listened_at": listen.started_at,
+                        "track_metadata": get_listenbrainz_metadata(listen),
+                    }))
+                    .collect::<Vec<_>>();
+                let listen_type = if listens.len() == 1 { "single" } else { "import" };
+
+                self.submit_listenbrainz(client, &json!({ "listen_type": listen_type, "payload": payload })).await
+            },
+        }
+    }
+
+    /// Loves the track `title` by `artist`, or takes the love back if `is_loved` is false.
+    async fn set_loved(&self, client: &reqwest::Client, artist: &str, title: &str, is_loved: bool) -> Result<(), String> {
+        match self {
+            Self::Lastfm { .. } => {
+                let method = if is_loved { "track.love" } else { "track.unlove" };
+                let params = vec![
+                    (String::from("artist"), artist.to_string()),
+                    (String::from("track"), title.to_string()),
+                ];
+
+                self.call_lastfm(client, method, params).await.map(|_| ())
+            },
+            Self::ListenBrainz { token } => {
+                // Feedback is given on a MusicBrainz recording, so the track has to be looked up first.
+                let lookup: Value = client.get(format!("{LISTENBRAINZ_API_URL}/metadata/lookup/"))
+                    .query(&[("artist_name", artist), ("recording_name", title)])
+                    .send().await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| e.to_string())?
+                    .text().await
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))?;
+                let recording_mbid = lookup.get("recording_mbid")
+                    .and_then(Value::as_str)
+                    .ok_or(format!("{title} by {artist} isn't on MusicBrainz"))?;
+
+                let feedback = json!({
+                    "recording_mbid": recording_mbid,
+                    "score": if is_loved { 1 } else { 0 },
+                });
+                client.post(format!("{LISTENBRAINZ_API_URL}/feedback/recording-feedback"))
+                    .header("Authorization", format!("Token {token}"))
+                    .header("Content-Type", "application/json")
+                    .body(feedback.to_string())
+                    .send().await
+                    .and_then(|response| response.error_for_status())
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            },
+        }
+    }
+
+    /// Calls the Last.fm API `method` with `params` on behalf of the user, returning its response.
+    async fn call_lastfm(&self, client: &reqwest::Client, method: &str, mut params: Vec<(String, String)>) -> Result<Value, String> {
+        let Self::Lastfm { api_key, api_secret, session_key } = self else {
+            return Err(String::from("Not a Last.fm account"));
+        };
+
+        params.push((String::from("sk"), session_key.clone()));
+        call_lastfm(client, api_key, api_secret, method, params).await
+    }
+
+    /// Submits `payload` to ListenBrainz's listens.
+    async fn submit_listenbrainz(&self, client: &reqwest::Client, payload: &Value) -> Result<(), String> {
+        let Self::ListenBrainz { token } = self else {
+            return Err(String::from("Not a ListenBrainz account"));
+        };
+
+        client.post(format!("{LISTENBRAINZ_API_URL}/submit-listens"))
+            .header("Authorization", format!("Token {token}"))
+            .header("Content-Type", "application/json")
+            .body(payload.to_string())
+            .send().await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Returns how long a track lasting `duration` has to be listened to before it is scrobbled: `threshold_percent` of it,
+/// or `threshold_secs`, whichever comes first. Returns `None` if the track is too short to be scrobbled at all.
+pub(crate) fn get_scrobble_threshold(duration: Duration, threshold_percent: u32, threshold_secs: u64) -> Option<Duration> {
+    if duration < MIN_SCROBBLE_DURATION {
+        return None;
+    }
+
+    let at_percent = duration.mul_f64(threshold_percent.min(100) as f64 / 100.0);
+    Some(at_percent.min(Duration::from_secs(threshold_secs)))
+}
+
+/// Returns the signature of a call to the Last.fm API with `params`: the MD5 hash of each parameter's name and value,
+/// sorted by name, followed by the API account's secret.
+pub(crate) fn get_lastfm_signature(params: &[(String, String)], api_secret: &str) -> String {
+    let mut params = params.iter()
+        .filter(|(name, _)| name != "format" && name != "callback")
+        .collect::<Vec<_>>();
+    params.sort();
+
+    let mut signed = params.into_iter()
+        .map(|(name, value)| format!("{name}{value}"))
+        .collect::<String>();
+    signed.push_str(api_secret);
+
+    format!("{:x}", md5::compute(signed))
+}
+
+/// Calls the Last.fm API `method` with `params`, signed with the API account's `api_key` and `api_secret`, returning
+/// its response.
+async fn call_lastfm(
+    client: &reqwest::Client,
+    api_key: &str,
+    api_secret: &str,
+    method: &str,
+    mut params: Vec<(String, String)>,
+) -> Result<Value, String> {
+    params.extend([
+        (String::from("method"), method.to_string()),
+        (String::from("api_key"), api_key.to_string()),
+    ]);
+    let signature = get_lastfm_signature(&params, api_secret);
+    params.extend([
+        (String::from("api_sig"), signature),
+        (String::from("format"), String::from("json")),
+    ]);
+
+    let text = client.post(LASTFM_API_URL)
+        .form(&params)
+        .send().await
+        .map_err(|e| e.to_string())?
+        .text().await
+        .map_err(|e| e.to_string())?;
+    let response: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+
+    // Errors are returned as `{"error": 9, "message": "Invalid session key"}`, sometimes with a success status.
+    if response.get("error").is_some() {
+        let message = response.get("message").and_then(Value::as_str).unwrap_or("Unknown error");
+        return Err(format!("Last.fm: {message}"));
+    }
+
+    Ok(response)
+}
+
+/// Returns the `track_metadata` of `listen` that is submitted to ListenBrainz.
+fn get_listenbrainz_metadata(listen: &Listen) -> Value {
+    let mut metadata = json!({
+        "artist_name": listen.artist,
+        "track_name": listen.title,
+        "additional_info": {
+            "duration_ms": listen.duration.as_millis() as u64,
+            "media_player": "tidal-tui",
+            "submission_client": "tidal-tui",
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+            "music_service": "tidal.com",
+        },
+    });
+    if let Some(album) = &listen.album {
+        metadata["release_name"] = json!(album);
+    }
+    if let Some(isrc) = &listen.isrc {
+        metadata["additional_info"]["isrc"] = json!(isrc);
+    }
+
+    metadata
+}
+
+/// Logs in to Last.fm with the API account in the config, asking the user to allow it in their browser, and prints
+/// the session key to add to the config.
+pub async fn log_in_to_lastfm() -> Result<(), String> {
+    let config = Config::load(&get_config_path().map_err(|e| e.to_string())?)?;
+    let lastfm = config.scrobbling.lastfm
+        .ok_or(String::from("Add your Last.fm API account's api_key and api_secret to the [scrobbling.lastfm] section of config.toml first"))?;
+
+    let client = reqwest::Client::new();
+    let response = call_lastfm(&client, &lastfm.api_key, &lastfm.api_secret, "auth.getToken", vec![]).await?;
+    let token = response.get("token")
+        .and_then(Value::as_str)
+        .ok_or(String::from("Last.fm didn't return a token"))?;
+
+    println!("Allow tidal-tui to scrobble to your Last.fm account at:\n\n  {LASTFM_AUTH_URL}?api_key={}&token={token}\n", lastfm.api_key);
+    println!("Then press Enter.");
+    io::stdin().read_line(&mut String::new()).map_err(|e| e.to_string())?;
+
+    let params = vec![(String::from("token"), token.to_string())];
+    let response = call_lastfm(&client, &lastfm.api_key, &lastfm.api_secret, "auth.getSession", params).await?;
+    let session = response.get("session").ok_or(String::from("Last.fm didn't return a session"))?;
+    let name = session.get("name").and_then(Value::as_str).unwrap_or_default();
+    let key = session.get("key")
+        .and_then(Value::as_str)
+        .ok_or(String::from("Last.fm didn't return a session key"))?;
+
+    println!("Logged in as {name}. Add this to the [scrobbling.lastfm] section of config.toml:\n\n  session_key = \"{key}\"");
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scrobbles_at_the_earlier_threshold() {
+        assert_eq!(get_scrobble_threshold(Duration::from_secs(200), 50, 240), Some(Duration::from_secs(100)));
+        assert_eq!(get_scrobble_threshold(Duration::from_secs(600), 50, 240), Some(Duration::from_secs(240)));
+        assert_eq!(get_scrobble_threshold(Duration::from_secs(200), 150, 240), Some(Duration::from_secs(200)));
+    }
+
+    #[test]
+    fn never_scrobbles_short_tracks() {
+        assert_eq!(get_scrobble_threshold(Duration::from_secs(29), 50, 240), None);
+        assert_eq!(get_scrobble_threshold(Duration::from_secs(30), 50, 240), Some(Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn signs_sorted_params_with_the_secret() {
+        let params = [("method", "track.love"), ("artist", "Cher"), ("track", "Believe"), ("api_key", "key"), ("sk", "session"), ("format", "json")]
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        assert_eq!(get_lastfm_signature(&params, "secret"), "cd4d1cb1d8c0860d7983d8967f46f8db");
+    }
+
+    #[test]
+    fn starts_a_new_listen_when_a_scrobbled_track_starts_over() {
+        let mut listening = Listening::new(String::from("1"), Duration::ZERO, 1000);
+        listening.last_position = Duration::from_secs(180);
+
+        assert!(!listening.is_new_listen("1", Duration::ZERO));
+        assert!(listening.is_new_listen("2", Duration::from_secs(180)));
+
+        listening.is_scrobbled = true;
+        assert!(listening.is_new_listen("1", Duration::ZERO));
+        assert!(!listening.is_new_listen("1", Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,161 @@
+use rtidalapi::Track;
+
+use crate::focus::Pane;
+
+/// A search within one table, typed after `/`, that jumps to the rows matching it.
+///
+/// Matching rows stay highlighted after the search is typed, and `n` / `N` jump between them.
+pub struct TableSearch {
+    pane: Pane,
+    query: String,
+    /// Whether the query is still being typed, in which case it captures all keys.
+    is_typing: bool,
+    /// The row selected before the search started, which each change to the query searches from.
+    start_row: Option<usize>,
+}
+
+/// Which way to look for the next match.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+impl TableSearch {
+    /// Returns a search of the table in `pane`, started while `start_row` was selected.
+    pub fn new(pane: Pane, start_row: Option<usize>) -> Self {
+        Self {
+            pane,
+            query: String::new(),
+            is_typing: true,
+            start_row,
+        }
+    }
+
+    /// Returns the pane of the table being searched.
+    pub fn get_pane(&self) -> Pane {
+        self.pane
+    }
+
+    /// Returns what is being searched for.
+    pub fn get_query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns what is being searched for, for editing.
+    pub fn get_query_mut(&mut self) -> &mut String {
+        &mut self.query
+    }
+
+    /// Returns true if the query is still being typed.
+    pub fn is_typing(&self) -> bool {
+        self.is_typing
+    }
+
+    /// Stops typing the query, keeping it for jumping between matches.
+    pub fn finish_typing(&mut self) {
+        self.is_typing = false;
+    }
+
+    /// Returns the row selected before the search started.
+    pub fn get_start_row(&self) -> Option<usize> {
+        self.start_row
+    }
+
+    /// Returns true if `text` contains the query, ignoring case. Nothing matches an empty query.
+    pub fn is_match(&self, text: &str) -> bool {
+        !self.query.is_empty() && text.to_lowercase().contains(&self.query.to_lowercase())
+    }
+
+    /// Returns the index of the first of `texts` that matches, looking from `from` in `direction` and wrapping around.
+    ///
+    /// The row at `from` itself is only checked last, so that repeating a search moves on to the next match.
+    pub fn find_match(&self, texts: &[String], from: usize, direction: SearchDirection) -> Option<usize> {
+        let len = texts.len();
+        if len == 0 {
+            return None;
+        }
+
+        (1..=len)
+            .map(|step| match direction {
+                SearchDirection::Forward => (from + step) % len,
+                SearchDirection::Backward => (from + len - step) % len,
+            })
+            .find(|&idx| self.is_match(&texts[idx]))
+    }
+
+    /// Returns the index of the first of `texts` that matches, starting at the row the search started from.
+    pub fn find_first_match(&self, texts: &[String]) -> Option<usize> {
+        let len = texts.len().max(1);
+
+        // Looking forward from the row before the start row checks the start row first.
+        let from = (self.start_row.unwrap_or(0) + len - 1) % len;
+        self.find_match(texts, from, SearchDirection::Forward)
+    }
+}
+
+/// Returns the text a search looks through for `track`: its title, artist, and album, as far as they have been fetched.
+pub fn get_search_text(track: &Track) -> String {
+    let mut parts = vec![];
+
+    if let Some(attributes) = track.get_cached_attributes() {
+        parts.push(attributes.get_full_title());
+    }
+    if let Some(credits) = track.get_cached_artist_credits() {
+        parts.push(credits.get_display_name());
+    }
+    if let Some(album) = track.get_cached_album() {
+        parts.push(album.attributes.title.clone());
+    }
+
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str) -> TableSearch {
+        let mut search = TableSearch::new(Pane::Collection, None);
+        search.get_query_mut().push_str(query);
+        search
+    }
+
+    #[test]
+    fn matches_ignoring_case() {
+        assert!(search("blue").is_match("Kind of Blue"));
+        assert!(!search("green").is_match("Kind of Blue"));
+        assert!(!search("").is_match("Kind of Blue"));
+    }
+
+    #[test]
+    fn finds_matches_in_both_directions() {
+        let texts = ["So What", "Freddie Freeloader", "Blue in Green", "All Blues", "Flamenco Sketches"]
+            .map(String::from);
+        let blue = search("blue");
+
+        assert_eq!(blue.find_match(&texts, 0, SearchDirection::Forward), Some(2));
+        assert_eq!(blue.find_match(&texts, 2, SearchDirection::Forward), Some(3));
+        // Wraps around past the end and the start.
+        assert_eq!(blue.find_match(&texts, 3, SearchDirection::Forward), Some(2));
+        assert_eq!(blue.find_match(&texts, 2, SearchDirection::Backward), Some(3));
+        assert_eq!(blue.find_match(&texts, 0, SearchDirection::Backward), Some(3));
+
+        assert_eq!(search("trane").find_match(&texts, 0, SearchDirection::Forward), None);
+        assert_eq!(blue.find_match(&[], 0, SearchDirection::Forward), None);
+    }
+
+    #[test]
+    fn finds_first_match_from_the_start_row() {
+        let texts = ["Blue Train", "Moment's Notice", "Lazy Bird", "Blue Train (Alternate Take)"]
+            .map(String::from);
+
+        let mut blue = TableSearch::new(Pane::Collection, Some(0));
+        blue.get_query_mut().push_str("blue");
+        assert_eq!(blue.find_first_match(&texts), Some(0));
+
+        let mut blue = TableSearch::new(Pane::Collection, Some(1));
+        blue.get_query_mut().push_str("blue");
+        assert_eq!(blue.find_first_match(&texts), Some(3));
+    }
+}
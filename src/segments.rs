@@ -0,0 +1,291 @@
+use std::{
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+    time::Duration,
+};
+
+use futures_util::{
+    stream,
+    StreamExt,
+};
+use reqwest::{
+    header,
+    StatusCode,
+};
+use tokio::io::{
+    AsyncWrite,
+    AsyncWriteExt,
+};
+
+/// Assembles the current track's stream from its DASH segments, writing them in order for stream_download to read.
+///
+/// The first few segments are downloaded in parallel, since on slow connections waiting on each request in turn is
+/// most of the delay before the track starts, and the rest are downloaded one at a time after them.
+pub struct SegmentAssembler {
+    client: reqwest::Client,
+    urls: Vec<String>,
+    /// Whether a segment that fails to download is retried from where it left off until all of it is,
+    /// instead of ending the stream there.
+    retries_failures: bool,
+    downloaded_bytes: Arc<AtomicU64>,
+    is_stream_revoked: Arc<AtomicBool>,
+    is_fully_downloaded: Arc<AtomicBool>,
+}
+
+/// Why a segment of the current track stopped downloading.
+enum SegmentError {
+    /// Tidal refused it, since the stream was taken over on another device.
+    Revoked,
+    /// The network dropped or the request failed.
+    Failed,
+    /// The reader of the stream went away (e.g. the track was skipped), so there is no use downloading the rest.
+    Closed,
+}
+
+impl SegmentAssembler {
+    /// How many segments are downloaded at once at the start of a track.
+    const PARALLEL_SEGMENTS: usize = 4;
+
+    /// How long to wait before first retrying a segment that failed to download.
+    /// The wait doubles with each retry after that, up to `MAX_RETRY_DELAY`.
+    const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+    /// The longest wait between retries of a segment that failed to download.
+    const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+    /// Returns an assembler of the segments at `urls`, which counts what it downloads in `downloaded_bytes`,
+    /// and sets `is_stream_revoked` if Tidal refuses them or `is_fully_downloaded` once it has all of them.
+    pub fn new(
+        client: reqwest::Client,
+        urls: Vec<String>,
+        retries_failures: bool,
+        downloaded_bytes: Arc<AtomicU64>,
+        is_stream_revoked: Arc<AtomicBool>,
+        is_fully_downloaded: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            client,
+            urls,
+            retries_failures,
+            downloaded_bytes,
+            is_stream_revoked,
+            is_fully_downloaded,
+        }
+    }
+
+    /// Downloads all of the segments, writing them to `writer` in order.
+    pub async fn run(self, mut writer: impl AsyncWrite + Unpin) {
+        let this = &self;
+        let (first_urls, other_urls) = self.urls.split_at(self.urls.len().min(Self::PARALLEL_SEGMENTS));
+
+        // The first segments are held in memory until the ones before them have been written.
+        let mut first_segments = stream::iter(first_urls.to_vec())
+            .map(|url| async move {
+                let mut segment = Vec::new();
+                this.download(&url, &mut segment).await.then_some(segment)
+            })
+            .buffered(Self::PARALLEL_SEGMENTS);
+
+        while let Some(segment) = first_segments.next().await {
+            let Some(segment) = segment else {
+                return;
+            };
+            if writer.write_all(&segment).await.is_err() {
+                return;
+            }
+        }
+
+        for url in other_urls {
+            if !self.download(url, &mut writer).await {
+                return;
+            }
+        }
+
+        self.is_fully_downloaded.store(true, Ordering::Relaxed);
+    }
+
+    /// Downloads the segment at `url` into `out`, retrying it if failures are, and returns whether all of it was.
+    ///
+    /// Errors aren't printed, since the TUI is drawn over stderr. The track just ends where the stream does.
+    async fn download(&self, url: &str, out: &mut (impl AsyncWrite + Unpin)) -> bool {
+        // How much of the segment has been received, which a retry picks up from.
+        let mut received = 0;
+        let mut retry_delay = Self::RETRY_DELAY;
+
+        loop {
+            match self.fetch(url, &mut received, out).await {
+                Ok(()) => return true,
+                Err(SegmentError::Revoked) => {
+                    self.is_stream_revoked.store(true, Ordering::Relaxed);
+                    return false;
+                },
+                Err(SegmentError::Failed) if self.retries_failures => {
+                    tokio::time::sleep(retry_delay).await;
+                    retry_delay = (retry_delay * 2).min(Self::MAX_RETRY_DELAY);
+                },
+                Err(SegmentError::Failed | SegmentError::Closed) => return false,
+            }
+        }
+    }
+
+    /// Downloads the segment at `url` into `out`, starting `received` bytes in,
+    /// and adds how much is received to both `received` and the downloaded bytes.
+    async fn fetch(&self, url: &str, received: &mut u64, out: &mut (impl AsyncWrite + Unpin)) -> Result<(), SegmentError> {
+        let mut request = self.client.get(url);
+        if *received > 0 {
+            request = request.header(header::RANGE, format!("bytes={received}-"));
+        }
+
+        let response = request.send().await.map_err(|_| SegmentError::Failed)?;
+        // Tidal refuses the segment urls once the account starts playing on another device.
+        if matches!(response.status(), StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+            return Err(SegmentError::Revoked);
+        }
+        let response = response.error_for_status().map_err(|_| SegmentError::Failed)?;
+
+        // A server that ignores the range sends the whole segment again, so the part already received is skipped.
+        let mut to_skip = if response.status() == StatusCode::PARTIAL_CONTENT { 0 } else { *received };
+
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let bytes = chunk.map_err(|_| SegmentError::Failed)?;
+            let skipped = to_skip.min(bytes.len() as u64);
+            to_skip -= skipped;
+            let bytes = &bytes[skipped as usize..];
+
+            out.write_all(bytes).await.map_err(|_| SegmentError::Closed)?;
+            *received += bytes.len() as u64;
+            self.downloaded_bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{
+            self,
+            BufRead,
+            BufReader,
+            Write,
+        },
+        net::TcpListener,
+        pin::Pin,
+        sync::atomic::AtomicUsize,
+        task::{
+            Context,
+            Poll,
+        },
+        thread,
+    };
+
+    use super::*;
+
+    /// Serves `/0`, `/1`, and so on from a background thread, answering the first segments slowest so that
+    /// the parallel downloads finish in reverse order. Returns the urls of `count` segments and how many were requested.
+    fn serve_segments(count: usize) -> (Vec<String>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let requests_clone = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let requests = Arc::clone(&requests_clone);
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(&stream);
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).is_ok_and(|len| len > 2) {
+                        header.clear();
+                    }
+                    requests.fetch_add(1, Ordering::Relaxed);
+
+                    let index: usize = request_line.split_whitespace().nth(1).unwrap()[1..].parse().unwrap();
+                    let delay = SegmentAssembler::PARALLEL_SEGMENTS.saturating_sub(index) as u64 * 50;
+                    thread::sleep(Duration::from_millis(delay));
+
+                    let body = format!("segment {index};");
+                    let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+                    let _ = (&stream).write_all(response.as_bytes());
+                });
+            }
+        });
+
+        let urls = (0..count).map(|index| format!("http://{address}/{index}")).collect();
+        (urls, requests)
+    }
+
+    /// Returns an assembler of `urls` that doesn't retry failures, and whether it got all of them.
+    fn new_assembler(urls: Vec<String>) -> (SegmentAssembler, Arc<AtomicBool>) {
+        let is_fully_downloaded = Arc::new(AtomicBool::new(false));
+        let assembler = SegmentAssembler::new(
+            reqwest::Client::new(),
+            urls,
+            false,
+            Arc::new(AtomicU64::new(0)),
+            Arc::new(AtomicBool::new(false)),
+            Arc::clone(&is_fully_downloaded),
+        );
+
+        (assembler, is_fully_downloaded)
+    }
+
+    fn new_runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap()
+    }
+
+    /// A reader that went away, failing every write.
+    struct ClosedWriter;
+
+    impl AsyncWrite for ClosedWriter {
+        fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, _: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(Err(io::Error::from(io::ErrorKind::BrokenPipe)))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn writes_parallel_segments_in_order_then_the_rest() {
+        let count = SegmentAssembler::PARALLEL_SEGMENTS + 3;
+        let (urls, requests) = serve_segments(count);
+        let (assembler, is_fully_downloaded) = new_assembler(urls);
+
+        let mut out = Vec::new();
+        new_runtime().block_on(assembler.run(&mut out));
+
+        let expected: String = (0..count).map(|index| format!("segment {index};")).collect();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+        assert_eq!(requests.load(Ordering::Relaxed), count);
+        assert!(is_fully_downloaded.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn stops_once_the_reader_is_gone() {
+        let count = SegmentAssembler::PARALLEL_SEGMENTS + 3;
+        let (urls, requests) = serve_segments(count);
+        let (assembler, is_fully_downloaded) = new_assembler(urls);
+
+        new_runtime().block_on(assembler.run(ClosedWriter));
+
+        // Only the segments downloaded in parallel were asked for before the first write failed.
+        assert!(requests.load(Ordering::Relaxed) <= SegmentAssembler::PARALLEL_SEGMENTS);
+        assert!(!is_fully_downloaded.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use crate::format::Locale;
+
+/// What has been played since the app was started.
+#[derive(Clone, Debug)]
+pub struct SessionStats {
+    started_at: Instant,
+    tracks_played: usize,
+    /// How long playback has been running, not counting time spent paused.
+    time_listened: Duration,
+    bytes_streamed: u64,
+}
+
+impl SessionStats {
+    /// Returns the stats of a session starting now.
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            tracks_played: 0,
+            time_listened: Duration::ZERO,
+            bytes_streamed: 0,
+        }
+    }
+
+    /// Records that a track started playing.
+    pub fn record_track_played(&mut self) {
+        self.tracks_played += 1;
+    }
+
+    /// Records that playback ran for `duration`.
+    pub fn record_time_listened(&mut self, duration: Duration) {
+        self.time_listened += duration;
+    }
+
+    /// Records that `bytes` of audio were streamed.
+    pub fn record_bytes_streamed(&mut self, bytes: u64) {
+        self.bytes_streamed += bytes;
+    }
+
+    /// Returns a line for each stat, written in `locale`, for displaying.
+    pub fn get_lines(&self, locale: Locale) -> Vec<String> {
+        vec![
+            format!("Tracks played: {}", locale.format_count(self.tracks_played)),
+            format!("Time listened: {}", locale.format_long_duration(self.time_listened)),
+            format!("Data streamed: {}", locale.format_bytes(self.bytes_streamed)),
+            format!("Session length: {}", locale.format_long_duration(self.started_at.elapsed())),
+        ]
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
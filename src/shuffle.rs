@@ -0,0 +1,242 @@
+use std::{
+    sync::{
+        Arc,
+        Mutex,
+    },
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use rand::{
+    seq::SliceRandom,
+    Rng,
+    RngCore,
+};
+use rtidalapi::Track;
+use serde::Deserialize;
+
+use crate::history::PlayHistory;
+
+/// How tracks are ordered when shuffling.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShuffleMode {
+    /// Every order is equally likely.
+    #[default]
+    Uniform,
+    /// Tracks that were added to the collection recently tend to come up first.
+    RecentlyAdded,
+    /// Tracks that have been played less tend to come up first.
+    LessPlayed,
+    /// Tracks that were played recently tend to come up last.
+    AvoidRecentlyPlayed,
+}
+
+/// Decides the order of the queue when shuffling.
+pub trait ShuffleStrategy: Send {
+    /// Shuffles `tracks` in place.
+    fn shuffle(&self, tracks: &mut [Arc<Track>], rng: &mut dyn RngCore);
+}
+
+/// Shuffles tracks uniformly at random.
+pub struct UniformShuffle;
+
+/// Favors tracks that were added to the collection recently.
+pub struct RecentlyAddedShuffle;
+
+/// Favors tracks that have been played less.
+pub struct LessPlayedShuffle {
+    history: Arc<Mutex<PlayHistory>>,
+}
+
+/// Holds back tracks that were played recently.
+pub struct AvoidRecentlyPlayedShuffle {
+    history: Arc<Mutex<PlayHistory>>,
+}
+
+impl ShuffleMode {
+    /// Returns the strategy for this mode, which looks up when tracks were played in `history`.
+    pub fn strategy(self, history: Arc<Mutex<PlayHistory>>) -> Box<dyn ShuffleStrategy> {
+        match self {
+            Self::Uniform => Box::new(UniformShuffle),
+            Self::RecentlyAdded => Box::new(RecentlyAddedShuffle),
+            Self::LessPlayed => Box::new(LessPlayedShuffle { history }),
+            Self::AvoidRecentlyPlayed => Box::new(AvoidRecentlyPlayedShuffle { history }),
+        }
+    }
+}
+
+impl ShuffleStrategy for UniformShuffle {
+    fn shuffle(&self, tracks: &mut [Arc<Track>], rng: &mut dyn RngCore) {
+        tracks.shuffle(rng);
+    }
+}
+
+impl ShuffleStrategy for RecentlyAddedShuffle {
+    fn shuffle(&self, tracks: &mut [Arc<Track>], rng: &mut dyn RngCore) {
+        let now = SystemTime::now();
+        let weights: Vec<f64> = tracks
+            .iter()
+            .map(|track| {
+                let age = track.get_added_at().map(|added_at| elapsed_since(now, added_at.into()));
+                recently_added_weight(age)
+            })
+            .collect();
+
+        weighted_shuffle(tracks, &weights, rng);
+    }
+}
+
+impl ShuffleStrategy for LessPlayedShuffle {
+    fn shuffle(&self, tracks: &mut [Arc<Track>], rng: &mut dyn RngCore) {
+        let history = self.history.lock().unwrap();
+        let weights: Vec<f64> = tracks
+            .iter()
+            .map(|track| less_played_weight(history.get(&track.id).map_or(0, |record| record.play_count)))
+            .collect();
+        drop(history);
+
+        weighted_shuffle(tracks, &weights, rng);
+    }
+}
+
+impl ShuffleStrategy for AvoidRecentlyPlayedShuffle {
+    fn shuffle(&self, tracks: &mut [Arc<Track>], rng: &mut dyn RngCore) {
+        let now = SystemTime::now();
+        let history = self.history.lock().unwrap();
+        let weights: Vec<f64> = tracks
+            .iter()
+            .map(|track| {
+                let since_played = history.get(&track.id).map(|record| elapsed_since(now, record.last_played()));
+                recently_played_weight(since_played)
+            })
+            .collect();
+        drop(history);
+
+        weighted_shuffle(tracks, &weights, rng);
+    }
+}
+
+/// How long it takes for a track's weight to halve as it gets older, when favoring recently added tracks.
+const RECENTLY_ADDED_HALF_LIFE: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+
+/// How long after being played a track is back to full weight, when holding back recently played tracks.
+const RECENTLY_PLAYED_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The lowest weight a track can have, so that every track can still come up early.
+const MIN_WEIGHT: f64 = 0.05;
+
+/// Returns the weight of a track that was added `age` ago, or `None` if that isn't known.
+fn recently_added_weight(age: Option<Duration>) -> f64 {
+    match age {
+        Some(age) => 0.5_f64.powf(age.as_secs_f64() / RECENTLY_ADDED_HALF_LIFE.as_secs_f64()).max(MIN_WEIGHT),
+        None => MIN_WEIGHT,
+    }
+}
+
+/// Returns the weight of a track that has been played `play_count` times.
+fn less_played_weight(play_count: u32) -> f64 {
+    1.0 / (1.0 + play_count as f64)
+}
+
+/// Returns the weight of a track that was last played `since_played` ago, or `None` if it has never been played.
+fn recently_played_weight(since_played: Option<Duration>) -> f64 {
+    match since_played {
+        Some(since_played) => (since_played.as_secs_f64() / RECENTLY_PLAYED_WINDOW.as_secs_f64()).clamp(MIN_WEIGHT, 1.0),
+        None => 1.0,
+    }
+}
+
+/// Returns how long ago `then` was, or zero if it is in the future (e.g. because the clock changed).
+fn elapsed_since(now: SystemTime, then: SystemTime) -> Duration {
+    now.duration_since(then).unwrap_or_default()
+}
+
+/// Shuffles `items` so that items with a higher weight tend to come first, where `weights[i]` is the weight of `items[i]`.
+///
+/// Each item gets a random key of `u^(1/w)` (compared through its logarithm), and the items are sorted by their keys.
+/// This picks items one by one with probability proportional to their weight (Efraimidis and Spirakis).
+fn weighted_shuffle<T: Clone>(items: &mut [T], weights: &[f64], rng: &mut dyn RngCore) {
+    let mut keyed_items: Vec<(f64, T)> = items
+        .iter()
+        .cloned()
+        .zip(weights)
+        .map(|(item, weight)| {
+            // 1 - u is in (0, 1], so its logarithm is finite.
+            let u: f64 = rng.random();
+            ((1.0 - u).ln() / weight.max(f64::MIN_POSITIVE), item)
+        })
+        .collect();
+
+    keyed_items.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (slot, (_, item)) in items.iter_mut().zip(keyed_items) {
+        *slot = item;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        SeedableRng,
+    };
+
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn weighted_shuffle_keeps_every_item() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut items: Vec<u32> = (0..50).collect();
+        let weights: Vec<f64> = items.iter().map(|&i| i as f64 + 1.0).collect();
+
+        weighted_shuffle(&mut items, &weights, &mut rng);
+
+        let mut sorted_items = items.clone();
+        sorted_items.sort();
+        assert_eq!(sorted_items, (0..50).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn weighted_shuffle_favors_heavier_items() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut heavy_first = 0;
+
+        for _ in 0..1000 {
+            let mut items = ["light", "heavy"];
+            weighted_shuffle(&mut items, &[1.0, 9.0], &mut rng);
+            if items[0] == "heavy" {
+                heavy_first += 1;
+            }
+        }
+
+        // The heavy item should come first about 90% of the time.
+        assert!((850..950).contains(&heavy_first), "heavy item came first {heavy_first} times");
+    }
+
+    #[test]
+    fn newer_tracks_weigh_more() {
+        assert_eq!(recently_added_weight(Some(Duration::ZERO)), 1.0);
+        assert!((recently_added_weight(Some(RECENTLY_ADDED_HALF_LIFE)) - 0.5).abs() < 1e-9);
+        assert_eq!(recently_added_weight(Some(100 * RECENTLY_ADDED_HALF_LIFE)), MIN_WEIGHT);
+        assert_eq!(recently_added_weight(None), MIN_WEIGHT);
+    }
+
+    #[test]
+    fn less_played_tracks_weigh_more() {
+        assert_eq!(less_played_weight(0), 1.0);
+        assert_eq!(less_played_weight(3), 0.25);
+    }
+
+    #[test]
+    fn recently_played_tracks_weigh_less() {
+        assert_eq!(recently_played_weight(None), 1.0);
+        assert_eq!(recently_played_weight(Some(Duration::ZERO)), MIN_WEIGHT);
+        assert!((recently_played_weight(Some(DAY * 7 / 2)) - 0.5).abs() < 1e-9);
+        assert_eq!(recently_played_weight(Some(DAY * 30)), 1.0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    io,
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+/// Sets `flag` once the process is asked to close by a signal: SIGTERM, SIGINT, or SIGHUP when its terminal is closed.
+///
+/// Must be called from within a Tokio runtime, which the signals are listened for on.
+#[cfg(unix)]
+pub fn listen_for_signals(flag: Arc<AtomicBool>) -> io::Result<()> {
+    use tokio::signal::unix::{
+        signal,
+        SignalKind,
+    };
+
+    for kind in [SignalKind::terminate(), SignalKind::interrupt(), SignalKind::hangup()] {
+        let mut signal = signal(kind)?;
+        let flag = Arc::clone(&flag);
+
+        tokio::spawn(async move {
+            if signal.recv().await.is_some() {
+                flag.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Sets `flag` once the process is asked to close by Ctrl+C or its console window being closed.
+///
+/// Must be called from within a Tokio runtime, which the signals are listened for on.
+#[cfg(windows)]
+pub fn listen_for_signals(flag: Arc<AtomicBool>) -> io::Result<()> {
+    use tokio::signal::windows::{
+        ctrl_c,
+        ctrl_close,
+    };
+
+    let mut ctrl_c = ctrl_c()?;
+    let flag_clone = Arc::clone(&flag);
+    tokio::spawn(async move {
+        if ctrl_c.recv().await.is_some() {
+            flag_clone.store(true, Ordering::Relaxed);
+        }
+    });
+
+    let mut ctrl_close = ctrl_close()?;
+    tokio::spawn(async move {
+        if ctrl_close.recv().await.is_some() {
+            flag.store(true, Ordering::Relaxed);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::{
+    cmp::Reverse,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use rtidalapi::{
+    Album,
+    Track,
+};
+
+use crate::i18n::{
+    tr,
+    Message,
+};
+
+/// What the tracks of My Collection - Tracks are sorted by.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortField {
+    /// The order Tidal lists them in, most recently added first.
+    #[default]
+    DateAdded,
+    Title,
+    Artist,
+    Album,
+    Duration,
+}
+
+/// How the tracks of My Collection - Tracks are ordered, which playing them follows too.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TrackSort {
+    pub field: SortField,
+    /// Whether the order is reversed, e.g. Z to A, or oldest first.
+    pub is_reversed: bool,
+}
+
+/// How a list of albums is ordered, like the albums in the collection or an artist's.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AlbumSort {
+    /// The order Tidal lists them in.
+    #[default]
+    Default,
+    /// By release date, newest first, or oldest first if `is_reversed` is true.
+    ReleaseDate {
+        is_reversed: bool,
+    },
+}
+
+/// What a track is compared by. Only values of the same kind are ever compared.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum SortValue {
+    Text(String),
+    Duration(Duration),
+    /// Most recent first.
+    AddedAt(Reverse<Option<DateTime<Utc>>>),
+}
+
+impl SortField {
+    /// All fields, in the order they are cycled through.
+    const ALL: [Self; 5] = [
+        Self::DateAdded,
+        Self::Title,
+        Self::Artist,
+        Self::Album,
+        Self::Duration,
+    ];
+
+    /// Returns the name shown for this field.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::DateAdded => tr(Message::SortDateAdded),
+            Self::Title => tr(Message::ColumnTitle),
+            Self::Artist => tr(Message::ColumnArtist),
+            Self::Album => tr(Message::ColumnAlbum),
+            Self::Duration => tr(Message::Duration),
+        }
+    }
+
+    /// Returns what `track` is compared by for this field, or `None` if the info it needs hasn't been fetched yet.
+    fn get_value(&self, track: &Track) -> Option<SortValue> {
+        match self {
+            Self::DateAdded => Some(SortValue::AddedAt(Reverse(track.get_added_at()))),
+            Self::Title => Some(SortValue::Text(track.get_cached_attributes()?.get_full_title().to_lowercase())),
+            Self::Artist => Some(SortValue::Text(track.get_cached_artist_credits()?.get_display_name().to_lowercase())),
+            Self::Album => Some(SortValue::Text(track.get_cached_album()?.attributes.title.to_lowercase())),
+            // The duration is parsed from the attributes, which would be fetched if they weren't already.
+            Self::Duration => {
+                track.get_cached_attributes()?;
+                Some(SortValue::Duration(*track.get_duration().ok()?))
+            },
+        }
+    }
+}
+
+impl TrackSort {
+    /// Returns true if this is the order Tidal lists the tracks in.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the sort by the field after this one, in its usual order, wrapping around.
+    pub fn cycle(&self) -> Self {
+        let index = SortField::ALL.iter().position(|field| *field == self.field).unwrap_or(0);
+
+        Self {
+            field: SortField::ALL[(index + 1) % SortField::ALL.len()],
+            is_reversed: false,
+        }
+    }
+
+    /// Returns the sort by the same field, in the other order.
+    pub fn reverse(&self) -> Self {
+        Self {
+            field: self.field,
+            is_reversed: !self.is_reversed,
+        }
+    }
+
+    /// Returns the name shown for this sort, like "Title ↑", with the arrow pointing up for A to Z, shortest first,
+    /// and oldest first.
+    pub fn get_name(&self) -> String {
+        // Most recently added first is the usual order for the date added, unlike for every other field.
+        let is_descending = self.is_reversed != (self.field == SortField::DateAdded);
+        let arrow = if is_descending { "↓" } else { "↑" };
+
+        format!("{} {arrow}", self.field.get_name())
+    }
+
+    /// Sorts `tracks` this way, keeping the ones that are equal by it most recently added first.
+    /// The tracks whose info hasn't been fetched yet go last, most recently added first.
+    pub fn sort(&self, tracks: &mut [Arc<Track>]) {
+        sort_by_keys(tracks, |track| SortField::DateAdded.get_value(track), false);
+        sort_by_keys(tracks, |track| self.field.get_value(track), self.is_reversed);
+    }
+}
+
+impl AlbumSort {
+    /// Returns the other sort, going between Tidal's order and newest first.
+    pub fn cycle(&self) -> Self {
+        match self {
+            Self::Default => Self::ReleaseDate { is_reversed: false },
+            Self::ReleaseDate { .. } => Self::Default,
+        }
+    }
+
+    /// Returns the sort by release date in the other order, or oldest first if it is in Tidal's order.
+    pub fn reverse(&self) -> Self {
+        match self {
+            Self::Default => Self::ReleaseDate { is_reversed: true },
+            Self::ReleaseDate { is_reversed } => Self::ReleaseDate { is_reversed: !is_reversed },
+        }
+    }
+
+    /// Returns the name shown for this sort, like "Release date ↓", with the arrow pointing up for oldest first.
+    pub fn get_name(&self) -> String {
+        match self {
+            Self::Default => String::from(tr(Message::SortTidalOrder)),
+            Self::ReleaseDate { is_reversed } => {
+                let arrow = if *is_reversed { "↑" } else { "↓" };
+                format!("{} {arrow}", tr(Message::SortReleaseDate))
+            },
+        }
+    }
+
+    /// Sorts `albums` this way, keeping albums released on the same day in the order they were in.
+    /// Albums without a release date go last. Albums are left as they are for Tidal's order.
+    pub fn sort(&self, albums: &mut [Arc<Album>]) {
+        let Self::ReleaseDate { is_reversed } = *self else {
+            return;
+        };
+
+        albums.sort_by(|a, b| {
+            let ordering = a.cmp_release_date(b);
+            let is_dated = a.attributes.get_release_date().is_some() && b.attributes.get_release_date().is_some();
+
+            if is_dated && !is_reversed { ordering.reverse() } else { ordering }
+        });
+    }
+}
+
+/// Sorts `items` by the key `get_key` returns for each, in reverse if `is_reversed` is true,
+/// keeping items with equal keys in the order they were in. Items without a key go last, in the order they were in.
+fn sort_by_keys<T: Clone, K: Ord>(items: &mut [T], get_key: impl Fn(&T) -> Option<K>, is_reversed: bool) {
+    let mut keyed: Vec<(Option<K>, T)> = items.iter()
+        .map(|item| (get_key(item), item.clone()))
+        .collect();
+
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) if is_reversed => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+
+    for (item, (_, sorted_item)) in items.iter_mut().zip(keyed) {
+        *item = sorted_item;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_items_without_keys_last() {
+        let mut items = ["b", "-", "a", "c", "--"];
+        let get_key = |item: &&str| (!item.starts_with('-')).then(|| item.to_string());
+
+        sort_by_keys(&mut items, get_key, false);
+        assert_eq!(items, ["a", "b", "c", "-", "--"]);
+
+        sort_by_keys(&mut items, get_key, true);
+        assert_eq!(items, ["c", "b", "a", "-", "--"]);
+    }
+
+    #[test]
+    fn keeps_equal_items_in_order() {
+        let mut items = [("b", 1), ("a", 2), ("b", 3), ("a", 4)];
+
+        sort_by_keys(&mut items, |(key, _)| Some(*key), true);
+        assert_eq!(items, [("b", 1), ("b", 3), ("a", 2), ("a", 4)]);
+    }
+
+    #[test]
+    fn cycles_through_fields() {
+        let sort = TrackSort::default().reverse();
+        assert_eq!(sort.cycle(), TrackSort { field: SortField::Title, is_reversed: false });
+        assert_eq!(TrackSort { field: SortField::Duration, is_reversed: false }.cycle(), TrackSort::default());
+        assert!(sort.reverse().is_default());
+    }
+
+    #[test]
+    fn switches_albums_between_tidal_order_and_release_date() {
+        let newest_first = AlbumSort::ReleaseDate { is_reversed: false };
+        assert_eq!(AlbumSort::default().cycle(), newest_first);
+        assert_eq!(newest_first.cycle(), AlbumSort::Default);
+        assert_eq!(newest_first.reverse(), AlbumSort::ReleaseDate { is_reversed: true });
+        assert_eq!(AlbumSort::default().reverse(), AlbumSort::ReleaseDate { is_reversed: true });
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    sync::{
+        Arc,
+        Condvar,
+        Mutex,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// How long each step of starting the app took, printed once the TUI is closed if it was launched with
+/// `--profile-startup`. Clones record into the same profile, since some steps run alongside others.
+#[derive(Clone)]
+pub struct StartupProfile {
+    started_at: Instant,
+    steps: Arc<Mutex<Vec<StartupStep>>>,
+}
+
+/// Lets the thread logging in take the terminal from the connecting screen when the user has to log in again,
+/// which is done in the plain terminal. Clones share the same terminal.
+#[derive(Clone, Default)]
+pub struct TerminalHandoff {
+    state: Arc<(Mutex<HandoffState>, Condvar)>,
+}
+
+#[derive(Default)]
+struct HandoffState {
+    is_requested: bool,
+    is_handed_over: bool,
+}
+
+/// A step of starting the app, timed from when the app was launched.
+#[derive(Clone, Debug, PartialEq)]
+struct StartupStep {
+    name: &'static str,
+    start: Duration,
+    end: Duration,
+}
+
+impl StartupProfile {
+    /// Returns a profile of a startup that starts now.
+    pub fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+            steps: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns how long it has been since the app was launched.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Runs `f`, recording how long it took as the step `name`.
+    pub fn time<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = self.started_at.elapsed();
+        let result = f();
+        let end = self.started_at.elapsed();
+
+        self.steps.lock().unwrap().push(StartupStep { name, start, end });
+        result
+    }
+
+    /// Records that `name` happened now, like the first frame being drawn.
+    pub fn mark(&self, name: &'static str) {
+        let now = self.started_at.elapsed();
+        self.steps.lock().unwrap().push(StartupStep { name, start: now, end: now });
+    }
+
+    /// Returns the report printed for `--profile-startup`, with the steps in the order they started.
+    pub fn get_report(&self) -> String {
+        format_report(&self.steps.lock().unwrap())
+    }
+}
+
+impl TerminalHandoff {
+    /// Asks for the terminal, and waits until the connecting screen has handed it over.
+    pub fn take(&self) {
+        let (state, handed_over) = &*self.state;
+        let mut unlocked_state = state.lock().unwrap();
+        unlocked_state.is_requested = true;
+
+        while !unlocked_state.is_handed_over {
+            unlocked_state = handed_over.wait(unlocked_state).unwrap();
+        }
+    }
+
+    /// Returns true if the terminal has been asked for.
+    pub fn is_requested(&self) -> bool {
+        self.state.0.lock().unwrap().is_requested
+    }
+
+    /// Lets the thread that asked for the terminal use it, once the connecting screen has been closed.
+    pub fn hand_over(&self) {
+        let (state, handed_over) = &*self.state;
+        state.lock().unwrap().is_handed_over = true;
+        handed_over.notify_all();
+    }
+}
+
+/// Returns a table of `steps`, with when each started and how long it took in milliseconds.
+fn format_report(steps: &[StartupStep]) -> String {
+    let mut steps = steps.to_vec();
+    steps.sort_by_key(|step| step.start);
+
+    let name_width = steps.iter().map(|step| step.name.len()).max().unwrap_or(0).max("Step".len());
+    let mut report = format!("{:<name_width$}  {:>9}  {:>9}\n", "Step", "At (ms)", "Took (ms)");
+    for step in &steps {
+        report += &format!(
+            "{:<name_width$}  {:>9}  {:>9}\n",
+            step.name,
+            step.start.as_millis(),
+            (step.end - step.start).as_millis(),
+        );
+    }
+
+    // Steps that run alongside each other overlap, so the total is when the last one finished rather than their sum.
+    let total = steps.iter().map(|step| step.end).max().unwrap_or_default();
+    report += &format!("Ready after {} ms\n", total.as_millis());
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_steps_in_the_order_they_started() {
+        let step = |name, start, end| StartupStep {
+            name,
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+        };
+        let report = format_report(&[step("Log in", 5, 820), step("Load the config", 0, 4), step("Open the audio output", 6, 140)]);
+
+        assert_eq!(
+            report,
+            "\
+Step                     At (ms)  Took (ms)
+Load the config                0          4
+Log in                         5        815
+Open the audio output          6        134
+Ready after 820 ms
+",
+        );
+    }
+}
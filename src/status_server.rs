@@ -0,0 +1,198 @@
+use std::{
+    error::Error,
+    io::{
+        BufRead,
+        BufReader,
+        Write,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::player::Player;
+
+/// The player's state as returned by `GET /status`.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct PlayerStatus {
+    pub state: PlaybackState,
+    pub track: Option<TrackStatus>,
+    pub position_secs: u64,
+    /// How many tracks are left in the queue after the current one.
+    pub queue_length: usize,
+    pub volume: u32,
+}
+
+/// Whether the player is playing, written as "playing", "paused", or "stopped" if there is no current track.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// The current track as returned by `GET /status`. Info that hasn't been fetched yet is `null`.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct TrackStatus {
+    pub id: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_secs: Option<u64>,
+}
+
+impl PlayerStatus {
+    /// Returns the status of `player`, only using track info that has already been fetched.
+    pub fn from_player(player: &Player) -> Self {
+        let track = player.get_current_track().map(|track| {
+            let attributes = track.get_cached_attributes();
+
+            TrackStatus {
+                id: track.id.clone(),
+                title: attributes.map(|attributes| attributes.get_full_title()),
+                artist: track.get_cached_artist_credits().map(|credits| credits.get_display_name()),
+                album: track.get_cached_album().map(|album| album.attributes.title.clone()),
+                duration_secs: attributes
+                    .and_then(|_| track.get_duration().ok())
+                    .map(|duration| duration.as_secs()),
+            }
+        });
+
+        Self {
+            state: PlaybackState::from_player(player),
+            track,
+            position_secs: player.get_position().as_secs(),
+            queue_length: player.get_queue().len(),
+            volume: player.get_volume(),
+        }
+    }
+}
+
+impl PlaybackState {
+    /// Returns whether `player` is playing.
+    pub fn from_player(player: &Player) -> Self {
+        match player.get_current_track() {
+            None => Self::Stopped,
+            Some(_) if player.is_playing() => Self::Playing,
+            Some(_) => Self::Paused,
+        }
+    }
+
+    /// Returns the name of this state, as it is written in the status.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Playing => "playing",
+            Self::Paused => "paused",
+            Self::Stopped => "stopped",
+        }
+    }
+}
+
+/// Serves the player's status as JSON at `http://<address>/status` from a background thread.
+pub fn start(address: &str, player: Arc<Mutex<Player>>) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(address)
+        .map_err(|e| format!("Unable to listen on {address} for the status endpoint: {e}"))?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A client that hangs up early shouldn't stop the server.
+            let _ = handle_connection(stream, &player);
+        }
+    });
+
+    Ok(())
+}
+
+/// Reads a request from `stream` and writes the response.
+fn handle_connection(mut stream: TcpStream, player: &Mutex<Player>) -> Result<(), Box<dyn Error>> {
+    // Requests are handled one at a time, so don't let a client that never sends anything hold up the others.
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    let response = if is_status_request(&request_line) {
+        let status = PlayerStatus::from_player(&*player.lock().map_err(|e| format!("{e:#?}"))?);
+        response("200 OK", "application/json", &serde_json::to_string(&status)?)
+    } else {
+        response("404 Not Found", "text/plain", "Not found")
+    };
+
+    stream.write_all(response.as_bytes())?;
+
+    Ok(())
+}
+
+/// Returns true if `request_line` (e.g. `GET /status HTTP/1.1`) asks for the status.
+fn is_status_request(request_line: &str) -> bool {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next(), parts.next());
+
+    // Ignore any query string, so that clients can add one to avoid caches.
+    method == Some("GET") && path.and_then(|path| path.split('?').next()) == Some("/status")
+}
+
+/// Returns an HTTP response with `status`, `content_type`, and `body`, after which the connection is closed.
+fn response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_status_requests() {
+        assert!(is_status_request("GET /status HTTP/1.1\r\n"));
+        assert!(is_status_request("GET /status?t=1 HTTP/1.1\r\n"));
+        assert!(!is_status_request("POST /status HTTP/1.1\r\n"));
+        assert!(!is_status_request("GET / HTTP/1.1\r\n"));
+        assert!(!is_status_request(""));
+    }
+
+    #[test]
+    fn serializes_status() {
+        let status = PlayerStatus {
+            state: PlaybackState::Playing,
+            track: Some(TrackStatus {
+                id: String::from("77646170"),
+                title: Some(String::from("Hurt")),
+                artist: Some(String::from("Nine Inch Nails")),
+                album: None,
+                duration_secs: Some(373),
+            }),
+            position_secs: 42,
+            queue_length: 3,
+            volume: 80,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&status).unwrap(),
+            r#"{"state":"playing","track":{"id":"77646170","title":"Hurt","artist":"Nine Inch Nails","album":null,"duration_secs":373},"position_secs":42,"queue_length":3,"volume":80}"#,
+        );
+    }
+
+    #[test]
+    fn builds_responses() {
+        assert_eq!(
+            response("404 Not Found", "text/plain", "Not found"),
+            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 9\r\nConnection: close\r\n\r\nNot found",
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::{
+    format::format_duration,
+    player::Player,
+};
+
+/// A format string describing what is playing, such as `"{artist} — {title} [{quality}]"`.
+///
+/// Variables are written in braces, and literal braces are written twice (`{{` and `}}`).
+/// Templates are parsed when the config is loaded, so unknown variables are reported up front.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+/// A part of a template.
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Variable(Variable),
+}
+
+/// Something about what is playing that can be put in a template.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Variable {
+    /// The track's title, including its version.
+    Title,
+    Artist,
+    Album,
+    /// The actual quality of the stream (e.g. 24-Bit 96kHz FLAC).
+    Quality,
+    /// Playing or Paused.
+    State,
+    /// How far into the track playback is (e.g. 1:23).
+    Position,
+    /// The track's duration (e.g. 4:56).
+    Duration,
+    /// The player's volume, out of 100.
+    Volume,
+    /// The track's id.
+    Id,
+}
+
+/// The values of every variable, for rendering templates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateValues {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub quality: String,
+    pub state: String,
+    pub position: String,
+    pub duration: String,
+    pub volume: String,
+    pub id: String,
+}
+
+impl Template {
+    /// Returns this template with its variables replaced by `values`.
+    pub fn render(&self, values: &TemplateValues) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => text.as_str(),
+                Segment::Variable(variable) => values.get(*variable),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                },
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                },
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("Unclosed \"{{\" in template \"{s}\" (write \"{{{{\" for a literal \"{{\")")),
+                        }
+                    }
+                    let variable = name.parse()
+                        .map_err(|e| format!("{e} in template \"{s}\""))?;
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Variable(variable));
+                },
+                '}' => return Err(format!("Unmatched \"}}\" in template \"{s}\" (write \"}}}}\" for a literal \"}}\")")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+}
+
+impl TryFrom<String> for Template {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for Variable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "title" => Ok(Self::Title),
+            "artist" => Ok(Self::Artist),
+            "album" => Ok(Self::Album),
+            "quality" => Ok(Self::Quality),
+            "state" => Ok(Self::State),
+            "position" => Ok(Self::Position),
+            "duration" => Ok(Self::Duration),
+            "volume" => Ok(Self::Volume),
+            "id" => Ok(Self::Id),
+            name => Err(format!("Unknown variable {{{name}}}")),
+        }
+    }
+}
+
+impl TemplateValues {
+    /// Returns the values for what `player` is currently playing, or `None` if nothing is.
+    ///
+    /// Only info that has already been fetched is used, so that rendering never blocks on a request.
+    pub fn from_player(player: &Player) -> Option<Self> {
+        let track = player.get_current_track()?;
+        let attributes = track.get_cached_attributes();
+
+        Some(Self {
+            title: attributes.map_or(format!("Track {}", track.id), |attributes| attributes.get_full_title()),
+            artist: track.get_cached_artist_credits().map_or(String::from("Unknown artist"), |credits| credits.get_display_name()),
+            album: track.get_cached_album().map_or(String::from("Unknown album"), |album| album.attributes.title.clone()),
+            quality: player.get_parsed_manifest().map(|manifest| manifest.get_quality_string()).unwrap_or_default(),
+            state: String::from(if player.is_playing() { "Playing" } else { "Paused" }),
+            position: format_duration(player.get_position()),
+            duration: attributes
+                .and_then(|_| track.get_duration().ok())
+                .map(|duration| format_duration(*duration))
+                .unwrap_or_default(),
+            volume: player.get_volume().to_string(),
+            id: track.id.clone(),
+        })
+    }
+
+    /// Returns the value of `variable`.
+    fn get(&self, variable: Variable) -> &str {
+        match variable {
+            Variable::Title => &self.title,
+            Variable::Artist => &self.artist,
+            Variable::Album => &self.album,
+            Variable::Quality => &self.quality,
+            Variable::State => &self.state,
+            Variable::Position => &self.position,
+            Variable::Duration => &self.duration,
+            Variable::Volume => &self.volume,
+            Variable::Id => &self.id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> TemplateValues {
+        TemplateValues {
+            title: String::from("Hurt"),
+            artist: String::from("Nine Inch Nails"),
+            quality: String::from("24-Bit 96kHz FLAC"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let template: Template = "{artist} — {title} [{quality}]".parse().unwrap();
+        assert_eq!(template.render(&values()), "Nine Inch Nails — Hurt [24-Bit 96kHz FLAC]");
+    }
+
+    #[test]
+    fn renders_escaped_braces() {
+        let template: Template = "{{{title}}}".parse().unwrap();
+        assert_eq!(template.render(&values()), "{Hurt}");
+    }
+
+    #[test]
+    fn rejects_bad_templates() {
+        assert!("{artist} - {name}".parse::<Template>().is_err());
+        assert!("{title".parse::<Template>().is_err());
+        assert!("title}".parse::<Template>().is_err());
+    }
+}
@@ -0,0 +1,41 @@
+pub mod badges;
+pub mod browse_tabs;
+pub mod connecting_screen;
+pub mod header;
+pub mod list_menu;
+pub mod lyrics_view;
+pub mod osd;
+pub mod plain_style;
+pub mod progress_bar;
+pub mod spinner;
+pub mod text_prompt;
+pub mod theme;
+pub mod track_cells;
+pub mod track_info_popup;
+pub mod truncate;
+
+// Re-exports
+pub use badges::media_tag_badges;
+pub use browse_tabs::BrowseTabs;
+pub use connecting_screen::ConnectingScreen;
+pub use header::Header;
+pub use list_menu::ListMenu;
+pub use lyrics_view::LyricsView;
+pub use osd::{
+    Osd,
+    OsdContent,
+};
+pub use plain_style::simplify_styles;
+pub use progress_bar::ProgressBar;
+pub use spinner::spinner_frame;
+pub use text_prompt::TextPrompt;
+pub use theme::Theme;
+pub use track_cells::TrackCells;
+pub use track_info_popup::TrackInfoPopup;
+pub use truncate::{
+    fit_cells,
+    get_column_widths,
+    get_first_visible,
+    truncate_line,
+    Marquee,
+};
@@ -0,0 +1,15 @@
+use ratatui::{
+    style::Stylize,
+    text::Span,
+};
+use rtidalapi::MediaTag;
+
+/// Returns compact badges (e.g. "HiRes", "Atmos") for the given media tags, to be appended after a title.
+///
+/// Each badge is preceded by a space, so the result can be pushed directly onto a title's spans.
+pub fn media_tag_badges(tags: &[MediaTag]) -> Vec<Span<'static>> {
+    tags.iter()
+        .filter_map(|tag| tag.to_badge())
+        .flat_map(|badge| [Span::raw(" "), badge.black().on_cyan()])
+        .collect()
+}
@@ -0,0 +1,42 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{
+        Line,
+        Span,
+    },
+    widgets::Widget,
+};
+
+/// The tabs above a view with a tab for each kind of item, like the results of a search, each with the name of the kind
+/// and how many there are (e.g. `Tracks (20)  Albums (8)`).
+pub struct BrowseTabs<'a> {
+    tabs: Vec<(&'a str, usize)>,
+    selected: usize,
+}
+
+impl<'a> BrowseTabs<'a> {
+    /// Returns new `BrowseTabs` showing the name and number of results of each tab in `tabs`,
+    /// with the one at index `selected` shown as selected.
+    pub fn new(tabs: Vec<(&'a str, usize)>, selected: usize) -> Self {
+        Self { tabs, selected }
+    }
+}
+
+impl Widget for BrowseTabs<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let spans: Vec<Span> = self.tabs
+            .into_iter()
+            .enumerate()
+            .flat_map(|(idx, (name, count))| {
+                let tab = format!(" {name} ({count}) ");
+                // The selected tab is reversed rather than only colored, so that it can be told apart without color.
+                let tab = if idx == self.selected { tab.reversed().bold() } else { tab.dark_gray() };
+                [tab, Span::raw(" ")]
+            })
+            .collect();
+
+        Line::from(spans).render(area, buf);
+    }
+}
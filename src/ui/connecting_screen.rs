@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    style::Stylize,
+    text::Line,
+    widgets::Widget,
+};
+
+use crate::{
+    i18n::{
+        tr,
+        Message,
+    },
+    ui::spinner_frame,
+};
+
+/// Shown from when the app is launched until it has logged in and can show everything else.
+pub struct ConnectingScreen {
+    elapsed: Duration,
+}
+
+impl ConnectingScreen {
+    /// Returns a new `ConnectingScreen`, with its spinner as far along as `elapsed` since the app was launched.
+    pub fn new(elapsed: Duration) -> Self {
+        Self {
+            elapsed,
+        }
+    }
+}
+
+impl Widget for ConnectingScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [line_area] = Layout::vertical([Constraint::Length(1)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        Line::from(vec![
+            format!("{} ", spinner_frame(self.elapsed)).cyan(),
+            tr(Message::ConnectingToTidal).into(),
+        ])
+            .centered()
+            .render(line_area, buf);
+    }
+}
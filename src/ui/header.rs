@@ -0,0 +1,128 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{
+        Line,
+        Span,
+    },
+    widgets::Widget,
+};
+use rtidalapi::{
+    user::UserAttributes,
+    AudioQuality,
+};
+
+use crate::i18n::{
+    tr,
+    tr_with,
+    Message,
+};
+
+/// A one line header showing the logged in user, their subscription tier, how My Collection is sorted,
+/// the quick slot playing, the connection status, and the quality setting.
+pub struct Header<'a> {
+    user: &'a UserAttributes,
+    tier: Option<String>,
+    is_online: bool,
+    is_offline_forced: bool,
+    pending_changes: usize,
+    quality: AudioQuality,
+    slot: Option<usize>,
+    sort: Option<String>,
+}
+
+impl<'a> Header<'a> {
+    /// Returns a new `Header` for `user`.
+    pub fn new(user: &'a UserAttributes) -> Self {
+        Self {
+            user,
+            tier: None,
+            is_online: true,
+            is_offline_forced: false,
+            pending_changes: 0,
+            quality: AudioQuality::Max,
+            slot: None,
+            sort: None,
+        }
+    }
+
+    /// Sets the name of the user's subscription tier, if it is known.
+    pub fn tier(mut self, tier: Option<String>) -> Self {
+        self.tier = tier;
+        self
+    }
+
+    /// Sets whether Tidal can currently be reached.
+    pub fn online(mut self, is_online: bool) -> Self {
+        self.is_online = is_online;
+        self
+    }
+
+    /// Sets whether offline mode has been turned on, which is shown instead of the connection status.
+    pub fn offline_forced(mut self, is_offline_forced: bool) -> Self {
+        self.is_offline_forced = is_offline_forced;
+        self
+    }
+
+    /// Sets how many changes are waiting to be sent to Tidal.
+    pub fn pending_changes(mut self, pending_changes: usize) -> Self {
+        self.pending_changes = pending_changes;
+        self
+    }
+
+    /// Sets the audio quality setting.
+    pub fn quality(mut self, quality: AudioQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    /// Sets the number of the quick slot the queue was swapped to, if it is still playing.
+    pub fn slot(mut self, slot: Option<usize>) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Sets the name of how My Collection - Tracks is sorted, if it isn't in the order Tidal lists it in.
+    pub fn sort(mut self, sort: Option<String>) -> Self {
+        self.sort = sort;
+        self
+    }
+}
+
+impl Widget for Header<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut user_spans = vec![
+            Span::from(format!(" {}", self.user.username)).bold(),
+            Span::from(format!(" · {}", self.user.country)).dark_gray(),
+        ];
+        if let Some(tier) = self.tier {
+            user_spans.push(Span::from(format!(" · {tier}")).dark_gray());
+        }
+
+        let connection = match (self.is_offline_forced, self.is_online) {
+            (true, _) => format!("● {}", tr(Message::OfflineMode)).yellow(),
+            (false, true) => format!("● {}", tr(Message::Online)).green(),
+            (false, false) => format!("● {}", tr(Message::Offline)).red(),
+        };
+        let mut status_spans = vec![];
+        if let Some(sort) = self.sort {
+            let sort = tr_with(Message::SortedBy, &[("sort", &sort)]);
+            status_spans.push(Span::from(format!("{sort}  ")).dark_gray());
+        }
+        if let Some(slot) = self.slot {
+            let slot = tr_with(Message::Slot, &[("slot", &slot.to_string())]);
+            status_spans.push(Span::from(format!("{slot}  ")).cyan());
+        }
+        status_spans.push(connection);
+        if self.pending_changes > 0 {
+            let pending = tr_with(Message::PendingChanges, &[("count", &self.pending_changes.to_string())]);
+            status_spans.push(Span::from(format!("  {pending}")).yellow());
+        }
+        let quality = tr_with(Message::Quality, &[("quality", &self.quality.to_string())]);
+        status_spans.push(Span::from(format!("  {quality} ")).dark_gray());
+
+        Line::from(user_spans).render(area, buf);
+        Line::from(status_spans).right_aligned().render(area, buf);
+    }
+}
@@ -0,0 +1,94 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    style::{
+        Color,
+        Style,
+        Stylize,
+    },
+    text::Line,
+    widgets::{
+        Block,
+        BorderType,
+        Borders,
+        Clear,
+        List,
+        ListState,
+        StatefulWidget,
+        Widget,
+    },
+};
+
+/// A popup with a list of options to choose from.
+pub struct ListMenu<'a> {
+    title: &'a str,
+    items: Vec<String>,
+    placeholder: &'a str,
+    footer: String,
+}
+
+impl<'a> ListMenu<'a> {
+    /// Returns a new `ListMenu` with `title` and `items`.
+    pub fn new(title: &'a str, items: Vec<String>) -> Self {
+        Self {
+            title,
+            items,
+            placeholder: "",
+            footer: String::new(),
+        }
+    }
+
+    /// Sets the text shown when there are no items (e.g. while they are loading).
+    pub fn placeholder(mut self, placeholder: &'a str) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Sets the keybind hints shown at the bottom of the popup.
+    pub fn footer(mut self, footer: String) -> Self {
+        self.footer = footer;
+        self
+    }
+}
+
+impl StatefulWidget for ListMenu<'_> {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        // Borders take up 2 lines.
+        let height = (self.items.len().max(1) as u16 + 2).min(area.height);
+        let [menu_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [menu_area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(Flex::Center)
+            .areas(menu_area);
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Cyan)
+            .title(format!(" {} ", self.title).bold())
+            .title_bottom(Line::from(self.footer).right_aligned());
+
+        Clear.render(menu_area, buf);
+
+        if self.items.is_empty() {
+            let inner_area = block.inner(menu_area);
+            block.render(menu_area, buf);
+            Line::from(format!(" {}", self.placeholder).dark_gray()).render(inner_area, buf);
+            return;
+        }
+
+        let list = List::new(self.items)
+            .block(block)
+            .highlight_style(Style::new().cyan().bold())
+            .highlight_symbol("> ");
+        StatefulWidget::render(list, menu_area, buf, state);
+    }
+}
@@ -0,0 +1,83 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Alignment,
+        Rect,
+    },
+    style::Stylize,
+    text::Line,
+    widgets::Widget,
+};
+use rtidalapi::Lyrics;
+
+use crate::ui::truncate_line;
+
+/// A track's lyrics, one line of text to each row, with the line being sung highlighted and kept in view.
+pub struct LyricsView<'a> {
+    lyrics: &'a Lyrics,
+    current_line: Option<usize>,
+}
+
+impl<'a> LyricsView<'a> {
+    /// Returns a new `LyricsView` showing `lyrics` from the start.
+    pub fn new(lyrics: &'a Lyrics) -> Self {
+        Self {
+            lyrics,
+            current_line: None,
+        }
+    }
+
+    /// Sets the index of the line being sung, which is highlighted and scrolled to, if the lyrics are timed.
+    pub fn current_line(mut self, current_line: Option<usize>) -> Self {
+        self.current_line = current_line;
+        self
+    }
+}
+
+impl Widget for LyricsView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let is_synced = self.lyrics.is_synced();
+        let alignment = if self.lyrics.is_right_to_left { Alignment::Right } else { Alignment::Center };
+        let first_line = get_first_line(self.current_line, self.lyrics.lines.len(), area.height as usize);
+
+        let rows = self.lyrics.lines.iter().enumerate().skip(first_line).take(area.height as usize);
+        for (row, (index, line)) in rows.enumerate() {
+            let text = truncate_line(Line::from(line.text.as_str()), area.width as usize);
+            // Timed lyrics dim every line but the one being sung, so that it stands out.
+            let text = if Some(index) == self.current_line {
+                text.cyan().bold()
+            } else if is_synced {
+                text.dark_gray()
+            } else {
+                text
+            };
+
+            let row_area = Rect::new(area.x, area.y + row as u16, area.width, 1);
+            text.alignment(alignment).render(row_area, buf);
+        }
+    }
+}
+
+/// Returns the index of the first of `len` lines to show in `height` rows, so that the line being sung (`current`)
+/// is a third of the way down, without scrolling past the start or end.
+fn get_first_line(current: Option<usize>, len: usize, height: usize) -> usize {
+    let Some(current) = current else {
+        return 0;
+    };
+
+    current.saturating_sub(height / 3).min(len.saturating_sub(height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_current_line_a_third_of_the_way_down() {
+        assert_eq!(get_first_line(None, 50, 9), 0);
+        assert_eq!(get_first_line(Some(2), 50, 9), 0);
+        assert_eq!(get_first_line(Some(20), 50, 9), 17);
+        assert_eq!(get_first_line(Some(48), 50, 9), 41);
+        assert_eq!(get_first_line(Some(3), 4, 9), 0);
+    }
+}
@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    style::{
+        Color,
+        Style,
+        Stylize,
+    },
+    text::Line,
+    widgets::{
+        Block,
+        BorderType,
+        Borders,
+        Clear,
+        Widget,
+    },
+};
+
+use crate::{
+    format::format_duration,
+    i18n::{
+        tr_with,
+        Message,
+    },
+};
+use super::ProgressBar;
+
+/// What an `Osd` is currently showing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OsdContent {
+    /// The new volume, between 0 and 100.
+    Volume(u32),
+    /// The target position of a seek, and the duration of the track.
+    Seek { position: Duration, duration: Duration },
+    /// A short message.
+    Message(String),
+}
+
+/// An on-screen display that briefly overlays feedback (e.g. the new volume) in the middle of an area.
+pub struct Osd<'a> {
+    content: &'a OsdContent,
+    dimmed: bool,
+}
+
+impl<'a> Osd<'a> {
+    /// Returns a new `Osd` showing `content`.
+    pub fn new(content: &'a OsdContent) -> Self {
+        Self {
+            content,
+            dimmed: false,
+        }
+    }
+
+    /// Sets whether the OSD is drawn dimmed, which is used to fade it out before it disappears.
+    pub fn dimmed(mut self, dimmed: bool) -> Self {
+        self.dimmed = dimmed;
+        self
+    }
+}
+
+impl Widget for Osd<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (label, ratio) = match self.content {
+            OsdContent::Volume(volume) => {
+                (tr_with(Message::Volume, &[("volume", &volume.to_string())]), Some(*volume as f64 / 100.0))
+            },
+            OsdContent::Seek { position, duration } => {
+                let ratio = if duration.is_zero() { 0.0 } else { position.as_secs_f64() / duration.as_secs_f64() };
+                (format!("{} / {}", format_duration(*position), format_duration(*duration)), Some(ratio))
+            },
+            OsdContent::Message(message) => (message.clone(), None),
+        };
+
+        // Borders take up 2 lines, plus 1 line for the label and 1 for the bar.
+        let height = if ratio.is_some() { 4 } else { 3 };
+        let width = (label.chars().count() as u16 + 4).max(32);
+
+        let [osd_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [osd_area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(osd_area);
+
+        let (border_color, played_color) = if self.dimmed {
+            (Color::DarkGray, Color::Gray)
+        } else {
+            (Color::Cyan, Color::Cyan)
+        };
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_color);
+        let inner_area = block.inner(osd_area);
+
+        Clear.render(osd_area, buf);
+        block.render(osd_area, buf);
+
+        let [label_area, bar_area] = Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
+            .areas(inner_area);
+
+        let label = Line::from(label.bold()).centered();
+        if self.dimmed {
+            label.style(Style::new().dark_gray()).render(label_area, buf);
+        } else {
+            label.render(label_area, buf);
+        }
+
+        if let Some(ratio) = ratio {
+            let [bar_area] = Layout::horizontal([Constraint::Fill(1)])
+                .horizontal_margin(1)
+                .areas(bar_area);
+
+            ProgressBar::default()
+                .ratio(ratio)
+                .colors(played_color, Color::DarkGray, Color::DarkGray)
+                .render(bar_area, buf);
+        }
+    }
+}
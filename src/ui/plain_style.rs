@@ -0,0 +1,48 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{
+        Color,
+        Modifier,
+    },
+};
+
+/// Strips the colors and most modifiers from everything drawn in `buf`, so that fewer style changes are sent to the terminal.
+///
+/// Only bold and reversed text are kept. Text drawn on a background color (like badges) is reversed instead,
+/// so that it still stands out.
+pub fn simplify_styles(buf: &mut Buffer) {
+    for cell in &mut buf.content {
+        let has_background = cell.bg != Color::Reset;
+        let modifier = cell.modifier & (Modifier::BOLD | Modifier::REVERSED);
+
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+        cell.modifier = if has_background { modifier | Modifier::REVERSED } else { modifier };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        layout::Rect,
+        style::Stylize,
+        text::Line,
+        widgets::Widget,
+    };
+
+    use super::*;
+
+    #[test]
+    fn keeps_only_bold_and_reversed() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        Line::from(vec!["a".cyan().bold(), "b".dark_gray().italic(), "c".black().on_cyan()]).render(buf.area, &mut buf);
+        simplify_styles(&mut buf);
+
+        let styles: Vec<_> = buf.content.iter().map(|cell| (cell.fg, cell.bg, cell.modifier)).collect();
+        assert_eq!(styles, vec![
+            (Color::Reset, Color::Reset, Modifier::BOLD),
+            (Color::Reset, Color::Reset, Modifier::empty()),
+            (Color::Reset, Color::Reset, Modifier::REVERSED),
+        ]);
+    }
+}
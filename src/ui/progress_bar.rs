@@ -0,0 +1,73 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::Widget,
+};
+
+/// A progress bar that also shows how much of the track has been buffered.
+///
+/// The played portion is drawn in `played_color`, the buffered portion that has
+/// not been played yet in `buffered_color`, and the rest in `background_color`.
+pub struct ProgressBar {
+    ratio: f64,
+    buffered_ratio: f64,
+    played_color: Color,
+    buffered_color: Color,
+    background_color: Color,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self {
+            ratio: 0.0,
+            buffered_ratio: 0.0,
+            played_color: Color::Cyan,
+            buffered_color: Color::Gray,
+            background_color: Color::DarkGray,
+        }
+    }
+}
+
+impl ProgressBar {
+    /// Sets the ratio of the track that has been played, clamped between 0 and 1.
+    pub fn ratio(mut self, ratio: f64) -> Self {
+        self.ratio = ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the ratio of the track that has been buffered, clamped between 0 and 1.
+    pub fn buffered_ratio(mut self, buffered_ratio: f64) -> Self {
+        self.buffered_ratio = buffered_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the colors of the played, buffered, and remaining portions of the bar.
+    pub fn colors(mut self, played: Color, buffered: Color, background: Color) -> Self {
+        self.played_color = played;
+        self.buffered_color = buffered;
+        self.background_color = background;
+        self
+    }
+}
+
+impl Widget for ProgressBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let played_end = area.left() + (self.ratio * area.width as f64).round() as u16;
+        let buffered_end = area.left() + (self.buffered_ratio * area.width as f64).round() as u16;
+
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let color = if x < played_end {
+                    self.played_color
+                } else if x < buffered_end {
+                    self.buffered_color
+                } else {
+                    self.background_color
+                };
+
+                buf[(x, y)].set_symbol(" ").set_bg(color);
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use std::time::Duration;
+
+/// Frames of the spinner animation.
+const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// How long each frame of the spinner is shown for.
+const FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// Returns the spinner frame to show after `elapsed` time has passed since it started.
+pub fn spinner_frame(elapsed: Duration) -> &'static str {
+    let frame = (elapsed.as_millis() / FRAME_DURATION.as_millis()) as usize;
+    FRAMES[frame % FRAMES.len()]
+}
@@ -0,0 +1,75 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    style::{
+        Color,
+        Stylize,
+    },
+    text::Line,
+    widgets::{
+        Block,
+        BorderType,
+        Borders,
+        Clear,
+        Widget,
+    },
+};
+
+/// A popup asking the user to type a line of text.
+pub struct TextPrompt<'a> {
+    title: &'a str,
+    input: &'a str,
+    footer: String,
+}
+
+impl<'a> TextPrompt<'a> {
+    /// Returns a new `TextPrompt` with `title`, showing what has been typed so far (`input`).
+    pub fn new(title: &'a str, input: &'a str) -> Self {
+        Self {
+            title,
+            input,
+            footer: String::new(),
+        }
+    }
+
+    /// Sets the hints shown at the bottom of the popup.
+    pub fn footer(mut self, footer: String) -> Self {
+        self.footer = footer;
+        self
+    }
+}
+
+impl Widget for TextPrompt<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Borders take up 2 lines, plus 1 line for the input.
+        let [prompt_area] = Layout::vertical([Constraint::Length(3)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [prompt_area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(prompt_area);
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Cyan)
+            .title(format!(" {} ", self.title).bold())
+            .title_bottom(Line::from(self.footer).right_aligned());
+        let inner_area = block.inner(prompt_area);
+
+        Clear.render(prompt_area, buf);
+        block.render(prompt_area, buf);
+
+        // Keep the end of the input (where the cursor is) visible when it is too long to fit.
+        let max_chars = inner_area.width.saturating_sub(3) as usize;
+        let input_chars = self.input.chars().count();
+        let visible_input: String = self.input.chars().skip(input_chars.saturating_sub(max_chars)).collect();
+
+        Line::from(vec![" ".into(), visible_input.into(), "█".cyan().slow_blink()]).render(inner_area, buf);
+    }
+}
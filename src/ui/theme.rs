@@ -0,0 +1,139 @@
+use std::env;
+
+use ratatui::{
+    buffer::Buffer,
+    style::{
+        Color,
+        Modifier,
+    },
+};
+
+/// The colors everything is drawn in.
+///
+/// Widgets are drawn in the default colors, and other themes change them afterwards with `apply`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Theme {
+    #[default]
+    Default,
+    /// Bright colors on the terminal's background, without dimmed or dark gray text.
+    HighContrast,
+    /// No colors at all, following https://no-color.org. Dark gray text is dimmed and colored text is bold instead.
+    Monochrome,
+}
+
+impl Theme {
+    /// Returns the theme to use when none was chosen: `Monochrome` if the `NO_COLOR` environment variable is set
+    /// or the terminal can't show colors, and `Default` otherwise.
+    pub fn detect() -> Self {
+        if is_no_color_set() || has_limited_colors() {
+            Self::Monochrome
+        } else {
+            Self::Default
+        }
+    }
+
+    /// Recolors everything drawn in `buf` in this theme.
+    pub fn apply(&self, buf: &mut Buffer) {
+        match self {
+            Self::Default => (),
+            Self::HighContrast => {
+                for cell in &mut buf.content {
+                    cell.fg = brighten(cell.fg);
+                    cell.bg = brighten(cell.bg);
+                    cell.modifier.remove(Modifier::DIM);
+                }
+            },
+            Self::Monochrome => {
+                for cell in &mut buf.content {
+                    let mut modifier = cell.modifier;
+                    match cell.fg {
+                        Color::Reset => (),
+                        Color::DarkGray => modifier.insert(Modifier::DIM),
+                        _ => modifier.insert(Modifier::BOLD),
+                    }
+                    // Text drawn on a background color (like badges) is reversed instead, so that it still stands out.
+                    if cell.bg != Color::Reset {
+                        modifier.insert(Modifier::REVERSED);
+                        modifier.remove(Modifier::DIM);
+                    }
+
+                    cell.fg = Color::Reset;
+                    cell.bg = Color::Reset;
+                    cell.modifier = modifier;
+                }
+            },
+        }
+    }
+}
+
+/// Returns the bright version of `color`, or white for grays. Black and the terminal's own colors are kept.
+fn brighten(color: Color) -> Color {
+    match color {
+        Color::Red => Color::LightRed,
+        Color::Green => Color::LightGreen,
+        Color::Yellow => Color::LightYellow,
+        Color::Blue => Color::LightBlue,
+        Color::Magenta => Color::LightMagenta,
+        Color::Cyan => Color::LightCyan,
+        Color::DarkGray | Color::Gray => Color::White,
+        color => color,
+    }
+}
+
+/// Returns true if the `NO_COLOR` environment variable is set to anything but an empty string.
+fn is_no_color_set() -> bool {
+    env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Returns true if `TERM` names a terminal that can't show colors.
+fn has_limited_colors() -> bool {
+    env::var("TERM").is_ok_and(|term| matches!(term.as_str(), "dumb" | "vt52" | "vt100" | "vt102" | "vt220"))
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        layout::Rect,
+        style::Stylize,
+        text::Line,
+        widgets::Widget,
+    };
+
+    use super::*;
+
+    fn draw_styled_line() -> Buffer {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        Line::from(vec!["a".cyan().bold(), "b".dark_gray(), "c".black().on_cyan(), "d".dim()]).render(buf.area, &mut buf);
+        buf
+    }
+
+    fn get_styles(buf: &Buffer) -> Vec<(Color, Color, Modifier)> {
+        buf.content.iter().map(|cell| (cell.fg, cell.bg, cell.modifier)).collect()
+    }
+
+    #[test]
+    fn brightens_colors_in_high_contrast() {
+        let mut buf = draw_styled_line();
+        Theme::HighContrast.apply(&mut buf);
+
+        assert_eq!(get_styles(&buf), vec![
+            (Color::LightCyan, Color::Reset, Modifier::BOLD),
+            (Color::White, Color::Reset, Modifier::empty()),
+            (Color::Black, Color::LightCyan, Modifier::empty()),
+            (Color::Reset, Color::Reset, Modifier::empty()),
+        ]);
+    }
+
+    #[test]
+    fn replaces_colors_with_modifiers_in_monochrome() {
+        let mut buf = draw_styled_line();
+        Theme::Monochrome.apply(&mut buf);
+
+        assert_eq!(get_styles(&buf), vec![
+            (Color::Reset, Color::Reset, Modifier::BOLD),
+            (Color::Reset, Color::Reset, Modifier::DIM),
+            (Color::Reset, Color::Reset, Modifier::BOLD | Modifier::REVERSED),
+            (Color::Reset, Color::Reset, Modifier::DIM),
+        ]);
+    }
+}
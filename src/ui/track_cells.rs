@@ -0,0 +1,117 @@
+use ratatui::{
+    style::{
+        Style,
+        Stylize,
+    },
+    text::{
+        Line,
+        Span,
+    },
+};
+use rtidalapi::{
+    ArtistRole,
+    Track,
+};
+
+use crate::{
+    format::format_duration,
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+};
+use super::media_tag_badges;
+
+/// The cells shown for a track in a track table.
+///
+/// In classical mode, `title` is the work the track is part of and `artist` is its composer.
+pub struct TrackCells {
+    pub title: Line<'static>,
+    pub artist: String,
+    /// The movement of the work, which is only filled in classical mode.
+    pub movement: String,
+    pub album: String,
+    pub time: String,
+    /// Style of the whole row, used to dim placeholder rows.
+    pub style: Style,
+}
+
+impl TrackCells {
+    /// Returns a placeholder for a track whose info is still being fetched.
+    pub fn loading(track: &Track) -> Self {
+        Self {
+            title: Line::from(format!("… {} {}", tr(Message::Track), track.id)),
+            artist: String::from("…"),
+            movement: String::new(),
+            album: String::from("…"),
+            time: String::from("-:--"),
+            style: Style::new().dark_gray(),
+        }
+    }
+
+    /// Returns the cells of `track`, or `None` if its info hasn't been fetched yet.
+    ///
+    /// If only some of the track's info could be fetched, whatever is missing is shown as unknown,
+    /// and the title says how to retry (using `retry_key`).
+    pub fn from_track(track: &Track, retry_key: &str, is_classical: bool) -> Option<Self> {
+        let info_error = track.get_info_error();
+
+        if !track.has_info() && info_error.is_none() {
+            return None;
+        }
+
+        let mut movement = String::new();
+        let mut title = match track.get_cached_attributes() {
+            Some(attributes) => {
+                let title = if is_classical {
+                    let (work, work_movement) = attributes.get_work_and_movement();
+                    movement = work_movement.unwrap_or_default();
+                    work
+                } else {
+                    attributes.get_full_title()
+                };
+
+                let mut spans = vec![Span::raw(title)];
+                spans.extend(media_tag_badges(&attributes.media_tags));
+                // Protected tracks are skipped when they come up in the queue.
+                if track.is_drm_protected() {
+                    spans.extend([Span::raw(" "), "DRM".black().on_red()]);
+                }
+                Line::default().spans(spans)
+            },
+            None => Line::from(tr_with(Message::UnknownTrack, &[("id", &track.id)])),
+        };
+        if info_error.is_some() {
+            title.push_span(format!(" — {}", tr_with(Message::LoadFailed, &[("key", retry_key)])).red());
+        }
+
+        let artist = match track.get_cached_artist_credits() {
+            Some(credits) if is_classical => {
+                let composers = credits.get_by_role(&ArtistRole::Composer)
+                    .iter()
+                    .map(|artist| artist.attributes.name.as_str())
+                    .collect::<Vec<_>>();
+                if composers.is_empty() { String::from(tr(Message::UnknownComposer)) } else { composers.join(", ") }
+            },
+            Some(credits) => credits.get_display_name(),
+            None => String::from(tr(Message::UnknownArtist)),
+        };
+        let album = track.get_cached_album()
+            .map(|album| album.attributes.title.clone())
+            .unwrap_or(String::from(tr(Message::UnknownAlbum)));
+        let time = match track.get_cached_attributes() {
+            Some(_) => format_duration(*track.get_duration().ok()?),
+            None => String::from("-:--"),
+        };
+
+        Some(Self {
+            title,
+            artist,
+            movement,
+            album,
+            time,
+            style: Style::new(),
+        })
+    }
+}
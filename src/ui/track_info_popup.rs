@@ -0,0 +1,216 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    style::{
+        Color,
+        Stylize,
+    },
+    text::{
+        Line,
+        Span,
+    },
+    widgets::{
+        Block,
+        BorderType,
+        Borders,
+        Clear,
+        Paragraph,
+        Widget,
+        Wrap,
+    },
+};
+use rtidalapi::{
+    Album,
+    AudioQuality,
+    MediaTag,
+    Track,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    format::{
+        format_duration,
+        Locale,
+    },
+    i18n::{
+        tr,
+        tr_with,
+        Message,
+    },
+};
+
+/// A popup showing all of the metadata we have for a track.
+pub struct TrackInfoPopup<'a> {
+    track: &'a Track,
+    stream_url: Option<&'a str>,
+    /// The names of the user's playlists that the track is in.
+    in_playlists: Vec<String>,
+    /// True if every playlist has been checked for the track.
+    in_playlists_complete: bool,
+    footer: String,
+    locale: Locale,
+}
+
+impl<'a> TrackInfoPopup<'a> {
+    /// Returns a new `TrackInfoPopup` for `track`.
+    pub fn new(track: &'a Track) -> Self {
+        Self {
+            track,
+            stream_url: None,
+            in_playlists: vec![],
+            in_playlists_complete: false,
+            footer: String::new(),
+            locale: Locale::default(),
+        }
+    }
+
+    /// Sets the url the track is being streamed from, if it is currently playing.
+    pub fn stream_url(mut self, stream_url: Option<&'a str>) -> Self {
+        self.stream_url = stream_url;
+        self
+    }
+
+    /// Sets the names of the user's playlists that the track is in,
+    /// and whether every playlist has been checked yet (`is_complete`).
+    pub fn in_playlists(mut self, names: Vec<String>, is_complete: bool) -> Self {
+        self.in_playlists = names;
+        self.in_playlists_complete = is_complete;
+        self
+    }
+
+    /// Sets the keybind hints shown at the bottom of the popup.
+    pub fn footer(mut self, footer: String) -> Self {
+        self.footer = footer;
+        self
+    }
+
+    /// Sets the locale that dates are written in.
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Returns the `(label, value)` rows displayed in the popup.
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let unknown = || String::from("...");
+
+        let attributes = self.track.get_cached_attributes();
+        let album = self.track.get_cached_album();
+        let artist_credits = self.track.get_cached_artist_credits();
+
+        vec![
+            (tr(Message::ColumnTitle), attributes.map_or_else(unknown, |a| a.get_full_title())),
+            (tr(Message::ColumnArtist), artist_credits.map_or_else(unknown, |a| a.get_display_name())),
+            (tr(Message::ColumnAlbum), album.map_or_else(unknown, |a| a.attributes.title.clone())),
+            (tr(Message::Released), album.and_then(|a| self.release_value(a)).unwrap_or_else(unknown)),
+            (tr(Message::Duration), match attributes {
+                Some(_) => self.track.get_duration().map_or_else(|_| unknown(), |d| format_duration(*d)),
+                None => unknown(),
+            }),
+            (tr(Message::Explicit), attributes.map_or_else(unknown, |a| tr(if a.explicit { Message::Yes } else { Message::No }).to_string())),
+            (tr(Message::Popularity), attributes.map_or_else(unknown, |a| format!("{:.0}%", a.popularity * 100.0))),
+            (tr(Message::Isrc), attributes.map(|a| a.isrc.clone()).filter(|isrc| !isrc.is_empty()).unwrap_or_else(unknown)),
+            (tr(Message::Copyright), attributes.and_then(|a| a.copyright.get("text").cloned()).unwrap_or_else(unknown)),
+            (tr(Message::Qualities), attributes.map_or_else(unknown, |a| Self::available_qualities(&a.media_tags))),
+            (tr(Message::InPlaylists), self.in_playlists_value()),
+            (tr(Message::StreamHost), self.stream_url.map_or(String::from(tr(Message::NotPlaying)), |url| url_host(url).to_string())),
+            (tr(Message::TrackId), self.track.id.clone()),
+        ]
+    }
+
+    /// Returns when `album` was released, as a full date if Tidal gives one, or otherwise just the year.
+    fn release_value(&self, album: &Album) -> Option<String> {
+        let date = album.attributes.get_release_date()?;
+
+        // Release dates that are only a year are read as January 1st.
+        if album.attributes.release_date.trim().len() > 4 {
+            Some(self.locale.format_date(date))
+        } else {
+            album.attributes.get_release_year().map(|year| year.to_string())
+        }
+    }
+
+    /// Returns the playlists the track is in, shortened to fit on one line.
+    fn in_playlists_value(&self) -> String {
+        const MAX_NAMES: usize = 3;
+
+        let mut value = self.in_playlists.iter()
+            .take(MAX_NAMES)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.in_playlists.len() > MAX_NAMES {
+            let count = (self.in_playlists.len() - MAX_NAMES).to_string();
+            value.push_str(&format!(" {}", tr_with(Message::AndMore, &[("count", &count)])));
+        }
+
+        match (value.is_empty(), self.in_playlists_complete) {
+            (true, true) => String::from(tr(Message::InNoPlaylists)),
+            (true, false) => String::from("..."),
+            (false, true) => value,
+            (false, false) => format!("{value}, ..."),
+        }
+    }
+
+    /// Returns the audio quality settings that content with `media_tags` is available in.
+    fn available_qualities(media_tags: &[MediaTag]) -> String {
+        let max_quality = MediaTag::get_max_quality(media_tags);
+
+        [AudioQuality::Low96, AudioQuality::Low320, AudioQuality::High, AudioQuality::Max]
+            .iter()
+            .filter(|quality| **quality <= max_quality)
+            .map(|quality| quality.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl Widget for TrackInfoPopup<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self.rows();
+
+        // Borders take up 2 lines.
+        let [popup_area] = Layout::vertical([Constraint::Length(rows.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup_area] = Layout::horizontal([Constraint::Max(72)])
+            .flex(Flex::Center)
+            .areas(popup_area);
+
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Color::Cyan)
+            .title(format!(" {} ", tr(Message::TrackInfo)).bold())
+            .title_bottom(Line::from(self.footer).right_aligned());
+
+        // Labels are padded to line up their values, which depends on how long they are in the chosen language.
+        let label_width = rows.iter().map(|(label, _)| label.width()).max().unwrap_or(0) + 1;
+        let lines: Vec<Line> = rows.into_iter()
+            .map(|(label, value)| Line::from(vec![
+                Span::from(format!("{label}{}", " ".repeat(label_width - label.width()))).dark_gray(),
+                Span::from(value),
+            ]))
+            .collect();
+
+        Clear.render(popup_area, buf);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(popup_area, buf);
+    }
+}
+
+/// Returns the host of `url`, or `url` itself if it can't be found.
+fn url_host(url: &str) -> &str {
+    url.split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or(url)
+}
@@ -0,0 +1,236 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{
+        Constraint,
+        Flex,
+        Layout,
+        Rect,
+    },
+    text::{
+        Line,
+        Span,
+    },
+    widgets::Cell,
+};
+use unicode_width::{
+    UnicodeWidthChar,
+    UnicodeWidthStr,
+};
+
+/// What is put at the end of text that was cut short.
+const ELLIPSIS: &str = "…";
+
+/// Returns `line` cut to fit within `width` columns, ending with an ellipsis if anything was cut.
+///
+/// Widths are measured in terminal columns, so wide characters (like CJK characters and most emoji) count as two,
+/// and are never cut in half. Each span keeps its style.
+pub fn truncate_line<'a>(line: Line<'a>, width: usize) -> Line<'a> {
+    if line.width() <= width {
+        return line;
+    }
+
+    let ellipsis_width = ELLIPSIS.width().min(width);
+    let mut truncated = slice_line(line, 0, width - ellipsis_width);
+    if ellipsis_width > 0 {
+        let style = truncated.spans.last().map(|span| span.style).unwrap_or_default();
+        truncated.spans.push(Span::styled(ELLIPSIS, style));
+    }
+
+    truncated
+}
+
+/// Returns the `width` columns of `line` starting at column `start`.
+///
+/// A wide character that would only partly fit at either end is left out, and the gap is filled with a space.
+pub fn slice_line<'a>(line: Line<'a>, start: usize, width: usize) -> Line<'a> {
+    let end = start + width;
+    let mut column = 0;
+    let mut spans = vec![];
+
+    for span in &line.spans {
+        let mut content = String::new();
+
+        for c in span.content.chars() {
+            let char_width = c.width().unwrap_or(0);
+            let char_end = column + char_width;
+
+            if column >= start && char_end <= end {
+                content.push(c);
+            } else if char_end > start && column < end {
+                // Only part of a wide character is within the slice.
+                content.push_str(&" ".repeat(char_end.min(end) - column.max(start)));
+            }
+
+            column = char_end;
+        }
+
+        if !content.is_empty() {
+            spans.push(Span::styled(content, span.style));
+        }
+    }
+
+    Line {
+        spans,
+        ..line
+    }
+}
+
+/// Text that scrolls back and forth when it is too wide to fit, instead of being cut short.
+#[derive(Clone, Copy, Debug)]
+pub struct Marquee {
+    /// How many columns per second the text scrolls.
+    pub speed: f64,
+    /// How long the text waits at each end before scrolling back.
+    pub pause: Duration,
+}
+
+impl Marquee {
+    /// Returns the columns of `line` to show after it has been scrolling for `elapsed`, if it is wider than `width`.
+    ///
+    /// The line scrolls along until its end is shown, then back to its start, pausing at each end.
+    /// Lines that can't scroll (with a speed of 0) are cut short instead.
+    pub fn get_line<'a>(&self, line: Line<'a>, width: usize, elapsed: Duration) -> Line<'a> {
+        let overflow = line.width().saturating_sub(width);
+        if overflow == 0 {
+            return line;
+        }
+        if self.speed <= 0.0 {
+            return truncate_line(line, width);
+        }
+
+        let offset = self.get_offset(overflow, elapsed);
+        slice_line(line, offset, width)
+    }
+
+    /// Returns how many columns a line that is `overflow` columns too wide is scrolled along after `elapsed`.
+    fn get_offset(&self, overflow: usize, elapsed: Duration) -> usize {
+        let scroll_secs = overflow as f64 / self.speed;
+        let pause_secs = self.pause.as_secs_f64();
+        let cycle_secs = 2.0 * (pause_secs + scroll_secs);
+
+        let t = elapsed.as_secs_f64() % cycle_secs;
+        let offset = if t < pause_secs {
+            0.0
+        } else if t < pause_secs + scroll_secs {
+            (t - pause_secs) * self.speed
+        } else if t < 2.0 * pause_secs + scroll_secs {
+            overflow as f64
+        } else {
+            overflow as f64 - (t - 2.0 * pause_secs - scroll_secs) * self.speed
+        };
+
+        (offset.round() as usize).min(overflow)
+    }
+}
+
+/// Returns the width of each column of a table with `constraints` drawn `width` columns wide,
+/// laid out the same way ratatui's `Table` lays them out.
+pub fn get_column_widths(constraints: &[Constraint], column_spacing: u16, width: u16) -> Vec<u16> {
+    Layout::horizontal(constraints.iter().copied())
+        .flex(Flex::Start)
+        .spacing(column_spacing)
+        .split(Rect::new(0, 0, width, 1))
+        .iter()
+        .map(|column| column.width)
+        .collect()
+}
+
+/// Returns the cells of a table row, each cut to fit the width of its column in `widths`.
+pub fn fit_cells<'a>(cells: Vec<Line<'a>>, widths: &[u16]) -> Vec<Cell<'a>> {
+    cells.into_iter()
+        .enumerate()
+        .map(|(idx, line)| match widths.get(idx) {
+            Some(width) => Cell::from(truncate_line(line, *width as usize)),
+            None => Cell::from(line),
+        })
+        .collect()
+}
+
+/// Returns the index of the first of the items with `widths`, laid out side by side `gap` columns apart,
+/// to show so that the one at `selected` fits within `width` columns.
+///
+/// Items are shown from the start for as long as the selected one fits, and scrolled just far enough otherwise.
+pub fn get_first_visible(widths: &[usize], selected: usize, gap: usize, width: usize) -> usize {
+    let mut first = selected.min(widths.len().saturating_sub(1));
+    let mut used = widths.get(first).copied().unwrap_or(0);
+
+    while first > 0 && used + gap + widths[first - 1] <= width {
+        first -= 1;
+        used += gap + widths[first];
+    }
+
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Stylize;
+
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn truncates_by_display_width() {
+        assert_eq!(text(&truncate_line(Line::from("Paranoid Android"), 16)), "Paranoid Android");
+        assert_eq!(text(&truncate_line(Line::from("Paranoid Android"), 10)), "Paranoid …");
+        // Each of these characters is two columns wide.
+        assert_eq!(text(&truncate_line(Line::from("夜に駆ける"), 6)), "夜に …");
+        assert_eq!(text(&truncate_line(Line::from("夜に駆ける"), 7)), "夜に駆…");
+        assert_eq!(text(&truncate_line(Line::from("🎸🎸🎸"), 4)), "🎸 …");
+        assert_eq!(text(&truncate_line(Line::from("Paranoid Android"), 0)), "");
+    }
+
+    #[test]
+    fn keeps_span_styles_when_truncating() {
+        let line = Line::from(vec!["Let Down".bold(), " (Remastered)".dark_gray()]);
+        let truncated = truncate_line(line, 12);
+
+        assert_eq!(text(&truncated), "Let Down (R…");
+        assert_eq!(truncated.spans[0], "Let Down".bold());
+        assert_eq!(truncated.spans[1], " (R".dark_gray());
+        assert_eq!(truncated.spans[2], "…".dark_gray());
+    }
+
+    #[test]
+    fn scrolls_marquees_back_and_forth() {
+        let marquee = Marquee { speed: 4.0, pause: Duration::from_secs(2) };
+
+        // 8 columns too wide, which takes 2 seconds to scroll at 4 columns per second.
+        assert_eq!(marquee.get_offset(8, Duration::from_secs(1)), 0);
+        assert_eq!(marquee.get_offset(8, Duration::from_secs(3)), 4);
+        assert_eq!(marquee.get_offset(8, Duration::from_secs(5)), 8);
+        assert_eq!(marquee.get_offset(8, Duration::from_secs(7)), 4);
+        assert_eq!(marquee.get_offset(8, Duration::from_secs(8)), 0);
+
+        let line = marquee.get_line(Line::from("Kid A Mnesia"), 8, Duration::from_secs(3));
+        assert_eq!(text(&line), "A Mnesia");
+        let line = marquee.get_line(Line::from("Kid A"), 8, Duration::from_secs(3));
+        assert_eq!(text(&line), "Kid A");
+    }
+
+    #[test]
+    fn truncates_marquees_that_cant_scroll() {
+        let marquee = Marquee { speed: 0.0, pause: Duration::from_secs(2) };
+
+        let line = marquee.get_line(Line::from("Kid A Mnesia"), 8, Duration::from_secs(3));
+        assert_eq!(text(&line), "Kid A M…");
+    }
+
+    #[test]
+    fn scrolls_to_the_selected_item() {
+        let widths = [10, 10, 10, 10];
+
+        assert_eq!(get_first_visible(&widths, 0, 2, 30), 0);
+        assert_eq!(get_first_visible(&widths, 1, 2, 30), 0);
+        assert_eq!(get_first_visible(&widths, 2, 2, 30), 1);
+        assert_eq!(get_first_visible(&widths, 3, 2, 30), 2);
+        assert_eq!(get_first_visible(&widths, 3, 2, 100), 0);
+        // The selected item is shown even if it doesn't fit by itself.
+        assert_eq!(get_first_visible(&widths, 2, 2, 5), 2);
+        assert_eq!(get_first_visible(&[], 0, 2, 30), 0);
+    }
+}
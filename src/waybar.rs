@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::{
+    format::format_duration,
+    status_server::PlayerStatus,
+};
+
+/// The player's status in the JSON format read by waybar's custom modules (with `"return-type": "json"`).
+#[derive(Debug, PartialEq, Serialize)]
+pub struct WaybarStatus {
+    /// "Artist - Title", or nothing if nothing is playing, so that the module can be hidden.
+    pub text: String,
+    /// The title, artist, album, and position, on separate lines.
+    pub tooltip: String,
+    /// "playing", "paused", "stopped", or "not-running" if no instance is running, for styling the module.
+    pub class: &'static str,
+    /// The same as `class`, for picking one of the module's `format-icons`.
+    pub alt: &'static str,
+    /// How far into the current track the player is, from 0 to 100.
+    pub percentage: u64,
+}
+
+impl WaybarStatus {
+    /// Returns the waybar status for the player's `status`.
+    pub fn from_status(status: &PlayerStatus) -> Self {
+        let class = status.state.get_name();
+
+        let Some(track) = &status.track else {
+            return Self {
+                text: String::new(),
+                tooltip: String::new(),
+                class,
+                alt: class,
+                percentage: 0,
+            };
+        };
+
+        let title = track.title.clone().unwrap_or_else(|| track.id.clone());
+        let text = match &track.artist {
+            Some(artist) => format!("{artist} - {title}"),
+            None => title.clone(),
+        };
+
+        let position = format_duration(Duration::from_secs(status.position_secs));
+        let position = match track.duration_secs {
+            Some(duration) => format!("{position} / {}", format_duration(Duration::from_secs(duration))),
+            None => position,
+        };
+
+        let tooltip = [Some(&title), track.artist.as_ref(), track.album.as_ref(), Some(&position)]
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let percentage = track.duration_secs
+            .filter(|duration| *duration > 0)
+            .map_or(0, |duration| (status.position_secs * 100 / duration).min(100));
+
+        Self {
+            text,
+            tooltip,
+            class,
+            alt: class,
+            percentage,
+        }
+    }
+
+    /// Returns the waybar status for when no instance is running, which hides the module.
+    pub fn not_running() -> Self {
+        Self {
+            text: String::new(),
+            tooltip: String::new(),
+            class: "not-running",
+            alt: "not-running",
+            percentage: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::status_server::{
+        PlaybackState,
+        TrackStatus,
+    };
+
+    use super::*;
+
+    #[test]
+    fn formats_playing_track() {
+        let status = PlayerStatus {
+            state: PlaybackState::Paused,
+            track: Some(TrackStatus {
+                id: String::from("77646170"),
+                title: Some(String::from("Hurt")),
+                artist: Some(String::from("Nine Inch Nails")),
+                album: None,
+                duration_secs: Some(373),
+            }),
+            position_secs: 42,
+            queue_length: 3,
+            volume: 80,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&WaybarStatus::from_status(&status)).unwrap(),
+            r#"{"text":"Nine Inch Nails - Hurt","tooltip":"Hurt\nNine Inch Nails\n0:42 / 6:13","class":"paused","alt":"paused","percentage":11}"#,
+        );
+    }
+
+    #[test]
+    fn hides_text_when_stopped() {
+        let status = PlayerStatus {
+            state: PlaybackState::Stopped,
+            track: None,
+            position_secs: 0,
+            queue_length: 0,
+            volume: 80,
+        };
+
+        let waybar_status = WaybarStatus::from_status(&status);
+
+        assert_eq!(waybar_status.text, "");
+        assert_eq!(waybar_status.class, "stopped");
+    }
+}